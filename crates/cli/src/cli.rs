@@ -5,8 +5,9 @@ use std::path::PathBuf;
 use anyhow::Result;
 use clap::{Parser, Subcommand};
 use warpnine_core::{
-    build_all, build_condensed, build_mono, build_sans,
-    pipeline::{clean, download},
+    PipelineContext, build_all_with, build_condensed_with, build_mono_with, build_sans_with,
+    pipeline::{SourceConfig, clean, download, download_sources},
+    plan_all,
 };
 
 use crate::dev::DevCommands;
@@ -29,6 +30,23 @@ pub struct BuildArgs {
     /// Version string (YYYY-MM-DD or YYYY-MM-DD.N format)
     #[arg(short, long)]
     pub version: Option<String>,
+    /// Instance each variable font at its masters and fail if one is not reproduced
+    #[arg(long)]
+    pub check: bool,
+    /// Write per-step timings and output sizes to build-stats.csv in the build directory
+    #[arg(long)]
+    pub stats: bool,
+}
+
+impl BuildArgs {
+    fn context(self) -> Result<PipelineContext> {
+        let mut ctx = PipelineContext::new(self.build_dir, self.dist_dir, self.version)?;
+        ctx.check_vf = self.check;
+        if self.stats {
+            ctx.record_stats();
+        }
+        Ok(ctx)
+    }
 }
 
 #[derive(Subcommand)]
@@ -37,6 +55,9 @@ pub enum Commands {
     Build {
         #[command(flatten)]
         args: BuildArgs,
+        /// Print each step's inputs and outputs without building anything
+        #[arg(long)]
+        plan: bool,
     },
     /// Build WarpnineMono fonts only (static + variable)
     BuildMono {
@@ -58,6 +79,9 @@ pub enum Commands {
         /// Directory for intermediate build files
         #[arg(long, default_value = "build")]
         build_dir: PathBuf,
+        /// TOML file listing the sources to download instead of the defaults
+        #[arg(long)]
+        sources: Option<PathBuf>,
     },
     /// Remove build artifacts (build/ and dist/ directories)
     Clean {
@@ -75,17 +99,17 @@ pub enum Commands {
 impl Commands {
     pub fn run(self) -> Result<()> {
         match self {
-            Commands::Build { args } => build_all(&args.build_dir, &args.dist_dir, args.version),
-            Commands::BuildMono { args } => {
-                build_mono(&args.build_dir, &args.dist_dir, args.version)
-            }
-            Commands::BuildSans { args } => {
-                build_sans(&args.build_dir, &args.dist_dir, args.version)
+            Commands::Build { args, plan: true } => {
+                plan_all(&args.build_dir, &args.dist_dir, args.version).map(|_| ())
             }
-            Commands::BuildCondensed { args } => {
-                build_condensed(&args.build_dir, &args.dist_dir, args.version)
+            Commands::Build { args, plan: false } => build_all_with(&args.context()?),
+            Commands::BuildMono { args } => build_mono_with(&args.context()?),
+            Commands::BuildSans { args } => build_sans_with(&args.context()?),
+            Commands::BuildCondensed { args } => build_condensed_with(&args.context()?),
+            Commands::Download { build_dir, sources: None } => download(&build_dir),
+            Commands::Download { build_dir, sources: Some(path) } => {
+                download_sources(&build_dir, &SourceConfig::load(&path)?)
             }
-            Commands::Download { build_dir } => download(&build_dir),
             Commands::Clean { build_dir, dist_dir } => clean(&build_dir, &dist_dir),
             Commands::Dev(dev) => dev.run(),
         }
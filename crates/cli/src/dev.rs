@@ -162,6 +162,9 @@ pub enum DevCommands {
         /// Output path for variable font
         #[arg(long, default_value = "dist/WarpnineMono-VF.ttf")]
         output: PathBuf,
+        /// Instance the result at each master and verify it reproduces the master
+        #[arg(long)]
+        check: bool,
     },
     /// Generate sample PDF using typst
     GenerateSample {
@@ -276,8 +279,8 @@ impl DevCommands {
             DevCommands::FixCalt { files } => {
                 run_parallel("Fix calt", &files, fix_calt_registration)?;
             }
-            DevCommands::BuildVf { dist_dir, output } => {
-                build_warpnine_mono_vf(&dist_dir, &output)?;
+            DevCommands::BuildVf { dist_dir, output, check } => {
+                build_warpnine_mono_vf(&dist_dir, &output, check)?;
             }
             DevCommands::GenerateSample { font_dir, output, watch, png } => {
                 generate_sample(&font_dir, &output, watch, png)?;
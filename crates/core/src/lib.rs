@@ -11,14 +11,20 @@ pub mod styles;
 pub mod warpnine;
 
 pub use pipeline::{
-    PipelineContext, build_all, build_condensed, build_mono, build_sans, build_warpnine_mono_vf,
-    warpnine_mono_designspace,
+    PipelineContext, PostStep, SourceConfig, SourceEntry, StepPlan, build_all, build_all_with,
+    build_condensed, build_condensed_with, build_mono, build_mono_with, build_sans,
+    build_sans_with, build_warpnine_mono_vf, plan_all, warpnine_mono_designspace,
 };
 pub use styles::{MONO_STYLES, SANS_STYLES, Slant, Style, Weight};
 pub use warpnine_font_condense::apply_horizontal_scale;
-pub use warpnine_font_metadata::{FontVersion, MonospaceSettings};
+pub use warpnine_font_metadata::{
+    FontVersion, MonospaceSettings, make_reproducible, set_unique_id_suffix,
+};
 pub use warpnine_font_ops::{
-    StyleBits, StyleNames, apply_style, copy_table, map_name_records, rewrite_font,
+    AvgCharWidthMethod, StyleBits, StyleNames, apply_style, copy_table, fit_win_metrics,
+    import_glyph_names, map_name_records, recompute_avg_char_width, rewrite_font,
+};
+pub use warpnine_font_subsetter::{
+    JAPANESE_RANGES, KOREAN_RANGES, SubsetDescription, SubsetReport, Subsetter, subset_family,
 };
-pub use warpnine_font_subsetter::{JAPANESE_RANGES, Subsetter};
 pub use warpnine_font_woff2::convert_to_woff2;
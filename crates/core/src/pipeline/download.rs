@@ -1,14 +1,16 @@
 use std::{
-    fs::{create_dir_all, write},
+    collections::BTreeMap,
+    fmt::Write as _,
+    fs::{create_dir_all, read_to_string, write},
     io::{Cursor, Read},
-    iter::once,
     path::Path,
-    sync::atomic::{AtomicUsize, Ordering},
 };
 
 use anyhow::{Context, Result, bail};
 use rayon::prelude::*;
 use reqwest::blocking::get;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
 use zip::ZipArchive;
 
 use crate::config::{
@@ -17,154 +19,233 @@ use crate::config::{
     RECURSIVE_LICENSE_URL, RECURSIVE_VF_FILENAME, RECURSIVE_ZIP_PATH, RECURSIVE_ZIP_URL,
 };
 
-struct DownloadItem {
-    url: &'static str,
-    output_name: &'static str,
-    description: &'static str,
+/// Hashes of previously downloaded sources, in `sha256sum` format, kept in
+/// the build directory.
+pub const SOURCE_HASHES_FILENAME: &str = "sources.sha256";
+
+/// A single file fetched by [`download_sources`].
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SourceEntry {
+    /// Output filename inside the build directory.
+    pub name: String,
+    pub url: String,
+    /// Expected lowercase hex SHA-256 of the fetched bytes (the archive itself
+    /// for zipped entries). `None` checks against the hash recorded in
+    /// [`SOURCE_HASHES_FILENAME`] by an earlier download, if any.
+    pub sha256: Option<String>,
+    /// Path of the file to extract when `url` points at a ZIP archive.
+    pub zip_path: Option<String>,
 }
 
-const DOWNLOADS: &[DownloadItem] = &[
-    DownloadItem {
-        url: NOTO_CJK_VF_URL,
-        output_name: NOTO_CJK_VF_FILENAME,
-        description: "Noto Sans Mono CJK JP (Variable)",
-    },
-    DownloadItem {
-        url: NOTO_CJK_LICENSE_URL,
-        output_name: "LICENSE-NotoSansCJK.txt",
-        description: "Noto CJK License",
-    },
-    DownloadItem {
-        url: RECURSIVE_LICENSE_URL,
-        output_name: "LICENSE-Recursive.txt",
-        description: "Recursive License (OFL)",
-    },
-    DownloadItem {
-        url: JETBRAINS_MONO_LICENSE_URL,
-        output_name: "LICENSE-JetBrainsMono.txt",
-        description: "JetBrains Mono License (OFL)",
-    },
-];
-
-fn download_file(item: &DownloadItem, output_dir: &Path) -> Result<()> {
-    let target = output_dir.join(item.output_name);
-    println!("Downloading {}", item.description);
-    println!("  {}", item.output_name);
-
-    let response = get(item.url).with_context(|| format!("Failed to fetch {}", item.url))?;
-    let status = response.status();
-    if !status.is_success() {
-        bail!("HTTP {status} for {}", item.url);
+impl SourceEntry {
+    pub fn new(name: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            url: url.into(),
+            sha256: None,
+            zip_path: None,
+        }
     }
 
-    let bytes = response.bytes()?;
-    write(&target, &bytes)?;
+    pub fn sha256(mut self, sha256: impl Into<String>) -> Self {
+        self.sha256 = Some(sha256.into());
+        self
+    }
 
-    let size_mb = bytes.len() as f64 / 1024.0 / 1024.0;
-    println!("  Downloaded ({size_mb:.2} MB)");
-    Ok(())
+    pub fn zip_path(mut self, zip_path: impl Into<String>) -> Self {
+        self.zip_path = Some(zip_path.into());
+        self
+    }
 }
 
-fn download_recursive_vf(output_dir: &Path) -> Result<()> {
-    let target = output_dir.join(RECURSIVE_VF_FILENAME);
-    println!("Downloading Recursive VF");
-    println!("  {RECURSIVE_VF_FILENAME}");
+/// Source fonts and licenses to download.
+///
+/// Loaded from TOML with one `[[sources]]` table per [`SourceEntry`]:
+///
+/// ```toml
+/// [[sources]]
+/// name = "Recursive_VF_1.085.ttf"
+/// url = "https://github.com/arrowtype/recursive/releases/download/v1.085/ArrowType-Recursive-1.085.zip"
+/// sha256 = "…"
+/// zip_path = "ArrowType-Recursive-1.085/Recursive_Desktop/Recursive_VF_1.085.ttf"
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SourceConfig {
+    pub sources: Vec<SourceEntry>,
+}
 
-    let response =
-        get(RECURSIVE_ZIP_URL).with_context(|| format!("Failed to fetch {RECURSIVE_ZIP_URL}"))?;
-    let status = response.status();
-    if !status.is_success() {
-        bail!("HTTP {status} for {RECURSIVE_ZIP_URL}");
+impl SourceConfig {
+    /// Parse a source list from TOML.
+    pub fn from_toml(text: &str) -> Result<Self> {
+        toml::from_str(text).context("Invalid source config")
     }
 
-    let bytes = response.bytes()?;
-    let cursor = Cursor::new(bytes.as_ref());
-    let mut archive = ZipArchive::new(cursor).context("Failed to open zip archive")?;
-
-    let mut file = archive
-        .by_name(RECURSIVE_ZIP_PATH)
-        .with_context(|| format!("File {RECURSIVE_ZIP_PATH} not found in zip"))?;
-
-    let mut buffer = Vec::new();
-    file.read_to_end(&mut buffer)?;
-    write(&target, &buffer)?;
-
-    let size_mb = buffer.len() as f64 / 1024.0 / 1024.0;
-    println!("  Downloaded ({size_mb:.2} MB)");
-    Ok(())
+    /// Load a source list from a TOML file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = read_to_string(path)
+            .with_context(|| format!("Failed to read source config {}", path.display()))?;
+        Self::from_toml(&text).with_context(|| format!("In {}", path.display()))
+    }
 }
 
-fn download_jetbrains_mono(output_dir: &Path) -> Result<()> {
-    let target = output_dir.join(JETBRAINS_MONO_FILENAME);
-    println!("Downloading JetBrains Mono");
-    println!("  {JETBRAINS_MONO_FILENAME}");
+impl Default for SourceConfig {
+    fn default() -> Self {
+        Self {
+            sources: vec![
+                SourceEntry::new(NOTO_CJK_VF_FILENAME, NOTO_CJK_VF_URL),
+                SourceEntry::new("LICENSE-NotoSansCJK.txt", NOTO_CJK_LICENSE_URL),
+                SourceEntry::new("LICENSE-Recursive.txt", RECURSIVE_LICENSE_URL),
+                SourceEntry::new("LICENSE-JetBrainsMono.txt", JETBRAINS_MONO_LICENSE_URL),
+                SourceEntry::new(RECURSIVE_VF_FILENAME, RECURSIVE_ZIP_URL)
+                    .zip_path(RECURSIVE_ZIP_PATH),
+                SourceEntry::new(JETBRAINS_MONO_FILENAME, JETBRAINS_MONO_ZIP_URL)
+                    .zip_path(JETBRAINS_MONO_ZIP_PATH),
+            ],
+        }
+    }
+}
 
-    let response = get(JETBRAINS_MONO_ZIP_URL)
-        .with_context(|| format!("Failed to fetch {JETBRAINS_MONO_ZIP_URL}"))?;
+fn fetch(url: &str) -> Result<Vec<u8>> {
+    let response = get(url).with_context(|| format!("Failed to fetch {url}"))?;
     let status = response.status();
     if !status.is_success() {
-        bail!("HTTP {status} for {JETBRAINS_MONO_ZIP_URL}");
+        bail!("HTTP {status} for {url}");
     }
+    Ok(response.bytes()?.to_vec())
+}
 
-    let bytes = response.bytes()?;
-    let cursor = Cursor::new(bytes.as_ref());
-    let mut archive = ZipArchive::new(cursor).context("Failed to open zip archive")?;
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .fold(String::with_capacity(64), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        })
+}
 
-    let mut file = archive
-        .by_name(JETBRAINS_MONO_ZIP_PATH)
-        .with_context(|| format!("File {JETBRAINS_MONO_ZIP_PATH} not found in zip"))?;
+/// Fetch `entry` into `output_dir`, checking the fetched bytes against
+/// `expected` (`entry.sha256` or a recorded hash). Returns their SHA-256.
+fn download_entry(
+    entry: &SourceEntry,
+    expected: Option<&str>,
+    output_dir: &Path,
+    fetch: impl Fn(&str) -> Result<Vec<u8>>,
+) -> Result<String> {
+    println!("Downloading {}", entry.name);
 
-    let mut buffer = Vec::new();
-    file.read_to_end(&mut buffer)?;
-    write(&target, &buffer)?;
+    let bytes = fetch(&entry.url)?;
 
-    let size_mb = buffer.len() as f64 / 1024.0 / 1024.0;
+    let actual = sha256_hex(&bytes);
+    if let Some(expected) = expected
+        && !actual.eq_ignore_ascii_case(expected)
+    {
+        bail!(
+            "SHA-256 mismatch for {} ({}): expected {expected}, got {actual}",
+            entry.name,
+            entry.url
+        );
+    }
+
+    let contents = match &entry.zip_path {
+        Some(zip_path) => {
+            let mut archive =
+                ZipArchive::new(Cursor::new(&bytes)).context("Failed to open zip archive")?;
+            let mut file = archive
+                .by_name(zip_path)
+                .with_context(|| format!("File {zip_path} not found in zip"))?;
+            let mut buffer = Vec::new();
+            file.read_to_end(&mut buffer)?;
+            buffer
+        }
+        None => bytes,
+    };
+    write(output_dir.join(&entry.name), &contents)?;
+
+    let size_mb = contents.len() as f64 / 1024.0 / 1024.0;
     println!("  Downloaded ({size_mb:.2} MB)");
-    Ok(())
+    Ok(actual)
+}
+
+/// Read [`SOURCE_HASHES_FILENAME`] from `build_dir`, keyed by source name.
+fn read_source_hashes(build_dir: &Path) -> Result<BTreeMap<String, String>> {
+    let path = build_dir.join(SOURCE_HASHES_FILENAME);
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    read_to_string(&path)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let (hash, name) = line
+                .split_once("  ")
+                .with_context(|| format!("Malformed line in {}: {line}", path.display()))?;
+            Ok((name.to_string(), hash.to_string()))
+        })
+        .collect()
 }
 
-/// Download task type for parallel processing.
-enum DownloadTask<'a> {
-    File(&'a DownloadItem),
-    RecursiveVf,
-    JetBrainsMono,
+fn write_source_hashes(build_dir: &Path, hashes: &BTreeMap<String, String>) -> Result<()> {
+    let contents = hashes.iter().fold(String::new(), |mut contents, (name, hash)| {
+        let _ = writeln!(contents, "{hash}  {name}");
+        contents
+    });
+    write(build_dir.join(SOURCE_HASHES_FILENAME), contents)?;
+    Ok(())
 }
 
+/// Download the default source fonts and licenses.
 pub fn download(build_dir: &Path) -> Result<()> {
+    download_sources(build_dir, &SourceConfig::default())
+}
+
+/// Download every entry in `config`, verifying SHA-256 hashes.
+///
+/// An entry without a `sha256` is checked against the hash recorded for its
+/// name in [`SOURCE_HASHES_FILENAME`] by an earlier download; the hashes of
+/// this download are recorded there for the next. Delete that file to accept
+/// changed sources.
+pub fn download_sources(build_dir: &Path, config: &SourceConfig) -> Result<()> {
+    download_sources_with(build_dir, config, fetch)
+}
+
+fn download_sources_with(
+    build_dir: &Path,
+    config: &SourceConfig,
+    fetch: impl Fn(&str) -> Result<Vec<u8>> + Sync,
+) -> Result<()> {
     create_dir_all(build_dir)?;
     println!("Downloading fonts to {}", build_dir.display());
 
-    let failure_count = AtomicUsize::new(0);
-
-    // Build list of all download tasks
-    let tasks: Vec<DownloadTask> = DOWNLOADS
-        .iter()
-        .map(DownloadTask::File)
-        .chain(once(DownloadTask::RecursiveVf))
-        .chain(once(DownloadTask::JetBrainsMono))
+    let mut hashes = read_source_hashes(build_dir)?;
+    let results: Vec<(&SourceEntry, Result<String>)> = config
+        .sources
+        .par_iter()
+        .map(|entry| {
+            let expected = entry
+                .sha256
+                .as_deref()
+                .or(hashes.get(&entry.name).map(String::as_str));
+            (entry, download_entry(entry, expected, build_dir, &fetch))
+        })
         .collect();
 
-    let total_count = tasks.len();
-
-    tasks.par_iter().for_each(|task| {
-        let result = match task {
-            DownloadTask::File(dl) => download_file(dl, build_dir),
-            DownloadTask::RecursiveVf => download_recursive_vf(build_dir),
-            DownloadTask::JetBrainsMono => download_jetbrains_mono(build_dir),
-        };
-        if let Err(e) = result {
-            let name = match task {
-                DownloadTask::File(dl) => dl.description,
-                DownloadTask::RecursiveVf => "Recursive VF",
-                DownloadTask::JetBrainsMono => "JetBrains Mono",
-            };
-            eprintln!("Error downloading {name}: {e:?}");
-            failure_count.fetch_add(1, Ordering::Relaxed);
+    let mut failures = 0;
+    for (entry, result) in results {
+        match result {
+            Ok(hash) => {
+                hashes.insert(entry.name.clone(), hash);
+            }
+            Err(e) => {
+                eprintln!("Error downloading {}: {e:?}", entry.name);
+                failures += 1;
+            }
         }
-    });
+    }
+    write_source_hashes(build_dir, &hashes)?;
 
-    let failures = failure_count.load(Ordering::Relaxed);
-    let success_count = total_count - failures;
+    let success_count = config.sources.len() - failures;
 
     println!("\nDownload Summary");
     println!("  Success: {success_count}");
@@ -176,3 +257,76 @@ pub fn download(build_dir: &Path) -> Result<()> {
     println!("All files ready in {}/", build_dir.display());
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{env::temp_dir, fs::remove_dir_all, process::id};
+
+    use super::*;
+
+    #[test]
+    fn hash_mismatch_aborts_download() {
+        let dir = temp_dir().join(format!("warpnine-download-{}", id()));
+        create_dir_all(&dir).unwrap();
+
+        let entry = SourceEntry::new("font.ttf", "file:///font.ttf").sha256("00".repeat(32));
+        let err =
+            download_entry(&entry, entry.sha256.as_deref(), &dir, |_| Ok(b"not the font".to_vec()))
+                .unwrap_err();
+
+        assert!(err.to_string().starts_with("SHA-256 mismatch for font.ttf"), "{err}");
+        assert!(!dir.join("font.ttf").exists());
+
+        let entry = entry.sha256(sha256_hex(b"not the font"));
+        download_entry(&entry, entry.sha256.as_deref(), &dir, |_| Ok(b"not the font".to_vec()))
+            .unwrap();
+        assert!(dir.join("font.ttf").exists());
+
+        remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn recorded_hash_verifies_later_downloads() {
+        let dir = temp_dir().join(format!("warpnine-download-recorded-{}", id()));
+        create_dir_all(&dir).unwrap();
+        let config = SourceConfig {
+            sources: vec![SourceEntry::new("font.ttf", "file:///font.ttf")],
+        };
+
+        download_sources_with(&dir, &config, |_| Ok(b"font v1".to_vec())).unwrap();
+        assert_eq!(read_source_hashes(&dir).unwrap()["font.ttf"], sha256_hex(b"font v1"));
+        download_sources_with(&dir, &config, |_| Ok(b"font v1".to_vec())).unwrap();
+
+        let err = download_sources_with(&dir, &config, |_| Ok(b"font v2".to_vec())).unwrap_err();
+        assert_eq!(err.to_string(), "Some downloads failed");
+        assert_eq!(read_source_hashes(&dir).unwrap()["font.ttf"], sha256_hex(b"font v1"));
+
+        remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn source_config_from_toml() {
+        let config = SourceConfig::from_toml(
+            r#"
+            [[sources]]
+            name = "LICENSE.txt"
+            url = "https://example.com/OFL.txt"
+            sha256 = "abc123"
+
+            [[sources]]
+            name = "Font.ttf"
+            url = "https://example.com/font.zip"
+            zip_path = "fonts/Font.ttf"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.sources.len(), 2);
+        assert_eq!(config.sources[0].sha256.as_deref(), Some("abc123"));
+        assert_eq!(config.sources[1].zip_path.as_deref(), Some("fonts/Font.ttf"));
+        assert_eq!(config.sources[1].sha256, None);
+
+        let err = SourceConfig::from_toml("[[sources]]\nname = \"x\"\nurl = \"y\"\nhash = \"z\"")
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("unknown field `hash`"), "{err:#}");
+    }
+}
@@ -2,20 +2,27 @@
 
 mod clean;
 mod download;
+mod plan;
 mod steps;
+mod telemetry;
 mod vf;
 
 use std::{
+    cell::{Ref, RefCell},
     path::{Path, PathBuf},
     time::Instant,
 };
 
 use anyhow::Result;
 pub use clean::clean;
-pub use download::download;
+pub use download::{SOURCE_HASHES_FILENAME, SourceConfig, SourceEntry, download, download_sources};
+pub use plan::{StepPlan, plan_all, plan_build, plan_steps, print_plan};
+pub(crate) use steps::step;
 pub use steps::{
     CONDENSED_ONLY_STEPS, FINAL_STEPS, MONO_STEPS, PipelineStep, SANS_ONLY_STEPS, SANS_STEPS,
+    StepPath,
 };
+pub use telemetry::{BUILD_STATS_FILENAME, FileSnapshot, StepStats, Telemetry, snapshot};
 pub use vf::{
     build_warpnine_condensed_vf, build_warpnine_mono_vf, build_warpnine_sans_vf,
     warpnine_mono_designspace,
@@ -27,6 +34,10 @@ use crate::{
     io::glob_fonts,
 };
 
+/// A per-font transform run on each static dist font, see
+/// [`PipelineContext::add_post_step`].
+pub type PostStep = Box<dyn Fn(&Path) -> Result<()>>;
+
 pub struct PipelineContext {
     pub build_dir: PathBuf,
     pub dist_dir: PathBuf,
@@ -34,6 +45,11 @@ pub struct PipelineContext {
     pub noto_vf: PathBuf,
     pub jetbrains_mono: PathBuf,
     pub version: FontVersion,
+    /// Instance each built variable font back at its masters and fail the
+    /// build if one is not reproduced
+    pub check_vf: bool,
+    post_steps: Vec<(String, PostStep)>,
+    telemetry: Option<RefCell<Telemetry>>,
 }
 
 impl PipelineContext {
@@ -53,9 +69,35 @@ impl PipelineContext {
             noto_vf,
             jetbrains_mono,
             version,
+            check_vf: false,
+            post_steps: Vec::new(),
+            telemetry: None,
         })
     }
 
+    /// Collect per-step timings and output sizes, written to
+    /// [`BUILD_STATS_FILENAME`] when a build finishes. Off by default, since
+    /// every step then snapshots the build and dist directories twice.
+    pub fn record_stats(&mut self) {
+        self.telemetry = Some(RefCell::new(Telemetry::new()));
+    }
+
+    /// Stats collected so far, if enabled with [`record_stats`](Self::record_stats).
+    pub fn telemetry(&self) -> Option<Ref<'_, Telemetry>> {
+        self.telemetry.as_ref().map(RefCell::borrow)
+    }
+
+    /// Register `step` to run on every static dist font of the built families
+    /// after a build's built-in steps, in registration order.
+    pub fn add_post_step(&mut self, name: impl Into<String>, step: PostStep) {
+        self.post_steps.push((name.into(), step));
+    }
+
+    /// Number of steps registered with [`add_post_step`](Self::add_post_step).
+    pub fn post_step_count(&self) -> usize {
+        self.post_steps.len()
+    }
+
     pub fn build_fonts(&self, pattern: &str) -> Result<Vec<PathBuf>> {
         glob_fonts(&self.build_dir, pattern)
     }
@@ -65,8 +107,13 @@ impl PipelineContext {
     }
 
     pub fn static_mono_fonts(&self) -> Result<Vec<PathBuf>> {
+        self.static_dist_fonts("WarpnineMono-*.ttf")
+    }
+
+    /// Dist fonts matching `pattern`, excluding variable fonts (`-VF`).
+    pub fn static_dist_fonts(&self, pattern: &str) -> Result<Vec<PathBuf>> {
         Ok(self
-            .dist_fonts("WarpnineMono-*.ttf")?
+            .dist_fonts(pattern)?
             .into_iter()
             .filter(|p| {
                 p.file_name()
@@ -101,9 +148,15 @@ pub fn run_step(
     f: impl Fn(&PipelineContext) -> Result<()>,
 ) -> Result<()> {
     println!("\n[{step_num}/{total}] {name}");
+    let dirs = [ctx.build_dir.as_path(), ctx.dist_dir.as_path()];
+    let before = ctx.telemetry.is_some().then(|| snapshot(&dirs));
     let start = Instant::now();
     f(ctx)?;
-    println!("  ✓ {name} ({:.2}s)", start.elapsed().as_secs_f64());
+    let elapsed = start.elapsed();
+    println!("  ✓ {name} ({:.2}s)", elapsed.as_secs_f64());
+    if let (Some(telemetry), Some(before)) = (&ctx.telemetry, &before) {
+        telemetry.borrow_mut().record(name, elapsed, before, &dirs);
+    }
     Ok(())
 }
 
@@ -113,29 +166,75 @@ pub fn run_steps(
     offset: usize,
     total: usize,
 ) -> Result<()> {
-    for (i, (name, step_fn)) in steps.iter().enumerate() {
-        run_step(name, offset + i + 1, total, ctx, step_fn)?;
+    for (i, step) in steps.iter().enumerate() {
+        run_step(step.name, offset + i + 1, total, ctx, step.run)?;
+    }
+    Ok(())
+}
+
+/// Run the steps registered with [`PipelineContext::add_post_step`], each
+/// over the static dist fonts matching any of `patterns`.
+pub fn run_post_steps(
+    ctx: &PipelineContext,
+    patterns: &[&str],
+    offset: usize,
+    total: usize,
+) -> Result<()> {
+    for (i, (name, post_step)) in ctx.post_steps.iter().enumerate() {
+        run_step(name, offset + i + 1, total, ctx, |ctx| {
+            for pattern in patterns {
+                for path in ctx.static_dist_fonts(pattern)? {
+                    post_step(&path)?;
+                }
+            }
+            Ok(())
+        })?;
     }
     Ok(())
 }
 
+/// Write the collected step stats to [`BUILD_STATS_FILENAME`] in the build
+/// directory, if [`PipelineContext::record_stats`] enabled them.
+pub fn write_build_stats(ctx: &PipelineContext) -> Result<()> {
+    let Some(telemetry) = ctx.telemetry() else {
+        return Ok(());
+    };
+    let path = ctx.build_dir.join(BUILD_STATS_FILENAME);
+    telemetry.write_csv(&path)?;
+    println!("   Stats: {}", path.display());
+    Ok(())
+}
+
 pub fn build_all(build_dir: &Path, dist_dir: &Path, version: Option<String>) -> Result<()> {
     let ctx = PipelineContext::new(build_dir.to_path_buf(), dist_dir.to_path_buf(), version)?;
+    build_all_with(&ctx)
+}
+
+/// [`build_all`] with a prepared context, running its post steps last.
+pub fn build_all_with(ctx: &PipelineContext) -> Result<()> {
     let start = Instant::now();
 
     println!("═══════════════════════════════════════════════════════════════════════════════");
     println!("Warpnine Fonts Build Pipeline (Rust)");
     println!("═══════════════════════════════════════════════════════════════════════════════");
 
-    let total = MONO_STEPS.len() + SANS_STEPS.len() + FINAL_STEPS.len();
+    let builtin = MONO_STEPS.len() + SANS_STEPS.len() + FINAL_STEPS.len();
+    let total = builtin + ctx.post_step_count();
 
-    run_steps(MONO_STEPS, &ctx, 0, total)?;
-    run_steps(SANS_STEPS, &ctx, MONO_STEPS.len(), total)?;
-    run_steps(FINAL_STEPS, &ctx, MONO_STEPS.len() + SANS_STEPS.len(), total)?;
+    run_steps(MONO_STEPS, ctx, 0, total)?;
+    run_steps(SANS_STEPS, ctx, MONO_STEPS.len(), total)?;
+    run_steps(FINAL_STEPS, ctx, MONO_STEPS.len() + SANS_STEPS.len(), total)?;
+    run_post_steps(
+        ctx,
+        &["WarpnineMono-*.ttf", "WarpnineSans-*.ttf", "WarpnineSansCondensed-*.ttf"],
+        builtin,
+        total,
+    )?;
 
     println!("\n═══════════════════════════════════════════════════════════════════════════════");
     println!("✨ Build complete in {:.2}s", start.elapsed().as_secs_f64());
     println!("   Output: {}", ctx.dist_dir.display());
+    write_build_stats(ctx)?;
 
     let mono_count = ctx.dist_fonts("WarpnineMono-*.ttf")?.len();
     let sans_count = ctx.dist_fonts("WarpnineSans-*.ttf")?.len();
@@ -149,20 +248,28 @@ pub fn build_all(build_dir: &Path, dist_dir: &Path, version: Option<String>) ->
 
 pub fn build_mono(build_dir: &Path, dist_dir: &Path, version: Option<String>) -> Result<()> {
     let ctx = PipelineContext::new(build_dir.to_path_buf(), dist_dir.to_path_buf(), version)?;
+    build_mono_with(&ctx)
+}
+
+/// [`build_mono`] with a prepared context, running its post steps last.
+pub fn build_mono_with(ctx: &PipelineContext) -> Result<()> {
     let start = Instant::now();
 
     println!("═══════════════════════════════════════════════════════════════════════════════");
     println!("Warpnine Mono Build Pipeline (Rust)");
     println!("═══════════════════════════════════════════════════════════════════════════════");
 
-    let total = MONO_STEPS.len() + FINAL_STEPS.len();
+    let builtin = MONO_STEPS.len() + FINAL_STEPS.len();
+    let total = builtin + ctx.post_step_count();
 
-    run_steps(MONO_STEPS, &ctx, 0, total)?;
-    run_steps(FINAL_STEPS, &ctx, MONO_STEPS.len(), total)?;
+    run_steps(MONO_STEPS, ctx, 0, total)?;
+    run_steps(FINAL_STEPS, ctx, MONO_STEPS.len(), total)?;
+    run_post_steps(ctx, &["WarpnineMono-*.ttf"], builtin, total)?;
 
     println!("\n═══════════════════════════════════════════════════════════════════════════════");
     println!("✨ Mono build complete in {:.2}s", start.elapsed().as_secs_f64());
     println!("   Output: {}", ctx.dist_dir.display());
+    write_build_stats(ctx)?;
 
     let mono_count = ctx.dist_fonts("WarpnineMono-*.ttf")?.len();
     println!("   Fonts: {mono_count} Mono");
@@ -173,19 +280,27 @@ pub fn build_mono(build_dir: &Path, dist_dir: &Path, version: Option<String>) ->
 
 pub fn build_sans(build_dir: &Path, dist_dir: &Path, version: Option<String>) -> Result<()> {
     let ctx = PipelineContext::new(build_dir.to_path_buf(), dist_dir.to_path_buf(), version)?;
+    build_sans_with(&ctx)
+}
+
+/// [`build_sans`] with a prepared context, running its post steps last.
+pub fn build_sans_with(ctx: &PipelineContext) -> Result<()> {
     let start = Instant::now();
 
     println!("═══════════════════════════════════════════════════════════════════════════════");
     println!("Warpnine Sans Build Pipeline (Rust)");
     println!("═══════════════════════════════════════════════════════════════════════════════");
 
-    let total = SANS_ONLY_STEPS.len();
+    let builtin = SANS_ONLY_STEPS.len();
+    let total = builtin + ctx.post_step_count();
 
-    run_steps(SANS_ONLY_STEPS, &ctx, 0, total)?;
+    run_steps(SANS_ONLY_STEPS, ctx, 0, total)?;
+    run_post_steps(ctx, &["WarpnineSans-*.ttf"], builtin, total)?;
 
     println!("\n═══════════════════════════════════════════════════════════════════════════════");
     println!("✨ Sans build complete in {:.2}s", start.elapsed().as_secs_f64());
     println!("   Output: {}", ctx.dist_dir.display());
+    write_build_stats(ctx)?;
 
     let sans_count = ctx.dist_fonts("WarpnineSans-*.ttf")?.len();
     println!("   Fonts: {sans_count} Sans");
@@ -196,19 +311,27 @@ pub fn build_sans(build_dir: &Path, dist_dir: &Path, version: Option<String>) ->
 
 pub fn build_condensed(build_dir: &Path, dist_dir: &Path, version: Option<String>) -> Result<()> {
     let ctx = PipelineContext::new(build_dir.to_path_buf(), dist_dir.to_path_buf(), version)?;
+    build_condensed_with(&ctx)
+}
+
+/// [`build_condensed`] with a prepared context, running its post steps last.
+pub fn build_condensed_with(ctx: &PipelineContext) -> Result<()> {
     let start = Instant::now();
 
     println!("═══════════════════════════════════════════════════════════════════════════════");
     println!("Warpnine Sans Condensed Build Pipeline (Rust)");
     println!("═══════════════════════════════════════════════════════════════════════════════");
 
-    let total = CONDENSED_ONLY_STEPS.len();
+    let builtin = CONDENSED_ONLY_STEPS.len();
+    let total = builtin + ctx.post_step_count();
 
-    run_steps(CONDENSED_ONLY_STEPS, &ctx, 0, total)?;
+    run_steps(CONDENSED_ONLY_STEPS, ctx, 0, total)?;
+    run_post_steps(ctx, &["WarpnineSansCondensed-*.ttf"], builtin, total)?;
 
     println!("\n═══════════════════════════════════════════════════════════════════════════════");
     println!("✨ Condensed build complete in {:.2}s", start.elapsed().as_secs_f64());
     println!("   Output: {}", ctx.dist_dir.display());
+    write_build_stats(ctx)?;
 
     let condensed_count = ctx.dist_fonts("WarpnineSansCondensed-*.ttf")?.len();
     println!("   Fonts: {condensed_count} Condensed");
@@ -216,3 +339,50 @@ pub fn build_condensed(build_dir: &Path, dist_dir: &Path, version: Option<String
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::RefCell,
+        env::temp_dir,
+        fs::{create_dir_all, read, remove_dir_all, write},
+        process::id,
+        rc::Rc,
+    };
+
+    use super::*;
+
+    #[test]
+    fn post_step_runs_once_per_static_font() {
+        let dir = temp_dir().join(format!("warpnine-post-step-{}", id()));
+        let mut ctx = PipelineContext::new(dir.join("build"), dir.join("dist"), None).unwrap();
+        create_dir_all(&ctx.dist_dir).unwrap();
+        for name in [
+            "WarpnineMono-Bold.ttf",
+            "WarpnineMono-Regular.ttf",
+            "WarpnineMono-VF.ttf",
+            "WarpnineSans-Regular.ttf",
+        ] {
+            write(ctx.dist_dir.join(name), [0u8; 4]).unwrap();
+        }
+
+        let touched = Rc::new(RefCell::new(Vec::new()));
+        let seen = Rc::clone(&touched);
+        ctx.add_post_step(
+            "touch",
+            Box::new(move |path: &Path| {
+                write(path, [1u8; 4])?;
+                seen.borrow_mut().push(path.file_name().unwrap().to_owned());
+                Ok(())
+            }),
+        );
+        run_post_steps(&ctx, &["WarpnineMono-*.ttf"], 0, ctx.post_step_count()).unwrap();
+
+        let mut touched = touched.take();
+        touched.sort();
+        assert_eq!(touched, ["WarpnineMono-Bold.ttf", "WarpnineMono-Regular.ttf"]);
+        assert_eq!(read(ctx.dist_dir.join("WarpnineSans-Regular.ttf")).unwrap(), [0u8; 4]);
+
+        remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,85 @@
+//! Dry-run build plans: each step's expected inputs and outputs, without
+//! running anything.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+use super::{FINAL_STEPS, MONO_STEPS, PipelineContext, PipelineStep, SANS_STEPS};
+
+/// The files one step reads and writes, resolved against the build and dist
+/// directories. Entries may be glob patterns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepPlan {
+    pub name: &'static str,
+    pub inputs: Vec<PathBuf>,
+    pub outputs: Vec<PathBuf>,
+}
+
+/// Plan `steps` in order.
+pub fn plan_steps(ctx: &PipelineContext, steps: &[PipelineStep]) -> Vec<StepPlan> {
+    steps
+        .iter()
+        .map(|step| StepPlan {
+            name: step.name,
+            inputs: step.inputs.iter().map(|path| path.resolve(ctx)).collect(),
+            outputs: step.outputs.iter().map(|path| path.resolve(ctx)).collect(),
+        })
+        .collect()
+}
+
+/// Plan the steps [`build_all`](super::build_all) runs.
+pub fn plan_build(ctx: &PipelineContext) -> Vec<StepPlan> {
+    [MONO_STEPS, SANS_STEPS, FINAL_STEPS]
+        .into_iter()
+        .flat_map(|steps| plan_steps(ctx, steps))
+        .collect()
+}
+
+/// Print one numbered entry per step with its inputs and outputs.
+pub fn print_plan(plan: &[StepPlan]) {
+    let total = plan.len();
+    for (i, step) in plan.iter().enumerate() {
+        println!("\n[{}/{total}] {}", i + 1, step.name);
+        for input in &step.inputs {
+            println!("  < {}", input.display());
+        }
+        for output in &step.outputs {
+            println!("  > {}", output.display());
+        }
+    }
+}
+
+/// Print and return the plan for [`build_all`](super::build_all) without
+/// running any step.
+pub fn plan_all(
+    build_dir: &Path,
+    dist_dir: &Path,
+    version: Option<String>,
+) -> Result<Vec<StepPlan>> {
+    let ctx = PipelineContext::new(build_dir.to_path_buf(), dist_dir.to_path_buf(), version)?;
+    let plan = plan_build(&ctx);
+
+    println!("═══════════════════════════════════════════════════════════════════════════════");
+    println!("Warpnine Fonts Build Plan (dry run)");
+    println!("═══════════════════════════════════════════════════════════════════════════════");
+    print_plan(&plan);
+
+    Ok(plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_vf_writes_mono_vf() {
+        let ctx = PipelineContext::new("build".into(), "dist".into(), None).unwrap();
+        let plan = plan_build(&ctx);
+
+        assert_eq!(plan.len(), MONO_STEPS.len() + SANS_STEPS.len() + FINAL_STEPS.len());
+        let build_vf = plan.iter().find(|step| step.name == "build-vf").unwrap();
+        assert_eq!(build_vf.outputs, [Path::new("dist").join("WarpnineMono-VF.ttf")]);
+        assert_eq!(build_vf.inputs, [Path::new("dist").join("WarpnineMono-*.ttf")]);
+    }
+}
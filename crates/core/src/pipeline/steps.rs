@@ -6,8 +6,11 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use anyhow::{Result, anyhow};
+use StepPath::{Build, Dist};
+use anyhow::{Context, Result, anyhow, bail};
+use font_instancer::validate_font;
 use rayon::prelude::*;
+use read_fonts::{FontRef, TableProvider};
 use warpnine_font_ops::copy_gsub_without_feature_variations;
 
 use super::{
@@ -17,7 +20,9 @@ use super::{
     vf::{build_warpnine_condensed_vf, build_warpnine_mono_vf, build_warpnine_sans_vf},
 };
 use crate::{
-    MonospaceSettings, Subsetter, convert_to_woff2,
+    MonospaceSettings, Subsetter,
+    config::{JETBRAINS_MONO_FILENAME, NOTO_CJK_VF_FILENAME, RECURSIVE_VF_FILENAME},
+    convert_to_woff2,
     freeze_batch::{AutoRvrn, freeze_features},
     instance::{AxisLocation, InstanceDef, create_instances_batch},
     io::{check_results, glob_fonts, read_font, write_font},
@@ -31,63 +36,137 @@ use crate::{
     },
 };
 
-pub type PipelineStep = (&'static str, fn(&PipelineContext) -> Result<()>);
+/// A build or dist file a step reads or writes, as a glob pattern relative to
+/// its directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepPath {
+    Build(&'static str),
+    Dist(&'static str),
+}
+
+impl StepPath {
+    /// The pattern joined onto the context's build or dist directory.
+    pub fn resolve(self, ctx: &PipelineContext) -> PathBuf {
+        match self {
+            Self::Build(pattern) => ctx.build_dir.join(pattern),
+            Self::Dist(pattern) => ctx.dist_dir.join(pattern),
+        }
+    }
+}
+
+/// A named pipeline step with the files it expects and produces.
+#[derive(Debug, Clone, Copy)]
+pub struct PipelineStep {
+    pub name: &'static str,
+    pub run: fn(&PipelineContext) -> Result<()>,
+    pub inputs: &'static [StepPath],
+    pub outputs: &'static [StepPath],
+}
+
+pub(crate) const fn step(
+    name: &'static str,
+    run: fn(&PipelineContext) -> Result<()>,
+    inputs: &'static [StepPath],
+    outputs: &'static [StepPath],
+) -> PipelineStep {
+    PipelineStep { name, run, inputs, outputs }
+}
+
+const RECURSIVE_VF: StepPath = Build(RECURSIVE_VF_FILENAME);
+const NOTO_CJK_VF: StepPath = Build(NOTO_CJK_VF_FILENAME);
+const JETBRAINS_MONO: StepPath = Build(JETBRAINS_MONO_FILENAME);
+const DUOTONE: StepPath = Build("RecMonoDuotone-*.ttf");
+const NOTO_WEIGHTS_TTF: StepPath = Build("Noto-*.ttf");
+const NOTO_SUBSETS: StepPath = Build("Noto-*-subset.ttf");
+const JETBRAINS_BOX: StepPath = Build("JetBrainsMono-BoxDrawing.ttf");
+const FROZEN_BACKUP: StepPath = Build("frozen/WarpnineMono-*.ttf");
+const MONO: StepPath = Dist("WarpnineMono-*.ttf");
+const MONO_VF: StepPath = Dist("WarpnineMono-VF.ttf");
+const MONO_WOFF2: StepPath = Dist("WarpnineMono-VF.woff2");
+const SANS: StepPath = Dist("WarpnineSans-*.ttf");
+const SANS_VF: StepPath = Dist("WarpnineSans-VF.ttf");
+const SANS_WOFF2: StepPath = Dist("WarpnineSans-VF.woff2");
+const CONDENSED: StepPath = Dist("WarpnineSansCondensed-*.ttf");
+const CONDENSED_VF: StepPath = Dist("WarpnineSansCondensed-VF.ttf");
+const CONDENSED_WOFF2: StepPath = Dist("WarpnineSansCondensed-VF.woff2");
+const ALL_TTF: StepPath = Dist("*.ttf");
 
 pub const MONO_STEPS: &[PipelineStep] = &[
-    ("clean", step_clean),
-    ("download", step_download),
-    ("extract-duotone", step_extract_duotone),
-    ("remove-ligatures", step_remove_ligatures),
-    ("extract-noto-weights", step_extract_noto_weights),
-    ("subset-noto", step_subset_noto),
-    ("subset-jetbrains-box", step_subset_jetbrains_box),
-    ("merge", step_merge),
-    ("set-names-mono", step_set_names_mono),
-    ("freeze-static-mono", step_freeze_static_mono),
-    ("backup-frozen", step_backup_frozen),
-    ("build-vf", step_build_vf),
-    ("copy-gsub", step_copy_gsub),
-    ("restore-frozen", step_restore_frozen),
-    ("set-names-vf", step_set_names_vf),
-    ("set-monospace", step_set_monospace),
-    ("generate-woff2", step_generate_woff2),
+    step("clean", step_clean, &[], &[]),
+    step("download", step_download, &[], &[RECURSIVE_VF, NOTO_CJK_VF, JETBRAINS_MONO]),
+    step("extract-duotone", step_extract_duotone, &[RECURSIVE_VF], &[DUOTONE]),
+    step("remove-ligatures", step_remove_ligatures, &[DUOTONE], &[DUOTONE]),
+    step("extract-noto-weights", step_extract_noto_weights, &[NOTO_CJK_VF], &[NOTO_WEIGHTS_TTF]),
+    step("subset-noto", step_subset_noto, &[NOTO_WEIGHTS_TTF], &[NOTO_SUBSETS]),
+    step("subset-jetbrains-box", step_subset_jetbrains_box, &[JETBRAINS_MONO], &[JETBRAINS_BOX]),
+    step("merge", step_merge, &[DUOTONE, JETBRAINS_BOX, NOTO_SUBSETS], &[MONO]),
+    step("set-names-mono", step_set_names_mono, &[MONO], &[MONO]),
+    step("freeze-static-mono", step_freeze_static_mono, &[MONO], &[MONO]),
+    step("backup-frozen", step_backup_frozen, &[MONO], &[FROZEN_BACKUP]),
+    step("build-vf", step_build_vf, &[MONO], &[MONO_VF]),
+    step("copy-gsub", step_copy_gsub, &[RECURSIVE_VF, MONO_VF], &[MONO_VF]),
+    step("restore-frozen", step_restore_frozen, &[FROZEN_BACKUP], &[MONO]),
+    step("set-names-vf", step_set_names_vf, &[MONO_VF], &[MONO_VF]),
+    step("set-monospace", step_set_monospace, &[MONO], &[MONO]),
+    step("validate", step_validate, &[MONO, MONO_VF], &[]),
+    step("generate-woff2", step_generate_woff2, &[MONO_VF], &[MONO_WOFF2]),
 ];
 
 pub const SANS_STEPS: &[PipelineStep] = &[
-    ("create-condensed", step_create_condensed),
-    ("create-sans", step_create_sans),
-    ("set-names-sans", step_set_names_sans),
-    ("freeze-vf-and-sans", step_freeze_vf_and_sans),
-    ("build-sans-vf", step_build_sans_vf),
-    ("build-condensed-vf", step_build_condensed_vf),
-    ("set-names-sans-vf", step_set_names_sans_vf),
-    ("set-names-condensed-vf", step_set_names_condensed_vf),
-    ("generate-woff2-sans", step_generate_woff2_sans),
-    ("generate-woff2-condensed", step_generate_woff2_condensed),
+    step("create-condensed", step_create_condensed, &[RECURSIVE_VF], &[CONDENSED]),
+    step("create-sans", step_create_sans, &[RECURSIVE_VF], &[SANS]),
+    step("set-names-sans", step_set_names_sans, &[SANS, CONDENSED], &[SANS, CONDENSED]),
+    step(
+        "freeze-vf-and-sans",
+        step_freeze_vf_and_sans,
+        &[MONO_VF, SANS, CONDENSED],
+        &[MONO_VF, SANS, CONDENSED],
+    ),
+    step("build-sans-vf", step_build_sans_vf, &[SANS], &[SANS_VF]),
+    step("build-condensed-vf", step_build_condensed_vf, &[CONDENSED], &[CONDENSED_VF]),
+    step("set-names-sans-vf", step_set_names_sans_vf, &[SANS_VF], &[SANS_VF]),
+    step("set-names-condensed-vf", step_set_names_condensed_vf, &[CONDENSED_VF], &[CONDENSED_VF]),
+    step("validate-sans", step_validate_sans, &[SANS, SANS_VF], &[]),
+    step("validate-condensed", step_validate_condensed, &[CONDENSED, CONDENSED_VF], &[]),
+    step("generate-woff2-sans", step_generate_woff2_sans, &[SANS_VF], &[SANS_WOFF2]),
+    step(
+        "generate-woff2-condensed",
+        step_generate_woff2_condensed,
+        &[CONDENSED_VF],
+        &[CONDENSED_WOFF2],
+    ),
 ];
 
-pub const FINAL_STEPS: &[PipelineStep] = &[("set-version", step_set_version)];
+pub const FINAL_STEPS: &[PipelineStep] =
+    &[step("set-version", step_set_version, &[ALL_TTF], &[ALL_TTF])];
 
 pub const SANS_ONLY_STEPS: &[PipelineStep] = &[
-    ("download", step_download),
-    ("create-sans", step_create_sans),
-    ("set-names-sans-only", step_set_names_sans_only),
-    ("freeze-sans", step_freeze_sans),
-    ("build-sans-vf", step_build_sans_vf),
-    ("set-names-sans-vf", step_set_names_sans_vf),
-    ("generate-woff2-sans", step_generate_woff2_sans),
-    ("set-version", step_set_version),
+    step("download", step_download, &[], &[RECURSIVE_VF, NOTO_CJK_VF, JETBRAINS_MONO]),
+    step("create-sans", step_create_sans, &[RECURSIVE_VF], &[SANS]),
+    step("set-names-sans-only", step_set_names_sans_only, &[SANS], &[SANS]),
+    step("freeze-sans", step_freeze_sans, &[SANS], &[SANS]),
+    step("build-sans-vf", step_build_sans_vf, &[SANS], &[SANS_VF]),
+    step("set-names-sans-vf", step_set_names_sans_vf, &[SANS_VF], &[SANS_VF]),
+    step("validate-sans", step_validate_sans, &[SANS, SANS_VF], &[]),
+    step("generate-woff2-sans", step_generate_woff2_sans, &[SANS_VF], &[SANS_WOFF2]),
+    step("set-version", step_set_version, &[ALL_TTF], &[ALL_TTF]),
 ];
 
 pub const CONDENSED_ONLY_STEPS: &[PipelineStep] = &[
-    ("download", step_download),
-    ("create-condensed", step_create_condensed),
-    ("set-names-condensed-only", step_set_names_condensed_only),
-    ("freeze-condensed", step_freeze_condensed),
-    ("build-condensed-vf", step_build_condensed_vf),
-    ("set-names-condensed-vf", step_set_names_condensed_vf),
-    ("generate-woff2-condensed", step_generate_woff2_condensed),
-    ("set-version", step_set_version),
+    step("download", step_download, &[], &[RECURSIVE_VF, NOTO_CJK_VF, JETBRAINS_MONO]),
+    step("create-condensed", step_create_condensed, &[RECURSIVE_VF], &[CONDENSED]),
+    step("set-names-condensed-only", step_set_names_condensed_only, &[CONDENSED], &[CONDENSED]),
+    step("freeze-condensed", step_freeze_condensed, &[CONDENSED], &[CONDENSED]),
+    step("build-condensed-vf", step_build_condensed_vf, &[CONDENSED], &[CONDENSED_VF]),
+    step("set-names-condensed-vf", step_set_names_condensed_vf, &[CONDENSED_VF], &[CONDENSED_VF]),
+    step("validate-condensed", step_validate_condensed, &[CONDENSED, CONDENSED_VF], &[]),
+    step(
+        "generate-woff2-condensed",
+        step_generate_woff2_condensed,
+        &[CONDENSED_VF],
+        &[CONDENSED_WOFF2],
+    ),
+    step("set-version", step_set_version, &[ALL_TTF], &[ALL_TTF]),
 ];
 
 fn step_clean(ctx: &PipelineContext) -> Result<()> {
@@ -271,7 +350,7 @@ fn step_backup_frozen(ctx: &PipelineContext) -> Result<()> {
 }
 
 fn step_build_vf(ctx: &PipelineContext) -> Result<()> {
-    build_warpnine_mono_vf(&ctx.dist_dir, &ctx.vf_output())
+    build_warpnine_mono_vf(&ctx.dist_dir, &ctx.vf_output(), ctx.check_vf)
 }
 
 fn step_copy_gsub(ctx: &PipelineContext) -> Result<()> {
@@ -327,6 +406,47 @@ fn step_set_monospace(ctx: &PipelineContext) -> Result<()> {
     check_results(&results, "set monospace")
 }
 
+fn step_validate(ctx: &PipelineContext) -> Result<()> {
+    validate_statics(&ctx.static_mono_fonts()?, &ctx.vf_output())
+}
+
+fn step_validate_sans(ctx: &PipelineContext) -> Result<()> {
+    validate_statics(&ctx.static_dist_fonts("WarpnineSans-*.ttf")?, &ctx.sans_vf_output())
+}
+
+fn step_validate_condensed(ctx: &PipelineContext) -> Result<()> {
+    let fonts = ctx.static_dist_fonts("WarpnineSansCondensed-*.ttf")?;
+    validate_statics(&fonts, &ctx.condensed_vf_output())
+}
+
+/// Re-parse each static font, checking its glyf/loca consistency and that
+/// its glyph count matches the variable font built from the same masters.
+/// Silently skips if the VF does not exist.
+fn validate_statics(fonts: &[PathBuf], vf: &Path) -> Result<()> {
+    if !vf.exists() {
+        println!("  VF not found, skipping validation");
+        return Ok(());
+    }
+
+    let vf_glyphs = FontRef::new(&read_font(vf)?)?.maxp()?.num_glyphs();
+    println!("  Validating {} static fonts against {}...", fonts.len(), vf.display());
+
+    let results: Vec<_> = fonts
+        .par_iter()
+        .map(|path| {
+            let data = read_font(path)?;
+            validate_font(&data).with_context(|| format!("{}", path.display()))?;
+            let glyphs = FontRef::new(&data)?.maxp()?.num_glyphs();
+            if glyphs != vf_glyphs {
+                bail!("{}: {glyphs} glyphs, but the VF has {vf_glyphs}", path.display());
+            }
+            Ok(())
+        })
+        .collect();
+
+    check_results(&results, "validate")
+}
+
 fn step_create_condensed(ctx: &PipelineContext) -> Result<()> {
     create_condensed(&ctx.recursive_vf, &ctx.dist_dir, 0.90)
 }
@@ -435,11 +555,11 @@ fn step_freeze_vf_and_sans(ctx: &PipelineContext) -> Result<()> {
 }
 
 fn step_build_sans_vf(ctx: &PipelineContext) -> Result<()> {
-    build_warpnine_sans_vf(&ctx.dist_dir, &ctx.sans_vf_output())
+    build_warpnine_sans_vf(&ctx.dist_dir, &ctx.sans_vf_output(), ctx.check_vf)
 }
 
 fn step_build_condensed_vf(ctx: &PipelineContext) -> Result<()> {
-    build_warpnine_condensed_vf(&ctx.dist_dir, &ctx.condensed_vf_output())
+    build_warpnine_condensed_vf(&ctx.dist_dir, &ctx.condensed_vf_output(), ctx.check_vf)
 }
 
 /// Set name records on a built variable font. Variable fonts use the family
@@ -0,0 +1,144 @@
+//! Per-step build timing and output size, for tracking regressions.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    fs::{read_dir, write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use anyhow::{Context, Result};
+
+/// File name of the CSV written by [`Telemetry::write_csv`] into the build
+/// directory.
+pub const BUILD_STATS_FILENAME: &str = "build-stats.csv";
+
+/// Modification time and size of every file under a set of directories.
+pub type FileSnapshot = BTreeMap<PathBuf, (Option<SystemTime>, u64)>;
+
+/// Timing and output of one pipeline step.
+#[derive(Debug, Clone)]
+pub struct StepStats {
+    pub step: String,
+    pub seconds: f64,
+    /// Files under the build and dist directories created or changed by the step
+    pub output_files: usize,
+    pub total_bytes: u64,
+}
+
+/// Collects [`StepStats`] as [`run_step`](super::run_step) completes steps,
+/// once enabled with [`PipelineContext::record_stats`](super::PipelineContext::record_stats).
+#[derive(Debug, Default)]
+pub struct Telemetry {
+    steps: Vec<StepStats>,
+}
+
+impl Telemetry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn steps(&self) -> &[StepStats] {
+        &self.steps
+    }
+
+    /// Record a step that took `elapsed`, counting files in `dirs` that are
+    /// new or changed since `before` as its output.
+    pub fn record(&mut self, step: &str, elapsed: Duration, before: &FileSnapshot, dirs: &[&Path]) {
+        let outputs: Vec<u64> = snapshot(dirs)
+            .into_iter()
+            .filter(|(path, meta)| before.get(path) != Some(meta))
+            .map(|(_, (_, len))| len)
+            .collect();
+        self.steps.push(StepStats {
+            step: step.to_string(),
+            seconds: elapsed.as_secs_f64(),
+            output_files: outputs.len(),
+            total_bytes: outputs.iter().sum(),
+        });
+    }
+
+    /// Write one `step,seconds,output_files,total_bytes` row per step.
+    pub fn write_csv(&self, path: &Path) -> Result<()> {
+        let mut csv = String::from("step,seconds,output_files,total_bytes\n");
+        for s in &self.steps {
+            writeln!(csv, "{},{:.3},{},{}", s.step, s.seconds, s.output_files, s.total_bytes)?;
+        }
+        write(path, csv).with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+/// Snapshot every file under `dirs`. Unreadable entries are skipped, since a
+/// missing directory just means nothing has been written there yet.
+pub fn snapshot(dirs: &[&Path]) -> FileSnapshot {
+    let mut files = FileSnapshot::new();
+    for dir in dirs {
+        collect_files(dir, &mut files);
+    }
+    files
+}
+
+fn collect_files(dir: &Path, files: &mut FileSnapshot) {
+    let Ok(entries) = read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        if meta.is_dir() {
+            collect_files(&entry.path(), files);
+        } else {
+            files.insert(entry.path(), (meta.modified().ok(), meta.len()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        env::temp_dir,
+        fs::{create_dir_all, read_to_string, remove_dir_all},
+        process::id,
+    };
+
+    use super::*;
+    use crate::pipeline::{PipelineContext, StepPath, run_steps, step};
+
+    fn write_one(ctx: &PipelineContext) -> Result<()> {
+        Ok(write(ctx.dist_dir.join("one.ttf"), [0u8; 10])?)
+    }
+
+    fn write_nothing(_: &PipelineContext) -> Result<()> {
+        Ok(())
+    }
+
+    #[test]
+    fn csv_has_one_row_per_step() {
+        let dir = temp_dir().join(format!("warpnine-telemetry-{}", id()));
+        let mut ctx = PipelineContext::new(dir.join("build"), dir.join("dist"), None).unwrap();
+        create_dir_all(&ctx.dist_dir).unwrap();
+
+        let steps = [
+            step("write-one", write_one, &[], &[StepPath::Dist("one.ttf")]),
+            step("write-nothing", write_nothing, &[], &[]),
+        ];
+        run_steps(&steps, &ctx, 0, steps.len()).unwrap();
+        assert!(ctx.telemetry().is_none());
+
+        ctx.record_stats();
+        run_steps(&steps, &ctx, 0, steps.len()).unwrap();
+        let path = dir.join(BUILD_STATS_FILENAME);
+        ctx.telemetry().unwrap().write_csv(&path).unwrap();
+        let csv = read_to_string(&path).unwrap();
+        let rows: Vec<Vec<&str>> = csv.lines().skip(1).map(|l| l.split(',').collect()).collect();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!((rows[0][0], rows[0][2], rows[0][3]), ("write-one", "1", "10"));
+        assert_eq!((rows[1][0], rows[1][2], rows[1][3]), ("write-nothing", "0", "0"));
+        assert!(rows.iter().all(|row| row[1].parse::<f64>().unwrap() >= 0.0));
+
+        remove_dir_all(&dir).unwrap();
+    }
+}
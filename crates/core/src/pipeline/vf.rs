@@ -3,7 +3,9 @@
 use std::{fs::write, path::Path};
 
 use anyhow::{Context, Result, bail};
-use warpnine_font_vf_builder::{Axis, DesignSpace, Instance, Source, build_variable_font};
+use warpnine_font_vf_builder::{
+    Axis, DesignSpace, Instance, Source, build_variable_font, build_variable_font_verified,
+};
 
 use crate::styles::{MONO_STYLES, SANS_STYLES, Style};
 
@@ -48,11 +50,15 @@ fn family_designspace(
 }
 
 /// Build a variable font from `designspace`, writing the result to `output`.
+///
+/// With `check`, the font is instanced back at every master's location and
+/// compared against that master before it is written.
 fn build_family_vf(
     designspace: &DesignSpace,
     output: &Path,
     label: &str,
     wght_max: f32,
+    check: bool,
 ) -> Result<()> {
     println!("Building {label} variable font...");
 
@@ -65,8 +71,12 @@ fn build_family_vf(
     println!("  Sources: {} masters", designspace.sources.len());
     println!("  Axes: wght (300-{wght_max:.0}), ital (0-1)");
 
-    let vf_data =
-        build_variable_font(designspace).with_context(|| "Failed to build variable font")?;
+    let vf_data = if check {
+        build_variable_font_verified(designspace)
+    } else {
+        build_variable_font(designspace)
+    }
+    .with_context(|| "Failed to build variable font")?;
 
     write(output, &vf_data).with_context(|| format!("Failed to write {}", output.display()))?;
 
@@ -80,17 +90,61 @@ pub fn warpnine_mono_designspace(dist_dir: &Path) -> DesignSpace {
     family_designspace(dist_dir, MONO_STYLES, "WarpnineMono-", 1000.0)
 }
 
-pub fn build_warpnine_mono_vf(dist_dir: &Path, output: &Path) -> Result<()> {
+pub fn build_warpnine_mono_vf(dist_dir: &Path, output: &Path, check: bool) -> Result<()> {
     let designspace = warpnine_mono_designspace(dist_dir);
-    build_family_vf(&designspace, output, "WarpnineMono", 1000.0)
+    build_family_vf(&designspace, output, "WarpnineMono", 1000.0, check)
 }
 
-pub fn build_warpnine_sans_vf(dist_dir: &Path, output: &Path) -> Result<()> {
+pub fn build_warpnine_sans_vf(dist_dir: &Path, output: &Path, check: bool) -> Result<()> {
     let designspace = family_designspace(dist_dir, SANS_STYLES, "WarpnineSans-", 900.0);
-    build_family_vf(&designspace, output, "WarpnineSans", 900.0)
+    build_family_vf(&designspace, output, "WarpnineSans", 900.0, check)
 }
 
-pub fn build_warpnine_condensed_vf(dist_dir: &Path, output: &Path) -> Result<()> {
+pub fn build_warpnine_condensed_vf(dist_dir: &Path, output: &Path, check: bool) -> Result<()> {
     let designspace = family_designspace(dist_dir, SANS_STYLES, "WarpnineSansCondensed-", 900.0);
-    build_family_vf(&designspace, output, "WarpnineSansCondensed", 900.0)
+    build_family_vf(&designspace, output, "WarpnineSansCondensed", 900.0, check)
+}
+
+#[cfg(test)]
+mod tests {
+    use warpnine_font_vf_builder::{VariationModel, build_variable_font_with_report};
+
+    use super::*;
+
+    #[test]
+    fn mono_variation_model_regions() {
+        let designspace = warpnine_mono_designspace(Path::new("dist"));
+        let model = VariationModel::new(&designspace).unwrap();
+        let regions = model.describe();
+
+        // One region per non-default master.
+        assert_eq!(regions.len(), MONO_STYLES.len() - 1);
+
+        let bold = MONO_STYLES.iter().position(|style| style.name == "Bold").unwrap();
+        let region = regions.iter().find(|region| region.master_idx == bold).unwrap();
+        // wght 700 on a 300/400/1000 axis normalizes to (700 - 400) / (1000 - 400).
+        assert_eq!(region.axes[0].1, 0.5);
+        assert_eq!(region.axes[1], (0.0, 0.0, 0.0));
+    }
+
+    /// Skipped unless the WarpnineMono static masters have been built into
+    /// the workspace's `dist/`.
+    #[test]
+    fn mono_named_instances_map_to_os2_classes() {
+        let dist_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../dist");
+        let designspace = warpnine_mono_designspace(&dist_dir);
+        if designspace.sources.iter().any(|source| !source.path.exists()) {
+            eprintln!("skipping: WarpnineMono masters not found in {}", dist_dir.display());
+            return;
+        }
+
+        let (_, report) = build_variable_font_with_report(&designspace).unwrap();
+        let classes = &report.instance_classes;
+        assert_eq!(classes.len(), MONO_STYLES.len());
+        for style in MONO_STYLES {
+            let (weight, width) = classes[&style.display_name()];
+            assert_eq!(weight, style.weight.as_class().0, "{}", style.name);
+            assert_eq!(width, 5, "{}", style.name);
+        }
+    }
 }
@@ -2,7 +2,7 @@
 
 use std::result;
 
-use read_fonts::ReadError;
+use read_fonts::{ReadError, types::Tag};
 use write_fonts::BuilderError;
 
 /// Errors that can occur during font feature freezing.
@@ -14,12 +14,18 @@ pub enum Error {
     #[error("no GSUB table in font")]
     NoGsub,
 
+    #[error("no GPOS table in font")]
+    NoGpos,
+
     #[error("no cmap table in font")]
     NoCmap,
 
     #[error("failed to build font: {0}")]
     Build(#[from] BuilderError),
 
+    #[error("feature '{0}' has no lookups for the selected script/language")]
+    FeatureNotFound(Tag),
+
     #[error("no matching features found for {0:?}")]
     NoMatchingFeatures(Vec<String>),
 
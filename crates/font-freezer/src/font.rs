@@ -9,20 +9,23 @@ use std::{
 
 use read_fonts::{
     FontRef, TableProvider,
-    tables::gsub::Gsub,
-    types::{GlyphId16, NameId},
+    tables::layout::{FeatureList, ScriptList},
+    types::{GlyphId, GlyphId16, NameId, Tag},
 };
 use write_fonts::{
     BuilderError, FontBuilder,
+    from_obj::ToOwnedTable,
     tables::{
         cmap::{Cmap, CmapSubtable, EncodingRecord, PlatformId, SequentialMapGroup},
+        hhea::Hhea,
+        hmtx::{Hmtx, LongMetric},
         name::{Name, NameRecord},
         post::Post,
     },
     types::Version16Dot16,
 };
 
-use crate::{Result, error::Error, gsub::GlyphSubstitutions, types::*};
+use crate::{Result, error::Error, gpos::KernPairs, gsub::GlyphSubstitutions, types::*};
 
 /// A parsed font ready for feature freezing.
 pub struct Font<'a> {
@@ -87,28 +90,37 @@ impl<'a> Font<'a> {
         Ok(FontReport { scripts_langs, features })
     }
 
+    /// Freeze the requested features, and kerning when asked to.
+    ///
+    /// Features apply in the order they are listed in `options`, so where
+    /// two features substitute the same source glyph, the later one wins;
+    /// each such case is recorded in [`FreezeStats::overrides`]. With
+    /// kerning requested and no features, GSUB and `cmap` are left alone.
+    ///
+    /// A requested feature without lookups under the selected
+    /// script/language fails the freeze in [strict](FreezeOptions::strict)
+    /// mode, and is otherwise reported in [`FreezeResult::warnings`].
     pub fn freeze(&self, options: &FreezeOptions) -> Result<FreezeResult> {
-        let gsub = self.inner.gsub().map_err(|_| Error::NoGsub)?;
-
-        let lookup_indices = FeatureResolver { gsub: &gsub, options }.resolve()?;
-        if lookup_indices.is_empty() {
-            return Err(Error::NoMatchingFeatures(options.features.clone()));
-        }
-
-        let mut subs = GlyphSubstitutions::new();
-        subs.process_lookups(&gsub, &lookup_indices)?;
-        if subs.is_empty() {
-            return Err(Error::NoSubstitutions(options.features.clone()));
-        }
+        let kern_only = options.wants_kern() && options.features.is_empty();
+        let (subs, mut stats, unresolved) =
+            if kern_only { Default::default() } else { self.substitutions(options)? };
 
         // Only compute glyph info if we need warnings/names (expensive for large fonts)
-        let (warnings, remapped_names) = if options.suffix.is_enabled() || options.warnings {
+        let (mut warnings, remapped_names) = if options.suffix.is_enabled() || options.warnings {
             GlyphInfo::from_font(&self.inner).analyze(&subs)
         } else {
             Default::default()
         };
+        warnings.extend(unresolved.iter().map(|tag| {
+            format!("Feature '{tag}' has no lookups for the selected script/language; skipped.")
+        }));
+        warnings.extend(self.short_alternate_warnings(&subs, options.alternate_index));
 
-        let mut data = FontEditor(self.inner.clone()).with_remapped_cmap(&subs)?;
+        let mut data = if kern_only {
+            self.data.to_vec()
+        } else {
+            FontEditor(self.inner.clone()).with_remapped_cmap(&subs)?
+        };
 
         if options.wants_name_edits() {
             data = FontEditor::from_data(&data)?.with_modified_names(options)?;
@@ -117,16 +129,122 @@ impl<'a> Font<'a> {
             data = FontEditor::from_data(&data)?.with_post_v3()?;
         }
 
-        Ok(FreezeResult {
-            data,
-            stats: FreezeStats {
-                features_requested: options.features.len(),
-                lookups_processed: lookup_indices.len(),
-                substitutions_applied: subs.len(),
-            },
-            warnings,
-            remapped_names,
-        })
+        let mut kern = None;
+        if options.wants_kern() {
+            let pairs = self.kern_pairs(&options.filter)?;
+            stats.kern_pairs = pairs.pairs();
+            kern = Some(KernFreeze::Reported);
+            if options.bake_kern {
+                stats.kern_baked = pairs.most_common();
+                data = FontEditor::from_data(&data)?.with_kerned_advances(&stats.kern_baked)?;
+                kern = Some(pairs.outcome(&stats.kern_baked, self.inner.maxp()?.num_glyphs()));
+            }
+        }
+
+        Ok(FreezeResult { data, stats, warnings, remapped_names, kern })
+    }
+
+    /// Resolve the requested features to GSUB substitutions, applying them
+    /// in the order they are listed, with the statistics of doing so and the
+    /// features that resolved to no lookups.
+    fn substitutions(
+        &self,
+        options: &FreezeOptions,
+    ) -> Result<(GlyphSubstitutions, FreezeStats, Vec<Tag>)> {
+        let gsub = self.inner.gsub().map_err(|_| Error::NoGsub)?;
+        let resolver = |feature_tags| {
+            FeatureResolver {
+                script_list: gsub.script_list()?,
+                feature_list: gsub.feature_list()?,
+                feature_tags,
+                filter: &options.filter,
+            }
+            .resolve()
+        };
+
+        let features = options
+            .ordered_feature_tags()
+            .into_iter()
+            .map(|feature| Ok((feature, resolver(HashSet::from([feature]))?.0)))
+            .collect::<Result<Vec<_>>>()?;
+        let unresolved: Vec<Tag> = features
+            .iter()
+            .filter(|(_, lookups)| lookups.is_empty())
+            .map(|(feature, _)| *feature)
+            .collect();
+        if options.strict
+            && let Some(&feature) = unresolved.first()
+        {
+            return Err(Error::FeatureNotFound(feature));
+        }
+
+        let (lookup_indices, scripts_touched) = resolver(options.feature_tags())?;
+        if lookup_indices.is_empty() {
+            return Err(Error::NoMatchingFeatures(options.features.clone()));
+        }
+
+        let mut subs = GlyphSubstitutions::new().with_alternate_index(options.alternate_index);
+        let mut overrides = Vec::new();
+        for (feature, feature_lookups) in features {
+            let mut feature_subs =
+                GlyphSubstitutions::new().with_alternate_index(options.alternate_index);
+            feature_subs.process_lookups(&gsub, &feature_lookups)?;
+            overrides.extend(subs.apply_later(&feature_subs).into_iter().map(
+                |(glyph, replaced, substitute)| SubstitutionOverride {
+                    feature,
+                    glyph,
+                    replaced,
+                    substitute,
+                },
+            ));
+        }
+        if subs.is_empty() {
+            return Err(Error::NoSubstitutions(options.features.clone()));
+        }
+
+        let stats = FreezeStats {
+            features_requested: options.features.len(),
+            lookups_processed: lookup_indices.len(),
+            substitutions_applied: subs.len(),
+            scripts_touched,
+            overrides,
+            ..Default::default()
+        };
+        Ok((subs, stats, unresolved))
+    }
+
+    /// Warn about alternate sets that had no alternate at `index`.
+    fn short_alternate_warnings(&self, subs: &GlyphSubstitutions, index: usize) -> Vec<String> {
+        let post = self.inner.post().ok();
+        subs.short_alternate_sets()
+            .iter()
+            .map(|&(gid, count)| {
+                let name = post
+                    .as_ref()
+                    .and_then(|post| post.glyph_name(GlyphId16::new(gid)))
+                    .map_or_else(|| format!("gid{gid}"), |name| name.to_string());
+                format!(
+                    "'{name}' has {count} alternates, so alternate {index} is out of range; using the last one."
+                )
+            })
+            .collect()
+    }
+
+    /// Read the GPOS `kern` feature's pair adjustments for `filter`.
+    fn kern_pairs(&self, filter: &ScriptLangFilter) -> Result<KernPairs> {
+        let gpos = self.inner.gpos().map_err(|_| Error::NoGpos)?;
+
+        let (lookup_indices, _) = FeatureResolver {
+            script_list: gpos.script_list()?,
+            feature_list: gpos.feature_list()?,
+            feature_tags: HashSet::from([Tag::new(b"kern")]),
+            filter,
+        }
+        .resolve()?;
+
+        let mut pairs = KernPairs::new();
+        pairs.process_lookups(&gpos, &lookup_indices)?;
+        Ok(pairs)
     }
 
     pub fn data(&self) -> &[u8] {
@@ -134,23 +252,40 @@ impl<'a> Font<'a> {
     }
 }
 
+/// Script/language systems, in script list order.
+type ScriptLangs = Vec<(ScriptTag, LangTag)>;
+
 struct FeatureResolver<'a> {
-    gsub: &'a Gsub<'a>,
-    options: &'a FreezeOptions,
+    script_list: ScriptList<'a>,
+    feature_list: FeatureList<'a>,
+    feature_tags: HashSet<Tag>,
+    filter: &'a ScriptLangFilter,
 }
 
 impl FeatureResolver<'_> {
+    /// Resolve the requested features to lookup indices.
+    ///
+    /// Features are applied in every script/language system they appear in
+    /// unless the options restrict them with a [`ScriptLangFilter`]. Also
+    /// returns the script/language systems that referenced a requested feature.
+    //
     // `.iter().map(|i| i.get())` over `&BigEndian<T>` reads as a redundant closure
     // to clippy, but the method-path form does not type-check: `get` takes `self`
     // by value while `iter()` yields `&BigEndian<T>`.
     #[allow(clippy::redundant_closure_for_method_calls)]
-    fn resolve(&self) -> Result<BTreeSet<u16>> {
-        let feature_indices = self.collect_feature_indices()?;
-        let feature_tags = self.options.feature_tags();
-        let feature_list = self.gsub.feature_list()?;
+    fn resolve(&self) -> Result<(BTreeSet<u16>, ScriptLangs)> {
+        let feature_tags = &self.feature_tags;
+        let feature_list = &self.feature_list;
+        let records = feature_list.feature_records();
+        let is_requested = |i: u16| {
+            records
+                .get(i as usize)
+                .is_some_and(|r| feature_tags.contains(&r.feature_tag()))
+        };
 
-        Ok(feature_list
-            .feature_records()
+        let (feature_indices, scripts_touched) = self.collect_feature_indices(is_requested)?;
+
+        let lookup_indices = records
             .iter()
             .enumerate()
             .filter(|(i, _)| feature_indices.as_ref().is_none_or(|fi| fi.contains(&(*i as u16))))
@@ -160,41 +295,62 @@ impl FeatureResolver<'_> {
                     .into_iter()
                     .flat_map(|f| f.lookup_list_indices().iter().map(|i| i.get()))
             })
-            .collect())
+            .collect();
+
+        Ok((lookup_indices, scripts_touched))
     }
 
+    /// Walk the script list, returning the feature indices allowed by the
+    /// filter (`None` when unfiltered) and the script/language systems in
+    /// which a requested feature was found.
+    //
     // See `resolve`: `.map(|i| i.get())` over `&BigEndian<T>` is a clippy
     // false-positive that cannot use the method-path form.
     #[allow(clippy::redundant_closure_for_method_calls)]
-    fn collect_feature_indices(&self) -> Result<Option<HashSet<u16>>> {
-        if !self.options.filter.is_active() {
-            return Ok(None);
-        }
-
-        let script_list = self.gsub.script_list()?;
+    fn collect_feature_indices(
+        &self,
+        is_requested: impl Fn(u16) -> bool,
+    ) -> Result<(Option<HashSet<u16>>, ScriptLangs)> {
+        let filter = self.filter;
+        let script_list = &self.script_list;
         let mut indices = HashSet::new();
+        let mut touched = Vec::new();
 
         for sr in script_list.script_records() {
-            if !self.options.filter.matches_script(&sr.script_tag().to_string()) {
+            let script_tag = sr.script_tag();
+            if !filter.matches_script(&script_tag.to_string()) {
                 continue;
             }
             let Ok(script) = sr.script(script_list.offset_data()) else {
                 continue;
             };
 
-            if self.options.filter.lang.is_some() {
-                for lr in script.lang_sys_records() {
-                    if self.options.filter.matches_lang(&lr.lang_sys_tag().to_string())
-                        && let Ok(ls) = lr.lang_sys(script.offset_data())
-                    {
-                        indices.extend(ls.feature_indices().iter().map(|i| i.get()));
-                    }
+            let default_lang = script
+                .default_lang_sys()
+                .and_then(|ls| ls.ok())
+                .filter(|_| filter.lang.is_none())
+                .map(|ls| (Tag::new(b"dflt"), ls));
+            let langs = script
+                .lang_sys_records()
+                .iter()
+                .filter(|lr| {
+                    !filter.is_active() || filter.matches_lang(&lr.lang_sys_tag().to_string())
+                })
+                .filter_map(|lr| {
+                    Some((lr.lang_sys_tag(), lr.lang_sys(script.offset_data()).ok()?))
+                });
+
+            for (lang_tag, ls) in default_lang.into_iter().chain(langs) {
+                let feature_indices: Vec<u16> =
+                    ls.feature_indices().iter().map(|i| i.get()).collect();
+                if feature_indices.iter().any(|&i| is_requested(i)) {
+                    touched.push((script_tag, lang_tag));
                 }
-            } else if let Some(Ok(ls)) = script.default_lang_sys() {
-                indices.extend(ls.feature_indices().iter().map(|i| i.get()));
+                indices.extend(feature_indices);
             }
         }
-        Ok(Some(indices))
+
+        Ok((filter.is_active().then_some(indices), touched))
     }
 }
 
@@ -307,6 +463,7 @@ impl<'a> FontEditor<'a> {
         let family_new = format!("{family}{}", options.suffix_string());
         let (family_old_ns, family_new_ns) =
             (family_old.replace(' ', ""), family_new.replace(' ', ""));
+        let suffix_ns = options.suffix_string().replace(' ', "");
         let features_csv = options.features.join(",");
 
         let records: Vec<_> = records
@@ -317,6 +474,10 @@ impl<'a> FontEditor<'a> {
                     1 | 4 | 16 | 18 | 21 => orig.replace(&family_old, &family_new),
                     3 => format!("{orig};featfreeze:{features_csv}"),
                     5 if options.info => format!("{orig}; featfreeze: {features_csv}"),
+                    6 | 20 if options.suffix_ps_name => format!(
+                        "{}{suffix_ns}",
+                        orig.replace(&family_old_ns, &family.replace(' ', ""))
+                    ),
                     6 | 20 => orig.replace(&family_old_ns, &family_new_ns),
                     _ => orig,
                 };
@@ -333,6 +494,36 @@ impl<'a> FontEditor<'a> {
         self.rebuild(|b| b.add_table(&Name::new(records)).map(|_| ()))
     }
 
+    /// Add each `(glyph, adjustment)` to the glyph's advance, writing every
+    /// glyph as a long metric.
+    pub fn with_kerned_advances(&self, baked: &[(u16, i16)]) -> Result<Vec<u8>> {
+        let hmtx = self.0.hmtx()?;
+        let num_glyphs = self.0.maxp()?.num_glyphs();
+        let adjustments: HashMap<u16, i16> = baked.iter().copied().collect();
+
+        let metrics: Vec<LongMetric> = (0..num_glyphs)
+            .map(|gid| {
+                let glyph = GlyphId::from(gid);
+                let advance = i32::from(hmtx.advance(glyph).unwrap_or(0))
+                    + i32::from(adjustments.get(&gid).copied().unwrap_or(0));
+                LongMetric::new(
+                    advance.clamp(0, i32::from(u16::MAX)) as u16,
+                    hmtx.side_bearing(glyph).unwrap_or(0),
+                )
+            })
+            .collect();
+
+        let mut hhea: Hhea = self.0.hhea()?.to_owned_table();
+        hhea.number_of_h_metrics = num_glyphs;
+        hhea.advance_width_max =
+            metrics.iter().map(|metric| metric.advance).max().unwrap_or(0).into();
+
+        self.rebuild(|b| {
+            b.add_table(&Hmtx::new(metrics, Vec::new()))?;
+            b.add_table(&hhea).map(|_| ())
+        })
+    }
+
     pub fn with_post_v3(&self) -> Result<Vec<u8>> {
         let post = self.0.post()?;
         let mut new_post = Post::new(
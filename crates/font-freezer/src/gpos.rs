@@ -0,0 +1,135 @@
+//! GPOS (Glyph Positioning) pair kerning extraction.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use read_fonts::tables::gpos::{ExtensionSubtable, Gpos, PairPos, PairPosFormat1, PositionLookup};
+
+use crate::{KernFreeze, KernPair, Result};
+
+/// Advance adjustments of glyph pairs, read from GPOS pair lookups.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KernPairs {
+    pairs: BTreeMap<(u16, u16), i16>,
+    class_subtables: usize,
+}
+
+impl KernPairs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Collect the first glyph's x advance adjustment of every pair in the
+    /// given lookups. Within a lookup the first subtable defining a pair
+    /// wins, as in shaping; pairs from separate lookups add up.
+    pub fn process_lookups(&mut self, gpos: &Gpos, indices: &BTreeSet<u16>) -> Result<()> {
+        let lookup_list = gpos.lookup_list()?;
+        for &idx in indices {
+            let Ok(lookup) = lookup_list.lookups().get(idx as usize) else {
+                continue;
+            };
+            let subtables: Vec<PairPos> = match lookup {
+                PositionLookup::Pair(lookup) => lookup.subtables().iter().flatten().collect(),
+                PositionLookup::Extension(lookup) => lookup
+                    .subtables()
+                    .iter()
+                    .flatten()
+                    .filter_map(|subtable| match subtable {
+                        ExtensionSubtable::Pair(ext) => ext.extension().ok(),
+                        _ => None,
+                    })
+                    .collect(),
+                _ => continue,
+            };
+
+            let mut lookup_pairs = BTreeMap::new();
+            for subtable in subtables {
+                match subtable {
+                    PairPos::Format1(format1) => process_format1(&format1, &mut lookup_pairs)?,
+                    PairPos::Format2(_) => self.class_subtables += 1,
+                }
+            }
+            for (pair, value) in lookup_pairs {
+                let total = self.pairs.entry(pair).or_default();
+                *total = total.saturating_add(value);
+            }
+        }
+        self.pairs.retain(|_, value| *value != 0);
+        Ok(())
+    }
+
+    /// Pairs with a non-zero adjustment, sorted by glyph.
+    pub fn pairs(&self) -> Vec<KernPair> {
+        self.pairs
+            .iter()
+            .map(|(&(left, right), &x_advance)| KernPair { left, right, x_advance })
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    /// The most common adjustment of each first glyph, ties going to the
+    /// smaller one.
+    pub fn most_common(&self) -> Vec<(u16, i16)> {
+        let mut counts: BTreeMap<u16, HashMap<i16, usize>> = BTreeMap::new();
+        for (&(left, _), &value) in &self.pairs {
+            *counts.entry(left).or_default().entry(value).or_default() += 1;
+        }
+        counts
+            .into_iter()
+            .filter_map(|(left, values)| {
+                values
+                    .into_iter()
+                    .max_by_key(|&(value, count)| (count, -i32::from(value).abs(), -value))
+                    .map(|(value, _)| (left, value))
+            })
+            .collect()
+    }
+
+    /// How faithfully `baked` advances reproduce the kerning, given the
+    /// font's glyph count.
+    pub fn outcome(&self, baked: &[(u16, i16)], num_glyphs: u16) -> KernFreeze {
+        let baked: HashMap<u16, i16> = baked.iter().copied().collect();
+        let unmatched: Vec<KernPair> = self
+            .pairs()
+            .into_iter()
+            .filter(|pair| baked.get(&pair.left) != Some(&pair.x_advance))
+            .collect();
+        let mut uncovered: Vec<u16> = baked
+            .keys()
+            .copied()
+            .filter(|&left| {
+                self.pairs.range((left, 0)..=(left, u16::MAX)).count() < usize::from(num_glyphs)
+            })
+            .collect();
+        uncovered.sort_unstable();
+
+        if unmatched.is_empty() && uncovered.is_empty() && self.class_subtables == 0 {
+            KernFreeze::Lossless
+        } else {
+            KernFreeze::Lossy {
+                unmatched,
+                uncovered,
+                class_subtables: self.class_subtables,
+            }
+        }
+    }
+}
+
+fn process_format1(format1: &PairPosFormat1, pairs: &mut BTreeMap<(u16, u16), i16>) -> Result<()> {
+    let coverage = format1.coverage()?;
+    for (left, pair_set) in coverage.iter().zip(format1.pair_sets().iter()) {
+        let Ok(pair_set) = pair_set else { continue };
+        for record in pair_set.pair_value_records().iter().flatten() {
+            let right = record.second_glyph().to_u16();
+            let value = record.value_record1().x_advance().unwrap_or(0);
+            pairs.entry((left.to_u16(), right)).or_insert(value);
+        }
+    }
+    Ok(())
+}
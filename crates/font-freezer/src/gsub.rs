@@ -11,13 +11,30 @@ use crate::Result;
 
 /// A map of glyph substitutions extracted from GSUB lookups.
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
-pub struct GlyphSubstitutions(HashMap<u16, u16>);
+pub struct GlyphSubstitutions {
+    map: HashMap<u16, u16>,
+    alternate_index: usize,
+    short_alternate_sets: Vec<(u16, usize)>,
+}
 
 impl GlyphSubstitutions {
     pub fn new() -> Self {
         Self::default()
     }
 
+    /// Substitute the alternate at `index` (0 for the first) from alternate
+    /// sets, or the last one of sets that are shorter.
+    pub fn with_alternate_index(mut self, index: usize) -> Self {
+        self.alternate_index = index;
+        self
+    }
+
+    /// Glyphs whose alternate set had no alternate at the requested index,
+    /// with the number of alternates it had.
+    pub fn short_alternate_sets(&self) -> &[(u16, usize)] {
+        &self.short_alternate_sets
+    }
+
     pub fn process_lookups(&mut self, gsub: &Gsub, indices: &BTreeSet<u16>) -> Result<()> {
         let lookup_list = gsub.lookup_list()?;
         for &idx in indices {
@@ -42,9 +59,16 @@ impl GlyphSubstitutions {
                             continue;
                         };
                         let Ok(alt) = alt else { continue };
-                        if let Some(gid) = alt.alternate_glyph_ids().first() {
-                            self.apply(orig, gid.get().to_u32() as u16);
-                        }
+                        let alternates = alt.alternate_glyph_ids();
+                        let gid = match alternates.get(self.alternate_index) {
+                            Some(gid) => gid,
+                            None => {
+                                let Some(last) = alternates.last() else { continue };
+                                self.short_alternate_sets.push((orig, alternates.len()));
+                                last
+                            }
+                        };
+                        self.apply(orig, gid.get().to_u32() as u16);
                     }
                 }
             }
@@ -73,33 +97,68 @@ impl GlyphSubstitutions {
         Ok(())
     }
 
+    /// Fold in the substitutions of a feature listed after those already
+    /// applied.
+    ///
+    /// Glyphs substituted earlier continue through the later feature. Where
+    /// the later feature substitutes an already substituted source glyph
+    /// directly, and differently, it wins; those cases are returned as
+    /// `(glyph, replaced, substitute)`, sorted by glyph.
+    pub fn apply_later(&mut self, later: &GlyphSubstitutions) -> Vec<(u16, u16, u16)> {
+        let earlier = self.map.clone();
+        for (from, to) in &earlier {
+            if let Some(&chained) = later.map.get(to) {
+                self.map.insert(*from, chained);
+            }
+        }
+
+        let mut sources: Vec<(u16, u16)> = later.map.iter().map(|(&f, &t)| (f, t)).collect();
+        sources.sort_unstable();
+        let mut overrides = Vec::new();
+        for (from, to) in sources {
+            match earlier.get(&from) {
+                None => {
+                    self.map.insert(from, to);
+                }
+                Some(&replaced) if replaced != to && later.map.get(&replaced) != Some(&to) => {
+                    self.map.insert(from, to);
+                    overrides.push((from, replaced, to));
+                }
+                Some(_) => {}
+            }
+        }
+        self.short_alternate_sets
+            .extend_from_slice(&later.short_alternate_sets);
+        overrides
+    }
+
     pub fn apply(&mut self, from: u16, to: u16) {
         for k in self
-            .0
+            .map
             .iter()
             .filter(|&(_, v)| *v == from)
             .map(|(k, _)| *k)
             .collect::<Vec<_>>()
         {
-            self.0.insert(k, to);
+            self.map.insert(k, to);
         }
-        self.0.entry(from).or_insert(to);
+        self.map.entry(from).or_insert(to);
     }
 
     pub fn remap(&self, gid: u16) -> u16 {
-        self.0.get(&gid).copied().unwrap_or(gid)
+        self.map.get(&gid).copied().unwrap_or(gid)
     }
 
     pub fn is_empty(&self) -> bool {
-        self.0.is_empty()
+        self.map.is_empty()
     }
 
     pub fn len(&self) -> usize {
-        self.0.len()
+        self.map.len()
     }
 
     pub fn iter(&self) -> impl Iterator<Item = (&u16, &u16)> {
-        self.0.iter()
+        self.map.iter()
     }
 }
 
@@ -157,4 +216,19 @@ mod tests {
         assert_eq!(subs.remap(1), 3);
         assert_eq!(subs.remap(2), 3);
     }
+
+    #[test]
+    fn test_apply_later_feature() {
+        let mut subs = GlyphSubstitutions::new();
+        subs.apply(1, 2);
+        subs.apply(5, 6);
+        let mut later = GlyphSubstitutions::new();
+        later.apply(2, 3);
+        later.apply(5, 7);
+
+        assert_eq!(subs.apply_later(&later), [(5, 6, 7)]);
+        assert_eq!(subs.remap(1), 3);
+        assert_eq!(subs.remap(2), 3);
+        assert_eq!(subs.remap(5), 7);
+    }
 }
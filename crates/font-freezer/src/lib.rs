@@ -18,14 +18,17 @@
 
 mod error;
 mod font;
+mod gpos;
 mod gsub;
 mod types;
 
 pub use error::{Error, Result};
 pub use font::Font;
+pub use gpos::KernPairs;
 pub use gsub::GlyphSubstitutions;
 pub use types::{
-    FontReport, FreezeOptions, FreezeResult, FreezeStats, ScriptLangFilter, SuffixConfig,
+    FontReport, FreezeOptions, FreezeResult, FreezeStats, KernFreeze, KernPair, LangTag,
+    ScriptLangFilter, ScriptTag, SubstitutionOverride, SuffixConfig,
 };
 
 /// Generate a report of available scripts, languages, and features.
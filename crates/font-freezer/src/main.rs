@@ -8,7 +8,7 @@ use std::{
 };
 
 use clap::Parser;
-use font_feature_freezer::{FreezeOptions, FreezeResult, freeze, report};
+use font_feature_freezer::{FreezeOptions, FreezeResult, KernFreeze, freeze, report};
 
 #[derive(Debug, thiserror::Error)]
 enum CliError {
@@ -52,6 +52,9 @@ struct Cli {
     /// Custom suffix (implies --suffix)
     #[arg(short = 'U', long)]
     usesuffix: Option<String>,
+    /// Append the suffix to the end of the PostScript names (IDs 6 and 20)
+    #[arg(long)]
+    suffix_psname: bool,
     /// Search/replace in names: 'old/new,old2/new2,...'
     #[arg(short = 'R', long)]
     replacenames: Option<String>,
@@ -64,6 +67,18 @@ struct Cli {
     /// Output names of remapped glyphs
     #[arg(short, long)]
     names: bool,
+    /// Alternate to freeze from alternate-set lookups such as aalt, 0 for the first
+    #[arg(short = 'a', long, default_value_t = 0)]
+    alternate_index: usize,
+    /// Report the GPOS kerning pairs that would be frozen
+    #[arg(long)]
+    kern: bool,
+    /// Bake each glyph's most common kerning into its advance (implies --kern)
+    #[arg(long)]
+    bake_kern: bool,
+    /// Fail if a requested feature is not in the font for the script/language
+    #[arg(long)]
+    strict: bool,
     /// Verbose output
     #[arg(short, long)]
     verbose: bool,
@@ -110,6 +125,27 @@ impl Cli {
             for w in &result.warnings {
                 eprintln!("WARNING: {w}");
             }
+            if self.verbose {
+                for o in &result.stats.overrides {
+                    println!(
+                        "{} overrides gid{} -> gid{} with gid{}",
+                        o.feature, o.glyph, o.replaced, o.substitute
+                    );
+                }
+                for pair in &result.stats.kern_pairs {
+                    println!("kern {} {} {}", pair.left, pair.right, pair.x_advance);
+                }
+            }
+            if let Some(KernFreeze::Lossy { unmatched, uncovered, class_subtables }) = &result.kern
+            {
+                eprintln!(
+                    "WARNING: kerning frozen lossily: {} pairs differ from the baked advance, \
+                     {} glyphs lack kerning against some glyphs, {class_subtables} class \
+                     subtables skipped",
+                    unmatched.len(),
+                    uncovered.len()
+                );
+            }
             println!(
                 "{}: {}",
                 self.input.file_name().unwrap_or_default().to_string_lossy(),
@@ -127,8 +163,11 @@ impl Cli {
     }
 
     fn parse_features(&self) -> CliResult<Vec<String>> {
+        if self.features.is_none() && (self.kern || self.bake_kern) {
+            return Ok(Vec::new());
+        }
         let features = self.features.as_deref().ok_or_else(|| {
-            CliError::Message("--features is required (unless using --report)".into())
+            CliError::Message("--features is required (unless using --report or --kern)".into())
         })?;
         let parsed: Vec<String> = features
             .split(',')
@@ -150,10 +189,15 @@ impl Cli {
             .with_lang_opt(self.lang.as_deref())
             .with_suffix_if(self.suffix)
             .with_usesuffix_opt(self.usesuffix.as_deref())
+            .with_ps_name_suffix_if(self.suffix_psname)
             .with_replacenames_opt(self.replacenames.as_deref())
             .with_info_if(self.info)
             .with_zapnames_if(self.zapnames)
             .with_warnings_if(self.names)
+            .alternate_index(self.alternate_index)
+            .with_kern(self.kern)
+            .with_baked_kern(self.bake_kern)
+            .strict(self.strict)
     }
 
     fn process_file(
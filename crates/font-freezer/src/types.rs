@@ -13,11 +13,27 @@ pub struct FreezeOptions {
     pub features: Vec<String>,
     pub filter: ScriptLangFilter,
     pub suffix: SuffixConfig,
+    /// Append the suffix, without spaces, to the end of the PostScript name
+    /// (ID 6) and the PostScript CID findfont name (ID 20) instead of after
+    /// their family part
+    pub suffix_ps_name: bool,
     pub replacenames: Option<String>,
     pub info: bool,
     pub zapnames: bool,
     /// Generate warnings for glyphs without unicode (expensive for large fonts)
     pub warnings: bool,
+    /// Report GPOS `kern` pair adjustments in [`FreezeStats::kern_pairs`]
+    pub kern: bool,
+    /// Alternate substituted from AlternateSubst sets, 0 for the first; sets
+    /// without it fall back to their last alternate
+    pub alternate_index: usize,
+    /// Also bake each glyph's most common kerning adjustment into its `hmtx`
+    /// advance (implies `kern`)
+    pub bake_kern: bool,
+    /// Fail with [`Error::FeatureNotFound`](crate::Error::FeatureNotFound)
+    /// when a requested feature has no lookups under the selected
+    /// script/language, instead of warning
+    pub strict: bool,
 }
 
 /// Restricts feature application to a specific OpenType script/language.
@@ -111,6 +127,11 @@ impl FreezeOptions {
         self
     }
 
+    pub fn with_ps_name_suffix(mut self) -> Self {
+        self.suffix_ps_name = true;
+        self
+    }
+
     pub fn with_replacenames(mut self, r: impl Into<String>) -> Self {
         self.replacenames = Some(r.into());
         self
@@ -155,6 +176,11 @@ impl FreezeOptions {
         self
     }
 
+    pub fn with_ps_name_suffix_if(mut self, on: bool) -> Self {
+        self.suffix_ps_name |= on;
+        self
+    }
+
     pub fn with_info_if(mut self, on: bool) -> Self {
         self.info |= on;
         self
@@ -175,6 +201,40 @@ impl FreezeOptions {
         self
     }
 
+    /// Freeze the alternate at `index` (0 for the first) of alternate-set
+    /// lookups such as `aalt`, instead of the first. Sets with fewer
+    /// alternates use their last one, with a warning in
+    /// [`FreezeResult::warnings`].
+    pub fn alternate_index(mut self, index: usize) -> Self {
+        self.alternate_index = index;
+        self
+    }
+
+    /// Read GPOS `kern` pair adjustments for the selected script/language
+    /// and report the affected pairs, leaving `hmtx` as it is.
+    pub fn with_kern(mut self, on: bool) -> Self {
+        self.kern = on;
+        self
+    }
+
+    /// Read GPOS `kern` pair adjustments and bake the most common one of each
+    /// first glyph into its advance, for environments that ignore GPOS.
+    pub fn with_baked_kern(mut self, on: bool) -> Self {
+        self.bake_kern = on;
+        self
+    }
+
+    /// Fail on requested features the font does not have for the selected
+    /// script/language, rather than only warning about them.
+    pub fn strict(mut self, on: bool) -> Self {
+        self.strict = on;
+        self
+    }
+
+    pub fn wants_kern(&self) -> bool {
+        self.kern || self.bake_kern
+    }
+
     pub fn wants_name_edits(&self) -> bool {
         self.suffix.is_enabled() || self.replacenames.is_some() || self.info
     }
@@ -183,6 +243,16 @@ impl FreezeOptions {
         self.suffix.as_string(&self.features)
     }
 
+    /// Tags of the requested features in the order given, without repeats.
+    pub fn ordered_feature_tags(&self) -> Vec<Tag> {
+        let mut seen = HashSet::new();
+        self.features
+            .iter()
+            .filter_map(|f| f.as_bytes().try_into().ok().map(Tag::new))
+            .filter(|tag| seen.insert(*tag))
+            .collect()
+    }
+
     pub fn feature_tags(&self) -> HashSet<Tag> {
         self.features
             .iter()
@@ -198,14 +268,79 @@ pub struct FreezeResult {
     pub stats: FreezeStats,
     pub warnings: Vec<String>,
     pub remapped_names: Vec<String>,
+    /// How kerning was frozen; `None` unless requested with
+    /// [`FreezeOptions::with_kern`]
+    pub kern: Option<KernFreeze>,
 }
 
+/// A source glyph that two requested features substitute differently; the
+/// later-listed feature wins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SubstitutionOverride {
+    /// The later feature, whose substitution was kept
+    pub feature: Tag,
+    pub glyph: u16,
+    /// Substitute from the earlier features
+    pub replaced: u16,
+    pub substitute: u16,
+}
+
+/// A GPOS pair adjustment to the first glyph's advance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct KernPair {
+    pub left: u16,
+    pub right: u16,
+    pub x_advance: i16,
+}
+
+/// Outcome of freezing kerning into `hmtx`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KernFreeze {
+    /// The affected pairs were only reported; `hmtx` is unchanged.
+    Reported,
+    /// The baked advances reproduce every kerning adjustment exactly.
+    Lossless,
+    /// The baked advances only approximate the kerning.
+    Lossy {
+        /// Pairs whose adjustment differs from the one baked into their
+        /// first glyph
+        unmatched: Vec<KernPair>,
+        /// Baked glyphs that do not kern against every glyph, so their new
+        /// advance also applies where no kerning did
+        uncovered: Vec<u16>,
+        /// Class-based pair subtables (format 2), which are not read
+        class_subtables: usize,
+    },
+}
+
+impl KernFreeze {
+    pub fn is_lossless(&self) -> bool {
+        matches!(self, Self::Lossless)
+    }
+}
+
+/// An OpenType script tag (e.g. `latn`).
+pub type ScriptTag = Tag;
+
+/// An OpenType language system tag (e.g. `TRK `), or `dflt` for the default.
+pub type LangTag = Tag;
+
 /// Statistics about the freeze operation.
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct FreezeStats {
     pub features_requested: usize,
     pub lookups_processed: usize,
     pub substitutions_applied: usize,
+    /// Script/language systems in which at least one requested feature was found.
+    pub scripts_touched: Vec<(ScriptTag, LangTag)>,
+    /// Source glyphs whose substitution by an earlier feature was replaced
+    /// by a later one, in feature order.
+    pub overrides: Vec<SubstitutionOverride>,
+    /// GPOS `kern` pairs with a non-zero advance adjustment, when kerning
+    /// was requested.
+    pub kern_pairs: Vec<KernPair>,
+    /// Glyph advances changed by baking kerning, as `(glyph, adjustment)`.
+    pub kern_baked: Vec<(u16, i16)>,
 }
 
 impl Display for FreezeStats {
@@ -214,7 +349,16 @@ impl Display for FreezeStats {
             f,
             "frozen {} features, {} substitutions",
             self.features_requested, self.substitutions_applied
-        )
+        )?;
+        if !self.kern_pairs.is_empty() {
+            write!(
+                f,
+                ", {} kern pairs, {} advances baked",
+                self.kern_pairs.len(),
+                self.kern_baked.len()
+            )?;
+        }
+        Ok(())
     }
 }
 
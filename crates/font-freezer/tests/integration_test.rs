@@ -6,9 +6,13 @@
 use std::{collections::HashMap, string::ToString};
 
 use font_feature_freezer::{
-    FreezeOptions, freeze, freeze_features, freeze_features_with_stats, report,
+    Error, FreezeOptions, KernFreeze, SubstitutionOverride, freeze, freeze_features,
+    freeze_features_with_stats, report,
+};
+use read_fonts::{
+    FontRef, TableProvider,
+    types::{GlyphId, GlyphId16, Tag},
 };
-use read_fonts::{FontRef, TableProvider, types::GlyphId16};
 
 fn get_cmap(data: &[u8]) -> HashMap<u32, u16> {
     let font = FontRef::new(data).unwrap();
@@ -102,7 +106,8 @@ fn test_freeze_pnum_opensans() {
 fn test_freeze_multiple_features_opensans() {
     let font_data = include_bytes!("fixtures/OpenSans-Bold.subset.ttf");
 
-    let frozen = freeze_features(font_data, ["onum", "pnum"]).unwrap();
+    // pnum's one.pnum continues through onum to one.os
+    let frozen = freeze_features(font_data, ["pnum", "onum"]).unwrap();
 
     let names = cmap_to_names(&frozen);
 
@@ -110,6 +115,34 @@ fn test_freeze_multiple_features_opensans() {
     assert_eq!(names.get(&0x31), Some(&"one.os".to_string()));
 }
 
+#[test]
+fn test_freeze_later_feature_overrides_earlier() {
+    use read_fonts::types::Tag;
+
+    let font_data = include_bytes!("fixtures/OpenSans-Bold.subset.ttf");
+
+    // onum maps one to one.os and pnum maps it to one.pnum; pnum is listed
+    // last, so it wins
+    let (frozen, stats) = freeze_features_with_stats(font_data, ["onum", "pnum"]).unwrap();
+
+    let names = cmap_to_names(&frozen);
+    assert_eq!(names.get(&0x30), Some(&"zero.os".to_string()));
+    assert_eq!(names.get(&0x31), Some(&"one.pnum".to_string()));
+
+    assert_eq!(
+        stats.overrides,
+        [SubstitutionOverride {
+            feature: Tag::new(b"pnum"),
+            glyph: 2,
+            replaced: 16,
+            substitute: 14,
+        }]
+    );
+
+    let (_, stats) = freeze_features_with_stats(font_data, ["pnum", "onum"]).unwrap();
+    assert!(stats.overrides.is_empty());
+}
+
 #[test]
 fn test_freeze_ss01_alternate_substitution() {
     // Equivalent to pyftfeatfreeze test_warn_substituting_glyphs_without_unicode
@@ -123,6 +156,21 @@ fn test_freeze_ss01_alternate_substitution() {
     assert_eq!(names.get(&0x61), Some(&"a.alt1".to_string()));
 }
 
+#[test]
+fn test_freeze_chosen_alternate() {
+    let font_data = include_bytes!("fixtures/SubGlyphsWithoutUnicode.ttf");
+
+    let result = freeze(font_data, &FreezeOptions::new(["ss01"]).alternate_index(1)).unwrap();
+    assert_eq!(cmap_to_names(&result.data).get(&0x61), Some(&"a.alt2".to_string()));
+    assert!(result.warnings.is_empty());
+
+    // 'a' has two alternates, so the last one is used instead
+    let result = freeze(font_data, &FreezeOptions::new(["ss01"]).alternate_index(5)).unwrap();
+    assert_eq!(cmap_to_names(&result.data).get(&0x61), Some(&"a.alt2".to_string()));
+    assert_eq!(result.warnings.len(), 1);
+    assert!(result.warnings[0].contains("'a' has 2 alternates"), "{:?}", result.warnings);
+}
+
 #[test]
 fn test_freeze_nonexistent_feature() {
     // Equivalent to pyftfeatfreeze test_cant_open (tests error handling)
@@ -139,6 +187,20 @@ fn test_freeze_nonexistent_feature() {
     );
 }
 
+#[test]
+fn test_freeze_unresolved_feature_strict() {
+    let font_data = include_bytes!("fixtures/OpenSans-Bold.subset.ttf");
+
+    let options = FreezeOptions::new(["onum", "sso1"]);
+    let result = freeze(font_data, &options).unwrap();
+    assert_eq!(cmap_to_names(&result.data).get(&0x31), Some(&"one.os".to_string()));
+    assert_eq!(result.warnings.len(), 1);
+    assert!(result.warnings[0].contains("'sso1'"), "{:?}", result.warnings);
+
+    let err = freeze(font_data, &options.strict(true)).unwrap_err();
+    assert!(matches!(err, Error::FeatureNotFound(tag) if tag == Tag::new(b"sso1")), "{err}");
+}
+
 #[test]
 fn test_freeze_with_stats() {
     let font_data = include_bytes!("fixtures/OpenSans-Bold.subset.ttf");
@@ -220,6 +282,42 @@ fn test_freeze_with_usesuffix() {
     assert_eq!(ps_name, "OpenSansSC-Bold");
 }
 
+#[test]
+fn test_freeze_with_ps_name_suffix() {
+    use write_fonts::{
+        FontBuilder,
+        from_obj::ToOwnedTable,
+        tables::name::{Name, NameRecord},
+        types::NameId,
+    };
+
+    // Give the fixture a CID findfont name to suffix alongside the PostScript name
+    let font = FontRef::new(include_bytes!("fixtures/OpenSans-Bold.subset.ttf")).unwrap();
+    let mut name: Name = font.name().unwrap().to_owned_table();
+    name.name_record.push(NameRecord::new(
+        3,
+        1,
+        0x409,
+        NameId::new(20),
+        "OpenSans-Bold".to_string().into(),
+    ));
+    let mut builder = FontBuilder::new();
+    builder.add_table(&name).unwrap();
+    builder.copy_missing_tables(font);
+    let font_data = builder.build();
+
+    let options = FreezeOptions::new(["onum"])
+        .with_usesuffix("Small Caps")
+        .with_ps_name_suffix();
+    let result = freeze(&font_data, &options).unwrap();
+
+    let ps_name = get_name_record(&result.data, 6).unwrap();
+    assert_eq!(ps_name, "OpenSans-BoldSmallCaps");
+    assert!(!ps_name.contains(' '));
+    assert_eq!(get_name_record(&result.data, 20).unwrap(), ps_name);
+    assert_eq!(get_name_record(&result.data, 1).unwrap(), "Open Sans Small Caps");
+}
+
 #[test]
 fn test_freeze_with_replacenames() {
     let font_data = include_bytes!("fixtures/OpenSans-Bold.subset.ttf");
@@ -275,6 +373,164 @@ fn test_freeze_script_filter_no_match() {
     );
 }
 
+#[test]
+fn test_freeze_reports_scripts_touched() {
+    use read_fonts::types::Tag;
+    use write_fonts::{
+        FontBuilder,
+        from_obj::ToOwnedTable,
+        tables::{
+            gsub::Gsub,
+            layout::{LangSys, LangSysRecord, Script, ScriptRecord},
+        },
+    };
+
+    // Copy `latn` to `cyrl` (with an extra `SRB ` language) and add a `grek`
+    // script that does not reference any features.
+    let font_data = include_bytes!("fixtures/OpenSans-Bold.subset.ttf");
+    let font = FontRef::new(font_data).unwrap();
+    let mut gsub: Gsub = font.gsub().unwrap().to_owned_table();
+    let latn = gsub.script_list.script_records[0].script.clone();
+    let mut cyrl = (*latn).clone();
+    cyrl.lang_sys_records
+        .push(LangSysRecord::new(Tag::new(b"SRB "), LangSys::new(vec![0, 1, 2, 3])));
+    let grek = Script::new(Some(LangSys::new(vec![])), vec![]);
+    gsub.script_list
+        .script_records
+        .insert(0, ScriptRecord::new(Tag::new(b"cyrl"), cyrl));
+    gsub.script_list
+        .script_records
+        .insert(1, ScriptRecord::new(Tag::new(b"grek"), grek));
+
+    let mut builder = FontBuilder::new();
+    builder.add_table(&gsub).unwrap();
+    builder.copy_missing_tables(font);
+    let multi_script = builder.build();
+
+    let (_, stats) = freeze_features_with_stats(&multi_script, ["onum"]).unwrap();
+
+    let dflt = Tag::new(b"dflt");
+    assert_eq!(
+        stats.scripts_touched,
+        vec![
+            (Tag::new(b"cyrl"), dflt),
+            (Tag::new(b"cyrl"), Tag::new(b"SRB ")),
+            (Tag::new(b"latn"), dflt),
+        ]
+    );
+}
+
+/// OpenSans with a GPOS `kern` feature for `latn`: `zero` kerns -40
+/// against `a` and `b` and -20 against `c`, and `a` kerns +10 against itself.
+fn opensans_with_kern() -> Vec<u8> {
+    use read_fonts::types::Tag;
+    use write_fonts::{
+        FontBuilder,
+        tables::{
+            gpos::{
+                Gpos, PairPos, PairSet, PairValueRecord, PositionLookup, PositionLookupList,
+                ValueRecord,
+            },
+            layout::{
+                Feature, FeatureList, FeatureRecord, LangSys, Lookup, LookupFlag, Script,
+                ScriptList, ScriptRecord, builders::CoverageTableBuilder,
+            },
+        },
+        types::GlyphId16,
+    };
+
+    let pair = |right: u16, x_advance: i16| {
+        PairValueRecord::new(
+            GlyphId16::new(right),
+            ValueRecord::new().with_x_advance(x_advance),
+            ValueRecord::new(),
+        )
+    };
+    let coverage = CoverageTableBuilder::from_glyphs(vec![GlyphId16::new(1), GlyphId16::new(11)]);
+    let pair_pos = PairPos::format_1(
+        coverage.build(),
+        vec![
+            PairSet::new(vec![pair(11, -40), pair(12, -40), pair(13, -20)]),
+            PairSet::new(vec![pair(11, 10)]),
+        ],
+    );
+    let lookup = PositionLookup::Pair(Lookup::new(LookupFlag::empty(), vec![pair_pos]));
+    let gpos = Gpos::new(
+        ScriptList::new(vec![ScriptRecord::new(
+            Tag::new(b"latn"),
+            Script::new(Some(LangSys::new(vec![0])), vec![]),
+        )]),
+        FeatureList::new(vec![FeatureRecord::new(Tag::new(b"kern"), Feature::new(None, vec![0]))]),
+        PositionLookupList::new(vec![lookup]),
+    );
+
+    let font = FontRef::new(include_bytes!("fixtures/OpenSans-Bold.subset.ttf")).unwrap();
+    let mut builder = FontBuilder::new();
+    builder.add_table(&gpos).unwrap();
+    builder.copy_missing_tables(font);
+    builder.build()
+}
+
+#[test]
+fn test_freeze_reports_kern_pairs() {
+    let font_data = opensans_with_kern();
+    let result = freeze(&font_data, &FreezeOptions::new(["onum"]).with_kern(true)).unwrap();
+
+    let pairs: Vec<(u16, u16, i16)> = result
+        .stats
+        .kern_pairs
+        .iter()
+        .map(|pair| (pair.left, pair.right, pair.x_advance))
+        .collect();
+    assert_eq!(pairs, [(1, 11, -40), (1, 12, -40), (1, 13, -20), (11, 11, 10)]);
+    assert!(result.stats.kern_baked.is_empty());
+    assert_eq!(result.kern, Some(KernFreeze::Reported));
+
+    let hmtx = |data: &[u8]| FontRef::new(data).unwrap().hmtx().unwrap().advance(GlyphId::new(1));
+    assert_eq!(hmtx(&result.data), hmtx(&font_data));
+
+    // A script without kerning reports no pairs
+    let options = FreezeOptions::new(Vec::<String>::new())
+        .with_kern(true)
+        .with_script("cyrl");
+    let result = freeze(&font_data, &options).unwrap();
+    assert!(result.stats.kern_pairs.is_empty());
+}
+
+#[test]
+fn test_freeze_bakes_kern_lossily() {
+    let font_data = opensans_with_kern();
+    let result =
+        freeze(&font_data, &FreezeOptions::new(Vec::<String>::new()).with_baked_kern(true))
+            .unwrap();
+
+    assert_eq!(result.stats.kern_baked, [(1, -40), (11, 10)]);
+    let advance = |data: &[u8], gid: u32| {
+        FontRef::new(data)
+            .unwrap()
+            .hmtx()
+            .unwrap()
+            .advance(GlyphId::new(gid))
+            .unwrap()
+    };
+    assert_eq!(advance(&result.data, 1), advance(&font_data, 1) - 40);
+    assert_eq!(advance(&result.data, 11), advance(&font_data, 11) + 10);
+    assert_eq!(advance(&result.data, 12), advance(&font_data, 12));
+    // Kerning alone leaves the cmap alone
+    assert_eq!(get_cmap(&result.data), get_cmap(&font_data));
+
+    let Some(KernFreeze::Lossy { unmatched, uncovered, class_subtables }) = result.kern else {
+        panic!("expected lossy kerning, got {:?}", result.kern);
+    };
+    let unmatched: Vec<(u16, u16, i16)> = unmatched
+        .iter()
+        .map(|pair| (pair.left, pair.right, pair.x_advance))
+        .collect();
+    assert_eq!(unmatched, [(1, 13, -20)]);
+    assert_eq!(uncovered, [1, 11]);
+    assert_eq!(class_subtables, 0);
+}
+
 // ============================================================================
 // Tests for report functionality
 // ============================================================================
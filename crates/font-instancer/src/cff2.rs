@@ -0,0 +1,571 @@
+//! Static `CFF ` tables from CFF2 outlines.
+//!
+//! Each charstring is evaluated at the instance's normalized coordinates,
+//! which resolves `blend` operators and inlines subroutines, and written back
+//! as a Type 2 charstring from rounded absolute coordinates. Stem hints and
+//! hint/counter masks are kept unless dropped; widths come from the
+//! instanced `hmtx`.
+//! Private DICTs are copied with their blends resolved.
+//!
+//! A CFF2 table with one Font DICT becomes a name-keyed `CFF ` table named
+//! from `post`; one with several becomes CID-keyed, with an identity charset
+//! and the Font DICTs kept as its FDArray.
+
+use std::collections::{HashMap, HashSet};
+
+use kurbo::{BezPath, Shape};
+use read_fonts::{
+    FontRef, TableProvider,
+    ps::{
+        cff::{CffFontRef, blend::BlendState, index::Index},
+        cs::CommandSink,
+        string::STANDARD_STRINGS,
+    },
+    types::{F2Dot14, Fixed, GlyphId, GlyphId16, Tag},
+};
+use skrifa::{MetadataProvider, string::StringId};
+use warpnine_font_ops::cff::{
+    self, encode_charstring_int, push_numbers, push_offsets, write_index,
+};
+use write_fonts::tables::glyf::Bbox;
+
+use crate::error::{Error, Result};
+
+pub(crate) const CFF2: Tag = Tag::new(b"CFF2");
+pub(crate) const CFF: Tag = Tag::new(b"CFF ");
+
+/// Type 2 charstrings hold at most 48 operands
+const MAX_OPERANDS: usize = 48;
+
+/// Stems per hint operator, leaving room for the width
+const MAX_STEMS_PER_OP: usize = (MAX_OPERANDS - 1) / 2;
+
+/// DICT operators
+const FONT_BBOX: u16 = 5;
+const CHARSET: u16 = 15;
+const CHARSTRINGS: u16 = 17;
+const PRIVATE: u16 = 18;
+const SUBRS: u16 = 19;
+const VSINDEX: u16 = 22;
+const BLEND: u16 = 23;
+const FONT_MATRIX: u16 = 12 << 8 | 7;
+const ROS: u16 = 12 << 8 | 30;
+const CID_COUNT: u16 = 12 << 8 | 34;
+const FD_ARRAY: u16 = 12 << 8 | 36;
+const FD_SELECT: u16 = 12 << 8 | 37;
+
+/// A CFF2 table instanced to CFF
+pub(crate) struct CffInstance {
+    /// The `CFF ` table
+    pub data: Vec<u8>,
+    /// Bounds of each glyph's outline, `None` for glyphs without one
+    pub bboxes: Vec<Option<Bbox>>,
+}
+
+/// Instance the font's `CFF2` table at `coords`, writing `advances` as the
+/// charstring widths and leaving out hints with `drop_hints`.
+pub(crate) fn instance_cff2(
+    font: &FontRef,
+    coords: &[F2Dot14],
+    advances: &[u16],
+    drop_hints: bool,
+) -> Result<CffInstance> {
+    let data = font.table_data(CFF2).ok_or(Error::NoCff2Support)?;
+    let data = data.as_bytes();
+    let cff = CffFontRef::new_cff2(data, Some(i32::from(font.head()?.units_per_em())))?;
+    let blend = || {
+        cff.var_store()
+            .map(|store| BlendState::new(store.clone(), coords, 0))
+            .transpose()
+    };
+
+    let top_dict = read_dict(font.cff2()?.top_dict_data(), None)?;
+    let font_matrix = dict_operands(&top_dict, FONT_MATRIX).map(<[f64]>::to_vec);
+    let fd_array_offset = dict_operands(&top_dict, FD_ARRAY)
+        .and_then(|operands| operands.first())
+        .ok_or(Error::Cff(read_fonts::ps::error::Error::MissingFdArray))?;
+    let fd_array = Index::new(dict_slice(data, *fd_array_offset as usize..data.len())?, true)?;
+
+    let mut private_dicts = Vec::with_capacity(fd_array.count() as usize);
+    let mut fd_matrices = Vec::with_capacity(fd_array.count() as usize);
+    for i in 0..fd_array.count() as usize {
+        let font_dict = read_dict(fd_array.get(i)?, None)?;
+        fd_matrices.push(dict_operands(&font_dict, FONT_MATRIX).map(<[f64]>::to_vec));
+        let private = match dict_operands(&font_dict, PRIVATE) {
+            Some(&[size, offset]) => {
+                let range = offset as usize..(offset + size) as usize;
+                read_dict(dict_slice(data, range)?, blend()?.as_mut())?
+            }
+            _ => Vec::new(),
+        };
+        private_dicts.push(write_dict(private.iter().filter(|(op, _)| *op != SUBRS)));
+    }
+
+    let num_glyphs = cff.num_glyphs();
+    let mut subfonts = HashMap::new();
+    let mut fd_indices = Vec::with_capacity(num_glyphs as usize);
+    let mut charstrings = Vec::with_capacity(num_glyphs as usize);
+    let mut bboxes = Vec::with_capacity(num_glyphs as usize);
+    for glyph_id in 0..num_glyphs {
+        let gid = GlyphId::new(glyph_id);
+        let fd = cff.subfont_index(gid).unwrap_or(0);
+        if let std::collections::hash_map::Entry::Vacant(e) = subfonts.entry(fd) {
+            e.insert(cff.subfont(fd, coords)?);
+        }
+        let mut recorder = CharstringRecorder::default();
+        cff.evaluate_charstring(&subfonts[&fd], gid, coords, &mut recorder)?;
+        let width = advances.get(glyph_id as usize).copied().unwrap_or(0);
+        charstrings.push(recorder.encode(i32::from(width), drop_hints));
+        bboxes.push(recorder.bbox());
+        fd_indices.push(fd);
+    }
+
+    let font_bbox = bboxes
+        .iter()
+        .flatten()
+        .fold(None, |acc: Option<Bbox>, bbox| Some(acc.map_or(*bbox, |acc| acc.union(*bbox))));
+    let font_bbox =
+        font_bbox.map_or([0.0; 4], |b| [b.x_min, b.y_min, b.x_max, b.y_max].map(f64::from));
+    let name = font
+        .localized_strings(StringId::POSTSCRIPT_NAME)
+        .english_or_first()
+        .map_or_else(|| "Instance".to_string(), |name| name.chars().collect());
+
+    let layout = CffLayout {
+        name,
+        font_matrix,
+        font_bbox,
+        charstrings,
+        private_dicts,
+    };
+    let data = if layout.private_dicts.len() == 1 {
+        layout.build_name_keyed(&glyph_names(font, num_glyphs))
+    } else {
+        layout.build_cid_keyed(&fd_indices, &fd_matrices)
+    };
+    Ok(CffInstance { data, bboxes })
+}
+
+/// Glyph names from `post`, or `glyphNNNNN` where it has none
+fn glyph_names(font: &FontRef, num_glyphs: u32) -> Vec<String> {
+    let post = font.post().ok();
+    let mut seen = HashSet::new();
+    (0..num_glyphs)
+        .map(|gid| {
+            let name = post
+                .as_ref()
+                .and_then(|post| post.glyph_name(GlyphId16::new(gid as u16)))
+                .filter(|name| !name.is_empty() && !seen.contains(*name))
+                .map(str::to_string);
+            let name = match (gid, name) {
+                (0, _) => ".notdef".to_string(),
+                (_, Some(name)) => name,
+                (_, None) => format!("glyph{gid:05}"),
+            };
+            seen.insert(name.clone());
+            name
+        })
+        .collect()
+}
+
+/// Path and hint commands of one evaluated charstring
+#[derive(Default)]
+struct CharstringRecorder {
+    /// Stem edges as absolute (lower, upper) pairs
+    hstems: Vec<(Fixed, Fixed)>,
+    vstems: Vec<(Fixed, Fixed)>,
+    commands: Vec<Command>,
+}
+
+enum Command {
+    MoveTo(Fixed, Fixed),
+    LineTo(Fixed, Fixed),
+    CurveTo([Fixed; 6]),
+    HintMask(Vec<u8>),
+    CounterMask(Vec<u8>),
+}
+
+impl CommandSink for CharstringRecorder {
+    fn move_to(&mut self, x: Fixed, y: Fixed) {
+        self.commands.push(Command::MoveTo(x, y));
+    }
+
+    fn line_to(&mut self, x: Fixed, y: Fixed) {
+        self.commands.push(Command::LineTo(x, y));
+    }
+
+    fn curve_to(&mut self, cx0: Fixed, cy0: Fixed, cx1: Fixed, cy1: Fixed, x: Fixed, y: Fixed) {
+        self.commands.push(Command::CurveTo([cx0, cy0, cx1, cy1, x, y]));
+    }
+
+    // Every contour is closed implicitly in CFF
+    fn close(&mut self) {}
+
+    fn hstem(&mut self, y: Fixed, dy: Fixed) {
+        self.hstems.push((y, dy));
+    }
+
+    fn vstem(&mut self, x: Fixed, dx: Fixed) {
+        self.vstems.push((x, dx));
+    }
+
+    fn hint_mask(&mut self, mask: &[u8]) {
+        self.commands.push(Command::HintMask(mask.to_vec()));
+    }
+
+    fn counter_mask(&mut self, mask: &[u8]) {
+        self.commands.push(Command::CounterMask(mask.to_vec()));
+    }
+}
+
+fn round(value: Fixed) -> i32 {
+    value.to_f64().round() as i32
+}
+
+impl CharstringRecorder {
+    /// Type 2 charstring for the recorded commands, starting with `width`
+    /// (relative to a `nominalWidthX` of 0). With `drop_hints`, stems and
+    /// hint/counter masks are left out.
+    fn encode(&self, width: i32, drop_hints: bool) -> Vec<u8> {
+        let mut writer = CharstringWriter { out: Vec::new(), operands: vec![width] };
+
+        let has_masks = self
+            .commands
+            .iter()
+            .any(|command| matches!(command, Command::HintMask(_) | Command::CounterMask(_)));
+        let (hstem, vstem) = if has_masks { (18, 23) } else { (1, 3) };
+        let stems: [(&[(Fixed, Fixed)], u8); 2] = if drop_hints {
+            [(&[], hstem), (&[], vstem)]
+        } else {
+            [(&self.hstems, hstem), (&self.vstems, vstem)]
+        };
+        for (stems, op) in stems {
+            for chunk in stems.chunks(MAX_STEMS_PER_OP) {
+                let mut edge = 0;
+                for &(lower, upper) in chunk {
+                    let (lower, upper) = (round(lower), round(upper));
+                    writer.operands.extend([lower - edge, upper - lower]);
+                    edge = upper;
+                }
+                writer.emit(&[op]);
+            }
+        }
+
+        let mut current = (0, 0);
+        let mut pending: Option<u8> = None;
+        for command in &self.commands {
+            let (op, points): (u8, &[Fixed]) = match command {
+                Command::MoveTo(x, y) => (21, &[*x, *y]),
+                Command::LineTo(x, y) => (5, &[*x, *y]),
+                Command::CurveTo(points) => (8, points),
+                Command::HintMask(_) | Command::CounterMask(_) if drop_hints => continue,
+                Command::HintMask(mask) | Command::CounterMask(mask) => {
+                    if let Some(op) = pending.take() {
+                        writer.emit(&[op]);
+                    }
+                    let op = if matches!(command, Command::HintMask(_)) { 19 } else { 20 };
+                    writer.emit(&[op]);
+                    writer.out.extend_from_slice(mask);
+                    continue;
+                }
+            };
+            // Consecutive lines and curves share one operator
+            if (pending
+                .is_some_and(|p| p != op || writer.operands.len() + points.len() > MAX_OPERANDS)
+                || op == 21)
+                && let Some(op) = pending.take()
+            {
+                writer.emit(&[op]);
+            }
+            for xy in points.chunks(2) {
+                let point = (round(xy[0]), round(xy[1]));
+                writer.operands.extend([point.0 - current.0, point.1 - current.1]);
+                current = point;
+            }
+            if op == 21 {
+                writer.emit(&[op]);
+            } else {
+                pending = Some(op);
+            }
+        }
+        if let Some(op) = pending {
+            writer.emit(&[op]);
+        }
+        writer.emit(&[14]);
+        writer.out
+    }
+
+    /// Bounds of the outline after rounding, as written by [`Self::encode`]
+    fn bbox(&self) -> Option<Bbox> {
+        let point = |x: Fixed, y: Fixed| (f64::from(round(x)), f64::from(round(y)));
+        let mut path = BezPath::new();
+        for command in &self.commands {
+            match command {
+                Command::MoveTo(x, y) => path.move_to(point(*x, *y)),
+                Command::LineTo(x, y) => path.line_to(point(*x, *y)),
+                Command::CurveTo([x0, y0, x1, y1, x, y]) => {
+                    path.curve_to(point(*x0, *y0), point(*x1, *y1), point(*x, *y));
+                }
+                Command::HintMask(_) | Command::CounterMask(_) => {}
+            }
+        }
+        if path.elements().is_empty() {
+            return None;
+        }
+        let rect = path.bounding_box();
+        Some(Bbox {
+            x_min: rect.x0.floor() as i16,
+            y_min: rect.y0.floor() as i16,
+            x_max: rect.x1.ceil() as i16,
+            y_max: rect.y1.ceil() as i16,
+        })
+    }
+}
+
+/// Accumulates operands and writes them out with their operator
+struct CharstringWriter {
+    out: Vec<u8>,
+    operands: Vec<i32>,
+}
+
+impl CharstringWriter {
+    fn emit(&mut self, op: &[u8]) {
+        for value in self.operands.drain(..) {
+            encode_charstring_int(&mut self.out, value);
+        }
+        self.out.extend_from_slice(op);
+    }
+}
+
+/// Parts of the output `CFF ` table shared by both keyings
+struct CffLayout {
+    /// PostScript name for the Name INDEX
+    name: String,
+    font_matrix: Option<Vec<f64>>,
+    font_bbox: [f64; 4],
+    charstrings: Vec<Vec<u8>>,
+    /// Encoded Private DICT of each Font DICT
+    private_dicts: Vec<Vec<u8>>,
+}
+
+impl CffLayout {
+    /// A name-keyed table with `glyph_names` in its charset.
+    fn build_name_keyed(&self, glyph_names: &[String]) -> Vec<u8> {
+        let mut strings = Strings::default();
+        let mut charset = vec![0u8];
+        for name in glyph_names.iter().skip(1) {
+            charset.extend_from_slice(&strings.sid(name).to_be_bytes());
+        }
+        let private_dict = &self.private_dicts[0];
+
+        // Offsets are written as 5-byte integers, so the Top DICT's size
+        // does not depend on their values
+        let top_dict_with = |charset_offset: usize, charstrings_offset: usize, private_offset| {
+            let mut dict = self.top_dict_prefix();
+            push_offsets(&mut dict, CHARSET, &[charset_offset]);
+            push_offsets(&mut dict, CHARSTRINGS, &[charstrings_offset]);
+            push_offsets(&mut dict, PRIVATE, &[private_dict.len(), private_offset]);
+            dict
+        };
+        let charstring_index = write_index(&self.charstrings);
+        let front = self.front(&top_dict_with(0, 0, 0), &strings);
+        let charset_offset = front.len();
+        let charstrings_offset = charset_offset + charset.len();
+        let private_offset = charstrings_offset + charstring_index.len();
+        let top_dict = top_dict_with(charset_offset, charstrings_offset, private_offset);
+
+        [self.front(&top_dict, &strings), charset, charstring_index, private_dict.clone()].concat()
+    }
+
+    /// A CID-keyed table with an identity charset, one Font DICT per
+    /// Private DICT and `fd_indices` as its FDSelect.
+    fn build_cid_keyed(&self, fd_indices: &[u16], fd_matrices: &[Option<Vec<f64>>]) -> Vec<u8> {
+        let mut strings = Strings::default();
+        let ros = [strings.sid("Adobe"), strings.sid("Identity")].map(f64::from);
+        let num_glyphs = self.charstrings.len();
+
+        let charset = match num_glyphs {
+            0 | 1 => vec![0u8],
+            n => [&[2u8, 0, 1][..], &((n - 2) as u16).to_be_bytes()].concat(),
+        };
+        let mut fd_select = vec![3u8];
+        let mut ranges: Vec<(usize, u16)> = Vec::new();
+        for (gid, &fd) in fd_indices.iter().enumerate() {
+            if ranges.last().is_none_or(|&(_, last)| last != fd) {
+                ranges.push((gid, fd));
+            }
+        }
+        fd_select.extend_from_slice(&(ranges.len() as u16).to_be_bytes());
+        for (first, fd) in ranges {
+            fd_select.extend_from_slice(&(first as u16).to_be_bytes());
+            fd_select.push(fd as u8);
+        }
+        fd_select.extend_from_slice(&(num_glyphs as u16).to_be_bytes());
+
+        let font_dicts_with = |private_offset: usize| {
+            let mut offset = private_offset;
+            let dicts: Vec<Vec<u8>> = self
+                .private_dicts
+                .iter()
+                .zip(fd_matrices)
+                .map(|(private, matrix)| {
+                    let mut dict = Vec::new();
+                    if let Some(matrix) = matrix {
+                        push_numbers(&mut dict, FONT_MATRIX, matrix);
+                    }
+                    push_offsets(&mut dict, PRIVATE, &[private.len(), offset]);
+                    offset += private.len();
+                    dict
+                })
+                .collect();
+            write_index(&dicts)
+        };
+        let top_dict_with = |offsets: [usize; 4]| {
+            let [charset_offset, fd_select_offset, charstrings_offset, fd_array_offset] = offsets;
+            let mut dict = Vec::new();
+            push_numbers(&mut dict, ROS, &[ros[0], ros[1], 0.0]);
+            dict.extend(self.top_dict_prefix());
+            push_numbers(&mut dict, CID_COUNT, &[num_glyphs as f64]);
+            push_offsets(&mut dict, CHARSET, &[charset_offset]);
+            push_offsets(&mut dict, FD_SELECT, &[fd_select_offset]);
+            push_offsets(&mut dict, CHARSTRINGS, &[charstrings_offset]);
+            push_offsets(&mut dict, FD_ARRAY, &[fd_array_offset]);
+            dict
+        };
+        let charstring_index = write_index(&self.charstrings);
+        let fd_array_len = font_dicts_with(0).len();
+        let charset_offset = self.front(&top_dict_with([0; 4]), &strings).len();
+        let fd_select_offset = charset_offset + charset.len();
+        let charstrings_offset = fd_select_offset + fd_select.len();
+        let fd_array_offset = charstrings_offset + charstring_index.len();
+        let private_offset = fd_array_offset + fd_array_len;
+        let top_dict =
+            top_dict_with([charset_offset, fd_select_offset, charstrings_offset, fd_array_offset]);
+
+        [
+            self.front(&top_dict, &strings),
+            charset,
+            fd_select,
+            charstring_index,
+            font_dicts_with(private_offset),
+            self.private_dicts.concat(),
+        ]
+        .concat()
+    }
+
+    /// FontMatrix and FontBBox, the Top DICT entries common to both keyings
+    fn top_dict_prefix(&self) -> Vec<u8> {
+        let mut dict = Vec::new();
+        if let Some(matrix) = &self.font_matrix {
+            push_numbers(&mut dict, FONT_MATRIX, matrix);
+        }
+        push_numbers(&mut dict, FONT_BBOX, &self.font_bbox);
+        dict
+    }
+
+    /// Header, Name, Top DICT, String and (empty) Global Subr INDEXes
+    fn front(&self, top_dict: &[u8], strings: &Strings) -> Vec<u8> {
+        [
+            vec![1u8, 0, 4, 4],
+            write_index(&[self.name.as_bytes()]),
+            write_index(&[top_dict]),
+            write_index(&strings.strings),
+            write_index::<&[u8]>(&[]),
+        ]
+        .concat()
+    }
+}
+
+/// Custom strings of a String INDEX, after the standard strings
+#[derive(Default)]
+struct Strings {
+    strings: Vec<Vec<u8>>,
+    sids: HashMap<String, u16>,
+}
+
+impl Strings {
+    fn sid(&mut self, string: &str) -> u16 {
+        if let Some(sid) = STANDARD_STRINGS.iter().position(|s| *s == string) {
+            return sid as u16;
+        }
+        let next = (STANDARD_STRINGS.len() + self.strings.len()) as u16;
+        *self.sids.entry(string.to_string()).or_insert_with(|| {
+            self.strings.push(string.as_bytes().to_vec());
+            next
+        })
+    }
+}
+
+/// Bounds-checked sub-slice of the CFF2 table
+fn dict_slice(data: &[u8], range: std::ops::Range<usize>) -> Result<&[u8]> {
+    data.get(range)
+        .ok_or_else(|| Error::InvalidFont("CFF2 offset out of bounds".to_string()))
+}
+
+/// Operands of the first `op` entry
+fn dict_operands(dict: &[(u16, Vec<f64>)], op: u16) -> Option<&[f64]> {
+    dict.iter()
+        .find(|(o, _)| *o == op)
+        .map(|(_, operands)| operands.as_slice())
+}
+
+/// DICT entries as (operator, operands), with `blend` applied and `vsindex`
+/// consumed. Escaped operators are `12 << 8 | b1`.
+fn read_dict(data: &[u8], mut blend: Option<&mut BlendState>) -> Result<Vec<(u16, Vec<f64>)>> {
+    let invalid = || Error::InvalidFont("malformed CFF2 DICT".to_string());
+    let mut entries = Vec::new();
+    let mut stack: Vec<f64> = Vec::new();
+    for (op, operands) in cff::dict_entries(data).ok_or_else(invalid)? {
+        stack.extend(cff::dict_numbers(operands).ok_or_else(invalid)?);
+        match op {
+            VSINDEX => {
+                let state = blend.as_deref_mut().ok_or_else(invalid)?;
+                state.set_store_index(stack.pop().ok_or_else(invalid)? as u16)?;
+            }
+            BLEND => {
+                let state = blend.as_deref_mut().ok_or_else(invalid)?;
+                let scalars: Vec<f64> = state
+                    .scalars()?
+                    .map(|scalar| scalar.map(Fixed::to_f64))
+                    .collect::<std::result::Result<_, _>>()?;
+                let count = stack.pop().ok_or_else(invalid)? as usize;
+                let start = stack
+                    .len()
+                    .checked_sub(count * (scalars.len() + 1))
+                    .ok_or_else(invalid)?;
+                let deltas = stack.split_off(start + count);
+                for (j, value) in stack[start..].iter_mut().enumerate() {
+                    let row = &deltas[j * scalars.len()..(j + 1) * scalars.len()];
+                    *value += row.iter().zip(&scalars).map(|(d, s)| d * s).sum::<f64>();
+                }
+            }
+            _ => entries.push((op, std::mem::take(&mut stack))),
+        }
+    }
+    Ok(entries)
+}
+
+/// Encode DICT entries for a CFF table
+fn write_dict<'a>(entries: impl Iterator<Item = &'a (u16, Vec<f64>)>) -> Vec<u8> {
+    let mut out = Vec::new();
+    for (op, operands) in entries {
+        push_numbers(&mut out, *op, operands);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dict_numbers_round_trip() {
+        let operands = vec![0.0, -107.0, 1131.0, -1131.0, 32767.0, 100_000.0, 0.039625, -0.5];
+        let mut dict = Vec::new();
+        push_numbers(&mut dict, FONT_MATRIX, &operands);
+        push_offsets(&mut dict, PRIVATE, &[12, 345]);
+
+        let entries = read_dict(&dict, None).unwrap();
+        assert_eq!(entries, vec![(FONT_MATRIX, operands), (PRIVATE, vec![12.0, 345.0])]);
+    }
+}
@@ -0,0 +1,109 @@
+//! TrueType Collection (`.ttc`) support.
+
+use read_fonts::FontRef;
+use write_fonts::FontBuilder;
+
+use crate::{AxisLocation, error::Result, instancer::instantiate};
+
+/// Extract a single face from a TrueType Collection as a standalone font.
+///
+/// A plain (non-collection) font is accepted when `face_index` is 0.
+///
+/// # Errors
+///
+/// - `Error::Parse` if the data is not a font or the index is out of range
+pub fn extract_from_collection(data: &[u8], face_index: usize) -> Result<Vec<u8>> {
+    let font = FontRef::from_index(data, face_index as u32)?;
+
+    let mut builder = FontBuilder::new();
+    for record in font.table_directory.table_records() {
+        if let Some(table_data) = font.table_data(record.tag()) {
+            builder.add_raw(record.tag(), table_data);
+        }
+    }
+    Ok(builder.build())
+}
+
+/// Instantiate a variable font stored in a TrueType Collection.
+///
+/// Extracts the face at `face_index` with [`extract_from_collection`], then
+/// instances it with [`instantiate`].
+pub fn instantiate_from_collection(
+    data: &[u8],
+    face_index: usize,
+    locations: &[AxisLocation],
+) -> Result<Vec<u8>> {
+    instantiate(&extract_from_collection(data, face_index)?, locations)
+}
+
+#[cfg(test)]
+mod tests {
+    use font_test_data::{SIMPLE_GLYF, VAZIRMATN_VAR};
+    use read_fonts::{ReadError, TableProvider};
+
+    use super::*;
+    use crate::Error;
+
+    /// Pack fonts into a version 1.0 TTC, rebasing each face's table offsets.
+    fn make_ttc(fonts: &[&[u8]]) -> Vec<u8> {
+        let header_len = 12 + 4 * fonts.len();
+        let mut offsets = Vec::new();
+        let mut body = Vec::new();
+        for font in fonts {
+            let base = header_len + body.len();
+            offsets.push(base as u32);
+            let mut face = font.to_vec();
+            let num_tables = u16::from_be_bytes([face[4], face[5]]) as usize;
+            for i in 0..num_tables {
+                let pos = 12 + i * 16 + 8;
+                let offset = u32::from_be_bytes(face[pos..pos + 4].try_into().unwrap());
+                face[pos..pos + 4].copy_from_slice(&(offset + base as u32).to_be_bytes());
+            }
+            body.extend(face);
+            body.resize(body.len().next_multiple_of(4), 0);
+        }
+
+        let mut ttc = b"ttcf".to_vec();
+        ttc.extend(0x0001_0000u32.to_be_bytes());
+        ttc.extend((fonts.len() as u32).to_be_bytes());
+        offsets.iter().for_each(|o| ttc.extend(o.to_be_bytes()));
+        ttc.extend(body);
+        ttc
+    }
+
+    #[test]
+    fn instantiates_face_from_collection() {
+        let ttc = make_ttc(&[VAZIRMATN_VAR, SIMPLE_GLYF]);
+
+        let result =
+            instantiate_from_collection(&ttc, 0, &[AxisLocation::new("wght", 700.0)]).unwrap();
+
+        let output = FontRef::new(&result).unwrap();
+        assert!(output.fvar().is_err());
+        assert!(output.gvar().is_err());
+        assert!(output.glyf().is_ok());
+        assert_eq!(
+            output.maxp().unwrap().num_glyphs(),
+            FontRef::new(VAZIRMATN_VAR).unwrap().maxp().unwrap().num_glyphs()
+        );
+    }
+
+    #[test]
+    fn rejects_static_face_and_bad_index() {
+        let ttc = make_ttc(&[VAZIRMATN_VAR, SIMPLE_GLYF]);
+        let location = [AxisLocation::new("wght", 700.0)];
+
+        let static_face = instantiate_from_collection(&ttc, 1, &location);
+        assert!(matches!(static_face, Err(Error::NotVariableFont)));
+
+        let out_of_range = instantiate_from_collection(&ttc, 2, &location);
+        assert!(matches!(out_of_range, Err(Error::Parse(ReadError::InvalidCollectionIndex(2)))));
+    }
+
+    #[test]
+    fn accepts_plain_font_at_index_zero() {
+        let extracted = extract_from_collection(VAZIRMATN_VAR, 0).unwrap();
+        assert!(FontRef::new(&extracted).unwrap().fvar().is_ok());
+        assert!(extract_from_collection(VAZIRMATN_VAR, 1).is_err());
+    }
+}
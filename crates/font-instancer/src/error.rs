@@ -1,6 +1,6 @@
 use std::result;
 
-use read_fonts::ReadError;
+use read_fonts::{ReadError, types::Tag};
 use write_fonts::{BuilderError, error};
 
 /// Error types for font-instancer.
@@ -12,7 +12,7 @@ pub enum Error {
     #[error("not a variable font (no fvar table)")]
     NotVariableFont,
 
-    #[error("no glyf table (CFF/CFF2 fonts not supported)")]
+    #[error("no glyf or CFF2 outlines to instance")]
     NoCff2Support,
 
     #[error("no gvar table")]
@@ -21,14 +21,39 @@ pub enum Error {
     #[error("axis not found: {0}")]
     AxisNotFound(String),
 
+    #[error(
+        "unknown axis {tag} (available: {})",
+        .available.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+    )]
+    UnknownAxis { tag: Tag, available: Vec<Tag> },
+
+    #[error("{tag}={value} is outside the axis range {min}..={max}")]
+    AxisOutOfRange { tag: Tag, value: f32, min: f32, max: f32 },
+
+    #[error("axis {0} cannot be both pinned and kept")]
+    KeptAxisPinned(Tag),
+
+    #[error("partial instancing does not support variations in '{0}'")]
+    UnsupportedForPartial(Tag),
+
+    #[error("failed to read CFF2 outlines: {0}")]
+    Cff(#[from] read_fonts::ps::error::Error),
+
     #[error("failed to build font: {0}")]
     Build(#[from] BuilderError),
 
     #[error("failed to write table: {0}")]
     Write(#[from] error::Error),
 
+    #[error("invalid font: {0}")]
+    InvalidFont(String),
+
     #[error("invalid axis value {value} for {tag} (range: {min}..{max})")]
     InvalidAxisValue { tag: String, value: f32, min: f32, max: f32 },
+
+    #[cfg(feature = "woff2")]
+    #[error("failed to encode WOFF2: {0}")]
+    Woff2(String),
 }
 
 pub type Result<T> = result::Result<T, Error>;
@@ -6,29 +6,32 @@ use std::{
 };
 
 use kurbo::Rect;
+use log::warn;
 pub use read_fonts::tables::glyf::CurvePoint;
 use read_fonts::{
-    FontRef, TableProvider,
+    FontData, FontRead, FontRef, ReadError, TableProvider,
     tables::{
         cmap::{Cmap, CmapSubtable},
         fvar::Fvar,
         glyf::{
-            Anchor as ReadAnchor, CompositeGlyph as ReadCompositeGlyph, Glyph, PointFlags,
-            SimpleGlyph as ReadSimpleGlyph,
+            Anchor as ReadAnchor, CompositeGlyph as ReadCompositeGlyph, CompositeGlyphFlags, Glyf,
+            Glyph, PointFlags, SimpleGlyph as ReadSimpleGlyph,
         },
         gsub::{Gsub, SingleSubst, SubstitutionLookup},
-        gvar::Gvar,
+        gvar::{GlyphDelta, Gvar},
         hhea::Hhea,
-        layout::Condition,
+        layout::{Condition, FeatureTableSubstitution, FeatureVariations},
+        loca::Loca,
         mvar::{
             Mvar,
             tags::{
                 CPHT, HASC, HCOF, HCRN, HCRS, HDSC, HLGP, SBXO, SBXS, SBYO, SBYS, SPXO, SPXS, SPYO,
-                SPYS, STRO, STRS, UNDO, UNDS, XHGT,
+                SPYS, STRO, STRS, UNDO, UNDS, VASC, VCOF, VCRN, VCRS, VDSC, VLGP, XHGT,
             },
         },
         os2::Os2,
         post::Post,
+        vhea::Vhea,
     },
     types::{F2Dot14, Fixed, GlyphId, Point, Tag},
 };
@@ -41,27 +44,38 @@ use write_fonts::{
             Anchor::Offset, Bbox, CompositeGlyph, Contour, GlyfLocaBuilder, Glyph as WriteGlyph,
             SimpleGlyph,
         },
-        head::Head,
+        gpos::Gpos as WriteGpos,
+        gsub::Gsub as WriteGsub,
+        head::{Flags, Head},
         hhea::Hhea as WriteHhea,
         hmtx::{Hmtx as WriteHmtx, LongMetric},
+        layout::{Feature, FeatureList},
         loca::LocaFormat,
         os2::Os2 as WriteOs2,
         post::Post as WritePost,
         stat::{AxisRecord, AxisValue, AxisValueTableFlags, Stat},
+        vhea::Vhea as WriteVhea,
+        vmtx::Vmtx as WriteVmtx,
     },
     types::NameId,
 };
 
 use crate::{
-    AxisLocation,
+    AxisLocation, InstanceOptions, InstanceReport,
+    cff2::{self, CFF, CFF2},
     error::{Error, Result},
+    named_instances::{NAMED_INSTANCES_TAG, encode_named_instances, fvar_named_instances},
+    partial::{
+        PARTIAL_DROPPED_TABLES, check_partial_support, partial_avar, partial_fvar, partial_gvar,
+        partial_stat,
+    },
 };
 
 fn clamp_i16(value: i32) -> i16 {
     value.clamp(i32::from(i16::MIN), i32::from(i16::MAX)) as i16
 }
 
-const VARIATION_TABLES: [Tag; 8] = [
+pub(crate) const VARIATION_TABLES: [Tag; 8] = [
     Tag::new(b"fvar"),
     Tag::new(b"gvar"),
     Tag::new(b"avar"),
@@ -72,7 +86,7 @@ const VARIATION_TABLES: [Tag; 8] = [
     Tag::new(b"STAT"),
 ];
 
-const REPLACED_TABLES: [Tag; 7] = [
+pub(crate) const REPLACED_TABLES: [Tag; 8] = [
     Tag::new(b"glyf"),
     Tag::new(b"loca"),
     Tag::new(b"hmtx"),
@@ -80,32 +94,278 @@ const REPLACED_TABLES: [Tag; 7] = [
     Tag::new(b"hhea"),
     Tag::new(b"OS/2"),
     Tag::new(b"post"),
+    // Instanced to `CFF `
+    CFF2,
 ];
 
-const REMOVED_TABLES: [Tag; 1] = [Tag::new(b"DSIG")];
+/// Rebuilt only when both are present; otherwise copied through unchanged.
+pub(crate) const VERTICAL_TABLES: [Tag; 2] = [Tag::new(b"vhea"), Tag::new(b"vmtx")];
+
+pub(crate) const REMOVED_TABLES: [Tag; 1] = [Tag::new(b"DSIG")];
 
-const PHANTOM_POINTS: usize = 4;
+/// Dropped unless [`InstanceOptions::keep_hinting`] is set, together with
+/// glyph instructions: control values are tuned to the default outlines.
+pub(crate) const HINTING_TABLES: [Tag; 4] =
+    [Tag::new(b"cvt "), Tag::new(b"fpgm"), Tag::new(b"prep"), Tag::new(b"gasp")];
+
+pub(crate) const PHANTOM_POINTS: usize = 4;
 
 /// Instantiate a variable font at the given axis locations.
 ///
 /// Converts a variable font to a static instance by:
 /// 1. Normalizing user-space coordinates
 /// 2. Applying gvar deltas to glyph coordinates
-/// 3. Updating hmtx from phantom point deltas
+/// 3. Updating hmtx (and vmtx, if present alongside vhea) from phantom point deltas
 /// 4. Removing variation tables (fvar, gvar, avar, etc.)
+/// 5. Removing hinting (`cvt `, `fpgm`, `prep`, `gasp` and glyph instructions), which no longer
+///    matches the interpolated outlines; see [`InstanceOptions::keep_hinting`]
+/// 6. Swapping in the GSUB/GPOS features that FeatureVariations select at the location, and mapping
+///    `cmap` through the `rvrn` substitutions among them
+///
+/// gvar point numbers are untrusted: deltas aimed past a glyph's points
+/// (phantom points included) are skipped rather than indexed.
+///
+/// CFF2 fonts are instanced to a static `CFF ` table instead: charstring
+/// blends are resolved at the location, subroutines inlined and hints kept,
+/// and hmtx/vmtx take their HVAR/VVAR deltas.
 ///
 /// # Errors
 ///
 /// - `Error::NotVariableFont` if the font has no fvar table
-/// - `Error::NoCff2Support` if the font uses CFF outlines (no glyf table)
-/// - `Error::NoGvar` if the font has no gvar table
+/// - `Error::NoCff2Support` if the font has neither glyf nor CFF2 outlines
+/// - `Error::NoGvar` if a glyf font has no gvar table
+/// - `Error::Cff` if a CFF2 charstring or DICT cannot be evaluated
+/// - `Error::UnknownAxis` if a location names an axis missing from fvar
+/// - `Error::AxisOutOfRange` if a location falls outside its axis's fvar range; use
+///   [`instantiate_clamped`] to pin at the nearest bound instead
 pub fn instantiate(data: &[u8], locations: &[AxisLocation]) -> Result<Vec<u8>> {
+    instantiate_with_options(data, locations, &InstanceOptions::default())
+}
+
+/// Instantiate a variable font, clamping each location to its axis range.
+///
+/// [`instantiate`] rejects out-of-range values; this restores the lenient
+/// behavior of pinning `wght=1200` on a 100–900 font at 900.
+///
+/// # Errors
+///
+/// As [`instantiate`], except that `Error::AxisOutOfRange` is never returned.
+pub fn instantiate_clamped(data: &[u8], locations: &[AxisLocation]) -> Result<Vec<u8>> {
     let font = FontRef::new(data)?;
+    let fvar = font.fvar().map_err(|_| Error::NotVariableFont)?;
+    let axes = fvar.axes()?;
 
+    let clamped: Vec<AxisLocation> = locations
+        .iter()
+        .map(|loc| match axes.iter().find(|axis| axis.axis_tag() == loc.tag) {
+            Some(axis) => AxisLocation {
+                tag: loc.tag,
+                value: loc.value.clamp(axis.min_value().to_f32(), axis.max_value().to_f32()),
+            },
+            None => *loc,
+        })
+        .collect();
+    instantiate(data, &clamped)
+}
+
+/// Instantiate a variable font at offsets from its default location.
+///
+/// Each `(tag, delta)` is added to that axis's fvar default and clamped to
+/// the axis range, so `(wght, 300.0)` on a Regular-default font lands on
+/// `wght=700`. Axes not listed stay at their default.
+///
+/// # Errors
+///
+/// As [`instantiate`].
+pub fn instantiate_relative(data: &[u8], deltas: &[(Tag, f32)]) -> Result<Vec<u8>> {
+    let font = FontRef::new(data)?;
     let fvar = font.fvar().map_err(|_| Error::NotVariableFont)?;
-    let glyf = font.glyf().map_err(|_| Error::NoCff2Support)?;
-    let loca = font.loca(None).map_err(|_| Error::NoCff2Support)?;
-    let gvar = font.gvar().map_err(|_| Error::NoGvar)?;
+    let axes = fvar.axes()?;
+
+    let locations = deltas
+        .iter()
+        .map(|&(tag, delta)| {
+            let axis = axes.iter().find(|axis| axis.axis_tag() == tag).ok_or_else(|| {
+                Error::UnknownAxis {
+                    tag,
+                    available: axes.iter().map(|a| a.axis_tag()).collect(),
+                }
+            })?;
+            let value = (axis.default_value().to_f32() + delta)
+                .clamp(axis.min_value().to_f32(), axis.max_value().to_f32());
+            Ok(AxisLocation { tag, value })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    instantiate(data, &locations)
+}
+
+/// Instantiate a variable font at the given axis locations, with options.
+///
+/// Behaves like [`instantiate`], with the output tweaked by `options`.
+pub fn instantiate_with_options(
+    data: &[u8],
+    locations: &[AxisLocation],
+    options: &InstanceOptions,
+) -> Result<Vec<u8>> {
+    instantiate_with_report(data, locations, options).map(|(instance, _)| instance)
+}
+
+/// [`instantiate_with_options`], also returning an [`InstanceReport`] of the
+/// glyphs [`skip_bad_glyphs`](InstanceOptions::skip_bad_glyphs) kept at
+/// their default outline.
+pub fn instantiate_with_report(
+    data: &[u8],
+    locations: &[AxisLocation],
+    options: &InstanceOptions,
+) -> Result<(Vec<u8>, InstanceReport)> {
+    instantiate_impl(&SourceFont::new(data)?, locations, options, &[])
+}
+
+/// Instantiate a variable font at several named locations.
+///
+/// The font and its `fvar`, `glyf`, `loca` and `gvar` tables are parsed, and
+/// every glyph looked up in `loca`, once for all instances. Each output is
+/// byte-identical to calling [`instantiate`] with the same locations, and
+/// is returned under its name in input order.
+///
+/// # Errors
+///
+/// As [`instantiate`], for the first location that fails.
+pub fn instantiate_many(
+    data: &[u8],
+    instances: &[(String, Vec<AxisLocation>)],
+) -> Result<Vec<(String, Vec<u8>)>> {
+    let source = SourceFont::new(data)?;
+    let options = InstanceOptions::default();
+    instances
+        .iter()
+        .map(|(name, locations)| {
+            Ok((name.clone(), instantiate_impl(&source, locations, &options, &[])?.0))
+        })
+        .collect()
+}
+
+/// Instantiate a variable font, pinning some axes and keeping others variable.
+///
+/// Axes in `keep` stay in `fvar` with their full range; every other axis is
+/// pinned at its location in `locations` (or its default). The pinned
+/// axes' share of each gvar tuple is folded into the default outlines, and
+/// gvar is rebuilt over the kept axes only. Like fontTools' partial
+/// instancer, but without range limiting of the kept axes.
+///
+/// HVAR, VVAR, MVAR and cvar are dropped, so advances vary through gvar
+/// phantom points only; `avar` maps and named instances are filtered to the
+/// kept axes and the pinned location, and STAT loses the pinned axes'
+/// records and the axis values that describe them.
+///
+/// # Errors
+///
+/// As [`instantiate`], plus:
+/// - `Error::KeptAxisPinned` if an axis is both in `keep` and `locations`
+/// - `Error::UnsupportedForPartial` for avar2, GSUB/GPOS FeatureVariations, or a GDEF variation
+///   store, whose axis references cannot be remapped yet
+pub fn instantiate_partial(
+    data: &[u8],
+    locations: &[AxisLocation],
+    keep: &[Tag],
+) -> Result<Vec<u8>> {
+    instantiate_impl(&SourceFont::new(data)?, locations, &InstanceOptions::default(), keep)
+        .map(|(instance, _)| instance)
+}
+
+/// A variable font's tables and glyphs, parsed once and shared by every
+/// instance made from it.
+struct SourceFont<'a> {
+    font: FontRef<'a>,
+    fvar: Fvar<'a>,
+    outlines: Outlines<'a>,
+}
+
+/// Where a [`SourceFont`]'s outlines and their variations live.
+enum Outlines<'a> {
+    Glyf(GlyfSource<'a>),
+    /// Evaluated from the `CFF2` table for each instance
+    Cff2,
+}
+
+struct GlyfSource<'a> {
+    glyf: Glyf<'a>,
+    loca: Loca<'a>,
+    gvar: Gvar<'a>,
+    /// Outline of each glyph, `None` where loca has no data
+    glyphs: Vec<Option<Glyph<'a>>>,
+}
+
+impl<'a> SourceFont<'a> {
+    fn new(data: &'a [u8]) -> Result<Self> {
+        let font = FontRef::new(data)?;
+        let fvar = font.fvar().map_err(|_| Error::NotVariableFont)?;
+        if font.glyf().is_err() && font.table_data(CFF2).is_some() {
+            return Ok(Self { font, fvar, outlines: Outlines::Cff2 });
+        }
+        let glyf = font.glyf().map_err(|_| Error::NoCff2Support)?;
+        let loca = font.loca(None).map_err(|_| Error::NoCff2Support)?;
+        let gvar = font.gvar().map_err(|_| Error::NoGvar)?;
+        let glyphs = (0..u32::from(font.maxp()?.num_glyphs()))
+            .map(|gid| loca.get_glyf(GlyphId::new(gid), &glyf).ok().flatten())
+            .collect();
+        let outlines = Outlines::Glyf(GlyfSource { glyf, loca, gvar, glyphs });
+        Ok(Self { font, fvar, outlines })
+    }
+}
+
+/// Instanced metrics, alongside the outline tables added to the builder.
+struct InstancedOutlines {
+    advances: Vec<u16>,
+    lsbs: Vec<i16>,
+    /// Advance heights and top side bearings; empty without vhea and vmtx
+    advance_heights: Vec<u16>,
+    tsbs: Vec<i16>,
+    bounds: FontBounds,
+    /// `head.indexToLocFormat`
+    index_to_loc_format: i16,
+    /// Glyphs kept at their default outline
+    skipped_glyphs: Vec<GlyphId>,
+}
+
+fn instantiate_impl(
+    source: &SourceFont,
+    locations: &[AxisLocation],
+    options: &InstanceOptions,
+    keep: &[Tag],
+) -> Result<(Vec<u8>, InstanceReport)> {
+    let SourceFont { font, fvar, outlines } = source;
+
+    let axes = fvar.axes()?;
+    let available: Vec<Tag> = axes.iter().map(|axis| axis.axis_tag()).collect();
+    let requested = locations.iter().map(|loc| loc.tag).chain(keep.iter().copied());
+    if let Some(unknown) = requested.into_iter().find(|tag| !available.contains(tag)) {
+        return Err(Error::UnknownAxis { tag: unknown, available });
+    }
+    if let Some(pinned) = locations.iter().find(|loc| keep.contains(&loc.tag)) {
+        return Err(Error::KeptAxisPinned(pinned.tag));
+    }
+    for loc in locations {
+        let Some(axis) = axes.iter().find(|axis| axis.axis_tag() == loc.tag) else {
+            continue;
+        };
+        let value = Fixed::from_f64(f64::from(loc.value));
+        if value < axis.min_value() || value > axis.max_value() {
+            return Err(Error::AxisOutOfRange {
+                tag: loc.tag,
+                value: loc.value,
+                min: axis.min_value().to_f32(),
+                max: axis.max_value().to_f32(),
+            });
+        }
+    }
+    let kept: Vec<usize> = (0..available.len())
+        .filter(|&i| keep.contains(&available[i]))
+        .collect();
+    let partial = !kept.is_empty();
+    if partial {
+        check_partial_support(font)?;
+    }
 
     let avar = font.avar().ok();
     let axis_count = fvar.axis_count() as usize;
@@ -118,12 +378,174 @@ pub fn instantiate(data: &[u8], locations: &[AxisLocation]) -> Result<Vec<u8>> {
 
     fvar.user_to_normalized(avar.as_ref(), user_coords, &mut normalized_coords);
 
+    let num_h_metrics = font.hhea()?.number_of_h_metrics() as usize;
+    let vertical = font.vhea().ok().zip(font.vmtx().ok());
+
+    let mut builder = FontBuilder::new();
+    let InstancedOutlines {
+        advances,
+        lsbs: new_lsbs,
+        advance_heights,
+        tsbs: new_tsbs,
+        bounds,
+        index_to_loc_format,
+        skipped_glyphs,
+    } = match outlines {
+        Outlines::Glyf(glyf) => {
+            instance_glyf(&mut builder, font, glyf, &normalized_coords, options)?
+        }
+        Outlines::Cff2 => instance_cff2(&mut builder, font, &normalized_coords, options)?,
+    };
+    builder.add_table(&build_new_hmtx(&advances, &new_lsbs, num_h_metrics))?;
+
+    // Get MVAR deltas if available
+    let mvar = font.mvar().ok();
+
+    // Build head table with recalculated bounds
+    if let Ok(head) = font.head() {
+        let mut flags = head.flags();
+        match options.integer_ppem {
+            Some(true) => flags.insert(Flags::FORCE_INTEGER_PPEM),
+            Some(false) => flags.remove(Flags::FORCE_INTEGER_PPEM),
+            None => {}
+        }
+        let new_head = Head::new(
+            head.font_revision(),
+            head.checksum_adjustment(),
+            flags,
+            head.units_per_em(),
+            head.created(),
+            head.modified(),
+            bounds.x_min,
+            bounds.y_min,
+            bounds.x_max,
+            bounds.y_max,
+            head.mac_style(),
+            options.lowest_rec_ppem.unwrap_or_else(|| head.lowest_rec_ppem()),
+            index_to_loc_format,
+        );
+        builder.add_table(&new_head)?;
+    }
+
+    // Build hhea table with recalculated metrics and MVAR deltas
+    if let Ok(hhea) = font.hhea() {
+        let new_hhea = build_new_hhea(&hhea, &bounds, mvar.as_ref(), &normalized_coords);
+        builder.add_table(&new_hhea)?;
+    }
+
+    // Build vhea/vmtx with instanced advance heights and MVAR deltas
+    if let Some((vhea, _)) = &vertical {
+        let num_v_metrics = vhea.number_of_long_ver_metrics() as usize;
+        builder.add_table(&build_new_vmtx(&advance_heights, &new_tsbs, num_v_metrics))?;
+        builder.add_table(&build_new_vhea(vhea, &bounds, mvar.as_ref(), &normalized_coords))?;
+    }
+
+    // Build OS/2 table with MVAR deltas and updated weight class
+    if let Ok(os2) = font.os2() {
+        let new_os2 = build_new_os2(&os2, mvar.as_ref(), &normalized_coords, locations);
+        builder.add_table(&new_os2)?;
+    }
+
+    // Build post table with MVAR deltas
+    if let Ok(post) = font.post() {
+        let new_post = build_new_post(&post, mvar.as_ref(), &normalized_coords);
+        builder.add_table(&new_post)?;
+    }
+
+    if partial {
+        builder.add_table(&partial_fvar(fvar, &kept, locations)?)?;
+        if let Ok(avar) = font.avar() {
+            builder.add_table(&partial_avar(&avar, &kept))?;
+        }
+        builder.add_table(&partial_gvar(font, &normalized_coords, &kept)?)?;
+        if let Ok(stat) = font.stat() {
+            builder.add_table(&partial_stat(&stat, fvar, &kept, locations)?)?;
+        }
+    } else {
+        // Build STAT table for the static instance
+        let stat = build_new_stat(fvar, locations, options.stat_precision);
+        builder.add_table(&stat)?;
+        if options.keep_named_instances {
+            builder
+                .add_raw(NAMED_INSTANCES_TAG, encode_named_instances(&fvar_named_instances(font)));
+        }
+    }
+
+    if !partial {
+        // Build cmap table with `rvrn` substitutions applied (only if substitutions exist)
+        if let Ok(cmap) = font.cmap()
+            && let Ok(gsub) = font.gsub()
+            && let Some(subs) = compute_feature_variation_substitutions(&gsub, &normalized_coords)
+            && let Some(new_cmap) = build_instanced_cmap(&cmap, Some(&subs))
+        {
+            builder.add_table(&new_cmap)?;
+        }
+        // A static font has no location for FeatureVariations to match, so
+        // swap in the features they select here
+        if let Ok(gsub) = font.gsub()
+            && let Some(feature_variations) = gsub.feature_variations()
+        {
+            let mut owned: WriteGsub = gsub.to_owned_table();
+            apply_feature_variations(
+                &feature_variations?,
+                &mut owned.feature_list,
+                &normalized_coords,
+            );
+            owned.feature_variations.clear();
+            builder.add_table(&owned)?;
+        }
+        if let Ok(gpos) = font.gpos()
+            && let Some(feature_variations) = gpos.feature_variations()
+        {
+            let mut owned: WriteGpos = gpos.to_owned_table();
+            apply_feature_variations(
+                &feature_variations?,
+                &mut owned.feature_list,
+                &normalized_coords,
+            );
+            owned.feature_variations.clear();
+            builder.add_table(&owned)?;
+        }
+    }
+
+    for record in font.table_directory.table_records() {
+        let tag = record.tag();
+        let is_replaced = REPLACED_TABLES.contains(&tag)
+            || (vertical.is_some() && VERTICAL_TABLES.contains(&tag))
+            || (!partial && builder.contains(tag));
+        let is_dropped = if partial {
+            PARTIAL_DROPPED_TABLES.contains(&tag) || builder.contains(tag)
+        } else {
+            VARIATION_TABLES.contains(&tag)
+        };
+        let is_hinting = !options.keep_hinting && HINTING_TABLES.contains(&tag);
+        if !is_dropped
+            && !is_replaced
+            && !is_hinting
+            && !REMOVED_TABLES.contains(&tag)
+            && let Some(data) = font.table_data(tag)
+        {
+            builder.add_raw(tag, data);
+        }
+    }
+
+    Ok((builder.build(), InstanceReport { skipped_glyphs }))
+}
+
+/// Apply gvar deltas to every glyph, adding `glyf` and `loca` to `builder`.
+fn instance_glyf(
+    builder: &mut FontBuilder,
+    font: &FontRef,
+    source: &GlyfSource,
+    normalized_coords: &[F2Dot14],
+    options: &InstanceOptions,
+) -> Result<InstancedOutlines> {
+    let GlyfSource { glyf, loca, gvar, glyphs: source_glyphs } = source;
     let maxp = font.maxp()?;
     let num_glyphs = u32::from(maxp.num_glyphs());
 
     let hmtx = font.hmtx()?;
-    let hhea = font.hhea()?;
-    let num_h_metrics = hhea.number_of_h_metrics() as usize;
+    let vertical = font.vhea().ok().zip(font.vmtx().ok());
 
     // Pass 1: Build all glyphs with delta application, collecting bboxes
     let mut glyphs: Vec<WriteGlyph> = Vec::with_capacity(num_glyphs as usize);
@@ -131,14 +553,21 @@ pub fn instantiate(data: &[u8], locations: &[AxisLocation]) -> Result<Vec<u8>> {
     let mut advance_width_deltas = Vec::with_capacity(num_glyphs as usize);
     let mut advances: Vec<u16> = Vec::with_capacity(num_glyphs as usize);
     let mut lsbs: Vec<i16> = Vec::with_capacity(num_glyphs as usize);
+    let mut advance_heights: Vec<u16> = Vec::new();
+    let mut tsbs: Vec<i16> = Vec::new();
+    // Instanced y of each glyph's top phantom point; tsb = top - yMax.
+    let mut vertical_tops: Vec<i32> = Vec::new();
+    let mut skipped_glyphs = Vec::new();
 
     for glyph_id in 0..num_glyphs {
         let gid = GlyphId::new(glyph_id);
 
-        let aw_delta = match gvar.phantom_point_deltas(&glyf, &loca, &normalized_coords, gid) {
-            Ok(Some(deltas)) => deltas.get(1).map_or(0, |d| d.x.to_i32() as i16),
-            _ => 0,
-        };
+        let phantom_deltas = gvar
+            .phantom_point_deltas(glyf, loca, normalized_coords, gid)
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+        let aw_delta = phantom_deltas[1].x.to_i32() as i16;
         advance_width_deltas.push(aw_delta);
 
         let orig_advance = hmtx.advance(gid).unwrap_or(0);
@@ -147,7 +576,20 @@ pub fn instantiate(data: &[u8], locations: &[AxisLocation]) -> Result<Vec<u8>> {
         advances.push(new_advance);
         lsbs.push(orig_lsb);
 
-        let Some(glyph) = loca.get_glyf(gid, &glyf).ok().flatten() else {
+        let glyph = source_glyphs.get(glyph_id as usize).cloned().flatten();
+
+        if let Some((_, vmtx)) = &vertical {
+            let [_, _, top, bottom] = phantom_deltas;
+            let orig_height = i32::from(vmtx.advance(gid).unwrap_or(0));
+            let orig_tsb = vmtx.side_bearing(gid).unwrap_or(0);
+            let orig_y_max = glyph.as_ref().map_or(0, |glyph| i32::from(glyph.y_max()));
+            let height_delta = top.y.to_i32() - bottom.y.to_i32();
+            advance_heights.push((orig_height + height_delta).clamp(0, i32::from(u16::MAX)) as u16);
+            tsbs.push(orig_tsb);
+            vertical_tops.push(orig_y_max + i32::from(orig_tsb) + top.y.to_i32());
+        }
+
+        let Some(glyph) = glyph else {
             glyphs.push(tables::glyf::Glyph::Empty);
             glyph_bboxes.push(None);
             continue;
@@ -155,15 +597,49 @@ pub fn instantiate(data: &[u8], locations: &[AxisLocation]) -> Result<Vec<u8>> {
 
         match glyph {
             Glyph::Simple(simple) => {
-                let new_glyph =
-                    apply_deltas_to_simple_glyph(&simple, &gvar, gid, &normalized_coords)?;
+                let instanced = apply_deltas_to_simple_glyph(
+                    &simple,
+                    gvar,
+                    gid,
+                    normalized_coords,
+                    options.integer_deltas,
+                    options.skip_bad_glyphs,
+                );
+                let mut new_glyph = match instanced {
+                    Ok(glyph) => glyph,
+                    Err(e) if options.skip_bad_glyphs => {
+                        warn!("glyph {gid}: {e}; keeping its default outline");
+                        skipped_glyphs.push(gid);
+                        default_outline(&simple)
+                    }
+                    Err(e) => return Err(e),
+                };
+                if !options.keep_hinting
+                    && let tables::glyf::Glyph::Simple(simple) = &mut new_glyph
+                {
+                    simple.instructions.clear();
+                }
                 let bbox = new_glyph.bbox();
                 glyph_bboxes.push(bbox);
                 glyphs.push(new_glyph);
             }
             Glyph::Composite(composite) => {
-                let new_glyph =
-                    apply_deltas_to_composite_glyph(&composite, &gvar, gid, &normalized_coords)?;
+                let mut new_glyph = apply_deltas_to_composite_glyph(
+                    &composite,
+                    gvar,
+                    gid,
+                    normalized_coords,
+                    options.integer_deltas,
+                )?;
+                // The rebuilt composite has no instructions, so without
+                // hinting WE_HAVE_INSTRUCTIONS is left unset as well
+                if options.keep_hinting
+                    && let Some(instructions) = composite.instructions().filter(|i| !i.is_empty())
+                    && let tables::glyf::Glyph::Composite(rebuilt) = &new_glyph
+                {
+                    new_glyph =
+                        tables::glyf::Glyph::Composite(with_instructions(rebuilt, instructions)?);
+                }
                 // Composite bbox will be recomputed in pass 2
                 glyph_bboxes.push(None);
                 glyphs.push(new_glyph);
@@ -198,106 +674,118 @@ pub fn instantiate(data: &[u8], locations: &[AxisLocation]) -> Result<Vec<u8>> {
     let mut glyf_builder = GlyfLocaBuilder::new();
     let mut bounds = FontBounds::new();
     let mut new_lsbs: Vec<i16> = Vec::with_capacity(num_glyphs as usize);
+    let mut new_tsbs: Vec<i16> = Vec::with_capacity(tsbs.len());
 
     for (i, glyph) in glyphs.iter().enumerate() {
         // LSB should equal the glyph's xMin after interpolation
         let new_lsb = get_glyph_xmin(glyph).unwrap_or(lsbs[i]);
         new_lsbs.push(new_lsb);
         bounds.update(glyph, advances[i], new_lsb);
+        if vertical.is_some() {
+            let new_tsb = get_glyph_ymax(glyph)
+                .map_or(tsbs[i], |y_max| clamp_i16(vertical_tops[i] - i32::from(y_max)));
+            new_tsbs.push(new_tsb);
+            bounds.update_vertical(glyph, advance_heights[i], new_tsb);
+        }
         glyf_builder.add_glyph(glyph)?;
     }
 
     bounds.finalize();
 
     let (new_glyf, new_loca, loca_format) = glyf_builder.build();
-    let new_hmtx = build_new_hmtx(&advances, &new_lsbs, num_h_metrics);
-
-    // Get MVAR deltas if available
-    let mvar = font.mvar().ok();
-
-    let mut builder = FontBuilder::new();
     builder.add_table(&new_glyf)?;
     builder.add_table(&new_loca)?;
-    builder.add_table(&new_hmtx)?;
-
-    // Build head table with recalculated bounds
-    if let Ok(head) = font.head() {
-        let new_head = Head::new(
-            head.font_revision(),
-            head.checksum_adjustment(),
-            head.flags(),
-            head.units_per_em(),
-            head.created(),
-            head.modified(),
-            bounds.x_min,
-            bounds.y_min,
-            bounds.x_max,
-            bounds.y_max,
-            head.mac_style(),
-            head.lowest_rec_ppem(),
-            match loca_format {
-                LocaFormat::Short => 0,
-                LocaFormat::Long => 1,
-            },
-        );
-        builder.add_table(&new_head)?;
-    }
 
-    // Build hhea table with recalculated metrics and MVAR deltas
-    if let Ok(hhea) = font.hhea() {
-        let new_hhea = build_new_hhea(&hhea, &bounds, mvar.as_ref(), &normalized_coords);
-        builder.add_table(&new_hhea)?;
-    }
-
-    // Build OS/2 table with MVAR deltas and updated weight class
-    if let Ok(os2) = font.os2() {
-        let new_os2 = build_new_os2(&os2, mvar.as_ref(), &normalized_coords, locations);
-        builder.add_table(&new_os2)?;
-    }
-
-    // Build post table with MVAR deltas
-    if let Ok(post) = font.post() {
-        let new_post = build_new_post(&post, mvar.as_ref(), &normalized_coords);
-        builder.add_table(&new_post)?;
-    }
+    Ok(InstancedOutlines {
+        advances,
+        lsbs: new_lsbs,
+        advance_heights,
+        tsbs: new_tsbs,
+        bounds,
+        index_to_loc_format: match loca_format {
+            LocaFormat::Short => 0,
+            LocaFormat::Long => 1,
+        },
+        skipped_glyphs,
+    })
+}
 
-    // Build STAT table for the static instance
-    let stat = build_new_stat(&fvar, locations);
-    builder.add_table(&stat)?;
+/// Evaluate CFF2 charstrings at the instance, adding `CFF ` to `builder`.
+///
+/// Advances and vertical metrics take their HVAR and VVAR deltas where
+/// those tables exist and are otherwise kept.
+fn instance_cff2(
+    builder: &mut FontBuilder,
+    font: &FontRef,
+    normalized_coords: &[F2Dot14],
+    options: &InstanceOptions,
+) -> Result<InstancedOutlines> {
+    let num_glyphs = u32::from(font.maxp()?.num_glyphs());
+    let hmtx = font.hmtx()?;
+    let hvar = font.hvar().ok();
+    let vertical = font.vhea().ok().zip(font.vmtx().ok());
+    let vvar = font.vvar().ok();
+    let delta = |delta: Option<Fixed>| delta.map_or(0, |delta| delta.round().to_i32());
+
+    let advances: Vec<u16> = (0..num_glyphs)
+        .map(|glyph_id| {
+            let gid = GlyphId::new(glyph_id);
+            let advance = i32::from(hmtx.advance(gid).unwrap_or(0));
+            let delta = delta(
+                hvar.as_ref()
+                    .and_then(|hvar| hvar.advance_width_delta(gid, normalized_coords).ok()),
+            );
+            (advance + delta).clamp(0, i32::from(u16::MAX)) as u16
+        })
+        .collect();
+    let instance = cff2::instance_cff2(font, normalized_coords, &advances, options.drop_cff_hints)?;
+    builder.add_raw(CFF, instance.data);
 
-    // Build cmap table with FeatureVariations substitutions applied (only if substitutions exist)
-    let cmap_replaced = if let Ok(cmap) = font.cmap() {
-        let gsub = font.gsub().ok();
-        let substitutions = gsub
-            .and_then(|gsub| compute_feature_variation_substitutions(&gsub, &normalized_coords));
-        if let Some(subs) = &substitutions {
-            if let Some(new_cmap) = build_instanced_cmap(&cmap, Some(subs)) {
-                builder.add_table(&new_cmap)?;
-                true
-            } else {
-                false
-            }
-        } else {
-            false
+    let mut bounds = FontBounds::new();
+    let mut lsbs = Vec::with_capacity(num_glyphs as usize);
+    let mut advance_heights = Vec::new();
+    let mut tsbs = Vec::new();
+    for (glyph_id, bbox) in instance.bboxes.iter().enumerate() {
+        let gid = GlyphId::new(glyph_id as u32);
+        let lsb = bbox.map_or_else(|| hmtx.side_bearing(gid).unwrap_or(0), |bbox| bbox.x_min);
+        lsbs.push(lsb);
+        if let Some(bbox) = bbox {
+            bounds.update_bbox(*bbox, advances[glyph_id]);
         }
-    } else {
-        false
-    };
-
-    let cmap_tag = Tag::new(b"cmap");
-    for record in font.table_directory.table_records() {
-        let tag = record.tag();
-        let is_replaced = REPLACED_TABLES.contains(&tag) || (tag == cmap_tag && cmap_replaced);
-        if !VARIATION_TABLES.contains(&tag)
-            && !is_replaced
-            && !REMOVED_TABLES.contains(&tag)
-            && let Some(data) = font.table_data(tag)
-        {
-            builder.add_raw(tag, data);
+        if let Some((_, vmtx)) = &vertical {
+            let vvar = vvar.as_ref();
+            let height = i32::from(vmtx.advance(gid).unwrap_or(0))
+                + delta(
+                    vvar.and_then(|vvar| vvar.advance_height_delta(gid, normalized_coords).ok()),
+                );
+            let tsb = i32::from(vmtx.side_bearing(gid).unwrap_or(0))
+                + delta(vvar.and_then(|vvar| vvar.tsb_delta(gid, normalized_coords).ok()));
+            let (height, tsb) = (height.clamp(0, i32::from(u16::MAX)) as u16, clamp_i16(tsb));
+            if let Some(bbox) = bbox {
+                bounds.update_vertical_bbox(*bbox, height, tsb);
+            }
+            advance_heights.push(height);
+            tsbs.push(tsb);
         }
     }
+    bounds.finalize();
 
-    Ok(builder.build())
+    Ok(InstancedOutlines {
+        advances,
+        lsbs,
+        advance_heights,
+        tsbs,
+        bounds,
+        index_to_loc_format: font.head()?.index_to_loc_format(),
+        skipped_glyphs: Vec::new(),
+    })
+}
+
+/// Scale a gvar delta by its tuple scalar, rounding it to whole units when
+/// `integer` is set.
+fn scaled_delta(delta: GlyphDelta, scalar: Fixed, integer: bool) -> Point<Fixed> {
+    let scaled: Point<Fixed> = delta.apply_scalar(scalar);
+    if integer { Point::new(scaled.x.round(), scaled.y.round()) } else { scaled }
 }
 
 fn apply_deltas_to_simple_glyph(
@@ -305,19 +793,44 @@ fn apply_deltas_to_simple_glyph(
     gvar: &Gvar,
     glyph_id: GlyphId,
     coords: &[F2Dot14],
+    integer_deltas: bool,
+    reject_bad_contours: bool,
 ) -> Result<WriteGlyph> {
     let num_points = simple.num_points();
     if num_points == 0 {
         return Ok(tables::glyf::Glyph::Empty);
     }
 
-    let end_pts: Vec<usize> = simple
+    let mut end_pts: Vec<usize> = simple
         .end_pts_of_contours()
         .iter()
         .map(|x| x.get() as usize)
         .collect();
+    // Contour ends index `points` below and drive IUP, so ends that are not
+    // strictly increasing within the outline either fail the glyph, for
+    // `skip_bad_glyphs` to report, or are dropped as in `default_outline`.
+    if end_pts.windows(2).any(|pair| pair[0] >= pair[1])
+        || end_pts.last().is_some_and(|&end| end >= num_points)
+    {
+        if reject_bad_contours {
+            return Err(Error::Parse(ReadError::MalformedData(
+                "glyf contour end points are not increasing",
+            )));
+        }
+        warn!("glyph {glyph_id}: contour end points are not increasing; dropping empty contours");
+        let mut previous = None;
+        end_pts.retain_mut(|end| {
+            *end = (*end).min(num_points - 1);
+            let keep = previous.is_none_or(|previous| *end > previous);
+            if keep {
+                previous = Some(*end);
+            }
+            keep
+        });
+    }
 
-    // Use Fixed (16.16) for delta accumulation to preserve fractional precision
+    // Use Fixed (16.16) for delta accumulation to preserve fractional
+    // precision; with `integer_deltas` each tuple's delta is rounded first
     let mut points: Vec<Point<Fixed>> = Vec::with_capacity(num_points + PHANTOM_POINTS);
     let mut flags: Vec<PointFlags> = Vec::with_capacity(num_points + PHANTOM_POINTS);
 
@@ -341,11 +854,13 @@ fn apply_deltas_to_simple_glyph(
 
     if let Ok(Some(var_data)) = gvar.glyph_variation_data(glyph_id) {
         for (tuple, scalar) in var_data.active_tuples_at(coords) {
+            // Every delta goes through `get_mut`: a malformed gvar may name
+            // points this glyph does not have.
             if tuple.has_deltas_for_all_points() {
                 for delta in tuple.deltas() {
                     let idx = delta.position as usize;
                     if let Some(point) = points.get_mut(idx) {
-                        let scaled: Point<Fixed> = delta.apply_scalar(scalar);
+                        let scaled = scaled_delta(delta, scalar, integer_deltas);
                         point.x += scaled.x;
                         point.y += scaled.y;
                     }
@@ -414,11 +929,40 @@ fn apply_deltas_to_simple_glyph(
     Ok(tables::glyf::Glyph::Simple(glyph))
 }
 
+/// The default outline of a simple glyph whose deltas could not be applied.
+///
+/// Contours whose end points run backwards or past the last point are
+/// dropped, since they delimit no points.
+fn default_outline(simple: &ReadSimpleGlyph) -> WriteGlyph {
+    let points: Vec<CurvePoint> = simple.points().collect();
+    let mut start = 0;
+    let mut contours = Vec::new();
+    for end in simple.end_pts_of_contours() {
+        let end = (usize::from(end.get()) + 1).min(points.len());
+        if end > start {
+            contours.push(Contour::from(points[start..end].to_vec()));
+            start = end;
+        }
+    }
+    if contours.is_empty() {
+        return WriteGlyph::Empty;
+    }
+
+    let mut glyph = SimpleGlyph {
+        bbox: Bbox::default(),
+        contours,
+        instructions: simple.instructions().to_vec(),
+    };
+    glyph.recompute_bounding_box();
+    WriteGlyph::Simple(glyph)
+}
+
 fn apply_deltas_to_composite_glyph(
     composite: &ReadCompositeGlyph,
     gvar: &Gvar,
     glyph_id: GlyphId,
     coords: &[F2Dot14],
+    integer_deltas: bool,
 ) -> Result<WriteGlyph> {
     use write_fonts::tables::glyf::{Anchor, Component, ComponentFlags, Transform};
 
@@ -446,7 +990,7 @@ fn apply_deltas_to_composite_glyph(
             for delta in tuple.deltas() {
                 let idx = delta.position as usize;
                 if let Some(point) = offsets.get_mut(idx) {
-                    let scaled: Point<Fixed> = delta.apply_scalar(scalar);
+                    let scaled = scaled_delta(delta, scalar, integer_deltas);
                     point.x += scaled.x;
                     point.y += scaled.y;
                 }
@@ -487,6 +1031,40 @@ fn apply_deltas_to_composite_glyph(
     Ok(tables::glyf::Glyph::Composite(new_composite))
 }
 
+/// `composite` followed by `instructions`, with WE_HAVE_INSTRUCTIONS set on
+/// its last component. write-fonts only carries instructions over from a
+/// parsed glyph, so the components are written out and parsed back.
+fn with_instructions(composite: &CompositeGlyph, instructions: &[u8]) -> Result<CompositeGlyph> {
+    let mut data = write_fonts::dump_table(composite)?;
+    // numberOfContours and the bounding box
+    let mut pos = 10;
+    loop {
+        let flags =
+            CompositeGlyphFlags::from_bits_truncate(u16::from_be_bytes([data[pos], data[pos + 1]]));
+        let args = if flags.contains(CompositeGlyphFlags::ARG_1_AND_2_ARE_WORDS) { 4 } else { 2 };
+        let transform = if flags.contains(CompositeGlyphFlags::WE_HAVE_A_TWO_BY_TWO) {
+            8
+        } else if flags.contains(CompositeGlyphFlags::WE_HAVE_AN_X_AND_Y_SCALE) {
+            4
+        } else if flags.contains(CompositeGlyphFlags::WE_HAVE_A_SCALE) {
+            2
+        } else {
+            0
+        };
+        let end = pos + 4 + args + transform;
+        if !flags.contains(CompositeGlyphFlags::MORE_COMPONENTS) {
+            let flags = flags | CompositeGlyphFlags::WE_HAVE_INSTRUCTIONS;
+            data[pos..pos + 2].copy_from_slice(&flags.bits().to_be_bytes());
+            data.truncate(end);
+            break;
+        }
+        pos = end;
+    }
+    data.extend_from_slice(&(instructions.len() as u16).to_be_bytes());
+    data.extend_from_slice(instructions);
+    Ok(ReadCompositeGlyph::read(FontData::new(&data))?.to_owned_table())
+}
+
 /// Recompute bounding boxes for composite glyphs from their components.
 ///
 /// This resolves composite glyph bboxes by transforming each component's bbox
@@ -594,7 +1172,7 @@ fn compute_composite_bbox(composite: &CompositeGlyph, bboxes: &[Option<Bbox>]) -
     Some(Bbox { x_min, y_min, x_max, y_max })
 }
 
-fn iup_contour(
+pub(crate) fn iup_contour(
     deltas: &mut [Point<i32>],
     has_delta: &[bool],
     points: &[Point<i32>],
@@ -694,6 +1272,15 @@ fn get_glyph_xmin(glyph: &WriteGlyph) -> Option<i16> {
     }
 }
 
+/// Get the yMax value from a glyph's bounding box.
+fn get_glyph_ymax(glyph: &WriteGlyph) -> Option<i16> {
+    match glyph {
+        tables::glyf::Glyph::Simple(s) => Some(s.bbox.y_max),
+        tables::glyf::Glyph::Composite(c) => Some(c.bbox.y_max),
+        tables::glyf::Glyph::Empty => None,
+    }
+}
+
 fn build_new_hmtx(advances: &[u16], lsbs: &[i16], num_h_metrics: usize) -> WriteHmtx {
     let num_glyphs = advances.len();
     let mut h_metrics = Vec::with_capacity(num_h_metrics);
@@ -713,6 +1300,19 @@ fn build_new_hmtx(advances: &[u16], lsbs: &[i16], num_h_metrics: usize) -> Write
     WriteHmtx { h_metrics, left_side_bearings }
 }
 
+fn build_new_vmtx(advances: &[u16], tsbs: &[i16], num_v_metrics: usize) -> WriteVmtx {
+    let split = num_v_metrics.min(advances.len());
+    let v_metrics = advances[..split]
+        .iter()
+        .zip(tsbs)
+        .map(|(&advance, &side_bearing)| LongMetric { advance, side_bearing })
+        .collect();
+    WriteVmtx {
+        v_metrics,
+        top_side_bearings: tsbs[split..].to_vec(),
+    }
+}
+
 /// Bounding box and metrics information calculated from glyph data.
 #[derive(Debug, Clone, Copy, Default)]
 struct FontBounds {
@@ -724,6 +1324,10 @@ struct FontBounds {
     min_right_side_bearing: i16,
     x_max_extent: i16,
     advance_width_max: u16,
+    min_top_side_bearing: i16,
+    min_bottom_side_bearing: i16,
+    y_max_extent: i16,
+    advance_height_max: u16,
 }
 
 impl FontBounds {
@@ -737,17 +1341,22 @@ impl FontBounds {
             min_right_side_bearing: i16::MAX,
             x_max_extent: i16::MIN,
             advance_width_max: 0,
+            min_top_side_bearing: i16::MAX,
+            min_bottom_side_bearing: i16::MAX,
+            y_max_extent: i16::MIN,
+            advance_height_max: 0,
         }
     }
 
     fn update(&mut self, glyph: &WriteGlyph, advance: u16, _orig_lsb: i16) {
         self.advance_width_max = self.advance_width_max.max(advance);
+        if let Some(bbox) = glyph_bbox(glyph) {
+            self.update_bbox(bbox, advance);
+        }
+    }
 
-        let bbox = match glyph {
-            tables::glyf::Glyph::Simple(s) => s.bbox,
-            tables::glyf::Glyph::Composite(c) => c.bbox,
-            tables::glyf::Glyph::Empty => return,
-        };
+    fn update_bbox(&mut self, bbox: Bbox, advance: u16) {
+        self.advance_width_max = self.advance_width_max.max(advance);
 
         if bbox.x_min == 0 && bbox.x_max == 0 && bbox.y_min == 0 && bbox.y_max == 0 {
             return;
@@ -771,6 +1380,33 @@ impl FontBounds {
         self.x_max_extent = self.x_max_extent.max(extent);
     }
 
+    fn update_vertical(&mut self, glyph: &WriteGlyph, advance_height: u16, tsb: i16) {
+        self.advance_height_max = self.advance_height_max.max(advance_height);
+        if let Some(bbox) = glyph_bbox(glyph) {
+            self.update_vertical_bbox(bbox, advance_height, tsb);
+        }
+    }
+
+    fn update_vertical_bbox(&mut self, bbox: Bbox, advance_height: u16, tsb: i16) {
+        self.advance_height_max = self.advance_height_max.max(advance_height);
+
+        if bbox.x_min == 0 && bbox.x_max == 0 && bbox.y_min == 0 && bbox.y_max == 0 {
+            return;
+        }
+
+        self.min_top_side_bearing = self.min_top_side_bearing.min(tsb);
+
+        // BSB = advance_height - TSB - glyph_height
+        let glyph_height = bbox.y_max.saturating_sub(bbox.y_min);
+        let bsb = (advance_height as i16)
+            .saturating_sub(tsb)
+            .saturating_sub(glyph_height);
+        self.min_bottom_side_bearing = self.min_bottom_side_bearing.min(bsb);
+
+        let extent = tsb.saturating_add(glyph_height);
+        self.y_max_extent = self.y_max_extent.max(extent);
+    }
+
     fn finalize(&mut self) {
         if self.x_min == i16::MAX {
             self.x_min = 0;
@@ -793,6 +1429,23 @@ impl FontBounds {
         if self.x_max_extent == i16::MIN {
             self.x_max_extent = 0;
         }
+        if self.min_top_side_bearing == i16::MAX {
+            self.min_top_side_bearing = 0;
+        }
+        if self.min_bottom_side_bearing == i16::MAX {
+            self.min_bottom_side_bearing = 0;
+        }
+        if self.y_max_extent == i16::MIN {
+            self.y_max_extent = 0;
+        }
+    }
+}
+
+fn glyph_bbox(glyph: &WriteGlyph) -> Option<Bbox> {
+    match glyph {
+        tables::glyf::Glyph::Simple(s) => Some(s.bbox),
+        tables::glyf::Glyph::Composite(c) => Some(c.bbox),
+        tables::glyf::Glyph::Empty => None,
     }
 }
 
@@ -829,6 +1482,34 @@ fn build_new_hhea(
     )
 }
 
+fn build_new_vhea(
+    original: &Vhea,
+    bounds: &FontBounds,
+    mvar: Option<&Mvar>,
+    coords: &[F2Dot14],
+) -> WriteVhea {
+    let ascender_delta = get_mvar_delta(mvar, VASC, coords);
+    let descender_delta = get_mvar_delta(mvar, VDSC, coords);
+    let line_gap_delta = get_mvar_delta(mvar, VLGP, coords);
+    let caret_slope_rise_delta = get_mvar_delta(mvar, VCRS, coords);
+    let caret_slope_run_delta = get_mvar_delta(mvar, VCRN, coords);
+    let caret_offset_delta = get_mvar_delta(mvar, VCOF, coords);
+
+    tables::vhea::Vhea::new(
+        clamp_i16(i32::from(original.ascender().to_i16()) + ascender_delta).into(),
+        clamp_i16(i32::from(original.descender().to_i16()) + descender_delta).into(),
+        clamp_i16(i32::from(original.line_gap().to_i16()) + line_gap_delta).into(),
+        bounds.advance_height_max.into(),
+        bounds.min_top_side_bearing.into(),
+        bounds.min_bottom_side_bearing.into(),
+        bounds.y_max_extent.into(),
+        clamp_i16(i32::from(original.caret_slope_rise()) + caret_slope_rise_delta),
+        clamp_i16(i32::from(original.caret_slope_run()) + caret_slope_run_delta),
+        clamp_i16(i32::from(original.caret_offset()) + caret_offset_delta),
+        original.number_of_long_ver_metrics(),
+    )
+}
+
 /// Convert wdth axis value (percentage, typically 50-200) to usWidthClass (1-9)
 fn wdth_to_width_class(wdth: f32) -> u16 {
     // OpenType spec usWidthClass values:
@@ -926,7 +1607,10 @@ fn build_new_post(original: &Post, mvar: Option<&Mvar>, coords: &[F2Dot14]) -> W
     post
 }
 
-fn build_new_stat(fvar: &Fvar, locations: &[AxisLocation]) -> Stat {
+/// Build a STAT table with one format 1 AxisValue per pinned axis.
+///
+/// With `precision`, values are rounded to that many decimal places first.
+fn build_new_stat(fvar: &Fvar, locations: &[AxisLocation], precision: Option<u8>) -> Stat {
     let Ok(axis_arrays) = fvar.axis_instance_arrays() else {
         return Stat::new(vec![], vec![], NameId::new(2));
     };
@@ -944,8 +1628,16 @@ fn build_new_stat(fvar: &Fvar, locations: &[AxisLocation]) -> Stat {
             let axis_index = axes.iter().position(|a| a.axis_tag() == loc.tag)?;
             let axis = axes.get(axis_index)?;
 
+            let value = match precision {
+                Some(digits) => {
+                    let scale = 10f64.powi(i32::from(digits));
+                    (f64::from(loc.value) * scale).round() / scale
+                }
+                None => f64::from(loc.value),
+            };
+
             let mut flags = AxisValueTableFlags::empty();
-            if loc.value == axis.default_value().to_f64() as f32 {
+            if value == axis.default_value().to_f64() {
                 flags |= AxisValueTableFlags::ELIDABLE_AXIS_VALUE_NAME;
             }
 
@@ -953,7 +1645,7 @@ fn build_new_stat(fvar: &Fvar, locations: &[AxisLocation]) -> Stat {
                 axis_index as u16,
                 flags,
                 axis.axis_name_id(),
-                Fixed::from_f64(f64::from(loc.value)),
+                Fixed::from_f64(value),
             ))
         })
         .collect();
@@ -961,6 +1653,65 @@ fn build_new_stat(fvar: &Fvar, locations: &[AxisLocation]) -> Stat {
     Stat::new(design_axes, axis_values, NameId::new(2))
 }
 
+/// The feature substitutions of the first FeatureVariation record whose
+/// conditions all hold at `normalized_coords`, which is the one a shaper
+/// applies there.
+fn matching_feature_substitution<'a>(
+    feature_variations: &FeatureVariations<'a>,
+    normalized_coords: &[F2Dot14],
+) -> Option<FeatureTableSubstitution<'a>> {
+    feature_variations
+        .feature_variation_records()
+        .iter()
+        .find_map(|record| {
+            let condition_set = record.condition_set(feature_variations.offset_data())?.ok()?;
+            let all_conditions_match = condition_set.conditions().iter().all(|cond| {
+                let Ok(Condition::Format1AxisRange(c)) = cond else {
+                    return false;
+                };
+                let Some(&coord) = normalized_coords.get(c.axis_index() as usize) else {
+                    return false;
+                };
+                coord >= c.filter_range_min_value() && coord <= c.filter_range_max_value()
+            });
+            if !all_conditions_match {
+                return None;
+            }
+            record
+                .feature_table_substitution(feature_variations.offset_data())?
+                .ok()
+        })
+}
+
+/// Replace the features that FeatureVariations select at `normalized_coords`
+/// in `feature_list`, so the static font shapes as the variable font did at
+/// that location.
+fn apply_feature_variations(
+    feature_variations: &FeatureVariations,
+    feature_list: &mut FeatureList,
+    normalized_coords: &[F2Dot14],
+) {
+    let Some(feature_table_subst) =
+        matching_feature_substitution(feature_variations, normalized_coords)
+    else {
+        return;
+    };
+    for subst_record in feature_table_subst.substitutions() {
+        let Some(feature_record) = feature_list
+            .feature_records
+            .get_mut(usize::from(subst_record.feature_index()))
+        else {
+            continue;
+        };
+        if let Ok(alternate_feature) =
+            subst_record.alternate_feature(feature_table_subst.offset_data())
+        {
+            let alternate_feature: Feature = alternate_feature.to_owned_table();
+            feature_record.feature.set(alternate_feature);
+        }
+    }
+}
+
 fn compute_feature_variation_substitutions(
     gsub: &Gsub,
     normalized_coords: &[F2Dot14],
@@ -969,104 +1720,75 @@ fn compute_feature_variation_substitutions(
     let lookup_list = gsub.lookup_list().ok()?;
     let feature_list = gsub.feature_list().ok()?;
 
-    for record in feature_variations.feature_variation_records() {
-        let Some(Ok(condition_set)) = record.condition_set(feature_variations.offset_data()) else {
+    let feature_table_subst =
+        matching_feature_substitution(&feature_variations, normalized_coords)?;
+
+    let mut substitutions = HashMap::new();
+
+    for subst_record in feature_table_subst.substitutions() {
+        let feature_index = subst_record.feature_index();
+
+        let Ok(feature_record) =
+            feature_list.feature_records().get(feature_index as usize).ok_or(())
+        else {
             continue;
         };
+        let feature_tag = feature_record.feature_tag();
 
-        let all_conditions_match = condition_set.conditions().iter().all(|cond| {
-            let Ok(Condition::Format1AxisRange(c)) = cond else {
-                return false;
-            };
-            let axis_index = c.axis_index() as usize;
-            if axis_index >= normalized_coords.len() {
-                return false;
-            }
-            let coord = normalized_coords[axis_index];
-            coord >= c.filter_range_min_value() && coord <= c.filter_range_max_value()
-        });
-
-        if !all_conditions_match {
+        if feature_tag != Tag::new(b"rvrn") {
             continue;
         }
 
-        let Some(Ok(feature_table_subst)) =
-            record.feature_table_substitution(feature_variations.offset_data())
+        let Ok(alternate_feature) =
+            subst_record.alternate_feature(feature_table_subst.offset_data())
         else {
             continue;
         };
 
-        let mut substitutions = HashMap::new();
-
-        for subst_record in feature_table_subst.substitutions() {
-            let feature_index = subst_record.feature_index();
-
-            let Ok(feature_record) =
-                feature_list.feature_records().get(feature_index as usize).ok_or(())
-            else {
-                continue;
-            };
-            let feature_tag = feature_record.feature_tag();
-
-            if feature_tag != Tag::new(b"rvrn") {
-                continue;
-            }
-
-            let Ok(alternate_feature) =
-                subst_record.alternate_feature(feature_table_subst.offset_data())
-            else {
+        for lookup_index in alternate_feature.lookup_list_indices() {
+            let lookup_idx = lookup_index.get() as usize;
+            let Ok(lookup) = lookup_list.lookups().get(lookup_idx) else {
                 continue;
             };
 
-            for lookup_index in alternate_feature.lookup_list_indices() {
-                let lookup_idx = lookup_index.get() as usize;
-                let Ok(lookup) = lookup_list.lookups().get(lookup_idx) else {
-                    continue;
-                };
-
-                if let SubstitutionLookup::Single(single_lookup) = lookup {
-                    for subtable_result in single_lookup.subtables().iter() {
-                        let Ok(subtable) = subtable_result else {
-                            continue;
-                        };
-                        match subtable {
-                            SingleSubst::Format1(f1) => {
-                                let Ok(coverage) = f1.coverage() else {
-                                    continue;
-                                };
-                                let delta = i32::from(f1.delta_glyph_id());
-                                for glyph in coverage.iter() {
+            if let SubstitutionLookup::Single(single_lookup) = lookup {
+                for subtable_result in single_lookup.subtables().iter() {
+                    let Ok(subtable) = subtable_result else {
+                        continue;
+                    };
+                    match subtable {
+                        SingleSubst::Format1(f1) => {
+                            let Ok(coverage) = f1.coverage() else {
+                                continue;
+                            };
+                            let delta = i32::from(f1.delta_glyph_id());
+                            for glyph in coverage.iter() {
+                                let from_gid = GlyphId::from(glyph);
+                                let to_gid =
+                                    GlyphId::new((from_gid.to_u32() as i32 + delta) as u32);
+                                substitutions.insert(from_gid, to_gid);
+                            }
+                        }
+                        SingleSubst::Format2(f2) => {
+                            let Ok(coverage) = f2.coverage() else {
+                                continue;
+                            };
+                            let substitute_glyph_ids = f2.substitute_glyph_ids();
+                            for (i, glyph) in coverage.iter().enumerate() {
+                                if let Some(substitute) = substitute_glyph_ids.get(i) {
                                     let from_gid = GlyphId::from(glyph);
-                                    let to_gid =
-                                        GlyphId::new((from_gid.to_u32() as i32 + delta) as u32);
+                                    let to_gid = GlyphId::from(substitute.get());
                                     substitutions.insert(from_gid, to_gid);
                                 }
                             }
-                            SingleSubst::Format2(f2) => {
-                                let Ok(coverage) = f2.coverage() else {
-                                    continue;
-                                };
-                                let substitute_glyph_ids = f2.substitute_glyph_ids();
-                                for (i, glyph) in coverage.iter().enumerate() {
-                                    if let Some(substitute) = substitute_glyph_ids.get(i) {
-                                        let from_gid = GlyphId::from(glyph);
-                                        let to_gid = GlyphId::from(substitute.get());
-                                        substitutions.insert(from_gid, to_gid);
-                                    }
-                                }
-                            }
                         }
                     }
                 }
             }
         }
-
-        if !substitutions.is_empty() {
-            return Some(substitutions);
-        }
     }
 
-    None
+    (!substitutions.is_empty()).then_some(substitutions)
 }
 
 fn build_instanced_cmap(
@@ -1152,6 +1874,352 @@ mod tests {
         assert!(output.hmtx().is_ok());
     }
 
+    #[test]
+    fn relative_delta_matches_absolute_location() {
+        let wght = Tag::new(b"wght");
+        let relative = instantiate_relative(VAZIRMATN_VAR, &[(wght, 300.0)]).unwrap();
+        let absolute = instantiate(VAZIRMATN_VAR, &[AxisLocation::new("wght", 700.0)]).unwrap();
+        assert_eq!(relative, absolute);
+
+        let clamped = instantiate_relative(VAZIRMATN_VAR, &[(wght, 1000.0)]).unwrap();
+        let max = instantiate(VAZIRMATN_VAR, &[AxisLocation::new("wght", 900.0)]).unwrap();
+        assert_eq!(clamped, max);
+    }
+
+    #[test]
+    fn unknown_axis_lists_available() {
+        let err = instantiate(VAZIRMATN_VAR, &[AxisLocation::new("wgth", 700.0)]).unwrap_err();
+        let Error::UnknownAxis { tag, available } = &err else {
+            panic!("expected UnknownAxis, got {err}");
+        };
+        assert_eq!(*tag, Tag::new(b"wgth"));
+        assert!(available.contains(&Tag::new(b"wght")));
+        assert_eq!(err.to_string(), "unknown axis wgth (available: wght)");
+    }
+
+    /// VAZIRMATN_VAR with a hand-built gvar whose only tuple (on glyph 1)
+    /// moves point 65535 by (10, 10).
+    fn font_with_out_of_range_delta() -> Vec<u8> {
+        #[rustfmt::skip]
+        let gvar: &[u8] = &[
+            0x00, 0x01, 0x00, 0x00,             // version 1.0
+            0x00, 0x01, 0x00, 0x00,             // axisCount 1, sharedTupleCount 0
+            0x00, 0x00, 0x00, 0x14,             // sharedTuplesOffset
+            0x00, 0x04, 0x00, 0x00,             // glyphCount 4, short offsets
+            0x00, 0x00, 0x00, 0x1E,             // glyphVariationDataArrayOffset
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x09, // offsets / 2: glyphs 0..=2
+            0x00, 0x09, 0x00, 0x09,             // glyph 3, end
+            // glyph 1: one tuple, serialized data at 10
+            0x00, 0x01, 0x00, 0x0A,
+            0x00, 0x08, 0xA0, 0x00, 0x40, 0x00, // size 8, embedded peak + private points, peak 1.0
+            0x01, 0x80, 0xFF, 0xFF,             // one point number: 65535 (word run)
+            0x00, 0x0A, 0x00, 0x0A,             // x delta 10, y delta 10
+        ];
+        let mut builder = FontBuilder::new();
+        builder.add_raw(Tag::new(b"gvar"), gvar);
+        builder.copy_missing_tables(FontRef::new(VAZIRMATN_VAR).unwrap());
+        builder.build()
+    }
+
+    #[test]
+    fn out_of_range_delta_position_is_ignored() {
+        let data = font_with_out_of_range_delta();
+        let result = instantiate(&data, &[AxisLocation::new("wght", 900.0)]).unwrap();
+
+        let default = instantiate(VAZIRMATN_VAR, &[AxisLocation::new("wght", 400.0)]).unwrap();
+        let output = FontRef::new(&result).unwrap();
+        let expected = FontRef::new(&default).unwrap();
+        assert_eq!(get_glyph_coords(&output, 1), get_glyph_coords(&expected, 1));
+    }
+
+    #[test]
+    fn hinting_dropped_unless_kept() {
+        let location = [AxisLocation::new("wght", 700.0)];
+        let gasp = Tag::new(b"gasp");
+
+        let dropped = instantiate(VAZIRMATN_VAR, &location).unwrap();
+        assert!(FontRef::new(&dropped).unwrap().table_data(gasp).is_none());
+
+        let options = InstanceOptions::new().keep_hinting(true);
+        let kept = instantiate_with_options(VAZIRMATN_VAR, &location, &options).unwrap();
+        let font = FontRef::new(VAZIRMATN_VAR).unwrap();
+        assert_eq!(
+            FontRef::new(&kept).unwrap().table_data(gasp).unwrap().as_bytes(),
+            font.table_data(gasp).unwrap().as_bytes()
+        );
+    }
+
+    #[test]
+    fn composite_instructions_follow_hinting() {
+        let location = [AxisLocation::new("wght", 700.0)];
+        let composite_of = |data: &[u8]| {
+            let font = FontRef::new(data).unwrap();
+            let glyf = font.glyf().unwrap();
+            let glyph = font.loca(None).unwrap().get_glyf(GlyphId::new(2), &glyf).unwrap();
+            let Some(Glyph::Composite(composite)) = glyph else {
+                panic!("glyph 2 is not a composite");
+            };
+            let has_flag = composite
+                .components()
+                .last()
+                .is_some_and(|c| c.flags.contains(CompositeGlyphFlags::WE_HAVE_INSTRUCTIONS));
+            (composite.instructions().unwrap_or_default().to_vec(), has_flag)
+        };
+        let (source, _) = composite_of(VAZIRMATN_VAR);
+        assert!(!source.is_empty());
+
+        let dropped = instantiate(VAZIRMATN_VAR, &location).unwrap();
+        assert_eq!(composite_of(&dropped), (Vec::new(), false));
+
+        let options = InstanceOptions::new().keep_hinting(true);
+        let kept = instantiate_with_options(VAZIRMATN_VAR, &location, &options).unwrap();
+        assert_eq!(composite_of(&kept), (source, true));
+    }
+
+    /// VAZIRMATN_VAR without avar, with glyph 1 moved by two wght tuples of
+    /// (3, -3) and (1, -1) units at every point.
+    fn two_tuple_font() -> Vec<u8> {
+        use tables::gvar::{
+            GlyphDelta as WriteDelta, GlyphDeltas, GlyphVariations, Gvar as WriteGvar, Tent,
+        };
+
+        let font = FontRef::new(VAZIRMATN_VAR).unwrap();
+        let count = get_glyph_coords(&font, 1).unwrap().len() + PHANTOM_POINTS;
+        let tuple = |x, y| {
+            let tent = Tent::new(F2Dot14::ONE, None);
+            GlyphDeltas::new(vec![tent], vec![WriteDelta::required(x, y); count])
+        };
+        let variations = (0..font.maxp().unwrap().num_glyphs())
+            .map(|gid| {
+                let tuples = if gid == 1 { vec![tuple(3, -3), tuple(1, -1)] } else { Vec::new() };
+                GlyphVariations::new(GlyphId::from(gid), tuples)
+            })
+            .collect();
+        let gvar = WriteGvar::new(variations, 1).unwrap();
+
+        let mut builder = FontBuilder::new();
+        builder.add_table(&gvar).unwrap();
+        for record in font.table_directory().table_records() {
+            let tag = record.tag();
+            if !builder.contains(tag) && tag != Tag::new(b"avar") {
+                builder.add_raw(tag, font.table_data(tag).unwrap());
+            }
+        }
+        builder.build()
+    }
+
+    #[test]
+    fn integer_deltas_round_each_tuple() {
+        let data = two_tuple_font();
+        // wght=650 normalizes to 0.5, halving both tuples to (1.5, -1.5)
+        // and (0.5, -0.5)
+        let location = [AxisLocation::new("wght", 650.0)];
+        let shift = |options: &InstanceOptions| {
+            let result = instantiate_with_options(&data, &location, options).unwrap();
+            let moved = get_glyph_coords(&FontRef::new(&result).unwrap(), 1).unwrap();
+            let orig = get_glyph_coords(&FontRef::new(&data).unwrap(), 1).unwrap();
+            (moved[0].0 - orig[0].0, moved[0].1 - orig[0].1)
+        };
+
+        // Fixed: 1.5 + 0.5 = 2 and -1.5 - 0.5 = -2, rounded once
+        assert_eq!(shift(&InstanceOptions::new()), (2, -2));
+        // Integer: otRound(1.5) + otRound(0.5) = 2 + 1 and
+        // otRound(-1.5) + otRound(-0.5) = -1 + 0, the fontTools reference
+        assert_eq!(shift(&InstanceOptions::new().integer_deltas(true)), (3, -1));
+    }
+
+    /// VAZIRMATN_VAR with glyph 1's first two contour end points swapped, so
+    /// they no longer increase.
+    fn font_with_bad_glyph() -> Vec<u8> {
+        let font = FontRef::new(VAZIRMATN_VAR).unwrap();
+        let loca = font.loca(None).unwrap();
+        let start = loca.get_raw(1).unwrap() as usize;
+        let mut glyf = font.table_data(Tag::new(b"glyf")).unwrap().as_bytes().to_vec();
+        // endPtsOfContours follows the 10-byte glyph header
+        let end_pts = start + 10;
+        assert!(i16::from_be_bytes([glyf[start], glyf[start + 1]]) >= 2);
+        let (first, second) = glyf[end_pts..end_pts + 4].split_at_mut(2);
+        first.swap_with_slice(second);
+
+        let mut builder = FontBuilder::new();
+        builder.add_raw(Tag::new(b"glyf"), glyf);
+        for record in font.table_directory().table_records() {
+            let tag = record.tag();
+            if !builder.contains(tag) {
+                builder.add_raw(tag, font.table_data(tag).unwrap());
+            }
+        }
+        builder.build()
+    }
+
+    #[test]
+    fn skip_bad_glyphs_keeps_default_outline() {
+        let data = font_with_bad_glyph();
+        let location = [AxisLocation::new("wght", 700.0)];
+        let options = InstanceOptions::new().skip_bad_glyphs(true);
+        let (result, report) = instantiate_with_report(&data, &location, &options).unwrap();
+        assert_eq!(report.skipped_glyphs, [GlyphId::new(1)]);
+
+        // Other glyphs are instanced as usual
+        let font = FontRef::new(&result).unwrap();
+        let expected = instantiate(VAZIRMATN_VAR, &location).unwrap();
+        let expected = FontRef::new(&expected).unwrap();
+        for gid in 2..font.maxp().unwrap().num_glyphs() as u32 {
+            assert_eq!(get_glyph_coords(&font, gid), get_glyph_coords(&expected, gid));
+        }
+        assert!(get_glyph_coords(&font, 1).is_some_and(|coords| !coords.is_empty()));
+    }
+
+    #[test]
+    fn disordered_contour_ends_are_dropped() {
+        let data = font_with_bad_glyph();
+        let location = [AxisLocation::new("wght", 700.0)];
+        let (result, report) =
+            instantiate_with_report(&data, &location, &InstanceOptions::new()).unwrap();
+        assert!(report.skipped_glyphs.is_empty());
+
+        // The swapped first end now precedes the second and delimits nothing
+        let contours = |data: &[u8]| {
+            let font = FontRef::new(data).unwrap();
+            let glyph = font
+                .loca(None)
+                .unwrap()
+                .get_glyf(GlyphId::new(1), &font.glyf().unwrap());
+            match glyph.unwrap() {
+                Some(Glyph::Simple(simple)) => simple.end_pts_of_contours().len(),
+                _ => panic!("glyph 1 is not simple"),
+            }
+        };
+        assert_eq!(contours(&result), contours(VAZIRMATN_VAR) - 1);
+    }
+
+    #[test]
+    fn head_overrides() {
+        let location = [AxisLocation::new("wght", 700.0)];
+        let source = FontRef::new(VAZIRMATN_VAR).unwrap().head().unwrap();
+
+        let result = instantiate(VAZIRMATN_VAR, &location).unwrap();
+        let head = FontRef::new(&result).unwrap().head().unwrap();
+        assert_eq!(head.lowest_rec_ppem(), source.lowest_rec_ppem());
+        assert_eq!(head.flags(), source.flags());
+
+        let options = InstanceOptions::new().lowest_rec_ppem(6).integer_ppem(false);
+        let result = instantiate_with_options(VAZIRMATN_VAR, &location, &options).unwrap();
+        let head = FontRef::new(&result).unwrap().head().unwrap();
+        assert_eq!(head.lowest_rec_ppem(), 6);
+        assert!(!head.flags().contains(Flags::FORCE_INTEGER_PPEM));
+    }
+
+    #[test]
+    fn many_matches_individual_instances() {
+        let instances: Vec<(String, Vec<AxisLocation>)> = [100.0, 400.0, 700.0]
+            .into_iter()
+            .map(|wght| (format!("wght{wght}"), vec![AxisLocation::new("wght", wght)]))
+            .collect();
+
+        let many = instantiate_many(VAZIRMATN_VAR, &instances).unwrap();
+        assert_eq!(many.len(), instances.len());
+        for ((name, data), (expected_name, locations)) in many.iter().zip(&instances) {
+            assert_eq!(name, expected_name);
+            assert_eq!(data, &instantiate(VAZIRMATN_VAR, locations).unwrap());
+        }
+    }
+
+    /// VAZIRMATN_VAR with a GSUB whose `rvrn` feature is empty by default and
+    /// substitutes glyph 1 ('A') with glyph 2 from wght 650 (normalized 0.5)
+    /// up.
+    fn font_with_heavy_rvrn() -> Vec<u8> {
+        use write_fonts::{
+            tables::{
+                gsub::{SingleSubst, SubstitutionLookup},
+                layout::{
+                    Condition, ConditionSet, FeatureRecord, FeatureTableSubstitution,
+                    FeatureTableSubstitutionRecord, FeatureVariationRecord, FeatureVariations,
+                    LangSys, Lookup, LookupFlag, LookupList, Script, ScriptList, ScriptRecord,
+                    builders::CoverageTableBuilder,
+                },
+            },
+            types::GlyphId16,
+        };
+
+        let rvrn = Tag::new(b"rvrn");
+        let coverage = CoverageTableBuilder::from_glyphs(vec![GlyphId16::new(1)]).build();
+        let lookup = SubstitutionLookup::Single(Lookup::new(
+            LookupFlag::empty(),
+            vec![SingleSubst::format_2(coverage, vec![GlyphId16::new(2)])],
+        ));
+        let mut gsub = WriteGsub::new(
+            ScriptList::new(vec![ScriptRecord::new(
+                Tag::new(b"DFLT"),
+                Script::new(Some(LangSys::new(vec![0])), vec![]),
+            )]),
+            FeatureList::new(vec![FeatureRecord::new(rvrn, Feature::new(None, vec![]))]),
+            LookupList::new(vec![lookup]),
+        );
+        let condition =
+            Condition::format_1_axis_range(0, F2Dot14::from_f32(0.5), F2Dot14::from_f32(1.0));
+        gsub.feature_variations
+            .set(FeatureVariations::new(vec![FeatureVariationRecord::new(
+                Some(ConditionSet::new(vec![condition])),
+                Some(FeatureTableSubstitution::new(vec![FeatureTableSubstitutionRecord::new(
+                    0,
+                    Feature::new(None, vec![0]),
+                )])),
+            )]));
+
+        let mut builder = FontBuilder::new();
+        builder.add_table(&gsub).unwrap();
+        builder.copy_missing_tables(FontRef::new(VAZIRMATN_VAR).unwrap());
+        builder.build()
+    }
+
+    #[test]
+    fn feature_variations_applied_at_location() {
+        let data = font_with_heavy_rvrn();
+        let rvrn_lookups = |wght: f32| {
+            let result = instantiate(&data, &[AxisLocation::new("wght", wght)]).unwrap();
+            let font = FontRef::new(&result).unwrap();
+            let gsub = font.gsub().unwrap();
+            assert!(gsub.feature_variations().is_none());
+            let feature_list = gsub.feature_list().unwrap();
+            let record = &feature_list.feature_records()[0];
+            let feature = record.feature(feature_list.offset_data()).unwrap();
+            let lookups: Vec<u16> = feature
+                .lookup_list_indices()
+                .iter()
+                .map(|index| index.get())
+                .collect();
+            (font.cmap().unwrap().map_codepoint('A'), lookups)
+        };
+
+        assert_eq!(rvrn_lookups(400.0), (Some(GlyphId::new(1)), vec![]));
+        assert_eq!(rvrn_lookups(900.0), (Some(GlyphId::new(2)), vec![0]));
+    }
+
+    #[test]
+    fn stat_precision_rounds_axis_value() {
+        use read_fonts::tables::stat::AxisValue;
+
+        let stat_value = |options: &InstanceOptions| {
+            let location = [AxisLocation::new("wght", 400.0001)];
+            let result = instantiate_with_options(VAZIRMATN_VAR, &location, options).unwrap();
+            let font = FontRef::new(&result).unwrap();
+            let stat = font.stat().unwrap();
+            let values = stat.offset_to_axis_values().unwrap().unwrap();
+            match values.axis_values().get(0).unwrap() {
+                AxisValue::Format1(value) => (value.value(), value.flags()),
+                _ => panic!("expected a format 1 AxisValue"),
+            }
+        };
+
+        let (raw, _) = stat_value(&InstanceOptions::new());
+        assert_ne!(raw, Fixed::from_i32(400));
+
+        let (rounded, flags) = stat_value(&InstanceOptions::new().stat_precision(0));
+        assert_eq!(rounded, Fixed::from_i32(400));
+        assert!(flags.contains(AxisValueTableFlags::ELIDABLE_AXIS_VALUE_NAME));
+    }
+
     #[test]
     fn instantiate_at_min() {
         let data = VAZIRMATN_VAR;
@@ -1172,6 +2240,23 @@ mod tests {
         assert!(get_glyph_coords(&output, 1).is_some());
     }
 
+    #[test]
+    fn rejects_out_of_range_location() {
+        let err = instantiate(VAZIRMATN_VAR, &[AxisLocation::new("wght", 1200.0)]).unwrap_err();
+        let Error::AxisOutOfRange { tag, value, min, max } = err else {
+            panic!("expected AxisOutOfRange, got {err}");
+        };
+        assert_eq!((tag, value, min, max), (Tag::new(b"wght"), 1200.0, 100.0, 900.0));
+    }
+
+    #[test]
+    fn clamped_pins_at_nearest_bound() {
+        let clamped =
+            instantiate_clamped(VAZIRMATN_VAR, &[AxisLocation::new("wght", 1200.0)]).unwrap();
+        let max = instantiate(VAZIRMATN_VAR, &[AxisLocation::new("wght", 900.0)]).unwrap();
+        assert_eq!(clamped, max);
+    }
+
     #[test]
     fn preserves_glyph_count() {
         let data = VAZIRMATN_VAR;
@@ -1201,11 +2286,143 @@ mod tests {
         assert_ne!(aw_min, aw_max);
     }
 
+    /// Outline commands of every glyph, drawn by skrifa at `location`
+    fn skrifa_outlines(data: &[u8], location: &[(&str, f32)]) -> Vec<Vec<(char, Vec<f32>)>> {
+        use skrifa::{
+            MetadataProvider,
+            instance::Size,
+            outline::{DrawSettings, OutlinePen},
+        };
+
+        #[derive(Default)]
+        struct Recorder(Vec<(char, Vec<f32>)>);
+        impl OutlinePen for Recorder {
+            fn move_to(&mut self, x: f32, y: f32) {
+                self.0.push(('M', vec![x, y]));
+            }
+            fn line_to(&mut self, x: f32, y: f32) {
+                self.0.push(('L', vec![x, y]));
+            }
+            fn quad_to(&mut self, cx0: f32, cy0: f32, x: f32, y: f32) {
+                self.0.push(('Q', vec![cx0, cy0, x, y]));
+            }
+            fn curve_to(&mut self, cx0: f32, cy0: f32, cx1: f32, cy1: f32, x: f32, y: f32) {
+                self.0.push(('C', vec![cx0, cy0, cx1, cy1, x, y]));
+            }
+            fn close(&mut self) {
+                self.0.push(('Z', vec![]));
+            }
+        }
+
+        let font = skrifa::FontRef::new(data).unwrap();
+        let location = font.axes().location(location.iter().copied());
+        let outlines = font.outline_glyphs();
+        (0..font.maxp().unwrap().num_glyphs())
+            .map(|gid| {
+                let mut pen = Recorder::default();
+                let settings = DrawSettings::unhinted(Size::unscaled(), &location);
+                outlines
+                    .get(GlyphId::new(gid.into()))
+                    .unwrap()
+                    .draw(settings, &mut pen)
+                    .unwrap();
+                pen.0
+            })
+            .collect()
+    }
+
+    #[test]
+    fn instances_cff2_font() {
+        use skrifa::{MetadataProvider, instance::Size};
+
+        let source = skrifa::FontRef::new(CANTARELL_VF_TRIMMED).unwrap();
+        for wght in [100.0, 400.0, 800.0] {
+            let result = instantiate(CANTARELL_VF_TRIMMED, &[AxisLocation::new("wght", wght)]);
+            let data = result.unwrap();
+            let font = FontRef::new(&data).unwrap();
+            assert!(font.cff().is_ok());
+            assert!(font.table_data(Tag::new(b"CFF2")).is_none());
+            assert!(font.fvar().is_err());
+
+            let expected = skrifa_outlines(CANTARELL_VF_TRIMMED, &[("wght", wght)]);
+            let actual = skrifa_outlines(&data, &[]);
+            assert_eq!(actual.len(), expected.len());
+            for (glyph, (actual, expected)) in actual.iter().zip(&expected).enumerate() {
+                let ops = |commands: &[(char, Vec<f32>)]| -> String {
+                    commands.iter().map(|(op, _)| *op).collect()
+                };
+                assert_eq!(ops(actual), ops(expected), "glyph {glyph} at wght={wght}");
+                let coords = actual.iter().zip(expected).flat_map(|(a, e)| a.1.iter().zip(&e.1));
+                for (a, e) in coords {
+                    assert!((a - e).abs() <= 0.5, "glyph {glyph} at wght={wght}: {a} vs {e}");
+                }
+            }
+
+            let location = source.axes().location([("wght", wght)]);
+            let metrics = source.glyph_metrics(Size::unscaled(), &location);
+            let hmtx = font.hmtx().unwrap();
+            for gid in 0..font.maxp().unwrap().num_glyphs() {
+                let gid = GlyphId::new(gid.into());
+                let expected = metrics.advance_width(gid).unwrap().round() as u16;
+                assert_eq!(hmtx.advance(gid), Some(expected), "advance of {gid} at wght={wght}");
+            }
+        }
+    }
+
+    #[test]
+    fn drop_cff_hints_strips_hint_operators() {
+        use read_fonts::ps::{cff::CffFontRef, cs::CommandSink};
+
+        /// Counts stem, hintmask and cntrmask operators
+        #[derive(Default)]
+        struct HintCounter(usize);
+
+        impl CommandSink for HintCounter {
+            fn move_to(&mut self, _: Fixed, _: Fixed) {}
+            fn line_to(&mut self, _: Fixed, _: Fixed) {}
+            fn curve_to(&mut self, _: Fixed, _: Fixed, _: Fixed, _: Fixed, _: Fixed, _: Fixed) {}
+            fn close(&mut self) {}
+            fn hstem(&mut self, _: Fixed, _: Fixed) {
+                self.0 += 1;
+            }
+            fn vstem(&mut self, _: Fixed, _: Fixed) {
+                self.0 += 1;
+            }
+            fn hint_mask(&mut self, _: &[u8]) {
+                self.0 += 1;
+            }
+            fn counter_mask(&mut self, _: &[u8]) {
+                self.0 += 1;
+            }
+        }
+
+        let hints = |data: &[u8]| -> usize {
+            let font = FontRef::new(data).unwrap();
+            let cff = CffFontRef::new_cff(font.table_data(CFF).unwrap().as_bytes(), 0, None);
+            let cff = cff.unwrap();
+            let mut counter = HintCounter::default();
+            for gid in 0..cff.num_glyphs() {
+                let gid = GlyphId::new(gid);
+                let subfont = cff.subfont(cff.subfont_index(gid).unwrap_or(0), &[]).unwrap();
+                cff.evaluate_charstring(&subfont, gid, &[], &mut counter).unwrap();
+            }
+            counter.0
+        };
+
+        let location = [AxisLocation::new("wght", 700.0)];
+        let hinted = instantiate(CANTARELL_VF_TRIMMED, &location).unwrap();
+        assert!(hints(&hinted) > 0);
+
+        let options = InstanceOptions::new().drop_cff_hints(true);
+        let unhinted = instantiate_with_options(CANTARELL_VF_TRIMMED, &location, &options).unwrap();
+        assert_eq!(hints(&unhinted), 0);
+        assert_eq!(skrifa_outlines(&unhinted, &[]), skrifa_outlines(&hinted, &[]));
+    }
+
     #[test]
-    fn rejects_cff_font() {
-        let data = CANTARELL_VF_TRIMMED;
-        let result = instantiate(data, &[AxisLocation::new("wght", 700.0)]);
-        assert!(matches!(result, Err(Error::NoCff2Support)));
+    fn cff2_rejected_for_partial() {
+        let result = instantiate_partial(CANTARELL_VF_TRIMMED, &[], &[Tag::new(b"wght")]);
+        assert!(matches!(result, Err(Error::UnsupportedForPartial(tag)) if tag == CFF2));
     }
 
     #[test]
@@ -1266,4 +2483,78 @@ mod tests {
             }
         }
     }
+
+    /// VAZIRMATN_VAR with vertical metrics: advance height `1000 + 10 * gid`,
+    /// tsb 50, and a stale advanceHeightMax.
+    fn font_with_vertical_metrics() -> Vec<u8> {
+        let font = FontRef::new(VAZIRMATN_VAR).unwrap();
+        let num_glyphs = font.maxp().unwrap().num_glyphs();
+        let v_metrics = (0..num_glyphs)
+            .map(|gid| LongMetric { advance: 1000 + 10 * gid, side_bearing: 50 })
+            .collect();
+        let vmtx = WriteVmtx { v_metrics, top_side_bearings: vec![] };
+        let vhea = WriteVhea::new(
+            500.into(),
+            (-500).into(),
+            0.into(),
+            1.into(),
+            0.into(),
+            0.into(),
+            0.into(),
+            0,
+            1,
+            0,
+            num_glyphs,
+        );
+
+        let mut builder = FontBuilder::new();
+        builder.add_table(&vhea).unwrap();
+        builder.add_table(&vmtx).unwrap();
+        builder.copy_missing_tables(font);
+        builder.build()
+    }
+
+    #[test]
+    fn vhea_advance_height_max_matches_vmtx() {
+        let data = font_with_vertical_metrics();
+        let result = instantiate(&data, &[AxisLocation::new("wght", 900.0)]).unwrap();
+        let font = FontRef::new(&result).unwrap();
+
+        let vmtx = font.vmtx().unwrap();
+        let num_glyphs = font.maxp().unwrap().num_glyphs();
+        let max_advance = (0..num_glyphs)
+            .filter_map(|gid| vmtx.advance(GlyphId::new(u32::from(gid))))
+            .max()
+            .unwrap();
+        assert_eq!(font.vhea().unwrap().advance_height_max().to_u16(), max_advance);
+
+        // Advance heights follow the top/bottom phantom points, and the top
+        // origin (yMax + tsb) moves only with the top phantom point.
+        let source = FontRef::new(&data).unwrap();
+        let (glyf, loca, gvar) =
+            (source.glyf().unwrap(), source.loca(None).unwrap(), source.gvar().unwrap());
+        let y_max = |font: &FontRef, gid: GlyphId| {
+            let glyph = font.loca(None).unwrap().get_glyf(gid, &font.glyf().unwrap()).unwrap();
+            glyph.map(|glyph| glyph.y_max())
+        };
+        let mut moved = 0;
+        for gid in (0..num_glyphs).map(|gid| GlyphId::new(u32::from(gid))) {
+            let [_, _, top, bottom] = gvar
+                .phantom_point_deltas(&glyf, &loca, &[F2Dot14::ONE], gid)
+                .unwrap()
+                .unwrap_or_default();
+            let (top, bottom) = (top.y.to_i32(), bottom.y.to_i32());
+            let expected_advance = 1000 + 10 * gid.to_u32() as i32 + top - bottom;
+            assert_eq!(i32::from(vmtx.advance(gid).unwrap()), expected_advance);
+
+            let (Some(new_y_max), Some(source_y_max)) = (y_max(&font, gid), y_max(&source, gid))
+            else {
+                continue;
+            };
+            moved += usize::from(new_y_max != source_y_max);
+            let tsb = i32::from(vmtx.side_bearing(gid).unwrap());
+            assert_eq!(tsb + i32::from(new_y_max), i32::from(source_y_max) + 50 + top);
+        }
+        assert!(moved > 0);
+    }
 }
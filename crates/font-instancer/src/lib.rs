@@ -2,7 +2,7 @@
 //!
 //! Convert variable fonts to static instances.
 //!
-//! A Rust port of fonttools varLib.instancer for TrueType outline fonts.
+//! A Rust port of fonttools varLib.instancer for TrueType and CFF2 outline fonts.
 //!
 //! ## Example
 //!
@@ -30,13 +30,37 @@
 //!     .collect();
 //! let static_font = instantiate(&vf_data, &location).unwrap();
 //! ```
+//!
+//! ## Limitations
+//!
+//! `glyf` outlines are instanced from `gvar`; CFF2 outlines are evaluated at
+//! the location and written as a static `CFF ` table, keeping their stem
+//! hints unless [`InstanceOptions::drop_cff_hints`] is set. Partial
+//! instancing supports `glyf` fonts only.
 
+mod cff2;
+mod collection;
 mod error;
 mod instancer;
+mod named_instances;
+mod partial;
+mod plan;
+mod validate;
+#[cfg(feature = "woff2")]
+mod woff2;
 
+pub use collection::{extract_from_collection, instantiate_from_collection};
 pub use error::{Error, Result};
-pub use instancer::instantiate;
-use read_fonts::types::Tag;
+pub use instancer::{
+    instantiate, instantiate_clamped, instantiate_many, instantiate_partial, instantiate_relative,
+    instantiate_with_options, instantiate_with_report,
+};
+pub use named_instances::{NAMED_INSTANCES_TAG, NamedInstance, list_named_instances};
+pub use plan::{TablePlan, plan_tables};
+use read_fonts::types::{GlyphId, Tag};
+pub use validate::validate_font;
+#[cfg(feature = "woff2")]
+pub use woff2::instantiate_woff2;
 
 /// Axis location specification (tag + user-space value).
 ///
@@ -78,6 +102,109 @@ impl From<(Tag, f32)> for AxisLocation {
     }
 }
 
+/// Options for [`instantiate_with_options`].
+#[derive(Debug, Clone, Default)]
+pub struct InstanceOptions {
+    /// Decimal places kept in STAT AxisValue locations; `None` writes the
+    /// requested location as given
+    pub stat_precision: Option<u8>,
+    /// Keep `cvt `, `fpgm`, `prep`, `gasp` and glyph instructions. They are
+    /// dropped by default because gvar deltas leave the control values tuned
+    /// to the default outlines, which causes artifacts at small sizes.
+    pub keep_hinting: bool,
+    /// Record the source `fvar` named instances in a
+    /// [`NAMED_INSTANCES_TAG`] table, readable with [`list_named_instances`].
+    /// Leave unset for fonts that ship; the table is private.
+    pub keep_named_instances: bool,
+    /// Round each gvar tuple's scaled delta to a whole unit, half up like
+    /// fontTools' `otRound`, before adding it, instead of summing at 16.16
+    /// precision and rounding once. Matches the output of tools that
+    /// accumulate integer deltas, at up to half a unit of error per tuple.
+    pub integer_deltas: bool,
+    /// Keep a glyph's default outline when its deltas cannot be applied,
+    /// logging a warning, instead of failing the whole instance. Glyphs with
+    /// out-of-order contour end points count as bad; without this option
+    /// they are instanced with the contours those ends leave empty dropped.
+    pub skip_bad_glyphs: bool,
+    /// Leave stem hints and hint/counter masks out of the charstrings of a
+    /// CFF2 font's instanced `CFF ` table
+    pub drop_cff_hints: bool,
+    /// `head.lowestRecPPEM` for the instance; `None` copies the source value
+    pub lowest_rec_ppem: Option<u16>,
+    /// Set or clear `head.flags` bit 3 (force integer ppem); `None` copies
+    /// the source flag
+    pub integer_ppem: Option<bool>,
+}
+
+impl InstanceOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Round STAT AxisValue locations to `digits` decimal places, so
+    /// `wght=399.9999` is recorded as 400 with a precision of 0.
+    pub fn stat_precision(mut self, digits: u8) -> Self {
+        self.stat_precision = Some(digits);
+        self
+    }
+
+    /// Copy hinting tables and glyph instructions through unchanged.
+    pub fn keep_hinting(mut self, keep: bool) -> Self {
+        self.keep_hinting = keep;
+        self
+    }
+
+    /// Carry the source font's named instances into the static instance, so
+    /// a build script can still enumerate them.
+    pub fn keep_named_instances(mut self, keep: bool) -> Self {
+        self.keep_named_instances = keep;
+        self
+    }
+
+    /// Accumulate glyph deltas at integer precision, see
+    /// [`integer_deltas`](Self::integer_deltas).
+    pub fn integer_deltas(mut self, integer: bool) -> Self {
+        self.integer_deltas = integer;
+        self
+    }
+
+    /// Fall back to the default-master outline of glyphs that fail to
+    /// instance; they are listed in the [`InstanceReport`].
+    pub fn skip_bad_glyphs(mut self, skip: bool) -> Self {
+        self.skip_bad_glyphs = skip;
+        self
+    }
+
+    /// Strip `hstem`/`vstem`/`hintmask`/`cntrmask` operators when instancing
+    /// a CFF2 font, whose hints are tuned to the default master.
+    pub fn drop_cff_hints(mut self, drop: bool) -> Self {
+        self.drop_cff_hints = drop;
+        self
+    }
+
+    /// Override `head.lowestRecPPEM`, which a hinted source often sets
+    /// higher than an unhinted instance needs.
+    pub fn lowest_rec_ppem(mut self, ppem: u16) -> Self {
+        self.lowest_rec_ppem = Some(ppem);
+        self
+    }
+
+    /// Override the `head` flag that forces integer ppem sizes, which only
+    /// matters while the instance keeps its hinting.
+    pub fn integer_ppem(mut self, integer: bool) -> Self {
+        self.integer_ppem = Some(integer);
+        self
+    }
+}
+
+/// What [`instantiate_with_report`] had to work around.
+#[derive(Debug, Clone, Default)]
+pub struct InstanceReport {
+    /// Glyphs left at their default outline by
+    /// [`skip_bad_glyphs`](InstanceOptions::skip_bad_glyphs)
+    pub skipped_glyphs: Vec<GlyphId>,
+}
+
 /// Instantiate a variable font from axis name/value pairs.
 ///
 /// This is a convenience wrapper around [`instantiate`] that accepts
@@ -5,8 +5,10 @@ use std::{
     process::ExitCode,
 };
 
-use clap::Parser;
-use font_instancer::{AxisLocation, instantiate};
+use clap::{Parser, ValueEnum};
+use font_instancer::{
+    AxisLocation, InstanceOptions, extract_from_collection, instantiate_with_report,
+};
 use skrifa::{FontRef, MetadataProvider};
 
 #[derive(Debug, thiserror::Error)]
@@ -33,6 +35,10 @@ struct Cli {
     /// Axis locations as TAG=VALUE (e.g., wght=700)
     locations: Vec<String>,
 
+    /// Face index when INPUT is a TrueType Collection
+    #[arg(long, default_value_t = 0)]
+    face_index: usize,
+
     /// Output file (default: INPUT-instance.ttf)
     #[arg(short, long)]
     output: Option<PathBuf>,
@@ -44,6 +50,23 @@ struct Cli {
     /// Show font axes info
     #[arg(long)]
     info: bool,
+
+    /// Precision of glyph delta accumulation; `integer` rounds each delta
+    /// like fontTools
+    #[arg(long, value_enum, default_value_t = Precision::Fixed)]
+    precision: Precision,
+
+    /// Keep the default outline of glyphs whose deltas cannot be applied
+    #[arg(long)]
+    skip_bad_glyphs: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Precision {
+    /// Sum deltas at 16.16 precision and round once
+    Fixed,
+    /// Round each tuple's delta to a whole unit
+    Integer,
 }
 
 fn main() -> ExitCode {
@@ -66,7 +89,11 @@ fn run(cli: Cli) -> Result<(), CliError> {
     let locations = parse_locations(&cli.locations)?;
 
     let data = read(&cli.input)?;
-    let result = instantiate(&data, &locations)?;
+    let options = InstanceOptions::new()
+        .integer_deltas(cli.precision == Precision::Integer)
+        .skip_bad_glyphs(cli.skip_bad_glyphs);
+    let face = extract_from_collection(&data, cli.face_index)?;
+    let (result, report) = instantiate_with_report(&face, &locations, &options)?;
 
     let output = cli.output.unwrap_or_else(|| {
         let stem = cli.input.file_stem().unwrap().to_string_lossy();
@@ -75,6 +102,9 @@ fn run(cli: Cli) -> Result<(), CliError> {
 
     write(&output, result)?;
     if !cli.quiet {
+        if !report.skipped_glyphs.is_empty() {
+            println!("Kept default outlines of {} bad glyph(s)", report.skipped_glyphs.len());
+        }
         println!("Wrote {}", output.display());
     }
 
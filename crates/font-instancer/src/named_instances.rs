@@ -0,0 +1,168 @@
+//! Named instances of a variable font, and the sidecar table that carries
+//! them into static instances.
+
+use read_fonts::{
+    FontData, FontRef, TableProvider,
+    types::{Fixed, Tag},
+};
+use skrifa::MetadataProvider;
+
+use crate::error::{Error, Result};
+
+/// Private table listing the source font's named instances, written by
+/// [`InstanceOptions::keep_named_instances`](crate::InstanceOptions::keep_named_instances).
+///
+/// The table is a `uint16` instance count, then per instance a `uint16`
+/// byte length and the UTF-8 subfamily name, a `uint16` axis count, and a
+/// `Tag` and `Fixed` user-space value per axis.
+///
+/// It is meant for intermediate builds that are instanced again or
+/// inspected, not for fonts that ship; no other software reads it.
+pub const NAMED_INSTANCES_TAG: Tag = Tag::new(b"WNIN");
+
+/// A named instance: its subfamily name and user-space location.
+pub type NamedInstance = (String, Vec<(Tag, f32)>);
+
+/// List the named instances of a font.
+///
+/// Reads `fvar` from a variable font, or the [`NAMED_INSTANCES_TAG`] table
+/// from a static instance made with
+/// [`InstanceOptions::keep_named_instances`](crate::InstanceOptions::keep_named_instances).
+/// Instances whose subfamily name is missing from `name` are listed as
+/// `nameID <id>`.
+///
+/// # Errors
+///
+/// - `Error::NotVariableFont` if the font has neither table
+/// - `Error::InvalidFont` if the sidecar table is malformed
+pub fn list_named_instances(data: &[u8]) -> Result<Vec<NamedInstance>> {
+    let font = FontRef::new(data)?;
+    if font.fvar().is_ok() {
+        return Ok(fvar_named_instances(&font));
+    }
+    let table = font.table_data(NAMED_INSTANCES_TAG).ok_or(Error::NotVariableFont)?;
+    parse_named_instances(table.as_bytes())
+}
+
+pub(crate) fn fvar_named_instances(font: &FontRef) -> Vec<NamedInstance> {
+    let tags: Vec<Tag> = font.axes().iter().map(|axis| axis.tag()).collect();
+    font.named_instances()
+        .iter()
+        .map(|instance| {
+            let name_id = instance.subfamily_name_id();
+            let name = font
+                .localized_strings(name_id)
+                .english_or_first()
+                .map_or_else(|| format!("nameID {}", name_id.to_u16()), |s| s.to_string());
+            (name, tags.iter().copied().zip(instance.user_coords()).collect())
+        })
+        .collect()
+}
+
+/// Encode instances as the [`NAMED_INSTANCES_TAG`] table.
+pub(crate) fn encode_named_instances(instances: &[NamedInstance]) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(&(instances.len() as u16).to_be_bytes());
+    for (name, location) in instances {
+        out.extend_from_slice(&(name.len() as u16).to_be_bytes());
+        out.extend_from_slice(name.as_bytes());
+        out.extend_from_slice(&(location.len() as u16).to_be_bytes());
+        for (tag, value) in location {
+            out.extend_from_slice(&tag.to_be_bytes());
+            out.extend_from_slice(&Fixed::from_f64(f64::from(*value)).to_be_bytes());
+        }
+    }
+    out
+}
+
+fn parse_named_instances(table: &[u8]) -> Result<Vec<NamedInstance>> {
+    let invalid = |reason: String| Error::InvalidFont(format!("{NAMED_INSTANCES_TAG}: {reason}"));
+    let data = FontData::new(table);
+    let mut pos = 0;
+    let read_u16 = |pos: &mut usize| {
+        let value = data.read_at::<u16>(*pos).map_err(|e| invalid(e.to_string()));
+        *pos += 2;
+        value
+    };
+
+    let count = read_u16(&mut pos)?;
+    let mut instances = Vec::with_capacity(usize::from(count));
+    for _ in 0..count {
+        let len = usize::from(read_u16(&mut pos)?);
+        let name = table
+            .get(pos..pos + len)
+            .ok_or_else(|| invalid("name past the end of the table".into()))?;
+        let name = str::from_utf8(name).map_err(|e| invalid(e.to_string()))?.to_string();
+        pos += len;
+
+        let axis_count = read_u16(&mut pos)?;
+        let location = (0..axis_count)
+            .map(|_| {
+                let tag = data.read_at::<Tag>(pos).map_err(|e| invalid(e.to_string()))?;
+                let value = data.read_at::<Fixed>(pos + 4).map_err(|e| invalid(e.to_string()))?;
+                pos += 8;
+                Ok((tag, value.to_f32()))
+            })
+            .collect::<Result<_>>()?;
+        instances.push((name, location));
+    }
+    Ok(instances)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AxisLocation, InstanceOptions, instantiate_with_options};
+
+    #[test]
+    fn sidecar_round_trips() {
+        let instances = vec![
+            ("Thin".to_string(), vec![(Tag::new(b"wght"), 100.0), (Tag::new(b"wdth"), 87.5)]),
+            (
+                "Extra Bold".to_string(),
+                vec![(Tag::new(b"wght"), 800.0), (Tag::new(b"wdth"), 100.0)],
+            ),
+        ];
+        let encoded = encode_named_instances(&instances);
+        assert_eq!(parse_named_instances(&encoded).unwrap(), instances);
+        assert!(parse_named_instances(&encoded[..encoded.len() - 1]).is_err());
+
+        // Names are length-prefixed, so separators in them need no escaping
+        let odd = vec![("Bold\tItalic\n".to_string(), vec![(Tag::new(b"wght"), 700.0)])];
+        assert_eq!(parse_named_instances(&encode_named_instances(&odd)).unwrap(), odd);
+    }
+
+    #[test]
+    fn lists_fvar_instances() {
+        let instances = list_named_instances(font_test_data::CANTARELL_VF_TRIMMED).unwrap();
+        let wght = Tag::new(b"wght");
+        let expected: Vec<NamedInstance> = [
+            ("Thin", 100.0),
+            ("Light", 300.0),
+            ("Regular", 400.0),
+            ("Bold", 700.0),
+            ("Extra Bold", 800.0),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), vec![(wght, value)]))
+        .collect();
+        assert_eq!(instances, expected);
+    }
+
+    #[test]
+    fn static_instance_keeps_named_instances() {
+        let vf = font_test_data::VAZIRMATN_VAR;
+        let location = [AxisLocation::new("wght", 700.0)];
+        let options = InstanceOptions::new().keep_named_instances(true);
+        let instance = instantiate_with_options(vf, &location, &options).unwrap();
+
+        let listed = list_named_instances(&instance).unwrap();
+        assert_eq!(listed, list_named_instances(vf).unwrap());
+        assert_eq!(listed.len(), 9);
+        assert_eq!(listed[6], ("Bold".to_string(), vec![(Tag::new(b"wght"), 700.0)]));
+        assert_eq!(listed[1].0, "nameID 259");
+
+        let plain = instantiate_with_options(vf, &location, &InstanceOptions::new()).unwrap();
+        assert!(matches!(list_named_instances(&plain), Err(Error::NotVariableFont)));
+    }
+}
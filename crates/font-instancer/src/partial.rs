@@ -0,0 +1,513 @@
+//! Table rebuilding for partial instancing.
+//!
+//! Only the kept axes survive in `fvar`, `avar`, `gvar` and `STAT`. Each gvar tuple
+//! is split into its pinned-axis scalar, which scales the deltas, and its
+//! kept-axis region, which becomes the new tuple. Tuples with no kept-axis
+//! region are already folded into the default outlines by the instancer.
+
+use read_fonts::{
+    FontRef, TableProvider,
+    tables::{avar::Avar, fvar::Fvar, glyf::Glyph, stat::Stat},
+    types::{F2Dot14, Fixed, GlyphId, Point, Tag},
+};
+use write_fonts::{
+    from_obj::ToOwnedTable,
+    tables::{
+        avar::Avar as WriteAvar,
+        fvar::Fvar as WriteFvar,
+        gvar::{GlyphDelta, GlyphDeltas, GlyphVariations, Gvar as WriteGvar, Tent},
+        stat::{AxisValue, Stat as WriteStat},
+    },
+};
+
+use crate::{
+    AxisLocation,
+    cff2::CFF2,
+    error::{Error, Result},
+    instancer::{PHANTOM_POINTS, iup_contour},
+};
+
+/// Variation tables dropped rather than rebuilt: their item variation stores
+/// still index the pinned axes.
+pub(crate) const PARTIAL_DROPPED_TABLES: [Tag; 4] =
+    [Tag::new(b"cvar"), Tag::new(b"HVAR"), Tag::new(b"MVAR"), Tag::new(b"VVAR")];
+
+/// Reject fonts whose other tables refer to axes by index.
+pub(crate) fn check_partial_support(font: &FontRef) -> Result<()> {
+    if font.table_data(CFF2).is_some() {
+        return Err(Error::UnsupportedForPartial(CFF2));
+    }
+    if font.avar().is_ok_and(|avar| avar.var_store().is_some()) {
+        return Err(Error::UnsupportedForPartial(Tag::new(b"avar")));
+    }
+    if font.gsub().is_ok_and(|gsub| gsub.feature_variations().is_some()) {
+        return Err(Error::UnsupportedForPartial(Tag::new(b"GSUB")));
+    }
+    if font.gpos().is_ok_and(|gpos| gpos.feature_variations().is_some()) {
+        return Err(Error::UnsupportedForPartial(Tag::new(b"GPOS")));
+    }
+    if font.gdef().is_ok_and(|gdef| gdef.item_var_store().is_some()) {
+        return Err(Error::UnsupportedForPartial(Tag::new(b"GDEF")));
+    }
+    Ok(())
+}
+
+/// `fvar` with only the kept axes, and only the named instances that sit at
+/// the pinned location.
+pub(crate) fn partial_fvar(
+    fvar: &Fvar,
+    kept: &[usize],
+    locations: &[AxisLocation],
+) -> Result<WriteFvar> {
+    let mut fvar: WriteFvar = fvar.to_owned_table();
+    let arrays = &mut *fvar.axis_instance_arrays;
+
+    let pinned: Vec<Option<Fixed>> = arrays
+        .axes
+        .iter()
+        .enumerate()
+        .map(|(i, axis)| {
+            (!kept.contains(&i)).then(|| {
+                locations
+                    .iter()
+                    .find(|loc| loc.tag == axis.axis_tag)
+                    .map_or(axis.default_value, |loc| Fixed::from_f64(f64::from(loc.value)))
+            })
+        })
+        .collect();
+
+    arrays.instances.retain(|instance| {
+        instance
+            .coordinates
+            .iter()
+            .zip(&pinned)
+            .all(|(coord, pin)| pin.is_none_or(|pin| *coord == pin))
+    });
+    for instance in &mut arrays.instances {
+        instance.coordinates = kept.iter().map(|&i| instance.coordinates[i]).collect();
+    }
+    arrays.axes = kept.iter().map(|&i| arrays.axes[i].clone()).collect();
+    Ok(fvar)
+}
+
+/// `avar` with only the kept axes' segment maps.
+pub(crate) fn partial_avar(avar: &Avar, kept: &[usize]) -> WriteAvar {
+    let mut avar: WriteAvar = avar.to_owned_table();
+    let maps = std::mem::take(&mut avar.axis_segment_maps);
+    avar.axis_segment_maps = maps
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, map)| kept.contains(&i).then_some(map))
+        .collect();
+    avar
+}
+
+/// `STAT` without the pinned axes' design axis records, and without the
+/// axis values that describe them; the remaining values are renumbered.
+///
+/// A format 4 value keeps its kept-axis records if all of its pinned-axis
+/// records sit at the pinned location, and is dropped otherwise.
+pub(crate) fn partial_stat(
+    stat: &Stat,
+    fvar: &Fvar,
+    kept: &[usize],
+    locations: &[AxisLocation],
+) -> Result<WriteStat> {
+    let pins: Vec<(Tag, Fixed)> = fvar
+        .axes()?
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !kept.contains(i))
+        .map(|(_, axis)| {
+            let pin = locations
+                .iter()
+                .find(|loc| loc.tag == axis.axis_tag())
+                .map_or(axis.default_value(), |loc| Fixed::from_f64(f64::from(loc.value)));
+            (axis.axis_tag(), pin)
+        })
+        .collect();
+
+    let mut stat: WriteStat = stat.to_owned_table();
+    let design_axes = std::mem::take(&mut *stat.design_axes);
+    let pin = |axis_index: u16| {
+        let tag = design_axes.get(usize::from(axis_index))?.axis_tag;
+        pins.iter()
+            .find(|(pinned, _)| *pinned == tag)
+            .map(|(_, value)| *value)
+    };
+    let mut next = 0;
+    let new_index: Vec<Option<u16>> = (0..design_axes.len() as u16)
+        .map(|i| {
+            pin(i).is_none().then(|| {
+                next += 1;
+                next - 1
+            })
+        })
+        .collect();
+    let renumber = |axis_index: &mut u16| match new_index.get(usize::from(*axis_index)) {
+        Some(&Some(index)) => {
+            *axis_index = index;
+            true
+        }
+        _ => false,
+    };
+
+    if let Some(values) = stat.offset_to_axis_values.as_mut() {
+        values.retain_mut(|value| match &mut **value {
+            AxisValue::Format1(value) => renumber(&mut value.axis_index),
+            AxisValue::Format2(value) => renumber(&mut value.axis_index),
+            AxisValue::Format3(value) => renumber(&mut value.axis_index),
+            AxisValue::Format4(value) => {
+                let off_pin = value
+                    .axis_values
+                    .iter()
+                    .any(|record| pin(record.axis_index).is_some_and(|pin| pin != record.value));
+                if off_pin {
+                    return false;
+                }
+                value
+                    .axis_values
+                    .retain_mut(|record| renumber(&mut record.axis_index));
+                !value.axis_values.is_empty()
+            }
+        });
+    }
+    *stat.design_axes = design_axes
+        .into_iter()
+        .zip(&new_index)
+        .filter_map(|(record, index)| index.map(|_| record))
+        .collect();
+    Ok(stat)
+}
+
+/// `gvar` over the kept axes, with each tuple scaled by the pinned axes.
+///
+/// `coords` holds the normalized pinned location, zero on kept axes.
+pub(crate) fn partial_gvar(
+    font: &FontRef,
+    coords: &[F2Dot14],
+    kept: &[usize],
+) -> Result<WriteGvar> {
+    let num_glyphs = u32::from(font.maxp()?.num_glyphs());
+    let variations = (0..num_glyphs)
+        .map(|gid| {
+            let gid = GlyphId::new(gid);
+            Ok(GlyphVariations::new(gid, glyph_deltas(font, gid, coords, kept)?))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    WriteGvar::new(variations, kept.len() as u16).map_err(|e| Error::InvalidFont(e.to_string()))
+}
+
+/// Remaining tuples of one glyph, with every point's delta made explicit.
+///
+/// Sparse tuples are expanded through IUP against the original outline
+/// before scaling, since the default outline they were drawn against moves.
+fn glyph_deltas(
+    font: &FontRef,
+    gid: GlyphId,
+    coords: &[F2Dot14],
+    kept: &[usize],
+) -> Result<Vec<GlyphDeltas>> {
+    let gvar = font.gvar()?;
+    let Some(data) = gvar.glyph_variation_data(gid)? else {
+        return Ok(Vec::new());
+    };
+
+    let (mut points, end_pts): (Vec<Point<i32>>, Vec<usize>) =
+        match font.loca(None)?.get_glyf(gid, &font.glyf()?)? {
+            Some(Glyph::Simple(simple)) => (
+                simple
+                    .points()
+                    .map(|p| Point::new(i32::from(p.x), i32::from(p.y)))
+                    .collect(),
+                simple
+                    .end_pts_of_contours()
+                    .iter()
+                    .map(|end| end.get() as usize)
+                    .collect(),
+            ),
+            Some(Glyph::Composite(composite)) => {
+                (vec![Point::default(); composite.components().count()], Vec::new())
+            }
+            None => (Vec::new(), Vec::new()),
+        };
+    points.resize(points.len() + PHANTOM_POINTS, Point::default());
+    // Contour ends index `points` during IUP; the instancer rejects bad ones.
+    if end_pts.windows(2).any(|pair| pair[0] >= pair[1])
+        || end_pts
+            .last()
+            .is_some_and(|&end| end + PHANTOM_POINTS >= points.len())
+    {
+        return Ok(Vec::new());
+    }
+
+    let mut variations = Vec::new();
+    for tuple in data.tuples() {
+        let peak = tuple.peak();
+        let peak_at = |i: usize| peak.get(i).unwrap_or_default();
+        if kept.iter().all(|&i| peak_at(i) == F2Dot14::ZERO) {
+            continue;
+        }
+
+        // Evaluate at the tuple's own peak on kept axes, leaving only the
+        // pinned axes' contribution.
+        let mut at = coords.to_vec();
+        for &i in kept {
+            at[i] = peak_at(i);
+        }
+        let Some(scalar) = tuple.compute_scalar_f32(&at).filter(|s| *s != 0.0) else {
+            continue;
+        };
+
+        let mut deltas = vec![Point::<i32>::default(); points.len()];
+        let mut touched = vec![false; points.len()];
+        for delta in tuple.deltas() {
+            let idx = delta.position as usize;
+            if let Some(slot) = deltas.get_mut(idx) {
+                *slot = Point::new(delta.x_delta, delta.y_delta);
+                touched[idx] = true;
+            }
+        }
+        if !tuple.has_deltas_for_all_points() {
+            let mut start = 0;
+            for &end in &end_pts {
+                iup_contour(&mut deltas, &touched, &points, start, end);
+                start = end + 1;
+            }
+        }
+
+        let scale = |v: i32| {
+            (v as f32 * scalar)
+                .round()
+                .clamp(f32::from(i16::MIN), f32::from(i16::MAX)) as i16
+        };
+        let deltas: Vec<GlyphDelta> = deltas
+            .iter()
+            .map(|d| GlyphDelta::required(scale(d.x), scale(d.y)))
+            .collect();
+        if deltas.iter().all(|d| d.x == 0 && d.y == 0) {
+            continue;
+        }
+
+        let intermediate = tuple.intermediate_start().zip(tuple.intermediate_end());
+        let tents = kept
+            .iter()
+            .map(|&i| {
+                let bounds = intermediate.as_ref().map(|(start, end)| {
+                    (start.get(i).unwrap_or_default(), end.get(i).unwrap_or_default())
+                });
+                Tent::new(peak_at(i), bounds)
+            })
+            .collect();
+        variations.push(GlyphDeltas::new(tents, deltas));
+    }
+    Ok(variations)
+}
+
+#[cfg(test)]
+mod tests {
+    use font_test_data::VAZIRMATN_VAR;
+    use read_fonts::tables::glyf::Glyph;
+    use write_fonts::{
+        FontBuilder,
+        tables::{
+            fvar::VariationAxisRecord,
+            gvar::GlyphDelta,
+            stat::{AxisRecord, AxisValueRecord, AxisValueTableFlags},
+        },
+        types::NameId,
+    };
+
+    use super::*;
+    use crate::{instantiate, instantiate_partial};
+
+    /// Dense deltas for `count` points, distinct per tuple via `seed`.
+    fn dense(count: usize, seed: i16) -> Vec<GlyphDelta> {
+        (0..count as i16)
+            .map(|i| GlyphDelta::required(seed * (i % 5 - 2), seed + i))
+            .collect()
+    }
+
+    fn tuple(wght: f32, ital: f32, deltas: Vec<GlyphDelta>) -> GlyphDeltas {
+        let tents = [wght, ital].map(|peak| Tent::new(F2Dot14::from_f32(peak), None));
+        GlyphDeltas::new(tents.to_vec(), deltas)
+    }
+
+    /// VAZIRMATN_VAR with an extra `ital` axis (0..1), a gvar mixing
+    /// wght-only, ital-only and combined tuples on glyphs 1 and 3, and a
+    /// STAT naming Regular, Roman, Italic, Bold and Bold Italic.
+    fn two_axis_font() -> Vec<u8> {
+        let font = FontRef::new(VAZIRMATN_VAR).unwrap();
+
+        let mut fvar: WriteFvar = font.fvar().unwrap().to_owned_table();
+        let arrays = &mut *fvar.axis_instance_arrays;
+        arrays.axes.push(VariationAxisRecord {
+            axis_tag: Tag::new(b"ital"),
+            min_value: Fixed::ZERO,
+            default_value: Fixed::ZERO,
+            max_value: Fixed::ONE,
+            flags: 0,
+            axis_name_id: NameId::new(256),
+        });
+        let upright = arrays.instances.clone();
+        for instance in &mut arrays.instances {
+            instance.coordinates.push(Fixed::ZERO);
+        }
+        arrays.instances.extend(upright.into_iter().map(|mut instance| {
+            instance.coordinates.push(Fixed::ONE);
+            instance
+        }));
+
+        let variations = (0..4)
+            .map(|gid| {
+                let count = match gid {
+                    1 => 14 + PHANTOM_POINTS,
+                    3 => 4 + PHANTOM_POINTS,
+                    _ => return GlyphVariations::new(GlyphId::new(gid), Vec::new()),
+                };
+                let tuples = vec![
+                    tuple(1.0, 0.0, dense(count, 3)),
+                    tuple(-1.0, 0.0, dense(count, -2)),
+                    tuple(0.0, 1.0, dense(count, 5)),
+                    tuple(1.0, 1.0, dense(count, 7)),
+                ];
+                GlyphVariations::new(GlyphId::new(gid), tuples)
+            })
+            .collect();
+        let gvar = WriteGvar::new(variations, 2).unwrap();
+
+        let flags = AxisValueTableFlags::empty();
+        let name = NameId::new;
+        let stat = WriteStat::new(
+            vec![
+                AxisRecord::new(Tag::new(b"wght"), name(256), 0),
+                AxisRecord::new(Tag::new(b"ital"), name(257), 1),
+            ],
+            vec![
+                AxisValue::format_1(0, flags, name(258), Fixed::from_i32(400)),
+                AxisValue::format_1(1, flags, name(259), Fixed::ZERO),
+                AxisValue::format_1(1, flags, name(260), Fixed::ONE),
+                AxisValue::format_4(
+                    flags,
+                    name(261),
+                    vec![
+                        AxisValueRecord::new(0, Fixed::from_i32(700)),
+                        AxisValueRecord::new(1, Fixed::ZERO),
+                    ],
+                ),
+                AxisValue::format_4(
+                    flags,
+                    name(262),
+                    vec![
+                        AxisValueRecord::new(0, Fixed::from_i32(700)),
+                        AxisValueRecord::new(1, Fixed::ONE),
+                    ],
+                ),
+            ],
+            name(2),
+        );
+
+        let mut builder = FontBuilder::new();
+        builder.add_table(&fvar).unwrap();
+        builder.add_table(&gvar).unwrap();
+        builder.add_table(&stat).unwrap();
+        for record in font.table_directory().table_records() {
+            let tag = record.tag();
+            if !builder.contains(tag) && !matches!(&tag.to_be_bytes(), b"avar" | b"HVAR") {
+                builder.add_raw(tag, font.table_data(tag).unwrap());
+            }
+        }
+        builder.build()
+    }
+
+    fn points(data: &[u8], gid: u32) -> Vec<(i16, i16)> {
+        let font = FontRef::new(data).unwrap();
+        let glyf = font.glyf().unwrap();
+        match font.loca(None).unwrap().get_glyf(GlyphId::new(gid), &glyf).unwrap() {
+            Some(Glyph::Simple(simple)) => simple.points().map(|p| (p.x, p.y)).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    #[test]
+    fn partial_then_full_matches_direct_instance() {
+        let font = two_axis_font();
+        let partial =
+            instantiate_partial(&font, &[AxisLocation::new("ital", 1.0)], &[Tag::new(b"wght")])
+                .unwrap();
+
+        let fvar = FontRef::new(&partial).unwrap().fvar().unwrap();
+        let tags: Vec<Tag> = fvar.axes().unwrap().iter().map(|axis| axis.axis_tag()).collect();
+        assert_eq!(tags, [Tag::new(b"wght")]);
+        assert_eq!(fvar.instance_count(), 9);
+
+        for wght in [100.0, 250.0, 400.0, 650.0, 900.0] {
+            let direct = instantiate(
+                &font,
+                &[AxisLocation::new("wght", wght), AxisLocation::new("ital", 1.0)],
+            )
+            .unwrap();
+            let staged = instantiate(&partial, &[AxisLocation::new("wght", wght)]).unwrap();
+            for gid in [1, 3] {
+                let (expected, actual) = (points(&direct, gid), points(&staged, gid));
+                assert_eq!(expected.len(), actual.len());
+                for (a, b) in expected.iter().zip(&actual) {
+                    assert!(
+                        (a.0 - b.0).abs() <= 1 && (a.1 - b.1).abs() <= 1,
+                        "gid {gid} at wght {wght}: {a:?} vs {b:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn stat_drops_pinned_axis() {
+        use read_fonts::tables::stat::AxisValue;
+
+        let font = two_axis_font();
+        let partial =
+            instantiate_partial(&font, &[AxisLocation::new("ital", 1.0)], &[Tag::new(b"wght")])
+                .unwrap();
+        let stat = FontRef::new(&partial).unwrap().stat().unwrap();
+
+        let axes: Vec<Tag> = stat.design_axes().unwrap().iter().map(|a| a.axis_tag()).collect();
+        assert_eq!(axes, [Tag::new(b"wght")]);
+        let values: Vec<(u16, Vec<(u16, f64)>)> = stat
+            .offset_to_axis_values()
+            .unwrap()
+            .unwrap()
+            .axis_values()
+            .iter()
+            .map(|value| match value.unwrap() {
+                AxisValue::Format1(value) => (
+                    value.value_name_id().to_u16(),
+                    vec![(value.axis_index(), value.value().to_f64())],
+                ),
+                AxisValue::Format4(value) => (
+                    value.value_name_id().to_u16(),
+                    value
+                        .axis_values()
+                        .iter()
+                        .map(|record| (record.axis_index(), record.value().to_f64()))
+                        .collect(),
+                ),
+                _ => unreachable!(),
+            })
+            .collect();
+        // Regular, and Bold Italic without its ital record; Roman, Italic and
+        // the upright Bold are gone.
+        assert_eq!(values, [(258, vec![(0, 400.0)]), (262, vec![(0, 700.0)])]);
+    }
+
+    #[test]
+    fn kept_axis_cannot_be_pinned() {
+        let err = instantiate_partial(
+            VAZIRMATN_VAR,
+            &[AxisLocation::new("wght", 700.0)],
+            &[Tag::new(b"wght")],
+        )
+        .unwrap_err();
+        assert!(matches!(err, Error::KeptAxisPinned(tag) if tag == Tag::new(b"wght")));
+    }
+}
@@ -0,0 +1,96 @@
+//! Up-front summary of what instancing does to a font's tables.
+
+use read_fonts::{FontRef, TableProvider, types::Tag};
+
+use crate::{
+    cff2::{CFF, CFF2},
+    error::{Error, Result},
+    instancer::{
+        HINTING_TABLES, REMOVED_TABLES, REPLACED_TABLES, VARIATION_TABLES, VERTICAL_TABLES,
+    },
+};
+
+/// Tables of a font sorted by what [`instantiate`](crate::instantiate) does
+/// with them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TablePlan {
+    /// Tables written that the font does not have yet
+    pub added: Vec<Tag>,
+    /// Tables rebuilt for the instance
+    pub replaced: Vec<Tag>,
+    /// Tables dropped from the output
+    pub removed: Vec<Tag>,
+    /// Tables copied through unchanged
+    pub passed_through: Vec<Tag>,
+}
+
+/// List which tables instancing `data` will add, replace, remove, or copy.
+///
+/// The plan does not depend on the requested location. `cmap`, `GSUB` and
+/// `GPOS` are listed as passed through even though they are rebuilt when
+/// FeatureVariations select features or substitute glyphs at the chosen
+/// location. `CFF2` is listed as replaced,
+/// with the `CFF ` table it becomes as added. Hinting tables are listed as
+/// removed, as with default [`InstanceOptions`](crate::InstanceOptions).
+///
+/// # Errors
+///
+/// - `Error::Parse` if the font cannot be read
+/// - `Error::NotVariableFont` if the font has no fvar table
+pub fn plan_tables(data: &[u8]) -> Result<TablePlan> {
+    let font = FontRef::new(data)?;
+    font.fvar().map_err(|_| Error::NotVariableFont)?;
+
+    let stat = Tag::new(b"STAT");
+    let vertical = font.vhea().is_ok() && font.vmtx().is_ok();
+
+    let mut plan = TablePlan::default();
+    for record in font.table_directory.table_records() {
+        let tag = record.tag();
+        let list = if REPLACED_TABLES.contains(&tag)
+            || tag == stat
+            || (vertical && VERTICAL_TABLES.contains(&tag))
+        {
+            &mut plan.replaced
+        } else if VARIATION_TABLES.contains(&tag)
+            || REMOVED_TABLES.contains(&tag)
+            || HINTING_TABLES.contains(&tag)
+        {
+            &mut plan.removed
+        } else {
+            &mut plan.passed_through
+        };
+        list.push(tag);
+    }
+    if !plan.replaced.contains(&stat) {
+        plan.added.push(stat);
+    }
+    if plan.replaced.contains(&CFF2) {
+        plan.added.push(CFF);
+    }
+
+    Ok(plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use font_test_data::{SIMPLE_GLYF, VAZIRMATN_VAR};
+
+    use super::*;
+
+    #[test]
+    fn variable_font_plan() {
+        let plan = plan_tables(VAZIRMATN_VAR).unwrap();
+        assert!(plan.removed.contains(&Tag::new(b"gvar")));
+        assert!(plan.removed.contains(&Tag::new(b"fvar")));
+        assert!(plan.replaced.contains(&Tag::new(b"glyf")));
+        assert!(plan.passed_through.contains(&Tag::new(b"cmap")));
+        assert!(!plan.removed.contains(&Tag::new(b"STAT")));
+        assert!(plan.removed.contains(&Tag::new(b"gasp")));
+    }
+
+    #[test]
+    fn static_font_is_rejected() {
+        assert!(matches!(plan_tables(SIMPLE_GLYF), Err(Error::NotVariableFont)));
+    }
+}
@@ -0,0 +1,117 @@
+//! Structural checks for instanced output.
+
+use read_fonts::{FontRef, TableProvider, types::GlyphId};
+
+use crate::error::{Error, Result};
+
+/// Check that a static font's `glyf`/`loca` pair is internally consistent.
+///
+/// Verifies that `loca` has one entry per glyph plus the end offset, that its
+/// offsets ascend and stay within `glyf`, that every glyph parses, and that
+/// `hhea.numberOfHMetrics` does not exceed the glyph count. Intended for
+/// catching interpolation regressions in [`instantiate`](crate::instantiate)
+/// output; it does not compare against the source variable font.
+///
+/// # Errors
+///
+/// - `Error::Parse` if the font or one of the checked tables cannot be read
+/// - `Error::InvalidFont` if the tables disagree with each other
+pub fn validate_font(data: &[u8]) -> Result<()> {
+    let font = FontRef::new(data)?;
+    let num_glyphs = u32::from(font.maxp()?.num_glyphs());
+    let glyf = font.glyf()?;
+    let loca = font.loca(None)?;
+
+    if loca.len() != num_glyphs as usize {
+        return Err(Error::InvalidFont(format!(
+            "loca has {} offsets for {num_glyphs} glyphs",
+            loca.len() + 1
+        )));
+    }
+    if !loca.all_offsets_are_ascending() {
+        return Err(Error::InvalidFont("loca offsets are not ascending".into()));
+    }
+    let glyf_len = glyf.offset_data().len();
+    let end = loca.get_raw(loca.len()).unwrap_or(0) as usize;
+    if end > glyf_len {
+        return Err(Error::InvalidFont(format!(
+            "loca ends at {end}, past the {glyf_len}-byte glyf table"
+        )));
+    }
+
+    for gid in 0..num_glyphs {
+        loca.get_glyf(GlyphId::new(gid), &glyf)
+            .map_err(|err| Error::InvalidFont(format!("glyph {gid}: {err}")))?;
+    }
+
+    let num_h_metrics = u32::from(font.hhea()?.number_of_h_metrics());
+    if num_h_metrics > num_glyphs {
+        return Err(Error::InvalidFont(format!(
+            "hhea declares {num_h_metrics} metrics for {num_glyphs} glyphs"
+        )));
+    }
+    font.hmtx()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use font_test_data::VAZIRMATN_VAR;
+    use read_fonts::types::Tag;
+    use write_fonts::FontBuilder;
+
+    use super::*;
+    use crate::{AxisLocation, instantiate};
+
+    /// Copy of `data` with its `loca` offsets passed through `edit`, written
+    /// back in the same format.
+    fn with_loca(data: &[u8], edit: impl FnOnce(&mut Vec<u32>)) -> Vec<u8> {
+        let font = FontRef::new(data).unwrap();
+        let loca = font.loca(None).unwrap();
+        let short = font.head().unwrap().index_to_loc_format() == 0;
+        let mut offsets: Vec<u32> = (0..=loca.len()).map(|i| loca.get_raw(i).unwrap()).collect();
+        edit(&mut offsets);
+
+        let raw: Vec<u8> = if short {
+            offsets
+                .iter()
+                .flat_map(|offset| ((offset / 2) as u16).to_be_bytes())
+                .collect()
+        } else {
+            offsets.iter().flat_map(|offset| offset.to_be_bytes()).collect()
+        };
+        let mut builder = FontBuilder::new();
+        builder.add_raw(Tag::new(b"loca"), raw);
+        builder.copy_missing_tables(font);
+        builder.build()
+    }
+
+    #[test]
+    fn accepts_instance() {
+        let instance = instantiate(VAZIRMATN_VAR, &[AxisLocation::new("wght", 700.0)]).unwrap();
+        validate_font(&instance).unwrap();
+    }
+
+    #[test]
+    fn rejects_truncated_font() {
+        let instance = instantiate(VAZIRMATN_VAR, &[AxisLocation::new("wght", 700.0)]).unwrap();
+        assert!(validate_font(&instance[..instance.len() / 2]).is_err());
+    }
+
+    #[test]
+    fn rejects_descending_loca() {
+        let instance = instantiate(VAZIRMATN_VAR, &[AxisLocation::new("wght", 700.0)]).unwrap();
+        let corrupt = with_loca(&instance, |offsets| offsets.swap(1, 2));
+        let err = validate_font(&corrupt).unwrap_err();
+        assert!(err.to_string().contains("not ascending"), "{err}");
+    }
+
+    #[test]
+    fn rejects_loca_past_glyf() {
+        let instance = instantiate(VAZIRMATN_VAR, &[AxisLocation::new("wght", 700.0)]).unwrap();
+        let corrupt = with_loca(&instance, |offsets| *offsets.last_mut().unwrap() += 64);
+        let err = validate_font(&corrupt).unwrap_err();
+        assert!(err.to_string().contains("past the"), "{err}");
+    }
+}
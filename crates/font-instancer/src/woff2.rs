@@ -0,0 +1,43 @@
+//! WOFF2 output for static instances (`woff2` feature).
+
+use warpnine_font_woff2::convert_to_woff2;
+
+use crate::{AxisLocation, Error, error::Result, instancer::instantiate};
+
+/// Instantiate a variable font and compress the result as WOFF2.
+///
+/// Convenient for per-weight web delivery. Compression goes through
+/// [`convert_to_woff2`], which also drops codepoints that fail OTS validation.
+pub fn instantiate_woff2(data: &[u8], locations: &[AxisLocation]) -> Result<Vec<u8>> {
+    let instance = instantiate(data, locations)?;
+    convert_to_woff2(&instance).map_err(|e| Error::Woff2(format!("{e:#}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use font_test_data::VAZIRMATN_VAR;
+    use read_fonts::{
+        FontRef, TableProvider,
+        types::{GlyphId, Tag},
+    };
+    use warpnine_font_woff2::decode::decode_woff2;
+
+    use super::*;
+
+    #[test]
+    fn test_instantiate_woff2_is_static() {
+        let woff2 = instantiate_woff2(VAZIRMATN_VAR, &[AxisLocation::new("wght", 400.0)]).unwrap();
+
+        let sfnt = decode_woff2(&woff2).unwrap();
+        let font = FontRef::new(&sfnt).unwrap();
+        assert!(font.table_data(Tag::new(b"fvar")).is_none());
+        assert!(font.table_data(Tag::new(b"gvar")).is_none());
+
+        let (glyf, loca) = (font.glyf().unwrap(), font.loca(None).unwrap());
+        let num_glyphs = font.maxp().unwrap().num_glyphs();
+        assert!(num_glyphs > 0);
+        for gid in 0..num_glyphs {
+            loca.get_glyf(GlyphId::from(gid), &glyf).unwrap();
+        }
+    }
+}
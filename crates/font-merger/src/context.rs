@@ -8,12 +8,18 @@
 use std::{collections::HashMap, string::ToString};
 
 use indexmap::{IndexMap, map::Entry};
-use read_fonts::{FontRef, TableProvider, tables::post::Post, types::GlyphId16};
+use read_fonts::{
+    FontRef, TableProvider,
+    ps::cff::CffFontRef,
+    tables::post::Post,
+    types::{GlyphId as ReadGlyphId, GlyphId16, Tag},
+};
 
 use super::types;
 use crate::{
+    MergeError, Result,
     glyph_order::GlyphName,
-    options::Options,
+    options::{NameSuffixScheme, Options},
     tables::cmap::DuplicateGlyphInfo,
     types::{FontIndex, GlyphId, MegaGlyphId},
 };
@@ -36,11 +42,24 @@ impl GlyphOrder {
     ///
     /// When the same glyph name appears in multiple fonts, later occurrences
     /// are renamed with a suffix (e.g., "A.1", "A.2")
-    pub fn compute(fonts: &[FontRef]) -> Self {
+    ///
+    /// Fails with `TooManyGlyphs` if the merged order would hold more than
+    /// `max_glyphs` glyphs.
+    pub fn compute(fonts: &[FontRef], max_glyphs: usize) -> Result<Self> {
+        Self::compute_with(fonts, max_glyphs, NameSuffixScheme::Numeric)
+    }
+
+    /// Compute the unified glyph order, renaming clashing glyph names with
+    /// `scheme`
+    pub fn compute_with(
+        fonts: &[FontRef],
+        max_glyphs: usize,
+        scheme: NameSuffixScheme,
+    ) -> Result<Self> {
         let mut mega_order: IndexMap<GlyphName, usize> = IndexMap::new();
         let mut per_font: Vec<IndexMap<GlyphId, GlyphName>> = Vec::with_capacity(fonts.len());
 
-        for font in fonts {
+        for (font_idx, font) in fonts.iter().enumerate() {
             let glyph_order = get_glyph_order(font);
             let mut font_mapping = IndexMap::new();
 
@@ -55,7 +74,19 @@ impl GlyphOrder {
                     Entry::Occupied(mut entry) => {
                         let count = *entry.get();
                         *entry.get_mut() = count + 1;
-                        let new_name = GlyphName::new(format!("{}.{count}", entry.key()));
+                        let numeric = format!("{}.{count}", entry.key());
+                        let new_name = match scheme {
+                            NameSuffixScheme::Numeric => numeric,
+                            NameSuffixScheme::FontPrefix => {
+                                let prefixed = format!("f{font_idx}.{}", entry.key());
+                                if mega_order.contains_key(prefixed.as_str()) {
+                                    numeric
+                                } else {
+                                    prefixed
+                                }
+                            }
+                        };
+                        let new_name = GlyphName::new(new_name);
                         mega_order.insert(new_name.clone(), 1);
                         font_mapping.insert(gid, new_name);
                     }
@@ -65,6 +96,10 @@ impl GlyphOrder {
             per_font.push(font_mapping);
         }
 
+        if mega_order.len() > max_glyphs {
+            return Err(MergeError::TooManyGlyphs { count: mega_order.len(), max_glyphs });
+        }
+
         let mega: Vec<GlyphName> = mega_order.keys().cloned().collect();
         let name_to_mega: HashMap<GlyphName, MegaGlyphId> = mega
             .iter()
@@ -72,7 +107,7 @@ impl GlyphOrder {
             .map(|(i, n)| (n.clone(), MegaGlyphId::new(i as u16)))
             .collect();
 
-        Self { mega, per_font, name_to_mega }
+        Ok(Self { mega, per_font, name_to_mega })
     }
 
     /// Get the mega glyph order (all unique names)
@@ -116,16 +151,22 @@ impl GlyphOrder {
     }
 }
 
-/// Extract glyph names from a font's post table
+/// Extract glyph names from a font's post table, falling back to the CFF
+/// charset for fonts with a version 3 post table
 fn get_glyph_order(font: &FontRef) -> Vec<GlyphName> {
     let num_glyphs = font.maxp().map(|m| m.num_glyphs()).unwrap_or_default() as usize;
     let post = font.post().ok();
+    let cff = font
+        .table_data(Tag::new(b"CFF "))
+        .and_then(|data| CffFontRef::new_cff(data.as_bytes(), 0, None).ok())
+        .filter(|cff| !cff.is_cid());
 
     (0..num_glyphs)
         .map(|gid| {
             let name = post
                 .as_ref()
                 .and_then(|p| get_glyph_name_from_post(p, gid as u16))
+                .or_else(|| cff.as_ref().and_then(|cff| get_glyph_name_from_cff(cff, gid as u32)))
                 .unwrap_or_else(|| format!("glyph{gid:05}"));
             GlyphName::new(name)
         })
@@ -136,6 +177,11 @@ fn get_glyph_name_from_post(post: &Post, gid: u16) -> Option<String> {
     post.glyph_name(GlyphId16::new(gid)).map(ToString::to_string)
 }
 
+fn get_glyph_name_from_cff(cff: &CffFontRef, gid: u32) -> Option<String> {
+    let sid = cff.charset()?.string_id(ReadGlyphId::new(gid)).ok()?;
+    cff.string(sid).map(|name| String::from_utf8_lossy(name).into_owned())
+}
+
 /// Mapping from source font GIDs to merged mega GIDs
 ///
 /// This is computed once per font and reused by all table mergers.
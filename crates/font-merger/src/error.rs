@@ -1,6 +1,6 @@
 use std::{io, result};
 
-use read_fonts::ReadError;
+use read_fonts::{ReadError, types::Tag};
 use thiserror::Error;
 use write_fonts::{BuilderError, error};
 
@@ -18,9 +18,15 @@ pub enum MergeError {
     #[error("no fonts provided for merging")]
     NoFonts,
 
+    #[error("merged font would have {count} glyphs, more than the limit of {max_glyphs}")]
+    TooManyGlyphs { count: usize, max_glyphs: usize },
+
     #[error("fonts have incompatible unitsPerEm: expected {expected}, got {actual}")]
     IncompatibleUnitsPerEm { expected: u16, actual: u16 },
 
+    #[error("cannot scale '{0}' table to a different unitsPerEm")]
+    UnscalableTable(Tag),
+
     #[error("table values must be equal for '{table}' field '{field}'")]
     NotEqual { table: &'static str, field: &'static str },
 
@@ -30,11 +36,29 @@ pub enum MergeError {
     #[error("CID-keyed CFF fonts are not supported")]
     CidKeyedCffNotSupported,
 
+    #[error("CFF fonts have different FontMatrix values")]
+    IncompatibleFontMatrix,
+
+    #[error("failed to read CFF data: {0}")]
+    CffError(#[from] read_fonts::ps::error::Error),
+
+    #[error("invalid CFF data: {0}")]
+    InvalidCharstring(String),
+
     #[error("I/O error: {0}")]
     Io(#[from] io::Error),
 
+    #[error("merged COLR table would have more than 65535 layer records")]
+    TooManyColrLayers,
+
     #[error("failed to build cmap table")]
     CmapBuildError,
+
+    #[error("invalid custom .notdef: {0}")]
+    InvalidNotdef(String),
+
+    #[error("failed to rebuild name table: {0}")]
+    NameRebuild(String),
 }
 
 pub type Result<T> = result::Result<T, MergeError>;
@@ -4,6 +4,8 @@ mod error;
 mod glyph_order;
 mod merger;
 mod options;
+mod report;
+mod scale;
 mod strategies;
 mod tables;
 mod types;
@@ -12,8 +14,12 @@ pub use context::{GidRemap, GlyphOrder, MergeContext};
 pub use convert::ToWrite;
 pub use error::{MergeError, Result};
 pub use glyph_order::GlyphName;
-pub use merger::Merger;
-pub use options::Options;
+pub use merger::{Merger, MergerBuilder};
+pub use options::{
+    ConflictStrategy, FontOptions, FontSelector, KernPreference, LineMetrics, NameSource,
+    NameSuffixScheme, Options,
+};
+pub use report::{DroppedLookup, MergeReport};
 pub use types::{Codepoint, FontIndex, GlyphId, MegaGlyphId, TableTag};
 
 /// Merge multiple fonts from raw byte slices using default options.
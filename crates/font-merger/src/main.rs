@@ -4,7 +4,8 @@ use std::{
 };
 
 use clap::Parser;
-use warpnine_font_merger::{MergeError, Merger, Options, Result};
+use read_fonts::types::Tag;
+use warpnine_font_merger::{FontSelector, MergeError, Merger, Options, Result};
 
 #[derive(Parser)]
 #[command(name = "font-merger")]
@@ -22,6 +23,30 @@ struct Cli {
     #[arg(long, value_delimiter = ',')]
     drop_tables: Vec<String>,
 
+    /// Decompose composite glyphs from all fonts except the first
+    #[arg(long)]
+    flatten_components: bool,
+
+    /// Index of the input font whose GPOS mark lookups are applied first
+    #[arg(long, value_name = "INDEX")]
+    prefer_marks_from: Option<usize>,
+
+    /// Comma-separated GSUB/GPOS feature tags to keep; all others are dropped
+    #[arg(long, value_delimiter = ',', value_parser = str::parse::<Tag>)]
+    keep_features: Option<Vec<Tag>>,
+
+    /// Fail if the merged font would have more than this many glyphs
+    #[arg(long, value_name = "COUNT")]
+    max_glyphs: Option<usize>,
+
+    /// Scale every input to this unitsPerEm instead of requiring them to match
+    #[arg(long, value_name = "UPEM")]
+    output_upem: Option<u16>,
+
+    /// Family name for the merged font, rewriting the first font's family names
+    #[arg(long, value_name = "NAME")]
+    family_name: Option<String>,
+
     /// Enable verbose output
     #[arg(short, long)]
     verbose: bool,
@@ -47,10 +72,41 @@ fn main() -> Result<()> {
 
     let font_refs: Vec<&[u8]> = font_data.iter().map(Vec::as_slice).collect();
 
-    let options = Options::new().drop_tables(cli.drop_tables).verbose(cli.verbose);
+    let mut options = Options::new().drop_tables(cli.drop_tables).verbose(cli.verbose);
+    if cli.flatten_components {
+        options = options.flatten_composites(FontSelector::NonFirst);
+    }
+    if let Some(idx) = cli.prefer_marks_from {
+        options = options.prefer_marks_from(idx);
+    }
+    if let Some(features) = cli.keep_features {
+        options = options.keep_features(features);
+    }
+    if let Some(max) = cli.max_glyphs {
+        options = options.max_glyphs(max);
+    }
+    if let Some(upem) = cli.output_upem {
+        options = options.output_upem(upem);
+    }
+
+    if let Some(family_name) = &cli.family_name {
+        options = options.family_name(family_name).rebuild_names(true);
+    }
 
     let merger = Merger::new(options);
-    let merged = merger.merge(&font_refs)?;
+    let (merged, report) = merger.merge_verbose(&font_refs)?;
+
+    if cli.verbose {
+        for dropped in &report.dropped_lookups {
+            eprintln!(
+                "  Dropped {} lookup {} (type {}) from {}",
+                dropped.table,
+                dropped.lookup_index,
+                dropped.lookup_type,
+                cli.input_files[dropped.font_index.as_usize()].display()
+            );
+        }
+    }
 
     write(&cli.output, &merged)?;
 
@@ -12,18 +12,25 @@ use crate::{
     MergeError::IncompatibleUnitsPerEm,
     Result,
     context::{GlyphOrder, MergeContext},
-    options::Options,
+    options::{FontOptions, Options},
+    report::MergeReport,
+    scale::scale_font,
     tables::{
         cff::{check_cff, merge_cff, merge_cff2},
         cmap::merge_cmap,
+        colr::merge_colr,
+        gdef::merge_gdef,
         glyf::merge_glyf,
         head::merge_head,
         hhea::merge_hhea,
         hint::{check_hint_compatibility, log_hint_info},
         hmtx::merge_hmtx,
+        kern::merge_kern,
         layout::{merge_gpos, merge_gsub},
         maxp::merge_maxp,
+        meta::merge_meta,
         name::merge_name,
+        notdef::load_notdef,
         os2::merge_os2,
         post::merge_post,
         vhea::merge_vhea,
@@ -34,7 +41,7 @@ use crate::{
 /// Constant table tags to avoid repeated construction
 const HANDLED_TABLES: &[[u8; 4]] = &[
     *b"head", *b"maxp", *b"cmap", *b"hmtx", *b"hhea", *b"post", *b"OS/2", *b"name", *b"vhea",
-    *b"vmtx", *b"glyf", *b"loca", *b"CFF ", *b"CFF2", *b"GSUB", *b"GPOS",
+    *b"vmtx", *b"glyf", *b"loca", *b"CFF ", *b"CFF2", *b"GSUB", *b"GPOS", *b"kern",
 ];
 
 /// Font merger that combines multiple fonts into one
@@ -49,8 +56,18 @@ impl Merger {
         Self { options }
     }
 
+    /// Start a merge whose fonts each carry their own [`FontOptions`]
+    pub fn builder<'a>() -> MergerBuilder<'a> {
+        MergerBuilder::default()
+    }
+
     /// Merge multiple font files into one
     pub fn merge(&self, font_data: &[&[u8]]) -> Result<Vec<u8>> {
+        self.merge_verbose(font_data).map(|(merged, _)| merged)
+    }
+
+    /// Merge multiple font files into one, reporting what was dropped
+    pub fn merge_verbose(&self, font_data: &[&[u8]]) -> Result<(Vec<u8>, MergeReport)> {
         if font_data.is_empty() {
             return Err(MergeError::NoFonts);
         }
@@ -60,46 +77,100 @@ impl Merger {
             .map(|data| FontRef::new(data))
             .collect::<result::Result<_, _>>()?;
 
-        self.merge_fonts(&fonts)
+        self.merge_fonts_verbose(&fonts)
     }
 
     /// Merge multiple FontRef instances
+    ///
+    /// With [`Options::output_upem`] set, inputs at any other UPEM are scaled
+    /// to it first; otherwise all inputs must share one UPEM.
     pub fn merge_fonts(&self, fonts: &[FontRef]) -> Result<Vec<u8>> {
+        self.merge_fonts_verbose(fonts).map(|(merged, _)| merged)
+    }
+
+    /// [`Merger::merge_fonts`], also returning a [`MergeReport`]
+    pub fn merge_fonts_verbose(&self, fonts: &[FontRef]) -> Result<(Vec<u8>, MergeReport)> {
         if fonts.is_empty() {
             return Err(MergeError::NoFonts);
         }
 
+        let Some(upem) = self.options.output_upem else {
+            return self.merge_scaled(fonts);
+        };
+        let scaled: Vec<Option<Vec<u8>>> = fonts
+            .iter()
+            .map(|font| scale_font(font, upem))
+            .collect::<Result<_>>()?;
+        let fonts: Vec<FontRef> = fonts
+            .iter()
+            .zip(&scaled)
+            .map(|(font, data)| match data {
+                Some(data) => FontRef::new(data),
+                None => Ok(font.clone()),
+            })
+            .collect::<result::Result<_, _>>()?;
+        self.merge_scaled(&fonts)
+    }
+
+    /// Merge fonts that already share one UPEM
+    fn merge_scaled(&self, fonts: &[FontRef]) -> Result<(Vec<u8>, MergeReport)> {
         self.validate_units_per_em(fonts)?;
 
         check_hint_compatibility(fonts);
         log_hint_info(fonts);
 
-        let glyph_order = GlyphOrder::compute(fonts);
+        let glyph_order = GlyphOrder::compute_with(
+            fonts,
+            self.options.glyph_limit(),
+            self.options.name_suffix_scheme,
+        )?;
         let total_glyphs = glyph_order.total_glyphs();
 
         info!("Merging {} fonts with {total_glyphs} total glyphs", fonts.len());
 
-        let (cmap, duplicate_info) = merge_cmap(fonts, &glyph_order)?;
+        let (cmap, duplicate_info) = merge_cmap(fonts, &glyph_order, &self.options)?;
 
         let ctx = MergeContext::new(fonts, glyph_order, duplicate_info, &self.options);
 
         let mut head = merge_head(ctx.fonts())?;
         let maxp = merge_maxp(ctx.fonts(), total_glyphs)?;
-        let hmtx = merge_hmtx(&ctx)?;
-        let hhea = merge_hhea(ctx.fonts(), total_glyphs)?;
+        let mut hmtx = merge_hmtx(&ctx)?;
+        let hhea = merge_hhea(ctx.fonts(), total_glyphs, self.options.line_metrics)?;
         let post = merge_post(&ctx)?;
 
-        let os2 = merge_os2(ctx.fonts())?;
+        let os2 = merge_os2(ctx.fonts(), self.options.line_metrics)?;
         let vhea = merge_vhea(ctx.fonts(), total_glyphs)?;
         let vmtx = merge_vmtx(&ctx)?;
 
         let has_cff = check_cff(ctx.fonts())?;
-        let (glyf_loca, cff_data) =
-            if has_cff { (None, merge_cff(&ctx)?) } else { (merge_glyf(&ctx)?, None) };
+        let notdef = self
+            .options
+            .notdef
+            .as_deref()
+            .map(|data| load_notdef(data, head.units_per_em))
+            .transpose()?;
+        let (glyf_loca, cff_data) = if has_cff {
+            (None, merge_cff(&ctx)?)
+        } else {
+            (merge_glyf(&ctx, notdef.as_ref().map(|notdef| &notdef.glyph))?, None)
+        };
+        if let Some(notdef) = &notdef {
+            if glyf_loca.is_none() {
+                return Err(MergeError::InvalidNotdef(
+                    "merged font has no glyf outlines to replace".into(),
+                ));
+            }
+            notdef.apply_metrics(&mut hmtx, &mut head);
+        }
         let cff2_data = merge_cff2(&ctx)?;
 
-        let gsub = merge_gsub(&ctx)?;
-        let gpos = merge_gpos(&ctx)?;
+        let mut report = MergeReport::default();
+        let gsub = merge_gsub(&ctx, &mut report)?;
+        let gpos = merge_gpos(&ctx, &mut report)?;
+        let gdef = merge_gdef(&ctx)?;
+        let meta = merge_meta(ctx.fonts())?;
+        let colr = merge_colr(&ctx)?;
+        let kern = merge_kern(&ctx)?;
 
         let mut builder = FontBuilder::new();
 
@@ -121,7 +192,13 @@ impl Merger {
             builder.add_table(&os2)?;
         }
         if !self.options.should_drop_tag(Tag::new(b"name")) {
-            merge_name(ctx.fonts(), &mut builder)?;
+            merge_name(
+                ctx.fonts(),
+                self.options.rebuild_names,
+                self.options.family_name.as_deref(),
+                self.options.name_source,
+                &mut builder,
+            )?;
         }
         if let Some(vhea) = vhea.filter(|_| !self.options.should_drop_tag(Tag::new(b"vhea"))) {
             builder.add_table(&vhea)?;
@@ -147,10 +224,27 @@ impl Merger {
         if let Some(gpos) = gpos {
             builder.add_table(&gpos)?;
         }
+        if let Some(gdef) = gdef.filter(|_| !self.options.should_drop_tag(Tag::new(b"GDEF"))) {
+            builder.add_table(&gdef)?;
+        }
+        if let Some(kern) = kern.filter(|_| !self.options.should_drop_tag(Tag::new(b"kern"))) {
+            builder.add_raw(Tag::new(b"kern"), kern);
+        }
+        if let Some(meta) = meta.filter(|_| !self.options.should_drop_tag(Tag::new(b"meta"))) {
+            builder.add_table(&meta)?;
+        }
+        if let Some((colr, cpal)) =
+            colr.filter(|_| !self.options.should_drop_tag(Tag::new(b"COLR")))
+        {
+            builder.add_table(&colr)?;
+            if let Some(cpal) = cpal.filter(|_| !self.options.should_drop_tag(Tag::new(b"CPAL"))) {
+                builder.add_table(&cpal)?;
+            }
+        }
 
         self.copy_other_tables(&mut builder, ctx.first_font())?;
 
-        Ok(builder.build())
+        Ok((builder.build(), report))
     }
 
     fn validate_units_per_em(&self, fonts: &[FontRef]) -> Result<()> {
@@ -186,6 +280,69 @@ impl Merger {
     }
 }
 
+/// Fonts to merge, each with its own [`FontOptions`]
+///
+/// ```no_run
+/// use warpnine_font_merger::{FontOptions, Merger};
+///
+/// let base = std::fs::read("base.ttf").unwrap();
+/// let fallback = std::fs::read("fallback.ttf").unwrap();
+/// let merged = Merger::builder()
+///     .add_font(&base)
+///     .with_font_options(FontOptions::new().exclude_range(0x2000, 0x206F))
+///     .add_font(&fallback)
+///     .merge()
+///     .unwrap();
+/// ```
+#[derive(Debug, Default)]
+pub struct MergerBuilder<'a> {
+    options: Options,
+    fonts: Vec<&'a [u8]>,
+    font_options: Vec<Option<FontOptions>>,
+}
+
+impl<'a> MergerBuilder<'a> {
+    /// Options shared by every font; a font's options given with
+    /// [`MergerBuilder::with_font_options`] take the place of any set here
+    pub fn options(mut self, options: Options) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Add a font after the ones already added, with the font options from
+    /// [`MergerBuilder::options`], or the defaults
+    pub fn add_font(mut self, data: &'a [u8]) -> Self {
+        self.fonts.push(data);
+        self.font_options.push(None);
+        self
+    }
+
+    /// Set the options of the font added last; before any font is added
+    /// this does nothing
+    pub fn with_font_options(mut self, options: FontOptions) -> Self {
+        if let Some(last) = self.font_options.last_mut() {
+            *last = Some(options);
+        }
+        self
+    }
+
+    /// Merge the added fonts
+    pub fn merge(self) -> Result<Vec<u8>> {
+        self.merge_verbose().map(|(merged, _)| merged)
+    }
+
+    /// [`MergerBuilder::merge`], also returning a [`MergeReport`]
+    pub fn merge_verbose(self) -> Result<(Vec<u8>, MergeReport)> {
+        let mut options = self.options;
+        for (idx, font_options) in self.font_options.into_iter().enumerate() {
+            if let Some(font_options) = font_options {
+                options = options.font_options(idx, font_options);
+            }
+        }
+        Merger::new(options).merge_verbose(&self.fonts)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
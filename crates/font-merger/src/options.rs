@@ -2,7 +2,191 @@
 
 use read_fonts::types::Tag;
 
-use crate::types::TableTag;
+use crate::{
+    Result,
+    strategies::first,
+    types::{FontIndex, TableTag},
+};
+
+/// Most glyphs a font can hold, since glyph IDs are 16-bit
+const MAX_GLYPHS: usize = 65535;
+
+/// Selects a subset of the input fonts by position
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum FontSelector {
+    /// No fonts
+    #[default]
+    None,
+    /// Every input font
+    All,
+    /// Every input font except the first
+    NonFirst,
+    /// Only the fonts at the given indices
+    Indices(Vec<FontIndex>),
+}
+
+impl FontSelector {
+    /// Check if the font at `idx` is selected
+    pub fn contains(&self, idx: FontIndex) -> bool {
+        match self {
+            Self::None => false,
+            Self::All => true,
+            Self::NonFirst => idx.as_usize() > 0,
+            Self::Indices(indices) => indices.contains(&idx),
+        }
+    }
+}
+
+/// Which font's glyph a codepoint maps to when several fonts map it
+///
+/// The losing glyphs stay in the merged `glyf`, since GSUB may still reach
+/// them, unless nothing references them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ConflictStrategy {
+    /// The first font mapping the codepoint wins
+    #[default]
+    PreferFirst,
+    /// The last font mapping the codepoint wins
+    PreferLast,
+    /// The given font wins for codepoints in the inclusive ranges, if it maps
+    /// them; the first font wins elsewhere
+    PreferRange(Vec<(u32, u32)>, FontIndex),
+}
+
+impl ConflictStrategy {
+    /// Pick the winner among `candidates`, given in font order
+    pub(crate) fn choose<T>(&self, codepoint: u32, candidates: &[(FontIndex, T)]) -> usize {
+        match self {
+            Self::PreferFirst => 0,
+            Self::PreferLast => candidates.len().saturating_sub(1),
+            Self::PreferRange(ranges, font) => ranges
+                .iter()
+                .any(|&(start, end)| (start..=end).contains(&codepoint))
+                .then(|| candidates.iter().position(|(idx, _)| idx == font))
+                .flatten()
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// How the merged hhea and OS/2 ascent, descent and line gap are chosen
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineMetrics {
+    /// Keep the first font's metrics
+    First,
+    /// Grow to fit every input: the highest ascent, the lowest descent and
+    /// the largest line gap
+    #[default]
+    Max,
+}
+
+impl LineMetrics {
+    /// Merge one metric, using `grow` to combine the values under `Max`
+    pub(crate) fn merge<T: Clone>(self, values: &[T], grow: fn(&[T]) -> Result<T>) -> Result<T> {
+        match self {
+            Self::First => first(values),
+            Self::Max => grow(values),
+        }
+    }
+}
+
+/// Which font supplies a language's name records when several fonts have
+/// records for the same platform, encoding and language
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NameSource {
+    /// The first font with records for the language
+    #[default]
+    First,
+    /// The last font with records for the language
+    Last,
+}
+
+/// How a glyph name already taken by an earlier font is renamed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NameSuffixScheme {
+    /// Append a per-name counter: the second `A` becomes `A.1`
+    #[default]
+    Numeric,
+    /// Prefix the index of the font the glyph comes from: `A` of the second
+    /// font becomes `f1.A`
+    FontPrefix,
+}
+
+/// Which kerning survives for a font with both a legacy `kern` table and
+/// GPOS pair positioning
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KernPreference {
+    /// Keep the GPOS pair positioning; the font's legacy kern pairs are
+    /// dropped so shapers do not kern twice
+    #[default]
+    Gpos,
+    /// Keep the legacy kern pairs and drop the font's GPOS `kern` feature
+    Legacy,
+    /// Keep both
+    Both,
+}
+
+/// Settings that apply to a single input font
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FontOptions {
+    /// Inclusive codepoint ranges the font leaves to the other fonts; its own
+    /// glyphs for them are not mapped in the merged cmap
+    pub exclude_codepoints: Vec<(u32, u32)>,
+
+    /// GSUB/GPOS feature tags of the font to rename, from the first tag to
+    /// the second
+    pub feature_renames: Vec<(Tag, Tag)>,
+
+    /// Fonts with a higher priority win codepoints mapped by several fonts;
+    /// fonts of equal priority fall back to the conflict strategy
+    pub priority: i32,
+}
+
+impl FontOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Leave the codepoints in the inclusive range to the other fonts
+    pub fn exclude_range(mut self, start: u32, end: u32) -> Self {
+        self.exclude_codepoints.push((start, end));
+        self
+    }
+
+    /// Leave the codepoints to the other fonts
+    pub fn exclude_codepoints(mut self, codepoints: impl IntoIterator<Item = u32>) -> Self {
+        self.exclude_codepoints
+            .extend(codepoints.into_iter().map(|cp| (cp, cp)));
+        self
+    }
+
+    /// Rename the font's `from` feature to `to`
+    pub fn rename_feature(mut self, from: Tag, to: Tag) -> Self {
+        self.feature_renames.push((from, to));
+        self
+    }
+
+    /// Set the font's priority in codepoint conflicts
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Check if the font leaves `codepoint` to the other fonts
+    pub fn excludes(&self, codepoint: u32) -> bool {
+        self.exclude_codepoints
+            .iter()
+            .any(|&(start, end)| (start..=end).contains(&codepoint))
+    }
+
+    /// The tag the font's `tag` feature is merged under
+    pub fn feature_tag(&self, tag: Tag) -> Tag {
+        self.feature_renames
+            .iter()
+            .find(|(from, _)| *from == tag)
+            .map_or(tag, |(_, to)| *to)
+    }
+}
 
 /// Options for font merging
 #[derive(Debug, Clone, Default)]
@@ -15,6 +199,53 @@ pub struct Options {
 
     /// Whether to enable timing information
     pub timing: bool,
+
+    /// Fonts whose composite glyphs are decomposed into simple outlines
+    pub flatten_composites: FontSelector,
+
+    /// Font whose GPOS mark attachment lookups are moved ahead of all others
+    pub prefer_marks_from: Option<FontIndex>,
+
+    /// GSUB/GPOS feature tags to keep; `None` keeps every feature
+    pub keep_features: Option<Vec<Tag>>,
+
+    /// Cap on the merged glyph count, below the format's own limit of 65535
+    pub max_glyphs: Option<usize>,
+
+    /// Replacement outline for GID 0: a font whose own GID 0 is used, or a
+    /// raw `glyf` glyph record
+    pub notdef: Option<Vec<u8>>,
+
+    /// unitsPerEm of the merged font; inputs at other UPEMs are scaled to it
+    pub output_upem: Option<u16>,
+
+    /// Which font's glyph wins a codepoint mapped by several fonts
+    pub conflict_strategy: ConflictStrategy,
+
+    /// How the merged line metrics are chosen
+    pub line_metrics: LineMetrics,
+
+    /// Family name written by `rebuild_names`; `None` keeps the first font's
+    pub family_name: Option<String>,
+
+    /// Whether to rewrite the family names instead of copying the first
+    /// font's name table as is
+    pub rebuild_names: bool,
+
+    /// Merge name records from every font, taking each language from the
+    /// chosen font; `None` copies the first font's name table as is
+    pub name_source: Option<NameSource>,
+
+    /// Per-font settings, by input position; fonts past the end use the
+    /// defaults
+    pub font_options: Vec<FontOptions>,
+
+    /// How clashing glyph names are renamed
+    pub name_suffix_scheme: NameSuffixScheme,
+
+    /// Which kerning a font with both a legacy `kern` table and GPOS pair
+    /// positioning keeps
+    pub kern_preference: KernPreference,
 }
 
 impl Options {
@@ -49,6 +280,137 @@ impl Options {
         self
     }
 
+    /// Decompose composite glyphs from the selected fonts into simple glyphs
+    pub fn flatten_composites(mut self, selector: FontSelector) -> Self {
+        self.flatten_composites = selector;
+        self
+    }
+
+    /// Move the font's GPOS mark attachment lookups ahead of every other
+    /// lookup, so shapers apply them first
+    pub fn prefer_marks_from(mut self, font: impl Into<FontIndex>) -> Self {
+        self.prefer_marks_from = Some(font.into());
+        self
+    }
+
+    /// Keep only these GSUB/GPOS features, pruning lookups left unreferenced
+    pub fn keep_features(mut self, features: Vec<Tag>) -> Self {
+        self.keep_features = Some(features);
+        self
+    }
+
+    /// Fail the merge if the combined glyph count exceeds `max`
+    pub fn max_glyphs(mut self, max: usize) -> Self {
+        self.max_glyphs = Some(max);
+        self
+    }
+
+    /// Replace the merged `.notdef` outline with one from a font or raw glyph
+    pub fn notdef_from(mut self, notdef: Option<Vec<u8>>) -> Self {
+        self.notdef = notdef;
+        self
+    }
+
+    /// Scale every input to `upem` units per em instead of requiring them to match
+    pub fn output_upem(mut self, upem: u16) -> Self {
+        self.output_upem = Some(upem);
+        self
+    }
+
+    /// Choose which font's glyph a codepoint mapped by several fonts gets
+    pub fn conflict_strategy(mut self, strategy: ConflictStrategy) -> Self {
+        self.conflict_strategy = strategy;
+        self
+    }
+
+    /// Choose the merged hhea and OS/2 line metrics
+    pub fn line_metrics(mut self, line_metrics: LineMetrics) -> Self {
+        self.line_metrics = line_metrics;
+        self
+    }
+
+    /// Family name for the merged font's name table; see [`Options::rebuild_names`]
+    pub fn family_name(mut self, family_name: &str) -> Self {
+        self.family_name = Some(family_name.to_string());
+        self
+    }
+
+    /// Rewrite name IDs 1, 4, 6, 16 and 17 of the first font's name table
+    /// for the family name, keeping its style
+    pub fn rebuild_names(mut self, rebuild_names: bool) -> Self {
+        self.rebuild_names = rebuild_names;
+        self
+    }
+
+    /// Merge name records per language, taking each from `name_source`
+    pub fn name_source(mut self, name_source: NameSource) -> Self {
+        self.name_source = Some(name_source);
+        self
+    }
+
+    /// Choose how a glyph name already taken by an earlier font is renamed
+    pub fn name_suffix_scheme(mut self, scheme: NameSuffixScheme) -> Self {
+        self.name_suffix_scheme = scheme;
+        self
+    }
+
+    /// Choose between legacy `kern` and GPOS kerning for fonts with both
+    pub fn kern_preference(mut self, preference: KernPreference) -> Self {
+        self.kern_preference = preference;
+        self
+    }
+
+    /// Set the options of the font at `font`
+    pub fn font_options(mut self, font: impl Into<FontIndex>, options: FontOptions) -> Self {
+        let idx = font.into().as_usize();
+        if self.font_options.len() <= idx {
+            self.font_options.resize_with(idx + 1, FontOptions::default);
+        }
+        self.font_options[idx] = options;
+        self
+    }
+
+    /// Options of the font at `font`, if any were set
+    pub fn options_for(&self, font: FontIndex) -> Option<&FontOptions> {
+        self.font_options.get(font.as_usize())
+    }
+
+    /// Check if the font at `font` leaves `codepoint` to the other fonts
+    pub(crate) fn excludes(&self, font: FontIndex, codepoint: u32) -> bool {
+        self.options_for(font)
+            .is_some_and(|options| options.excludes(codepoint))
+    }
+
+    /// The tag the `tag` feature of the font at `font` is merged under
+    pub(crate) fn feature_tag(&self, font: FontIndex, tag: Tag) -> Tag {
+        self.options_for(font).map_or(tag, |options| options.feature_tag(tag))
+    }
+
+    /// Priority of the font at `font` in codepoint conflicts
+    pub(crate) fn priority(&self, font: FontIndex) -> i32 {
+        self.options_for(font).map_or(0, |options| options.priority)
+    }
+
+    /// Pick the winner among `candidates`, given in font order: the highest
+    /// priority fonts, then the conflict strategy among them
+    pub(crate) fn choose<T>(&self, codepoint: u32, candidates: &[(FontIndex, T)]) -> usize {
+        let top = candidates.iter().map(|(idx, _)| self.priority(*idx)).max();
+        let preferred: Vec<(FontIndex, usize)> = candidates
+            .iter()
+            .enumerate()
+            .filter(|(_, (idx, _))| Some(self.priority(*idx)) == top)
+            .map(|(i, (idx, _))| (*idx, i))
+            .collect();
+        preferred
+            .get(self.conflict_strategy.choose(codepoint, &preferred))
+            .map_or(0, |&(_, i)| i)
+    }
+
+    /// Glyph count the merged font may not exceed
+    pub fn glyph_limit(&self) -> usize {
+        self.max_glyphs.map_or(MAX_GLYPHS, |max| max.min(MAX_GLYPHS))
+    }
+
     /// Check if a table should be dropped
     pub fn should_drop(&self, tag: &TableTag) -> bool {
         self.drop_tables.contains(tag)
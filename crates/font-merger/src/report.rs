@@ -0,0 +1,27 @@
+//! Report of what a merge could not carry over
+
+use read_fonts::types::Tag;
+
+use crate::types::FontIndex;
+
+/// What [`Merger::merge_verbose`](crate::Merger::merge_verbose) dropped
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MergeReport {
+    /// GSUB/GPOS lookups that could not be converted, in merge order
+    pub dropped_lookups: Vec<DroppedLookup>,
+}
+
+/// A GSUB or GPOS lookup left out of the merged font
+///
+/// A lookup is dropped when its subtables cannot be read, or when none of
+/// them survive glyph remapping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DroppedLookup {
+    pub font_index: FontIndex,
+    /// `GSUB` or `GPOS`
+    pub table: Tag,
+    /// Index in the source font's lookup list
+    pub lookup_index: u16,
+    /// LookupType as stored in the lookup table (7 or 9 for Extension)
+    pub lookup_type: u16,
+}
@@ -0,0 +1,429 @@
+//! Rescaling fonts to a different unitsPerEm
+//!
+//! Inputs whose UPEM differs from [`Options::output_upem`] are rewritten
+//! before merging: outlines, metrics, vertical metrics, OS/2 and post
+//! dimensions, GPOS values and anchors, and GDEF caret positions are all
+//! multiplied by `output_upem / input_upem` and rounded.
+//!
+//! Hinting does not survive a change of scale, so scaled fonts lose their
+//! glyph instructions along with `fpgm`, `prep` and `cvt `; pixel-size tables
+//! (`hdmx`, `LTSH`, `VDMX`) are dropped too. Tables holding design-unit data
+//! that is not rewritten here make the merge fail rather than come out
+//! silently at the wrong size.
+//!
+//! [`Options::output_upem`]: crate::Options::output_upem
+
+use std::collections::HashSet;
+
+use read_fonts::{FontData, FontRef, TableProvider, tables::glyf::CurvePoint, types::GlyphId};
+use write_fonts::{
+    FontBuilder, NullableOffsetMarker,
+    from_obj::{FromObjRef, ToOwnedTable},
+    tables::{
+        gdef::{CaretValue, Gdef},
+        glyf::{Anchor, Bbox, CompositeGlyph, GlyfLocaBuilder, Glyph},
+        gpos::{
+            AnchorTable, CursivePosFormat1, ExtensionSubtable, Gpos, MarkArray, MarkBasePosFormat1,
+            MarkLigPosFormat1, MarkMarkPosFormat1, PairPos, PositionLookup, SinglePos, ValueRecord,
+        },
+        head::Head,
+        hhea::Hhea,
+        hmtx::{Hmtx, LongMetric},
+        loca::LocaFormat,
+        os2::Os2,
+        post::Post,
+        vhea::Vhea,
+        vmtx::Vmtx,
+    },
+    types::{FWord, Tag, UfWord},
+};
+
+use crate::{MergeError, Result};
+
+/// Tables carrying design units that are not rescaled
+const UNSCALABLE_TABLES: &[[u8; 4]] = &[*b"CFF ", *b"CFF2", *b"gvar", *b"COLR", *b"kern", *b"MATH"];
+
+/// Tables dropped from rescaled fonts: hinting and pixel-size metrics
+const DROPPED_TABLES: &[[u8; 4]] = &[*b"fpgm", *b"prep", *b"cvt ", *b"hdmx", *b"LTSH", *b"VDMX"];
+
+/// Tables rebuilt at the new scale
+const SCALED_TABLES: &[[u8; 4]] = &[
+    *b"head", *b"hhea", *b"hmtx", *b"vhea", *b"vmtx", *b"OS/2", *b"post", *b"glyf", *b"loca",
+    *b"GPOS", *b"GDEF",
+];
+
+/// Rescale `font` to `units_per_em`
+///
+/// Returns `None` when the font already uses that UPEM.
+pub fn scale_font(font: &FontRef, units_per_em: u16) -> Result<Option<Vec<u8>>> {
+    let source_upem = font.head()?.units_per_em();
+    if source_upem == units_per_em {
+        return Ok(None);
+    }
+    if let Some(tag) = UNSCALABLE_TABLES
+        .iter()
+        .map(Tag::new)
+        .find(|tag| font.table_data(*tag).is_some())
+    {
+        return Err(MergeError::UnscalableTable(tag));
+    }
+
+    let scale = Scale(f64::from(units_per_em) / f64::from(source_upem));
+    let mut builder = FontBuilder::new();
+
+    let mut head: Head = font.head()?.to_owned_table();
+    head.units_per_em = units_per_em;
+    head.x_min = scale.i16(head.x_min);
+    head.y_min = scale.i16(head.y_min);
+    head.x_max = scale.i16(head.x_max);
+    head.y_max = scale.i16(head.y_max);
+
+    if let (Ok(glyf), Ok(loca)) = (font.glyf(), font.loca(None)) {
+        let mut glyphs = GlyfLocaBuilder::new();
+        for gid in 0..u32::from(font.maxp()?.num_glyphs()) {
+            let glyph = match loca.get_glyf(GlyphId::new(gid), &glyf)? {
+                Some(glyph) => scale.glyph(Glyph::from_obj_ref(&glyph, FontData::default())),
+                None => Glyph::Empty,
+            };
+            glyphs.add_glyph(&glyph)?;
+        }
+        let (glyf, loca, format) = glyphs.build();
+        head.index_to_loc_format = match format {
+            LocaFormat::Short => 0,
+            LocaFormat::Long => 1,
+        };
+        builder.add_table(&glyf)?;
+        builder.add_table(&loca)?;
+    }
+    builder.add_table(&head)?;
+
+    let mut hhea: Hhea = font.hhea()?.to_owned_table();
+    hhea.ascender = scale.fword(hhea.ascender);
+    hhea.descender = scale.fword(hhea.descender);
+    hhea.line_gap = scale.fword(hhea.line_gap);
+    hhea.advance_width_max = scale.ufword(hhea.advance_width_max);
+    hhea.min_left_side_bearing = scale.fword(hhea.min_left_side_bearing);
+    hhea.min_right_side_bearing = scale.fword(hhea.min_right_side_bearing);
+    hhea.x_max_extent = scale.fword(hhea.x_max_extent);
+    hhea.caret_offset = scale.i16(hhea.caret_offset);
+    builder.add_table(&hhea)?;
+
+    let mut hmtx: Hmtx = font.hmtx()?.to_owned_table();
+    scale.metrics(&mut hmtx.h_metrics, &mut hmtx.left_side_bearings);
+    builder.add_table(&hmtx)?;
+
+    if let Ok(vhea) = font.vhea() {
+        let mut vhea: Vhea = vhea.to_owned_table();
+        vhea.ascender = scale.fword(vhea.ascender);
+        vhea.descender = scale.fword(vhea.descender);
+        vhea.line_gap = scale.fword(vhea.line_gap);
+        vhea.advance_height_max = scale.ufword(vhea.advance_height_max);
+        vhea.min_top_side_bearing = scale.fword(vhea.min_top_side_bearing);
+        vhea.min_bottom_side_bearing = scale.fword(vhea.min_bottom_side_bearing);
+        vhea.y_max_extent = scale.fword(vhea.y_max_extent);
+        vhea.caret_offset = scale.i16(vhea.caret_offset);
+        builder.add_table(&vhea)?;
+    }
+    if let Ok(vmtx) = font.vmtx() {
+        let mut vmtx: Vmtx = vmtx.to_owned_table();
+        scale.metrics(&mut vmtx.v_metrics, &mut vmtx.top_side_bearings);
+        builder.add_table(&vmtx)?;
+    }
+
+    if let Ok(os2) = font.os2() {
+        let mut os2: Os2 = os2.to_owned_table();
+        for value in [
+            &mut os2.x_avg_char_width,
+            &mut os2.y_subscript_x_size,
+            &mut os2.y_subscript_y_size,
+            &mut os2.y_subscript_x_offset,
+            &mut os2.y_subscript_y_offset,
+            &mut os2.y_superscript_x_size,
+            &mut os2.y_superscript_y_size,
+            &mut os2.y_superscript_x_offset,
+            &mut os2.y_superscript_y_offset,
+            &mut os2.y_strikeout_size,
+            &mut os2.y_strikeout_position,
+            &mut os2.s_typo_ascender,
+            &mut os2.s_typo_descender,
+            &mut os2.s_typo_line_gap,
+        ] {
+            *value = scale.i16(*value);
+        }
+        os2.us_win_ascent = scale.u16(os2.us_win_ascent);
+        os2.us_win_descent = scale.u16(os2.us_win_descent);
+        os2.sx_height = os2.sx_height.map(|v| scale.i16(v));
+        os2.s_cap_height = os2.s_cap_height.map(|v| scale.i16(v));
+        builder.add_table(&os2)?;
+    }
+
+    if let Ok(post) = font.post() {
+        let mut post: Post = post.to_owned_table();
+        post.underline_position = scale.fword(post.underline_position);
+        post.underline_thickness = scale.fword(post.underline_thickness);
+        builder.add_table(&post)?;
+    }
+
+    if let Ok(gpos) = font.gpos() {
+        let mut gpos: Gpos = gpos.to_owned_table();
+        for lookup in &mut gpos.lookup_list.lookups {
+            scale.position_lookup(lookup);
+        }
+        builder.add_table(&gpos)?;
+    }
+
+    if let Ok(gdef) = font.gdef() {
+        let mut gdef: Gdef = gdef.to_owned_table();
+        if let Some(carets) = gdef.lig_caret_list.as_mut() {
+            for caret in carets
+                .lig_glyphs
+                .iter_mut()
+                .flat_map(|lig| lig.caret_values.iter_mut())
+            {
+                match &mut **caret {
+                    CaretValue::Format1(value) => value.coordinate = scale.i16(value.coordinate),
+                    CaretValue::Format2(_) => (),
+                    CaretValue::Format3(value) => value.coordinate = scale.i16(value.coordinate),
+                }
+            }
+        }
+        builder.add_table(&gdef)?;
+    }
+
+    let skipped: HashSet<Tag> = SCALED_TABLES.iter().chain(DROPPED_TABLES).map(Tag::new).collect();
+    for record in font.table_directory.table_records() {
+        let tag = record.tag();
+        if skipped.contains(&tag) || builder.contains(tag) {
+            continue;
+        }
+        if let Some(data) = font.table_data(tag) {
+            builder.add_raw(tag, data.as_bytes().to_vec());
+        }
+    }
+
+    Ok(Some(builder.build()))
+}
+
+/// Multiplier from the source UPEM to the target UPEM
+#[derive(Clone, Copy)]
+struct Scale(f64);
+
+impl Scale {
+    fn i16(self, value: i16) -> i16 {
+        (f64::from(value) * self.0)
+            .round()
+            .clamp(f64::from(i16::MIN), f64::from(i16::MAX)) as i16
+    }
+
+    fn u16(self, value: u16) -> u16 {
+        (f64::from(value) * self.0).round().clamp(0.0, f64::from(u16::MAX)) as u16
+    }
+
+    fn fword(self, value: FWord) -> FWord {
+        FWord::new(self.i16(value.to_i16()))
+    }
+
+    fn ufword(self, value: UfWord) -> UfWord {
+        UfWord::new(self.u16(value.to_u16()))
+    }
+
+    fn bbox(self, bbox: Bbox) -> Bbox {
+        Bbox {
+            x_min: self.i16(bbox.x_min),
+            y_min: self.i16(bbox.y_min),
+            x_max: self.i16(bbox.x_max),
+            y_max: self.i16(bbox.y_max),
+        }
+    }
+
+    fn metrics(self, long: &mut [LongMetric], bearings: &mut [i16]) {
+        for metric in long {
+            metric.advance = self.u16(metric.advance);
+            metric.side_bearing = self.i16(metric.side_bearing);
+        }
+        for bearing in bearings {
+            *bearing = self.i16(*bearing);
+        }
+    }
+
+    /// Scale a glyph's points or component offsets, dropping its instructions
+    fn glyph(self, glyph: Glyph) -> Glyph {
+        match glyph {
+            Glyph::Empty => Glyph::Empty,
+            Glyph::Simple(mut simple) => {
+                for contour in &mut simple.contours {
+                    let mut points: Vec<CurvePoint> = contour.clone().into();
+                    for point in &mut points {
+                        point.x = self.i16(point.x);
+                        point.y = self.i16(point.y);
+                    }
+                    *contour = points.into();
+                }
+                simple.instructions.clear();
+                simple.recompute_bounding_box();
+                Glyph::Simple(simple)
+            }
+            Glyph::Composite(composite) => {
+                let bbox = self.bbox(composite.bbox);
+                let mut components = composite.components().iter().cloned().map(|mut component| {
+                    if let Anchor::Offset { x, y } = component.anchor {
+                        component.anchor = Anchor::Offset { x: self.i16(x), y: self.i16(y) };
+                    }
+                    component
+                });
+                let Some(first) = components.next() else {
+                    return Glyph::Empty;
+                };
+                let mut scaled = CompositeGlyph::new(first, bbox);
+                for component in components {
+                    scaled.add_component(component, bbox);
+                }
+                Glyph::Composite(scaled)
+            }
+        }
+    }
+
+    fn value_record(self, record: &mut ValueRecord) {
+        for value in [
+            &mut record.x_placement,
+            &mut record.y_placement,
+            &mut record.x_advance,
+            &mut record.y_advance,
+        ] {
+            *value = value.map(|v| self.i16(v));
+        }
+    }
+
+    fn anchor(self, anchor: &mut AnchorTable) {
+        let (x, y) = match anchor {
+            AnchorTable::Format1(a) => (&mut a.x_coordinate, &mut a.y_coordinate),
+            AnchorTable::Format2(a) => (&mut a.x_coordinate, &mut a.y_coordinate),
+            AnchorTable::Format3(a) => (&mut a.x_coordinate, &mut a.y_coordinate),
+        };
+        *x = self.i16(*x);
+        *y = self.i16(*y);
+    }
+
+    fn mark_array(self, marks: &mut MarkArray) {
+        for record in &mut marks.mark_records {
+            self.anchor(&mut record.mark_anchor);
+        }
+    }
+
+    fn anchors<'a>(self, anchors: impl Iterator<Item = &'a mut NullableOffsetMarker<AnchorTable>>) {
+        anchors.filter_map(|a| a.as_mut()).for_each(|a| self.anchor(a));
+    }
+
+    fn single_pos(self, subtable: &mut SinglePos) {
+        match subtable {
+            SinglePos::Format1(single) => self.value_record(&mut single.value_record),
+            SinglePos::Format2(single) => {
+                single.value_records.iter_mut().for_each(|r| self.value_record(r));
+            }
+        }
+    }
+
+    fn pair_pos(self, subtable: &mut PairPos) {
+        let records: Vec<(&mut ValueRecord, &mut ValueRecord)> = match subtable {
+            PairPos::Format1(pair) => pair
+                .pair_sets
+                .iter_mut()
+                .flat_map(|set| set.pair_value_records.iter_mut())
+                .map(|r| (&mut r.value_record1, &mut r.value_record2))
+                .collect(),
+            PairPos::Format2(pair) => pair
+                .class1_records
+                .iter_mut()
+                .flat_map(|class1| class1.class2_records.iter_mut())
+                .map(|r| (&mut r.value_record1, &mut r.value_record2))
+                .collect(),
+        };
+        for (first, second) in records {
+            self.value_record(first);
+            self.value_record(second);
+        }
+    }
+
+    fn cursive_pos(self, subtable: &mut CursivePosFormat1) {
+        self.anchors(
+            subtable
+                .entry_exit_record
+                .iter_mut()
+                .flat_map(|r| [&mut r.entry_anchor, &mut r.exit_anchor]),
+        );
+    }
+
+    fn mark_base_pos(self, subtable: &mut MarkBasePosFormat1) {
+        self.mark_array(&mut subtable.mark_array);
+        self.anchors(
+            subtable
+                .base_array
+                .base_records
+                .iter_mut()
+                .flat_map(|r| r.base_anchors.iter_mut()),
+        );
+    }
+
+    fn mark_lig_pos(self, subtable: &mut MarkLigPosFormat1) {
+        self.mark_array(&mut subtable.mark_array);
+        self.anchors(
+            subtable
+                .ligature_array
+                .ligature_attaches
+                .iter_mut()
+                .flat_map(|attach| attach.component_records.iter_mut())
+                .flat_map(|r| r.ligature_anchors.iter_mut()),
+        );
+    }
+
+    fn mark_mark_pos(self, subtable: &mut MarkMarkPosFormat1) {
+        self.mark_array(&mut subtable.mark1_array);
+        self.anchors(
+            subtable
+                .mark2_array
+                .mark2_records
+                .iter_mut()
+                .flat_map(|r| r.mark2_anchors.iter_mut()),
+        );
+    }
+
+    /// Scale every value record and anchor in a GPOS lookup
+    ///
+    /// Contextual lookups only reference other lookups, which are scaled in
+    /// their own right.
+    fn position_lookup(self, lookup: &mut PositionLookup) {
+        match lookup {
+            PositionLookup::Single(l) => l.subtables.iter_mut().for_each(|s| self.single_pos(s)),
+            PositionLookup::Pair(l) => l.subtables.iter_mut().for_each(|s| self.pair_pos(s)),
+            PositionLookup::Cursive(l) => l.subtables.iter_mut().for_each(|s| self.cursive_pos(s)),
+            PositionLookup::MarkToBase(l) => {
+                l.subtables.iter_mut().for_each(|s| self.mark_base_pos(s));
+            }
+            PositionLookup::MarkToLig(l) => {
+                l.subtables.iter_mut().for_each(|s| self.mark_lig_pos(s));
+            }
+            PositionLookup::MarkToMark(l) => {
+                l.subtables.iter_mut().for_each(|s| self.mark_mark_pos(s));
+            }
+            PositionLookup::Contextual(_) | PositionLookup::ChainContextual(_) => (),
+            PositionLookup::Extension(l) => {
+                for subtable in &mut l.subtables {
+                    match &mut **subtable {
+                        ExtensionSubtable::Single(ext) => self.single_pos(&mut ext.extension),
+                        ExtensionSubtable::Pair(ext) => self.pair_pos(&mut ext.extension),
+                        ExtensionSubtable::Cursive(ext) => self.cursive_pos(&mut ext.extension),
+                        ExtensionSubtable::MarkToBase(ext) => {
+                            self.mark_base_pos(&mut ext.extension);
+                        }
+                        ExtensionSubtable::MarkToLig(ext) => self.mark_lig_pos(&mut ext.extension),
+                        ExtensionSubtable::MarkToMark(ext) => {
+                            self.mark_mark_pos(&mut ext.extension);
+                        }
+                        ExtensionSubtable::Contextual(_)
+                        | ExtensionSubtable::ChainContextual(_) => (),
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,34 +1,45 @@
 //! CFF table merging
 //!
-//! **Current Limitations:**
+//! Charstrings from every input are desubroutinized (local and global subrs
+//! inlined, hints kept) and written in mega glyph order into a new non-CID
+//! `CFF ` table without subroutines. Each charstring's width is rewritten
+//! from `hmtx`, so the inputs' `defaultWidthX`/`nominalWidthX` need not agree.
 //!
-//! CFF (Compact Font Format) table merging is not yet fully implemented.
-//! The current implementation copies the CFF table from the first font only.
+//! The Top DICT, String INDEX and Private DICT come from the first font, so
+//! hinting zones of later fonts are replaced by the first font's. All inputs
+//! must be CFF with the same FontMatrix; CID-keyed fonts are rejected.
 //!
-//! A proper implementation would require:
-//! 1. Parsing CFF charstrings from all fonts
-//! 2. Desubroutinizing charstrings (inlining all local and global subroutines)
-//! 3. Reordering charstrings to match the mega glyph order
-//! 4. Combining charstrings into a single CFF table
-//! 5. Optionally re-subroutinizing for size optimization
-//!
-//! This is complex because:
-//! - CFF uses a stack-based bytecode for charstrings
-//! - Subroutines are shared code sequences that must be inlined before merging
-//! - The `write-fonts` crate doesn't yet have CFF table support
-//!
-//! For fonts with CFF outlines, users may want to convert to TrueType outlines
-//! before merging, or use fontTools' merge functionality instead.
+//! CFF2 merging is not implemented; see [`merge_cff2`].
+
+use std::{collections::HashMap, ops::Range};
 
 use log::{info, warn};
-use read_fonts::{FontRef, TableProvider, types::Tag};
+use read_fonts::{
+    FontRef, TableProvider,
+    ps::{
+        cff::{CffFontRef, index::Index},
+        string::STANDARD_STRINGS,
+    },
+    tables::cff::Cff,
+    types::{GlyphId as ReadGlyphId, Tag},
+};
+use warpnine_font_ops::cff::{self, encode_charstring_int, push_offsets, push_op, write_index};
+
+use crate::{MergeError, Result, context::MergeContext, glyph_order::GlyphName};
 
-use crate::{Result, context::MergeContext};
+/// Nesting limit for subroutine calls, from the Type 2 charstring spec
+const MAX_SUBR_DEPTH: usize = 10;
+
+/// Top DICT operators rewritten for the merged table: charset, Encoding,
+/// CharStrings and Private
+const TOP_DICT_REPLACED: [u16; 4] = [15, 16, 17, 18];
+
+/// Private DICT operators dropped: Subrs, defaultWidthX and nominalWidthX
+const PRIVATE_DICT_DROPPED: [u16; 3] = [19, 20, 21];
 
 /// Check if any font has CFF outlines
 ///
 /// Returns true if at least one font has a CFF table.
-/// Note: CID-keyed CFF fonts are not currently supported.
 pub fn check_cff(fonts: &[FontRef]) -> Result<bool> {
     for font in fonts {
         if font.cff().is_ok() {
@@ -40,49 +51,56 @@ pub fn check_cff(fonts: &[FontRef]) -> Result<bool> {
 
 /// Merge CFF tables from multiple fonts
 ///
-/// # Current Implementation
-///
-/// This function currently **only copies the CFF table from the first font**.
-/// Glyphs from subsequent fonts will not be included in the output.
-///
-/// # Warnings
-///
-/// When merging multiple CFF fonts, a warning is logged because the merge
-/// is incomplete. The resulting font will only contain glyphs from the first font.
-///
-/// # Future Implementation
-///
-/// A proper implementation would:
-/// 1. Parse each CFF table using a CFF parser
-/// 2. Desubroutinize all charstrings (inline subroutine calls)
-/// 3. Create a new charstring index with glyphs in mega glyph order
-/// 4. Build a new CFF table with combined data
-/// 5. Optionally apply subroutinization for size optimization
+/// Returns `None` when no font has a `CFF ` table. Fails with
+/// `MissingTable` if only some inputs are CFF, `CidKeyedCffNotSupported`
+/// for CID-keyed inputs and `IncompatibleFontMatrix` when FontMatrix
+/// differs between inputs.
 pub fn merge_cff(ctx: &MergeContext) -> Result<Option<Vec<u8>>> {
-    // Check if any font has CFF
-    let cff_fonts: Vec<_> = ctx.fonts().iter().filter(|f| f.cff().is_ok()).collect();
-    if cff_fonts.is_empty() {
+    if !check_cff(ctx.fonts())? {
         return Ok(None);
     }
 
-    // Warn if we're merging multiple CFF fonts
-    if cff_fonts.len() > 1 {
-        warn!(
-            "CFF merging is not fully implemented. Only glyphs from the first font will be included. \
-             {} fonts have CFF outlines but only the first will be used.",
-            cff_fonts.len()
-        );
+    let tables: Vec<&[u8]> = ctx
+        .fonts()
+        .iter()
+        .map(|font| {
+            font.table_data(Tag::new(b"CFF "))
+                .map(|data| data.as_bytes())
+                .ok_or(MergeError::MissingTable("CFF "))
+        })
+        .collect::<Result<_>>()?;
+    let sources: Vec<CffFontRef> = tables
+        .iter()
+        .map(|data| {
+            let cff = CffFontRef::new_cff(data, 0, None)?;
+            if cff.is_cid() {
+                return Err(MergeError::CidKeyedCffNotSupported);
+            }
+            Ok(cff)
+        })
+        .collect::<Result<_>>()?;
+    if sources.iter().any(|cff| cff.matrix() != sources[0].matrix()) {
+        return Err(MergeError::IncompatibleFontMatrix);
     }
 
-    // Return the first font's CFF table
-    if let Some(font) = cff_fonts.first()
-        && let Some(data) = font.table_data(Tag::new(b"CFF "))
-    {
-        info!("Copying CFF table from first font ({} bytes)", data.len());
-        return Ok(Some(data.as_bytes().to_vec()));
+    let mut charstrings = vec![vec![14u8]; ctx.total_glyphs() as usize];
+    for (font_idx, font, remap) in ctx.fonts_with_remap() {
+        let cff = &sources[font_idx.as_usize()];
+        let local_subrs = local_subrs(cff)?;
+        let hmtx = font.hmtx()?;
+        for (gid, mega) in remap.iter() {
+            let gid = ReadGlyphId::new(gid.to_u32());
+            let source = cff.charstrings().get(gid.to_u32() as usize)?;
+            let advance = hmtx.advance(gid).unwrap_or_default();
+            charstrings[mega.to_u16() as usize] =
+                desubroutinize(source, cff.global_subrs(), &local_subrs, advance)?;
+        }
     }
 
-    Ok(None)
+    let first = ctx.first_font().cff()?;
+    let data = build_cff(&first, tables[0], ctx.mega(), &charstrings)?;
+    info!("Merged CFF table: {} glyphs, {} bytes", charstrings.len(), data.len());
+    Ok(Some(data))
 }
 
 /// Merge CFF2 tables from multiple fonts
@@ -114,3 +132,300 @@ pub fn merge_cff2(ctx: &MergeContext) -> Result<Option<Vec<u8>>> {
 
     Ok(None)
 }
+
+/// The local Subrs INDEX of a non-CID font, or an empty one
+fn local_subrs<'a>(cff: &CffFontRef<'a>) -> Result<Index<'a>> {
+    let offset = cff.subfont(0, &[])?.subrs_offset() as usize;
+    if offset == 0 {
+        return Ok(Index::Empty);
+    }
+    let data = cff
+        .data()
+        .get(offset..)
+        .ok_or(MergeError::InvalidCharstring("Subrs offset out of bounds".into()))?;
+    Ok(Index::new(data, false)?)
+}
+
+/// Inline every subroutine call of `charstring` and replace its width with
+/// `advance`, relative to a `nominalWidthX` of 0.
+fn desubroutinize(
+    charstring: &[u8],
+    global_subrs: &Index,
+    local_subrs: &Index,
+    advance: u16,
+) -> Result<Vec<u8>> {
+    let mut flattener = Flattener::new(global_subrs, local_subrs);
+    flattener.run(charstring, 0)?;
+    if !flattener.ended {
+        flattener.out.push(14);
+    }
+
+    let mut out = Vec::with_capacity(flattener.out.len() + 3);
+    if advance != 0 {
+        encode_charstring_int(&mut out, i32::from(advance));
+    }
+    let width = flattener.width.unwrap_or(0..0);
+    out.extend_from_slice(&flattener.out[..width.start]);
+    out.extend_from_slice(&flattener.out[width.end..]);
+    Ok(out)
+}
+
+/// Copies a Type 2 charstring token by token, following subroutine calls.
+///
+/// Stem hints are counted to know how many mask bytes follow `hintmask` and
+/// `cntrmask`; the byte range of the width operand, if any, is recorded at
+/// the first stack-clearing operator.
+struct Flattener<'a, 'b> {
+    global_subrs: &'b Index<'a>,
+    local_subrs: &'b Index<'a>,
+    out: Vec<u8>,
+    /// Start in `out` and integer value of each operand on the stack
+    operands: Vec<(usize, Option<i32>)>,
+    stems: usize,
+    width: Option<Range<usize>>,
+    seen_stack_clear: bool,
+    ended: bool,
+}
+
+impl<'a, 'b> Flattener<'a, 'b> {
+    fn new(global_subrs: &'b Index<'a>, local_subrs: &'b Index<'a>) -> Self {
+        Self {
+            global_subrs,
+            local_subrs,
+            out: Vec::new(),
+            operands: Vec::new(),
+            stems: 0,
+            width: None,
+            seen_stack_clear: false,
+            ended: false,
+        }
+    }
+
+    fn run(&mut self, code: &[u8], depth: usize) -> Result<()> {
+        let invalid = |reason: &str| MergeError::InvalidCharstring(reason.into());
+        let mut i = 0;
+        while i < code.len() && !self.ended {
+            let b0 = code[i];
+            match b0 {
+                28 | 32..=255 => {
+                    let len = match b0 {
+                        28 => 3,
+                        32..=246 => 1,
+                        247..=254 => 2,
+                        _ => 5,
+                    };
+                    let bytes = code.get(i..i + len).ok_or_else(|| invalid("truncated number"))?;
+                    self.operands.push((self.out.len(), decode_charstring_int(bytes)));
+                    self.out.extend_from_slice(bytes);
+                    i += len;
+                }
+                // callsubr, callgsubr
+                10 | 29 => {
+                    let (start, index) =
+                        self.operands.pop().ok_or_else(|| invalid("empty stack"))?;
+                    let index = index.ok_or_else(|| invalid("computed subroutine index"))?;
+                    self.out.truncate(start);
+                    let subrs = if b0 == 10 { self.local_subrs } else { self.global_subrs };
+                    let index = usize::try_from(index + subrs.subr_bias())
+                        .map_err(|_| invalid("negative subroutine index"))?;
+                    if depth >= MAX_SUBR_DEPTH {
+                        return Err(invalid("subroutines nested too deeply"));
+                    }
+                    self.run(subrs.get(index)?, depth + 1)?;
+                    i += 1;
+                }
+                // return
+                11 => return Ok(()),
+                12 => {
+                    let b1 = *code.get(i + 1).ok_or_else(|| invalid("truncated operator"))?;
+                    // Only dotsection and the flex operators; arithmetic and
+                    // storage operators would need a real interpreter
+                    if !matches!(b1, 0 | 34..=37) {
+                        return Err(invalid(&format!("unsupported operator 12 {b1}")));
+                    }
+                    self.out.extend_from_slice(&code[i..i + 2]);
+                    self.operands.clear();
+                    i += 2;
+                }
+                _ => {
+                    let n = self.operands.len();
+                    self.check_width(b0, n);
+                    // hstem, vstem, hstemhm, vstemhm, and the implied vstem
+                    // of hintmask/cntrmask
+                    if matches!(b0, 1 | 3 | 18 | 23 | 19 | 20) {
+                        self.stems += n / 2;
+                    }
+                    self.out.push(b0);
+                    i += 1;
+                    if matches!(b0, 19 | 20) {
+                        let len = self.stems.div_ceil(8);
+                        let mask = code.get(i..i + len).ok_or_else(|| invalid("truncated mask"))?;
+                        self.out.extend_from_slice(mask);
+                        i += len;
+                    }
+                    self.ended = b0 == 14;
+                    self.operands.clear();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Record the width operand if `op` is the first stack-clearing operator
+    /// and carries one more operand than it takes.
+    fn check_width(&mut self, op: u8, operand_count: usize) {
+        if self.seen_stack_clear {
+            return;
+        }
+        let has_width = match op {
+            1 | 3 | 18 | 23 | 19 | 20 => operand_count % 2 == 1,
+            21 => operand_count == 3,
+            4 | 22 => operand_count == 2,
+            14 => operand_count == 1 || operand_count == 5,
+            _ => return,
+        };
+        self.seen_stack_clear = true;
+        if has_width {
+            let end = self.operands.get(1).map_or(self.out.len(), |operand| operand.0);
+            self.width = Some(self.operands[0].0..end);
+        }
+    }
+}
+
+/// Integer value of an encoded charstring number; `None` for fractions
+fn decode_charstring_int(bytes: &[u8]) -> Option<i32> {
+    let b0 = i32::from(bytes[0]);
+    match bytes {
+        [28, hi, lo] => Some(i32::from(i16::from_be_bytes([*hi, *lo]))),
+        [_] => Some(b0 - 139),
+        [247..=250, b1] => Some((b0 - 247) * 256 + i32::from(*b1) + 108),
+        [_, b1] => Some(-(b0 - 251) * 256 - i32::from(*b1) - 108),
+        [_, a, b, c, d] => {
+            let fixed = i32::from_be_bytes([*a, *b, *c, *d]);
+            (fixed & 0xFFFF == 0).then_some(fixed >> 16)
+        }
+        _ => None,
+    }
+}
+
+/// Assemble a CFF table around `charstrings`, reusing the first font's
+/// name, Top DICT, strings and Private DICT.
+fn build_cff(
+    first: &Cff,
+    first_data: &[u8],
+    glyph_names: &[GlyphName],
+    charstrings: &[Vec<u8>],
+) -> Result<Vec<u8>> {
+    let top_dict = dict_entries(first.top_dicts().get(0)?)?;
+    let private = top_dict
+        .iter()
+        .find(|(op, _)| *op == 18)
+        .map(|(_, operands)| dict_ints(operands))
+        .transpose()?;
+    let private_dict: Vec<u8> = match private.as_deref() {
+        Some(&[size, offset]) => {
+            let range = offset as usize..(offset + size) as usize;
+            let data = first_data
+                .get(range)
+                .ok_or(MergeError::InvalidCharstring("Private DICT out of bounds".into()))?;
+            write_entries(
+                dict_entries(data)?
+                    .into_iter()
+                    .filter(|(op, _)| !PRIVATE_DICT_DROPPED.contains(op)),
+            )
+        }
+        _ => Vec::new(),
+    };
+    let top_dict =
+        write_entries(top_dict.into_iter().filter(|(op, _)| !TOP_DICT_REPLACED.contains(op)));
+
+    // Strings: the first font's, so its Top DICT SIDs stay valid, followed
+    // by any new glyph names
+    let first_strings = first.strings();
+    let mut strings: Vec<&[u8]> = (0..first_strings.count() as usize)
+        .map(|i| first_strings.get(i))
+        .collect::<std::result::Result<_, _>>()?;
+    let mut sids: HashMap<&[u8], u16> = STANDARD_STRINGS
+        .iter()
+        .enumerate()
+        .map(|(sid, name)| (name.as_bytes(), sid as u16))
+        .collect();
+    for (i, string) in strings.iter().enumerate() {
+        sids.entry(string).or_insert((STANDARD_STRINGS.len() + i) as u16);
+    }
+    let mut charset = vec![0u8];
+    for name in glyph_names.iter().skip(1) {
+        let next = (STANDARD_STRINGS.len() + strings.len()) as u16;
+        let sid = *sids.entry(name.as_bytes()).or_insert_with(|| {
+            strings.push(name.as_bytes());
+            next
+        });
+        charset.extend_from_slice(&sid.to_be_bytes());
+    }
+
+    let name = first.name(0).unwrap_or(b"Merged");
+    let charstrings: Vec<&[u8]> = charstrings.iter().map(Vec::as_slice).collect();
+    let header = [1u8, 0, 4, 4];
+    let name_index = write_index(&[name]);
+    let string_index = write_index(&strings);
+    let global_subrs = write_index::<&[u8]>(&[]);
+    let charstring_index = write_index(&charstrings);
+
+    // Offsets are written as 5-byte integers, so the Top DICT's size does
+    // not depend on their values
+    let top_dict_with =
+        |charset_offset: usize, charstrings_offset: usize, private_offset: usize| {
+            let mut dict = top_dict.clone();
+            push_offsets(&mut dict, 15, &[charset_offset]);
+            push_offsets(&mut dict, 17, &[charstrings_offset]);
+            push_offsets(&mut dict, 18, &[private_dict.len(), private_offset]);
+            dict
+        };
+    let top_index_len = write_index(&[&top_dict_with(0, 0, 0)]).len();
+    let charset_offset =
+        header.len() + name_index.len() + top_index_len + string_index.len() + global_subrs.len();
+    let charstrings_offset = charset_offset + charset.len();
+    let private_offset = charstrings_offset + charstring_index.len();
+    let top_index =
+        write_index(&[&top_dict_with(charset_offset, charstrings_offset, private_offset)]);
+
+    if private.is_none() {
+        warn!("First CFF font has no Private DICT; writing an empty one");
+    }
+    Ok([
+        &header[..],
+        &name_index,
+        &top_index,
+        &string_index,
+        &global_subrs,
+        &charset,
+        &charstring_index,
+        &private_dict,
+    ]
+    .concat())
+}
+
+/// DICT entries as (operator, raw operand bytes)
+fn dict_entries(data: &[u8]) -> Result<Vec<(u16, &[u8])>> {
+    cff::dict_entries(data).ok_or_else(|| MergeError::InvalidCharstring("malformed DICT".into()))
+}
+
+/// Integer operands of a DICT entry
+fn dict_ints(operands: &[u8]) -> Result<Vec<i32>> {
+    let invalid = || MergeError::InvalidCharstring("non-integer DICT operand".into());
+    cff::dict_numbers(operands)
+        .ok_or_else(invalid)?
+        .into_iter()
+        .map(|value| (value.fract() == 0.0).then_some(value as i32).ok_or_else(invalid))
+        .collect()
+}
+
+/// DICT entries written back with their raw operand bytes
+fn write_entries<'a>(entries: impl IntoIterator<Item = (u16, &'a [u8])>) -> Vec<u8> {
+    let mut out = Vec::new();
+    for (op, operands) in entries {
+        out.extend_from_slice(operands);
+        push_op(&mut out, op);
+    }
+    out
+}
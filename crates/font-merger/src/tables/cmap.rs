@@ -1,23 +1,28 @@
 //! cmap table merging
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use font_types::BigEndian;
-use indexmap::{IndexMap, map::Entry};
+use indexmap::IndexMap;
 use read_fonts::{
     FontRef, TableProvider,
-    tables::cmap::{Cmap as ReadCmap, CmapSubtable, PlatformId},
+    tables::cmap::{Cmap as ReadCmap, Cmap14 as ReadCmap14, CmapSubtable, MapVariant, PlatformId},
 };
-use write_fonts::tables::cmap::{
-    Cmap, Cmap12, CmapSubtable as WriteCmapSubtable, EncodingRecord, PlatformId as WritePlatformId,
-    SequentialMapGroup,
+use write_fonts::{
+    tables::cmap::{
+        Cmap, Cmap12, Cmap14, CmapSubtable as WriteCmapSubtable, DefaultUvs, EncodingRecord,
+        NonDefaultUvs, PlatformId as WritePlatformId, SequentialMapGroup, UnicodeRange, UvsMapping,
+        VariationSelector,
+    },
+    types::Uint24,
 };
 
 use crate::{
     Result,
     context::GlyphOrder,
     glyph_order::GlyphName,
-    types::{Codepoint, GlyphId},
+    options::Options,
+    types::{Codepoint, FontIndex, GlyphId},
 };
 
 /// Information about duplicate glyphs (same codepoint, different glyphs)
@@ -29,39 +34,66 @@ pub struct DuplicateGlyphInfo {
 
 /// Merge cmap tables from multiple fonts
 ///
+/// A codepoint mapped by several fonts goes to the glyph the highest
+/// priority font has, ties going to the conflict strategy, and so does a
+/// variation sequence defined by several fonts. Codepoints a font excludes
+/// are left to the other fonts. Variation
+/// sequences are written as a format 14 subtable.
 /// Returns the merged cmap and information about duplicate glyphs
 pub fn merge_cmap(
     fonts: &[FontRef],
     glyph_order: &GlyphOrder,
+    options: &Options,
 ) -> Result<(Cmap, DuplicateGlyphInfo)> {
-    let mut codepoint_to_glyph: IndexMap<Codepoint, GlyphName> = IndexMap::new();
-    let mut duplicate_info = DuplicateGlyphInfo { per_font: vec![HashMap::new(); fonts.len()] };
+    let mut candidates: IndexMap<Codepoint, Vec<(FontIndex, GlyphName)>> = IndexMap::new();
+    let mut sequence_candidates: BTreeMap<(u32, u32), Vec<(FontIndex, GlyphName)>> =
+        BTreeMap::new();
 
     for (font_idx, font) in fonts.iter().enumerate() {
         let cmap = font.cmap()?;
         let mapping = glyph_order.font_mapping(font_idx);
 
+        let excluded = |codepoint| options.excludes(FontIndex::new(font_idx), codepoint);
+
+        for (sequence, name) in variation_sequences(&cmap, mapping) {
+            if excluded(sequence.1) {
+                continue;
+            }
+            sequence_candidates
+                .entry(sequence)
+                .or_default()
+                .push((FontIndex::new(font_idx), name));
+        }
+
         // Find best cmap subtable (prefer Unicode BMP, then full Unicode)
         if let Some(subtable) = find_best_subtable(&cmap) {
             for (codepoint, glyph_id) in iter_cmap_subtable(&subtable) {
+                if excluded(codepoint.to_u32()) {
+                    continue;
+                }
                 if let Some(name) = mapping.get(&glyph_id) {
-                    match codepoint_to_glyph.entry(codepoint) {
-                        Entry::Vacant(slot) => {
-                            slot.insert(name.clone());
-                        }
-                        Entry::Occupied(slot) => {
-                            let existing = slot.get();
-                            if existing != name {
-                                duplicate_info.per_font[font_idx]
-                                    .insert(name.clone(), existing.clone());
-                            }
-                        }
+                    let glyphs = candidates.entry(codepoint).or_default();
+                    // A font mapping the codepoint twice keeps its first glyph
+                    if glyphs.last().is_none_or(|(idx, _)| idx.as_usize() != font_idx) {
+                        glyphs.push((FontIndex::new(font_idx), name.clone()));
                     }
                 }
             }
         }
     }
 
+    let mut duplicate_info = DuplicateGlyphInfo { per_font: vec![HashMap::new(); fonts.len()] };
+    let mut codepoint_to_glyph: IndexMap<Codepoint, GlyphName> = IndexMap::new();
+    for (codepoint, glyphs) in candidates {
+        let winner = glyphs[options.choose(codepoint.to_u32(), &glyphs)].1.clone();
+        for (font_idx, name) in &glyphs {
+            if *name != winner {
+                duplicate_info.per_font[font_idx.as_usize()].insert(name.clone(), winner.clone());
+            }
+        }
+        codepoint_to_glyph.insert(codepoint, winner);
+    }
+
     // Build the cmap using format 12 only to avoid format 4 overflow with large character sets
     let mut mappings: Vec<(u32, u32)> = codepoint_to_glyph
         .iter()
@@ -73,7 +105,34 @@ pub fn merge_cmap(
 
     mappings.sort_by_key(|(cp, _)| *cp);
 
-    let cmap = build_cmap_format12(&mappings);
+    let mut cmap = build_cmap_format12(&mappings);
+
+    // A sequence whose glyph is the base codepoint's merged glyph is a
+    // default sequence; any other glyph is written explicitly.
+    let mut sequences: BTreeMap<u32, Vec<(u32, Option<u16>)>> = BTreeMap::new();
+    for ((selector, codepoint), glyphs) in sequence_candidates {
+        let winner = &glyphs[options.choose(codepoint, &glyphs)].1;
+        let glyph = if codepoint_to_glyph.get(&Codepoint::new(codepoint)) == Some(winner) {
+            None
+        } else {
+            let Some(mega_gid) = glyph_order.mega_id(winner) else {
+                continue;
+            };
+            Some(mega_gid.to_u16())
+        };
+        sequences.entry(selector).or_default().push((codepoint, glyph));
+    }
+    if !sequences.is_empty() {
+        // Keeps the records sorted: (0, 4), (0, 5), (3, 10)
+        cmap.encoding_records.insert(
+            1,
+            EncodingRecord::new(
+                WritePlatformId::Unicode,
+                5, // Unicode Variation Sequences
+                WriteCmapSubtable::Format14(build_cmap14(&sequences)),
+            ),
+        );
+    }
 
     Ok((cmap, duplicate_info))
 }
@@ -142,6 +201,80 @@ fn build_sequential_groups(mappings: &[(u32, u32)]) -> Vec<SequentialMapGroup> {
     groups
 }
 
+/// A font's variation sequences as `((selector, codepoint), glyph)`, with
+/// default sequences resolved through the font's own cmap.
+fn variation_sequences(
+    cmap: &ReadCmap,
+    mapping: &IndexMap<GlyphId, GlyphName>,
+) -> Vec<((u32, u32), GlyphName)> {
+    let Some(cmap14) = find_cmap14(cmap) else {
+        return Vec::new();
+    };
+    cmap14
+        .iter()
+        .filter_map(|(codepoint, selector, variant)| {
+            let gid = match variant {
+                MapVariant::UseDefault => cmap.map_codepoint(codepoint)?,
+                MapVariant::Variant(gid) => gid,
+            };
+            let name = mapping.get(&GlyphId::new(u16::try_from(gid.to_u32()).ok()?))?;
+            Some(((selector, codepoint), name.clone()))
+        })
+        .collect()
+}
+
+fn find_cmap14<'a>(cmap: &ReadCmap<'a>) -> Option<ReadCmap14<'a>> {
+    cmap.encoding_records()
+        .iter()
+        .find_map(|record| match record.subtable(cmap.offset_data()) {
+            Ok(CmapSubtable::Format14(cmap14)) => Some(cmap14),
+            _ => None,
+        })
+}
+
+/// Build a format 14 subtable from sorted `(codepoint, glyph)` sequences per
+/// selector, where a `None` glyph marks a default sequence.
+fn build_cmap14(sequences: &BTreeMap<u32, Vec<(u32, Option<u16>)>>) -> Cmap14 {
+    // Header, then an 11-byte record per selector
+    let mut length = 10 + 11 * sequences.len();
+    let var_selector = sequences
+        .iter()
+        .map(|(&selector, sequences)| {
+            let mut ranges: Vec<UnicodeRange> = Vec::new();
+            let mut mappings = Vec::new();
+            for &(codepoint, glyph) in sequences {
+                match glyph {
+                    Some(gid) => mappings.push(UvsMapping::new(Uint24::new(codepoint), gid)),
+                    None => match ranges.last_mut() {
+                        Some(range)
+                            if range.additional_count < u8::MAX
+                                && range.start_unicode_value.to_u32()
+                                    + u32::from(range.additional_count)
+                                    + 1
+                                    == codepoint =>
+                        {
+                            range.additional_count += 1;
+                        }
+                        _ => ranges.push(UnicodeRange::new(Uint24::new(codepoint), 0)),
+                    },
+                }
+            }
+
+            let default_uvs = (!ranges.is_empty()).then(|| {
+                length += 4 + 4 * ranges.len();
+                DefaultUvs::new(ranges.len() as u32, ranges)
+            });
+            let non_default_uvs = (!mappings.is_empty()).then(|| {
+                length += 4 + 5 * mappings.len();
+                NonDefaultUvs::new(mappings.len() as u32, mappings)
+            });
+            VariationSelector::new(Uint24::new(selector), default_uvs, non_default_uvs)
+        })
+        .collect::<Vec<_>>();
+
+    Cmap14::new(length as u32, var_selector.len() as u32, var_selector)
+}
+
 fn find_best_subtable<'a>(cmap: &'a ReadCmap<'a>) -> Option<CmapSubtable<'a>> {
     // Priority: Format 12 (full Unicode) > Format 4 (BMP) > others
     let records = cmap.encoding_records();
@@ -0,0 +1,297 @@
+//! COLR/CPAL table merging
+//!
+//! COLR is rebuilt whenever any font carries one, even when it is the only
+//! one, so a lone table still has its glyph IDs remapped to the merged glyph
+//! order. Palettes are concatenated entry-wise: palette `p` of the merged
+//! CPAL holds palette `p` of every font in order (falling back to a font's
+//! palette 0 when it has fewer palettes), so each font's palette indices
+//! shift by the entry counts of the fonts before it. Both COLRv0 layer
+//! records and the COLRv1 paint graph are rewritten with remapped glyph IDs,
+//! shifted palette indices, and shifted layer indices.
+
+use log::warn;
+use read_fonts::{FontRef, TableProvider, types::GlyphId16};
+use write_fonts::{
+    from_obj::ToOwnedTable,
+    tables::{
+        colr::{
+            BaseGlyph, BaseGlyphList, BaseGlyphPaint, Clip, ClipList, Colr, Layer, LayerList, Paint,
+        },
+        cpal::{ColorRecord, Cpal},
+    },
+};
+
+use crate::{
+    MergeError, Result,
+    context::{GidRemap, MergeContext},
+};
+
+/// Palette index reserved for the text foreground color.
+const FOREGROUND_PALETTE_INDEX: u16 = 0xFFFF;
+
+/// Per-font offsets applied while rewriting a COLR table.
+struct ColrOffsets<'a> {
+    remap: &'a GidRemap,
+    palette: u16,
+    layer_list: u32,
+}
+
+impl ColrOffsets<'_> {
+    fn glyph(&self, gid: GlyphId16) -> GlyphId16 {
+        self.remap
+            .get_u16(gid.to_u16())
+            .map_or(GlyphId16::NOTDEF, GlyphId16::new)
+    }
+
+    fn palette_index(&self, index: u16) -> u16 {
+        if index == FOREGROUND_PALETTE_INDEX { index } else { index.saturating_add(self.palette) }
+    }
+}
+
+/// Merge COLR and CPAL tables from multiple fonts
+pub fn merge_colr(ctx: &MergeContext) -> Result<Option<(Colr, Option<Cpal>)>> {
+    if ctx.fonts().iter().all(|font| font.colr().is_err()) {
+        return Ok(None);
+    }
+
+    let palette_offsets = palette_offsets(ctx.fonts());
+    let cpal = merge_cpal(ctx.fonts())?;
+
+    let mut merged = Colr::default();
+    let mut base_glyphs = Vec::new();
+    let mut layers = Vec::new();
+    let mut base_glyph_paints = Vec::new();
+    let mut layer_paints = Vec::new();
+    let mut clips = Vec::new();
+
+    for (font_idx, font, remap) in ctx.fonts_with_remap() {
+        let Ok(colr) = font.colr() else {
+            continue;
+        };
+        let colr: Colr = colr.to_owned_table();
+        let offsets = ColrOffsets {
+            remap,
+            palette: palette_offsets[font_idx.as_usize()],
+            layer_list: layer_paints.len() as u32,
+        };
+
+        let layer_offset =
+            u16::try_from(layers.len()).map_err(|_| MergeError::TooManyColrLayers)?;
+        for base in colr.base_glyph_records.as_ref().into_iter().flatten() {
+            if let Some(gid) = remap.get_u16(base.glyph_id.to_u16()) {
+                let first_layer_index = base
+                    .first_layer_index
+                    .checked_add(layer_offset)
+                    .ok_or(MergeError::TooManyColrLayers)?;
+                base_glyphs.push(BaseGlyph::new(
+                    GlyphId16::new(gid),
+                    first_layer_index,
+                    base.num_layers,
+                ));
+            }
+        }
+        layers.extend(colr.layer_records.as_ref().into_iter().flatten().map(|layer| {
+            Layer::new(offsets.glyph(layer.glyph_id), offsets.palette_index(layer.palette_index))
+        }));
+
+        for mut record in colr
+            .base_glyph_list
+            .into_inner()
+            .into_iter()
+            .flat_map(|list| list.base_glyph_paint_records)
+        {
+            if let Some(gid) = remap.get_u16(record.glyph_id.to_u16()) {
+                remap_paint(&mut record.paint, &offsets);
+                base_glyph_paints
+                    .push(BaseGlyphPaint::new(GlyphId16::new(gid), record.paint.into_inner()));
+            }
+        }
+        for mut paint in colr.layer_list.into_inner().into_iter().flat_map(|list| list.paints) {
+            remap_paint(&mut paint, &offsets);
+            layer_paints.push(paint.into_inner());
+        }
+        if let Some(list) = colr.clip_list.as_ref() {
+            for clip in &list.clips {
+                for gid in clip.start_glyph_id.to_u16()..=clip.end_glyph_id.to_u16() {
+                    if let Some(new_gid) = remap.get_u16(gid) {
+                        let new_gid = GlyphId16::new(new_gid);
+                        clips.push(Clip::new(new_gid, new_gid, (*clip.clip_box).clone()));
+                    }
+                }
+            }
+        }
+
+        if colr.item_variation_store.is_some() {
+            if merged.item_variation_store.is_some() {
+                warn!(
+                    "Variable COLR merging is not supported. Only the first variable COLR \
+                     table's variation store is kept; font {font_idx}'s variable paints may \
+                     resolve to the wrong deltas."
+                );
+            } else {
+                merged.var_index_map = colr.var_index_map;
+                merged.item_variation_store = colr.item_variation_store;
+            }
+        }
+    }
+
+    if !base_glyphs.is_empty() {
+        base_glyphs.sort_by_key(|base| base.glyph_id);
+        merged.num_base_glyph_records = base_glyphs.len() as u16;
+        merged.base_glyph_records = Some(base_glyphs).into();
+        merged.num_layer_records =
+            u16::try_from(layers.len()).map_err(|_| MergeError::TooManyColrLayers)?;
+        merged.layer_records = Some(layers).into();
+    }
+    if !base_glyph_paints.is_empty() {
+        base_glyph_paints.sort_by_key(|record| record.glyph_id);
+        merged.base_glyph_list =
+            Some(BaseGlyphList::new(base_glyph_paints.len() as u32, base_glyph_paints)).into();
+    }
+    if !layer_paints.is_empty() {
+        merged.layer_list = Some(LayerList::new(layer_paints.len() as u32, layer_paints)).into();
+    }
+    if !clips.is_empty() {
+        clips.sort_by_key(|clip| clip.start_glyph_id);
+        merged.clip_list = Some(ClipList::new(1, clips.len() as u32, clips)).into();
+    }
+
+    Ok(Some((merged, cpal)))
+}
+
+/// Per-font offset to add to palette indices
+///
+/// Each font's offset is the total palette entry count of the fonts before it.
+pub fn palette_offsets(fonts: &[FontRef]) -> Vec<u16> {
+    fonts
+        .iter()
+        .scan(0u16, |next, font| {
+            let offset = *next;
+            let entries = font.cpal().map_or(0, |cpal| cpal.num_palette_entries());
+            *next = next.saturating_add(entries);
+            Some(offset)
+        })
+        .collect()
+}
+
+/// Concatenate every font's palette entries, palette by palette.
+///
+/// A lone CPAL is copied as is; otherwise palette types and labels are not
+/// carried over.
+fn merge_cpal(fonts: &[FontRef]) -> Result<Option<Cpal>> {
+    let cpals: Vec<_> = fonts.iter().filter_map(|font| font.cpal().ok()).collect();
+    if let [cpal] = cpals.as_slice() {
+        return Ok(Some(cpal.to_owned_table()));
+    }
+    let palettes: Vec<Vec<Vec<ColorRecord>>> = cpals
+        .iter()
+        .map(|cpal| {
+            let records = cpal.color_records_array().transpose()?.unwrap_or_default();
+            let entries = usize::from(cpal.num_palette_entries());
+            Ok(cpal
+                .color_record_indices()
+                .iter()
+                .map(|first| {
+                    let first = usize::from(first.get());
+                    records
+                        .get(first..first + entries)
+                        .unwrap_or_default()
+                        .iter()
+                        .map(|c| ColorRecord::new(c.blue(), c.green(), c.red(), c.alpha()))
+                        .collect()
+                })
+                .collect())
+        })
+        .collect::<Result<_>>()?;
+
+    let Some(num_palettes) = palettes.iter().map(Vec::len).max().filter(|&n| n > 0) else {
+        return Ok(None);
+    };
+
+    let mut color_records = Vec::new();
+    let mut color_record_indices = Vec::with_capacity(num_palettes);
+    for palette in 0..num_palettes {
+        color_record_indices.push(color_records.len() as u16);
+        for font_palettes in &palettes {
+            let colors = font_palettes.get(palette).or(font_palettes.first());
+            color_records.extend(colors.into_iter().flatten().cloned());
+        }
+    }
+
+    let num_palette_entries = color_records.len() / num_palettes;
+    Ok(Some(Cpal::new(
+        num_palette_entries as u16,
+        num_palettes as u16,
+        color_records.len() as u16,
+        Some(color_records),
+        color_record_indices,
+    )))
+}
+
+/// Rewrite glyph IDs, palette indices, and layer indices throughout a paint graph.
+fn remap_paint(paint: &mut Paint, offsets: &ColrOffsets) {
+    match paint {
+        Paint::ColrLayers(p) => p.first_layer_index += offsets.layer_list,
+        Paint::Solid(p) => p.palette_index = offsets.palette_index(p.palette_index),
+        Paint::VarSolid(p) => p.palette_index = offsets.palette_index(p.palette_index),
+        Paint::LinearGradient(p) => {
+            for stop in &mut p.color_line.color_stops {
+                stop.palette_index = offsets.palette_index(stop.palette_index);
+            }
+        }
+        Paint::VarLinearGradient(p) => {
+            for stop in &mut p.color_line.color_stops {
+                stop.palette_index = offsets.palette_index(stop.palette_index);
+            }
+        }
+        Paint::RadialGradient(p) => {
+            for stop in &mut p.color_line.color_stops {
+                stop.palette_index = offsets.palette_index(stop.palette_index);
+            }
+        }
+        Paint::VarRadialGradient(p) => {
+            for stop in &mut p.color_line.color_stops {
+                stop.palette_index = offsets.palette_index(stop.palette_index);
+            }
+        }
+        Paint::SweepGradient(p) => {
+            for stop in &mut p.color_line.color_stops {
+                stop.palette_index = offsets.palette_index(stop.palette_index);
+            }
+        }
+        Paint::VarSweepGradient(p) => {
+            for stop in &mut p.color_line.color_stops {
+                stop.palette_index = offsets.palette_index(stop.palette_index);
+            }
+        }
+        Paint::Glyph(p) => {
+            p.glyph_id = offsets.glyph(p.glyph_id);
+            remap_paint(&mut p.paint, offsets);
+        }
+        Paint::ColrGlyph(p) => p.glyph_id = offsets.glyph(p.glyph_id),
+        Paint::Transform(p) => remap_paint(&mut p.paint, offsets),
+        Paint::VarTransform(p) => remap_paint(&mut p.paint, offsets),
+        Paint::Translate(p) => remap_paint(&mut p.paint, offsets),
+        Paint::VarTranslate(p) => remap_paint(&mut p.paint, offsets),
+        Paint::Scale(p) => remap_paint(&mut p.paint, offsets),
+        Paint::VarScale(p) => remap_paint(&mut p.paint, offsets),
+        Paint::ScaleAroundCenter(p) => remap_paint(&mut p.paint, offsets),
+        Paint::VarScaleAroundCenter(p) => remap_paint(&mut p.paint, offsets),
+        Paint::ScaleUniform(p) => remap_paint(&mut p.paint, offsets),
+        Paint::VarScaleUniform(p) => remap_paint(&mut p.paint, offsets),
+        Paint::ScaleUniformAroundCenter(p) => remap_paint(&mut p.paint, offsets),
+        Paint::VarScaleUniformAroundCenter(p) => remap_paint(&mut p.paint, offsets),
+        Paint::Rotate(p) => remap_paint(&mut p.paint, offsets),
+        Paint::VarRotate(p) => remap_paint(&mut p.paint, offsets),
+        Paint::RotateAroundCenter(p) => remap_paint(&mut p.paint, offsets),
+        Paint::VarRotateAroundCenter(p) => remap_paint(&mut p.paint, offsets),
+        Paint::Skew(p) => remap_paint(&mut p.paint, offsets),
+        Paint::VarSkew(p) => remap_paint(&mut p.paint, offsets),
+        Paint::SkewAroundCenter(p) => remap_paint(&mut p.paint, offsets),
+        Paint::VarSkewAroundCenter(p) => remap_paint(&mut p.paint, offsets),
+        Paint::Composite(p) => {
+            remap_paint(&mut p.source_paint, offsets);
+            remap_paint(&mut p.backdrop_paint, offsets);
+        }
+    }
+}
@@ -0,0 +1,86 @@
+//! GDEF table merging
+//!
+//! GDEF is only rebuilt when a font other than the first carries one;
+//! otherwise the first font's table is copied as-is with the other
+//! unhandled tables. When rebuilt, glyph classes are unioned (first font
+//! wins) so `IgnoreMarks`/`IgnoreBaseGlyphs` still apply to later fonts'
+//! glyphs, and mark glyph sets are concatenated in font order. Lookups
+//! shift their mark filtering set index by [`mark_set_offsets`] to match.
+
+use std::{collections::BTreeMap, result};
+
+use read_fonts::{FontRef, TableProvider, types::GlyphId16};
+use write_fonts::{
+    from_obj::ToOwnedTable,
+    tables::{
+        gdef::{Gdef, MarkGlyphSets},
+        layout::ClassDef,
+    },
+};
+
+use crate::{Result, context::MergeContext, tables::layout::remap_coverage};
+
+/// Merge GDEF tables from multiple fonts
+pub fn merge_gdef(ctx: &MergeContext) -> Result<Option<Gdef>> {
+    if ctx.fonts().iter().skip(1).all(|font| font.gdef().is_err()) {
+        return Ok(None);
+    }
+
+    let mut gdef = ctx
+        .first_font()
+        .gdef()
+        .map_or_else(|_| Gdef::default(), |g| g.to_owned_table());
+
+    let mut glyph_classes: BTreeMap<GlyphId16, u16> = BTreeMap::new();
+    let mut mark_sets = Vec::new();
+
+    for (_font_idx, font, remap) in ctx.fonts_with_remap() {
+        let Ok(source) = font.gdef() else {
+            continue;
+        };
+
+        if let Some(Ok(class_def)) = source.glyph_class_def() {
+            for (gid, class) in class_def.iter() {
+                if let Some(new_gid) = remap.get_u16(gid.to_u32() as u16) {
+                    glyph_classes.entry(GlyphId16::new(new_gid)).or_insert(class);
+                }
+            }
+        }
+
+        if let Some(Ok(sets)) = source.mark_glyph_sets_def() {
+            for coverage in sets.coverages().iter() {
+                mark_sets.push(remap_coverage(&coverage?, remap));
+            }
+        }
+    }
+
+    gdef.glyph_class_def = (!glyph_classes.is_empty())
+        .then(|| ClassDef::from_iter(glyph_classes))
+        .into();
+    gdef.mark_glyph_sets_def =
+        (!mark_sets.is_empty()).then(|| MarkGlyphSets::new(mark_sets)).into();
+
+    Ok(Some(gdef))
+}
+
+/// Per-font offset to add to lookup mark filtering set indices
+///
+/// Mirrors the order in which [`merge_gdef`] concatenates mark glyph sets.
+pub fn mark_set_offsets(fonts: &[FontRef]) -> Vec<u16> {
+    fonts
+        .iter()
+        .scan(0u16, |next, font| {
+            let offset = *next;
+            *next = next.saturating_add(mark_glyph_set_count(font));
+            Some(offset)
+        })
+        .collect()
+}
+
+fn mark_glyph_set_count(font: &FontRef) -> u16 {
+    font.gdef()
+        .ok()
+        .and_then(|gdef| gdef.mark_glyph_sets_def())
+        .and_then(result::Result::ok)
+        .map_or(0, |sets| sets.mark_glyph_set_count())
+}
@@ -8,13 +8,22 @@
 //! `fpgm` or values in `cvt`, which are only copied from the first font. Using
 //! instructions that reference incompatible function numbers or CVT indices
 //! could cause rendering errors or crashes.
+//!
+//! Composite glyphs from fonts selected by [`Options::flatten_composites`] are
+//! decomposed into simple glyphs before merging, so they no longer depend on
+//! component GIDs being remapped correctly.
+//!
+//! [`Options::flatten_composites`]: crate::Options::flatten_composites
 
 use std::collections::{HashMap, HashSet};
 
 use font_types::GlyphId16;
 use read_fonts::{
     TableProvider, tables,
-    tables::glyf::{Anchor, CurvePoint, Glyph as ReadGlyph},
+    tables::{
+        glyf::{Anchor, CompositeGlyphFlags, CurvePoint, Glyf as ReadGlyf, Glyph as ReadGlyph},
+        loca::Loca as ReadLoca,
+    },
     types,
 };
 use write_fonts::tables::{
@@ -30,9 +39,12 @@ use crate::{
     Result,
     context::MergeContext,
     glyph_order::GlyphName,
-    types::{GlyphId, MegaGlyphId},
+    types::{FontIndex, GlyphId, MegaGlyphId},
 };
 
+/// Maximum nesting of composite glyphs followed when flattening
+const MAX_COMPONENT_DEPTH: usize = 64;
+
 /// Merge glyf tables from multiple fonts
 ///
 /// Returns the glyf table, loca table, and loca format.
@@ -40,7 +52,12 @@ use crate::{
 /// Per-glyph hinting instructions are stripped from all fonts except the first,
 /// matching fontTools behavior. This is because instructions may reference
 /// `fpgm` functions or `cvt` values that only exist in the first font.
-pub fn merge_glyf(ctx: &MergeContext) -> Result<Option<(Glyf, Loca, LocaFormat)>> {
+///
+/// A `notdef` outline, when given, replaces whatever the fonts supply for GID 0.
+pub fn merge_glyf(
+    ctx: &MergeContext,
+    notdef: Option<&Glyph>,
+) -> Result<Option<(Glyf, Loca, LocaFormat)>> {
     let fonts = ctx.fonts();
 
     // Check if fonts have glyf tables (TrueType outlines)
@@ -74,6 +91,7 @@ pub fn merge_glyf(ctx: &MergeContext) -> Result<Option<(Glyf, Loca, LocaFormat)>
         let Ok(loca) = font.loca(None) else {
             continue;
         };
+        let flatten = ctx.options().flatten_composites.contains(FontIndex::new(font_idx));
 
         for (gid, glyph_name) in mapping {
             if glyph_map.contains_key(glyph_name) {
@@ -86,8 +104,10 @@ pub fn merge_glyf(ctx: &MergeContext) -> Result<Option<(Glyf, Loca, LocaFormat)>
             };
 
             let strip_hinting = font_idx > 0;
-            let converted =
-                convert_glyph(&glyph, font_idx, &gid_to_name, name_to_new_gid, strip_hinting);
+            let converted = match &glyph {
+                ReadGlyph::Composite(_) if flatten => flatten_glyph(&glyph, &glyf, &loca),
+                _ => convert_glyph(&glyph, font_idx, &gid_to_name, name_to_new_gid, strip_hinting),
+            };
 
             glyph_map.insert(glyph_name.clone(), converted);
         }
@@ -122,8 +142,12 @@ pub fn merge_glyf(ctx: &MergeContext) -> Result<Option<(Glyf, Loca, LocaFormat)>
     // Build the glyf and loca tables using GlyfLocaBuilder
     let mut builder = GlyfLocaBuilder::new();
 
-    for name in ctx.mega() {
+    for (gid, name) in ctx.mega().iter().enumerate() {
         let glyph = glyph_map.remove(name).unwrap_or(Glyph::Empty);
+        let glyph = match notdef {
+            Some(notdef) if gid == 0 => notdef.clone(),
+            _ => glyph,
+        };
         // Ignore validation errors for empty/invalid glyphs
         let _ = builder.add_glyph(&glyph);
     }
@@ -241,3 +265,108 @@ fn convert_glyph(
         }
     }
 }
+
+/// Decompose a glyph into a simple glyph by resolving all of its components
+/// against the source font's outlines
+///
+/// Instructions are dropped, since composite instructions cannot be carried
+/// over to the decomposed outline.
+pub(crate) fn flatten_glyph(glyph: &ReadGlyph, glyf: &ReadGlyf, loca: &ReadLoca) -> Glyph {
+    let mut contours = Vec::new();
+    collect_contours(glyph, glyf, loca, 0, &mut contours);
+
+    let points = contours.iter().flatten();
+    let Some(bbox) = points.fold(None::<Bbox>, |bbox, pt| {
+        Some(match bbox {
+            None => Bbox { x_min: pt.x, y_min: pt.y, x_max: pt.x, y_max: pt.y },
+            Some(b) => Bbox {
+                x_min: b.x_min.min(pt.x),
+                y_min: b.y_min.min(pt.y),
+                x_max: b.x_max.max(pt.x),
+                y_max: b.y_max.max(pt.y),
+            },
+        })
+    }) else {
+        return Glyph::Empty;
+    };
+
+    let contours = contours.into_iter().map(Contour::from).collect();
+    Glyph::Simple(SimpleGlyph { bbox, contours, instructions: vec![] })
+}
+
+/// Append the contours of `glyph` to `out`, recursively applying component
+/// transforms and offsets
+fn collect_contours(
+    glyph: &ReadGlyph,
+    glyf: &ReadGlyf,
+    loca: &ReadLoca,
+    depth: usize,
+    out: &mut Vec<Vec<CurvePoint>>,
+) {
+    match glyph {
+        ReadGlyph::Simple(simple) => {
+            let mut points = simple.points();
+            let mut start = 0usize;
+            for end_pt in simple.end_pts_of_contours() {
+                let end = end_pt.get() as usize;
+                let contour: Vec<CurvePoint> = points
+                    .by_ref()
+                    .take((end + 1).saturating_sub(start))
+                    .map(|pt| CurvePoint { x: pt.x, y: pt.y, on_curve: pt.on_curve })
+                    .collect();
+                start = end + 1;
+                out.push(contour);
+            }
+        }
+        ReadGlyph::Composite(composite) => {
+            if depth >= MAX_COMPONENT_DEPTH {
+                return;
+            }
+            // Components may anchor to points of earlier components, so keep
+            // track of where this glyph's own points start in `out`.
+            let first_contour = out.len();
+            for comp in composite.components() {
+                let Ok(Some(child)) = loca.get_glyf(comp.glyph.into(), glyf) else {
+                    continue;
+                };
+                let mut child_contours = Vec::new();
+                collect_contours(&child, glyf, loca, depth + 1, &mut child_contours);
+
+                let t = &comp.transform;
+                let (xx, yx, xy, yy) = (t.xx.to_f32(), t.yx.to_f32(), t.xy.to_f32(), t.yy.to_f32());
+                for pt in child_contours.iter_mut().flatten() {
+                    let (x, y) = (f32::from(pt.x), f32::from(pt.y));
+                    pt.x = (x * xx + y * xy).round() as i16;
+                    pt.y = (x * yx + y * yy).round() as i16;
+                }
+
+                let (dx, dy) = match comp.anchor {
+                    // A scaled offset is transformed along with the outline
+                    Anchor::Offset { x, y }
+                        if comp.flags.contains(CompositeGlyphFlags::SCALED_COMPONENT_OFFSET) =>
+                    {
+                        let (x, y) = (f32::from(x), f32::from(y));
+                        ((x * xx + y * xy).round() as i16, (x * yx + y * yy).round() as i16)
+                    }
+                    Anchor::Offset { x, y } => (x, y),
+                    Anchor::Point { base, component } => {
+                        let base = out[first_contour..].iter().flatten().nth(base as usize);
+                        let child = child_contours.iter().flatten().nth(component as usize);
+                        match (base, child) {
+                            (Some(b), Some(c)) => {
+                                (b.x.saturating_sub(c.x), b.y.saturating_sub(c.y))
+                            }
+                            _ => (0, 0),
+                        }
+                    }
+                };
+                for pt in child_contours.iter_mut().flatten() {
+                    pt.x = pt.x.saturating_add(dx);
+                    pt.y = pt.y.saturating_add(dy);
+                }
+
+                out.extend(child_contours);
+            }
+        }
+    }
+}
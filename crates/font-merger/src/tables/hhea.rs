@@ -11,10 +11,15 @@ use write_fonts::tables::hhea::Hhea;
 
 use crate::{
     MergeError, Result,
+    options::LineMetrics,
     strategies::{first, max, min},
 };
 
-pub fn merge_hhea(fonts: &[FontRef], num_h_metrics: u16) -> Result<Hhea> {
+pub fn merge_hhea(
+    fonts: &[FontRef],
+    num_h_metrics: u16,
+    line_metrics: LineMetrics,
+) -> Result<Hhea> {
     let tables: Vec<ReadHhea> = fonts
         .iter()
         .map(TableProvider::hhea)
@@ -34,9 +39,9 @@ pub fn merge_hhea(fonts: &[FontRef], num_h_metrics: u16) -> Result<Hhea> {
     let x_max_extents: Vec<i16> = tables.iter().map(|t| t.x_max_extent().to_i16()).collect();
 
     Ok(Hhea {
-        ascender: FWord::new(max(&ascenders)?),
-        descender: FWord::new(min(&descenders)?),
-        line_gap: FWord::new(max(&line_gaps)?),
+        ascender: FWord::new(line_metrics.merge(&ascenders, max)?),
+        descender: FWord::new(line_metrics.merge(&descenders, min)?),
+        line_gap: FWord::new(line_metrics.merge(&line_gaps, max)?),
         advance_width_max: UfWord::new(max(&advance_width_maxs)?),
         min_left_side_bearing: FWord::new(min(&min_lsbs)?),
         min_right_side_bearing: FWord::new(min(&min_rsbs)?),
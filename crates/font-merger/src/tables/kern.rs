@@ -0,0 +1,116 @@
+//! Legacy kern table merging
+//!
+//! Horizontal format 0 pairs from every font are remapped to merged glyph IDs
+//! and written as a single format 0 subtable. By default a font that also has
+//! GPOS pair positioning contributes no kern pairs, since shapers that apply
+//! both would kern it twice; [`KernPreference`] can keep them instead. Other
+//! subtable formats, and vertical or cross-stream subtables, are dropped.
+
+use std::collections::BTreeMap;
+
+use read_fonts::{
+    FontRef, TableProvider,
+    tables::{
+        gpos::{ExtensionSubtable, PositionLookup},
+        kern::{Subtable, SubtableKind},
+    },
+};
+
+use crate::{Result, context::MergeContext, options::KernPreference};
+
+/// Coverage of the merged subtable: horizontal, format 0
+const HORIZONTAL_FORMAT_0: u16 = 0x0001;
+
+/// Size of one kerning pair record in bytes
+const PAIR_SIZE: u16 = 6;
+
+/// Merge kern tables from multiple fonts into raw `kern` table data
+pub fn merge_kern(ctx: &MergeContext) -> Result<Option<Vec<u8>>> {
+    let mut pairs: BTreeMap<(u16, u16), i16> = BTreeMap::new();
+
+    for (_, font, remap) in ctx.fonts_with_remap() {
+        let Ok(kern) = font.kern() else {
+            continue;
+        };
+        if ctx.options().kern_preference == KernPreference::Gpos && has_pair_positioning(font) {
+            continue;
+        }
+        for subtable in kern.subtables().filter_map(|subtable| subtable.ok()) {
+            if !is_plain_horizontal(&subtable) {
+                continue;
+            }
+            let Ok(SubtableKind::Format0(format0)) = subtable.kind() else {
+                continue;
+            };
+            for pair in format0.pairs() {
+                let (Some(left), Some(right)) =
+                    (remap.get_u16(pair.left().to_u16()), remap.get_u16(pair.right().to_u16()))
+                else {
+                    continue;
+                };
+                // Values from several subtables of one font add up
+                let value = pairs.entry((left, right)).or_default();
+                *value = value.saturating_add(pair.value());
+            }
+        }
+    }
+
+    if pairs.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(build_kern(&pairs)))
+}
+
+fn is_plain_horizontal(subtable: &Subtable) -> bool {
+    subtable.is_horizontal() && !subtable.is_cross_stream() && !subtable.is_variable()
+}
+
+/// Whether the font's GPOS has any pair adjustment lookup
+fn has_pair_positioning(font: &FontRef) -> bool {
+    let Ok(lookups) = font.gpos().and_then(|gpos| gpos.lookup_list()) else {
+        return false;
+    };
+    lookups.lookups().iter().flatten().any(|lookup| match lookup {
+        PositionLookup::Pair(_) => true,
+        PositionLookup::Extension(extension) => extension
+            .subtables()
+            .iter()
+            .flatten()
+            .any(|subtable| matches!(subtable, ExtensionSubtable::Pair(_))),
+        _ => false,
+    })
+}
+
+/// Serialize an OpenType version 0 kern table with one format 0 subtable.
+///
+/// `nPairs` is 16-bit, so pairs beyond the first 65535 are dropped. The
+/// subtable length saturates, which readers tolerate for the last subtable.
+fn build_kern(pairs: &BTreeMap<(u16, u16), i16>) -> Vec<u8> {
+    let n_pairs = pairs.len().min(usize::from(u16::MAX)) as u16;
+    let entry_selector = n_pairs.checked_ilog2().unwrap_or(0) as u16;
+    let search_range = (1u16 << entry_selector).saturating_mul(PAIR_SIZE);
+    let range_shift = n_pairs.saturating_mul(PAIR_SIZE).saturating_sub(search_range);
+    let length = 14 + usize::from(n_pairs) * usize::from(PAIR_SIZE);
+
+    let mut data = Vec::with_capacity(4 + length);
+    for value in [0, 1] {
+        data.extend_from_slice(&u16::to_be_bytes(value));
+    }
+    for value in [
+        0,
+        u16::try_from(length).unwrap_or(u16::MAX),
+        HORIZONTAL_FORMAT_0,
+        n_pairs,
+        search_range,
+        entry_selector,
+        range_shift,
+    ] {
+        data.extend_from_slice(&value.to_be_bytes());
+    }
+    for (&(left, right), &value) in pairs.iter().take(usize::from(n_pairs)) {
+        data.extend_from_slice(&left.to_be_bytes());
+        data.extend_from_slice(&right.to_be_bytes());
+        data.extend_from_slice(&value.to_be_bytes());
+    }
+    data
+}
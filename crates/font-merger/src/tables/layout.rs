@@ -1,6 +1,6 @@
 //! GSUB/GPOS layout table merging
 
-use std::{collections::HashMap, result};
+use std::{collections::HashMap, ops::Range, result};
 
 use font_types::{BigEndian, GlyphId16};
 use read_fonts::{
@@ -18,7 +18,7 @@ use write_fonts::tables::{
         SinglePosFormat2, ValueRecord,
     },
     gsub::{
-        Gsub, SingleSubst, SubstitutionChainContext, SubstitutionLookupList,
+        Gsub, SingleSubst, SubstitutionChainContext, SubstitutionLookup, SubstitutionLookupList,
         SubstitutionSequenceContext,
     },
     layout::{
@@ -36,16 +36,20 @@ use crate::{
     Result,
     context::{GidRemap, GlyphOrder, MergeContext},
     convert::{MarkArrayExt, ToWrite},
+    options::KernPreference,
+    report::{DroppedLookup, MergeReport},
     tables::{
         cmap::DuplicateGlyphInfo,
+        gdef::mark_set_offsets,
         layout_types::{
             FeatureIndex, LangTag, LookupIndex, MergedFeatureList, ScriptLangFeatureMap, ScriptTag,
         },
     },
 };
 
-/// Merge GSUB tables from multiple fonts
-pub fn merge_gsub(ctx: &MergeContext) -> Result<Option<Gsub>> {
+/// Merge GSUB tables from multiple fonts, recording lookups that could not
+/// be converted in `report`
+pub fn merge_gsub(ctx: &MergeContext, report: &mut MergeReport) -> Result<Option<Gsub>> {
     let fonts = ctx.fonts();
     let has_gsub = fonts.iter().any(|f| f.gsub().is_ok());
     if !has_gsub {
@@ -56,18 +60,27 @@ pub fn merge_gsub(ctx: &MergeContext) -> Result<Option<Gsub>> {
     let mut features = MergedFeatureList::new();
     let mut lookups: Vec<write_fonts::tables::gsub::SubstitutionLookup> = Vec::new();
 
-    for (_font_idx, font, remap) in ctx.fonts_with_remap() {
+    let mark_set_offsets = mark_set_offsets(fonts);
+    for (font_idx, font, remap) in ctx.fonts_with_remap() {
         let Ok(gsub) = font.gsub() else {
             continue;
         };
 
         let lookup_offset = LookupIndex::new(lookups.len() as u16);
+        let mark_set_offset = mark_set_offsets[font_idx.as_usize()];
         if let Ok(lookup_list) = gsub.lookup_list() {
             for lookup_idx in 0..lookup_list.lookup_count() {
-                if let Ok(lookup) = lookup_list.lookups().get(lookup_idx as usize)
-                    && let Some(converted) = convert_gsub_lookup(&lookup, remap, lookup_offset)
-                {
-                    lookups.push(converted);
+                let Ok(lookup) = lookup_list.lookups().get(lookup_idx as usize) else {
+                    continue;
+                };
+                match convert_gsub_lookup(&lookup, remap, lookup_offset, mark_set_offset) {
+                    Some(converted) => lookups.push(converted),
+                    None => report.dropped_lookups.push(DroppedLookup {
+                        font_index: font_idx,
+                        table: Tag::new(b"GSUB"),
+                        lookup_index: lookup_idx,
+                        lookup_type: lookup.lookup_type(),
+                    }),
                 }
             }
         }
@@ -79,7 +92,7 @@ pub fn merge_gsub(ctx: &MergeContext) -> Result<Option<Gsub>> {
                 if let Some(record) = records.get(i)
                     && let Ok(feature) = record.feature(feature_list.offset_data())
                 {
-                    let tag = record.feature_tag();
+                    let tag = ctx.options().feature_tag(font_idx, record.feature_tag());
                     let lookup_indices: Vec<LookupIndex> = feature
                         .lookup_list_indices()
                         .iter()
@@ -106,6 +119,14 @@ pub fn merge_gsub(ctx: &MergeContext) -> Result<Option<Gsub>> {
         );
     }
 
+    if let Some(keep) = &ctx.options().keep_features {
+        retain_features(|_, tag| keep.contains(&tag), &mut scripts, &mut features, &mut lookups);
+    }
+
+    if lookups.is_empty() && features.is_empty() {
+        return Ok(None);
+    }
+
     let gsub = build_gsub(scripts.into_raw(), features.into_raw(), lookups)?;
 
     Ok(Some(gsub))
@@ -116,6 +137,7 @@ fn convert_gsub_lookup(
     lookup: &read_fonts::tables::gsub::SubstitutionLookup,
     gid_remap: &GidRemap,
     lookup_offset: LookupIndex,
+    mark_set_offset: u16,
 ) -> Option<write_fonts::tables::gsub::SubstitutionLookup> {
     let _lookup_offset = lookup_offset.as_u16();
     use write_fonts::tables::gsub::{
@@ -130,6 +152,9 @@ fn convert_gsub_lookup(
     // extensions or hand-roll the unwrap; the read-fonts API already handles
     // this cleanly.
     let flag = lookup.lookup_flag();
+    let mark_filtering_set = lookup
+        .mark_filtering_set()
+        .map(|set| set.saturating_add(mark_set_offset));
     let read_subs = lookup.subtables().ok()?;
     match read_subs {
         SubstitutionSubtables::Single(read_subs) => {
@@ -188,7 +213,7 @@ fn convert_gsub_lookup(
             if subtables.is_empty() {
                 return None;
             }
-            Some(WriteLookup::Single(Lookup::new(flag, subtables)))
+            Some(WriteLookup::Single(new_lookup(flag, subtables, mark_filtering_set)))
         }
         SubstitutionSubtables::Multiple(read_subs) => {
             let mut subtables = Vec::new();
@@ -223,7 +248,7 @@ fn convert_gsub_lookup(
             if subtables.is_empty() {
                 return None;
             }
-            Some(WriteLookup::Multiple(Lookup::new(flag, subtables)))
+            Some(WriteLookup::Multiple(new_lookup(flag, subtables, mark_filtering_set)))
         }
         SubstitutionSubtables::Alternate(read_subs) => {
             let mut subtables = Vec::new();
@@ -258,7 +283,7 @@ fn convert_gsub_lookup(
             if subtables.is_empty() {
                 return None;
             }
-            Some(WriteLookup::Alternate(Lookup::new(flag, subtables)))
+            Some(WriteLookup::Alternate(new_lookup(flag, subtables, mark_filtering_set)))
         }
         SubstitutionSubtables::Ligature(read_subs) => {
             let mut subtables = Vec::new();
@@ -304,7 +329,7 @@ fn convert_gsub_lookup(
             if subtables.is_empty() {
                 return None;
             }
-            Some(WriteLookup::Ligature(Lookup::new(flag, subtables)))
+            Some(WriteLookup::Ligature(new_lookup(flag, subtables, mark_filtering_set)))
         }
         SubstitutionSubtables::Contextual(read_subs) => {
             let mut subtables = Vec::new();
@@ -318,7 +343,7 @@ fn convert_gsub_lookup(
             if subtables.is_empty() {
                 return None;
             }
-            Some(WriteLookup::Contextual(Lookup::new(flag, subtables)))
+            Some(WriteLookup::Contextual(new_lookup(flag, subtables, mark_filtering_set)))
         }
         SubstitutionSubtables::ChainContextual(read_subs) => {
             let mut subtables = Vec::new();
@@ -332,7 +357,7 @@ fn convert_gsub_lookup(
             if subtables.is_empty() {
                 return None;
             }
-            Some(WriteLookup::ChainContextual(Lookup::new(flag, subtables)))
+            Some(WriteLookup::ChainContextual(new_lookup(flag, subtables, mark_filtering_set)))
         }
         SubstitutionSubtables::Reverse(read_subs) => {
             let mut subtables = Vec::new();
@@ -377,13 +402,14 @@ fn convert_gsub_lookup(
             if subtables.is_empty() {
                 return None;
             }
-            Some(WriteLookup::Reverse(Lookup::new(flag, subtables)))
+            Some(WriteLookup::Reverse(new_lookup(flag, subtables, mark_filtering_set)))
         }
     }
 }
 
-/// Merge GPOS tables from multiple fonts
-pub fn merge_gpos(ctx: &MergeContext) -> Result<Option<Gpos>> {
+/// Merge GPOS tables from multiple fonts, recording lookups that could not
+/// be converted in `report`
+pub fn merge_gpos(ctx: &MergeContext, report: &mut MergeReport) -> Result<Option<Gpos>> {
     let fonts = ctx.fonts();
     let has_gpos = fonts.iter().any(|f| f.gpos().is_ok());
     if !has_gpos {
@@ -393,19 +419,33 @@ pub fn merge_gpos(ctx: &MergeContext) -> Result<Option<Gpos>> {
     let mut scripts = ScriptLangFeatureMap::new();
     let mut features = MergedFeatureList::new();
     let mut lookups: Vec<PositionLookup> = Vec::new();
+    let mut preferred_lookups = None;
+    // Features of fonts whose legacy kern table is kept in their place
+    let mut legacy_kern: Vec<FeatureIndex> = Vec::new();
 
-    for (_font_idx, font, remap) in ctx.fonts_with_remap() {
+    let mark_set_offsets = mark_set_offsets(fonts);
+    for (font_idx, font, remap) in ctx.fonts_with_remap() {
         let Ok(gpos) = font.gpos() else {
             continue;
         };
+        let prefer_legacy =
+            ctx.options().kern_preference == KernPreference::Legacy && font.kern().is_ok();
 
         let lookup_offset = LookupIndex::new(lookups.len() as u16);
+        let mark_set_offset = mark_set_offsets[font_idx.as_usize()];
         if let Ok(lookup_list) = gpos.lookup_list() {
             for lookup_idx in 0..lookup_list.lookup_count() {
-                if let Ok(lookup) = lookup_list.lookups().get(lookup_idx as usize)
-                    && let Some(converted) = convert_gpos_lookup(&lookup, remap, lookup_offset)
-                {
-                    lookups.push(converted);
+                let Ok(lookup) = lookup_list.lookups().get(lookup_idx as usize) else {
+                    continue;
+                };
+                match convert_gpos_lookup(&lookup, remap, lookup_offset, mark_set_offset) {
+                    Some(converted) => lookups.push(converted),
+                    None => report.dropped_lookups.push(DroppedLookup {
+                        font_index: font_idx,
+                        table: Tag::new(b"GPOS"),
+                        lookup_index: lookup_idx,
+                        lookup_type: lookup.lookup_type(),
+                    }),
                 }
             }
         }
@@ -417,13 +457,16 @@ pub fn merge_gpos(ctx: &MergeContext) -> Result<Option<Gpos>> {
                 if let Some(record) = records.get(i)
                     && let Ok(feature) = record.feature(feature_list.offset_data())
                 {
-                    let tag = record.feature_tag();
+                    let tag = ctx.options().feature_tag(font_idx, record.feature_tag());
                     let lookup_indices: Vec<LookupIndex> = feature
                         .lookup_list_indices()
                         .iter()
                         .map(|idx| LookupIndex::new(idx.get() + lookup_offset.as_u16()))
                         .collect();
-                    features.add(tag, lookup_indices);
+                    let idx = features.add(tag, lookup_indices);
+                    if prefer_legacy && record.feature_tag() == Tag::new(b"kern") {
+                        legacy_kern.push(idx);
+                    }
                 }
             }
         }
@@ -431,6 +474,24 @@ pub fn merge_gpos(ctx: &MergeContext) -> Result<Option<Gpos>> {
         if let Ok(script_list) = gpos.script_list() {
             collect_scripts_typed(&script_list, &mut scripts, feature_offset);
         }
+
+        if ctx.options().prefer_marks_from == Some(font_idx) {
+            preferred_lookups = Some(usize::from(lookup_offset.as_u16())..lookups.len());
+        }
+    }
+
+    if let Some(range) = preferred_lookups {
+        prefer_mark_lookups(&mut lookups, &mut features, range);
+    }
+
+    let keep = ctx.options().keep_features.as_deref();
+    if keep.is_some() || !legacy_kern.is_empty() {
+        retain_features(
+            |idx, tag| keep.is_none_or(|keep| keep.contains(&tag)) && !legacy_kern.contains(&idx),
+            &mut scripts,
+            &mut features,
+            &mut lookups,
+        );
     }
 
     if lookups.is_empty() && features.is_empty() {
@@ -442,16 +503,201 @@ pub fn merge_gpos(ctx: &MergeContext) -> Result<Option<Gpos>> {
     Ok(Some(gpos))
 }
 
+/// Move the mark attachment lookups in `range` ahead of every other lookup
+///
+/// Shapers apply lookups in list order, so the moved lookups position a mark
+/// first; a later lookup that also covers the mark still repositions it.
+/// Every other lookup keeps its relative order, and feature and nested
+/// contextual lookup references are rewritten to the new order.
+fn prefer_mark_lookups(
+    lookups: &mut Vec<PositionLookup>,
+    features: &mut MergedFeatureList,
+    range: Range<usize>,
+) {
+    let is_mark = |lookup: &PositionLookup| {
+        matches!(
+            lookup,
+            PositionLookup::MarkToBase(_)
+                | PositionLookup::MarkToLig(_)
+                | PositionLookup::MarkToMark(_)
+        )
+    };
+    let (preferred, rest): (Vec<usize>, Vec<usize>) =
+        (0..lookups.len()).partition(|&i| range.contains(&i) && is_mark(&lookups[i]));
+    if preferred.is_empty() {
+        return;
+    }
+
+    let order: Vec<usize> = preferred.into_iter().chain(rest).collect();
+    let mut new_index = vec![0u16; order.len()];
+    for (new, &old) in order.iter().enumerate() {
+        new_index[old] = new as u16;
+    }
+    let map = |idx: u16| new_index.get(usize::from(idx)).copied().unwrap_or(idx);
+
+    let mut slots: Vec<Option<PositionLookup>> = lookups.drain(..).map(Some).collect();
+    lookups.extend(order.iter().filter_map(|&old| slots[old].take()));
+
+    features.remap_lookups(|idx| LookupIndex::new(map(idx.as_u16())));
+    for lookup in lookups.iter_mut() {
+        lookup.remap_nested_lookups(&mut |idx| map(idx));
+    }
+}
+
+/// Lookups whose contextual subtables reference other lookups by index
+trait NestedLookups {
+    /// Rewrite every nested lookup index through `map`
+    fn remap_nested_lookups(&mut self, map: &mut impl FnMut(u16) -> u16);
+}
+
+impl NestedLookups for PositionLookup {
+    fn remap_nested_lookups(&mut self, map: &mut impl FnMut(u16) -> u16) {
+        match self {
+            PositionLookup::Contextual(lookup) => {
+                for subtable in &mut lookup.subtables {
+                    remap_sequence_context_lookups(subtable, map);
+                }
+            }
+            PositionLookup::ChainContextual(lookup) => {
+                for subtable in &mut lookup.subtables {
+                    remap_chained_context_lookups(subtable, map);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl NestedLookups for SubstitutionLookup {
+    fn remap_nested_lookups(&mut self, map: &mut impl FnMut(u16) -> u16) {
+        match self {
+            SubstitutionLookup::Contextual(lookup) => {
+                for subtable in &mut lookup.subtables {
+                    remap_sequence_context_lookups(subtable, map);
+                }
+            }
+            SubstitutionLookup::ChainContextual(lookup) => {
+                for subtable in &mut lookup.subtables {
+                    remap_chained_context_lookups(subtable, map);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Drop features whose index and tag fail `keep`, then every lookup that no
+/// remaining feature reaches, directly or through contextual lookups
+///
+/// Script/language systems and all lookup references are rewritten to the
+/// compacted feature and lookup lists.
+fn retain_features<L: NestedLookups>(
+    keep: impl Fn(FeatureIndex, Tag) -> bool,
+    scripts: &mut ScriptLangFeatureMap,
+    features: &mut MergedFeatureList,
+    lookups: &mut Vec<L>,
+) {
+    let feature_map = features.retain(keep);
+    scripts.remap_features(|idx| feature_map.get(usize::from(idx.as_u16())).copied().flatten());
+
+    let mut reachable = vec![false; lookups.len()];
+    let mut pending: Vec<u16> = features.lookup_indices().map(LookupIndex::as_u16).collect();
+    while let Some(idx) = pending.pop() {
+        let Some(seen) = reachable.get_mut(usize::from(idx)) else {
+            continue;
+        };
+        if !*seen {
+            *seen = true;
+            lookups[usize::from(idx)].remap_nested_lookups(&mut |nested| {
+                pending.push(nested);
+                nested
+            });
+        }
+    }
+
+    let mut new_index = vec![None; lookups.len()];
+    let kept = reachable.iter().enumerate().filter(|(_, seen)| **seen);
+    for (new, (old, _)) in kept.enumerate() {
+        new_index[old] = Some(new as u16);
+    }
+    let mut map = |idx: u16| new_index.get(usize::from(idx)).copied().flatten().unwrap_or(idx);
+
+    let mut old = 0;
+    lookups.retain(|_| {
+        old += 1;
+        reachable[old - 1]
+    });
+    features.remap_lookups(|idx| LookupIndex::new(map(idx.as_u16())));
+    for lookup in lookups.iter_mut() {
+        lookup.remap_nested_lookups(&mut map);
+    }
+}
+
+fn remap_lookup_records(records: &mut [SequenceLookupRecord], map: &mut impl FnMut(u16) -> u16) {
+    for record in records {
+        record.lookup_list_index = map(record.lookup_list_index);
+    }
+}
+
+fn remap_sequence_context_lookups(context: &mut SequenceContext, map: &mut impl FnMut(u16) -> u16) {
+    match context {
+        SequenceContext::Format1(f1) => {
+            for rule_set in f1.seq_rule_sets.iter_mut().filter_map(|s| s.as_mut()) {
+                for rule in &mut rule_set.seq_rules {
+                    remap_lookup_records(&mut rule.seq_lookup_records, map);
+                }
+            }
+        }
+        SequenceContext::Format2(f2) => {
+            for rule_set in f2.class_seq_rule_sets.iter_mut().filter_map(|s| s.as_mut()) {
+                for rule in &mut rule_set.class_seq_rules {
+                    remap_lookup_records(&mut rule.seq_lookup_records, map);
+                }
+            }
+        }
+        SequenceContext::Format3(f3) => remap_lookup_records(&mut f3.seq_lookup_records, map),
+    }
+}
+
+fn remap_chained_context_lookups(
+    context: &mut ChainedSequenceContext,
+    map: &mut impl FnMut(u16) -> u16,
+) {
+    match context {
+        ChainedSequenceContext::Format1(f1) => {
+            for rule_set in f1.chained_seq_rule_sets.iter_mut().filter_map(|s| s.as_mut()) {
+                for rule in &mut rule_set.chained_seq_rules {
+                    remap_lookup_records(&mut rule.seq_lookup_records, map);
+                }
+            }
+        }
+        ChainedSequenceContext::Format2(f2) => {
+            for rule_set in f2.chained_class_seq_rule_sets.iter_mut().filter_map(|s| s.as_mut()) {
+                for rule in &mut rule_set.chained_class_seq_rules {
+                    remap_lookup_records(&mut rule.seq_lookup_records, map);
+                }
+            }
+        }
+        ChainedSequenceContext::Format3(f3) => {
+            remap_lookup_records(&mut f3.seq_lookup_records, map);
+        }
+    }
+}
+
 /// Convert a read-fonts GPOS lookup to write-fonts format with GID remapping
 fn convert_gpos_lookup(
     lookup: &tables::gpos::PositionLookup,
     gid_remap: &GidRemap,
     lookup_offset: LookupIndex,
+    mark_set_offset: u16,
 ) -> Option<PositionLookup> {
     let _lookup_offset = lookup_offset.as_u16();
     // See the matching comment in `convert_gsub_lookup`: dispatch via
     // `subtables()` so LookupType 9 (Extension) is auto-unwrapped.
     let flag = lookup.lookup_flag();
+    let mark_filtering_set = lookup
+        .mark_filtering_set()
+        .map(|set| set.saturating_add(mark_set_offset));
     let read_subs = lookup.subtables().ok()?;
     match read_subs {
         PositionSubtables::Single(read_subs) => {
@@ -488,7 +734,7 @@ fn convert_gpos_lookup(
             if subtables.is_empty() {
                 return None;
             }
-            Some(PositionLookup::Single(Lookup::new(flag, subtables)))
+            Some(PositionLookup::Single(new_lookup(flag, subtables, mark_filtering_set)))
         }
         PositionSubtables::Pair(read_subs) => {
             let mut subtables = Vec::new();
@@ -572,7 +818,7 @@ fn convert_gpos_lookup(
             if subtables.is_empty() {
                 return None;
             }
-            Some(PositionLookup::Pair(Lookup::new(flag, subtables)))
+            Some(PositionLookup::Pair(new_lookup(flag, subtables, mark_filtering_set)))
         }
         PositionSubtables::Cursive(read_subs) => {
             let mut subtables = Vec::new();
@@ -600,7 +846,7 @@ fn convert_gpos_lookup(
             if subtables.is_empty() {
                 return None;
             }
-            Some(PositionLookup::Cursive(Lookup::new(flag, subtables)))
+            Some(PositionLookup::Cursive(new_lookup(flag, subtables, mark_filtering_set)))
         }
         PositionSubtables::MarkToBase(read_subs) => {
             let mut subtables = Vec::new();
@@ -647,7 +893,7 @@ fn convert_gpos_lookup(
             if subtables.is_empty() {
                 return None;
             }
-            Some(PositionLookup::MarkToBase(Lookup::new(flag, subtables)))
+            Some(PositionLookup::MarkToBase(new_lookup(flag, subtables, mark_filtering_set)))
         }
         PositionSubtables::MarkToLig(read_subs) => {
             let mut subtables = Vec::new();
@@ -704,7 +950,7 @@ fn convert_gpos_lookup(
             if subtables.is_empty() {
                 return None;
             }
-            Some(PositionLookup::MarkToLig(Lookup::new(flag, subtables)))
+            Some(PositionLookup::MarkToLig(new_lookup(flag, subtables, mark_filtering_set)))
         }
         PositionSubtables::MarkToMark(read_subs) => {
             let mut subtables = Vec::new();
@@ -751,7 +997,7 @@ fn convert_gpos_lookup(
             if subtables.is_empty() {
                 return None;
             }
-            Some(PositionLookup::MarkToMark(Lookup::new(flag, subtables)))
+            Some(PositionLookup::MarkToMark(new_lookup(flag, subtables, mark_filtering_set)))
         }
         PositionSubtables::Contextual(read_subs) => {
             let mut subtables = Vec::new();
@@ -765,7 +1011,7 @@ fn convert_gpos_lookup(
             if subtables.is_empty() {
                 return None;
             }
-            Some(PositionLookup::Contextual(Lookup::new(flag, subtables)))
+            Some(PositionLookup::Contextual(new_lookup(flag, subtables, mark_filtering_set)))
         }
         PositionSubtables::ChainContextual(read_subs) => {
             let mut subtables = Vec::new();
@@ -779,13 +1025,25 @@ fn convert_gpos_lookup(
             if subtables.is_empty() {
                 return None;
             }
-            Some(PositionLookup::ChainContextual(Lookup::new(flag, subtables)))
+            Some(PositionLookup::ChainContextual(new_lookup(flag, subtables, mark_filtering_set)))
         }
     }
 }
 
+/// Build a lookup, keeping its remapped mark filtering set index
+fn new_lookup<T: Default>(
+    flag: LookupFlag,
+    subtables: Vec<T>,
+    mark_filtering_set: Option<u16>,
+) -> Lookup<T> {
+    Lookup { mark_filtering_set, ..Lookup::new(flag, subtables) }
+}
+
 /// Remap coverage table glyphs
-fn remap_coverage(coverage: &layout::CoverageTable, gid_remap: &GidRemap) -> CoverageTable {
+pub(crate) fn remap_coverage(
+    coverage: &layout::CoverageTable,
+    gid_remap: &GidRemap,
+) -> CoverageTable {
     let glyphs: Vec<GlyphId16> = coverage
         .iter()
         .filter_map(|gid| {
@@ -110,6 +110,14 @@ impl ScriptLangFeatureMap {
         }
     }
 
+    /// Rewrite every language system's feature indices, dropping those
+    /// mapped to `None`
+    pub fn remap_features(&mut self, map: impl Fn(FeatureIndex) -> Option<FeatureIndex>) {
+        for features in self.inner.values_mut().flat_map(HashMap::values_mut) {
+            *features = features.iter().filter_map(|&idx| map(idx)).collect();
+        }
+    }
+
     /// Convert to raw format for building
     pub fn into_raw(self) -> HashMap<Tag, HashMap<Tag, Vec<u16>>> {
         self.inner
@@ -167,6 +175,47 @@ impl MergedFeatureList {
         self.features.is_empty()
     }
 
+    /// Keep only features whose index and tag satisfy `keep`
+    ///
+    /// Returns the new index of each old feature, or `None` if it was dropped.
+    pub fn retain(
+        &mut self,
+        keep: impl Fn(FeatureIndex, Tag) -> bool,
+    ) -> Vec<Option<FeatureIndex>> {
+        let mut next = 0u16;
+        let map: Vec<Option<FeatureIndex>> = self
+            .features
+            .iter()
+            .enumerate()
+            .map(|(idx, feature)| {
+                keep(FeatureIndex::new(idx as u16), feature.tag).then(|| {
+                    next += 1;
+                    FeatureIndex::new(next - 1)
+                })
+            })
+            .collect();
+        let mut old = 0;
+        self.features.retain(|_| {
+            old += 1;
+            map[old - 1].is_some()
+        });
+        map
+    }
+
+    /// Iterate over every lookup index referenced by any feature
+    pub fn lookup_indices(&self) -> impl Iterator<Item = LookupIndex> + '_ {
+        self.features.iter().flat_map(|f| f.lookup_indices.iter().copied())
+    }
+
+    /// Rewrite every feature's lookup indices (e.g., after reordering lookups)
+    pub fn remap_lookups(&mut self, map: impl Fn(LookupIndex) -> LookupIndex) {
+        for feature in &mut self.features {
+            for idx in &mut feature.lookup_indices {
+                *idx = map(*idx);
+            }
+        }
+    }
+
     /// Convert to raw format for building
     pub fn into_raw(self) -> Vec<(Tag, Vec<u16>)> {
         self.features
@@ -0,0 +1,56 @@
+//! meta table merging
+//!
+//! `dlng`/`slng` ScriptLangTag lists are unioned across fonts in font order;
+//! any other data map is taken from the first font that carries its tag.
+
+use indexmap::{IndexMap, map::Entry};
+use read_fonts::{FontRef, TableProvider, types::Tag};
+use write_fonts::{
+    from_obj::ToOwnedTable,
+    tables::meta::{DataMapRecord, Meta, Metadata},
+};
+
+use crate::Result;
+
+/// Merge meta tables from multiple fonts
+pub fn merge_meta(fonts: &[FontRef]) -> Result<Option<Meta>> {
+    let mut data_maps: IndexMap<Tag, Metadata> = IndexMap::new();
+
+    for font in fonts {
+        let Ok(meta) = font.meta() else {
+            continue;
+        };
+        let meta: Meta = meta.to_owned_table();
+
+        for record in meta.data_maps {
+            let data = (*record.data).clone();
+            match data_maps.entry(record.tag) {
+                Entry::Vacant(entry) => {
+                    entry.insert(data);
+                }
+                Entry::Occupied(mut entry) => {
+                    if let (Metadata::ScriptLangTags(merged), Metadata::ScriptLangTags(tags)) =
+                        (entry.get_mut(), data)
+                    {
+                        for tag in tags {
+                            if !merged.contains(&tag) {
+                                merged.push(tag);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    if data_maps.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(Meta::new(
+        data_maps
+            .into_iter()
+            .map(|(tag, data)| DataMapRecord::new(tag, data))
+            .collect(),
+    )))
+}
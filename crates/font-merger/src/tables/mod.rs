@@ -2,15 +2,20 @@
 
 pub mod cff;
 pub mod cmap;
+pub mod colr;
+pub mod gdef;
 pub mod glyf;
 pub mod head;
 pub mod hhea;
 pub mod hint;
 pub mod hmtx;
+pub mod kern;
 pub mod layout;
 pub mod layout_types;
 pub mod maxp;
+pub mod meta;
 pub mod name;
+pub mod notdef;
 pub mod os2;
 pub mod post;
 pub mod vhea;
@@ -1,22 +1,170 @@
 //! name table merging
 
-use read_fonts::{FontRef, types::Tag};
-use write_fonts::FontBuilder;
+use std::collections::{BTreeMap, BTreeSet};
 
-use crate::Result;
+use read_fonts::{
+    FontRef, TableProvider,
+    tables::{name::Name, os2::SelectionFlags},
+    types::{NameId, Tag},
+};
+use warpnine_font_ops::{build_style_name_table, ribbi_names};
+use write_fonts::{
+    FontBuilder, OffsetMarker,
+    tables::name::{LangTagRecord, Name as WriteName, NameRecord},
+};
+
+use crate::{MergeError, NameSource, Result};
 
 /// Merge name tables from multiple fonts
 ///
-/// For now, we just copy the name table from the first font as raw bytes
-/// A more sophisticated implementation would merge unique name records
-pub fn merge_name(fonts: &[FontRef], builder: &mut FontBuilder) -> Result<()> {
+/// Without a `source`, the first font's name table is copied as raw bytes.
+/// With one, records are merged per platform, encoding and language: each
+/// such group is taken whole from the first font (or last, for
+/// [`NameSource::Last`]) that has it, so a language never mixes names from
+/// two fonts. Language-tag records are carried over and renumbered, and
+/// records whose string cannot be decoded are dropped.
+///
+/// When `rebuild` is set, the first font's family names are instead
+/// rewritten for `family_name` (or its own typographic family), keeping its
+/// subfamily, weight and slope. Name IDs 1, 4, 6 and 16 follow the RIBBI
+/// grouping of [`ribbi_names`](warpnine_font_ops::ribbi_names), and the
+/// PostScript name is the family and subfamily without spaces.
+pub fn merge_name(
+    fonts: &[FontRef],
+    rebuild: bool,
+    family_name: Option<&str>,
+    source: Option<NameSource>,
+    builder: &mut FontBuilder,
+) -> Result<()> {
     let Some(first) = fonts.first() else {
         return Ok(());
     };
 
-    if let Some(data) = first.table_data(Tag::new(b"name")) {
-        builder.add_raw(Tag::new(b"name"), data.as_bytes().to_vec());
+    if rebuild && let Ok(name) = first.name() {
+        let name_string = |id| {
+            name.name_record()
+                .iter()
+                .filter(|record| record.name_id() == id)
+                .find_map(|record| record.string(name.string_data()).ok())
+                .map(|string| string.chars().collect::<String>())
+        };
+        let family = family_name
+            .map(str::to_string)
+            .or_else(|| name_string(NameId::TYPOGRAPHIC_FAMILY_NAME))
+            .or_else(|| name_string(NameId::FAMILY_NAME))
+            .unwrap_or_default();
+        let subfamily =
+            name_string(NameId::SUBFAMILY_NAME).unwrap_or_else(|| "Regular".to_string());
+        let typo_subfamily =
+            name_string(NameId::TYPOGRAPHIC_SUBFAMILY_NAME).unwrap_or_else(|| subfamily.clone());
+
+        let os2 = first.os2().ok();
+        let weight = os2.as_ref().map_or(400, |os2| os2.us_weight_class());
+        let italic = os2.is_some_and(|os2| os2.fs_selection().contains(SelectionFlags::ITALIC));
+
+        let mut names = ribbi_names(
+            &family,
+            &family.replace(' ', ""),
+            &typo_subfamily.replace(' ', ""),
+            weight,
+            italic,
+        );
+        names.subfamily = subfamily;
+        names.typo_subfamily = typo_subfamily;
+        let name = build_style_name_table(first, &names)
+            .map_err(|e| MergeError::NameRebuild(e.to_string()))?;
+        builder.add_table(&name)?;
+        return Ok(());
+    }
+
+    let Some(source) = source else {
+        if let Some(data) = first.table_data(Tag::new(b"name")) {
+            builder.add_raw(Tag::new(b"name"), data.as_bytes().to_vec());
+        }
+        return Ok(());
+    };
+
+    let mut names: Vec<Name> = fonts.iter().filter_map(|font| font.name().ok()).collect();
+    if source == NameSource::Last {
+        names.reverse();
     }
 
+    // Each (platform, encoding, language) group comes whole from one font
+    let mut groups: BTreeMap<(u16, u16, Language), Vec<(u16, String)>> = BTreeMap::new();
+    for name in &names {
+        let mut own: BTreeMap<(u16, u16, Language), Vec<(u16, String)>> = BTreeMap::new();
+        for record in name.name_record() {
+            let (Ok(string), Some(language)) =
+                (record.string(name.string_data()), Language::of(name, record.language_id()))
+            else {
+                continue;
+            };
+            own.entry((record.platform_id(), record.encoding_id(), language))
+                .or_default()
+                .push((record.name_id().to_u16(), string.chars().collect()));
+        }
+        for (key, records) in own {
+            groups.entry(key).or_insert(records);
+        }
+    }
+
+    // Language tags are renumbered from 0x8000 in the merged table
+    let tags: BTreeSet<&str> = groups
+        .keys()
+        .filter_map(|(_, _, language)| match language {
+            Language::Tag(tag) => Some(tag.as_str()),
+            Language::Id(_) => None,
+        })
+        .collect();
+    let tag_ids: BTreeMap<&str, u16> = tags.iter().copied().zip(0x8000..).collect();
+
+    let mut records: Vec<NameRecord> = groups
+        .iter()
+        .flat_map(|((platform, encoding, language), group)| {
+            let language = match language {
+                Language::Id(id) => *id,
+                Language::Tag(tag) => tag_ids[tag.as_str()],
+            };
+            group.iter().map(move |(id, string)| {
+                NameRecord::new(
+                    *platform,
+                    *encoding,
+                    language,
+                    NameId::new(*id),
+                    string.clone().into(),
+                )
+            })
+        })
+        .collect();
+    records.sort();
+
+    let mut name = WriteName::new(records);
+    if !tags.is_empty() {
+        name.lang_tag_record = Some(
+            tags.into_iter()
+                .map(|tag| LangTagRecord::new(OffsetMarker::new(tag.to_string())))
+                .collect(),
+        );
+    }
+    builder.add_table(&name)?;
     Ok(())
 }
+
+/// A name record's language: a platform language ID, or for IDs from
+/// 0x8000 the font's own language tag the ID indexes
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Language {
+    Id(u16),
+    Tag(String),
+}
+
+impl Language {
+    fn of(name: &Name, language_id: u16) -> Option<Self> {
+        if language_id < 0x8000 {
+            return Some(Self::Id(language_id));
+        }
+        let record = name.lang_tag_record()?.get(usize::from(language_id - 0x8000))?;
+        let tag = record.lang_tag(name.string_data()).ok()?;
+        Some(Self::Tag(tag.chars().collect()))
+    }
+}
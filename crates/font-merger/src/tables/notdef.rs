@@ -0,0 +1,106 @@
+//! Custom `.notdef` replacement
+//!
+//! The supplied data is either a font, whose GID 0 is taken (composites are
+//! flattened against its own `glyf`) along with its advance width, or a raw
+//! `glyf` glyph record. Hinting instructions are dropped in both cases, since
+//! they could reference `fpgm`/`cvt` entries the merged font does not carry.
+
+use read_fonts::{
+    FontData, FontRead, FontRef, TableProvider,
+    tables::glyf::{Glyph as ReadGlyph, SimpleGlyph as ReadSimpleGlyph},
+    types::GlyphId,
+};
+use write_fonts::{
+    from_obj::FromObjRef,
+    tables::{
+        glyf::{Glyph, SimpleGlyph},
+        head::Head,
+        hmtx::Hmtx,
+    },
+};
+
+use super::glyf::flatten_glyph;
+use crate::{MergeError, Result};
+
+/// Outline and metrics that replace GID 0 in the merged font
+#[derive(Debug, Clone)]
+pub struct CustomNotdef {
+    pub glyph: Glyph,
+    /// Advance width from the source font; `None` for raw glyph records
+    pub advance: Option<u16>,
+}
+
+impl CustomNotdef {
+    /// Point GID 0's hmtx entry at this outline and grow the head bbox to cover it
+    pub fn apply_metrics(&self, hmtx: &mut Hmtx, head: &mut Head) {
+        let Some(metric) = hmtx.h_metrics.first_mut() else {
+            return;
+        };
+        if let Some(advance) = self.advance {
+            metric.advance = advance;
+        }
+        if let Some(bbox) = self.glyph.bbox() {
+            metric.side_bearing = bbox.x_min;
+            head.x_min = head.x_min.min(bbox.x_min);
+            head.y_min = head.y_min.min(bbox.y_min);
+            head.x_max = head.x_max.max(bbox.x_max);
+            head.y_max = head.y_max.max(bbox.y_max);
+        }
+    }
+}
+
+/// Load a custom `.notdef` from a font or a raw glyph record
+///
+/// The outline's bounding box may not be wider or taller than `units_per_em`.
+pub fn load_notdef(data: &[u8], units_per_em: u16) -> Result<CustomNotdef> {
+    let notdef = match FontRef::new(data) {
+        Ok(font) => from_font(&font, units_per_em)?,
+        Err(_) => CustomNotdef { glyph: from_raw_glyph(data)?, advance: None },
+    };
+
+    if let Some(bbox) = notdef.glyph.bbox() {
+        let width = i32::from(bbox.x_max) - i32::from(bbox.x_min);
+        let height = i32::from(bbox.y_max) - i32::from(bbox.y_min);
+        if width > i32::from(units_per_em) || height > i32::from(units_per_em) {
+            return Err(MergeError::InvalidNotdef(format!(
+                "outline is {width}x{height}, larger than the {units_per_em} unit em"
+            )));
+        }
+    }
+    Ok(notdef)
+}
+
+fn from_font(font: &FontRef, units_per_em: u16) -> Result<CustomNotdef> {
+    let upem = font.head()?.units_per_em();
+    if upem != units_per_em {
+        return Err(MergeError::IncompatibleUnitsPerEm { expected: units_per_em, actual: upem });
+    }
+
+    let glyf = font.glyf()?;
+    let loca = font.loca(None)?;
+    let glyph = match loca.get_glyf(GlyphId::NOTDEF, &glyf)? {
+        Some(glyph) => flatten_glyph(&glyph, &glyf, &loca),
+        None => Glyph::Empty,
+    };
+    let advance = font.hmtx()?.advance(GlyphId::NOTDEF);
+    Ok(CustomNotdef { glyph, advance })
+}
+
+fn from_raw_glyph(data: &[u8]) -> Result<Glyph> {
+    if data.is_empty() {
+        return Ok(Glyph::Empty);
+    }
+    match ReadGlyph::read(FontData::new(data)) {
+        Ok(ReadGlyph::Simple(simple)) => Ok(Glyph::Simple(without_hinting(&simple))),
+        Ok(ReadGlyph::Composite(_)) => Err(MergeError::InvalidNotdef(
+            "composite glyph records need their components; supply a font instead".into(),
+        )),
+        Err(err) => Err(MergeError::InvalidNotdef(format!("not a font or glyph record: {err}"))),
+    }
+}
+
+fn without_hinting(simple: &ReadSimpleGlyph) -> SimpleGlyph {
+    let mut glyph = SimpleGlyph::from_obj_ref(simple, FontData::default());
+    glyph.instructions.clear();
+    glyph
+}
@@ -8,10 +8,11 @@ use write_fonts::tables::os2::{Os2, SelectionFlags};
 
 use crate::{
     Result,
+    options::LineMetrics,
     strategies::{first, max, min},
 };
 
-pub fn merge_os2(fonts: &[FontRef]) -> Result<Option<Os2>> {
+pub fn merge_os2(fonts: &[FontRef], line_metrics: LineMetrics) -> Result<Option<Os2>> {
     let tables: Vec<ReadOs2> = fonts.iter().filter_map(|f| f.os2().ok()).collect();
 
     if tables.is_empty() {
@@ -113,11 +114,12 @@ pub fn merge_os2(fonts: &[FontRef]) -> Result<Option<Os2>> {
         fs_selection,
         us_first_char_index,
         us_last_char_index,
-        s_typo_ascender: max(&s_typo_ascenders)?,
-        s_typo_descender: min(&s_typo_descenders)?,
-        s_typo_line_gap: max(&s_typo_line_gaps)?,
-        us_win_ascent: max(&us_win_ascents)?,
-        us_win_descent: max(&us_win_descents)?,
+        s_typo_ascender: line_metrics.merge(&s_typo_ascenders, max)?,
+        s_typo_descender: line_metrics.merge(&s_typo_descenders, min)?,
+        s_typo_line_gap: line_metrics.merge(&s_typo_line_gaps, max)?,
+        us_win_ascent: line_metrics.merge(&us_win_ascents, max)?,
+        // usWinDescent is positive below the baseline
+        us_win_descent: line_metrics.merge(&us_win_descents, max)?,
         // Version 1+ fields
         ul_code_page_range_1: if max_version >= 1 { ul_code_page_range1.or(Some(0)) } else { None },
         ul_code_page_range_2: if max_version >= 1 { ul_code_page_range2.or(Some(0)) } else { None },
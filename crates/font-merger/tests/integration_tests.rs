@@ -4,9 +4,13 @@ use std::{collections::HashMap, result::Result};
 
 use font_types::{FWord, Fixed, LongDateTime, Tag, UfWord, Version16Dot16};
 use read_fonts::{FontRef, TableProvider, tables, types::GlyphId};
-use warpnine_font_merger::{Merger, Options};
+use warpnine_font_merger::{
+    ConflictStrategy, FontIndex, FontOptions, FontSelector, KernPreference, LineMetrics,
+    MergeError, Merger, NameSource, NameSuffixScheme, Options,
+};
 use write_fonts::{
     FontBuilder,
+    from_obj::ToOwnedTable,
     tables::{
         cmap::Cmap,
         glyf::{Bbox, GlyfLocaBuilder, Glyph, SimpleGlyph},
@@ -180,6 +184,30 @@ fn make_test_font_with_bounds(
     builder.build()
 }
 
+/// Replace the outlines of a test font, keeping all other tables
+fn with_glyphs(font: &[u8], glyphs: &[Glyph]) -> Vec<u8> {
+    let font_ref = FontRef::new(font).expect("parse font");
+
+    let mut glyf_builder = GlyfLocaBuilder::new();
+    for glyph in glyphs {
+        glyf_builder.add_glyph(glyph).expect("add glyph");
+    }
+    let (glyf, loca, loca_format) = glyf_builder.build();
+
+    let mut head: Head = font_ref.head().expect("head").to_owned_table();
+    head.index_to_loc_format = match loca_format {
+        LocaFormat::Short => 0,
+        LocaFormat::Long => 1,
+    };
+
+    let mut builder = FontBuilder::new();
+    builder.add_table(&head).unwrap();
+    builder.add_table(&glyf).unwrap();
+    builder.add_table(&loca).unwrap();
+    builder.copy_missing_tables(font_ref);
+    builder.build()
+}
+
 fn make_os2(version: u16) -> Os2 {
     Os2 {
         x_avg_char_width: 500,
@@ -285,11 +313,93 @@ fn test_merge_with_duplicates() {
     assert!(cmap.map_codepoint(0x41u32).is_some(), "missing A");
 }
 
-/// Test that fonts with incompatible unitsPerEm are rejected
+/// The conflict strategy decides which font's glyph a shared codepoint maps
+/// to; the losing glyph stays in the font either way.
+#[test]
+fn test_conflict_strategy_picks_cmap_glyph() {
+    let base = make_test_font(
+        &[".notdef", "A", "ellipsis"],
+        &[(0x41, "A"), (0x2026, "ellipsis")],
+        Some(4),
+    );
+    let fallback = make_test_font(
+        &[".notdef", "A", "ellipsis"],
+        &[(0x41, "A"), (0x2026, "ellipsis")],
+        Some(4),
+    );
+    // Merged order: .notdef A ellipsis .notdef.1 A.1 ellipsis.1
+    let mapped = |strategy: ConflictStrategy| {
+        let merged = Merger::new(Options::new().conflict_strategy(strategy))
+            .merge(&[&base, &fallback])
+            .expect("merge failed");
+        let font = FontRef::new(&merged).expect("parse merged font");
+        assert_eq!(font.maxp().unwrap().num_glyphs(), 6);
+        let cmap = font.cmap().unwrap();
+        [0x41u32, 0x2026].map(|cp| cmap.map_codepoint(cp).unwrap().to_u32())
+    };
+
+    assert_eq!(mapped(ConflictStrategy::PreferFirst), [1, 2]);
+    assert_eq!(mapped(ConflictStrategy::PreferLast), [4, 5]);
+    let ellipsis_from_fallback =
+        ConflictStrategy::PreferRange(vec![(0x2000, 0x206F)], FontIndex::new(1));
+    assert_eq!(mapped(ellipsis_from_fallback), [1, 5]);
+}
+
+/// Test that fonts with incompatible unitsPerEm are rejected unless an output
+/// UPEM is given, and that glyphs already at that UPEM pass through unchanged
 #[test]
 fn test_incompatible_upem() {
-    // This test validates the error case conceptually
-    // Creating fonts with different upem would require modifying make_test_font
+    use read_fonts::tables::glyf::CurvePoint;
+    use write_fonts::tables::glyf::Contour;
+
+    let triangle = |size: i16| {
+        let contour: Contour = [(0, 0), (size, 0), (size / 2, size)]
+            .iter()
+            .map(|&(x, y)| CurvePoint { x, y, on_curve: true })
+            .collect::<Vec<_>>()
+            .into();
+        Glyph::Simple(SimpleGlyph {
+            bbox: Bbox { x_min: 0, y_min: 0, x_max: size, y_max: size },
+            contours: vec![contour],
+            instructions: vec![],
+        })
+    };
+    let glyph_bytes = |data: &[u8], cp: u32| -> Vec<u8> {
+        let font = FontRef::new(data).unwrap();
+        let gid = font.cmap().unwrap().map_codepoint(cp).expect("mapped");
+        let loca = font.loca(None).unwrap();
+        let start = loca.get_raw(gid.to_u32() as usize).unwrap() as usize;
+        let end = loca.get_raw(gid.to_u32() as usize + 1).unwrap() as usize;
+        font.table_data(Tag::new(b"glyf")).unwrap().as_bytes()[start..end].to_vec()
+    };
+
+    let base = make_test_font(&[".notdef", "A"], &[(0x41, "A")], Some(4));
+    let base = with_glyphs(&base, &[Glyph::Empty, triangle(500)]);
+    let fallback = make_test_font(&[".notdef", "B"], &[(0x42, "B")], Some(4));
+    let fallback = with_glyphs(&fallback, &[Glyph::Empty, triangle(1023)]);
+    let fallback = {
+        let font = FontRef::new(&fallback).unwrap();
+        let mut head: Head = font.head().unwrap().to_owned_table();
+        head.units_per_em = 2048;
+        let mut builder = FontBuilder::new();
+        builder.add_table(&head).unwrap();
+        builder.copy_missing_tables(font);
+        builder.build()
+    };
+
+    let err = Merger::default().merge(&[&base, &fallback]).unwrap_err();
+    assert!(
+        matches!(err, MergeError::IncompatibleUnitsPerEm { expected: 1000, actual: 2048 }),
+        "{err}"
+    );
+
+    let merged = Merger::new(Options::new().output_upem(2048))
+        .merge(&[&base, &fallback])
+        .expect("merge failed");
+    let font = FontRef::new(&merged).expect("parse merged font");
+    assert_eq!(font.head().unwrap().units_per_em(), 2048);
+    assert_eq!(glyph_bytes(&merged, 0x42), glyph_bytes(&fallback, 0x42));
+    assert_ne!(glyph_bytes(&merged, 0x41), glyph_bytes(&base, 0x41));
 }
 
 /// Test that merging a single font works
@@ -586,6 +696,49 @@ fn test_merge_os2_unicode_ranges() {
     assert_eq!(os2.ul_unicode_range_1(), 0b0011, "Unicode ranges should be OR'd");
 }
 
+/// Under `LineMetrics::Max` the merged line metrics fit the tallest ascent and
+/// deepest descent of any input; under `First` they are the first font's.
+#[test]
+fn test_line_metrics_fit_tallest_input() {
+    fn with_line_metrics(ascent: i16, descent: i16) -> Vec<u8> {
+        let font = make_test_font(&[".notdef", "A"], &[(0x41, "A")], Some(4));
+        let font = FontRef::new(&font).unwrap();
+        let mut hhea: Hhea = font.hhea().unwrap().to_owned_table();
+        hhea.ascender = FWord::new(ascent);
+        hhea.descender = FWord::new(descent);
+        let mut os2: Os2 = font.os2().unwrap().to_owned_table();
+        os2.s_typo_ascender = ascent;
+        os2.s_typo_descender = descent;
+        os2.us_win_ascent = ascent as u16;
+        os2.us_win_descent = descent.unsigned_abs();
+
+        let mut builder = FontBuilder::new();
+        builder.add_table(&hhea).unwrap();
+        builder.add_table(&os2).unwrap();
+        builder.copy_missing_tables(font);
+        builder.build()
+    }
+
+    let latin = with_line_metrics(700, -200);
+    let cjk = with_line_metrics(880, -120);
+    let merged_metrics = |line_metrics: LineMetrics| {
+        let merged = Merger::new(Options::new().line_metrics(line_metrics))
+            .merge(&[&latin, &cjk])
+            .expect("merge failed");
+        let font = FontRef::new(&merged).expect("parse merged font");
+        let hhea = font.hhea().unwrap();
+        let os2 = font.os2().unwrap();
+        [
+            (hhea.ascender().to_i16(), hhea.descender().to_i16()),
+            (os2.s_typo_ascender(), os2.s_typo_descender()),
+            (os2.us_win_ascent() as i16, -(os2.us_win_descent() as i16)),
+        ]
+    };
+
+    assert_eq!(merged_metrics(LineMetrics::Max), [(880, -200); 3]);
+    assert_eq!(merged_metrics(LineMetrics::First), [(700, -200); 3]);
+}
+
 // ============================================================================
 // Head Table Tests
 // ============================================================================
@@ -647,6 +800,215 @@ fn test_drop_tables() {
     assert!(font_ref.os2().is_err(), "OS/2 should have been dropped");
 }
 
+/// Test that exceeding the configured glyph cap fails before assembly
+#[test]
+fn test_max_glyphs() {
+    let font1 = make_test_font(&[".notdef", "A", "B"], &[(0x41, "A"), (0x42, "B")], Some(4));
+    let font2 = make_test_font(&[".notdef", "C", "D"], &[(0x43, "C"), (0x44, "D")], Some(4));
+
+    let merger = Merger::new(Options::new().max_glyphs(5));
+    let result = merger.merge(&[&font1, &font2]);
+    assert!(
+        matches!(result, Err(MergeError::TooManyGlyphs { count: 6, max_glyphs: 5 })),
+        "expected TooManyGlyphs, got {result:?}"
+    );
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "merged font would have 6 glyphs, more than the limit of 5"
+    );
+
+    let merger = Merger::new(Options::new().max_glyphs(6));
+    merger.merge(&[&font1, &font2]).expect("merge within the cap");
+}
+
+/// Test that every input is scaled to the requested output UPEM
+#[test]
+fn test_output_upem() {
+    use read_fonts::tables::glyf::CurvePoint;
+    use write_fonts::tables::glyf::Contour;
+
+    let square = |size: i16| {
+        let contour: Contour = [(0, 0), (size, 0), (size, size), (0, size)]
+            .iter()
+            .map(|&(x, y)| CurvePoint { x, y, on_curve: true })
+            .collect::<Vec<_>>()
+            .into();
+        Glyph::Simple(SimpleGlyph {
+            bbox: Bbox { x_min: 0, y_min: 0, x_max: size, y_max: size },
+            contours: vec![contour],
+            instructions: vec![],
+        })
+    };
+    let font1 = make_test_font(&[".notdef", "A"], &[(0x41, "A")], Some(4));
+    let font1 = with_glyphs(&font1, &[Glyph::Empty, square(500)]);
+    let font2 = make_test_font(&[".notdef", "B"], &[(0x42, "B")], Some(4));
+    let font2 = with_glyphs(&font2, &[Glyph::Empty, square(250)]);
+
+    let merged = Merger::new(Options::new().output_upem(2048))
+        .merge(&[&font1, &font2])
+        .expect("merge failed");
+    let font_ref = FontRef::new(&merged).expect("parse merged font");
+    assert_eq!(font_ref.head().unwrap().units_per_em(), 2048);
+    assert_eq!(font_ref.hhea().unwrap().ascender().to_i16(), 1434);
+
+    let points = |cp: u32| -> Vec<(i16, i16)> {
+        let gid = font_ref.cmap().unwrap().map_codepoint(cp).expect("mapped");
+        let glyph = font_ref
+            .loca(None)
+            .unwrap()
+            .get_glyf(gid, &font_ref.glyf().unwrap())
+            .expect("glyph lookup")
+            .expect("glyph exists");
+        let tables::glyf::Glyph::Simple(simple) = glyph else {
+            panic!("expected a simple glyph");
+        };
+        simple.points().map(|p| (p.x, p.y)).collect()
+    };
+    assert_eq!(points(0x41), [(0, 0), (1024, 0), (1024, 1024), (0, 1024)]);
+    assert_eq!(points(0x42), [(0, 0), (512, 0), (512, 512), (0, 512)]);
+}
+
+/// Test that composites from selected fonts are decomposed into simple glyphs
+#[test]
+fn test_flatten_composites() {
+    use font_types::GlyphId16;
+    use read_fonts::tables::glyf::CurvePoint;
+    use write_fonts::tables::glyf::{
+        Anchor, Component, ComponentFlags, CompositeGlyph, Contour, Transform,
+    };
+
+    fn simple(points: &[(i16, i16)]) -> Glyph {
+        let xs = points.iter().map(|p| p.0);
+        let ys = points.iter().map(|p| p.1);
+        let bbox = Bbox {
+            x_min: xs.clone().min().unwrap(),
+            y_min: ys.clone().min().unwrap(),
+            x_max: xs.max().unwrap(),
+            y_max: ys.max().unwrap(),
+        };
+        let contour: Contour = points
+            .iter()
+            .map(|&(x, y)| CurvePoint { x, y, on_curve: true })
+            .collect::<Vec<_>>()
+            .into();
+        Glyph::Simple(SimpleGlyph {
+            bbox,
+            contours: vec![contour],
+            instructions: vec![],
+        })
+    }
+
+    let e = simple(&[(100, 0), (400, 0), (400, 500), (100, 500)]);
+    let acute = simple(&[(200, 600), (300, 600), (250, 700)]);
+    let component = |gid: u16, x: i16| {
+        Component::new(
+            GlyphId16::new(gid),
+            Anchor::Offset { x, y: 0 },
+            Transform::default(),
+            ComponentFlags::default(),
+        )
+    };
+    let mut eacute = CompositeGlyph::new(component(1, 0), Bbox::default());
+    eacute.add_component(component(2, 50), Bbox::default());
+
+    let font1 = make_test_font(&[".notdef", "A"], &[(0x41, "A")], Some(4));
+    let font2 = make_test_font(
+        &[".notdef", "e", "acutecomb", "eacute"],
+        &[(0x65, "e"), (0x301, "acutecomb"), (0xE9, "eacute")],
+        Some(4),
+    );
+    let font2 = with_glyphs(&font2, &[Glyph::Empty, e, acute, Glyph::Composite(eacute)]);
+
+    let options = Options::new().flatten_composites(FontSelector::NonFirst);
+    let merged = Merger::new(options).merge(&[&font1, &font2]).expect("merge failed");
+
+    let font_ref = FontRef::new(&merged).expect("parse merged font");
+    let gid = font_ref
+        .cmap()
+        .unwrap()
+        .map_codepoint(0xE9u32)
+        .expect("eacute mapped");
+    let glyph = font_ref
+        .loca(None)
+        .unwrap()
+        .get_glyf(gid, &font_ref.glyf().unwrap())
+        .expect("glyph lookup")
+        .expect("eacute exists");
+
+    let tables::glyf::Glyph::Simple(simple) = glyph else {
+        panic!("flattened eacute should be a simple glyph");
+    };
+    assert_eq!(simple.end_pts_of_contours().len(), 2, "expected e and acute contours");
+    let points: Vec<(i16, i16)> = simple.points().map(|p| (p.x, p.y)).collect();
+    assert_eq!(points.len(), 7);
+    assert!(points.contains(&(100, 500)), "e contour missing: {points:?}");
+    assert!(points.contains(&(300, 700)), "acute should be offset by 50: {points:?}");
+    assert_eq!((simple.y_min(), simple.y_max()), (0, 700));
+}
+
+/// A component offset flagged as scaled is transformed along with the
+/// component's outline when flattened
+#[test]
+fn test_flatten_scaled_component_offset() {
+    use font_types::{F2Dot14, GlyphId16};
+    use read_fonts::tables::glyf::CurvePoint;
+    use write_fonts::tables::glyf::{
+        Anchor, Component, ComponentFlags, CompositeGlyph, Contour, Transform,
+    };
+
+    let contour: Contour = [(0, 0), (100, 0), (100, 100), (0, 100)]
+        .map(|(x, y)| CurvePoint { x, y, on_curve: true })
+        .to_vec()
+        .into();
+    let square = Glyph::Simple(SimpleGlyph {
+        bbox: Bbox { x_min: 0, y_min: 0, x_max: 100, y_max: 100 },
+        contours: vec![contour],
+        instructions: vec![],
+    });
+    let half = F2Dot14::from_f32(0.5);
+    let small = CompositeGlyph::new(
+        Component::new(
+            GlyphId16::new(1),
+            Anchor::Offset { x: 200, y: 0 },
+            Transform { xx: half, yy: half, ..Default::default() },
+            ComponentFlags {
+                scaled_component_offset: true,
+                ..Default::default()
+            },
+        ),
+        Bbox::default(),
+    );
+
+    let font1 = make_test_font(&[".notdef", "A"], &[(0x41, "A")], Some(4));
+    let font2 = make_test_font(
+        &[".notdef", "square", "small"],
+        &[(0x25A0, "square"), (0x25AA, "small")],
+        Some(4),
+    );
+    let font2 = with_glyphs(&font2, &[Glyph::Empty, square, Glyph::Composite(small)]);
+
+    let options = Options::new().flatten_composites(FontSelector::NonFirst);
+    let merged = Merger::new(options).merge(&[&font1, &font2]).expect("merge failed");
+
+    let font_ref = FontRef::new(&merged).expect("parse merged font");
+    let gid = font_ref
+        .cmap()
+        .unwrap()
+        .map_codepoint(0x25AAu32)
+        .expect("small square mapped");
+    let glyph = font_ref
+        .loca(None)
+        .unwrap()
+        .get_glyf(gid, &font_ref.glyf().unwrap())
+        .expect("glyph lookup")
+        .expect("small square exists");
+    let tables::glyf::Glyph::Simple(simple) = glyph else {
+        panic!("flattened composite should be a simple glyph");
+    };
+    let points: Vec<(i16, i16)> = simple.points().map(|p| (p.x, p.y)).collect();
+    assert_eq!(points, [(100, 0), (150, 0), (150, 50), (100, 50)]);
+}
+
 // ============================================================================
 // Glyph Name Tests
 // ============================================================================
@@ -675,6 +1037,34 @@ fn test_glyph_name_disambiguation() {
     assert_ne!(gid1, gid2, "disambiguated glyphs should have different GIDs");
 }
 
+/// Test that FontPrefix renames a clashing glyph after its font's index
+#[test]
+fn test_glyph_name_font_prefix() {
+    // The test fonts have a version 3 post; give them real glyph names
+    let named = |font: Vec<u8>| {
+        let mut builder = FontBuilder::new();
+        builder.add_table(&Post::new_v2([".notdef", "A"])).unwrap();
+        builder.copy_missing_tables(FontRef::new(&font).unwrap());
+        builder.build()
+    };
+    let font1 = named(make_test_font(&[".notdef", "A"], &[(0x41, "A")], Some(4)));
+    let font2 = named(make_test_font(&[".notdef", "A"], &[(0x42, "A")], Some(4)));
+
+    let names = |scheme| {
+        let merger = Merger::new(Options::new().name_suffix_scheme(scheme));
+        let merged = merger.merge(&[&font1, &font2]).expect("merge failed");
+        let font_ref = FontRef::new(&merged).expect("parse merged font");
+        let post = font_ref.post().expect("post");
+        let cmap = font_ref.cmap().expect("cmap");
+        [0x41u32, 0x42]
+            .map(|cp| post.glyph_name(cmap.map_codepoint(cp).unwrap().try_into().unwrap()))
+            .map(|name| name.unwrap().to_string())
+    };
+
+    assert_eq!(names(NameSuffixScheme::Numeric), ["A", "A.1"]);
+    assert_eq!(names(NameSuffixScheme::FontPrefix), ["A", "f1.A"]);
+}
+
 /// Test that .notdef is always at GID 0
 #[test]
 fn test_notdef_at_gid_zero() {
@@ -694,6 +1084,53 @@ fn test_notdef_at_gid_zero() {
     // This is just a sanity check that the font structure is correct
 }
 
+/// Test that a supplied .notdef replaces the merged GID 0 outline
+#[test]
+fn test_custom_notdef() {
+    use read_fonts::tables::glyf::CurvePoint;
+    use write_fonts::tables::glyf::Contour;
+
+    fn notdef_font(points: &[(i16, i16)]) -> Vec<u8> {
+        let (x_max, y_max) = points.iter().fold((0, 0), |(x, y), p| (p.0.max(x), p.1.max(y)));
+        let contour: Contour = points
+            .iter()
+            .map(|&(x, y)| CurvePoint { x, y, on_curve: true })
+            .collect::<Vec<_>>()
+            .into();
+        let glyph = Glyph::Simple(SimpleGlyph {
+            bbox: Bbox { x_min: 0, y_min: 0, x_max, y_max },
+            contours: vec![contour],
+            instructions: vec![],
+        });
+        with_glyphs(&make_test_font(&[".notdef"], &[], Some(4)), &[glyph])
+    }
+
+    let font1 = make_test_font(&[".notdef", "A"], &[(0x41, "A")], Some(4));
+    let font2 = make_test_font(&[".notdef", "B"], &[(0x42, "B")], Some(4));
+
+    let box_points = [(50, 0), (450, 0), (450, 700), (50, 700)];
+    let options = Options::new().notdef_from(Some(notdef_font(&box_points)));
+    let merged = Merger::new(options).merge(&[&font1, &font2]).expect("merge failed");
+
+    let font_ref = FontRef::new(&merged).expect("parse merged font");
+    let glyph = font_ref
+        .loca(None)
+        .unwrap()
+        .get_glyf(GlyphId::NOTDEF, &font_ref.glyf().unwrap())
+        .expect("glyph lookup")
+        .expect(".notdef has an outline");
+    let tables::glyf::Glyph::Simple(simple) = glyph else {
+        panic!("custom .notdef should be a simple glyph");
+    };
+    let points: Vec<(i16, i16)> = simple.points().map(|p| (p.x, p.y)).collect();
+    assert_eq!(points, box_points);
+    assert_eq!(font_ref.hmtx().unwrap().side_bearing(GlyphId::NOTDEF), Some(50));
+
+    let oversized = notdef_font(&[(0, 0), (1200, 0), (1200, 700), (0, 700)]);
+    let options = Options::new().notdef_from(Some(oversized));
+    assert!(Merger::new(options).merge(&[&font1, &font2]).is_err());
+}
+
 // ============================================================================
 // cmap Tests (based on fontTools CmapMergeUnitTest)
 // ============================================================================
@@ -1075,3 +1512,1282 @@ fn test_merge_preserves_extension_gpos_lookups() {
         "vpal's value record was not preserved through merge — likely crossed with another feature's lookup"
     );
 }
+
+/// `merge_verbose` lists lookups that were left out of the merged font.
+/// Extension-wrapped lookups are unwrapped and kept, so only the empty
+/// lookup is reported.
+#[test]
+fn test_merge_verbose_reports_dropped_lookups() {
+    use font_types::GlyphId16;
+    use warpnine_font_merger::{DroppedLookup, FontIndex};
+    use write_fonts::tables::{
+        gpos::{
+            ExtensionPosFormat1, ExtensionSubtable, PositionLookup, PositionLookupList, SinglePos,
+            SinglePosFormat1, ValueRecord,
+        },
+        layout::{
+            CoverageTable, Feature, FeatureList, FeatureRecord, LangSys, Lookup, LookupFlag,
+            Script, ScriptList, ScriptRecord,
+        },
+    };
+
+    let single = SinglePos::Format1(SinglePosFormat1::new(
+        CoverageTable::format_1(vec![GlyphId16::new(1)]),
+        ValueRecord::new().with_x_advance(-50),
+    ));
+    let extension = PositionLookup::Extension(Lookup::new(
+        LookupFlag::default(),
+        vec![ExtensionSubtable::Single(ExtensionPosFormat1::new(1, single))],
+    ));
+    let empty = PositionLookup::Single(Lookup::new(LookupFlag::default(), Vec::new()));
+    let gpos = Gpos::new(
+        ScriptList::new(vec![ScriptRecord::new(
+            Tag::new(b"DFLT"),
+            Script::new(Some(LangSys::new(vec![0])), vec![]),
+        )]),
+        FeatureList::new(vec![FeatureRecord::new(
+            Tag::new(b"kern"),
+            Feature::new(None, vec![0, 1]),
+        )]),
+        PositionLookupList::new(vec![extension, empty]),
+    );
+
+    let plain = make_test_font(&[".notdef", "B"], &[(0x42, "B")], Some(4));
+    let with_gpos = make_test_font_with_gpos(&[".notdef", "A"], &[(0x41, "A")], &gpos);
+
+    let (merged, report) = Merger::default().merge_verbose(&[&plain, &with_gpos]).unwrap();
+    assert_eq!(
+        report.dropped_lookups,
+        [DroppedLookup {
+            font_index: FontIndex::new(1),
+            table: Tag::new(b"GPOS"),
+            lookup_index: 1,
+            lookup_type: 1,
+        }]
+    );
+    let font = FontRef::new(&merged).unwrap();
+    assert_eq!(font.gpos().unwrap().lookup_list().unwrap().lookup_count(), 1);
+}
+
+/// Lookups that use a mark filtering set index into their own font's GDEF
+/// MarkGlyphSets. After merging, the sets of all fonts live in a single
+/// GDEF, so later fonts' lookups must be shifted past the earlier sets.
+#[test]
+fn test_merge_remaps_mark_filtering_sets() {
+    use font_types::GlyphId16;
+    use write_fonts::tables::{
+        gdef::{Gdef, GlyphClassDef, MarkGlyphSets},
+        gpos::{PositionLookup, PositionLookupList, SinglePos, SinglePosFormat1, ValueRecord},
+        layout::{
+            ClassDef, CoverageTable, Feature, FeatureList, FeatureRecord, LangSys, Lookup,
+            LookupFlag, Script, ScriptList, ScriptRecord,
+        },
+    };
+
+    fn font_with_mark_sets(
+        glyph_names: &[&str],
+        cmap_entries: &[(u32, &str)],
+        marks: &[u16],
+        filtering_set: u16,
+    ) -> Vec<u8> {
+        let classes = marks
+            .iter()
+            .map(|&gid| (GlyphId16::new(gid), GlyphClassDef::Mark as u16));
+        let mut gdef = Gdef::new(Some(ClassDef::from_iter(classes)), None, None, None);
+        gdef.mark_glyph_sets_def = Some(MarkGlyphSets::new(
+            marks
+                .iter()
+                .map(|&gid| CoverageTable::format_1(vec![GlyphId16::new(gid)]))
+                .collect(),
+        ))
+        .into();
+
+        let subtable = SinglePos::Format1(SinglePosFormat1::new(
+            CoverageTable::format_1(vec![GlyphId16::new(1)]),
+            ValueRecord::new().with_x_advance(10),
+        ));
+        let mut lookup = Lookup::new(LookupFlag::USE_MARK_FILTERING_SET, vec![subtable]);
+        lookup.mark_filtering_set = Some(filtering_set);
+        let gpos = Gpos::new(
+            ScriptList::new(vec![ScriptRecord::new(
+                Tag::new(b"DFLT"),
+                Script::new(Some(LangSys::new(vec![0])), vec![]),
+            )]),
+            FeatureList::new(vec![FeatureRecord::new(
+                Tag::new(b"kern"),
+                Feature::new(None, vec![0]),
+            )]),
+            PositionLookupList::new(vec![PositionLookup::Single(lookup)]),
+        );
+
+        let font = make_test_font_with_gpos(glyph_names, cmap_entries, &gpos);
+        let mut builder = FontBuilder::new();
+        builder.add_table(&gdef).unwrap();
+        builder.copy_missing_tables(FontRef::new(&font).unwrap());
+        builder.build()
+    }
+
+    let font1 = font_with_mark_sets(&[".notdef", "a", "acutecomb"], &[(0x61, "a")], &[2], 0);
+    let font2 = font_with_mark_sets(
+        &[".notdef", "b", "gravecomb", "dotbelowcomb"],
+        &[(0x62, "b")],
+        &[2, 3],
+        1,
+    );
+
+    let merged = Merger::default().merge(&[&font1, &font2]).expect("merge failed");
+    let font = FontRef::new(&merged).expect("parse merged font");
+    // The test fonts carry no glyph names, so font2's glyphs are appended
+    // after font1's three: gravecomb -> 5, dotbelowcomb -> 6.
+    let (acutecomb, gravecomb, dotbelowcomb) = (2, 5, 6);
+
+    let gdef = font.gdef().expect("merged font has GDEF");
+    let mark_sets = gdef.mark_glyph_sets_def().expect("mark glyph sets").unwrap();
+    let sets: Vec<Vec<u16>> = mark_sets
+        .coverages()
+        .iter()
+        .map(|coverage| coverage.unwrap().iter().map(|gid| gid.to_u16()).collect())
+        .collect();
+    assert_eq!(sets, vec![vec![acutecomb], vec![gravecomb], vec![dotbelowcomb]]);
+
+    let class_def = gdef.glyph_class_def().expect("glyph class def").unwrap();
+    assert_eq!(class_def.get(GlyphId16::new(dotbelowcomb)), 3);
+
+    let lookup_list = font.gpos().unwrap().lookup_list().unwrap();
+    let filtering_sets: Vec<Option<u16>> = lookup_list
+        .lookups()
+        .iter()
+        .map(|lookup| lookup.unwrap().mark_filtering_set())
+        .collect();
+    // font2's set 1 (dotbelowcomb) follows font1's single set.
+    assert_eq!(filtering_sets, vec![Some(0), Some(2)]);
+}
+
+/// A marks font's MarkToBase lookups are moved to the front of the merged
+/// lookup list, ahead of the base font's, and every feature keeps pointing at
+/// its own lookups.
+#[test]
+fn test_prefer_marks_from() {
+    use font_types::GlyphId16;
+    use read_fonts::tables::gpos::PositionSubtables;
+    use write_fonts::tables::{
+        gpos::{
+            AnchorTable, BaseArray, BaseRecord, MarkArray, MarkBasePosFormat1, MarkRecord,
+            PositionLookup, PositionLookupList, SinglePos, SinglePosFormat1, ValueRecord,
+        },
+        layout::{
+            CoverageTable, Feature, FeatureList, FeatureRecord, LangSys, Lookup, LookupFlag,
+            Script, ScriptList, ScriptRecord,
+        },
+    };
+
+    fn mark_to_base(mark_x: i16) -> PositionLookup {
+        let subtable = MarkBasePosFormat1::new(
+            CoverageTable::format_1(vec![GlyphId16::new(2)]),
+            CoverageTable::format_1(vec![GlyphId16::new(1)]),
+            MarkArray::new(vec![MarkRecord::new(0, AnchorTable::format_1(mark_x, 500))]),
+            BaseArray::new(vec![BaseRecord::new(vec![Some(AnchorTable::format_1(250, 700))])]),
+        );
+        PositionLookup::MarkToBase(Lookup::new(LookupFlag::empty(), vec![subtable]))
+    }
+
+    fn gpos(lookups: Vec<PositionLookup>, features: &[(&[u8; 4], u16)]) -> Gpos {
+        let records = features
+            .iter()
+            .map(|(tag, lookup)| {
+                FeatureRecord::new(Tag::new(tag), Feature::new(None, vec![*lookup]))
+            })
+            .collect();
+        let lang_sys = LangSys::new((0..features.len() as u16).collect());
+        Gpos::new(
+            ScriptList::new(vec![ScriptRecord::new(
+                Tag::new(b"DFLT"),
+                Script::new(Some(lang_sys), vec![]),
+            )]),
+            FeatureList::new(records),
+            PositionLookupList::new(lookups),
+        )
+    }
+
+    let kern = PositionLookup::Single(Lookup::new(
+        LookupFlag::empty(),
+        vec![SinglePos::Format1(SinglePosFormat1::new(
+            CoverageTable::format_1(vec![GlyphId16::new(1)]),
+            ValueRecord::new().with_x_advance(-20),
+        ))],
+    ));
+    let base_gpos = gpos(vec![kern, mark_to_base(111)], &[(b"kern", 0), (b"mark", 1)]);
+    let marks_gpos = gpos(vec![mark_to_base(222)], &[(b"mark", 0)]);
+
+    let glyphs = [".notdef", "a", "acutecomb"];
+    let base = make_test_font_with_gpos(&glyphs, &[(0x61, "a")], &base_gpos);
+    let marks = make_test_font_with_gpos(&glyphs, &[(0x301, "acutecomb")], &marks_gpos);
+
+    let merger = Merger::new(Options::default().prefer_marks_from(1));
+    let merged = merger.merge(&[&base, &marks]).expect("merge failed");
+    let font = FontRef::new(&merged).expect("parse merged font");
+    let gpos = font.gpos().expect("merged font has GPOS");
+
+    // The x of the mark anchor identifies which font a MarkToBase lookup came from.
+    let lookup_list = gpos.lookup_list().unwrap();
+    let mark_anchor_x = |idx: usize| -> Option<i16> {
+        let lookup = lookup_list.lookups().get(idx).unwrap();
+        let PositionSubtables::MarkToBase(subtables) = lookup.subtables().unwrap() else {
+            return None;
+        };
+        let subtable = subtables.get(0).unwrap();
+        let mark_array = subtable.mark_array().unwrap();
+        let anchor = mark_array.mark_records()[0]
+            .mark_anchor(mark_array.offset_data())
+            .unwrap();
+        Some(anchor.x_coordinate())
+    };
+    assert_eq!(mark_anchor_x(0), Some(222));
+    assert_eq!(mark_anchor_x(1), None);
+    assert_eq!(mark_anchor_x(2), Some(111));
+
+    let feature_list = gpos.feature_list().unwrap();
+    let references: Vec<(Tag, Vec<u16>)> = feature_list
+        .feature_records()
+        .iter()
+        .map(|record| {
+            let feature = record.feature(feature_list.offset_data()).unwrap();
+            let indices = feature.lookup_list_indices().iter().map(|i| i.get()).collect();
+            (record.feature_tag(), indices)
+        })
+        .collect();
+    assert_eq!(
+        references,
+        vec![
+            (Tag::new(b"kern"), vec![1]),
+            (Tag::new(b"mark"), vec![2]),
+            (Tag::new(b"mark"), vec![0]),
+        ]
+    );
+}
+
+/// Only the preferred font's mark attachment lookups move; every other
+/// lookup keeps its relative order, and contextual lookups still reach the
+/// mark lookups they reference.
+#[test]
+fn test_prefer_marks_from_remaps_nested_lookups() {
+    use font_types::GlyphId16;
+    use read_fonts::tables::{gpos::PositionSubtables, layout::ChainedSequenceContext as Context};
+    use write_fonts::tables::{
+        gpos::{
+            AnchorTable, BaseArray, BaseRecord, MarkArray, MarkBasePosFormat1, MarkRecord,
+            PositionChainContext, PositionLookup, PositionLookupList, SinglePos, SinglePosFormat1,
+            ValueRecord,
+        },
+        layout::{
+            ChainedSequenceContext, ChainedSequenceContextFormat3, CoverageTable, Feature,
+            FeatureList, FeatureRecord, LangSys, Lookup, LookupFlag, Script, ScriptList,
+            ScriptRecord, SequenceLookupRecord,
+        },
+    };
+
+    let mark_to_base = || {
+        let subtable = MarkBasePosFormat1::new(
+            CoverageTable::format_1(vec![GlyphId16::new(2)]),
+            CoverageTable::format_1(vec![GlyphId16::new(1)]),
+            MarkArray::new(vec![MarkRecord::new(0, AnchorTable::format_1(0, 500))]),
+            BaseArray::new(vec![BaseRecord::new(vec![Some(AnchorTable::format_1(250, 700))])]),
+        );
+        PositionLookup::MarkToBase(Lookup::new(LookupFlag::empty(), vec![subtable]))
+    };
+    let gpos = |lookups: Vec<PositionLookup>, feature_lookups: Vec<u16>| {
+        Gpos::new(
+            ScriptList::new(vec![ScriptRecord::new(
+                Tag::new(b"DFLT"),
+                Script::new(Some(LangSys::new(vec![0])), vec![]),
+            )]),
+            FeatureList::new(vec![FeatureRecord::new(
+                Tag::new(b"mark"),
+                Feature::new(None, feature_lookups),
+            )]),
+            PositionLookupList::new(lookups),
+        )
+    };
+
+    let kern = PositionLookup::Single(Lookup::new(
+        LookupFlag::empty(),
+        vec![SinglePos::Format1(SinglePosFormat1::new(
+            CoverageTable::format_1(vec![GlyphId16::new(1)]),
+            ValueRecord::new().with_x_advance(-20),
+        ))],
+    ));
+    // Applies the marks font's MarkToBase (its lookup 1) after glyph 1.
+    let context = PositionLookup::ChainContextual(Lookup::new(
+        LookupFlag::empty(),
+        vec![PositionChainContext::from(ChainedSequenceContext::Format3(
+            ChainedSequenceContextFormat3::new(
+                vec![],
+                vec![
+                    CoverageTable::format_1(vec![GlyphId16::new(1)]),
+                    CoverageTable::format_1(vec![GlyphId16::new(2)]),
+                ],
+                vec![],
+                vec![SequenceLookupRecord::new(1, 1)],
+            ),
+        ))],
+    ));
+    let base_gpos = gpos(vec![kern, mark_to_base()], vec![0, 1]);
+    let marks_gpos = gpos(vec![context, mark_to_base()], vec![0]);
+
+    let glyphs = [".notdef", "a", "acutecomb"];
+    let base = make_test_font_with_gpos(&glyphs, &[(0x61, "a")], &base_gpos);
+    let marks = make_test_font_with_gpos(&glyphs, &[(0x301, "acutecomb")], &marks_gpos);
+
+    let merger = Merger::new(Options::default().prefer_marks_from(1));
+    let merged = merger.merge(&[&base, &marks]).expect("merge failed");
+    let font = FontRef::new(&merged).expect("parse merged font");
+    let lookup_list = font.gpos().unwrap().lookup_list().unwrap();
+    let subtables: Vec<PositionSubtables> = lookup_list
+        .lookups()
+        .iter()
+        .map(|lookup| lookup.unwrap().subtables().unwrap())
+        .collect();
+
+    // Merged order was kern, base mark, context, marks-font mark.
+    let kinds: Vec<&str> = subtables
+        .iter()
+        .map(|subtables| match subtables {
+            PositionSubtables::Single(_) => "single",
+            PositionSubtables::MarkToBase(_) => "mark",
+            PositionSubtables::ChainContextual(_) => "context",
+            _ => "other",
+        })
+        .collect();
+    assert_eq!(kinds, ["mark", "single", "mark", "context"]);
+    let PositionSubtables::ChainContextual(contexts) = &subtables[3] else {
+        unreachable!();
+    };
+    let Context::Format3(context) = contexts.get(0).unwrap() else {
+        panic!("expected format 3");
+    };
+    assert_eq!(context.seq_lookup_records()[0].lookup_list_index(), 0);
+}
+
+#[test]
+fn test_merge_unions_meta_script_tags() {
+    use write_fonts::tables::meta::{DataMapRecord, Meta, Metadata, SLNG, ScriptLangTag};
+
+    fn with_slng(font: &[u8], scripts: &[&str]) -> Vec<u8> {
+        let tags = scripts
+            .iter()
+            .map(|script| ScriptLangTag::new((*script).to_string()).unwrap())
+            .collect();
+        let meta = Meta::new(vec![DataMapRecord::new(SLNG, Metadata::ScriptLangTags(tags))]);
+        let mut builder = FontBuilder::new();
+        builder.add_table(&meta).unwrap();
+        builder.copy_missing_tables(FontRef::new(font).unwrap());
+        builder.build()
+    }
+
+    let latin = with_slng(&make_test_font(&[".notdef", "A"], &[(0x41, "A")], Some(4)), &["Latn"]);
+    let cjk = with_slng(
+        &make_test_font(&[".notdef", "uni4E00"], &[(0x4E00, "uni4E00")], Some(4)),
+        &["Hani", "Kana"],
+    );
+
+    let merged = Merger::default().merge(&[&latin, &cjk]).expect("merge failed");
+    let font = FontRef::new(&merged).expect("parse merged font");
+
+    let meta = font.meta().expect("merged font has meta");
+    let record = meta.data_maps().iter().find(|record| record.tag() == SLNG).unwrap();
+    let tables::meta::Metadata::ScriptLangTags(tags) = record.data(meta.offset_data()).unwrap()
+    else {
+        panic!("slng should hold ScriptLangTags");
+    };
+    let scripts: Vec<&str> = tags.iter().map(|tag| tag.unwrap().as_str()).collect();
+    assert_eq!(scripts, ["Latn", "Hani", "Kana"]);
+}
+
+/// COLRv1 paints from the second font must point at its own colors (shifted
+/// past the first font's palette entries) and its own layers.
+#[test]
+fn test_merge_offsets_colr_palette_indices() {
+    use font_types::{F2Dot14, GlyphId16};
+    use write_fonts::tables::{
+        colr::{BaseGlyphList, BaseGlyphPaint, Colr, LayerList, Paint},
+        cpal::{ColorRecord, Cpal},
+    };
+
+    fn color_font(glyph_names: &[&str], cmap: &[(u32, &str)], colors: &[ColorRecord]) -> Vec<u8> {
+        // Glyph 1 is drawn as a single layer filled with the last palette entry.
+        let layer =
+            Paint::glyph(Paint::solid(colors.len() as u16 - 1, F2Dot14::ONE), GlyphId16::new(1));
+        let colr = Colr {
+            base_glyph_list: Some(BaseGlyphList::new(
+                1,
+                vec![BaseGlyphPaint::new(GlyphId16::new(1), Paint::colr_layers(1, 0))],
+            ))
+            .into(),
+            layer_list: Some(LayerList::new(1, vec![layer])).into(),
+            ..Default::default()
+        };
+        let cpal =
+            Cpal::new(colors.len() as u16, 1, colors.len() as u16, Some(colors.to_vec()), vec![0]);
+
+        let font = make_test_font(glyph_names, cmap, Some(4));
+        let mut builder = FontBuilder::new();
+        builder.add_table(&colr).unwrap();
+        builder.add_table(&cpal).unwrap();
+        builder.copy_missing_tables(FontRef::new(&font).unwrap());
+        builder.build()
+    }
+
+    let red = ColorRecord::new(0, 0, 255, 255);
+    let green = ColorRecord::new(0, 255, 0, 255);
+    let blue = ColorRecord::new(255, 0, 0, 255);
+    let font1 = color_font(&[".notdef", "A"], &[(0x41, "A")], &[red.clone(), green.clone()]);
+    let font2 = color_font(&[".notdef", "B"], &[(0x42, "B")], std::slice::from_ref(&blue));
+
+    let merged = Merger::default().merge(&[&font1, &font2]).expect("merge failed");
+    let font = FontRef::new(&merged).expect("parse merged font");
+
+    let cpal: Cpal = font.cpal().expect("merged font has CPAL").to_owned_table();
+    assert_eq!(cpal.num_palette_entries, 3);
+    assert_eq!(cpal.color_records_array.as_ref().unwrap(), &vec![red, green, blue]);
+
+    let colr: Colr = font.colr().expect("merged font has COLR").to_owned_table();
+    // font2's glyphs follow font1's two: B -> 3.
+    let base_glyphs: Vec<(u16, Paint)> = colr
+        .base_glyph_list
+        .as_ref()
+        .unwrap()
+        .base_glyph_paint_records
+        .iter()
+        .map(|record| (record.glyph_id.to_u16(), (*record.paint).clone()))
+        .collect();
+    assert_eq!(base_glyphs, vec![(1, Paint::colr_layers(1, 0)), (3, Paint::colr_layers(1, 1))]);
+
+    let layers: Vec<Paint> = colr
+        .layer_list
+        .as_ref()
+        .unwrap()
+        .paints
+        .iter()
+        .map(|paint| (**paint).clone())
+        .collect();
+    assert_eq!(
+        layers,
+        vec![
+            Paint::glyph(Paint::solid(1, F2Dot14::ONE), GlyphId16::new(1)),
+            // Blue was entry 0 of font2's palette and is entry 2 once merged.
+            Paint::glyph(Paint::solid(2, F2Dot14::ONE), GlyphId16::new(3)),
+        ]
+    );
+}
+
+/// A COLR table carried by a single input is still remapped to the merged
+/// glyph order, and its CPAL is copied through.
+#[test]
+fn test_merge_remaps_lone_colr() {
+    use font_types::{F2Dot14, GlyphId16};
+    use write_fonts::tables::{
+        colr::{BaseGlyphList, BaseGlyphPaint, Colr, Paint},
+        cpal::{ColorRecord, Cpal},
+    };
+
+    let colr = Colr {
+        base_glyph_list: Some(BaseGlyphList::new(
+            1,
+            vec![BaseGlyphPaint::new(
+                GlyphId16::new(1),
+                Paint::glyph(Paint::solid(0, F2Dot14::ONE), GlyphId16::new(1)),
+            )],
+        ))
+        .into(),
+        ..Default::default()
+    };
+    let red = ColorRecord::new(0, 0, 255, 255);
+    let cpal = Cpal::new(1, 1, 1, Some(vec![red]), vec![0]);
+    let plain = make_test_font(&[".notdef", "A"], &[(0x41, "A")], Some(4));
+    let base = make_test_font(&[".notdef", "B"], &[(0x42, "B")], Some(4));
+    let mut builder = FontBuilder::new();
+    builder.add_table(&colr).unwrap();
+    builder.add_table(&cpal).unwrap();
+    builder.copy_missing_tables(FontRef::new(&base).unwrap());
+    let color = builder.build();
+
+    let merged = Merger::default().merge(&[&plain, &color]).expect("merge failed");
+    let font = FontRef::new(&merged).expect("parse merged font");
+
+    let merged_cpal: Cpal = font.cpal().expect("merged font has CPAL").to_owned_table();
+    assert_eq!(merged_cpal, cpal);
+    let colr: Colr = font.colr().expect("merged font has COLR").to_owned_table();
+    let records = &colr.base_glyph_list.as_ref().unwrap().base_glyph_paint_records;
+    // B follows the first font's .notdef and A.
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].glyph_id, GlyphId16::new(3));
+    assert_eq!(*records[0].paint, Paint::glyph(Paint::solid(0, F2Dot14::ONE), GlyphId16::new(3)));
+}
+
+/// COLRv0 layer indices that no longer fit in 16 bits once the fonts' layer
+/// records are concatenated are an error rather than wrapping around.
+#[test]
+fn test_merge_rejects_colr_layer_overflow() {
+    use font_types::GlyphId16;
+    use write_fonts::tables::colr::{BaseGlyph, Colr, Layer};
+
+    fn layered_font(glyph_names: &[&str], cmap: &[(u32, &str)], num_layers: u16) -> Vec<u8> {
+        // Glyph 1 is drawn by the last of `num_layers` layer records.
+        let layers = vec![Layer::new(GlyphId16::new(1), 0); usize::from(num_layers)];
+        let colr = Colr::new(
+            1,
+            Some(vec![BaseGlyph::new(GlyphId16::new(1), num_layers - 1, 1)]),
+            Some(layers),
+            num_layers,
+        );
+        let font = make_test_font(glyph_names, cmap, Some(4));
+        let mut builder = FontBuilder::new();
+        builder.add_table(&colr).unwrap();
+        builder.copy_missing_tables(FontRef::new(&font).unwrap());
+        builder.build()
+    }
+
+    let font1 = layered_font(&[".notdef", "A"], &[(0x41, "A")], 40000);
+    let font2 = layered_font(&[".notdef", "B"], &[(0x42, "B")], 30000);
+
+    let result = Merger::default().merge(&[&font1, &font2]);
+    assert!(matches!(result, Err(MergeError::TooManyColrLayers)));
+}
+
+#[test]
+fn test_keep_features_prunes_unreferenced_lookups() {
+    use font_types::GlyphId16;
+    use write_fonts::tables::{
+        gpos::{PositionLookup, PositionLookupList, SinglePos, SinglePosFormat1, ValueRecord},
+        gsub::{
+            Gsub, Ligature, LigatureSet, LigatureSubstFormat1, SingleSubst,
+            SubstitutionChainContext, SubstitutionLookup, SubstitutionLookupList,
+        },
+        layout::{
+            ChainedSequenceContext, ChainedSequenceContextFormat3, CoverageTable, Feature,
+            FeatureList, FeatureRecord, LangSys, Lookup, LookupFlag, Script, ScriptList,
+            ScriptRecord, SequenceLookupRecord,
+        },
+    };
+
+    fn script_list(feature_count: u16) -> ScriptList {
+        ScriptList::new(vec![ScriptRecord::new(
+            Tag::new(b"DFLT"),
+            Script::new(Some(LangSys::new((0..feature_count).collect())), vec![]),
+        )])
+    }
+
+    fn feature_list(features: &[(&[u8; 4], Vec<u16>)]) -> FeatureList {
+        FeatureList::new(
+            features
+                .iter()
+                .map(|(tag, lookups)| {
+                    FeatureRecord::new(Tag::new(tag), Feature::new(None, lookups.clone()))
+                })
+                .collect(),
+        )
+    }
+
+    let coverage = || CoverageTable::format_1(vec![GlyphId16::new(1)]);
+    let gsub = Gsub::new(
+        script_list(2),
+        feature_list(&[(b"liga", vec![0]), (b"calt", vec![1])]),
+        SubstitutionLookupList::new(vec![
+            SubstitutionLookup::Ligature(Lookup::new(
+                LookupFlag::empty(),
+                vec![LigatureSubstFormat1::new(
+                    coverage(),
+                    vec![LigatureSet::new(vec![Ligature::new(
+                        GlyphId16::new(2),
+                        vec![GlyphId16::new(1)],
+                    )])],
+                )],
+            )),
+            SubstitutionLookup::ChainContextual(Lookup::new(
+                LookupFlag::empty(),
+                vec![SubstitutionChainContext::from(ChainedSequenceContext::Format3(
+                    ChainedSequenceContextFormat3::new(
+                        vec![],
+                        vec![coverage()],
+                        vec![],
+                        vec![SequenceLookupRecord::new(0, 2)],
+                    ),
+                ))],
+            )),
+            // Only reachable through calt's nested lookup record.
+            SubstitutionLookup::Single(Lookup::new(
+                LookupFlag::empty(),
+                vec![SingleSubst::format_1(coverage(), 1)],
+            )),
+        ]),
+    );
+    let kern = PositionLookup::Single(Lookup::new(
+        LookupFlag::empty(),
+        vec![SinglePos::Format1(SinglePosFormat1::new(
+            coverage(),
+            ValueRecord::new().with_x_advance(-20),
+        ))],
+    ));
+    let gpos = Gpos::new(
+        script_list(2),
+        feature_list(&[(b"kern", vec![0]), (b"dist", vec![1])]),
+        PositionLookupList::new(vec![kern.clone(), kern]),
+    );
+
+    let glyphs = [".notdef", "a", "a.alt"];
+    let base = make_test_font_with_gpos(&glyphs, &[(0x61, "a")], &gpos);
+    let mut builder = FontBuilder::new();
+    builder.add_table(&gsub).unwrap();
+    builder.copy_missing_tables(FontRef::new(&base).unwrap());
+    let font1 = builder.build();
+    let font2 = make_test_font(&[".notdef", "b"], &[(0x62, "b")], Some(4));
+
+    let feature_tags = |font: &FontRef, gsub: bool| -> Vec<Tag> {
+        let list = if gsub {
+            font.gsub().unwrap().feature_list().unwrap()
+        } else {
+            font.gpos().unwrap().feature_list().unwrap()
+        };
+        list.feature_records().iter().map(|r| r.feature_tag()).collect()
+    };
+
+    let merged = Merger::new(Options::default().keep_features(vec![Tag::new(b"kern")]))
+        .merge(&[&font1, &font2])
+        .expect("merge failed");
+    let font = FontRef::new(&merged).unwrap();
+    // Every GSUB feature is pruned, so the merged font has no GSUB at all.
+    assert!(font.gsub().is_err());
+    assert_eq!(feature_tags(&font, false), [Tag::new(b"kern")]);
+    assert_eq!(font.gpos().unwrap().lookup_list().unwrap().lookup_count(), 1);
+    let script_list = font.gpos().unwrap().script_list().unwrap();
+    let script = script_list.script_records()[0]
+        .script(script_list.offset_data())
+        .unwrap();
+    let lang_sys = script.default_lang_sys().unwrap().unwrap();
+    assert_eq!(lang_sys.feature_indices().iter().map(|i| i.get()).collect::<Vec<_>>(), [0]);
+
+    // Keeping calt keeps the lookup it calls, renumbered after liga is pruned.
+    let merged = Merger::new(Options::default().keep_features(vec![Tag::new(b"calt")]))
+        .merge(&[&font1, &font2])
+        .expect("merge failed");
+    let font = FontRef::new(&merged).unwrap();
+    let gsub = font.gsub().unwrap();
+    assert_eq!(feature_tags(&font, true), [Tag::new(b"calt")]);
+    let lookups = gsub.lookup_list().unwrap();
+    assert_eq!(lookups.lookup_count(), 2);
+    let tables::gsub::SubstitutionSubtables::ChainContextual(subtables) =
+        lookups.lookups().get(0).unwrap().subtables().unwrap()
+    else {
+        panic!("first lookup should be calt's chain context");
+    };
+    let tables::layout::ChainedSequenceContext::Format3(context) = subtables.get(0).unwrap() else {
+        panic!("expected format 3");
+    };
+    assert_eq!(context.seq_lookup_records()[0].lookup_list_index(), 1);
+}
+
+/// Charstring commands, hints included, in the order they are evaluated
+#[derive(Default, Debug, PartialEq)]
+struct CharstringCommands(Vec<String>);
+
+impl read_fonts::ps::cs::CommandSink for CharstringCommands {
+    fn move_to(&mut self, x: Fixed, y: Fixed) {
+        self.0.push(format!("M{x:?},{y:?}"));
+    }
+    fn line_to(&mut self, x: Fixed, y: Fixed) {
+        self.0.push(format!("L{x:?},{y:?}"));
+    }
+    fn curve_to(&mut self, cx0: Fixed, cy0: Fixed, cx1: Fixed, cy1: Fixed, x: Fixed, y: Fixed) {
+        self.0.push(format!("C{cx0:?},{cy0:?},{cx1:?},{cy1:?},{x:?},{y:?}"));
+    }
+    fn close(&mut self) {
+        self.0.push("Z".into());
+    }
+    fn hstem(&mut self, y: Fixed, dy: Fixed) {
+        self.0.push(format!("H{y:?},{dy:?}"));
+    }
+    fn vstem(&mut self, x: Fixed, dx: Fixed) {
+        self.0.push(format!("V{x:?},{dx:?}"));
+    }
+    fn hint_mask(&mut self, mask: &[u8]) {
+        self.0.push(format!("mask{mask:?}"));
+    }
+}
+
+/// Glyph ID, evaluated commands and advance width of every glyph in a
+/// font's CFF, by charset name
+fn cff_glyphs(data: &[u8]) -> HashMap<String, (GlyphId, CharstringCommands, Option<Fixed>)> {
+    use read_fonts::ps::cff::CffFontRef;
+
+    let font = FontRef::new(data).unwrap();
+    let cff = CffFontRef::new_cff(font.table_data(Tag::new(b"CFF ")).unwrap().as_bytes(), 0, None)
+        .unwrap();
+    let subfont = cff.subfont(0, &[]).unwrap();
+    let charset = cff.charset().unwrap();
+    (0..cff.num_glyphs())
+        .map(|gid| {
+            let gid = GlyphId::new(gid);
+            let name = cff.string(charset.string_id(gid).unwrap()).unwrap();
+            let mut commands = CharstringCommands::default();
+            let width = cff.evaluate_charstring(&subfont, gid, &[], &mut commands).unwrap();
+            (String::from_utf8_lossy(name).into_owned(), (gid, commands, width))
+        })
+        .collect()
+}
+
+#[test]
+fn test_merge_cff_fonts() {
+    // Both non-CID; the first uses global and local subroutines
+    let font1 = font_test_data::NOTO_SERIF_DISPLAY_TRIMMED;
+    let font2 = font_test_data::CHARSTRING_PATH_OPS;
+
+    let merged = Merger::default().merge(&[font1, font2]).unwrap();
+    let font = FontRef::new(&merged).unwrap();
+    assert!(font.glyf().is_err());
+    assert_eq!(font.maxp().unwrap().num_glyphs(), 10);
+
+    let merged = cff_glyphs(&merged);
+    assert_eq!(merged.len(), 10);
+    for (source, renamed) in [(font1, false), (font2, true)] {
+        let hmtx = FontRef::new(source).unwrap().hmtx().unwrap();
+        for (name, (gid, commands, _)) in cff_glyphs(source) {
+            let advance = hmtx.advance(gid).unwrap();
+            // Names shared with the first font get a ".1" suffix
+            let name = if renamed && ["i", ".notdef"].contains(&name.as_str()) {
+                format!("{name}.1")
+            } else {
+                name
+            };
+            let (_, merged_commands, merged_width) = &merged[&name];
+            assert_eq!(merged_commands, &commands, "outline of {name}");
+            // Widths are rewritten from hmtx
+            assert_eq!(*merged_width, Some(Fixed::from_i32(advance.into())), "width of {name}");
+        }
+    }
+}
+
+#[test]
+fn test_merge_cff_rejects_cid_keyed() {
+    let result = Merger::default()
+        .merge(&[font_test_data::NOTO_SANS_JP_CFF, font_test_data::CHARSTRING_PATH_OPS]);
+    assert!(matches!(result, Err(MergeError::CidKeyedCffNotSupported)));
+}
+
+/// Add a version 0 kern table with one format 0 subtable of `pairs`
+fn with_kern(font: &[u8], pairs: &[(u16, u16, i16)]) -> Vec<u8> {
+    let words = [0, 1, 0, 14 + 6 * pairs.len() as u16, 1, pairs.len() as u16, 6, 0, 0];
+    let mut kern: Vec<u8> = words.iter().flat_map(|word| word.to_be_bytes()).collect();
+    for (left, right, value) in pairs {
+        kern.extend_from_slice(&left.to_be_bytes());
+        kern.extend_from_slice(&right.to_be_bytes());
+        kern.extend_from_slice(&value.to_be_bytes());
+    }
+    let mut builder = FontBuilder::new();
+    builder.add_raw(Tag::new(b"kern"), kern);
+    builder.copy_missing_tables(FontRef::new(font).unwrap());
+    builder.build()
+}
+
+/// Format 0 kern pairs are remapped into the merged glyph order, and a font
+/// that also has GPOS pair positioning keeps only its GPOS kerning.
+#[test]
+fn test_merge_kern_tables() {
+    use font_types::GlyphId16;
+    use write_fonts::tables::{
+        gpos::{
+            PairPos, PairPosFormat1, PairSet, PairValueRecord, PositionLookup, PositionLookupList,
+            ValueRecord,
+        },
+        layout::{CoverageTable, FeatureList, Lookup, LookupFlag, ScriptList},
+    };
+
+    let latin = with_kern(
+        &make_test_font(&[".notdef", "A", "V"], &[(0x41, "A"), (0x56, "V")], Some(4)),
+        &[(1, 2, -80)],
+    );
+    let greek = with_kern(
+        &make_test_font(&[".notdef", "Alpha", "Tau"], &[(0x391, "Alpha"), (0x3A4, "Tau")], Some(4)),
+        &[(2, 1, -50)],
+    );
+    let pair_pos = PairPos::Format1(PairPosFormat1::new(
+        CoverageTable::format_1(vec![GlyphId16::new(1)]),
+        vec![PairSet::new(vec![PairValueRecord::new(
+            GlyphId16::new(2),
+            ValueRecord::new().with_x_advance(-30),
+            ValueRecord::new(),
+        )])],
+    ));
+    let gpos = Gpos::new(
+        ScriptList::default(),
+        FeatureList::default(),
+        PositionLookupList::new(vec![PositionLookup::Pair(Lookup::new(
+            LookupFlag::default(),
+            vec![pair_pos],
+        ))]),
+    );
+    let cyrillic = with_kern(
+        &make_test_font_with_gpos(&[".notdef", "Ge", "Ze"], &[(0x413, "Ge"), (0x417, "Ze")], &gpos),
+        &[(1, 2, -99)],
+    );
+
+    let merged = Merger::default()
+        .merge(&[&latin, &greek, &cyrillic])
+        .expect("merge failed");
+    let font = FontRef::new(&merged).expect("parse merged font");
+
+    let kern = font.kern().expect("merged font has kern");
+    let subtables: Vec<_> = kern.subtables().map(|subtable| subtable.unwrap()).collect();
+    assert_eq!(subtables.len(), 1);
+    assert!(subtables[0].is_horizontal());
+    let Ok(tables::kern::SubtableKind::Format0(format0)) = subtables[0].kind() else {
+        panic!("merged kern should be format 0");
+    };
+    let pairs: Vec<(u16, u16, i16)> = format0
+        .pairs()
+        .iter()
+        .map(|pair| (pair.left().to_u16(), pair.right().to_u16(), pair.value()))
+        .collect();
+    // Alpha and Tau land at gids 4 and 5; Ge and Ze are kerned only by GPOS
+    assert_eq!(pairs, [(1, 2, -80), (5, 4, -50)]);
+    assert_eq!(format0.kerning(GlyphId::new(5), GlyphId::new(4)), Some(-50));
+}
+
+/// `KernPreference` picks between a font's legacy kern pairs and its GPOS
+/// `kern` feature; the font's other features are untouched.
+#[test]
+fn test_kern_preference() {
+    use font_types::GlyphId16;
+    use write_fonts::tables::{
+        gpos::{
+            PairPos, PairPosFormat1, PairSet, PairValueRecord, PositionLookup, PositionLookupList,
+            SinglePos, SinglePosFormat1, ValueRecord,
+        },
+        layout::{
+            CoverageTable, Feature, FeatureList, FeatureRecord, LangSys, Lookup, LookupFlag,
+            Script, ScriptList, ScriptRecord,
+        },
+    };
+
+    let coverage = || CoverageTable::format_1(vec![GlyphId16::new(1)]);
+    let pair_pos = PairPos::Format1(PairPosFormat1::new(
+        coverage(),
+        vec![PairSet::new(vec![PairValueRecord::new(
+            GlyphId16::new(2),
+            ValueRecord::new().with_x_advance(-30),
+            ValueRecord::new(),
+        )])],
+    ));
+    let single_pos = SinglePos::Format1(SinglePosFormat1::new(
+        coverage(),
+        ValueRecord::new().with_x_advance(10),
+    ));
+    let gpos = Gpos::new(
+        ScriptList::new(vec![ScriptRecord::new(
+            Tag::new(b"DFLT"),
+            Script::new(Some(LangSys::new(vec![0, 1])), vec![]),
+        )]),
+        FeatureList::new(vec![
+            FeatureRecord::new(Tag::new(b"kern"), Feature::new(None, vec![0])),
+            FeatureRecord::new(Tag::new(b"dist"), Feature::new(None, vec![1])),
+        ]),
+        PositionLookupList::new(vec![
+            PositionLookup::Pair(Lookup::new(LookupFlag::empty(), vec![pair_pos])),
+            PositionLookup::Single(Lookup::new(LookupFlag::empty(), vec![single_pos])),
+        ]),
+    );
+    let latin = with_kern(
+        &make_test_font_with_gpos(&[".notdef", "A", "V"], &[(0x41, "A"), (0x56, "V")], &gpos),
+        &[(1, 2, -80)],
+    );
+    let other = make_test_font(&[".notdef", "B"], &[(0x42, "B")], Some(4));
+
+    let merge = |preference: KernPreference| {
+        let merged = Merger::new(Options::default().kern_preference(preference))
+            .merge(&[&latin, &other])
+            .expect("merge failed");
+        let font = FontRef::new(&merged).unwrap();
+        let has_kern = font.kern().is_ok();
+        let gpos = font.gpos().unwrap();
+        let features: Vec<Tag> = gpos
+            .feature_list()
+            .unwrap()
+            .feature_records()
+            .iter()
+            .map(|record| record.feature_tag())
+            .collect();
+        (has_kern, features, gpos.lookup_list().unwrap().lookup_count())
+    };
+    let (kern, dist) = (Tag::new(b"kern"), Tag::new(b"dist"));
+
+    assert_eq!(merge(KernPreference::Gpos), (false, vec![kern, dist], 2));
+    assert_eq!(merge(KernPreference::Legacy), (true, vec![dist], 1));
+    assert_eq!(merge(KernPreference::Both), (true, vec![kern, dist], 2));
+
+    let merged = Merger::new(Options::default().kern_preference(KernPreference::Legacy))
+        .merge(&[&latin, &other])
+        .unwrap();
+    let font = FontRef::new(&merged).unwrap();
+    let lookup = font.gpos().unwrap().lookup_list().unwrap().lookups().get(0).unwrap();
+    assert!(matches!(lookup, tables::gpos::PositionLookup::Single(_)));
+    let kern_table = font.kern().unwrap();
+    let Ok(tables::kern::SubtableKind::Format0(format0)) =
+        kern_table.subtables().next().unwrap().unwrap().kind()
+    else {
+        panic!("merged kern should be format 0");
+    };
+    assert_eq!(format0.kerning(GlyphId::new(1), GlyphId::new(2)), Some(-80));
+}
+
+/// Variation sequences from every font survive the merge, pointing at the
+/// merged glyph IDs.
+#[test]
+fn test_merge_cmap_variation_sequences() {
+    use font_types::Uint24;
+    use read_fonts::tables::cmap::MapVariant;
+    use write_fonts::tables::cmap::{
+        Cmap14, CmapSubtable, DefaultUvs, EncodingRecord, NonDefaultUvs, PlatformId, UnicodeRange,
+        UvsMapping, VariationSelector,
+    };
+
+    /// Add a format 14 subtable with one default and one non-default
+    /// sequence, each on its own selector
+    fn with_uvs(font: &[u8], codepoint: u32, default: u32, variant: (u32, u16)) -> Vec<u8> {
+        let cp = Uint24::new(codepoint);
+        let mut selectors = vec![
+            VariationSelector::new(
+                Uint24::new(default),
+                Some(DefaultUvs::new(1, vec![UnicodeRange::new(cp, 0)])),
+                None,
+            ),
+            VariationSelector::new(
+                Uint24::new(variant.0),
+                None,
+                Some(NonDefaultUvs::new(1, vec![UvsMapping::new(cp, variant.1)])),
+            ),
+        ];
+        selectors.sort_by_key(|selector| selector.var_selector.to_u32());
+        let cmap14 = Cmap14::new(10 + 11 * 2 + 8 + 9, 2, selectors);
+
+        let font_ref = FontRef::new(font).unwrap();
+        let mut cmap: Cmap = font_ref.cmap().unwrap().to_owned_table();
+        let unicode_records = cmap
+            .encoding_records
+            .iter()
+            .take_while(|record| record.platform_id == PlatformId::Unicode)
+            .count();
+        cmap.encoding_records.insert(
+            unicode_records,
+            EncodingRecord::new(PlatformId::Unicode, 5, CmapSubtable::Format14(cmap14)),
+        );
+        let mut builder = FontBuilder::new();
+        builder.add_table(&cmap).unwrap();
+        builder.copy_missing_tables(font_ref);
+        builder.build()
+    }
+
+    let kuzu = with_uvs(
+        &make_test_font(&[".notdef", "uni845B", "uni845B.jp"], &[(0x845B, "uni845B")], Some(4)),
+        0x845B,
+        0xE0100,
+        (0xE0101, 2),
+    );
+    let tsuji = with_uvs(
+        &make_test_font(&[".notdef", "uni8FBB", "uni8FBB.alt"], &[(0x8FBB, "uni8FBB")], Some(4)),
+        0x8FBB,
+        0xE0101,
+        (0xE0100, 2),
+    );
+
+    let merged = Merger::default().merge(&[&kuzu, &tsuji]).expect("merge failed");
+    let font = FontRef::new(&merged).expect("parse merged font");
+    let cmap = font.cmap().unwrap();
+    let cmap14 = cmap
+        .encoding_records()
+        .iter()
+        .find_map(|record| match record.subtable(cmap.offset_data()) {
+            Ok(tables::cmap::CmapSubtable::Format14(cmap14)) => Some(cmap14),
+            _ => None,
+        })
+        .expect("merged cmap has a format 14 subtable");
+
+    // The second font's glyphs follow the first font's three
+    let variant = |gid: u32| Some(MapVariant::Variant(GlyphId::new(gid)));
+    assert_eq!(cmap14.map_variant(0x845Bu32, 0xE0100u32), Some(MapVariant::UseDefault));
+    assert_eq!(cmap14.map_variant(0x845Bu32, 0xE0101u32), variant(2));
+    assert_eq!(cmap14.map_variant(0x8FBBu32, 0xE0100u32), variant(5));
+    assert_eq!(cmap14.map_variant(0x8FBBu32, 0xE0101u32), Some(MapVariant::UseDefault));
+    assert_eq!(cmap.map_codepoint(0x8FBBu32), Some(GlyphId::new(4)));
+}
+
+#[test]
+fn test_rebuild_names_for_family() {
+    use font_types::NameId;
+    use write_fonts::tables::name::{Name, NameRecord};
+
+    let base = make_test_font(&[".notdef", "A"], &[(0x41, "A")], Some(4));
+    let base_ref = FontRef::new(&base).unwrap();
+    let mut os2: Os2 = base_ref.os2().unwrap().to_owned_table();
+    os2.us_weight_class = 700;
+    os2.fs_selection = SelectionFlags::BOLD;
+    let name = Name::new(
+        [(1, "Recursive"), (2, "Bold"), (4, "Recursive Bold"), (6, "Recursive-Bold")]
+            .into_iter()
+            .map(|(id, string)| {
+                NameRecord::new(3, 1, 0x409, NameId::new(id), string.to_string().into())
+            })
+            .collect(),
+    );
+    let mut builder = FontBuilder::new();
+    builder.add_table(&os2).unwrap();
+    builder.add_table(&name).unwrap();
+    builder.copy_missing_tables(base_ref);
+    let recursive = builder.build();
+    let noto = make_test_font(&[".notdef", "uni3042"], &[(0x3042, "uni3042")], Some(4));
+
+    let names = |merged: &[u8]| -> Vec<(u16, String)> {
+        let font = FontRef::new(merged).unwrap();
+        let name = font.name().unwrap();
+        name.name_record()
+            .iter()
+            .map(|record| {
+                let string = record.string(name.string_data()).unwrap();
+                (record.name_id().to_u16(), string.chars().collect())
+            })
+            .collect()
+    };
+
+    let options = Options::new().family_name("Warpnine Sans").rebuild_names(true);
+    let merged = Merger::new(options)
+        .merge(&[&recursive, &noto])
+        .expect("merge failed");
+    let expected = [
+        (1, "Warpnine Sans"),
+        (2, "Bold"),
+        (4, "Warpnine Sans Bold"),
+        (6, "WarpnineSans-Bold"),
+        (16, "Warpnine Sans"),
+        (17, "Bold"),
+    ]
+    .map(|(id, string)| (id, string.to_string()));
+    assert_eq!(names(&merged), expected);
+
+    // Without rebuild_names the first font's table is kept as is
+    let options = Options::new().family_name("Warpnine Sans");
+    let merged = Merger::new(options)
+        .merge(&[&recursive, &noto])
+        .expect("merge failed");
+    assert_eq!(names(&merged)[0], (1, "Recursive".to_string()));
+    assert_eq!(names(&merged).len(), 4);
+}
+
+#[test]
+fn test_merge_localized_names() {
+    use font_types::NameId;
+    use write_fonts::{
+        OffsetMarker,
+        tables::name::{LangTagRecord, Name, NameRecord},
+    };
+
+    let with_names = |glyph: &str, codepoint: u32, names: &[(u16, u16, &str)], tags: &[&str]| {
+        let font = make_test_font(&[".notdef", glyph], &[(codepoint, glyph)], Some(4));
+        let font_ref = FontRef::new(&font).unwrap();
+        let mut name = Name::new(
+            names
+                .iter()
+                .map(|&(language, id, string)| {
+                    NameRecord::new(3, 1, language, NameId::new(id), string.to_string().into())
+                })
+                .collect(),
+        );
+        if !tags.is_empty() {
+            name.lang_tag_record = Some(
+                tags.iter()
+                    .map(|tag| LangTagRecord::new(OffsetMarker::new(tag.to_string())))
+                    .collect(),
+            );
+        }
+        let mut builder = FontBuilder::new();
+        builder.add_table(&name).unwrap();
+        builder.copy_missing_tables(font_ref);
+        builder.build()
+    };
+    let latin_names = [(0x409, 1, "Warpnine Sans"), (0x411, 1, "ワープナイン サンズ")];
+    let japanese_names = [
+        (0x409, 1, "Noto Sans JP"),
+        (0x411, 1, "Noto Sans JP 日本語"),
+        (0x411, 4, "Noto Sans JP 日本語 Regular"),
+        (0x412, 1, "노토 산스 JP"),
+        (0x8000, 1, "ノト サンズ"),
+    ];
+    let latin = with_names("A", 0x41, &latin_names, &[]);
+    let japanese = with_names("uni3042", 0x3042, &japanese_names, &["ja-Hira"]);
+
+    let names = |merged: &[u8]| -> Vec<(u16, u16, String)> {
+        let font = FontRef::new(merged).unwrap();
+        let name = font.name().unwrap();
+        name.name_record()
+            .iter()
+            .map(|record| {
+                let string = record.string(name.string_data()).unwrap();
+                (record.language_id(), record.name_id().to_u16(), string.chars().collect())
+            })
+            .collect()
+    };
+    let lang_tags = |merged: &[u8]| -> Vec<String> {
+        let font = FontRef::new(merged).unwrap();
+        let name = font.name().unwrap();
+        name.lang_tag_record()
+            .unwrap_or_default()
+            .iter()
+            .map(|record| record.lang_tag(name.string_data()).unwrap().chars().collect())
+            .collect()
+    };
+    let expected = |records: &[(u16, u16, &str)]| {
+        records
+            .iter()
+            .map(|&(language, id, string)| (language, id, string.to_string()))
+            .collect::<Vec<_>>()
+    };
+
+    // Without a name source the first font's table is copied
+    let merged = Merger::new(Options::new())
+        .merge(&[&latin, &japanese])
+        .expect("merge failed");
+    assert_eq!(names(&merged), expected(&latin_names));
+
+    // Japanese comes whole from the first font, so Noto's ID 4 is not mixed
+    // in; languages only the second font has are taken from it
+    let merged = Merger::new(Options::new().name_source(NameSource::First))
+        .merge(&[&latin, &japanese])
+        .expect("merge failed");
+    assert_eq!(
+        names(&merged),
+        expected(&[
+            (0x409, 1, "Warpnine Sans"),
+            (0x411, 1, "ワープナイン サンズ"),
+            (0x412, 1, "노토 산스 JP"),
+            (0x8000, 1, "ノト サンズ"),
+        ])
+    );
+    assert_eq!(lang_tags(&merged), ["ja-Hira"]);
+
+    let merged = Merger::new(Options::new().name_source(NameSource::Last))
+        .merge(&[&latin, &japanese])
+        .expect("merge failed");
+    assert_eq!(names(&merged), expected(&japanese_names));
+    assert_eq!(lang_tags(&merged), ["ja-Hira"]);
+}
+
+/// Each font's exclusions leave only its own glyphs unmapped, so a shared
+/// codepoint goes to the font that keeps it
+#[test]
+fn test_builder_per_font_exclusions() {
+    let first = make_test_font(
+        &[".notdef", "A", "B", "C"],
+        &[(0x41, "A"), (0x42, "B"), (0x43, "C")],
+        Some(4),
+    );
+    let second = make_test_font(&[".notdef", "A", "B"], &[(0x41, "A"), (0x42, "B")], Some(4));
+
+    // Merged order: .notdef A B C .notdef.1 A.1 B.1
+    let merged = Merger::builder()
+        .add_font(&first)
+        .with_font_options(FontOptions::new().exclude_codepoints([0x41, 0x43]))
+        .add_font(&second)
+        .with_font_options(FontOptions::new().exclude_range(0x42, 0x42))
+        .merge()
+        .expect("merge failed");
+
+    let font = FontRef::new(&merged).expect("parse merged font");
+    assert_eq!(font.maxp().unwrap().num_glyphs(), 7);
+    let cmap = font.cmap().unwrap();
+    let mapped = |cp: u32| cmap.map_codepoint(cp).map(|gid| gid.to_u32());
+    assert_eq!(mapped(0x41), Some(5));
+    assert_eq!(mapped(0x42), Some(2));
+    assert_eq!(mapped(0x43), None);
+}
+
+/// Font options set on the shared options still apply to fonts the builder
+/// gives none
+#[test]
+fn test_builder_keeps_shared_font_options() {
+    let first = make_test_font(&[".notdef", "A"], &[(0x41, "A")], Some(4));
+    let second = make_test_font(&[".notdef", "A", "B"], &[(0x41, "A"), (0x42, "B")], Some(4));
+
+    // Merged order: .notdef A .notdef.1 A.1 B
+    let merged = Merger::builder()
+        .options(
+            Options::default()
+                .font_options(0usize, FontOptions::new().exclude_codepoints([0x41]))
+                .font_options(1usize, FontOptions::new().exclude_codepoints([0x42])),
+        )
+        .add_font(&first)
+        .add_font(&second)
+        .with_font_options(FontOptions::default())
+        .merge()
+        .expect("merge failed");
+
+    let font = FontRef::new(&merged).expect("parse merged font");
+    let cmap = font.cmap().unwrap();
+    let mapped = |cp: u32| cmap.map_codepoint(cp).map(|gid| gid.to_u32());
+    assert_eq!(mapped(0x41), Some(3));
+    assert_eq!(mapped(0x42), Some(4));
+}
+
+/// A font's priority beats the conflict strategy, and feature renames apply
+/// to the font they are set on
+#[test]
+fn test_builder_priority_and_feature_renames() {
+    let base = make_test_font(&[".notdef", "A"], &[(0x41, "A")], Some(4));
+    let fallback = make_test_font(&[".notdef", "A"], &[(0x41, "A")], Some(4));
+
+    let merged = Merger::builder()
+        .add_font(&base)
+        .add_font(&fallback)
+        .with_font_options(FontOptions::new().priority(1))
+        .merge()
+        .expect("merge failed");
+    let font = FontRef::new(&merged).expect("parse merged font");
+    assert_eq!(font.cmap().unwrap().map_codepoint(0x41u32).map(|gid| gid.to_u32()), Some(3));
+
+    use write_fonts::tables::{
+        gpos::{PositionLookup, PositionLookupList, SinglePos, SinglePosFormat1, ValueRecord},
+        layout::{
+            CoverageTable, Feature, FeatureList, FeatureRecord, LangSys, Lookup, LookupFlag,
+            Script, ScriptList, ScriptRecord,
+        },
+    };
+    let single = SinglePos::Format1(SinglePosFormat1::new(
+        CoverageTable::format_1(vec![font_types::GlyphId16::new(1)]),
+        ValueRecord::new().with_x_advance(-50),
+    ));
+    let gpos = Gpos::new(
+        ScriptList::new(vec![ScriptRecord::new(
+            Tag::new(b"DFLT"),
+            Script::new(Some(LangSys::new(vec![0])), vec![]),
+        )]),
+        FeatureList::new(vec![FeatureRecord::new(Tag::new(b"kern"), Feature::new(None, vec![0]))]),
+        PositionLookupList::new(vec![PositionLookup::Single(Lookup::new(
+            LookupFlag::default(),
+            vec![single],
+        ))]),
+    );
+    let first = make_test_font_with_gpos(&[".notdef", "A"], &[(0x41, "A")], &gpos);
+    let second = make_test_font_with_gpos(&[".notdef", "B"], &[(0x42, "B")], &gpos);
+
+    let merged = Merger::builder()
+        .add_font(&first)
+        .add_font(&second)
+        .with_font_options(FontOptions::new().rename_feature(Tag::new(b"kern"), Tag::new(b"dist")))
+        .merge()
+        .expect("merge failed");
+    let font = FontRef::new(&merged).expect("parse merged font");
+    let features = font.gpos().unwrap().feature_list().unwrap();
+    let mut tags: Vec<Tag> = features
+        .feature_records()
+        .iter()
+        .map(|record| record.feature_tag())
+        .collect();
+    tags.sort();
+    assert_eq!(tags, [Tag::new(b"dist"), Tag::new(b"kern")]);
+}
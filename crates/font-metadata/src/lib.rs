@@ -1,20 +1,32 @@
 //! Font metadata manipulation (monospace settings, versioning).
 
-use std::string::ToString;
+use std::{collections::BTreeMap, string::ToString};
 
-use anyhow::{Result, anyhow};
-use chrono::{Datelike, Local, NaiveDate};
+use anyhow::{Result, anyhow, ensure};
+use chrono::{Datelike, Local, NaiveDate, Utc};
 use read_fonts::TableProvider;
 use warpnine_font_ops::{map_name_records, rewrite_font};
 use write_fonts::{
     from_obj::ToOwnedTable,
-    tables::{head::Head, os2::Os2, post::Post},
-    types::Fixed,
+    tables::{
+        head::{Head, MacStyle},
+        name::{Name, NameRecord},
+        os2::{Os2, SelectionFlags},
+        post::Post,
+    },
+    types::{Fixed, LongDateTime, NameId, Tag},
 };
 
 /// Name table IDs.
+const NAME_ID_COPYRIGHT: u16 = 0;
+const NAME_ID_FAMILY: u16 = 1;
+const NAME_ID_SUBFAMILY: u16 = 2;
 const NAME_ID_UNIQUE_ID: u16 = 3;
 const NAME_ID_VERSION: u16 = 5;
+const NAME_ID_LICENSE: u16 = 13;
+const NAME_ID_LICENSE_URL: u16 = 14;
+const NAME_ID_TYPO_FAMILY: u16 = 16;
+const NAME_ID_TYPO_SUBFAMILY: u16 = 17;
 
 /// Monospace metadata settings.
 #[derive(Debug, Clone, Copy, Default)]
@@ -55,6 +67,70 @@ impl MonospaceSettings {
     }
 }
 
+/// Style bits shared by `OS/2.fsSelection` and `head.macStyle`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StyleFlags {
+    pub italic: bool,
+    pub bold: bool,
+    /// Only honored when neither `italic` nor `bold` is set.
+    pub regular: bool,
+}
+
+impl StyleFlags {
+    /// Set the style bits of `OS/2.fsSelection` (ITALIC, BOLD, REGULAR) and
+    /// `head.macStyle` (bold, italic) to match, leaving other bits alone.
+    ///
+    /// REGULAR is cleared whenever bold or italic is set.
+    pub fn apply(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let flags = *self;
+
+        rewrite_font(data, |font, builder| {
+            if let Ok(os2) = font.os2() {
+                let mut new_os2: Os2 = os2.to_owned_table();
+                new_os2.fs_selection = flags.fs_selection(new_os2.fs_selection);
+                builder.add_table(&new_os2)?;
+            }
+
+            if let Ok(head) = font.head() {
+                let mut new_head: Head = head.to_owned_table();
+                new_head.mac_style = flags.mac_style(new_head.mac_style);
+                builder.add_table(&new_head)?;
+            }
+
+            Ok(())
+        })
+    }
+
+    /// `selection` with its style bits replaced by these flags.
+    pub fn fs_selection(&self, selection: SelectionFlags) -> SelectionFlags {
+        let mut selection = selection;
+        selection.remove(SelectionFlags::ITALIC | SelectionFlags::BOLD | SelectionFlags::REGULAR);
+        if self.italic {
+            selection |= SelectionFlags::ITALIC;
+        }
+        if self.bold {
+            selection |= SelectionFlags::BOLD;
+        }
+        if self.regular && !self.italic && !self.bold {
+            selection |= SelectionFlags::REGULAR;
+        }
+        selection
+    }
+
+    /// `style` with its bold and italic bits replaced by these flags.
+    pub fn mac_style(&self, style: MacStyle) -> MacStyle {
+        let mut style = style;
+        style.remove(MacStyle::BOLD | MacStyle::ITALIC);
+        if self.bold {
+            style |= MacStyle::BOLD;
+        }
+        if self.italic {
+            style |= MacStyle::ITALIC;
+        }
+        style
+    }
+}
+
 /// Font version information.
 #[derive(Debug, Clone)]
 pub struct FontVersion {
@@ -117,6 +193,9 @@ impl FontVersion {
     /// Apply this version to font data.
     ///
     /// Updates `head.font_revision` and name IDs 3 (unique ID) and 5 (version).
+    /// In the unique ID only the version field is replaced: the first field
+    /// holding a version tag or number (as in [`build_unique_id`] output),
+    /// or else the last.
     pub fn apply(&self, data: &[u8]) -> Result<Vec<u8>> {
         let version_string = self.version_string();
         let revision_value = self.revision();
@@ -133,17 +212,7 @@ impl FontVersion {
                 if name_id == NAME_ID_VERSION {
                     Some(version_string.clone())
                 } else if name_id == NAME_ID_UNIQUE_ID {
-                    let parts: Vec<&str> =
-                        current.split(';').map(str::trim).filter(|s| !s.is_empty()).collect();
-                    let new_parts = if !parts.is_empty() {
-                        let mut new_parts: Vec<String> =
-                            parts[..parts.len() - 1].iter().map(ToString::to_string).collect();
-                        new_parts.push(version_tag.clone());
-                        new_parts
-                    } else {
-                        vec![version_tag.clone()]
-                    };
-                    Some(new_parts.join("; "))
+                    Some(UniqueIdFields::parse(current).with_version(&version_tag))
                 } else {
                     None
                 }
@@ -155,6 +224,341 @@ impl FontVersion {
     }
 }
 
+/// Name ID 3 (unique ID) split into its `;`-separated fields, each kept
+/// with its original spacing.
+struct UniqueIdFields<'a>(Vec<&'a str>);
+
+impl<'a> UniqueIdFields<'a> {
+    fn parse(unique_id: &'a str) -> Self {
+        let unique_id = unique_id.trim_end();
+        Self(if unique_id.trim().is_empty() { Vec::new() } else { unique_id.split(';').collect() })
+    }
+
+    /// The first field holding a version tag or number, or else the last.
+    fn version_index(&self) -> Option<usize> {
+        self.0
+            .iter()
+            .position(|field| is_version_field(field.trim()))
+            .or(self.0.len().checked_sub(1))
+    }
+
+    /// The unique ID with its version field replaced by `tag`.
+    fn with_version(&self, tag: &str) -> String {
+        let Some(index) = self.version_index() else {
+            return tag.to_string();
+        };
+        let field = self.0[index];
+        let spacing = &field[..field.len() - field.trim_start().len()];
+        let mut fields: Vec<String> = self.0.iter().map(ToString::to_string).collect();
+        fields[index] = format!("{spacing}{tag}");
+        fields.join(";")
+    }
+
+    /// The unique ID with `; {suffix}` appended, or `suffix` alone if empty.
+    fn with_suffix(&self, suffix: &str) -> String {
+        if self.0.is_empty() {
+            suffix.to_string()
+        } else {
+            format!("{}; {suffix}", self.0.join(";"))
+        }
+    }
+}
+
+/// Whether a unique ID field is a version: a version tag (`2024-01-15`,
+/// `2024-01-15.1`) or a `major.minor` number, optionally after `Version `.
+fn is_version_field(field: &str) -> bool {
+    let field = field.strip_prefix("Version ").unwrap_or(field);
+    let is_number = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+    FontVersion::parse(Some(field)).is_ok()
+        || field
+            .split_once('.')
+            .is_some_and(|(major, minor)| is_number(major) && is_number(minor))
+}
+
+/// Append a suffix (e.g., a build hash) to name ID 3 (unique ID).
+///
+/// The suffix becomes a new `; `-separated field after the existing ones,
+/// so [`FontVersion::apply`] still finds and replaces the version field.
+pub fn set_unique_id_suffix(data: &[u8], suffix: &str) -> Result<Vec<u8>> {
+    rewrite_font(data, |font, builder| {
+        let new_name = map_name_records(font, |name_id, current| {
+            (name_id == NAME_ID_UNIQUE_ID)
+                .then(|| UniqueIdFields::parse(current).with_suffix(suffix))
+        })?;
+        builder.add_table(&new_name)?;
+        Ok(())
+    })
+}
+
+/// Build a unique ID (name ID 3) in the recommended
+/// `{version};{vendor};{family}-{style}` form.
+///
+/// The version field is the version tag, and the last field is the
+/// PostScript name: `family` and `style` with spaces removed.
+pub fn build_unique_id(vendor: &str, family: &str, style: &str, version: &FontVersion) -> String {
+    format!("{};{vendor};{}-{}", version.tag, family.replace(' ', ""), style.replace(' ', ""))
+}
+
+/// Replace every name ID 3 (unique ID) record with `unique_id`.
+///
+/// A font without one gets a Windows English record. See
+/// [`build_unique_id`] for the recommended form.
+pub fn set_unique_id(data: &[u8], unique_id: &str) -> Result<Vec<u8>> {
+    rewrite_font(data, |font, builder| {
+        let mut new_name = map_name_records(font, |name_id, _| {
+            (name_id == NAME_ID_UNIQUE_ID).then(|| unique_id.to_string())
+        })?;
+        if !new_name
+            .name_record
+            .iter()
+            .any(|record| record.name_id.to_u16() == NAME_ID_UNIQUE_ID)
+        {
+            new_name.name_record.push(NameRecord::new(
+                3,
+                1,
+                0x409,
+                NameId::new(NAME_ID_UNIQUE_ID),
+                unique_id.to_string().into(),
+            ));
+            new_name.name_record.sort();
+        }
+        builder.add_table(&new_name)?;
+        Ok(())
+    })
+}
+
+/// Replace the legal name records: ID 0 (copyright), 13 (license
+/// description) and 14 (license URL).
+///
+/// Each given string replaces every record of its ID, across all platforms
+/// and encodings; `None` keeps the existing records. A given string whose
+/// ID the font does not have is added as a Windows English record.
+pub fn set_legal_names(
+    data: &[u8],
+    copyright: Option<&str>,
+    license: Option<&str>,
+    license_url: Option<&str>,
+) -> Result<Vec<u8>> {
+    let legal_names = [
+        (NAME_ID_COPYRIGHT, copyright),
+        (NAME_ID_LICENSE, license),
+        (NAME_ID_LICENSE_URL, license_url),
+    ];
+    rewrite_font(data, |font, builder| {
+        let mut new_name = map_name_records(font, |name_id, _| {
+            legal_names
+                .iter()
+                .find(|(id, _)| *id == name_id)
+                .and_then(|(_, value)| value.map(ToString::to_string))
+        })?;
+        for (id, value) in legal_names {
+            let Some(value) = value else { continue };
+            if !new_name
+                .name_record
+                .iter()
+                .any(|record| record.name_id.to_u16() == id)
+            {
+                new_name.name_record.push(NameRecord::new(
+                    3,
+                    1,
+                    0x409,
+                    NameId::new(id),
+                    value.to_string().into(),
+                ));
+            }
+        }
+        new_name.name_record.sort();
+        builder.add_table(&new_name)?;
+        Ok(())
+    })
+}
+
+/// Write localized family and style names, keyed by Windows language ID
+/// (e.g. `0x411` for Japanese).
+///
+/// For each `(family, style)`, name IDs 1 and 16 get `family` and IDs 2 and
+/// 17 get `style`, as Windows Unicode BMP (3, 1) records in that language.
+/// Existing records for those IDs and languages are replaced; every other
+/// record, including the default English names, is kept.
+pub fn apply_localized_names(
+    data: &[u8],
+    names: &BTreeMap<u16, (String, String)>,
+) -> Result<Vec<u8>> {
+    rewrite_font(data, |font, builder| {
+        let mut new_name: Name = font.name()?.to_owned_table();
+        new_name.name_record.retain(|record| {
+            !(record.platform_id == 3
+                && record.encoding_id == 1
+                && names.contains_key(&record.language_id)
+                && matches!(
+                    record.name_id.to_u16(),
+                    NAME_ID_FAMILY
+                        | NAME_ID_SUBFAMILY
+                        | NAME_ID_TYPO_FAMILY
+                        | NAME_ID_TYPO_SUBFAMILY
+                ))
+        });
+        for (&language, (family, style)) in names {
+            for (name_id, string) in [
+                (NAME_ID_FAMILY, family),
+                (NAME_ID_SUBFAMILY, style),
+                (NAME_ID_TYPO_FAMILY, family),
+                (NAME_ID_TYPO_SUBFAMILY, style),
+            ] {
+                new_name.name_record.push(NameRecord::new(
+                    3,
+                    1,
+                    language,
+                    NameId::new(name_id),
+                    string.clone().into(),
+                ));
+            }
+        }
+        new_name.name_record.sort();
+        builder.add_table(&new_name)?;
+        Ok(())
+    })
+}
+
+/// Timestamp written to `head.created`/`head.modified` by [`make_reproducible`]
+/// (the 1904-01-01 `LONGDATETIME` epoch).
+pub const REPRODUCIBLE_TIMESTAMP: LongDateTime = LongDateTime::new(0);
+
+/// Normalize per-build variation so identical inputs give identical bytes.
+///
+/// Pins the `head` timestamps to [`REPRODUCIBLE_TIMESTAMP`] and zeroes
+/// `checksumAdjustment` before the font is rebuilt; the rebuild recomputes the
+/// checksum and re-pads every table with zeros in a fixed table order.
+pub fn make_reproducible(data: &[u8]) -> Result<Vec<u8>> {
+    rewrite_font(data, |font, builder| {
+        let mut head: Head = font.head()?.to_owned_table();
+        head.created = REPRODUCIBLE_TIMESTAMP;
+        head.modified = REPRODUCIBLE_TIMESTAMP;
+        head.checksum_adjustment = 0;
+        builder.add_table(&head)?;
+        Ok(())
+    })
+}
+
+/// Set `OS/2.usWeightClass`, e.g. to round an instanced 587 to 600.
+///
+/// Accepts the full 1–1000 range.
+pub fn set_weight_class(data: &[u8], weight: u16) -> Result<Vec<u8>> {
+    ensure!((1..=1000).contains(&weight), "Invalid weight class {weight}. Expected 1-1000.");
+    rewrite_font(data, |font, builder| {
+        let mut os2: Os2 = font.os2()?.to_owned_table();
+        os2.us_weight_class = weight;
+        builder.add_table(&os2)?;
+        Ok(())
+    })
+}
+
+/// Set `OS/2.usWidthClass`, from 1 (ultra-condensed) to 9 (ultra-expanded).
+pub fn set_width_class(data: &[u8], width: u16) -> Result<Vec<u8>> {
+    ensure!((1..=9).contains(&width), "Invalid width class {width}. Expected 1-9.");
+    rewrite_font(data, |font, builder| {
+        let mut os2: Os2 = font.os2()?.to_owned_table();
+        os2.us_width_class = width;
+        builder.add_table(&os2)?;
+        Ok(())
+    })
+}
+
+/// Set `OS/2.achVendID` to `vendor`, right-padded with spaces to 4 bytes.
+///
+/// `vendor` must be 1–4 ASCII characters.
+pub fn set_vendor_id(data: &[u8], vendor: &str) -> Result<Vec<u8>> {
+    ensure!(
+        !vendor.is_empty() && vendor.len() <= 4 && vendor.is_ascii(),
+        "Invalid vendor ID '{vendor}'. Expected 1-4 ASCII characters."
+    );
+    let mut bytes = [b' '; 4];
+    bytes[..vendor.len()].copy_from_slice(vendor.as_bytes());
+    rewrite_font(data, |font, builder| {
+        let mut os2: Os2 = font.os2()?.to_owned_table();
+        os2.ach_vend_id = Tag::new(&bytes);
+        builder.add_table(&os2)?;
+        Ok(())
+    })
+}
+
+/// Set `head.created` and `head.modified` to midnight UTC of the given dates.
+///
+/// `created` is kept when `None`; `modified` defaults to today in UTC.
+pub fn set_timestamps(
+    data: &[u8],
+    created: Option<NaiveDate>,
+    modified: Option<NaiveDate>,
+) -> Result<Vec<u8>> {
+    let created = created.map(long_date_time);
+    let modified = long_date_time(modified.unwrap_or_else(|| Utc::now().date_naive()));
+    rewrite_font(data, |font, builder| {
+        let mut head: Head = font.head()?.to_owned_table();
+        if let Some(created) = created {
+            head.created = created;
+        }
+        head.modified = modified;
+        builder.add_table(&head)?;
+        Ok(())
+    })
+}
+
+/// Seconds from the `LONGDATETIME` epoch (1904-01-01) to midnight of `date`.
+fn long_date_time(date: NaiveDate) -> LongDateTime {
+    let epoch = NaiveDate::from_ymd_opt(1904, 1, 1).expect("valid epoch");
+    LongDateTime::new((date - epoch).num_days() * 86_400)
+}
+
+/// Byte offset of `checksumAdjustment` within `head`.
+const CHECKSUM_ADJUSTMENT_OFFSET: usize = 8;
+
+/// Target of the whole-file checksum, per the OpenType `head` spec.
+const CHECKSUM_MAGIC: u32 = 0xB1B0_AFBA;
+
+/// Recompute the table checksums and `head.checksumAdjustment` of built font
+/// bytes, without rebuilding the font.
+///
+/// Each table directory checksum is recomputed, `head`'s with its
+/// `checksumAdjustment` zeroed, and the adjustment is then set to
+/// `0xB1B0AFBA` minus the checksum of the whole file.
+pub fn fix_checksum(data: &[u8]) -> Result<Vec<u8>> {
+    let font = read_fonts::FontRef::new(data)?;
+    let records: Vec<_> = font.table_directory().table_records().to_vec();
+    let head = records
+        .iter()
+        .find(|record| record.tag() == Tag::new(b"head"))
+        .ok_or_else(|| anyhow!("Font has no head table."))?;
+    let adjustment = head.offset() as usize + CHECKSUM_ADJUSTMENT_OFFSET;
+    ensure!(adjustment + 4 <= data.len(), "head table is truncated.");
+
+    let mut data = data.to_vec();
+    data[adjustment..adjustment + 4].fill(0);
+    for (i, record) in records.iter().enumerate() {
+        let start = record.offset() as usize;
+        let table = start
+            .checked_add(record.length() as usize)
+            .and_then(|end| data.get(start..end))
+            .ok_or_else(|| anyhow!("Table '{}' lies outside the font.", record.tag()))?;
+        let checksum = checksum(table);
+        // Table records start after the 12-byte sfnt header; the checksum
+        // follows each record's tag
+        let field = 12 + 16 * i + 4;
+        data[field..field + 4].copy_from_slice(&checksum.to_be_bytes());
+    }
+    let value = CHECKSUM_MAGIC.wrapping_sub(checksum(&data));
+    data[adjustment..adjustment + 4].copy_from_slice(&value.to_be_bytes());
+    Ok(data)
+}
+
+/// Sum of `data` as big-endian `u32`s, zero-padding the last one.
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
 /// Apply default monospace settings to font data.
 pub fn set_monospace(data: &[u8]) -> Result<Vec<u8>> {
     MonospaceSettings::DEFAULT.apply(data)
@@ -169,6 +573,8 @@ pub fn set_version(data: &[u8], date: NaiveDate, tag: &str) -> Result<Vec<u8>> {
 mod tests {
     #[cfg(test)]
     use font_test_data::CMAP12_FONT1;
+    use read_fonts::FontRef;
+    use write_fonts::FontBuilder;
 
     use super::*;
 
@@ -222,4 +628,307 @@ mod tests {
         let result = set_monospace(data);
         assert!(result.is_ok());
     }
+
+    fn font_with_unique_id(unique_id: &str) -> Vec<u8> {
+        let font = FontRef::new(CMAP12_FONT1).unwrap();
+        let name = Name::new(vec![NameRecord::new(
+            3,
+            1,
+            0x409,
+            NameId::new(NAME_ID_UNIQUE_ID),
+            unique_id.to_string().into(),
+        )]);
+        let mut builder = FontBuilder::new();
+        builder.add_table(&name).unwrap();
+        builder.copy_missing_tables(font);
+        builder.build()
+    }
+
+    fn unique_id(data: &[u8]) -> String {
+        let font = FontRef::new(data).unwrap();
+        let name = font.name().unwrap();
+        name.name_record()
+            .iter()
+            .find(|r| r.name_id().to_u16() == NAME_ID_UNIQUE_ID)
+            .and_then(|r| r.string(name.string_data()).ok())
+            .map(|s| s.chars().collect())
+            .unwrap()
+    }
+
+    fn name_strings(data: &[u8], name_id: u16) -> Vec<String> {
+        let font = FontRef::new(data).unwrap();
+        let name = font.name().unwrap();
+        name.name_record()
+            .iter()
+            .filter(|r| r.name_id().to_u16() == name_id)
+            .map(|r| r.string(name.string_data()).unwrap().chars().collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_set_legal_names() {
+        let font = FontRef::new(CMAP12_FONT1).unwrap();
+        let record = |platform, encoding, language, name_id, string: &str| {
+            NameRecord::new(
+                platform,
+                encoding,
+                language,
+                NameId::new(name_id),
+                string.to_string().into(),
+            )
+        };
+        let mut records = vec![
+            record(1, 0, 0, NAME_ID_COPYRIGHT, "Old copyright"),
+            record(3, 1, 0x409, NAME_ID_COPYRIGHT, "Old copyright"),
+            record(3, 1, 0x409, NAME_ID_UNIQUE_ID, "1.000;WARP;WarpnineMono-Regular"),
+            record(3, 1, 0x409, NAME_ID_LICENSE, "Old license"),
+            record(3, 1, 0x409, NAME_ID_LICENSE_URL, "https://example.com/license"),
+        ];
+        records.sort();
+        let mut builder = FontBuilder::new();
+        builder.add_table(&Name::new(records)).unwrap();
+        builder.copy_missing_tables(font);
+        let data = builder.build();
+
+        let ofl = "This Font Software is licensed under the SIL Open Font License, Version 1.1.";
+        let result =
+            set_legal_names(&data, Some("Copyright 2024 Warpnine"), Some(ofl), None).unwrap();
+        assert_eq!(name_strings(&result, NAME_ID_COPYRIGHT), ["Copyright 2024 Warpnine"; 2]);
+        assert_eq!(name_strings(&result, NAME_ID_LICENSE), [ofl]);
+        assert_eq!(name_strings(&result, NAME_ID_LICENSE_URL), ["https://example.com/license"]);
+        assert_eq!(unique_id(&result), "1.000;WARP;WarpnineMono-Regular");
+    }
+
+    #[test]
+    fn test_set_legal_names_adds_missing() {
+        let font = font_with_unique_id("1.000;WARP;WarpnineMono-Regular");
+        let ofl = "This Font Software is licensed under the SIL Open Font License, Version 1.1.";
+        let result = set_legal_names(&font, None, Some(ofl), None).unwrap();
+        assert_eq!(name_strings(&result, NAME_ID_LICENSE), [ofl]);
+        assert!(name_strings(&result, NAME_ID_COPYRIGHT).is_empty());
+        assert!(name_strings(&result, NAME_ID_LICENSE_URL).is_empty());
+        assert_eq!(unique_id(&result), "1.000;WARP;WarpnineMono-Regular");
+
+        let font = FontRef::new(&result).unwrap();
+        let name = font.name().unwrap();
+        let record = name
+            .name_record()
+            .iter()
+            .find(|r| r.name_id().to_u16() == NAME_ID_LICENSE)
+            .unwrap();
+        assert_eq!(
+            (record.platform_id(), record.encoding_id(), record.language_id()),
+            (3, 1, 0x409)
+        );
+    }
+
+    #[test]
+    fn test_apply_localized_names() {
+        let font = font_with_unique_id("1.000;WARP;WarpnineMono-Regular");
+        let names = BTreeMap::from([(0x411, ("ワープナイン".to_string(), "標準".to_string()))]);
+        let result = apply_localized_names(&font, &names).unwrap();
+
+        let font = FontRef::new(&result).unwrap();
+        let name = font.name().unwrap();
+        let localized = |name_id: u16| -> Vec<(u16, u16, String)> {
+            name.name_record()
+                .iter()
+                .filter(|r| r.language_id() == 0x411 && r.name_id().to_u16() == name_id)
+                .map(|r| {
+                    let string = r.string(name.string_data()).unwrap().chars().collect();
+                    (r.platform_id(), r.encoding_id(), string)
+                })
+                .collect()
+        };
+        assert_eq!(localized(NAME_ID_FAMILY), [(3, 1, "ワープナイン".to_string())]);
+        assert_eq!(localized(NAME_ID_TYPO_FAMILY), [(3, 1, "ワープナイン".to_string())]);
+        assert_eq!(localized(NAME_ID_SUBFAMILY), [(3, 1, "標準".to_string())]);
+        assert_eq!(localized(NAME_ID_TYPO_SUBFAMILY), [(3, 1, "標準".to_string())]);
+        assert_eq!(unique_id(&result), "1.000;WARP;WarpnineMono-Regular");
+
+        // Applying again replaces rather than duplicates
+        let names = BTreeMap::from([(0x411, ("ワープ".to_string(), "太字".to_string()))]);
+        let result = apply_localized_names(&result, &names).unwrap();
+        assert_eq!(name_strings(&result, NAME_ID_FAMILY), ["ワープ"]);
+    }
+
+    #[test]
+    fn test_set_unique_id_suffix() {
+        let font = font_with_unique_id("1.000;WARP;WarpnineMono-Regular");
+        let result = set_unique_id_suffix(&font, "abc1234").unwrap();
+        assert_eq!(unique_id(&result), "1.000;WARP;WarpnineMono-Regular; abc1234");
+
+        // Applying a version afterwards keeps the suffix
+        let version = FontVersion::parse(Some("2024-01-15")).unwrap();
+        let result = version.apply(&result).unwrap();
+        assert_eq!(unique_id(&result), "2024-01-15;WARP;WarpnineMono-Regular; abc1234");
+        let result = set_unique_id_suffix(&result, "def5678").unwrap();
+        assert_eq!(unique_id(&result), "2024-01-15;WARP;WarpnineMono-Regular; abc1234; def5678");
+    }
+
+    #[test]
+    fn test_build_unique_id() {
+        let version = FontVersion::new(NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(), "2024-01-15");
+        let built = build_unique_id("WARP", "Warpnine Mono", "Bold Italic", &version);
+        assert_eq!(built, "2024-01-15;WARP;WarpnineMono-BoldItalic");
+
+        let font = font_with_unique_id("1.000;WARP;WarpnineMono-Regular");
+        let result = set_unique_id(&font, &built).unwrap();
+        assert_eq!(unique_id(&result), "2024-01-15;WARP;WarpnineMono-BoldItalic");
+    }
+
+    #[test]
+    fn test_apply_version_to_unique_id() {
+        let version = |tag| FontVersion::parse(Some(tag)).unwrap();
+        let old = version("2024-01-15");
+        let built = build_unique_id("WARP", "Warpnine Mono", "Bold", &old);
+        let font = set_unique_id(&font_with_unique_id(""), &built).unwrap();
+        let result = version("2025-02-01.1").apply(&font).unwrap();
+        assert_eq!(unique_id(&result), "2025-02-01.1;WARP;WarpnineMono-Bold");
+
+        // A leading version number is replaced in place, spacing kept
+        let font = font_with_unique_id("Version 1.000; WARP; WarpnineMono-Regular");
+        let result = version("2025-02-01").apply(&font).unwrap();
+        assert_eq!(unique_id(&result), "2025-02-01; WARP; WarpnineMono-Regular");
+
+        // Without a version field the last field is the version
+        let font = font_with_unique_id("Warpnine Mono Regular; build");
+        let result = version("2025-02-01").apply(&font).unwrap();
+        assert_eq!(unique_id(&result), "Warpnine Mono Regular; 2025-02-01");
+    }
+
+    #[test]
+    fn test_set_unique_id_when_absent() {
+        let font = FontRef::new(CMAP12_FONT1).unwrap();
+        let name = Name::new(vec![NameRecord::new(
+            3,
+            1,
+            0x409,
+            NameId::new(1),
+            "Warpnine Mono".to_string().into(),
+        )]);
+        let mut builder = FontBuilder::new();
+        builder.add_table(&name).unwrap();
+        builder.copy_missing_tables(font);
+        let font = builder.build();
+
+        let result = set_unique_id(&font, "2024-01-15;WARP;WarpnineMono-Regular").unwrap();
+        assert_eq!(unique_id(&result), "2024-01-15;WARP;WarpnineMono-Regular");
+        assert_eq!(FontRef::new(&result).unwrap().name().unwrap().name_record().len(), 2);
+    }
+
+    #[test]
+    fn test_style_flags_apply() {
+        let bold_italic = StyleFlags { italic: true, bold: true, regular: true };
+        let result = bold_italic.apply(font_test_data::TINOS_SUBSET).unwrap();
+        let font = FontRef::new(&result).unwrap();
+        let selection = font.os2().unwrap().fs_selection();
+        assert!(selection.contains(SelectionFlags::ITALIC | SelectionFlags::BOLD));
+        assert!(!selection.contains(SelectionFlags::REGULAR));
+        assert_eq!(font.head().unwrap().mac_style(), MacStyle::BOLD | MacStyle::ITALIC);
+
+        let regular = StyleFlags { regular: true, ..Default::default() };
+        let result = regular.apply(&result).unwrap();
+        let font = FontRef::new(&result).unwrap();
+        let selection = font.os2().unwrap().fs_selection();
+        assert!(selection.contains(SelectionFlags::REGULAR));
+        assert!(!selection.intersects(SelectionFlags::ITALIC | SelectionFlags::BOLD));
+        assert_eq!(font.head().unwrap().mac_style(), MacStyle::empty());
+    }
+
+    #[test]
+    fn test_set_weight_and_width_class() {
+        let result = set_weight_class(font_test_data::TINOS_SUBSET, 600).unwrap();
+        let result = set_width_class(&result, 3).unwrap();
+        let os2 = FontRef::new(&result).unwrap().os2().unwrap();
+        assert_eq!(os2.us_weight_class(), 600);
+        assert_eq!(os2.us_width_class(), 3);
+
+        assert!(set_weight_class(font_test_data::TINOS_SUBSET, 1000).is_ok());
+        assert!(set_weight_class(font_test_data::TINOS_SUBSET, 0).is_err());
+        assert!(set_weight_class(font_test_data::TINOS_SUBSET, 1001).is_err());
+        assert!(set_width_class(font_test_data::TINOS_SUBSET, 0).is_err());
+        assert!(set_width_class(font_test_data::TINOS_SUBSET, 10).is_err());
+    }
+
+    #[test]
+    fn test_set_vendor_id() {
+        let result = set_vendor_id(font_test_data::TINOS_SUBSET, "WN").unwrap();
+        let os2 = FontRef::new(&result).unwrap().os2().unwrap();
+        assert_eq!(os2.ach_vend_id(), Tag::new(b"WN  "));
+
+        for invalid in ["", "TOOLONG", "W\u{e9}"] {
+            assert!(set_vendor_id(font_test_data::TINOS_SUBSET, invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_set_timestamps() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let original = FontRef::new(CMAP12_FONT1).unwrap().head().unwrap().created();
+
+        let result = set_timestamps(CMAP12_FONT1, None, Some(date(2024, 1, 1))).unwrap();
+        let head = FontRef::new(&result).unwrap().head().unwrap();
+        assert_eq!(head.created(), original);
+        assert_eq!(head.modified(), LongDateTime::new(3_786_912_000));
+
+        // The date may roll over during the call
+        let before = long_date_time(Utc::now().date_naive());
+        let result = set_timestamps(&result, Some(date(1904, 1, 2)), None).unwrap();
+        let after = long_date_time(Utc::now().date_naive());
+        let head = FontRef::new(&result).unwrap().head().unwrap();
+        assert_eq!(head.created(), LongDateTime::new(86_400));
+        assert!([before, after].contains(&head.modified()));
+    }
+
+    #[test]
+    fn test_fix_checksum() {
+        let mut stale = CMAP12_FONT1.to_vec();
+        let font = FontRef::new(CMAP12_FONT1).unwrap();
+        let records = font.table_directory().table_records().to_vec();
+        let head = records.iter().find(|r| r.tag() == Tag::new(b"head")).unwrap();
+        let adjustment = head.offset() as usize + CHECKSUM_ADJUSTMENT_OFFSET;
+        stale[adjustment..adjustment + 4].copy_from_slice(&0xDEAD_BEEF_u32.to_be_bytes());
+
+        let fixed = fix_checksum(&stale).unwrap();
+        let stored = u32::from_be_bytes(fixed[adjustment..adjustment + 4].try_into().unwrap());
+        let mut zeroed = fixed.clone();
+        zeroed[adjustment..adjustment + 4].fill(0);
+        assert_eq!(checksum(&zeroed).wrapping_add(stored), CHECKSUM_MAGIC);
+        // The whole file, adjustment included, then sums to the magic value
+        assert_eq!(checksum(&fixed), CHECKSUM_MAGIC);
+
+        let font = FontRef::new(&zeroed).unwrap();
+        for record in font.table_directory().table_records() {
+            let start = record.offset() as usize;
+            let table = &zeroed[start..start + record.length() as usize];
+            assert_eq!(record.checksum(), checksum(table), "{}", record.tag());
+        }
+        assert_eq!(fix_checksum(&fixed).unwrap(), fixed);
+    }
+
+    fn font_modified_at(seconds: i64) -> Vec<u8> {
+        let font = FontRef::new(CMAP12_FONT1).unwrap();
+        let mut head: Head = font.head().unwrap().to_owned_table();
+        head.modified = LongDateTime::new(seconds);
+        let mut builder = FontBuilder::new();
+        builder.add_table(&head).unwrap();
+        builder.copy_missing_tables(font);
+        builder.build()
+    }
+
+    #[test]
+    fn test_make_reproducible() {
+        let first = font_modified_at(3_700_000_000);
+        let second = font_modified_at(3_800_000_000);
+        assert_ne!(first, second);
+
+        let first = make_reproducible(&first).unwrap();
+        let second = make_reproducible(&second).unwrap();
+        assert_eq!(first, second);
+
+        let head = FontRef::new(&first).unwrap().head().unwrap();
+        assert_eq!(head.created(), REPRODUCIBLE_TIMESTAMP);
+        assert_eq!(head.modified(), REPRODUCIBLE_TIMESTAMP);
+    }
 }
@@ -0,0 +1,224 @@
+//! CFF encoding helpers: DICT entries, INDEX structures and charstring
+//! integers.
+
+/// DICT entries as (operator, raw operand bytes), or `None` if the DICT is
+/// malformed. Escaped operators are `12 << 8 | b1`.
+pub fn dict_entries(data: &[u8]) -> Option<Vec<(u16, &[u8])>> {
+    let mut entries = Vec::new();
+    let (mut start, mut i) = (0, 0);
+    while i < data.len() {
+        let end = i;
+        let op = match data[i] {
+            12 => {
+                let b1 = *data.get(i + 1)?;
+                i += 2;
+                12 << 8 | u16::from(b1)
+            }
+            op @ 0..=27 => {
+                i += 1;
+                u16::from(op)
+            }
+            _ => {
+                i += dict_number(&data[i..])?.1;
+                continue;
+            }
+        };
+        entries.push((op, &data[start..end]));
+        start = i;
+    }
+    Some(entries)
+}
+
+/// Values of a DICT entry's raw operand bytes
+pub fn dict_numbers(mut operands: &[u8]) -> Option<Vec<f64>> {
+    let mut values = Vec::new();
+    while !operands.is_empty() {
+        let (value, len) = dict_number(operands)?;
+        values.push(value);
+        operands = &operands[len..];
+    }
+    Some(values)
+}
+
+/// A DICT operand and its encoded length
+fn dict_number(data: &[u8]) -> Option<(f64, usize)> {
+    let b0 = i32::from(data[0]);
+    Some(match *data {
+        [28, a, b, ..] => (f64::from(i16::from_be_bytes([a, b])), 3),
+        [29, a, b, c, d, ..] => (f64::from(i32::from_be_bytes([a, b, c, d])), 5),
+        [30, ..] => {
+            let mut text = String::new();
+            for (i, byte) in data[1..].iter().enumerate() {
+                for nibble in [byte >> 4, byte & 0x0F] {
+                    match nibble {
+                        0..=9 => text.push(char::from(b'0' + nibble)),
+                        0xA => text.push('.'),
+                        0xB => text.push('E'),
+                        0xC => text.push_str("E-"),
+                        0xE => text.push('-'),
+                        0xF => return Some((text.parse().ok()?, i + 2)),
+                        _ => return None,
+                    }
+                }
+            }
+            return None;
+        }
+        [32..=246, ..] => (f64::from(b0 - 139), 1),
+        [247..=250, b1, ..] => (f64::from((b0 - 247) * 256 + i32::from(b1) + 108), 2),
+        [251..=254, b1, ..] => (f64::from(-(b0 - 251) * 256 - i32::from(b1) - 108), 2),
+        _ => return None,
+    })
+}
+
+/// A DICT operator, escaped if it is `12 << 8 | b1`
+pub fn push_op(out: &mut Vec<u8>, op: u16) {
+    if op >> 8 == 12 {
+        out.extend_from_slice(&[12, op as u8]);
+    } else {
+        out.push(op as u8);
+    }
+}
+
+/// A DICT entry with offset operands, written as 5-byte integers so the
+/// entry's size does not depend on their values
+pub fn push_offsets(out: &mut Vec<u8>, op: u16, offsets: &[usize]) {
+    for &offset in offsets {
+        out.push(29);
+        out.extend_from_slice(&(offset as i32).to_be_bytes());
+    }
+    push_op(out, op);
+}
+
+/// A DICT entry with numeric operands: integers where exact, otherwise reals
+/// to six decimal places
+pub fn push_numbers(out: &mut Vec<u8>, op: u16, operands: &[f64]) {
+    for &value in operands {
+        let int = value.round();
+        if (value - int).abs() < 1e-9 && int.abs() < 2f64.powi(31) {
+            let int = int as i32;
+            match int {
+                -107..=107 => out.push((int + 139) as u8),
+                108..=1131 => {
+                    let v = int - 108;
+                    out.extend_from_slice(&[(v / 256 + 247) as u8, (v % 256) as u8]);
+                }
+                -1131..=-108 => {
+                    let v = -int - 108;
+                    out.extend_from_slice(&[(v / 256 + 251) as u8, (v % 256) as u8]);
+                }
+                -32768..=32767 => {
+                    out.push(28);
+                    out.extend_from_slice(&(int as i16).to_be_bytes());
+                }
+                _ => {
+                    out.push(29);
+                    out.extend_from_slice(&int.to_be_bytes());
+                }
+            }
+        } else {
+            let text = ((value * 1e6).round() / 1e6).to_string();
+            let mut nibbles: Vec<u8> = text
+                .bytes()
+                .map(|c| match c {
+                    b'.' => 0xA,
+                    b'-' => 0xE,
+                    digit => digit - b'0',
+                })
+                .collect();
+            nibbles.push(0xF);
+            if nibbles.len() % 2 == 1 {
+                nibbles.push(0xF);
+            }
+            out.push(30);
+            out.extend(nibbles.chunks(2).map(|pair| pair[0] << 4 | pair[1]));
+        }
+    }
+    push_op(out, op);
+}
+
+/// A Type 2 charstring integer operand, as a 16.16 fixed past the 16-bit
+/// range
+pub fn encode_charstring_int(out: &mut Vec<u8>, value: i32) {
+    match value {
+        -107..=107 => out.push((value + 139) as u8),
+        108..=1131 => {
+            let v = value - 108;
+            out.extend_from_slice(&[(v / 256 + 247) as u8, (v % 256) as u8]);
+        }
+        -1131..=-108 => {
+            let v = -value - 108;
+            out.extend_from_slice(&[(v / 256 + 251) as u8, (v % 256) as u8]);
+        }
+        -32768..=32767 => {
+            out.push(28);
+            out.extend_from_slice(&(value as i16).to_be_bytes());
+        }
+        _ => {
+            out.push(255);
+            out.extend_from_slice(&(value << 16).to_be_bytes());
+        }
+    }
+}
+
+/// Serialize a CFF INDEX with the smallest offset size that fits
+pub fn write_index<T: AsRef<[u8]>>(items: &[T]) -> Vec<u8> {
+    if items.is_empty() {
+        return vec![0, 0];
+    }
+    let last_offset = items.iter().map(|item| item.as_ref().len()).sum::<usize>() + 1;
+    let off_size = match last_offset {
+        0..=0xFF => 1,
+        0x100..=0xFFFF => 2,
+        0x1_0000..=0xFF_FFFF => 3,
+        _ => 4,
+    };
+    let mut out = Vec::with_capacity(3 + (items.len() + 1) * off_size + last_offset);
+    out.extend_from_slice(&(items.len() as u16).to_be_bytes());
+    out.push(off_size as u8);
+    let mut offset = 1usize;
+    out.extend_from_slice(&(offset as u32).to_be_bytes()[4 - off_size..]);
+    for item in items {
+        offset += item.as_ref().len();
+        out.extend_from_slice(&(offset as u32).to_be_bytes()[4 - off_size..]);
+    }
+    for item in items {
+        out.extend_from_slice(item.as_ref());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dict_round_trip() {
+        let operands = vec![0.0, -107.0, 1131.0, -1131.0, 32767.0, 100_000.0, 0.039625, -0.5];
+        let mut dict = Vec::new();
+        push_numbers(&mut dict, 12 << 8 | 7, &operands);
+        push_offsets(&mut dict, 18, &[12, 345]);
+
+        let entries = dict_entries(&dict).unwrap();
+        let ops: Vec<u16> = entries.iter().map(|(op, _)| *op).collect();
+        assert_eq!(ops, [12 << 8 | 7, 18]);
+        assert_eq!(dict_numbers(entries[0].1).unwrap(), operands);
+        assert_eq!(dict_numbers(entries[1].1).unwrap(), [12.0, 345.0]);
+    }
+
+    #[test]
+    fn malformed_dict() {
+        // Escape byte with no second byte, and a truncated 16-bit operand
+        assert_eq!(dict_entries(&[139, 12]), None);
+        assert_eq!(dict_entries(&[28, 1]), None);
+    }
+
+    #[test]
+    fn index_offset_sizes() {
+        assert_eq!(write_index::<&[u8]>(&[]), [0, 0]);
+        assert_eq!(write_index(&[b"ab"]), [0, 1, 1, 1, 3, b'a', b'b']);
+
+        let large = write_index(&[vec![0u8; 300]]);
+        assert_eq!(&large[..7], [0, 1, 2, 0, 1, 0x01, 0x2D]);
+        assert_eq!(large.len(), 7 + 300);
+    }
+}
@@ -0,0 +1,340 @@
+//! Internal consistency checks for GSUB and GPOS.
+
+use std::fmt;
+
+use anyhow::Result;
+use read_fonts::{
+    ArrayOfOffsets, FontRef, ReadError, TableProvider,
+    tables::{
+        gpos::{PairPos, PositionLookup, PositionSubtables, SinglePos},
+        gsub::{SingleSubst, SubstitutionLookup, SubstitutionSubtables},
+        layout::{ChainedSequenceContext, CoverageTable, FeatureList, ScriptList, SequenceContext},
+    },
+    types::{GlyphId16, Tag},
+};
+
+/// LangSys `requiredFeatureIndex` meaning "no required feature".
+const NO_REQUIRED_FEATURE: u16 = 0xFFFF;
+
+/// A dangling reference found by [`validate_layout`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayoutIssue {
+    /// A feature lists a lookup index past the end of the lookup list.
+    LookupIndex { table: Tag, feature: Tag, lookup_index: u16, lookup_count: u16 },
+    /// A language system lists a feature index past the end of the feature
+    /// list; `lang_sys` is `None` for the script's default.
+    FeatureIndex {
+        table: Tag,
+        script: Tag,
+        lang_sys: Option<Tag>,
+        feature_index: u16,
+        feature_count: u16,
+    },
+    /// A lookup's coverage table lists a glyph the font does not have.
+    CoverageGlyph { table: Tag, lookup_index: u16, glyph: GlyphId16, num_glyphs: u16 },
+}
+
+impl fmt::Display for LayoutIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LookupIndex { table, feature, lookup_index, lookup_count } => write!(
+                f,
+                "{table} feature '{feature}' references lookup {lookup_index}, but the lookup \
+                 list has {lookup_count}"
+            ),
+            Self::FeatureIndex {
+                table,
+                script,
+                lang_sys,
+                feature_index,
+                feature_count,
+            } => {
+                let lang_sys = lang_sys.map_or_else(|| "default".to_string(), |t| t.to_string());
+                write!(
+                    f,
+                    "{table} script '{script}' language {lang_sys} references feature \
+                     {feature_index}, but the feature list has {feature_count}"
+                )
+            }
+            Self::CoverageGlyph { table, lookup_index, glyph, num_glyphs } => write!(
+                f,
+                "{table} lookup {lookup_index} covers glyph {glyph}, but the font has {num_glyphs}"
+            ),
+        }
+    }
+}
+
+/// Check that GSUB and GPOS only reference what exists.
+///
+/// Every feature's lookup indices must be within the lookup list, every
+/// language system's feature indices within the feature list, and every
+/// glyph in a lookup's coverage tables below `maxp.numGlyphs`. A table that
+/// fails to parse is an error rather than an issue.
+pub fn validate_layout(data: &[u8]) -> Result<Vec<LayoutIssue>> {
+    let font = FontRef::new(data)?;
+    let num_glyphs = font.maxp()?.num_glyphs();
+    let mut issues = Vec::new();
+
+    if let Some(gsub) = present(font.gsub())? {
+        let table = Tag::new(b"GSUB");
+        let lookups = gsub.lookup_list()?;
+        check_lists(
+            table,
+            gsub.script_list()?,
+            gsub.feature_list()?,
+            lookups.lookup_count(),
+            &mut issues,
+        )?;
+        for (index, lookup) in lookups.lookups().iter().enumerate() {
+            let coverages = substitution_coverages(&lookup?)?;
+            check_coverages(table, index as u16, &coverages, num_glyphs, &mut issues);
+        }
+    }
+    if let Some(gpos) = present(font.gpos())? {
+        let table = Tag::new(b"GPOS");
+        let lookups = gpos.lookup_list()?;
+        check_lists(
+            table,
+            gpos.script_list()?,
+            gpos.feature_list()?,
+            lookups.lookup_count(),
+            &mut issues,
+        )?;
+        for (index, lookup) in lookups.lookups().iter().enumerate() {
+            let coverages = position_coverages(&lookup?)?;
+            check_coverages(table, index as u16, &coverages, num_glyphs, &mut issues);
+        }
+    }
+    Ok(issues)
+}
+
+/// `None` for a missing table, leaving a table that fails to parse an error.
+fn present<T>(table: Result<T, ReadError>) -> Result<Option<T>, ReadError> {
+    match table {
+        Err(ReadError::TableIsMissing(_)) => Ok(None),
+        table => table.map(Some),
+    }
+}
+
+/// Check the script list's feature indices and the feature list's lookup
+/// indices.
+fn check_lists(
+    table: Tag,
+    scripts: ScriptList,
+    features: FeatureList,
+    lookup_count: u16,
+    issues: &mut Vec<LayoutIssue>,
+) -> Result<()> {
+    let feature_count = features.feature_count();
+    for record in scripts.script_records() {
+        let script = record.script(scripts.offset_data())?;
+        let lang_systems = script
+            .default_lang_sys()
+            .transpose()?
+            .map(|lang_sys| (None, lang_sys))
+            .into_iter()
+            .chain(
+                script
+                    .lang_sys_records()
+                    .iter()
+                    .map(|r| Ok((Some(r.lang_sys_tag()), r.lang_sys(script.offset_data())?)))
+                    .collect::<Result<Vec<_>, ReadError>>()?,
+            );
+        for (lang_sys_tag, lang_sys) in lang_systems {
+            let required = Some(lang_sys.required_feature_index())
+                .filter(|&index| index != NO_REQUIRED_FEATURE);
+            let indices = lang_sys.feature_indices().iter().map(|index| index.get());
+            for feature_index in required.into_iter().chain(indices) {
+                if feature_index >= feature_count {
+                    issues.push(LayoutIssue::FeatureIndex {
+                        table,
+                        script: record.script_tag(),
+                        lang_sys: lang_sys_tag,
+                        feature_index,
+                        feature_count,
+                    });
+                }
+            }
+        }
+    }
+
+    for record in features.feature_records() {
+        let feature = record.feature(features.offset_data())?;
+        for lookup_index in feature.lookup_list_indices().iter().map(|index| index.get()) {
+            if lookup_index >= lookup_count {
+                issues.push(LayoutIssue::LookupIndex {
+                    table,
+                    feature: record.feature_tag(),
+                    lookup_index,
+                    lookup_count,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+fn check_coverages(
+    table: Tag,
+    lookup_index: u16,
+    coverages: &[CoverageTable],
+    num_glyphs: u16,
+    issues: &mut Vec<LayoutIssue>,
+) {
+    for coverage in coverages {
+        for glyph in coverage.iter().filter(|glyph| glyph.to_u16() >= num_glyphs) {
+            issues.push(LayoutIssue::CoverageGlyph { table, lookup_index, glyph, num_glyphs });
+        }
+    }
+}
+
+/// Every coverage table of a GSUB lookup's subtables, extensions resolved.
+fn substitution_coverages<'a>(
+    lookup: &SubstitutionLookup<'a>,
+) -> Result<Vec<CoverageTable<'a>>, ReadError> {
+    let mut coverages = Vec::new();
+    match lookup.subtables()? {
+        SubstitutionSubtables::Single(subtables) => {
+            for subtable in subtables.iter() {
+                coverages.push(match subtable? {
+                    SingleSubst::Format1(subtable) => subtable.coverage()?,
+                    SingleSubst::Format2(subtable) => subtable.coverage()?,
+                });
+            }
+        }
+        SubstitutionSubtables::Multiple(subtables) => {
+            for subtable in subtables.iter() {
+                coverages.push(subtable?.coverage()?);
+            }
+        }
+        SubstitutionSubtables::Alternate(subtables) => {
+            for subtable in subtables.iter() {
+                coverages.push(subtable?.coverage()?);
+            }
+        }
+        SubstitutionSubtables::Ligature(subtables) => {
+            for subtable in subtables.iter() {
+                coverages.push(subtable?.coverage()?);
+            }
+        }
+        SubstitutionSubtables::Contextual(subtables) => {
+            for subtable in subtables.iter() {
+                sequence_context_coverages(&subtable?, &mut coverages)?;
+            }
+        }
+        SubstitutionSubtables::ChainContextual(subtables) => {
+            for subtable in subtables.iter() {
+                chain_context_coverages(&subtable?, &mut coverages)?;
+            }
+        }
+        SubstitutionSubtables::Reverse(subtables) => {
+            for subtable in subtables.iter() {
+                let subtable = subtable?;
+                coverages.push(subtable.coverage()?);
+                extend(&mut coverages, subtable.backtrack_coverages())?;
+                extend(&mut coverages, subtable.lookahead_coverages())?;
+            }
+        }
+    }
+    Ok(coverages)
+}
+
+/// Every coverage table of a GPOS lookup's subtables, extensions resolved.
+fn position_coverages<'a>(
+    lookup: &PositionLookup<'a>,
+) -> Result<Vec<CoverageTable<'a>>, ReadError> {
+    let mut coverages = Vec::new();
+    match lookup.subtables()? {
+        PositionSubtables::Single(subtables) => {
+            for subtable in subtables.iter() {
+                coverages.push(match subtable? {
+                    SinglePos::Format1(subtable) => subtable.coverage()?,
+                    SinglePos::Format2(subtable) => subtable.coverage()?,
+                });
+            }
+        }
+        PositionSubtables::Pair(subtables) => {
+            for subtable in subtables.iter() {
+                coverages.push(match subtable? {
+                    PairPos::Format1(subtable) => subtable.coverage()?,
+                    PairPos::Format2(subtable) => subtable.coverage()?,
+                });
+            }
+        }
+        PositionSubtables::Cursive(subtables) => {
+            for subtable in subtables.iter() {
+                coverages.push(subtable?.coverage()?);
+            }
+        }
+        PositionSubtables::MarkToBase(subtables) => {
+            for subtable in subtables.iter() {
+                let subtable = subtable?;
+                coverages.push(subtable.mark_coverage()?);
+                coverages.push(subtable.base_coverage()?);
+            }
+        }
+        PositionSubtables::MarkToLig(subtables) => {
+            for subtable in subtables.iter() {
+                let subtable = subtable?;
+                coverages.push(subtable.mark_coverage()?);
+                coverages.push(subtable.ligature_coverage()?);
+            }
+        }
+        PositionSubtables::MarkToMark(subtables) => {
+            for subtable in subtables.iter() {
+                let subtable = subtable?;
+                coverages.push(subtable.mark1_coverage()?);
+                coverages.push(subtable.mark2_coverage()?);
+            }
+        }
+        PositionSubtables::Contextual(subtables) => {
+            for subtable in subtables.iter() {
+                sequence_context_coverages(&subtable?, &mut coverages)?;
+            }
+        }
+        PositionSubtables::ChainContextual(subtables) => {
+            for subtable in subtables.iter() {
+                chain_context_coverages(&subtable?, &mut coverages)?;
+            }
+        }
+    }
+    Ok(coverages)
+}
+
+fn sequence_context_coverages<'a>(
+    context: &SequenceContext<'a>,
+    coverages: &mut Vec<CoverageTable<'a>>,
+) -> Result<(), ReadError> {
+    match context {
+        SequenceContext::Format1(context) => coverages.push(context.coverage()?),
+        SequenceContext::Format2(context) => coverages.push(context.coverage()?),
+        SequenceContext::Format3(context) => extend(coverages, context.coverages())?,
+    }
+    Ok(())
+}
+
+fn chain_context_coverages<'a>(
+    context: &ChainedSequenceContext<'a>,
+    coverages: &mut Vec<CoverageTable<'a>>,
+) -> Result<(), ReadError> {
+    match context {
+        ChainedSequenceContext::Format1(context) => coverages.push(context.coverage()?),
+        ChainedSequenceContext::Format2(context) => coverages.push(context.coverage()?),
+        ChainedSequenceContext::Format3(context) => {
+            extend(coverages, context.backtrack_coverages())?;
+            extend(coverages, context.input_coverages())?;
+            extend(coverages, context.lookahead_coverages())?;
+        }
+    }
+    Ok(())
+}
+
+fn extend<'a>(
+    coverages: &mut Vec<CoverageTable<'a>>,
+    offsets: ArrayOfOffsets<'a, CoverageTable<'a>>,
+) -> Result<(), ReadError> {
+    for coverage in offsets.iter() {
+        coverages.push(coverage?);
+    }
+    Ok(())
+}
@@ -1,19 +1,27 @@
 //! Generic font table manipulation utilities.
 
+pub mod cff;
+mod layout;
+mod svg;
+
 use std::collections::HashSet;
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, anyhow, bail};
+pub use layout::{LayoutIssue, validate_layout};
 use read_fonts::{
     FontRef, TableProvider,
-    types::{NameId, Tag},
+    types::{GlyphId, GlyphId16, NameId, Tag},
 };
+pub use svg::glyph_to_svg_path;
 use write_fonts::{
     FontBuilder,
     from_obj::ToOwnedTable,
     tables::{
+        cmap::Cmap,
         head::{Head, MacStyle},
         name::{Name, NameRecord},
         os2::{Os2, SelectionFlags},
+        post::Post,
     },
 };
 
@@ -98,10 +106,12 @@ pub struct StyleBits {
     pub weight_class: u16,
 }
 
-/// OpenType/CSS weight name for a `usWeightClass` value (300 → "Light" …
+/// OpenType/CSS weight name for a `usWeightClass` value (100 → "Thin" …
 /// 1000 → "ExtraBlack"). Unknown values fall back to "Regular".
 pub fn weight_name(weight: u16) -> &'static str {
     match weight {
+        100 => "Thin",
+        200 => "ExtraLight",
         300 => "Light",
         500 => "Medium",
         600 => "SemiBold",
@@ -185,7 +195,7 @@ pub fn ribbi_names(
 /// subfamily (17) are *synthesized* on every platform record that carries a
 /// legacy family (ID 1) but lacks them, so typographic grouping works even when
 /// the donor font ships only the legacy RIBBI name IDs (0-6).
-fn build_style_name_table(font: &FontRef, names: &StyleNames) -> Result<Name> {
+pub fn build_style_name_table(font: &FontRef, names: &StyleNames) -> Result<Name> {
     let name = font.name()?;
     let mut records: Vec<NameRecord> = Vec::new();
 
@@ -294,11 +304,26 @@ pub fn apply_style(font_data: &[u8], names: &StyleNames, bits: &StyleBits) -> Re
 /// Returns the new font data with the specified table replaced (or added)
 /// from the source font.
 pub fn copy_table(source_data: &[u8], target_data: &[u8], tag: Tag) -> Result<Vec<u8>> {
+    copy_tables(source_data, target_data, &[tag])
+}
+
+/// Copy several tables from source font to target font in one rebuild.
+///
+/// Like [`copy_table`] for each of `tags`, but both fonts are parsed once,
+/// so copying GSUB, GPOS and GDEF together costs a single pass. Fails,
+/// naming the tag, if the source lacks any of them.
+pub fn copy_tables(source_data: &[u8], target_data: &[u8], tags: &[Tag]) -> Result<Vec<u8>> {
     let source_font = FontRef::new(source_data).context("Failed to parse source font")?;
 
-    let table_data = source_font
-        .table_data(tag)
-        .with_context(|| format!("Source font has no {tag} table"))?;
+    let tables = tags
+        .iter()
+        .map(|&tag| {
+            let table_data = source_font
+                .table_data(tag)
+                .with_context(|| format!("Source font has no {tag} table"))?;
+            Ok((tag, table_data))
+        })
+        .collect::<Result<Vec<_>>>()?;
 
     let target_font = FontRef::new(target_data).context("Failed to parse target font")?;
 
@@ -306,7 +331,7 @@ pub fn copy_table(source_data: &[u8], target_data: &[u8], tag: Tag) -> Result<Ve
 
     for record in target_font.table_directory.table_records() {
         let record_tag = record.tag();
-        if record_tag == tag {
+        if tags.contains(&record_tag) {
             continue;
         }
         if let Some(data) = target_font.table_data(record_tag) {
@@ -314,11 +339,55 @@ pub fn copy_table(source_data: &[u8], target_data: &[u8], tag: Tag) -> Result<Ve
         }
     }
 
-    builder.add_raw(tag, table_data);
+    for (tag, table_data) in tables {
+        builder.add_raw(tag, table_data);
+    }
+
+    Ok(builder.build())
+}
+
+/// Remove the table `tag` from a font.
+///
+/// A font without the table is returned rebuilt but otherwise unchanged.
+pub fn remove_table(data: &[u8], tag: Tag) -> Result<Vec<u8>> {
+    remove_tables(data, &[tag])
+}
+
+/// Remove every table in `tags` from a font, such as `DSIG` or vendor
+/// tables before distribution; tags the font lacks are ignored.
+pub fn remove_tables(data: &[u8], tags: &[Tag]) -> Result<Vec<u8>> {
+    let font = FontRef::new(data)?;
+    let mut builder = FontBuilder::new();
+
+    for record in font.table_directory.table_records() {
+        let tag = record.tag();
+        if tags.contains(&tag) {
+            continue;
+        }
+        if let Some(table_data) = font.table_data(tag) {
+            builder.add_raw(tag, table_data);
+        }
+    }
 
     Ok(builder.build())
 }
 
+/// List each table in a font with its length in bytes, sorted by tag.
+///
+/// Lengths come straight from the table directory, so the font is not
+/// rebuilt or otherwise validated.
+pub fn list_tables(data: &[u8]) -> Result<Vec<(Tag, u32)>> {
+    let font = FontRef::new(data)?;
+    let mut tables: Vec<(Tag, u32)> = font
+        .table_directory
+        .table_records()
+        .iter()
+        .map(|record| (record.tag(), record.length()))
+        .collect();
+    tables.sort_by_key(|&(tag, _)| tag);
+    Ok(tables)
+}
+
 /// Copy GSUB table from source font to target font, removing FeatureVariations.
 ///
 /// FeatureVariations may reference axis indices that don't exist in the target
@@ -356,12 +425,198 @@ pub fn copy_gsub_without_feature_variations(
     Ok(builder.build())
 }
 
+/// Set OS/2 `usWinAscent`/`usWinDescent` to cover every glyph's bounds.
+///
+/// Scans the `glyf` bounding boxes for the largest yMax and smallest yMin, then
+/// sets `usWinAscent = max(yMax, 0)` and `usWinDescent = max(-yMin, 0)` so
+/// Windows does not clip any glyph.
+pub fn fit_win_metrics(data: &[u8]) -> Result<Vec<u8>> {
+    rewrite_font(data, |font, builder| {
+        let glyf = font.glyf().context("Font has no glyf table")?;
+        let loca = font.loca(None).context("Font has no loca table")?;
+        let num_glyphs = font.maxp()?.num_glyphs();
+
+        let mut y_max = 0i32;
+        let mut y_min = 0i32;
+        for gid in 0..num_glyphs {
+            if let Some(glyph) = loca.get_glyf(GlyphId::from(gid), &glyf)? {
+                y_max = y_max.max(i32::from(glyph.y_max()));
+                y_min = y_min.min(i32::from(glyph.y_min()));
+            }
+        }
+
+        let mut os2: Os2 = font.os2().context("Font has no OS/2 table")?.to_owned_table();
+        os2.us_win_ascent = u16::try_from(y_max).unwrap_or(u16::MAX);
+        os2.us_win_descent = u16::try_from(-y_min).unwrap_or(u16::MAX);
+        builder.add_table(&os2)?;
+        Ok(())
+    })
+}
+
+/// How [`recompute_avg_char_width`] averages advance widths.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AvgCharWidthMethod {
+    /// OS/2 versions 0–2: frequency-weighted average of `a`–`z` and space.
+    Weighted,
+    /// OS/2 version 3+: plain average of every non-zero advance width.
+    AllGlyphs,
+}
+
+/// Letter frequencies per 1000 used by the version 0–2 `xAvgCharWidth` formula.
+const AVG_CHAR_WIDTH_WEIGHTS: [(char, u32); 27] = [
+    ('a', 64),
+    ('b', 14),
+    ('c', 27),
+    ('d', 35),
+    ('e', 100),
+    ('f', 20),
+    ('g', 14),
+    ('h', 42),
+    ('i', 63),
+    ('j', 3),
+    ('k', 6),
+    ('l', 35),
+    ('m', 20),
+    ('n', 56),
+    ('o', 56),
+    ('p', 17),
+    ('q', 4),
+    ('r', 49),
+    ('s', 56),
+    ('t', 71),
+    ('u', 31),
+    ('v', 10),
+    ('w', 18),
+    ('x', 3),
+    ('y', 18),
+    ('z', 2),
+    (' ', 166),
+];
+
+/// Recompute OS/2 `xAvgCharWidth` from the `hmtx` advances.
+///
+/// [`AvgCharWidthMethod::Weighted`] requires every weighted character to be
+/// mapped in `cmap`; a missing one is an error rather than a silently skewed
+/// average. The result is rounded to the nearest integer.
+pub fn recompute_avg_char_width(data: &[u8], method: AvgCharWidthMethod) -> Result<Vec<u8>> {
+    rewrite_font(data, |font, builder| {
+        let hmtx = font.hmtx().context("Font has no hmtx table")?;
+        let num_glyphs = font.maxp()?.num_glyphs();
+        let advance = |gid: GlyphId| hmtx.advance(gid).unwrap_or(0);
+
+        let avg = match method {
+            AvgCharWidthMethod::Weighted => {
+                let cmap = font.cmap().context("Font has no cmap table")?;
+                let mut total = 0u64;
+                for (ch, weight) in AVG_CHAR_WIDTH_WEIGHTS {
+                    let gid = cmap
+                        .map_codepoint(ch)
+                        .with_context(|| format!("Font has no glyph for {ch:?}"))?;
+                    total += u64::from(advance(gid)) * u64::from(weight);
+                }
+                (total as f64 / 1000.0).round()
+            }
+            AvgCharWidthMethod::AllGlyphs => {
+                let advances: Vec<u16> = (0..num_glyphs)
+                    .map(|gid| advance(GlyphId::from(gid)))
+                    .filter(|&width| width != 0)
+                    .collect();
+                if advances.is_empty() {
+                    0.0
+                } else {
+                    let total: u64 = advances.iter().map(|&width| u64::from(width)).sum();
+                    (total as f64 / advances.len() as f64).round()
+                }
+            }
+        };
+
+        let mut os2: Os2 = font.os2().context("Font has no OS/2 table")?.to_owned_table();
+        os2.x_avg_char_width = avg.min(f64::from(i16::MAX)) as i16;
+        builder.add_table(&os2)?;
+        Ok(())
+    })
+}
+
+/// Copy glyph names from `source` into `target`'s `post` table.
+///
+/// Each `(source_gid, target_gid)` pair in `gid_map` names the target glyph
+/// after the source glyph. The target `post` is rewritten as version 2.0,
+/// keeping its existing names for unmapped glyphs (or `glyphNNNNN` if it had
+/// none) and all of its header fields.
+pub fn import_glyph_names(target: &[u8], source: &[u8], gid_map: &[(u16, u16)]) -> Result<Vec<u8>> {
+    let source_font = FontRef::new(source).context("Failed to parse source font")?;
+    let source_post = source_font.post().context("Source font has no post table")?;
+
+    rewrite_font(target, |font, builder| {
+        let post = font.post().context("Font has no post table")?;
+        let num_glyphs = font.maxp()?.num_glyphs();
+
+        let mut names: Vec<String> = (0..num_glyphs)
+            .map(|gid| {
+                post.glyph_name(GlyphId16::new(gid))
+                    .map_or_else(|| format!("glyph{gid:05}"), str::to_string)
+            })
+            .collect();
+        for &(source_gid, target_gid) in gid_map {
+            let name = source_post
+                .glyph_name(GlyphId16::new(source_gid))
+                .with_context(|| format!("Source font has no name for glyph {source_gid}"))?;
+            let slot = names
+                .get_mut(usize::from(target_gid))
+                .with_context(|| format!("Target font has no glyph {target_gid}"))?;
+            *slot = name.to_string();
+        }
+
+        let original: Post = post.to_owned_table();
+        let new_post = Post {
+            italic_angle: original.italic_angle,
+            underline_position: original.underline_position,
+            underline_thickness: original.underline_thickness,
+            is_fixed_pitch: original.is_fixed_pitch,
+            min_mem_type42: original.min_mem_type42,
+            max_mem_type42: original.max_mem_type42,
+            min_mem_type1: original.min_mem_type1,
+            max_mem_type1: original.max_mem_type1,
+            ..Post::new_v2(names.iter().map(String::as_str))
+        };
+        builder.add_table(&new_post)?;
+        Ok(())
+    })
+}
+
+/// Replace `cmap` with one built from `(codepoint, glyph_id)` pairs.
+///
+/// The new table has a format 4 subtable, plus format 12 when any codepoint
+/// is outside the BMP. Every glyph ID must exist in the font, every codepoint
+/// must be a Unicode scalar value, and a codepoint may map to only one glyph.
+pub fn rebuild_cmap(data: &[u8], mappings: &[(u32, u16)]) -> Result<Vec<u8>> {
+    rewrite_font(data, |font, builder| {
+        let num_glyphs = font.maxp()?.num_glyphs();
+        let mappings = mappings
+            .iter()
+            .map(|&(codepoint, gid)| {
+                let ch = char::from_u32(codepoint)
+                    .with_context(|| format!("U+{codepoint:04X} is not a valid codepoint"))?;
+                if gid >= num_glyphs {
+                    bail!("U+{codepoint:04X} maps to glyph {gid}, but the font has {num_glyphs}");
+                }
+                Ok((ch, GlyphId::from(gid)))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let cmap = Cmap::from_mappings(mappings).map_err(|conflict| anyhow!("{conflict}"))?;
+        builder.add_table(&cmap)?;
+        Ok(())
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn weight_names() {
+        assert_eq!(weight_name(100), "Thin");
         assert_eq!(weight_name(300), "Light");
         assert_eq!(weight_name(400), "Regular");
         assert_eq!(weight_name(600), "SemiBold");
@@ -0,0 +1,263 @@
+//! SVG path export of TrueType outlines, for inspecting glyphs by eye.
+
+use anyhow::{Context, Result, bail};
+use kurbo::{Affine, BezPath, Point};
+use read_fonts::{
+    FontRef, TableProvider,
+    tables::{
+        glyf::{Anchor, CompositeGlyphFlags, Glyf, Glyph, SimpleGlyph},
+        loca::Loca,
+    },
+    types::GlyphId,
+};
+
+/// Component nesting limit, guarding against cyclic composites.
+const MAX_COMPONENT_DEPTH: usize = 64;
+
+/// SVG path `d` string of a glyph's outline, in font units with y up.
+///
+/// Composite glyphs are resolved into the contours of their components, with
+/// component transforms and offsets applied. Each contour becomes one closed
+/// `M…Z` subpath; an empty glyph yields an empty string.
+pub fn glyph_to_svg_path(data: &[u8], gid: u16) -> Result<String> {
+    let font = FontRef::new(data).context("Failed to parse font")?;
+    let glyf = font.glyf().context("Font has no glyf table")?;
+    let loca = font.loca(None).context("Font has no loca table")?;
+    let num_glyphs = font.maxp()?.num_glyphs();
+    if gid >= num_glyphs {
+        bail!("Glyph {gid} is out of range, the font has {num_glyphs}");
+    }
+
+    let mut contours = Vec::new();
+    collect_contours(&glyf, &loca, GlyphId::from(gid), 0, &mut contours)?;
+
+    let mut path = BezPath::new();
+    for contour in &contours {
+        append_contour(&mut path, contour);
+    }
+    Ok(path.to_svg())
+}
+
+/// A contour as `(point, on_curve)` pairs.
+type Contour = Vec<(Point, bool)>;
+
+/// Append the contours of glyph `gid` to `out`, applying component transforms.
+fn collect_contours(
+    glyf: &Glyf,
+    loca: &Loca,
+    gid: GlyphId,
+    depth: usize,
+    out: &mut Vec<Contour>,
+) -> Result<()> {
+    match loca.get_glyf(gid, glyf)? {
+        None => {}
+        Some(Glyph::Simple(simple)) => out.extend(simple_contours(&simple)),
+        Some(Glyph::Composite(composite)) => {
+            if depth >= MAX_COMPONENT_DEPTH {
+                bail!("Glyph {gid} nests components deeper than {MAX_COMPONENT_DEPTH}");
+            }
+            // Point anchors number the points of this glyph's earlier components
+            let first_contour = out.len();
+            for component in composite.components() {
+                let mut child = Vec::new();
+                collect_contours(glyf, loca, component.glyph.into(), depth + 1, &mut child)?;
+
+                let t = component.transform;
+                let [xx, yx, xy, yy] = [t.xx, t.yx, t.xy, t.yy].map(|v| f64::from(v.to_f32()));
+                let linear = Affine::new([xx, yx, xy, yy, 0.0, 0.0]);
+                for (point, _) in child.iter_mut().flatten() {
+                    *point = linear * *point;
+                }
+
+                let offset = match component.anchor {
+                    // A scaled offset is transformed along with the outline
+                    Anchor::Offset { x, y }
+                        if component
+                            .flags
+                            .contains(CompositeGlyphFlags::SCALED_COMPONENT_OFFSET) =>
+                    {
+                        (linear * Point::new(f64::from(x), f64::from(y))).to_vec2()
+                    }
+                    Anchor::Offset { x, y } => (f64::from(x), f64::from(y)).into(),
+                    Anchor::Point { base, component } => {
+                        let base = out[first_contour..].iter().flatten().nth(usize::from(base));
+                        let own = child.iter().flatten().nth(usize::from(component));
+                        match (base, own) {
+                            (Some((base, _)), Some((own, _))) => *base - *own,
+                            _ => bail!("Glyph {gid} anchors to a point it does not have"),
+                        }
+                    }
+                };
+                for (point, _) in child.iter_mut().flatten() {
+                    *point += offset;
+                }
+                out.extend(child);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn simple_contours(simple: &SimpleGlyph) -> Vec<Contour> {
+    let mut points = simple.points();
+    let mut start = 0;
+    simple
+        .end_pts_of_contours()
+        .iter()
+        .map(|end| {
+            let end = usize::from(end.get());
+            let contour = points
+                .by_ref()
+                .take((end + 1).saturating_sub(start))
+                .map(|pt| ((f64::from(pt.x), f64::from(pt.y)).into(), pt.on_curve))
+                .collect();
+            start = end + 1;
+            contour
+        })
+        .collect()
+}
+
+/// Append one closed quadratic contour, inserting the on-curve points implied
+/// between consecutive off-curve points.
+fn append_contour(path: &mut BezPath, contour: &[(Point, bool)]) {
+    let Some(first_on) = contour.iter().position(|&(_, on_curve)| on_curve) else {
+        // All off-curve: start at the midpoint of the first two points
+        let Some(&(first, _)) = contour.first() else {
+            return;
+        };
+        let second = contour.get(1).map_or(first, |&(point, _)| point);
+        let start = first.midpoint(second);
+        path.move_to(start);
+        append_segments(path, contour.iter().cycle().skip(1).take(contour.len()), start);
+        return;
+    };
+
+    let start = contour[first_on].0;
+    path.move_to(start);
+    let rest = contour.iter().cycle().skip(first_on + 1).take(contour.len() - 1);
+    append_segments(path, rest, start);
+}
+
+/// Draw segments through `points`, then back to the on-curve `start`.
+fn append_segments<'a>(
+    path: &mut BezPath,
+    points: impl Iterator<Item = &'a (Point, bool)>,
+    start: Point,
+) {
+    let mut control: Option<Point> = None;
+    for &(point, on_curve) in points {
+        match (on_curve, control) {
+            (true, None) => path.line_to(point),
+            (true, Some(ctrl)) => {
+                path.quad_to(ctrl, point);
+                control = None;
+            }
+            (false, None) => control = Some(point),
+            (false, Some(ctrl)) => {
+                path.quad_to(ctrl, ctrl.midpoint(point));
+                control = Some(point);
+            }
+        }
+    }
+    if let Some(ctrl) = control {
+        path.quad_to(ctrl, start);
+    }
+    path.close_path();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn composite_path_has_every_component_contour() {
+        let font = font_test_data::VAZIRMATN_VAR;
+        let simple = glyph_to_svg_path(font, 1).unwrap();
+        let composite = glyph_to_svg_path(font, 2).unwrap();
+
+        assert!(simple.starts_with('M'), "{simple}");
+        assert!(composite.starts_with('M'), "{composite}");
+        // Glyph 2 places glyph 1 (3 contours) and glyph 3 (1 contour)
+        assert_eq!(simple.matches('M').count(), 3);
+        assert_eq!(composite.matches('M').count(), 4);
+        assert_eq!(composite.matches('Z').count(), 4);
+        assert!(composite.starts_with(&simple));
+
+        assert_eq!(glyph_to_svg_path(font, 0).unwrap(), "");
+        assert!(glyph_to_svg_path(font, 99).is_err());
+    }
+
+    #[test]
+    fn scaled_component_offset_is_transformed() {
+        use read_fonts::{
+            tables::glyf::{CurvePoint, Transform},
+            types::{F2Dot14, GlyphId16},
+        };
+        use write_fonts::{
+            FontBuilder,
+            tables::{
+                glyf::{
+                    Bbox, Component, ComponentFlags, CompositeGlyph, GlyfLocaBuilder,
+                    Glyph as WriteGlyph, SimpleGlyph as WriteSimpleGlyph,
+                },
+                head::Head,
+                maxp::Maxp,
+            },
+        };
+
+        let square = [(0, 0), (100, 0), (100, 100), (0, 100)].map(|(x, y)| CurvePoint {
+            x,
+            y,
+            on_curve: true,
+        });
+        let square = WriteSimpleGlyph {
+            bbox: Bbox { x_min: 0, y_min: 0, x_max: 100, y_max: 100 },
+            contours: vec![square.to_vec().into()],
+            instructions: vec![],
+        };
+        let half = F2Dot14::from_f32(0.5);
+        let composite = CompositeGlyph::new(
+            Component::new(
+                GlyphId16::new(1),
+                Anchor::Offset { x: 200, y: 0 },
+                Transform { xx: half, yy: half, ..Default::default() },
+                ComponentFlags {
+                    scaled_component_offset: true,
+                    ..Default::default()
+                },
+            ),
+            Bbox::default(),
+        );
+
+        let mut glyphs = GlyfLocaBuilder::new();
+        glyphs.add_glyph(&WriteGlyph::Empty).unwrap();
+        glyphs.add_glyph(&WriteGlyph::Simple(square)).unwrap();
+        glyphs.add_glyph(&WriteGlyph::Composite(composite)).unwrap();
+        let (glyf, loca, format) = glyphs.build();
+        let mut builder = FontBuilder::new();
+        builder.add_table(&glyf).unwrap();
+        builder.add_table(&loca).unwrap();
+        builder
+            .add_table(&Head {
+                index_to_loc_format: format as i16,
+                ..Default::default()
+            })
+            .unwrap();
+        builder.add_table(&Maxp::new(3)).unwrap();
+        let data = builder.build();
+
+        let font = FontRef::new(&data).unwrap();
+        let mut contours = Vec::new();
+        collect_contours(
+            &font.glyf().unwrap(),
+            &font.loca(None).unwrap(),
+            GlyphId::new(2),
+            0,
+            &mut contours,
+        )
+        .unwrap();
+        // The offset is halved along with the square
+        let points: Vec<_> = contours.iter().flatten().map(|(pt, _)| (pt.x, pt.y)).collect();
+        assert_eq!(points, [(100.0, 0.0), (150.0, 0.0), (150.0, 50.0), (100.0, 50.0)]);
+    }
+}
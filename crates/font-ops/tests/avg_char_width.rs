@@ -0,0 +1,29 @@
+//! Tests for `recompute_avg_char_width`: OS/2 xAvgCharWidth from hmtx advances.
+
+use read_fonts::{FontRef, TableProvider, types::GlyphId};
+use warpnine_font_ops::{AvgCharWidthMethod, recompute_avg_char_width};
+
+const FIXTURE: &[u8] = include_bytes!("fixtures/OpenSans-Bold.subset.ttf");
+
+#[test]
+fn all_glyphs_matches_manual_average() {
+    let font = FontRef::new(FIXTURE).unwrap();
+    let hmtx = font.hmtx().unwrap();
+    let advances: Vec<u32> = (0..font.maxp().unwrap().num_glyphs())
+        .filter_map(|gid| hmtx.advance(GlyphId::from(gid)))
+        .filter(|&width| width != 0)
+        .map(u32::from)
+        .collect();
+    let expected = (advances.iter().sum::<u32>() as f64 / advances.len() as f64).round() as i16;
+
+    let result = recompute_avg_char_width(FIXTURE, AvgCharWidthMethod::AllGlyphs).unwrap();
+    let os2 = FontRef::new(&result).unwrap().os2().unwrap();
+    assert_eq!(os2.x_avg_char_width(), expected);
+}
+
+#[test]
+fn weighted_requires_every_weighted_letter() {
+    // The subset fixture lacks most lowercase letters.
+    let err = recompute_avg_char_width(FIXTURE, AvgCharWidthMethod::Weighted).unwrap_err();
+    assert!(err.to_string().starts_with("Font has no glyph for"), "{err}");
+}
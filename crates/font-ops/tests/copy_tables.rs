@@ -0,0 +1,37 @@
+//! Tests for `copy_tables`.
+
+use read_fonts::{FontRef, types::Tag};
+use warpnine_font_ops::{copy_table, copy_tables, remove_tables};
+
+const FIXTURE: &[u8] = include_bytes!("fixtures/OpenSans-Bold.subset.ttf");
+
+fn table<'a>(font: &'a FontRef, tag: Tag) -> Option<&'a [u8]> {
+    font.table_data(tag).map(|data| data.as_bytes())
+}
+
+#[test]
+fn tables_are_copied_in_one_pass() {
+    let gpos = Tag::new(b"GPOS");
+    let post = Tag::new(b"post");
+    let target = remove_tables(FIXTURE, &[gpos, post]).unwrap();
+
+    let result = copy_tables(FIXTURE, &target, &[gpos, post]).unwrap();
+    let source = FontRef::new(FIXTURE).unwrap();
+    let font = FontRef::new(&result).unwrap();
+    for record in source.table_directory.table_records() {
+        let tag = record.tag();
+        assert_eq!(table(&font, tag), table(&source, tag), "{tag}");
+    }
+
+    let one_by_one = copy_table(FIXTURE, &copy_table(FIXTURE, &target, gpos).unwrap(), post);
+    assert_eq!(result, one_by_one.unwrap());
+}
+
+#[test]
+fn missing_source_table_is_an_error() {
+    let gpos = Tag::new(b"GPOS");
+    let source = remove_tables(FIXTURE, &[gpos]).unwrap();
+
+    let err = copy_tables(&source, FIXTURE, &[Tag::new(b"post"), gpos]).unwrap_err();
+    assert!(err.to_string().contains("GPOS"), "{err}");
+}
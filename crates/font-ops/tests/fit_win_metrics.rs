@@ -0,0 +1,48 @@
+//! Tests for `fit_win_metrics`: OS/2 usWinAscent/usWinDescent cover all glyph bounds.
+
+use read_fonts::{FontRef, TableProvider, types::Tag};
+use warpnine_font_ops::{fit_win_metrics, rewrite_font};
+
+const FIXTURE: &[u8] = include_bytes!("fixtures/OpenSans-Bold.subset.ttf");
+
+/// Raise the bbox yMax of `gid` in the `glyf` header to `y_max`.
+fn with_tall_glyph(data: &[u8], gid: usize, y_max: i16) -> Vec<u8> {
+    rewrite_font(data, |font, builder| {
+        let loca = font.loca(None)?;
+        let start = loca.get_raw(gid).unwrap() as usize;
+        let mut glyf = font.table_data(Tag::new(b"glyf")).unwrap().as_bytes().to_vec();
+        // numberOfContours, xMin, yMin, xMax precede yMax in the glyph header.
+        glyf[start + 8..start + 10].copy_from_slice(&y_max.to_be_bytes());
+        builder.add_raw(Tag::new(b"glyf"), glyf);
+        Ok(())
+    })
+    .unwrap()
+}
+
+#[test]
+fn win_ascent_covers_tall_glyph() {
+    let font = FontRef::new(FIXTURE).unwrap();
+    let os2 = font.os2().unwrap();
+    let tall = i16::try_from(os2.us_win_ascent()).unwrap() + 500;
+
+    let data = with_tall_glyph(FIXTURE, 1, tall);
+    let result = fit_win_metrics(&data).unwrap();
+
+    let os2 = FontRef::new(&result).unwrap().os2().unwrap();
+    assert!(i32::from(os2.us_win_ascent()) >= i32::from(tall));
+}
+
+#[test]
+fn win_descent_covers_lowest_glyph() {
+    let result = fit_win_metrics(FIXTURE).unwrap();
+    let font = FontRef::new(&result).unwrap();
+    let glyf = font.glyf().unwrap();
+    let loca = font.loca(None).unwrap();
+
+    let y_min = (0..font.maxp().unwrap().num_glyphs())
+        .filter_map(|gid| loca.get_glyf(gid.into(), &glyf).unwrap())
+        .map(|glyph| glyph.y_min())
+        .min()
+        .unwrap();
+    assert_eq!(i32::from(font.os2().unwrap().us_win_descent()), -i32::from(y_min.min(0)));
+}
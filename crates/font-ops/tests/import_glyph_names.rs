@@ -0,0 +1,34 @@
+//! Tests for `import_glyph_names`: copying post glyph names between fonts.
+
+use read_fonts::{FontRef, TableProvider, types::GlyphId16};
+use warpnine_font_ops::import_glyph_names;
+
+const FIXTURE: &[u8] = include_bytes!("fixtures/OpenSans-Bold.subset.ttf");
+
+fn glyph_name(data: &[u8], gid: u16) -> Option<String> {
+    let font = FontRef::new(data).unwrap();
+    font.post()
+        .unwrap()
+        .glyph_name(GlyphId16::new(gid))
+        .map(str::to_string)
+}
+
+#[test]
+fn imports_mapped_names() {
+    let (first, second) = (glyph_name(FIXTURE, 1).unwrap(), glyph_name(FIXTURE, 2).unwrap());
+    assert_ne!(first, second);
+
+    // Swap the names of glyphs 1 and 2.
+    let result = import_glyph_names(FIXTURE, FIXTURE, &[(1, 2), (2, 1)]).unwrap();
+
+    assert_eq!(glyph_name(&result, 1), Some(second));
+    assert_eq!(glyph_name(&result, 2), Some(first));
+    assert_eq!(glyph_name(&result, 0), glyph_name(FIXTURE, 0));
+}
+
+#[test]
+fn rejects_out_of_range_target() {
+    let num_glyphs = FontRef::new(FIXTURE).unwrap().maxp().unwrap().num_glyphs();
+    let err = import_glyph_names(FIXTURE, FIXTURE, &[(1, num_glyphs)]).unwrap_err();
+    assert!(err.to_string().starts_with("Target font has no glyph"), "{err}");
+}
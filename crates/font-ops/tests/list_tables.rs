@@ -0,0 +1,28 @@
+//! Tests for `list_tables`.
+
+use read_fonts::{FontRef, types::Tag};
+use warpnine_font_ops::{list_tables, remove_table};
+
+const FIXTURE: &[u8] = include_bytes!("fixtures/OpenSans-Bold.subset.ttf");
+
+#[test]
+fn tables_are_sorted_with_lengths() {
+    let tables = list_tables(FIXTURE).unwrap();
+    let font = FontRef::new(FIXTURE).unwrap();
+    assert_eq!(tables.len(), font.table_directory.num_tables() as usize);
+    assert!(tables.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    for (tag, length) in &tables {
+        assert_eq!(*length as usize, font.table_data(*tag).unwrap().len(), "{tag}");
+    }
+}
+
+#[test]
+fn removed_table_is_not_listed() {
+    let gpos = Tag::new(b"GPOS");
+    let result = remove_table(FIXTURE, gpos).unwrap();
+    let before = list_tables(FIXTURE).unwrap();
+    let after = list_tables(&result).unwrap();
+    assert!(before.iter().any(|&(tag, _)| tag == gpos));
+    assert_eq!(after.len(), before.len() - 1);
+    assert!(after.iter().all(|&(tag, _)| tag != gpos));
+}
@@ -0,0 +1,30 @@
+//! Tests for `rebuild_cmap`: replacing cmap from codepoint→glyph pairs.
+
+use read_fonts::{FontRef, TableProvider, types::GlyphId};
+use warpnine_font_ops::rebuild_cmap;
+
+const FIXTURE: &[u8] = include_bytes!("fixtures/OpenSans-Bold.subset.ttf");
+
+#[test]
+fn new_mappings_replace_old_ones() {
+    let cmap = FontRef::new(FIXTURE).unwrap().cmap().unwrap();
+    let old = cmap.map_codepoint('a').expect("fixture maps 'a'");
+    let gid = u16::try_from(old.to_u32()).unwrap();
+
+    let result = rebuild_cmap(FIXTURE, &[(0x7A, gid), (0x1F600, 1)]).unwrap();
+    let font = FontRef::new(&result).unwrap();
+    let cmap = font.cmap().unwrap();
+    assert_eq!(cmap.map_codepoint('z'), Some(old));
+    assert_eq!(cmap.map_codepoint('\u{1F600}'), Some(GlyphId::new(1)));
+    assert_eq!(cmap.map_codepoint('a'), None);
+}
+
+#[test]
+fn out_of_range_glyph_is_rejected() {
+    let num_glyphs = FontRef::new(FIXTURE).unwrap().maxp().unwrap().num_glyphs();
+    let err = rebuild_cmap(FIXTURE, &[(0x41, num_glyphs)]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!("U+0041 maps to glyph {num_glyphs}, but the font has {num_glyphs}")
+    );
+}
@@ -0,0 +1,38 @@
+//! Tests for `remove_table` and `remove_tables`.
+
+use read_fonts::{FontRef, types::Tag};
+use warpnine_font_ops::{remove_table, remove_tables};
+
+const FIXTURE: &[u8] = include_bytes!("fixtures/OpenSans-Bold.subset.ttf");
+
+fn tags(data: &[u8]) -> Vec<Tag> {
+    let font = FontRef::new(data).unwrap();
+    font.table_directory.table_records().iter().map(|r| r.tag()).collect()
+}
+
+#[test]
+fn named_tables_are_removed() {
+    let gpos = Tag::new(b"GPOS");
+    let post = Tag::new(b"post");
+    let before = tags(FIXTURE);
+    assert!(before.contains(&gpos) && before.contains(&post));
+
+    let result = remove_table(FIXTURE, gpos).unwrap();
+    let expected: Vec<Tag> = before.iter().copied().filter(|&t| t != gpos).collect();
+    assert_eq!(tags(&result), expected);
+
+    let result = remove_tables(FIXTURE, &[gpos, post, Tag::new(b"DSIG")]).unwrap();
+    let expected: Vec<Tag> = before.into_iter().filter(|&t| t != gpos && t != post).collect();
+    assert_eq!(tags(&result), expected);
+}
+
+#[test]
+fn missing_table_keeps_the_font() {
+    let result = remove_table(FIXTURE, Tag::new(b"DSIG")).unwrap();
+    assert_eq!(tags(&result), tags(FIXTURE));
+    let font = FontRef::new(&result).unwrap();
+    for tag in tags(FIXTURE) {
+        let original = FontRef::new(FIXTURE).unwrap().table_data(tag).unwrap();
+        assert_eq!(font.table_data(tag).unwrap().as_bytes(), original.as_bytes(), "{tag}");
+    }
+}
@@ -0,0 +1,108 @@
+//! Tests for `validate_layout`: dangling GSUB/GPOS references.
+
+use read_fonts::{FontRef, TableProvider, types::Tag};
+use warpnine_font_ops::{LayoutIssue, validate_layout};
+use write_fonts::{
+    FontBuilder,
+    tables::{
+        gsub::{Gsub, SingleSubst, SubstitutionLookup, SubstitutionLookupList},
+        layout::{
+            CoverageTable, Feature, FeatureList, FeatureRecord, LangSys, Lookup, LookupFlag,
+            Script, ScriptList, ScriptRecord,
+        },
+    },
+    types::GlyphId16,
+};
+
+const FIXTURE: &[u8] = include_bytes!("fixtures/OpenSans-Bold.subset.ttf");
+
+/// The fixture with a GSUB whose `latn` default language lists feature
+/// `feature_index` and whose `smcp` feature points at `lookup_index`; the
+/// only feature is 0 and the only lookup is 0, substituting `glyph` with the
+/// glyph after it.
+fn font_with_gsub(feature_index: u16, lookup_index: u16, glyph: u16) -> Vec<u8> {
+    let gsub = Gsub::new(
+        ScriptList::new(vec![ScriptRecord::new(
+            Tag::new(b"latn"),
+            Script::new(Some(LangSys::new(vec![feature_index])), vec![]),
+        )]),
+        FeatureList::new(vec![FeatureRecord::new(
+            Tag::new(b"smcp"),
+            Feature::new(None, vec![lookup_index]),
+        )]),
+        SubstitutionLookupList::new(vec![SubstitutionLookup::Single(Lookup::new(
+            LookupFlag::default(),
+            vec![SingleSubst::format_1(CoverageTable::format_1(vec![GlyphId16::new(glyph)]), 1)],
+        ))]),
+    );
+    let mut builder = FontBuilder::new();
+    builder.add_table(&gsub).unwrap();
+    builder.copy_missing_tables(FontRef::new(FIXTURE).unwrap());
+    builder.build()
+}
+
+#[test]
+fn well_formed_font_has_no_issues() {
+    assert_eq!(validate_layout(FIXTURE).unwrap(), []);
+    assert_eq!(validate_layout(&font_with_gsub(0, 0, 1)).unwrap(), []);
+}
+
+#[test]
+fn out_of_range_lookup_index_is_reported() {
+    let issues = validate_layout(&font_with_gsub(0, 1, 1)).unwrap();
+    assert_eq!(
+        issues,
+        [LayoutIssue::LookupIndex {
+            table: Tag::new(b"GSUB"),
+            feature: Tag::new(b"smcp"),
+            lookup_index: 1,
+            lookup_count: 1,
+        }]
+    );
+    assert_eq!(
+        issues[0].to_string(),
+        "GSUB feature 'smcp' references lookup 1, but the lookup list has 1"
+    );
+}
+
+#[test]
+fn out_of_range_feature_index_is_reported() {
+    let issues = validate_layout(&font_with_gsub(3, 0, 1)).unwrap();
+    assert_eq!(
+        issues,
+        [LayoutIssue::FeatureIndex {
+            table: Tag::new(b"GSUB"),
+            script: Tag::new(b"latn"),
+            lang_sys: None,
+            feature_index: 3,
+            feature_count: 1,
+        }]
+    );
+    assert_eq!(
+        issues[0].to_string(),
+        "GSUB script 'latn' language default references feature 3, but the feature list has 1"
+    );
+}
+
+#[test]
+fn out_of_range_coverage_glyph_is_reported() {
+    let num_glyphs = FontRef::new(FIXTURE).unwrap().maxp().unwrap().num_glyphs();
+    let issues = validate_layout(&font_with_gsub(0, 0, num_glyphs)).unwrap();
+    assert_eq!(
+        issues,
+        [LayoutIssue::CoverageGlyph {
+            table: Tag::new(b"GSUB"),
+            lookup_index: 0,
+            glyph: GlyphId16::new(num_glyphs),
+            num_glyphs,
+        }]
+    );
+}
+
+#[test]
+fn unparsable_table_is_an_error() {
+    let mut builder = FontBuilder::new();
+    builder.add_raw(Tag::new(b"GSUB"), vec![0, 1, 0]);
+    builder.copy_missing_tables(FontRef::new(FIXTURE).unwrap());
+    assert!(validate_layout(&builder.build()).is_err());
+}
@@ -22,8 +22,26 @@
 //! let subset = subset_japanese(font_data);
 //! ```
 
-use anyhow::Result;
-use hb_subset::{Blob, FontFace, SubsetInput, Tag};
+mod shape;
+
+use std::collections::{BTreeSet, HashSet};
+
+use anyhow::{Context, Result, bail};
+use hb_subset::{Blob, FontFace, SubsetInput, Tag, sys};
+use read_fonts::{
+    FontRef, TableProvider, TopLevelTable,
+    tables::{
+        glyf::Glyph,
+        name::{Name, NameRecord},
+    },
+    types::{GlyphId, NameId, Offset16},
+};
+use skrifa::{GlyphNameSource, MetadataProvider};
+use write_fonts::{
+    FontBuilder,
+    from_obj::ToOwnedTable,
+    tables::cmap::{Cmap, CmapSubtable, EncodingRecord, PlatformId},
+};
 
 /// Variable font tables to drop during subsetting.
 ///
@@ -32,6 +50,12 @@ use hb_subset::{Blob, FontFace, SubsetInput, Tag};
 pub const VF_TABLES_TO_DROP: &[&[u8; 4]] =
     &[b"HVAR", b"MVAR", b"STAT", b"avar", b"fvar", b"gvar", b"cvar"];
 
+/// Layout tables to drop during subsetting.
+///
+/// Fonts reached only through cmap, such as icon fonts, never shape text, so
+/// their layout tables are dead weight.
+pub const LAYOUT_TABLES_TO_DROP: &[&[u8; 4]] = &[b"GSUB", b"GPOS", b"GDEF"];
+
 /// Japanese Unicode ranges for subsetting.
 ///
 /// Includes:
@@ -67,6 +91,24 @@ pub const JAPANESE_RANGES: &[(u32, u32)] = &[
     (0x2F800, 0x2FA1F),
 ];
 
+/// Korean Unicode ranges for subsetting.
+///
+/// Includes:
+/// - Hangul Jamo (U+1100-U+11FF)
+/// - CJK Symbols and Punctuation (U+3000-U+303F)
+/// - Hangul Compatibility Jamo (U+3130-U+318F)
+/// - Hangul Jamo Extended-A (U+A960-U+A97F)
+/// - Hangul Syllables (U+AC00-U+D7A3)
+/// - Hangul Jamo Extended-B (U+D7B0-U+D7FF)
+pub const KOREAN_RANGES: &[(u32, u32)] = &[
+    (0x1100, 0x11FF),
+    (0x3000, 0x303F),
+    (0x3130, 0x318F),
+    (0xA960, 0xA97F),
+    (0xAC00, 0xD7A3),
+    (0xD7B0, 0xD7FF),
+];
+
 /// Symbol ranges useful for terminal/programming applications.
 ///
 /// These ranges contain characters commonly used in terminal UIs, mathematical
@@ -106,6 +148,91 @@ pub const LAYOUT_FEATURES: &[&[u8; 4]] = &[
     b"nlck", b"pwid", b"vert", b"vjmo", b"vrt2", b"halt", b"vhal", b"kern", b"mark", b"mkmk",
 ];
 
+/// Unicode codepoints of Mac Roman bytes 0x80-0xFF; lower bytes match ASCII.
+const MAC_ROMAN_HIGH: [u32; 128] = [
+    0x00C4, 0x00C5, 0x00C7, 0x00C9, 0x00D1, 0x00D6, 0x00DC, 0x00E1, 0x00E0, 0x00E2, 0x00E4, 0x00E3,
+    0x00E5, 0x00E7, 0x00E9, 0x00E8, 0x00EA, 0x00EB, 0x00ED, 0x00EC, 0x00EE, 0x00EF, 0x00F1, 0x00F3,
+    0x00F2, 0x00F4, 0x00F6, 0x00F5, 0x00FA, 0x00F9, 0x00FB, 0x00FC, 0x2020, 0x00B0, 0x00A2, 0x00A3,
+    0x00A7, 0x2022, 0x00B6, 0x00DF, 0x00AE, 0x00A9, 0x2122, 0x00B4, 0x00A8, 0x2260, 0x00C6, 0x00D8,
+    0x221E, 0x00B1, 0x2264, 0x2265, 0x00A5, 0x00B5, 0x2202, 0x2211, 0x220F, 0x03C0, 0x222B, 0x00AA,
+    0x00BA, 0x03A9, 0x00E6, 0x00F8, 0x00BF, 0x00A1, 0x00AC, 0x221A, 0x0192, 0x2248, 0x2206, 0x00AB,
+    0x00BB, 0x2026, 0x00A0, 0x00C0, 0x00C3, 0x00D5, 0x0152, 0x0153, 0x2013, 0x2014, 0x201C, 0x201D,
+    0x2018, 0x2019, 0x00F7, 0x25CA, 0x00FF, 0x0178, 0x2044, 0x20AC, 0x2039, 0x203A, 0xFB01, 0xFB02,
+    0x2021, 0x00B7, 0x201A, 0x201E, 0x2030, 0x00C2, 0x00CA, 0x00C1, 0x00CB, 0x00C8, 0x00CD, 0x00CE,
+    0x00CF, 0x00CC, 0x00D3, 0x00D4, 0xF8FF, 0x00D2, 0x00DA, 0x00DB, 0x00D9, 0x0131, 0x02C6, 0x02DC,
+    0x00AF, 0x02D8, 0x02D9, 0x02DA, 0x00B8, 0x02DD, 0x02DB, 0x02C7,
+];
+
+/// Summary of a subset font, as returned by [`Subsetter::subset_describe`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubsetDescription {
+    /// Whether the subset still carries an `fvar` table.
+    pub is_variable: bool,
+    /// Axis tags that survived subsetting, in `fvar` order.
+    pub axes: Vec<[u8; 4]>,
+    /// Number of glyphs in the subset (`maxp.numGlyphs`).
+    pub glyph_count: u16,
+    /// Number of codepoints mapped by the subset's `cmap`.
+    pub codepoint_count: usize,
+}
+
+impl SubsetDescription {
+    /// Describes already-subset font data.
+    pub fn from_font(data: &[u8]) -> Result<Self> {
+        let font = FontRef::new(data)?;
+        let axes: Vec<[u8; 4]> = match font.fvar() {
+            Ok(fvar) => fvar
+                .axes()?
+                .iter()
+                .map(|axis| axis.axis_tag().to_be_bytes())
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        Ok(Self {
+            is_variable: font.fvar().is_ok(),
+            axes,
+            glyph_count: font.maxp()?.num_glyphs(),
+            codepoint_count: font.charmap().mappings().count(),
+        })
+    }
+}
+
+/// What a subset kept, as returned by [`Subsetter::subset_with_report`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubsetReport {
+    /// Number of glyphs in the input font.
+    pub input_glyph_count: u16,
+    /// Number of glyphs in the subset.
+    pub output_glyph_count: u16,
+    /// Number of codepoints mapped by the subset's `cmap`.
+    pub codepoint_count: usize,
+    /// GSUB/GPOS feature tags of the input that the subset no longer has.
+    pub dropped_features: Vec<[u8; 4]>,
+}
+
+/// What a subset would keep, as returned by [`Subsetter::explain`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubsetPlan {
+    /// Tables of the input font that hb-subset will keep.
+    pub retained_tables: Vec<[u8; 4]>,
+    /// Tables of the input font that hb-subset will drop, including those in
+    /// its own default drop list.
+    pub dropped_tables: Vec<[u8; 4]>,
+    /// `fvar` axis tags that survive, empty when `fvar` is dropped.
+    pub axes: Vec<[u8; 4]>,
+    /// Configured Unicode ranges, in the order they were added.
+    pub unicode_ranges: Vec<(u32, u32)>,
+    /// Number of codepoints requested, after exclusions; the font need not
+    /// map all of them.
+    pub unicode_count: usize,
+    /// GSUB/GPOS feature tags of the input that are in the retained set.
+    pub features: Vec<[u8; 4]>,
+    /// Whether glyph names are retained.
+    pub retain_glyph_names: bool,
+    /// Whether glyph IDs are kept in place rather than renumbered.
+    pub retain_gids: bool,
+}
+
 /// Font subsetter with builder pattern.
 ///
 /// Provides a flexible way to configure font subsetting options before
@@ -113,10 +240,19 @@ pub const LAYOUT_FEATURES: &[&[u8; 4]] = &[
 #[derive(Default)]
 pub struct Subsetter {
     unicode_ranges: Vec<(u32, u32)>,
+    codepoints: BTreeSet<u32>,
+    shaped_texts: Vec<(String, Vec<[u8; 4]>)>,
+    glyph_ids: Vec<u32>,
     exclude_codepoints: Vec<u32>,
     drop_vf_tables: bool,
+    drop_layout: bool,
     retain_glyph_names: bool,
+    drop_hints: bool,
+    desubroutinize: bool,
+    keep_mac_cmap: bool,
     layout_features: Vec<[u8; 4]>,
+    max_extra_glyphs: Option<usize>,
+    name_suffix: Option<String>,
 }
 
 impl Subsetter {
@@ -143,10 +279,28 @@ impl Subsetter {
         ranges.extend_from_slice(SYMBOL_RANGES);
         Self {
             unicode_ranges: ranges,
-            exclude_codepoints: Vec::new(),
             drop_vf_tables: true,
             retain_glyph_names: true,
-            layout_features: LAYOUT_FEATURES.iter().map(|f| **f).collect(),
+            ..Self::new()
+        }
+    }
+
+    /// Creates a subsetter pre-configured for Korean font subsetting.
+    ///
+    /// This preset:
+    /// - Uses [`KOREAN_RANGES`] and [`SYMBOL_RANGES`] for Unicode coverage
+    /// - Drops variable font tables
+    /// - Retains glyph names
+    /// - Uses standard [`LAYOUT_FEATURES`], which include `vert`, `vjmo` and `vrt2` for vertical
+    ///   layout
+    pub fn korean() -> Self {
+        let mut ranges = KOREAN_RANGES.to_vec();
+        ranges.extend_from_slice(SYMBOL_RANGES);
+        Self {
+            unicode_ranges: ranges,
+            drop_vf_tables: true,
+            retain_glyph_names: true,
+            ..Self::new()
         }
     }
 
@@ -160,10 +314,7 @@ impl Subsetter {
     pub fn box_drawing() -> Self {
         Self {
             unicode_ranges: vec![(0x2500, 0x257F)],
-            exclude_codepoints: Vec::new(),
-            drop_vf_tables: false,
-            retain_glyph_names: false,
-            layout_features: LAYOUT_FEATURES.iter().map(|f| **f).collect(),
+            ..Self::new()
         }
     }
 
@@ -175,6 +326,39 @@ impl Subsetter {
         self
     }
 
+    /// Adds every character of `text` to the subset.
+    ///
+    /// Characters are taken as Unicode scalar values, so astral characters
+    /// such as emoji count once rather than as two surrogates. Repeated
+    /// characters are deduplicated, and an empty string adds nothing.
+    pub fn with_text(mut self, text: &str) -> Self {
+        self.codepoints.extend(text.chars().map(u32::from));
+        self
+    }
+
+    /// Adds `text` and the glyphs HarfBuzz produces when shaping it.
+    ///
+    /// Like [`with_text`](Self::with_text), plus the output glyphs of
+    /// shaping `text` with `features` enabled on top of the defaults, such as
+    /// the `fi` ligature for `"fi"` with `liga`. These are kept even when the
+    /// feature itself is not among the retained
+    /// [layout features](Self::with_layout_features).
+    pub fn with_shaped_text(mut self, text: &str, features: &[[u8; 4]]) -> Self {
+        self.codepoints.extend(text.chars().map(u32::from));
+        self.shaped_texts.push((text.to_string(), features.to_vec()));
+        self
+    }
+
+    /// Adds glyph IDs to keep in the subset.
+    ///
+    /// These are kept in addition to the glyphs reached from the Unicode
+    /// ranges, even when no `cmap` entry points at them, so a subset can be
+    /// described by glyph IDs alone.
+    pub fn with_glyph_ids(mut self, glyph_ids: impl IntoIterator<Item = u16>) -> Self {
+        self.glyph_ids.extend(glyph_ids.into_iter().map(u32::from));
+        self
+    }
+
     /// Adds codepoints to exclude from the subset.
     ///
     /// These codepoints will be removed even if they fall within the Unicode ranges.
@@ -194,6 +378,16 @@ impl Subsetter {
         self
     }
 
+    /// Sets whether to drop the layout tables.
+    ///
+    /// When `true`, `GSUB`, `GPOS`, and `GDEF` are removed entirely, leaving a
+    /// font whose glyphs are reachable only through `cmap`. This is independent
+    /// of [`drop_vf_tables`](Self::drop_vf_tables).
+    pub fn drop_layout(mut self, drop: bool) -> Self {
+        self.drop_layout = drop;
+        self
+    }
+
     /// Sets whether to retain glyph names in the subset.
     ///
     /// Glyph names can be useful for debugging but increase file size.
@@ -202,6 +396,36 @@ impl Subsetter {
         self
     }
 
+    /// Sets whether to strip hinting from the subset.
+    ///
+    /// Maps to `HB_SUBSET_FLAGS_NO_HINTING`: TrueType instructions (`fpgm`,
+    /// `prep`, `cvt `, per-glyph programs) and CFF hints are removed, which
+    /// typically shrinks hinted TrueType fonts noticeably.
+    pub fn drop_hints(mut self, drop: bool) -> Self {
+        self.drop_hints = drop;
+        self
+    }
+
+    /// Sets whether to desubroutinize CFF charstrings.
+    ///
+    /// Maps to `HB_SUBSET_FLAGS_DESUBROUTINIZE`. Only affects CFF/CFF2 fonts;
+    /// the raw output grows, but it usually compresses better as WOFF2.
+    pub fn desubroutinize(mut self, desubroutinize: bool) -> Self {
+        self.desubroutinize = desubroutinize;
+        self
+    }
+
+    /// Sets whether to add a Mac Roman `cmap` subtable to the subset.
+    ///
+    /// hb-subset drops the (1, 0) subtable. When `true`, a format 0 one is
+    /// rebuilt from the subset's Unicode mappings for every Mac Roman
+    /// character the subset still covers. Format 0 stores 8-bit glyph IDs, so
+    /// characters whose glyph ID exceeds 255 map to `.notdef`.
+    pub fn keep_mac_cmap(mut self, keep: bool) -> Self {
+        self.keep_mac_cmap = keep;
+        self
+    }
+
     /// Sets the layout features to retain in the subset.
     ///
     /// Replaces any previously configured layout features.
@@ -210,6 +434,29 @@ impl Subsetter {
         self
     }
 
+    /// Caps how many glyphs the layout closure may add.
+    ///
+    /// The glyphs reached directly are `.notdef`, the `cmap` targets of the
+    /// requested codepoints, the glyph IDs from
+    /// [`with_glyph_ids`](Self::with_glyph_ids) and their composite
+    /// components. Subsetting fails if the subset has more than `max` glyphs
+    /// beyond those, so a feature such as `aalt` that pulls in every
+    /// alternate cannot silently bloat the output.
+    pub fn max_extra_glyphs(mut self, max: usize) -> Self {
+        self.max_extra_glyphs = Some(max);
+        self
+    }
+
+    /// Appends a suffix to the subset's family names.
+    ///
+    /// Name IDs 1, 4 and 16 get `" {suffix}"`; the PostScript name (ID 6)
+    /// gets the suffix with spaces removed. This keeps an installed subset
+    /// from colliding with the full font in font menus.
+    pub fn name_suffix(mut self, suffix: Option<String>) -> Self {
+        self.name_suffix = suffix;
+        self
+    }
+
     /// Subsets the font data and returns the result.
     ///
     /// # Arguments
@@ -220,11 +467,65 @@ impl Subsetter {
     ///
     /// The subset font data as a byte vector, or an error if subsetting fails.
     pub fn subset(&self, data: &[u8]) -> Result<Vec<u8>> {
+        self.subset_with_report(data).map(|(subset, _)| subset)
+    }
+
+    /// Subsets the font data and reports what survived.
+    ///
+    /// Both the input and the subset are parsed to fill in the report, so
+    /// callers can check glyph counts without reparsing the output.
+    pub fn subset_with_report(&self, data: &[u8]) -> Result<(Vec<u8>, SubsetReport)> {
+        let subset = self.subset_with(data, self.retain_glyph_names, None)?;
+
+        let input = FontRef::new(data)?;
+        let output = FontRef::new(&subset)?;
+        let kept = feature_tags(&output)?;
+        let report = SubsetReport {
+            input_glyph_count: input.maxp()?.num_glyphs(),
+            output_glyph_count: output.maxp()?.num_glyphs(),
+            codepoint_count: output.charmap().mappings().count(),
+            dropped_features: feature_tags(&input)?.difference(&kept).copied().collect(),
+        };
+        Ok((subset, report))
+    }
+
+    /// Returns whether `codepoint` falls in a configured range or text and is
+    /// not excluded.
+    fn includes(&self, codepoint: u32) -> bool {
+        (self.codepoints.contains(&codepoint)
+            || self
+                .unicode_ranges
+                .iter()
+                .any(|(start, end)| (*start..=*end).contains(&codepoint)))
+            && !self.exclude_codepoints.contains(&codepoint)
+    }
+
+    /// Builds the hb-subset input for this configuration, returning it with
+    /// the glyph IDs requested directly.
+    ///
+    /// With `retain`, the unicode ranges are replaced by the given codepoints
+    /// and glyph IDs.
+    fn subset_input(
+        &self,
+        font: &FontFace,
+        retain_glyph_names: bool,
+        retain: Option<(&[u32], &[u32])>,
+    ) -> Result<(SubsetInput, Vec<u32>)> {
         let mut input = SubsetInput::new()?;
+        let mut glyph_ids = self.glyph_ids.clone();
+        for (text, features) in &self.shaped_texts {
+            glyph_ids.extend(shaped_glyphs(font, text, features));
+        }
 
-        if self.retain_glyph_names {
+        if retain_glyph_names {
             input.flags().retain_glyph_names();
         }
+        if self.drop_hints {
+            input.flags().remove_hinting();
+        }
+        if self.desubroutinize {
+            input.flags().remove_subroutines();
+        }
 
         {
             let mut feature_set = input.layout_feature_tag_set();
@@ -233,10 +534,22 @@ impl Subsetter {
             }
         }
 
-        {
-            let mut unicode_set = input.unicode_set();
-            for (start, end) in &self.unicode_ranges {
-                for cp in *start..=*end {
+        if let Some((codepoints, glyphs)) = retain {
+            {
+                let mut unicode_set = input.unicode_set();
+                for c in codepoints.iter().copied().filter_map(char::from_u32) {
+                    unicode_set.insert(c);
+                }
+            }
+            let mut glyph_set = input.glyph_set();
+            for gid in glyphs {
+                glyph_set.insert(*gid);
+            }
+        } else {
+            {
+                let mut unicode_set = input.unicode_set();
+                let ranges = self.unicode_ranges.iter().flat_map(|(start, end)| *start..=*end);
+                for cp in ranges.chain(self.codepoints.iter().copied()) {
                     if self.exclude_codepoints.contains(&cp) {
                         continue;
                     }
@@ -245,18 +558,123 @@ impl Subsetter {
                     }
                 }
             }
+            let mut glyph_set = input.glyph_set();
+            for gid in &glyph_ids {
+                glyph_set.insert(*gid);
+            }
         }
 
-        if self.drop_vf_tables {
+        {
             let mut drop_tables = input.drop_table_tag_set();
-            for table in VF_TABLES_TO_DROP {
-                drop_tables.insert(Tag::new(*table));
+            if self.drop_vf_tables {
+                for table in VF_TABLES_TO_DROP {
+                    drop_tables.insert(Tag::new(*table));
+                }
+            }
+            if self.drop_layout {
+                for table in LAYOUT_TABLES_TO_DROP {
+                    drop_tables.insert(Tag::new(*table));
+                }
             }
         }
 
+        Ok((input, glyph_ids))
+    }
+
+    /// Runs hb-subset with this configuration; `retain` is as for
+    /// [`subset_input`](Self::subset_input).
+    fn subset_with(
+        &self,
+        data: &[u8],
+        retain_glyph_names: bool,
+        retain: Option<(&[u32], &[u32])>,
+    ) -> Result<Vec<u8>> {
         let font = FontFace::new(Blob::from_bytes(data)?)?;
+        let (input, glyph_ids) = self.subset_input(&font, retain_glyph_names, retain)?;
+
         let subset_font = input.subset_font(&font)?;
-        Ok(subset_font.underlying_blob().to_vec())
+        let subset = subset_font.underlying_blob().to_vec();
+
+        if let Some(max) = self.max_extra_glyphs {
+            let font = FontRef::new(data)?;
+            let direct = match retain {
+                Some((codepoints, glyphs)) => {
+                    direct_glyphs(&font, |cp| codepoints.contains(&cp), glyphs)?
+                }
+                None => direct_glyphs(&font, |cp| self.includes(cp), &glyph_ids)?,
+            };
+            let glyph_count = usize::from(FontRef::new(&subset)?.maxp()?.num_glyphs());
+            let extra = glyph_count.saturating_sub(direct.len());
+            if extra > max {
+                bail!(
+                    "layout closure adds {extra} glyphs beyond the {} reached directly (max {max})",
+                    direct.len()
+                );
+            }
+        }
+
+        let subset = if self.keep_mac_cmap { add_mac_roman_cmap(&subset)? } else { subset };
+        match &self.name_suffix {
+            Some(suffix) => append_name_suffix(&subset, suffix),
+            None => Ok(subset),
+        }
+    }
+
+    /// Subsets the font data and describes what the subset contains.
+    ///
+    /// Useful with `drop_vf_tables(false)` to check which axes survived.
+    pub fn subset_describe(&self, data: &[u8]) -> Result<(Vec<u8>, SubsetDescription)> {
+        let subset = self.subset(data)?;
+        let description = SubsetDescription::from_font(&subset)?;
+        Ok((subset, description))
+    }
+
+    /// Reports what [`subset`](Self::subset) would keep, without subsetting.
+    ///
+    /// The plan is read back from the hb-subset input this configuration
+    /// builds, so it shows hb-subset's own defaults too, such as tables it
+    /// always drops. Useful for working out why a subset lost something.
+    pub fn explain(&self, data: &[u8]) -> Result<SubsetPlan> {
+        let face = FontFace::new(Blob::from_bytes(data)?)?;
+        let (mut input, _) = self.subset_input(&face, self.retain_glyph_names, None)?;
+        let font = FontRef::new(data)?;
+
+        let drop_set: HashSet<[u8; 4]> =
+            input.drop_table_tag_set().iter().map(<[u8; 4]>::from).collect();
+        let (dropped_tables, retained_tables): (Vec<[u8; 4]>, Vec<[u8; 4]>) = font
+            .table_directory
+            .table_records()
+            .iter()
+            .map(|record| record.tag().to_be_bytes())
+            .partition(|tag| drop_set.contains(tag));
+
+        let axes = match font.fvar() {
+            Ok(fvar) if retained_tables.contains(b"fvar") => fvar
+                .axes()?
+                .iter()
+                .map(|axis| axis.axis_tag().to_be_bytes())
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        let feature_set: HashSet<[u8; 4]> =
+            input.layout_feature_tag_set().iter().map(<[u8; 4]>::from).collect();
+        let features = feature_tags(&font)?
+            .into_iter()
+            .filter(|tag| feature_set.contains(tag))
+            .collect();
+
+        let flags = (*input.flags()).0;
+        Ok(SubsetPlan {
+            retained_tables,
+            dropped_tables,
+            axes,
+            unicode_ranges: self.unicode_ranges.clone(),
+            unicode_count: input.unicode_set().len(),
+            features,
+            retain_glyph_names: flags.0 & sys::hb_subset_flags_t::GLYPH_NAMES.0 != 0,
+            retain_gids: flags.0 & sys::hb_subset_flags_t::RETAIN_GIDS.0 != 0,
+        })
     }
 }
 
@@ -275,6 +693,253 @@ pub fn subset_japanese(data: &[u8]) -> Result<Vec<u8>> {
     Subsetter::japanese().subset(data)
 }
 
+/// Subsets each font of a family to a shared set of glyph names.
+///
+/// Every font is first subset with `subsetter`; only the glyph names present
+/// in all of those results are then kept, so the outputs retain the same
+/// name set (barring glyphs pulled back in by layout closure). Glyph names
+/// are always retained, and every font must carry real names (`post` or CFF).
+pub fn subset_family(fonts: &[&[u8]], subsetter: &Subsetter) -> Result<Vec<Vec<u8>>> {
+    let mut shared: Option<HashSet<String>> = None;
+    for (idx, data) in fonts.iter().enumerate() {
+        let subset = subsetter.subset_with(data, true, None)?;
+        let names =
+            glyph_name_set(&subset)?.with_context(|| format!("font {idx} has no glyph names"))?;
+        shared = Some(match shared {
+            None => names,
+            Some(shared) => shared.intersection(&names).cloned().collect(),
+        });
+    }
+    let shared = shared.unwrap_or_default();
+
+    fonts
+        .iter()
+        .map(|data| {
+            let font = FontRef::new(data)?;
+            let glyphs: HashSet<GlyphId> = font
+                .glyph_names()
+                .iter()
+                .filter(|(_, name)| shared.contains(name.as_str()))
+                .map(|(gid, _)| gid)
+                .collect();
+            let codepoints: Vec<u32> = font
+                .charmap()
+                .mappings()
+                .filter(|(cp, gid)| glyphs.contains(gid) && subsetter.includes(*cp))
+                .map(|(cp, _)| cp)
+                .collect();
+            let glyphs: Vec<u32> = glyphs.iter().map(|gid| gid.to_u32()).collect();
+            subsetter.subset_with(data, true, Some((&codepoints, &glyphs)))
+        })
+        .collect()
+}
+
+/// Add a (1, 0) format 0 `cmap` subtable derived from the Unicode mappings.
+fn add_mac_roman_cmap(data: &[u8]) -> Result<Vec<u8>> {
+    let font = FontRef::new(data)?;
+    let cmap = font.cmap().context("subset font has no cmap table")?;
+
+    let glyph_ids: Vec<u8> = (0..=255u32)
+        .map(|byte| {
+            let codepoint = if byte < 0x80 { byte } else { MAC_ROMAN_HIGH[byte as usize - 0x80] };
+            cmap.map_codepoint(codepoint)
+                .and_then(|gid| u8::try_from(gid.to_u32()).ok())
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let mut new_cmap: Cmap = cmap.to_owned_table();
+    new_cmap
+        .encoding_records
+        .retain(|record| !(record.platform_id == PlatformId::Macintosh && record.encoding_id == 0));
+    new_cmap.encoding_records.push(EncodingRecord::new(
+        PlatformId::Macintosh,
+        0,
+        CmapSubtable::format_0(0, glyph_ids),
+    ));
+    new_cmap
+        .encoding_records
+        .sort_by_key(|record| (record.platform_id as u16, record.encoding_id));
+
+    let mut builder = FontBuilder::new();
+    builder.add_table(&new_cmap)?;
+    builder.copy_missing_tables(font);
+    Ok(builder.build())
+}
+
+/// Append `suffix` to name IDs 1, 4 and 16, and without spaces to ID 6.
+///
+/// The suffix is appended to each string's bytes in the record's own
+/// encoding. Records in an encoding other than UTF-16 or Mac Roman, or whose
+/// encoding cannot hold the suffix, are kept unchanged.
+fn append_name_suffix(data: &[u8], suffix: &str) -> Result<Vec<u8>> {
+    let font = FontRef::new(data)?;
+    let name = font.name().context("subset font has no name table")?;
+    let storage = name.string_data().as_bytes();
+    let string = |offset: Offset16, length: u16| {
+        let start = offset.to_u32() as usize;
+        storage
+            .get(start..start + usize::from(length))
+            .context("name string is out of bounds")
+    };
+    let ps_suffix = suffix.replace(' ', "");
+
+    let mut records = Vec::new();
+    for record in name.name_record() {
+        let mut bytes = string(record.string_offset(), record.length())?.to_vec();
+        let suffix = match record.name_id() {
+            NameId::FAMILY_NAME | NameId::FULL_NAME | NameId::TYPOGRAPHIC_FAMILY_NAME => {
+                Some(format!(" {suffix}"))
+            }
+            NameId::POSTSCRIPT_NAME => Some(ps_suffix.clone()),
+            _ => None,
+        };
+        if let Some(encoded) = suffix.and_then(|suffix| encode_name_text(record, &suffix)) {
+            bytes.extend(encoded);
+        }
+        records.push((record, bytes));
+    }
+    let lang_tags = name
+        .lang_tag_record()
+        .unwrap_or_default()
+        .iter()
+        .map(|record| string(record.lang_tag_offset(), record.length()))
+        .collect::<Result<Vec<_>>>()?;
+
+    let u16_of = |n: usize| u16::try_from(n).context("suffixed name table is too large");
+    let has_lang_tags = name.version() >= 1;
+    let header_len =
+        6 + 12 * records.len() + if has_lang_tags { 2 + 4 * lang_tags.len() } else { 0 };
+    let mut table = Vec::new();
+    let mut strings = Vec::new();
+    for field in [name.version(), u16_of(records.len())?, u16_of(header_len)?] {
+        table.extend(field.to_be_bytes());
+    }
+    for (record, bytes) in &records {
+        for field in [
+            record.platform_id(),
+            record.encoding_id(),
+            record.language_id(),
+            record.name_id().to_u16(),
+            u16_of(bytes.len())?,
+            u16_of(strings.len())?,
+        ] {
+            table.extend(field.to_be_bytes());
+        }
+        strings.extend_from_slice(bytes);
+    }
+    if has_lang_tags {
+        table.extend(u16_of(lang_tags.len())?.to_be_bytes());
+        for bytes in lang_tags {
+            for field in [u16_of(bytes.len())?, u16_of(strings.len())?] {
+                table.extend(field.to_be_bytes());
+            }
+            strings.extend_from_slice(bytes);
+        }
+    }
+    table.extend(strings);
+
+    let mut builder = FontBuilder::new();
+    builder.add_raw(Name::TAG, table);
+    builder.copy_missing_tables(font);
+    Ok(builder.build())
+}
+
+/// `text` in the encoding of `record`, if that is UTF-16 or a Mac Roman that
+/// has every character of `text`.
+fn encode_name_text(record: &NameRecord, text: &str) -> Option<Vec<u8>> {
+    if record.is_unicode() {
+        return Some(text.encode_utf16().flat_map(u16::to_be_bytes).collect());
+    }
+    if (record.platform_id(), record.encoding_id()) != (1, 0) {
+        return None;
+    }
+    text.chars()
+        .map(|c| match u8::try_from(c) {
+            Ok(byte) if byte < 0x80 => Some(byte),
+            _ => MAC_ROMAN_HIGH
+                .iter()
+                .position(|&codepoint| codepoint == u32::from(c))
+                .map(|idx| 0x80 + idx as u8),
+        })
+        .collect()
+}
+
+/// Output glyph IDs of shaping `text` with HarfBuzz, with `features` enabled
+/// over the whole text.
+fn shaped_glyphs(face: &FontFace, text: &str, features: &[[u8; 4]]) -> Vec<u32> {
+    let font = shape::Font::new(face);
+    let mut buffer = shape::Buffer::new(text);
+    buffer.shape(&font, features);
+    buffer.glyph_ids()
+}
+
+/// Glyphs a subset reaches without layout closure: `.notdef`, the `cmap`
+/// targets of codepoints accepted by `includes`, `glyph_ids`, and the
+/// components of any composite among them.
+fn direct_glyphs(
+    font: &FontRef,
+    includes: impl Fn(u32) -> bool,
+    glyph_ids: &[u32],
+) -> Result<BTreeSet<GlyphId>> {
+    let mut pending: Vec<GlyphId> = font
+        .charmap()
+        .mappings()
+        .filter(|(cp, _)| includes(*cp))
+        .map(|(_, gid)| gid)
+        .chain(glyph_ids.iter().map(|gid| GlyphId::new(*gid)))
+        .chain([GlyphId::NOTDEF])
+        .collect();
+
+    let glyf = font.glyf().ok().zip(font.loca(None).ok());
+    let mut glyphs = BTreeSet::new();
+    while let Some(gid) = pending.pop() {
+        if !glyphs.insert(gid) {
+            continue;
+        }
+        if let Some((glyf, loca)) = &glyf
+            && let Some(Glyph::Composite(composite)) = loca.get_glyf(gid, glyf)?
+        {
+            pending.extend(composite.components().map(|component| GlyphId::from(component.glyph)));
+        }
+    }
+    Ok(glyphs)
+}
+
+/// Feature tags listed by a font's GSUB and GPOS tables.
+fn feature_tags(font: &FontRef) -> Result<BTreeSet<[u8; 4]>> {
+    let mut tags = BTreeSet::new();
+    if let Ok(gsub) = font.gsub() {
+        let features = gsub.feature_list()?;
+        tags.extend(
+            features
+                .feature_records()
+                .iter()
+                .map(|r| r.feature_tag().to_be_bytes()),
+        );
+    }
+    if let Ok(gpos) = font.gpos() {
+        let features = gpos.feature_list()?;
+        tags.extend(
+            features
+                .feature_records()
+                .iter()
+                .map(|r| r.feature_tag().to_be_bytes()),
+        );
+    }
+    Ok(tags)
+}
+
+/// Glyph names of a font, or `None` if it only has synthesized names.
+fn glyph_name_set(data: &[u8]) -> Result<Option<HashSet<String>>> {
+    let font = FontRef::new(data)?;
+    let names = font.glyph_names();
+    if names.source() == GlyphNameSource::Synthesized {
+        return Ok(None);
+    }
+    Ok(Some(names.iter().map(|(_, name)| name.as_str().to_string()).collect()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -289,6 +954,11 @@ mod tests {
         assert_eq!(SYMBOL_RANGES.len(), 10);
     }
 
+    #[test]
+    fn test_korean_ranges_count() {
+        assert_eq!(KOREAN_RANGES.len(), 6);
+    }
+
     #[test]
     fn test_layout_features_count() {
         assert_eq!(LAYOUT_FEATURES.len(), 20);
@@ -299,16 +969,266 @@ mod tests {
         assert_eq!(VF_TABLES_TO_DROP.len(), 7);
     }
 
+    #[test]
+    fn test_drop_layout_keeps_cmap() {
+        let font = font_test_data::MATERIAL_ICONS_SUBSET;
+        assert!(FontRef::new(font).unwrap().gsub().is_ok());
+
+        let subset = Subsetter::new()
+            .with_unicode_ranges([(0xE000, 0xF8FF)])
+            .drop_layout(true)
+            .subset(font)
+            .unwrap();
+
+        let subset = FontRef::new(&subset).unwrap();
+        for tag in LAYOUT_TABLES_TO_DROP {
+            assert!(subset.table_data(read_fonts::types::Tag::new(tag)).is_none());
+        }
+        let gid = subset.charmap().map(0xE951_u32).unwrap();
+        assert_ne!(gid, GlyphId::NOTDEF);
+    }
+
+    #[test]
+    fn test_subset_with_report() {
+        let font = font_test_data::MATERIAL_SYMBOLS_SUBSET;
+        let (subset, report) = Subsetter::new()
+            .with_unicode_ranges([(0x61, 0x6E)])
+            .drop_layout(true)
+            .subset_with_report(font)
+            .unwrap();
+
+        let output = FontRef::new(&subset).unwrap();
+        assert_eq!(report.input_glyph_count, 11);
+        assert_eq!(report.output_glyph_count, output.maxp().unwrap().num_glyphs());
+        assert_eq!(report.codepoint_count, 5);
+        assert_eq!(report.dropped_features, vec![*b"rclt", *b"rlig"]);
+    }
+
+    #[test]
+    fn test_glyph_ids_without_cmap_entries() {
+        // Glyphs 2 and 4 of this font have no cmap entry.
+        let font = font_test_data::MATERIAL_SYMBOLS_SUBSET;
+        let glyph_count = |data: &[u8]| FontRef::new(data).unwrap().maxp().unwrap().num_glyphs();
+
+        let subset = Subsetter::new().with_glyph_ids([2, 4]).subset(font).unwrap();
+        assert!(glyph_count(&subset) >= 3);
+        assert_eq!(FontRef::new(&subset).unwrap().charmap().mappings().count(), 0);
+
+        let ranges_only = Subsetter::new()
+            .with_unicode_ranges([(0x61, 0x61)])
+            .subset(font)
+            .unwrap();
+        let union = Subsetter::new()
+            .with_unicode_ranges([(0x61, 0x61)])
+            .with_glyph_ids([2, 4])
+            .subset(font)
+            .unwrap();
+        assert!(FontRef::new(&union).unwrap().charmap().map('a').is_some());
+        assert!(glyph_count(&union) > glyph_count(&ranges_only));
+    }
+
+    #[test]
+    fn test_max_extra_glyphs() {
+        // Ligatures spelled with a, i, l, m and n reach icons that are not
+        // requested by codepoint.
+        let font = font_test_data::MATERIAL_SYMBOLS_SUBSET;
+        let subsetter = Subsetter::new()
+            .with_unicode_ranges([(0x61, 0x6E)])
+            .with_layout_features([*b"rlig", *b"rclt"]);
+
+        let direct =
+            direct_glyphs(&FontRef::new(font).unwrap(), |cp| subsetter.includes(cp), &[]).unwrap();
+        let subset = subsetter.subset(font).unwrap();
+        let glyph_count = usize::from(FontRef::new(&subset).unwrap().maxp().unwrap().num_glyphs());
+        assert!(glyph_count > direct.len());
+
+        let extra = glyph_count - direct.len();
+        let subsetter = subsetter.max_extra_glyphs(extra - 1);
+        let err = subsetter.subset(font).unwrap_err();
+        assert!(err.to_string().contains("layout closure adds"), "{err}");
+        assert!(subsetter.max_extra_glyphs(extra).subset(font).is_ok());
+    }
+
+    #[test]
+    fn test_name_suffix() {
+        let names = |data: &[u8]| -> Vec<(u16, String)> {
+            let font = FontRef::new(data).unwrap();
+            let name = font.name().unwrap();
+            name.name_record()
+                .iter()
+                .filter(|record| record.platform_id() == 3)
+                .map(|record| {
+                    let string = record.string(name.string_data()).unwrap();
+                    (record.name_id().to_u16(), string.chars().collect())
+                })
+                .collect()
+        };
+        let subsetter = Subsetter::new().with_unicode_ranges([(0x41, 0x5A)]);
+        let plain = names(&subsetter.subset(font_test_data::AHEM).unwrap());
+        let suffixed = subsetter.name_suffix(Some("JP".to_string()));
+        let suffixed = names(&suffixed.subset(font_test_data::AHEM).unwrap());
+
+        let find = |names: &[(u16, String)], id| {
+            names
+                .iter()
+                .find(|(name_id, _)| *name_id == id)
+                .map(|(_, s)| s.clone())
+        };
+        assert!(find(&suffixed, 1).unwrap().ends_with(" JP"));
+        assert_eq!(find(&suffixed, 1).unwrap(), find(&plain, 1).unwrap() + " JP");
+        assert_eq!(find(&suffixed, 6).unwrap(), find(&plain, 6).unwrap() + "JP");
+        assert_eq!(find(&suffixed, 2), find(&plain, 2));
+    }
+
+    #[test]
+    fn test_name_suffix_keeps_legacy_records() {
+        use write_fonts::{
+            dump_table,
+            tables::name::{Name as WriteName, NameRecord as WriteRecord},
+        };
+
+        let name = WriteName::new(vec![
+            WriteRecord::new(1, 0, 0, NameId::FAMILY_NAME, "Caf\u{e9}".to_string().into()),
+            WriteRecord::new(1, 0, 0, NameId::FULL_NAME, "Ahem".to_string().into()),
+            WriteRecord::new(3, 1, 0x409, NameId::FAMILY_NAME, "Ahem".to_string().into()),
+        ]);
+        let mut table = dump_table(&name).unwrap();
+        // Make the second record Mac Japanese, which the suffix is not encoded in
+        table[6 + 12 + 2..6 + 12 + 4].copy_from_slice(&1u16.to_be_bytes());
+        let mut builder = FontBuilder::new();
+        builder.add_raw(Name::TAG, table);
+        builder.copy_missing_tables(FontRef::new(font_test_data::AHEM).unwrap());
+        let suffixed = append_name_suffix(&builder.build(), "JP").unwrap();
+
+        let font = FontRef::new(&suffixed).unwrap();
+        let name = font.name().unwrap();
+        let storage = name.string_data().as_bytes();
+        let records: Vec<_> = name
+            .name_record()
+            .iter()
+            .map(|record| {
+                let start = record.string_offset().to_u32() as usize;
+                let bytes = &storage[start..start + usize::from(record.length())];
+                (record.encoding_id(), record.name_id(), bytes.to_vec())
+            })
+            .collect();
+        let utf16 = |text: &str| text.encode_utf16().flat_map(u16::to_be_bytes).collect();
+        assert_eq!(
+            records,
+            [
+                (0, NameId::FAMILY_NAME, b"Caf\x8E JP".to_vec()),
+                (1, NameId::FULL_NAME, b"Ahem".to_vec()),
+                (1, NameId::FAMILY_NAME, utf16("Ahem JP")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_with_text() {
+        let subsetter = Subsetter::new().with_text("").with_text("ij\u{1F600}i");
+        assert_eq!(subsetter.codepoints, BTreeSet::from([0x69, 0x6A, 0x1F600]));
+        assert!(subsetter.includes(0x1F600));
+        assert!(!subsetter.includes(0xD83D));
+
+        let subset = subsetter.subset(font_test_data::AHEM).unwrap();
+        let subset = FontRef::new(&subset).unwrap();
+        let mapped: Vec<u32> = subset.charmap().mappings().map(|(cp, _)| cp).collect();
+        assert_eq!(mapped, [0x69, 0x6A]);
+    }
+
+    /// AHEM with a `liga` feature turning "fi" into the glyph of 'X'.
+    fn font_with_fi_ligature() -> (Vec<u8>, u32) {
+        use write_fonts::{
+            tables::{
+                gsub::{Gsub, Ligature, LigatureSet, LigatureSubstFormat1, SubstitutionLookup},
+                layout::{
+                    CoverageTable, Feature, FeatureList, FeatureRecord, LangSys, Lookup,
+                    LookupFlag, LookupList, Script, ScriptList, ScriptRecord,
+                },
+            },
+            types::{GlyphId16, Tag as FontTag},
+        };
+
+        let font = FontRef::new(font_test_data::AHEM).unwrap();
+        let gid = |c: char| GlyphId16::try_from(font.charmap().map(c).unwrap()).unwrap();
+        let (f, i, x) = (gid('f'), gid('i'), gid('X'));
+        let ligature = LigatureSubstFormat1::new(
+            CoverageTable::format_1(vec![f]),
+            vec![LigatureSet::new(vec![Ligature::new(x, vec![i])])],
+        );
+        let gsub = Gsub::new(
+            ScriptList::new(vec![ScriptRecord::new(
+                FontTag::new(b"DFLT"),
+                Script::new(Some(LangSys::new(vec![0])), vec![]),
+            )]),
+            FeatureList::new(vec![FeatureRecord::new(
+                FontTag::new(b"liga"),
+                Feature::new(None, vec![0]),
+            )]),
+            LookupList::new(vec![SubstitutionLookup::Ligature(Lookup::new(
+                LookupFlag::default(),
+                vec![ligature],
+            ))]),
+        );
+        let mut builder = FontBuilder::new();
+        builder.add_table(&gsub).unwrap();
+        builder.copy_missing_tables(font);
+        (builder.build(), u32::from(x.to_u16()))
+    }
+
+    #[test]
+    fn test_with_shaped_text() {
+        let (data, ligature) = font_with_fi_ligature();
+        let face = FontFace::new(Blob::from_bytes(&data).unwrap()).unwrap();
+        assert_eq!(shaped_glyphs(&face, "fi", &[*b"liga"]), [ligature]);
+
+        // Without retained layout features, only shaping reaches the ligature
+        let glyph_count = |subsetter: Subsetter| {
+            let subset = subsetter.with_layout_features([]).subset(&data).unwrap();
+            FontRef::new(&subset).unwrap().maxp().unwrap().num_glyphs()
+        };
+        assert_eq!(glyph_count(Subsetter::new().with_text("fi")), 3);
+        assert_eq!(glyph_count(Subsetter::new().with_shaped_text("fi", &[*b"liga"])), 4);
+    }
+
+    #[test]
+    fn test_keep_mac_cmap() {
+        let subset = Subsetter::new()
+            .with_unicode_ranges([(0x0000, 0x007F)])
+            .keep_mac_cmap(true)
+            .subset(font_test_data::AHEM)
+            .unwrap();
+
+        let font = FontRef::new(&subset).unwrap();
+        let cmap = font.cmap().unwrap();
+        let record = cmap
+            .encoding_records()
+            .iter()
+            .find(|record| {
+                record.platform_id() == read_fonts::tables::cmap::PlatformId::Macintosh
+                    && record.encoding_id() == 0
+            })
+            .expect("Mac Roman subtable");
+        let subtable = record.subtable(cmap.offset_data()).unwrap();
+        let expected = cmap.map_codepoint('A').unwrap();
+        assert_ne!(expected, GlyphId::NOTDEF);
+        assert_eq!(subtable.map_codepoint('A'), Some(expected));
+    }
+
     #[test]
     fn test_builder_chain() {
         let subsetter = Subsetter::new()
             .with_unicode_ranges([(0x0000, 0x007F)])
             .drop_vf_tables(true)
             .retain_glyph_names(true)
+            .drop_hints(true)
+            .desubroutinize(true)
             .with_layout_features([*b"kern", *b"liga"]);
 
         assert!(subsetter.drop_vf_tables);
         assert!(subsetter.retain_glyph_names);
+        assert!(subsetter.drop_hints);
+        assert!(subsetter.desubroutinize);
         assert_eq!(subsetter.unicode_ranges.len(), 1);
         assert_eq!(subsetter.layout_features.len(), 2);
     }
@@ -318,7 +1238,80 @@ mod tests {
         let subsetter = Subsetter::japanese();
         assert!(subsetter.drop_vf_tables);
         assert!(subsetter.retain_glyph_names);
+        assert!(!subsetter.drop_hints);
         // 21 Japanese ranges + 10 symbol ranges
         assert_eq!(subsetter.unicode_ranges.len(), JAPANESE_RANGES.len() + SYMBOL_RANGES.len());
     }
+
+    #[test]
+    fn test_korean_preset() {
+        let subsetter = Subsetter::korean();
+        assert!(subsetter.drop_vf_tables);
+        assert!(subsetter.retain_glyph_names);
+        assert!(subsetter.includes(0xAC00));
+        assert!(subsetter.includes(0x3131));
+        for feature in [b"vert", b"vjmo", b"vrt2"] {
+            assert!(subsetter.layout_features.contains(feature));
+        }
+    }
+
+    #[test]
+    fn test_subset_describe_keeps_axes() {
+        let (subset, description) = Subsetter::new()
+            .with_unicode_ranges([(0x0000, 0x00FF)])
+            .drop_vf_tables(false)
+            .subset_describe(font_test_data::VAZIRMATN_VAR)
+            .unwrap();
+
+        assert!(!subset.is_empty());
+        assert!(description.is_variable);
+        assert_eq!(description.axes, vec![*b"wght"]);
+        assert_eq!(description.codepoint_count, 3);
+        assert!(description.glyph_count > 0);
+    }
+
+    #[test]
+    fn test_explain_japanese() {
+        let plan = Subsetter::japanese().explain(font_test_data::VAZIRMATN_VAR).unwrap();
+
+        assert_eq!(&plan.unicode_ranges[..JAPANESE_RANGES.len()], JAPANESE_RANGES);
+        assert!(plan.retain_glyph_names);
+        assert!(!plan.retain_gids);
+        assert!(plan.axes.is_empty());
+        for tag in [b"fvar", b"gvar"] {
+            assert!(plan.dropped_tables.contains(tag));
+        }
+        for tag in VF_TABLES_TO_DROP {
+            assert!(!plan.retained_tables.contains(*tag));
+        }
+        assert!(plan.retained_tables.contains(b"glyf"));
+
+        let subset = Subsetter::japanese().subset(font_test_data::VAZIRMATN_VAR).unwrap();
+        let subset = FontRef::new(&subset).unwrap();
+        let tags: Vec<[u8; 4]> = subset
+            .table_directory
+            .table_records()
+            .iter()
+            .map(|record| record.tag().to_be_bytes())
+            .collect();
+        assert!(plan.dropped_tables.iter().all(|tag| !tags.contains(tag)));
+    }
+
+    #[test]
+    fn test_subset_family_shares_glyph_names() {
+        let fonts = [font_test_data::AHEM, font_test_data::NOTO_SERIF_DISPLAY_TRIMMED];
+        let subsetter = Subsetter::new().with_unicode_ranges([(0x0000, 0x007F)]);
+        let subsets = subset_family(&fonts, &subsetter).unwrap();
+
+        let names: Vec<HashSet<String>> = subsets
+            .iter()
+            .map(|subset| glyph_name_set(subset).unwrap().unwrap())
+            .collect();
+        let expected: HashSet<String> = [".notdef", "i", "j", "k", "l"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(names[0], expected);
+        assert_eq!(names[1], expected);
+    }
 }
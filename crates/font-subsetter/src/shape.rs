@@ -0,0 +1,94 @@
+//! Owned HarfBuzz font and buffer handles for shaping.
+//!
+//! hb-subset exposes shaping only through its raw bindings, so the unsafe
+//! calls live here behind types that destroy their handle on drop.
+
+use std::marker::PhantomData;
+
+use hb_subset::{FontFace, sys};
+
+/// A shaping font over a [`FontFace`].
+pub(crate) struct Font<'a> {
+    raw: *mut sys::hb_font_t,
+    _face: PhantomData<&'a FontFace<'a>>,
+}
+
+impl<'a> Font<'a> {
+    pub(crate) fn new(face: &'a FontFace<'_>) -> Self {
+        // SAFETY: the face pointer is valid for `'a`, and HarfBuzz takes its
+        // own reference to it.
+        let raw = unsafe { sys::hb_font_create(face.as_raw()) };
+        Self { raw, _face: PhantomData }
+    }
+}
+
+impl Drop for Font<'_> {
+    fn drop(&mut self) {
+        // SAFETY: `raw` was created by `hb_font_create` and is destroyed once.
+        unsafe { sys::hb_font_destroy(self.raw) }
+    }
+}
+
+/// A buffer of text to shape, then of the shaped glyphs.
+pub(crate) struct Buffer {
+    raw: *mut sys::hb_buffer_t,
+}
+
+impl Buffer {
+    /// A buffer holding `text`, with direction, script and language guessed
+    /// from it.
+    pub(crate) fn new(text: &str) -> Self {
+        let length = i32::try_from(text.len()).unwrap_or(i32::MAX);
+        // SAFETY: `text` is valid UTF-8 of at least `length` bytes, and
+        // HarfBuzz copies it into the buffer before returning.
+        unsafe {
+            let raw = sys::hb_buffer_create();
+            sys::hb_buffer_add_utf8(raw, text.as_ptr().cast(), length, 0, length);
+            sys::hb_buffer_guess_segment_properties(raw);
+            Self { raw }
+        }
+    }
+
+    /// Shape the buffer with `font`, with `features` enabled over the whole
+    /// text.
+    pub(crate) fn shape(&mut self, font: &Font, features: &[[u8; 4]]) {
+        let features: Vec<sys::hb_feature_t> = features
+            .iter()
+            .map(|tag| sys::hb_feature_t {
+                tag: u32::from_be_bytes(*tag),
+                value: 1,
+                start: 0,
+                end: u32::MAX,
+            })
+            .collect();
+        // SAFETY: both handles are live, and `features` outlives the call.
+        unsafe {
+            sys::hb_shape(font.raw, self.raw, features.as_ptr(), features.len() as u32);
+        }
+    }
+
+    /// Glyph IDs in the buffer, in order.
+    pub(crate) fn glyph_ids(&self) -> Vec<u32> {
+        let mut count = 0;
+        // SAFETY: the infos are owned by the buffer, which `&self` keeps
+        // alive and unmodified while they are copied out.
+        unsafe {
+            let infos = sys::hb_buffer_get_glyph_infos(self.raw, &mut count);
+            if infos.is_null() {
+                return Vec::new();
+            }
+            std::slice::from_raw_parts(infos, count as usize)
+                .iter()
+                .map(|info| info.codepoint)
+                .collect()
+        }
+    }
+}
+
+impl Drop for Buffer {
+    fn drop(&mut self) {
+        // SAFETY: `raw` was created by `hb_buffer_create` and is destroyed
+        // once.
+        unsafe { sys::hb_buffer_destroy(self.raw) }
+    }
+}
@@ -0,0 +1,229 @@
+//! Variable GPOS anchors.
+//!
+//! Each master carries its own static GPOS, so mark, ligature and cursive
+//! anchors that move between masters would otherwise be frozen at the
+//! default master's positions. Their deltas go into a GDEF ItemVariationStore
+//! built over the output axes, and the anchors reference them through
+//! VariationIndex tables.
+
+use std::collections::HashMap;
+
+use read_fonts::{FontRef, TableProvider};
+use write_fonts::{
+    from_obj::ToOwnedTable,
+    tables::{
+        gpos::{
+            AnchorTable, CursivePosFormat1, ExtensionSubtable, Gpos, MarkArray, MarkBasePosFormat1,
+            MarkLigPosFormat1, MarkMarkPosFormat1, PositionLookup,
+        },
+        layout::{DeviceOrVariationIndex, Lookup},
+        variations::{ItemVariationStore, VariationRegion, ivs_builder::VariationStoreBuilder},
+    },
+};
+
+use crate::{
+    error::Result,
+    variation_model::VariationModel,
+    vf_builder::{scale_xy, variation_regions},
+};
+
+/// Anchor slots of one lookup, in traversal order; `None` marks a null
+/// anchor offset.
+type Slots<'a> = Vec<Option<&'a mut AnchorTable>>;
+
+/// Build the default master's GPOS with every anchor that moves between
+/// masters made variable, along with the variation store holding the deltas.
+///
+/// A lookup whose anchor layout differs in any master keeps its default
+/// anchors. Returns `None` when no anchor varies, or the default master has
+/// no GPOS.
+pub(crate) fn build_variable_anchors(
+    masters: &[FontRef],
+    default_idx: usize,
+    scales: &[f64],
+    model: &VariationModel,
+    axis_count: u16,
+) -> Result<Option<(Gpos, ItemVariationStore)>> {
+    let Ok(default_gpos) = masters[default_idx].gpos() else {
+        return Ok(None);
+    };
+    let mut gpos: Gpos = default_gpos.to_owned_table();
+    let master_anchors = masters
+        .iter()
+        .zip(scales)
+        .map(|(master, &scale)| {
+            let Ok(gpos) = master.gpos() else {
+                return Ok(None);
+            };
+            let mut gpos: Gpos = gpos.to_owned_table();
+            Ok(Some(anchor_coordinates(&mut gpos, scale)))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let Some(master_anchors) = master_anchors.into_iter().collect::<Option<Vec<_>>>() else {
+        return Ok(None);
+    };
+
+    let regions = variation_regions(model);
+    let mut store = VariationStoreBuilder::new(axis_count);
+    let mut pending: HashMap<(usize, usize), [Option<u32>; 2]> = HashMap::new();
+    let default_anchors = &master_anchors[default_idx];
+    for (lookup_idx, default_lookup) in default_anchors.iter().enumerate() {
+        let lookups: Vec<&Vec<Option<(i16, i16)>>> = master_anchors
+            .iter()
+            .filter_map(|anchors| anchors.get(lookup_idx))
+            .collect();
+        if lookups.len() != masters.len()
+            || lookups.iter().any(|lookup| lookup.len() != default_lookup.len())
+        {
+            continue;
+        }
+        for anchor_idx in 0..default_lookup.len() {
+            let Some(values) = lookups
+                .iter()
+                .map(|lookup| lookup[anchor_idx])
+                .collect::<Option<Vec<(i16, i16)>>>()
+            else {
+                continue;
+            };
+            let (_, deltas) = model.compute_deltas_2d(&values);
+            let mut add = |axis: fn(&(i16, i16)) -> i16| {
+                let deltas: Vec<(VariationRegion, i16)> =
+                    regions.iter().cloned().zip(deltas.iter().map(axis)).collect();
+                deltas
+                    .iter()
+                    .any(|(_, delta)| *delta != 0)
+                    .then(|| store.add_deltas(deltas))
+            };
+            let ids = [add(|d| d.0), add(|d| d.1)];
+            if ids.iter().any(Option::is_some) {
+                pending.insert((lookup_idx, anchor_idx), ids);
+            }
+        }
+    }
+    if pending.is_empty() {
+        return Ok(None);
+    }
+
+    let (store, remapping) = store.build();
+    for (lookup_idx, lookup) in gpos.lookup_list.lookups.iter_mut().enumerate() {
+        for (anchor_idx, anchor) in lookup_anchors(lookup).into_iter().enumerate() {
+            let (Some(anchor), Some(ids)) = (anchor, pending.get(&(lookup_idx, anchor_idx))) else {
+                continue;
+            };
+            let [x_device, y_device] = ids.map(|id| {
+                id.and_then(|id| remapping.get(id))
+                    .map(DeviceOrVariationIndex::VariationIndex)
+            });
+            let (x, y) = coordinates(anchor);
+            *anchor = AnchorTable::format_3(x, y, x_device, y_device);
+        }
+    }
+    Ok(Some((gpos, store)))
+}
+
+/// Coordinates of every anchor slot, per lookup, scaled by `scale`.
+fn anchor_coordinates(gpos: &mut Gpos, scale: f64) -> Vec<Vec<Option<(i16, i16)>>> {
+    gpos.lookup_list
+        .lookups
+        .iter_mut()
+        .map(|lookup| {
+            lookup_anchors(lookup)
+                .into_iter()
+                .map(|anchor| {
+                    anchor.map(|anchor| {
+                        let (x, y) = coordinates(anchor);
+                        scale_xy(x, y, scale)
+                    })
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn coordinates(anchor: &AnchorTable) -> (i16, i16) {
+    match anchor {
+        AnchorTable::Format1(anchor) => (anchor.x_coordinate, anchor.y_coordinate),
+        AnchorTable::Format2(anchor) => (anchor.x_coordinate, anchor.y_coordinate),
+        AnchorTable::Format3(anchor) => (anchor.x_coordinate, anchor.y_coordinate),
+    }
+}
+
+/// Anchor slots of an attachment or cursive lookup, including those behind
+/// extension subtables; other lookups have none.
+fn lookup_anchors(lookup: &mut PositionLookup) -> Slots<'_> {
+    let mut slots = Vec::new();
+    match lookup {
+        PositionLookup::Cursive(lookup) => subtables(lookup).for_each(|s| cursive(s, &mut slots)),
+        PositionLookup::MarkToBase(lookup) => {
+            subtables(lookup).for_each(|s| mark_to_base(s, &mut slots))
+        }
+        PositionLookup::MarkToLig(lookup) => {
+            subtables(lookup).for_each(|s| mark_to_lig(s, &mut slots))
+        }
+        PositionLookup::MarkToMark(lookup) => {
+            subtables(lookup).for_each(|s| mark_to_mark(s, &mut slots))
+        }
+        PositionLookup::Extension(lookup) => {
+            for subtable in subtables(lookup) {
+                match subtable {
+                    ExtensionSubtable::Cursive(ext) => cursive(&mut ext.extension, &mut slots),
+                    ExtensionSubtable::MarkToBase(ext) => {
+                        mark_to_base(&mut ext.extension, &mut slots)
+                    }
+                    ExtensionSubtable::MarkToLig(ext) => {
+                        mark_to_lig(&mut ext.extension, &mut slots)
+                    }
+                    ExtensionSubtable::MarkToMark(ext) => {
+                        mark_to_mark(&mut ext.extension, &mut slots)
+                    }
+                    _ => {}
+                }
+            }
+        }
+        _ => {}
+    }
+    slots
+}
+
+fn subtables<T>(lookup: &mut Lookup<T>) -> impl Iterator<Item = &mut T> {
+    lookup.subtables.iter_mut().map(|subtable| &mut **subtable)
+}
+
+fn cursive<'a>(subtable: &'a mut CursivePosFormat1, slots: &mut Slots<'a>) {
+    for record in &mut subtable.entry_exit_record {
+        slots.push(record.entry_anchor.as_mut());
+        slots.push(record.exit_anchor.as_mut());
+    }
+}
+
+fn marks<'a>(array: &'a mut MarkArray, slots: &mut Slots<'a>) {
+    slots.extend(
+        array
+            .mark_records
+            .iter_mut()
+            .map(|record| Some(&mut *record.mark_anchor)),
+    );
+}
+
+fn mark_to_base<'a>(subtable: &'a mut MarkBasePosFormat1, slots: &mut Slots<'a>) {
+    marks(&mut subtable.mark_array, slots);
+    for record in &mut subtable.base_array.base_records {
+        slots.extend(record.base_anchors.iter_mut().map(|anchor| anchor.as_mut()));
+    }
+}
+
+fn mark_to_lig<'a>(subtable: &'a mut MarkLigPosFormat1, slots: &mut Slots<'a>) {
+    marks(&mut subtable.mark_array, slots);
+    for attach in &mut subtable.ligature_array.ligature_attaches {
+        for record in &mut attach.component_records {
+            slots.extend(record.ligature_anchors.iter_mut().map(|anchor| anchor.as_mut()));
+        }
+    }
+}
+
+fn mark_to_mark<'a>(subtable: &'a mut MarkMarkPosFormat1, slots: &mut Slots<'a>) {
+    marks(&mut subtable.mark1_array, slots);
+    for record in &mut subtable.mark2_array.mark2_records {
+        slots.extend(record.mark2_anchors.iter_mut().map(|anchor| anchor.as_mut()));
+    }
+}
@@ -4,6 +4,8 @@
 
 use std::{collections::HashMap, path::PathBuf};
 
+use crate::{AvarMapIssue, Error};
+
 /// A variation axis in the designspace.
 #[derive(Debug, Clone)]
 pub struct Axis {
@@ -17,8 +19,30 @@ pub struct Axis {
     pub default: f32,
     /// Maximum value on this axis
     pub maximum: f32,
+    /// avar segment map as normalized `(input, output)` pairs (see
+    /// [`Axis::with_map`]); `None` leaves the axis linear
+    pub map: Option<Vec<(f32, f32)>>,
+    /// How [`DesignSpace::auto_avar`] spaces the axis between masters
+    pub spacing: AvarSpacing,
+}
+
+/// How [`DesignSpace::auto_avar`] spaces an axis between adjacent masters,
+/// which it places at equal normalized steps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AvarSpacing {
+    /// Normalized position grows linearly with the axis value
+    #[default]
+    Linear,
+    /// Equal normalized steps multiply the axis value by the same factor, so
+    /// the instances between two masters progress geometrically, as stem
+    /// weights do in Lucas de Groot's interpolation theory. Axes with values
+    /// at or below zero are spaced linearly.
+    Geometric,
 }
 
+/// Segments sampled between masters for [`AvarSpacing::Geometric`].
+const GEOMETRIC_STEPS: u16 = 8;
+
 impl Axis {
     /// Create a new axis.
     pub fn new(tag: &str, name: &str, minimum: f32, default: f32, maximum: f32) -> Self {
@@ -28,9 +52,57 @@ impl Axis {
             minimum,
             default,
             maximum,
+            map: None,
+            spacing: AvarSpacing::Linear,
         }
     }
 
+    /// Warp the axis with an avar segment map of normalized `(input, output)`
+    /// pairs.
+    ///
+    /// The map must be sorted by input, with non-decreasing outputs, and map
+    /// -1, 0 and 1 to themselves; [`build_variable_font`](crate::build_variable_font)
+    /// reports any other map as [`Error::InvalidAvarMap`](crate::Error::InvalidAvarMap).
+    /// The map takes precedence over [`DesignSpace::auto_avar`] for this axis.
+    pub fn with_map(mut self, map: Vec<(f32, f32)>) -> Self {
+        self.map = Some(map);
+        self
+    }
+
+    /// Space the axis between masters with `spacing` when
+    /// [`DesignSpace::auto_avar`] derives its map.
+    pub fn with_spacing(mut self, spacing: AvarSpacing) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    /// Check that [`Axis::map`], if any, is a valid avar segment map, failing
+    /// with [`Error::InvalidAvarMap`] otherwise.
+    pub fn validate_map(&self) -> crate::Result<()> {
+        let Some(map) = &self.map else {
+            return Ok(());
+        };
+        let invalid = |reason| Err(Error::InvalidAvarMap { axis: self.tag.clone(), reason });
+        if let Some(&(from, to)) = map
+            .iter()
+            .find(|(from, to)| !(-1.0..=1.0).contains(from) || !(-1.0..=1.0).contains(to))
+        {
+            return invalid(AvarMapIssue::OutOfRange { from, to });
+        }
+        if let Some(pair) = map
+            .windows(2)
+            .find(|pair| pair[0].0 >= pair[1].0 || pair[0].1 > pair[1].1)
+        {
+            return invalid(AvarMapIssue::NotMonotonic { previous: pair[0], next: pair[1] });
+        }
+        for anchor in [-1.0, 0.0, 1.0] {
+            if !map.contains(&(anchor, anchor)) {
+                return invalid(AvarMapIssue::MissingAnchor(anchor));
+            }
+        }
+        Ok(())
+    }
+
     /// Create a standard weight axis (wght: 100-900, default 400).
     pub fn weight() -> Self {
         Self::new("wght", "Weight", 100.0, 400.0, 900.0)
@@ -41,6 +113,15 @@ impl Axis {
         Self::new("ital", "Italic", 0.0, 0.0, 1.0)
     }
 
+    /// Map a normalized value in [-1, 1] back to user space.
+    pub fn denormalize(&self, value: f32) -> f32 {
+        if value < 0.0 {
+            self.default + value * (self.default - self.minimum)
+        } else {
+            self.default + value * (self.maximum - self.default)
+        }
+    }
+
     /// Normalize a user-space value to the range [-1, 1].
     ///
     /// Values below the default normalize to [-1, 0].
@@ -161,12 +242,29 @@ pub struct DesignSpace {
     pub sources: Vec<Source>,
     /// Named instances
     pub instances: Vec<Instance>,
+    /// Whether to emit an avar that spaces masters evenly (see [`DesignSpace::auto_avar`])
+    pub auto_avar: bool,
+    /// Family name for the built font, replacing the default master's
+    pub family_name: Option<String>,
+    /// Whether to rescale masters whose unitsPerEm differs from the default's
+    /// (see [`DesignSpace::scale_upem`])
+    pub scale_upem: bool,
+    /// Whether to emit an HVAR table (see [`DesignSpace::with_hvar`])
+    pub hvar: bool,
 }
 
 impl DesignSpace {
     /// Create a new designspace with the given axes and sources.
     pub fn new(axes: Vec<Axis>, sources: Vec<Source>) -> Self {
-        Self { axes, sources, instances: Vec::new() }
+        Self {
+            axes,
+            sources,
+            instances: Vec::new(),
+            auto_avar: false,
+            family_name: None,
+            scale_upem: true,
+            hvar: false,
+        }
     }
 
     /// Add named instances to the designspace.
@@ -175,6 +273,97 @@ impl DesignSpace {
         self
     }
 
+    /// Build the font under a different family name.
+    ///
+    /// Rewrites name IDs 1 and 16 to `family_name`, and derives the full name
+    /// (ID 4) and PostScript name (ID 6) from it and the default master's
+    /// subfamily.
+    pub fn with_family_name(mut self, family_name: String) -> Self {
+        self.family_name = Some(family_name);
+        self
+    }
+
+    /// Derive an avar table that spaces each axis's masters evenly.
+    ///
+    /// On each side of the default, the masters (and the axis extreme, if no
+    /// master sits there) are mapped to equal normalized steps: masters at
+    /// 400/500/1000 on a 400-1000 axis land at 0, 0.5 and 1 instead of 0,
+    /// 0.167 and 1. Between masters the axis follows its [`Axis::spacing`].
+    pub fn auto_avar(mut self, enabled: bool) -> Self {
+        self.auto_avar = enabled;
+        self
+    }
+
+    /// Rescale masters drawn at a different unitsPerEm than the default.
+    ///
+    /// Enabled by default: outlines and component offsets of such masters are
+    /// multiplied by `default_upem / master_upem` and rounded before deltas
+    /// are computed. When disabled, a mismatch is reported as
+    /// [`Error::UpemMismatch`](crate::Error::UpemMismatch).
+    pub fn scale_upem(mut self, enabled: bool) -> Self {
+        self.scale_upem = enabled;
+        self
+    }
+
+    /// Emit an HVAR table carrying advance width deltas.
+    ///
+    /// Disabled by default, leaving advances to vary only through the gvar
+    /// phantom points. When enabled, each glyph's advance deltas are derived
+    /// from the masters' hmtx and stored in a single item variation subtable
+    /// indexed by glyph ID.
+    pub fn with_hvar(mut self, enabled: bool) -> Self {
+        self.hvar = enabled;
+        self
+    }
+
+    /// Per-axis avar segment maps as `(from, to)` normalized pairs.
+    ///
+    /// Each axis uses its own [`Axis::map`], then the derived map if
+    /// [`DesignSpace::auto_avar`] is enabled, then the identity. Returns
+    /// `None` when no axis has a map and auto avar is disabled.
+    pub fn avar_maps(&self) -> Option<Vec<Vec<(f32, f32)>>> {
+        if !self.auto_avar && self.axes.iter().all(|axis| axis.map.is_none()) {
+            return None;
+        }
+        let maps = self.axes.iter().map(|axis| match &axis.map {
+            Some(map) => map.clone(),
+            None if self.auto_avar => self.axis_avar_map(axis),
+            None => vec![(-1.0, -1.0), (0.0, 0.0), (1.0, 1.0)],
+        });
+        Some(maps.collect())
+    }
+
+    fn axis_avar_map(&self, axis: &Axis) -> Vec<(f32, f32)> {
+        let mut positions: Vec<f32> = self
+            .sources
+            .iter()
+            .map(|source| axis.normalize(source.axis_value(axis)))
+            .chain([-1.0, 1.0])
+            .collect();
+        positions.sort_by(f32::total_cmp);
+        positions.dedup();
+
+        let below: Vec<f32> = positions.iter().copied().filter(|p| *p < 0.0).collect();
+        let above: Vec<f32> = positions.iter().copied().filter(|p| *p > 0.0).collect();
+
+        let mut map: Vec<(f32, f32)> = below
+            .iter()
+            .enumerate()
+            .map(|(i, from)| (*from, -((below.len() - i) as f32) / below.len() as f32))
+            .collect();
+        map.push((0.0, 0.0));
+        map.extend(
+            above
+                .iter()
+                .enumerate()
+                .map(|(i, from)| (*from, (i + 1) as f32 / above.len() as f32)),
+        );
+        if axis.spacing == AvarSpacing::Geometric && axis.minimum > 0.0 {
+            map = geometric_segments(axis, &map);
+        }
+        map
+    }
+
     /// Find the default source (the one at the default location for all axes).
     pub fn default_source(&self) -> Option<&Source> {
         self.sources.iter().find(|source| {
@@ -194,10 +383,24 @@ impl DesignSpace {
     }
 
     /// Get all unique axis locations from sources as normalized coordinates.
+    ///
+    /// With [`DesignSpace::auto_avar`] or an [`Axis::map`], coordinates are
+    /// passed through the avar maps so they match what a renderer computes.
     pub fn master_locations(&self) -> Vec<Vec<f32>> {
+        let maps = self.avar_maps();
         self.sources
             .iter()
-            .map(|source| source.normalized_location(&self.axes))
+            .map(|source| {
+                let location = source.normalized_location(&self.axes);
+                match &maps {
+                    Some(maps) => location
+                        .iter()
+                        .zip(maps)
+                        .map(|(value, map)| apply_avar_map(map, *value))
+                        .collect(),
+                    None => location,
+                }
+            })
             .collect()
     }
 
@@ -224,6 +427,36 @@ impl DesignSpace {
     }
 }
 
+/// Piecewise-linear lookup of `value` in an avar segment map.
+/// `map` with each segment between masters resampled so that equal output
+/// steps multiply the axis value by the same factor.
+fn geometric_segments(axis: &Axis, map: &[(f32, f32)]) -> Vec<(f32, f32)> {
+    let mut segments = vec![map[0]];
+    for pair in map.windows(2) {
+        let ((from_lo, to_lo), (from_hi, to_hi)) = (pair[0], pair[1]);
+        let (lo, hi) = (axis.denormalize(from_lo), axis.denormalize(from_hi));
+        if lo < hi {
+            segments.extend((1..GEOMETRIC_STEPS).map(|step| {
+                let t = f32::from(step) / f32::from(GEOMETRIC_STEPS);
+                (axis.normalize(lo * (hi / lo).powf(t)), to_lo + t * (to_hi - to_lo))
+            }));
+        }
+        segments.push(pair[1]);
+    }
+    segments
+}
+
+pub(crate) fn apply_avar_map(map: &[(f32, f32)], value: f32) -> f32 {
+    map.windows(2).find(|pair| value <= pair[1].0).map_or(value, |pair| {
+        let ((from_lo, to_lo), (from_hi, to_hi)) = (pair[0], pair[1]);
+        if from_hi == from_lo {
+            to_lo
+        } else {
+            to_lo + (value - from_lo) / (from_hi - from_lo) * (to_hi - to_lo)
+        }
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -279,4 +512,75 @@ mod tests {
 
         assert_eq!(default.path, PathBuf::from("Regular.ttf"));
     }
+
+    #[test]
+    fn auto_avar_spaces_masters_evenly() {
+        let designspace = DesignSpace::new(
+            vec![Axis::new("wght", "Weight", 400.0, 400.0, 1000.0)],
+            vec![
+                Source::new("Regular.ttf", vec![("wght", 400.0)]),
+                Source::new("Medium.ttf", vec![("wght", 500.0)]),
+                Source::new("Black.ttf", vec![("wght", 1000.0)]),
+            ],
+        );
+        assert!(designspace.avar_maps().is_none());
+
+        let designspace = designspace.auto_avar(true);
+        let maps = designspace.avar_maps().unwrap();
+        assert_eq!(maps[0], vec![(-1.0, -1.0), (0.0, 0.0), (1.0 / 6.0, 0.5), (1.0, 1.0)]);
+        assert_eq!(designspace.master_locations(), vec![vec![0.0], vec![0.5], vec![1.0]]);
+    }
+
+    #[test]
+    fn axis_map_overrides_auto_avar() {
+        let map = vec![(-1.0, -1.0), (0.0, 0.0), (0.5, 0.25), (1.0, 1.0)];
+        let designspace = DesignSpace::new(
+            vec![
+                Axis::new("wght", "Weight", 400.0, 400.0, 1000.0).with_map(map.clone()),
+                Axis::new("ital", "Italic", 0.0, 0.0, 1.0),
+            ],
+            vec![
+                Source::new("Regular.ttf", vec![("wght", 400.0)]),
+                Source::new("Bold.ttf", vec![("wght", 700.0), ("ital", 1.0)]),
+            ],
+        );
+        let identity = vec![(-1.0, -1.0), (0.0, 0.0), (1.0, 1.0)];
+        assert_eq!(designspace.avar_maps().unwrap(), vec![map.clone(), identity]);
+        assert_eq!(designspace.master_locations(), vec![vec![0.0, 0.0], vec![0.25, 1.0]]);
+
+        let designspace = designspace.auto_avar(true);
+        assert_eq!(designspace.avar_maps().unwrap()[0], map);
+    }
+
+    #[test]
+    fn axis_map_validation() {
+        let axis = |map: Vec<(f32, f32)>| Axis::weight().with_map(map).validate_map();
+
+        assert!(Axis::weight().validate_map().is_ok());
+        assert!(axis(vec![(-1.0, -1.0), (0.0, 0.0), (0.4, 0.6), (1.0, 1.0)]).is_ok());
+        let issue = |map| match axis(map) {
+            Err(Error::InvalidAvarMap { axis, reason }) => {
+                assert_eq!(axis, "wght");
+                reason
+            }
+            other => panic!("expected InvalidAvarMap, got {other:?}"),
+        };
+        assert_eq!(
+            issue(vec![(-1.0, -1.0), (0.0, 0.0), (0.4, 0.6), (0.3, 0.7), (1.0, 1.0)]),
+            AvarMapIssue::NotMonotonic { previous: (0.4, 0.6), next: (0.3, 0.7) }
+        );
+        assert_eq!(
+            issue(vec![(-1.0, -1.0), (0.0, 0.0), (0.4, 0.6), (0.5, 0.5), (1.0, 1.0)]),
+            AvarMapIssue::NotMonotonic { previous: (0.4, 0.6), next: (0.5, 0.5) }
+        );
+        assert_eq!(
+            issue(vec![(-1.0, -1.0), (0.0, 0.0), (1.0, 0.9)]),
+            AvarMapIssue::MissingAnchor(1.0)
+        );
+        assert_eq!(issue(vec![(0.0, 0.0), (1.0, 1.0)]), AvarMapIssue::MissingAnchor(-1.0));
+        assert_eq!(
+            issue(vec![(-1.0, -1.0), (0.0, 0.0), (1.0, 1.0), (1.5, 1.5)]),
+            AvarMapIssue::OutOfRange { from: 1.5, to: 1.5 }
+        );
+    }
 }
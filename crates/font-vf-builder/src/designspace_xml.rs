@@ -0,0 +1,267 @@
+//! Reading fontmake-style `.designspace` files into a [`DesignSpace`].
+
+use std::{collections::HashMap, fs, path::Path};
+
+use log::warn;
+use roxmltree::{Document, Node};
+
+use crate::{
+    designspace::{Axis, DesignSpace, Instance, Source, apply_avar_map},
+    error::{Error, Result},
+};
+
+/// Top-level elements that are read; anything else is ignored with a warning.
+const SUPPORTED_ELEMENTS: &[&str] = &["axes", "sources", "instances"];
+
+impl DesignSpace {
+    /// Read a `.designspace` file.
+    ///
+    /// Source filenames are resolved relative to the file's directory. See
+    /// [`DesignSpace::from_designspace_str`] for what is read.
+    pub fn from_designspace_xml(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let xml = fs::read_to_string(path)
+            .map_err(|source| Error::ReadDesignspace { path: path.to_path_buf(), source })?;
+        let base_dir = path.parent().unwrap_or(Path::new(""));
+        Self::from_designspace_str(&xml, base_dir)
+    }
+
+    /// Parse designspace XML, resolving source filenames against `base_dir`.
+    ///
+    /// Reads `<axes>`, `<sources>` and `<instances>`. An axis's `<map>`
+    /// elements become its [`Axis::map`], and design-space locations are
+    /// converted back to user space through them. Other top-level elements
+    /// such as `<rules>` and `<lib>` are skipped with a logged warning.
+    pub fn from_designspace_str(xml: &str, base_dir: impl AsRef<Path>) -> Result<Self> {
+        let document =
+            Document::parse(xml).map_err(|err| Error::ParseDesignspace(err.to_string()))?;
+        let root = document.root_element();
+        if !root.has_tag_name("designspace") {
+            return Err(Error::ParseDesignspace(format!(
+                "root element is <{}>, expected <designspace>",
+                root.tag_name().name()
+            )));
+        }
+        for element in root.children().filter(Node::is_element) {
+            if !SUPPORTED_ELEMENTS.contains(&element.tag_name().name()) {
+                warn!("Ignoring unsupported designspace element <{}>", element.tag_name().name());
+            }
+        }
+
+        let axes: Vec<DesignAxis> = child(root, "axes")
+            .map(|axes| children_named(axes, "axis").map(DesignAxis::parse).collect())
+            .transpose()?
+            .unwrap_or_default();
+
+        let base_dir = base_dir.as_ref();
+        let sources = child(root, "sources")
+            .into_iter()
+            .flat_map(|sources| children_named(sources, "source"))
+            .map(|source| {
+                let filename = required_attr(source, "filename")?;
+                Ok(Source {
+                    path: base_dir.join(filename),
+                    location: read_location(source, &axes)?,
+                    family_name: source.attribute("familyname").map(str::to_string),
+                    style_name: source.attribute("stylename").map(str::to_string),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let instances = child(root, "instances")
+            .into_iter()
+            .flat_map(|instances| children_named(instances, "instance"))
+            .map(|instance| {
+                let name = instance
+                    .attribute("stylename")
+                    .or_else(|| instance.attribute("name"))
+                    .ok_or_else(|| missing_attr(instance, "stylename"))?;
+                Ok(Instance {
+                    name: name.to_string(),
+                    location: read_location(instance, &axes)?,
+                    postscript_name: instance.attribute("postscriptfontname").map(str::to_string),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let axes = axes.into_iter().map(|axis| axis.axis).collect();
+        Ok(Self::new(axes, sources).with_instances(instances))
+    }
+}
+
+/// An axis with its user-to-design `<map>`, if any.
+struct DesignAxis {
+    axis: Axis,
+    /// `(user, design)` pairs sorted by user value
+    map: Vec<(f32, f32)>,
+}
+
+impl DesignAxis {
+    fn parse(element: Node) -> Result<Self> {
+        let tag = required_attr(element, "tag")?;
+        let name = element.attribute("name").unwrap_or(tag);
+        if element.attribute("values").is_some() && element.attribute("minimum").is_none() {
+            return Err(Error::ParseDesignspace(format!("discrete axis '{tag}' is not supported")));
+        }
+        let mut axis = Axis::new(
+            tag,
+            name,
+            number_attr(element, "minimum")?,
+            number_attr(element, "default")?,
+            number_attr(element, "maximum")?,
+        );
+
+        let mut map = children_named(element, "map")
+            .map(|map| Ok((number_attr(map, "input")?, number_attr(map, "output")?)))
+            .collect::<Result<Vec<_>>>()?;
+        map.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        if !map.is_empty() {
+            let design = |user: f32| apply_map(&map, user);
+            let design_axis = Axis::new(
+                tag,
+                name,
+                design(axis.minimum),
+                design(axis.default),
+                design(axis.maximum),
+            );
+            let mut avar: Vec<(f32, f32)> = map
+                .iter()
+                .map(|(user, design)| (axis.normalize(*user), design_axis.normalize(*design)))
+                .chain([(-1.0, -1.0), (0.0, 0.0), (1.0, 1.0)])
+                .collect();
+            avar.sort_by(|a, b| a.0.total_cmp(&b.0));
+            avar.dedup_by(|a, b| a.0 == b.0);
+            axis = axis.with_map(avar);
+        }
+
+        Ok(Self { axis, map })
+    }
+
+    /// Convert a design-space value to user space.
+    fn user_value(&self, design: f32) -> f32 {
+        let inverse: Vec<(f32, f32)> =
+            self.map.iter().map(|(user, design)| (*design, *user)).collect();
+        apply_map(&inverse, design)
+    }
+}
+
+/// Piecewise-linear lookup that holds the end values beyond the map.
+fn apply_map(map: &[(f32, f32)], value: f32) -> f32 {
+    match (map.first(), map.last()) {
+        (Some(first), _) if value <= first.0 => first.1,
+        (_, Some(last)) if value >= last.0 => last.1,
+        (Some(_), Some(_)) => apply_avar_map(map, value),
+        _ => value,
+    }
+}
+
+/// Read the `<location>` of a source or instance as user-space values keyed
+/// by axis tag.
+fn read_location(element: Node, axes: &[DesignAxis]) -> Result<HashMap<String, f32>> {
+    let Some(location) = child(element, "location") else {
+        return Ok(HashMap::new());
+    };
+    children_named(location, "dimension")
+        .map(|dimension| {
+            let name = required_attr(dimension, "name")?;
+            let axis = axes
+                .iter()
+                .find(|axis| axis.axis.name == name || axis.axis.tag == name)
+                .ok_or_else(|| {
+                    Error::ParseDesignspace(format!("location names unknown axis '{name}'"))
+                })?;
+            let value = if dimension.attribute("uservalue").is_some() {
+                number_attr(dimension, "uservalue")?
+            } else {
+                axis.user_value(number_attr(dimension, "xvalue")?)
+            };
+            Ok((axis.axis.tag.clone(), value))
+        })
+        .collect()
+}
+
+/// First child element called `name`.
+fn child<'a, 'input>(element: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    children_named(element, name).next()
+}
+
+/// Child elements called `name`, in document order.
+fn children_named<'a, 'input>(
+    element: Node<'a, 'input>,
+    name: &str,
+) -> impl Iterator<Item = Node<'a, 'input>> {
+    element.children().filter(move |child| child.has_tag_name(name))
+}
+
+fn missing_attr(element: Node, name: &str) -> Error {
+    Error::ParseDesignspace(format!(
+        "<{}> is missing attribute '{name}'",
+        element.tag_name().name()
+    ))
+}
+
+fn required_attr<'a>(element: Node<'a, '_>, name: &str) -> Result<&'a str> {
+    element.attribute(name).ok_or_else(|| missing_attr(element, name))
+}
+
+fn number_attr(element: Node, name: &str) -> Result<f32> {
+    let value = required_attr(element, name)?;
+    value.trim().parse().map_err(|_| {
+        Error::ParseDesignspace(format!(
+            "<{}> attribute '{name}' is not a number: '{value}'",
+            element.tag_name().name()
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn axis_map_becomes_avar_and_converts_locations() {
+        let xml = r#"<designspace format="4.1">
+  <axes>
+    <axis tag="wght" name="Weight" minimum="100" default="400" maximum="900">
+      <map input="100" output="20"/>
+      <map input="400" output="80"/>
+      <map input="700" output="160"/>
+      <map input="900" output="200"/>
+    </axis>
+  </axes>
+  <sources>
+    <source filename="Bold.ttf"><location><dimension name="Weight" xvalue="160"/></location></source>
+  </sources>
+</designspace>"#;
+        let designspace = DesignSpace::from_designspace_str(xml, "").unwrap();
+
+        let axis = &designspace.axes[0];
+        assert_eq!((axis.minimum, axis.default, axis.maximum), (100.0, 400.0, 900.0));
+        let map = axis.map.as_ref().unwrap();
+        assert_eq!(map[..2], [(-1.0, -1.0), (0.0, 0.0)]);
+        assert!((map[2].0 - 0.6).abs() < 1e-6 && (map[2].1 - 2.0 / 3.0).abs() < 1e-6);
+        assert_eq!(map[3], (1.0, 1.0));
+        assert!((designspace.sources[0].location["wght"] - 700.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn unknown_axis_in_location_is_an_error() {
+        let xml = r#"<designspace>
+  <axes><axis tag="wght" name="Weight" minimum="100" default="400" maximum="900"/></axes>
+  <sources>
+    <source filename="A.ttf"><location><dimension name="Width" xvalue="100"/></location></source>
+  </sources>
+</designspace>"#;
+        let err = DesignSpace::from_designspace_str(xml, "").unwrap_err();
+        assert!(err.to_string().contains("unknown axis 'Width'"), "{err}");
+    }
+
+    #[test]
+    fn malformed_xml_is_an_error() {
+        for xml in ["<designspace><axes></designspace>", "<designspace>", ""] {
+            let err = DesignSpace::from_designspace_str(xml, "").unwrap_err();
+            assert!(matches!(err, Error::ParseDesignspace(_)), "{err}");
+        }
+    }
+}
@@ -1,6 +1,6 @@
 //! Error types for variable font building.
 
-use std::{io, path::PathBuf, result};
+use std::{fmt, io, path::PathBuf, result};
 
 use read_fonts::ReadError;
 use write_fonts::{BuilderError, error, tables::gvar::GvarInputError};
@@ -27,22 +27,68 @@ pub enum Error {
     #[error("Glyph count mismatch: master '{path}' has {actual} glyphs, expected {expected}")]
     GlyphCountMismatch { path: PathBuf, expected: u16, actual: u16 },
 
+    /// unitsPerEm mismatch between masters with rescaling disabled.
+    #[error("unitsPerEm mismatch: master '{path}' has {actual}, default master has {expected}")]
+    UpemMismatch { path: PathBuf, expected: u16, actual: u16 },
+
     /// Point count mismatch for a glyph between masters.
     #[error(
         "Point count mismatch for glyph {glyph_id}: master '{path}' has {actual} points, expected {expected}"
     )]
     PointCountMismatch { path: PathBuf, glyph_id: u32, expected: usize, actual: usize },
 
+    /// A glyph's point set for one master has a different length than its
+    /// contour ends describe.
+    #[error(
+        "Point count mismatch for glyph {glyph_id}: master {master} has {actual} points, expected {expected}"
+    )]
+    PointSetMismatch { master: usize, glyph_id: u32, expected: usize, actual: usize },
+
     /// Contour count mismatch for a glyph between masters.
     #[error(
         "Contour count mismatch for glyph {glyph_id}: master '{path}' has {actual} contours, expected {expected}"
     )]
     ContourCountMismatch { path: PathBuf, glyph_id: u32, expected: usize, actual: usize },
 
+    /// Glyphs whose outline structure differs between masters, found before
+    /// any deltas are computed.
+    #[error(
+        "{} glyphs are incompatible across masters:{}",
+        glyphs.len(),
+        glyphs.iter().map(|glyph| format!("\n  {glyph}")).collect::<String>()
+    )]
+    IncompatibleMasters { glyphs: Vec<GlyphIncompat> },
+
+    /// The default master has no outline for a glyph that other masters draw.
+    ///
+    /// gvar can only move points that exist in the default outline, so such a
+    /// glyph cannot vary.
+    #[error(
+        "Glyph {glyph_id} is empty in the default master but has an outline in master '{path}'"
+    )]
+    DefaultGlyphEmpty { path: PathBuf, glyph_id: u32 },
+
+    /// Instancing the built font at a master's location did not reproduce
+    /// that master's outline.
+    #[error("Round-trip mismatch for glyph {glyph_id}: instance differs from master '{path}'")]
+    RoundTripMismatch { path: PathBuf, glyph_id: u32 },
+
+    /// Failed to read a `.designspace` file.
+    #[error("Failed to read designspace '{path}': {source}")]
+    ReadDesignspace { path: PathBuf, source: io::Error },
+
+    /// A `.designspace` file is malformed or uses unsupported features.
+    #[error("Failed to parse designspace: {0}")]
+    ParseDesignspace(String),
+
     /// Invalid designspace configuration.
     #[error("Invalid designspace: {0}")]
     InvalidDesignspace(String),
 
+    /// An axis map is not a valid avar segment map.
+    #[error("Invalid avar map for axis '{axis}': {reason}")]
+    InvalidAvarMap { axis: String, reason: AvarMapIssue },
+
     /// No default source found in designspace.
     #[error("No source at default location found in designspace")]
     NoDefaultSource,
@@ -63,7 +109,92 @@ pub enum Error {
     #[error("Font write error: {0}")]
     WriteError(#[from] error::Error),
 
+    /// Instancing error while verifying a built font.
+    #[cfg(feature = "verify")]
+    #[error("Failed to instance variable font: {0}")]
+    Instance(#[from] font_instancer::Error),
+
     /// Gvar building error.
     #[error("Error building gvar table: {0:?}")]
     GvarBuild(GvarInputError),
 }
+
+/// A glyph that cannot be interpolated between the default master and
+/// another master.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlyphIncompat {
+    pub glyph_id: u32,
+    /// Path of the master that differs from the default
+    pub path: PathBuf,
+    pub reason: IncompatReason,
+}
+
+/// How a glyph's structure differs from the default master's.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IncompatReason {
+    /// One master has a simple glyph, the other a composite
+    GlyphKind,
+    ContourCount {
+        expected: usize,
+        actual: usize,
+    },
+    /// A contour has a different number of points
+    PointCount {
+        contour: usize,
+        expected: usize,
+        actual: usize,
+    },
+    /// A point is on-curve in one master and off-curve in the other
+    OnCurveFlag {
+        point: usize,
+    },
+    ComponentCount {
+        expected: usize,
+        actual: usize,
+    },
+}
+
+impl fmt::Display for GlyphIncompat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "glyph {} in '{}': ", self.glyph_id, self.path.display())?;
+        match &self.reason {
+            IncompatReason::GlyphKind => write!(f, "simple and composite glyphs are mixed"),
+            IncompatReason::ContourCount { expected, actual } => {
+                write!(f, "{actual} contours, expected {expected}")
+            }
+            IncompatReason::PointCount { contour, expected, actual } => {
+                write!(f, "contour {contour} has {actual} points, expected {expected}")
+            }
+            IncompatReason::OnCurveFlag { point } => {
+                write!(f, "point {point} differs in being on or off the curve")
+            }
+            IncompatReason::ComponentCount { expected, actual } => {
+                write!(f, "{actual} components, expected {expected}")
+            }
+        }
+    }
+}
+
+/// Why an axis map is not a valid avar segment map.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AvarMapIssue {
+    /// A mapping has a coordinate outside -1..=1
+    OutOfRange { from: f32, to: f32 },
+    /// A mapping's input does not increase, or its output decreases, after
+    /// the previous one
+    NotMonotonic { previous: (f32, f32), next: (f32, f32) },
+    /// One of -1, 0 and 1 does not map to itself
+    MissingAnchor(f32),
+}
+
+impl fmt::Display for AvarMapIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OutOfRange { from, to } => write!(f, "({from}, {to}) is outside -1..=1"),
+            Self::NotMonotonic { previous, next } => {
+                write!(f, "{previous:?} -> {next:?} is not monotonic")
+            }
+            Self::MissingAnchor(anchor) => write!(f, "missing anchor ({anchor}, {anchor})"),
+        }
+    }
+}
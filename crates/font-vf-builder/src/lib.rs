@@ -26,11 +26,22 @@
 //! std::fs::write("Variable.ttf", vf_data).unwrap();
 //! ```
 
+mod anchors;
 mod designspace;
+mod designspace_xml;
 mod error;
+mod points;
+mod start_points;
 mod variation_model;
+#[cfg(feature = "verify")]
+mod verify;
 mod vf_builder;
 
-pub use designspace::{Axis, DesignSpace, Instance, Source};
-pub use error::{Error, Result};
-pub use vf_builder::build_variable_font;
+pub use designspace::{AvarSpacing, Axis, DesignSpace, Instance, Source};
+pub use error::{AvarMapIssue, Error, GlyphIncompat, IncompatReason, Result};
+pub use points::{GlyphPoints, build_variable_font_from_points};
+pub use start_points::StartPointWarning;
+pub use variation_model::{RegionDescription, VariationModel};
+#[cfg(feature = "verify")]
+pub use verify::{build_variable_font_verified, verify_round_trip};
+pub use vf_builder::{BuildReport, build_variable_font, build_variable_font_with_report};
@@ -0,0 +1,215 @@
+//! Building variable fonts from per-master point data instead of TTF masters.
+
+use std::collections::HashMap;
+
+use read_fonts::{
+    tables::glyf::CurvePoint,
+    types::{Fixed, GlyphId, LongDateTime, NameId},
+};
+use write_fonts::{
+    FontBuilder,
+    tables::{
+        glyf::{Bbox, GlyfLocaBuilder, Glyph, SimpleGlyph},
+        gvar::Gvar,
+        head::{Flags, Head, MacStyle},
+        hhea::Hhea,
+        hmtx::{Hmtx, LongMetric},
+        loca::LocaFormat,
+        maxp::Maxp,
+        name::{Name, NameRecord},
+    },
+};
+
+use crate::{
+    designspace::{Axis, DesignSpace, Source},
+    error::{Error, Result},
+    variation_model::VariationModel,
+    vf_builder::{StatNames, build_fvar, point_variations},
+};
+
+/// One glyph's outline in every master.
+#[derive(Debug, Clone)]
+pub struct GlyphPoints {
+    /// Index of the last point of each contour, shared by all masters
+    pub contour_ends: Vec<u16>,
+    /// Points of the glyph in each master, in the same order as the locations
+    pub masters: Vec<Vec<CurvePoint>>,
+    /// Advance width in the default master
+    pub advance_width: u16,
+}
+
+impl GlyphPoints {
+    /// Create a glyph from its contour ends and per-master points.
+    pub fn new(contour_ends: Vec<u16>, masters: Vec<Vec<CurvePoint>>, advance_width: u16) -> Self {
+        Self { contour_ends, masters, advance_width }
+    }
+}
+
+/// Build a TrueType variable font from point data, without master fonts.
+///
+/// `locations` gives each master's user-space location, keyed by axis tag;
+/// exactly one must sit at the default of every axis. `glyphs` are laid out
+/// in glyph ID order, and each carries one point set per location. Outlines
+/// and advances come from the default master, deltas from the same variation
+/// model [`build_variable_font`](crate::build_variable_font) uses. The font
+/// is minimal: head, hhea, maxp, hmtx, name, glyf, loca, fvar and gvar, at
+/// `units_per_em`. Its name table calls the default `family_name` Regular.
+pub fn build_variable_font_from_points(
+    family_name: &str,
+    axes: &[Axis],
+    locations: &[HashMap<String, f32>],
+    glyphs: &[GlyphPoints],
+    units_per_em: u16,
+) -> Result<Vec<u8>> {
+    let sources = locations
+        .iter()
+        .map(|location| Source { location: location.clone(), ..Source::new("", []) })
+        .collect();
+    let designspace = DesignSpace::new(axes.to_vec(), sources);
+    designspace.validate().map_err(Error::InvalidDesignspace)?;
+    let model = VariationModel::new(&designspace).ok_or(Error::NoDefaultSource)?;
+    let num_glyphs = u16::try_from(glyphs.len())
+        .map_err(|_| Error::InvalidDesignspace(format!("{} glyphs is too many", glyphs.len())))?;
+
+    let mut glyf_builder = GlyfLocaBuilder::new();
+    let mut variations = Vec::with_capacity(glyphs.len());
+    let mut h_metrics = Vec::with_capacity(glyphs.len());
+    let mut font_bbox: Option<Bbox> = None;
+    let (mut max_points, mut max_contours) = (0, 0);
+    for (glyph_idx, glyph) in glyphs.iter().enumerate() {
+        let gid = GlyphId::new(glyph_idx as u32);
+        if glyph.masters.len() != locations.len() {
+            return Err(Error::InvalidDesignspace(format!(
+                "glyph {glyph_idx} has {} point sets for {} locations",
+                glyph.masters.len(),
+                locations.len()
+            )));
+        }
+        let default_points = &glyph.masters[model.default_idx];
+        let expected = usize::from(glyph.contour_ends.last().map_or(0, |end| end + 1));
+        if let Some((master, points)) = glyph
+            .masters
+            .iter()
+            .enumerate()
+            .find(|(_, points)| points.len() != expected)
+        {
+            return Err(Error::PointSetMismatch {
+                master,
+                glyph_id: gid.to_u32(),
+                expected,
+                actual: points.len(),
+            });
+        }
+
+        let (outline, lsb) = if default_points.is_empty() {
+            (Glyph::Empty, 0)
+        } else {
+            let mut start = 0;
+            let contours = glyph
+                .contour_ends
+                .iter()
+                .map(|&end| {
+                    let contour = default_points[start..=usize::from(end)].to_vec();
+                    start = usize::from(end) + 1;
+                    contour.into()
+                })
+                .collect();
+            let mut simple = SimpleGlyph {
+                bbox: Bbox::default(),
+                contours,
+                instructions: vec![],
+            };
+            simple.recompute_bounding_box();
+            font_bbox = Some(font_bbox.map_or(simple.bbox, |bbox| bbox.union(simple.bbox)));
+            max_points = max_points.max(default_points.len() as u16);
+            max_contours = max_contours.max(glyph.contour_ends.len() as u16);
+            let lsb = simple.bbox.x_min;
+            (Glyph::Simple(simple), lsb)
+        };
+        h_metrics.push(LongMetric::new(glyph.advance_width, lsb));
+        glyf_builder.add_glyph(&outline)?;
+
+        let master_points: Vec<Vec<(i16, i16)>> = glyph
+            .masters
+            .iter()
+            .map(|points| points.iter().map(|p| (p.x, p.y)).collect())
+            .collect();
+        let contour_ends: Vec<usize> = glyph.contour_ends.iter().map(|&e| usize::from(e)).collect();
+        variations.push(point_variations(gid, &master_points, &contour_ends, &model));
+    }
+    let (glyf, loca, loca_format) = glyf_builder.build();
+    let gvar = Gvar::new(variations, axes.len() as u16).map_err(Error::GvarBuild)?;
+    let bbox = font_bbox.unwrap_or_default();
+
+    let head = Head::new(
+        Fixed::ONE,
+        0,
+        Flags::empty(),
+        units_per_em,
+        LongDateTime::new(0),
+        LongDateTime::new(0),
+        bbox.x_min,
+        bbox.y_min,
+        bbox.x_max,
+        bbox.y_max,
+        MacStyle::empty(),
+        0,
+        match loca_format {
+            LocaFormat::Short => 0,
+            LocaFormat::Long => 1,
+        },
+    );
+    let hhea = Hhea {
+        ascender: bbox.y_max.into(),
+        descender: bbox.y_min.into(),
+        advance_width_max: glyphs.iter().map(|g| g.advance_width).max().unwrap_or(0).into(),
+        number_of_h_metrics: num_glyphs,
+        caret_slope_rise: 1,
+        ..Default::default()
+    };
+    let maxp = Maxp {
+        max_points: Some(max_points),
+        max_contours: Some(max_contours),
+        max_composite_points: Some(0),
+        max_composite_contours: Some(0),
+        max_zones: Some(1),
+        max_twilight_points: Some(0),
+        max_storage: Some(0),
+        max_function_defs: Some(0),
+        max_instruction_defs: Some(0),
+        max_stack_elements: Some(0),
+        max_size_of_instructions: Some(0),
+        max_component_elements: Some(0),
+        max_component_depth: Some(0),
+        ..Maxp::new(num_glyphs)
+    };
+    let family_names = [
+        (NameId::FAMILY_NAME, family_name.to_string()),
+        (NameId::SUBFAMILY_NAME, "Regular".to_string()),
+        (NameId::FULL_NAME, format!("{family_name} Regular")),
+        (NameId::POSTSCRIPT_NAME, format!("{}-Regular", family_name.replace(' ', ""))),
+    ];
+    let stat_names = StatNames::new(&designspace)
+        .strings
+        .into_iter()
+        .map(|(id, string)| (NameId::new(id), string));
+    let name = Name::new(
+        family_names
+            .into_iter()
+            .chain(stat_names)
+            .map(|(id, string)| NameRecord::new(3, 1, 0x409, id, string.into()))
+            .collect(),
+    );
+
+    let mut builder = FontBuilder::new();
+    builder.add_table(&head)?;
+    builder.add_table(&hhea)?;
+    builder.add_table(&maxp)?;
+    builder.add_table(&Hmtx::new(h_metrics, vec![]))?;
+    builder.add_table(&name)?;
+    builder.add_table(&glyf)?;
+    builder.add_table(&loca)?;
+    builder.add_table(&build_fvar(&designspace)?)?;
+    builder.add_table(&gvar)?;
+    Ok(builder.build())
+}
@@ -0,0 +1,288 @@
+//! Contour start point and direction checks across masters.
+//!
+//! Masters can agree on every point count and still interpolate badly when a
+//! contour starts at a different point, or runs the other way round, than in
+//! the default master: each point then travels to the wrong partner and the
+//! outline twists at intermediate locations.
+
+use std::{fmt, fs::read, path::PathBuf};
+
+use read_fonts::{
+    FontData, FontRef, TableProvider,
+    tables::glyf::{CurvePoint, Glyph as ReadGlyph, SimpleGlyph},
+    types::GlyphId,
+};
+use write_fonts::{
+    FontBuilder,
+    from_obj::{FromObjRef, ToOwnedTable},
+    tables::{
+        glyf::{CompositeGlyph, GlyfLocaBuilder, Glyph, SimpleGlyph as WriteSimpleGlyph},
+        head::Head,
+        loca::LocaFormat,
+    },
+};
+
+use crate::{
+    designspace::DesignSpace,
+    error::{
+        Error::{self, MissingTable, ParseFont, ReadFont},
+        Result,
+    },
+};
+
+/// A better alignment is reported only when it at most halves the squared
+/// distance to the default contour, so near-symmetric contours such as
+/// circles do not flag on ordinary master differences.
+const MIN_IMPROVEMENT: f64 = 0.5;
+
+/// A master contour whose start point or direction differs from the default
+/// master's.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StartPointWarning {
+    pub glyph_id: u32,
+    /// Path of the master that differs from the default
+    pub path: PathBuf,
+    pub contour: usize,
+    /// Index within the contour of the master point matching the default's
+    /// start point
+    pub start: usize,
+    /// Whether the master contour runs in the opposite direction
+    pub reversed: bool,
+}
+
+impl fmt::Display for StartPointWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "glyph {} in '{}': contour {} starts at point {}",
+            self.glyph_id,
+            self.path.display(),
+            self.contour,
+            self.start
+        )?;
+        if self.reversed {
+            write!(f, " and is reversed")?;
+        }
+        Ok(())
+    }
+}
+
+impl DesignSpace {
+    /// Compare every master's contours against the default master's and
+    /// report those that line up better once rotated or reversed.
+    ///
+    /// For each simple glyph, every contour with the default's point count
+    /// is tried at each start point in both directions, keeping on-curve
+    /// flags aligned, and the closest fit by squared distance after centering
+    /// wins. Glyphs a master leaves empty or draws differently are skipped;
+    /// the structural checks in
+    /// [`build_variable_font`](crate::build_variable_font) report those.
+    pub fn check_start_points(&self) -> Result<Vec<StartPointWarning>> {
+        let master_data = self.read_masters()?;
+        let masters = self.parse_masters(&master_data)?;
+        start_point_warnings(self, &masters)
+    }
+
+    /// Read every master and return its data, in source order, with the
+    /// contours [`check_start_points`](Self::check_start_points) reports
+    /// rotated and reversed to match the default master.
+    ///
+    /// Masters without warnings are returned unchanged. Hinting
+    /// instructions of rewritten glyphs are kept as they are.
+    pub fn fix_start_points(&self) -> Result<Vec<Vec<u8>>> {
+        let master_data = self.read_masters()?;
+        let masters = self.parse_masters(&master_data)?;
+        let warnings = start_point_warnings(self, &masters)?;
+
+        masters
+            .iter()
+            .zip(&self.sources)
+            .map(|(master, source)| {
+                let fixes: Vec<&StartPointWarning> = warnings
+                    .iter()
+                    .filter(|warning| warning.path == source.path)
+                    .collect();
+                if fixes.is_empty() {
+                    Ok(master.data().as_bytes().to_vec())
+                } else {
+                    realign_contours(master, &fixes)
+                }
+            })
+            .collect()
+    }
+
+    fn read_masters(&self) -> Result<Vec<Vec<u8>>> {
+        self.sources
+            .iter()
+            .map(|source| {
+                read(&source.path).map_err(|e| ReadFont { path: source.path.clone(), source: e })
+            })
+            .collect()
+    }
+
+    fn parse_masters<'a>(&self, master_data: &'a [Vec<u8>]) -> Result<Vec<FontRef<'a>>> {
+        master_data
+            .iter()
+            .zip(&self.sources)
+            .map(|(data, source)| {
+                FontRef::new(data)
+                    .map_err(|e| ParseFont { path: source.path.clone(), message: e.to_string() })
+            })
+            .collect()
+    }
+}
+
+fn start_point_warnings(
+    designspace: &DesignSpace,
+    masters: &[FontRef],
+) -> Result<Vec<StartPointWarning>> {
+    let default_idx = designspace.default_source_index().ok_or(Error::NoDefaultSource)?;
+    let glyf_loca = |idx: usize| {
+        let master: &FontRef = &masters[idx];
+        let missing = || MissingTable {
+            path: designspace.sources[idx].path.clone(),
+            table: "glyf".to_string(),
+        };
+        Ok::<_, Error>((master.glyf().map_err(|_| missing())?, master.loca(None)?))
+    };
+    let (default_glyf, default_loca) = glyf_loca(default_idx)?;
+    let num_glyphs = masters[default_idx].maxp()?.num_glyphs();
+
+    let mut warnings = Vec::new();
+    for master_idx in (0..masters.len()).filter(|idx| *idx != default_idx) {
+        let (glyf, loca) = glyf_loca(master_idx)?;
+        for gid in (0..u32::from(num_glyphs)).map(GlyphId::new) {
+            let (Ok(Some(ReadGlyph::Simple(default))), Ok(Some(ReadGlyph::Simple(master)))) =
+                (default_loca.get_glyf(gid, &default_glyf), loca.get_glyf(gid, &glyf))
+            else {
+                continue;
+            };
+            let (default, master) = (contours(&default), contours(&master));
+            if default.len() != master.len() {
+                continue;
+            }
+            for (contour, (expected, actual)) in default.iter().zip(&master).enumerate() {
+                if let Some((start, reversed)) = misalignment(expected, actual) {
+                    warnings.push(StartPointWarning {
+                        glyph_id: gid.to_u32(),
+                        path: designspace.sources[master_idx].path.clone(),
+                        contour,
+                        start,
+                        reversed,
+                    });
+                }
+            }
+        }
+    }
+    Ok(warnings)
+}
+
+fn contours(glyph: &SimpleGlyph) -> Vec<Vec<CurvePoint>> {
+    let points: Vec<CurvePoint> = glyph.points().collect();
+    let mut start = 0;
+    glyph
+        .end_pts_of_contours()
+        .iter()
+        .map(|end| {
+            let end = usize::from(end.get()) + 1;
+            let contour = points.get(start..end).unwrap_or_default().to_vec();
+            start = end;
+            contour
+        })
+        .collect()
+}
+
+/// Master point index at `i` of `actual` read from `start`, in reverse when
+/// `reversed`.
+fn aligned_index(i: usize, start: usize, reversed: bool, len: usize) -> usize {
+    if reversed { (start + len - i) % len } else { (start + i) % len }
+}
+
+/// The start point and direction that best align `actual` with `expected`,
+/// if they beat the contour as drawn.
+fn misalignment(expected: &[CurvePoint], actual: &[CurvePoint]) -> Option<(usize, bool)> {
+    let len = expected.len();
+    if len < 3 || actual.len() != len {
+        return None;
+    }
+    let centroid = |points: &[CurvePoint]| {
+        let (x, y) = points
+            .iter()
+            .fold((0.0, 0.0), |(x, y), p| (x + f64::from(p.x), y + f64::from(p.y)));
+        (x / len as f64, y / len as f64)
+    };
+    let (expected_center, actual_center) = (centroid(expected), centroid(actual));
+
+    let cost = |start: usize, reversed: bool| {
+        let mut flags_match = true;
+        let mut sum = 0.0;
+        for (i, e) in expected.iter().enumerate() {
+            let a = actual[aligned_index(i, start, reversed, len)];
+            flags_match &= a.on_curve == e.on_curve;
+            let dx = (f64::from(e.x) - expected_center.0) - (f64::from(a.x) - actual_center.0);
+            let dy = (f64::from(e.y) - expected_center.1) - (f64::from(a.y) - actual_center.1);
+            sum += dx * dx + dy * dy;
+        }
+        (flags_match, sum)
+    };
+
+    let (drawn_flags_match, drawn_cost) = cost(0, false);
+    let (best, best_cost) = (0..len)
+        .flat_map(|start| [(start, false), (start, true)])
+        .filter(|&alignment| alignment != (0, false))
+        .filter_map(|(start, reversed)| {
+            let (flags_match, sum) = cost(start, reversed);
+            flags_match.then_some(((start, reversed), sum))
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))?;
+
+    let improves = if drawn_flags_match { best_cost < drawn_cost * MIN_IMPROVEMENT } else { true };
+    improves.then_some(best)
+}
+
+/// Rewrite `master`'s glyf and loca with each fixed contour read from its
+/// matching start point and direction.
+fn realign_contours(master: &FontRef, fixes: &[&StartPointWarning]) -> Result<Vec<u8>> {
+    let glyf = master.glyf()?;
+    let loca = master.loca(None)?;
+
+    let mut builder = GlyfLocaBuilder::new();
+    for gid in 0..u32::from(master.maxp()?.num_glyphs()) {
+        let glyph = match loca.get_glyf(GlyphId::new(gid), &glyf)? {
+            None => Glyph::Empty,
+            Some(ReadGlyph::Simple(simple)) => {
+                let mut glyph = WriteSimpleGlyph::from_obj_ref(&simple, FontData::new(&[]));
+                for fix in fixes.iter().filter(|fix| fix.glyph_id == gid) {
+                    let Some(contour) = glyph.contours.get_mut(fix.contour) else {
+                        continue;
+                    };
+                    let points: Vec<CurvePoint> = contour.clone().into();
+                    let len = points.len();
+                    *contour = (0..len)
+                        .map(|i| points[aligned_index(i, fix.start, fix.reversed, len)])
+                        .collect::<Vec<_>>()
+                        .into();
+                }
+                Glyph::Simple(glyph)
+            }
+            Some(ReadGlyph::Composite(composite)) => {
+                Glyph::Composite(CompositeGlyph::from_obj_ref(&composite, FontData::new(&[])))
+            }
+        };
+        builder.add_glyph(&glyph)?;
+    }
+    let (glyf, loca, loca_format) = builder.build();
+
+    let mut head: Head = master.head()?.to_owned_table();
+    head.index_to_loc_format = match loca_format {
+        LocaFormat::Short => 0,
+        LocaFormat::Long => 1,
+    };
+
+    let mut font_builder = FontBuilder::new();
+    font_builder.add_table(&glyf)?;
+    font_builder.add_table(&loca)?;
+    font_builder.add_table(&head)?;
+    font_builder.copy_missing_tables(master.clone());
+    Ok(font_builder.build())
+}
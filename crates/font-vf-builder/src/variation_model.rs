@@ -139,6 +139,15 @@ impl Region {
     }
 }
 
+/// Public view of one region of a [`VariationModel`], for inspection.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegionDescription {
+    /// Index of the master this region belongs to, in designspace source order
+    pub master_idx: usize,
+    /// (min, peak, max) for each axis in normalized coordinates
+    pub axes: Vec<(f32, f32, f32)>,
+}
+
 /// Variation model for computing deltas from master values.
 #[derive(Debug)]
 pub struct VariationModel {
@@ -200,6 +209,18 @@ impl VariationModel {
         Some(Self { regions, default_idx, master_order, region_scalars })
     }
 
+    /// Describe the regions this model derived, in delta computation order.
+    pub fn describe(&self) -> Vec<RegionDescription> {
+        self.regions
+            .iter()
+            .zip(&self.master_order[1..])
+            .map(|(region, &master_idx)| RegionDescription {
+                master_idx,
+                axes: region.axes.clone(),
+            })
+            .collect()
+    }
+
     /// Compute deltas from master values.
     ///
     /// Given values at each master location, compute the deltas needed
@@ -212,7 +233,6 @@ impl VariationModel {
     /// # Returns
     ///
     /// A tuple of (default_value, deltas) where deltas correspond to `self.regions`.
-    #[cfg(test)]
     pub fn compute_deltas(&self, master_values: &[i16]) -> (i16, Vec<i16>) {
         let default_value = master_values[self.default_idx];
         let mut deltas = Vec::with_capacity(self.regions.len());
@@ -365,4 +385,17 @@ mod tests {
         // This is a simplified test - actual values depend on master ordering
         assert_eq!(deltas.len(), 3);
     }
+
+    #[test]
+    fn describe_regions() {
+        let ds = make_2axis_designspace();
+        let model = VariationModel::new(&ds).unwrap();
+        let regions = model.describe();
+
+        // Single-axis masters come before the corner master.
+        let masters: Vec<usize> = regions.iter().map(|r| r.master_idx).collect();
+        assert_eq!(masters, vec![1, 2, 3]);
+        assert_eq!(regions[0].axes, vec![(0.0, 1.0, 1.0), (0.0, 0.0, 0.0)]);
+        assert_eq!(regions[2].axes, vec![(0.0, 1.0, 1.0), (0.0, 1.0, 1.0)]);
+    }
 }
@@ -0,0 +1,116 @@
+//! Round-trip verification of built variable fonts.
+//!
+//! Instancing a variable font at a master's location must reproduce that
+//! master's outlines. Comparing the two catches delta-computation bugs that
+//! would otherwise only show up as distorted glyphs at intermediate weights.
+
+use std::fs::read;
+
+use font_instancer::{AxisLocation, instantiate};
+use read_fonts::{
+    FontRef, TableProvider,
+    tables::{
+        glyf::{Anchor, Glyf, Glyph},
+        loca::Loca,
+    },
+    types::GlyphId,
+};
+
+use crate::{
+    designspace::DesignSpace,
+    error::{
+        Error::{ParseFont, ReadFont, RoundTripMismatch},
+        Result,
+    },
+    vf_builder::build_variable_font,
+};
+
+/// Largest per-coordinate difference accepted between a master and its
+/// instance. Deltas are IUP-optimized to within half a unit, so an instance
+/// may land one unit off its master.
+const ROUND_TRIP_TOLERANCE: i32 = 1;
+
+/// Build a variable font and verify that it round-trips through every master.
+///
+/// See [`verify_round_trip`] for what is compared.
+pub fn build_variable_font_verified(designspace: &DesignSpace) -> Result<Vec<u8>> {
+    let vf = build_variable_font(designspace)?;
+    verify_round_trip(designspace, &vf)?;
+    Ok(vf)
+}
+
+/// Instance `vf` at each source's location and compare the result against
+/// that source's outlines.
+///
+/// Masters at a different unitsPerEm than `vf` are scaled to its UPEM first,
+/// as [`build_variable_font`] does.
+///
+/// Simple glyphs are compared point by point, composite glyphs by component
+/// offset. Returns [`Error::RoundTripMismatch`](crate::Error::RoundTripMismatch)
+/// for the first glyph whose coordinates drift further than rounding can
+/// explain.
+pub fn verify_round_trip(designspace: &DesignSpace, vf: &[u8]) -> Result<()> {
+    let vf_upem = FontRef::new(vf)?.head()?.units_per_em();
+    for source in &designspace.sources {
+        let master_data =
+            read(&source.path).map_err(|e| ReadFont { path: source.path.clone(), source: e })?;
+        let master = FontRef::new(&master_data)
+            .map_err(|e| ParseFont { path: source.path.clone(), message: e.to_string() })?;
+
+        let location: Vec<AxisLocation> = designspace
+            .axes
+            .iter()
+            .map(|axis| AxisLocation::new(&axis.tag, source.axis_value(axis)))
+            .collect();
+        let instance_data = instantiate(vf, &location)?;
+        let instance = FontRef::new(&instance_data)?;
+
+        let scale = f64::from(vf_upem) / f64::from(master.head()?.units_per_em());
+        if let Some(glyph_id) = first_mismatch(&master, &instance, scale)? {
+            return Err(RoundTripMismatch { path: source.path.clone(), glyph_id });
+        }
+    }
+    Ok(())
+}
+
+/// GID of the first glyph whose coordinates differ beyond the tolerance.
+fn first_mismatch(master: &FontRef, instance: &FontRef, scale: f64) -> Result<Option<u32>> {
+    let (master_glyf, master_loca) = (master.glyf()?, master.loca(None)?);
+    let (instance_glyf, instance_loca) = (instance.glyf()?, instance.loca(None)?);
+
+    for gid in 0..u32::from(master.maxp()?.num_glyphs()) {
+        let expected: Vec<(i32, i32)> = coordinates(&master_glyf, &master_loca, gid)?
+            .into_iter()
+            .map(|(x, y)| {
+                ((f64::from(x) * scale).round() as i32, (f64::from(y) * scale).round() as i32)
+            })
+            .collect();
+        let actual = coordinates(&instance_glyf, &instance_loca, gid)?;
+        let matches = expected.len() == actual.len()
+            && expected.iter().zip(&actual).all(|(a, b)| {
+                (a.0 - b.0).abs() <= ROUND_TRIP_TOLERANCE
+                    && (a.1 - b.1).abs() <= ROUND_TRIP_TOLERANCE
+            });
+        if !matches {
+            return Ok(Some(gid));
+        }
+    }
+    Ok(None)
+}
+
+/// Point coordinates of a simple glyph, or component offsets of a composite.
+fn coordinates(glyf: &Glyf, loca: &Loca, gid: u32) -> Result<Vec<(i32, i32)>> {
+    Ok(match loca.get_glyf(GlyphId::new(gid), glyf)? {
+        None => Vec::new(),
+        Some(Glyph::Simple(simple)) => {
+            simple.points().map(|p| (i32::from(p.x), i32::from(p.y))).collect()
+        }
+        Some(Glyph::Composite(composite)) => composite
+            .components()
+            .map(|component| match component.anchor {
+                Anchor::Offset { x, y } => (i32::from(x), i32::from(y)),
+                Anchor::Point { .. } => (0, 0),
+            })
+            .collect(),
+    })
+}
@@ -1,6 +1,9 @@
 //! Variable font builder implementation.
 
-use std::{collections::HashSet, time::Instant};
+use std::{
+    collections::{HashMap, HashSet},
+    time::Instant,
+};
 
 use log::info;
 use read_fonts::{
@@ -11,18 +14,22 @@ use write_fonts::{
     FontBuilder,
     from_obj::FromObjRef,
     tables::{
+        avar::{Avar, AxisValueMap, SegmentMaps},
         fvar::{AxisInstanceArrays, Fvar, InstanceRecord, VariationAxisRecord},
         glyf::{GlyfLocaBuilder, Glyph as WriteGlyph},
         gvar::{GlyphDelta, GlyphDeltas, GlyphVariations, Gvar, Tent, iup::iup_delta_optimize},
         head::Head,
+        hvar::Hvar,
         name::{Name, NameRecord},
         stat::{AxisRecord as StatAxisRecord, AxisValue, AxisValueTableFlags, Stat},
+        variations::{RegionAxisCoordinates, VariationRegion, ivs_builder::VariationStoreBuilder},
     },
 };
 
 use crate::{
+    anchors::build_variable_anchors,
     designspace::DesignSpace,
-    error::{Error, Result},
+    error::{Error, GlyphIncompat, IncompatReason, Result},
     variation_model::VariationModel,
 };
 
@@ -33,6 +40,7 @@ const SKIP_TABLES: &[Tag] = &[
     Tag::new(b"head"),
     Tag::new(b"fvar"),
     Tag::new(b"gvar"),
+    Tag::new(b"avar"),
     Tag::new(b"STAT"),
     Tag::new(b"HVAR"),
     Tag::new(b"MVAR"),
@@ -47,6 +55,16 @@ const SKIP_TABLES: &[Tag] = &[
 /// Starting name ID for instance names (256+ are user-defined)
 const INSTANCE_NAME_ID_START: u16 = 256;
 
+/// What [`build_variable_font_with_report`] derived alongside the font.
+#[derive(Debug, Clone, Default)]
+pub struct BuildReport {
+    /// `OS/2` (usWeightClass, usWidthClass) a static instanced at each named
+    /// instance's location should carry, by instance name. Taken from the
+    /// `wght` and `wdth` coordinates, or the default master's `OS/2` for an
+    /// axis the designspace lacks.
+    pub instance_classes: HashMap<String, (u16, u16)>,
+}
+
 /// Build a variable font from a designspace.
 ///
 /// This function:
@@ -56,7 +74,17 @@ const INSTANCE_NAME_ID_START: u16 = 256;
 /// 4. Builds fvar, gvar, and other required tables
 /// 5. Copies other tables from the default master
 pub fn build_variable_font(designspace: &DesignSpace) -> Result<Vec<u8>> {
+    build_variable_font_with_report(designspace).map(|(vf, _)| vf)
+}
+
+/// [`build_variable_font`], also returning a [`BuildReport`].
+pub fn build_variable_font_with_report(
+    designspace: &DesignSpace,
+) -> Result<(Vec<u8>, BuildReport)> {
     designspace.validate().map_err(Error::InvalidDesignspace)?;
+    for axis in &designspace.axes {
+        axis.validate_map()?;
+    }
 
     info!("Building variable font from {} masters", designspace.sources.len());
 
@@ -83,6 +111,7 @@ pub fn build_variable_font(designspace: &DesignSpace) -> Result<Vec<u8>> {
 
     // Verify glyph compatibility
     verify_glyph_compatibility(designspace, &masters)?;
+    let scales = master_scales(designspace, &masters)?;
 
     // Build variation model
     let model = VariationModel::new(designspace).ok_or(Error::NoDefaultSource)?;
@@ -100,7 +129,7 @@ pub fn build_variable_font(designspace: &DesignSpace) -> Result<Vec<u8>> {
 
     // Build gvar table
     let gvar_start = Instant::now();
-    let gvar = build_gvar(designspace, &masters, &model, num_glyphs)?;
+    let gvar = build_gvar(designspace, &masters, &scales, &model, num_glyphs)?;
     info!("Built gvar table in {:.2}s", gvar_start.elapsed().as_secs_f64());
 
     // Build glyf/loca tables (copy from default)
@@ -131,10 +160,37 @@ pub fn build_variable_font(designspace: &DesignSpace) -> Result<Vec<u8>> {
     builder.add_table(&name)?;
     builder.add_table(&stat)?;
 
-    // Copy GDEF without VarStore (source VarStore has wrong axis count)
-    if let Ok(gdef) = default_font.gdef() {
-        let new_gdef = build_gdef_without_varstore(&gdef);
-        builder.add_table(&new_gdef)?;
+    if let Some(avar) = build_avar(designspace) {
+        builder.add_table(&avar)?;
+        info!("Built avar table");
+    }
+
+    if designspace.hvar {
+        let hvar = build_hvar(designspace, &masters, &scales, &model, num_glyphs)?;
+        builder.add_table(&hvar)?;
+        info!("Built HVAR table");
+    }
+
+    // Rebuild GDEF's VarStore over the output axes (the source VarStore has
+    // the wrong axis count), holding the deltas of anchors that vary
+    let variable_anchors = build_variable_anchors(
+        &masters,
+        default_idx,
+        &scales,
+        &model,
+        designspace.axes.len() as u16,
+    )?;
+    let mut new_gdef = default_font
+        .gdef()
+        .ok()
+        .map(|gdef| build_gdef_without_varstore(&gdef));
+    if let Some((gpos, store)) = variable_anchors {
+        builder.add_table(&gpos)?;
+        new_gdef.get_or_insert_default().item_var_store = store.into();
+        info!("Built GPOS anchor variations");
+    }
+    if let Some(gdef) = new_gdef {
+        builder.add_table(&gdef)?;
     }
 
     // Copy GSUB without FeatureVariations (source uses wrong axis indices)
@@ -148,13 +204,57 @@ pub fn build_variable_font(designspace: &DesignSpace) -> Result<Vec<u8>> {
     for record in default_font.table_directory.table_records() {
         let tag = record.tag();
         if !skip_set.contains(&tag)
+            && !builder.contains(tag)
             && let Some(data) = default_font.table_data(tag)
         {
             builder.add_raw(tag, data);
         }
     }
 
-    Ok(builder.build())
+    let report = BuildReport {
+        instance_classes: instance_classes(designspace, default_font),
+    };
+    Ok((builder.build(), report))
+}
+
+/// `OS/2` weight and width classes of each named instance, see
+/// [`BuildReport::instance_classes`].
+fn instance_classes(
+    designspace: &DesignSpace,
+    default_font: &FontRef,
+) -> HashMap<String, (u16, u16)> {
+    let os2 = default_font.os2().ok();
+    let default_weight = os2.as_ref().map_or(400, |os2| os2.us_weight_class());
+    let default_width = os2.as_ref().map_or(5, |os2| os2.us_width_class());
+    let axis = |tag: &str| designspace.axes.iter().find(|axis| axis.tag == tag);
+    let (wght, wdth) = (axis("wght"), axis("wdth"));
+
+    designspace
+        .instances
+        .iter()
+        .map(|instance| {
+            let weight = wght.map_or(default_weight, |axis| {
+                instance.axis_value(axis).round().clamp(1.0, 1000.0) as u16
+            });
+            let width = wdth.map_or(default_width, |axis| width_class(instance.axis_value(axis)));
+            (instance.name.clone(), (weight, width))
+        })
+        .collect()
+}
+
+/// `usWidthClass` of a `wdth` percentage, interpolated between the
+/// registered widths in [`WIDTH_NAMES`] and rounded, as fontTools'
+/// instancer does.
+fn width_class(wdth: f32) -> u16 {
+    let wdth = wdth.clamp(WIDTH_NAMES[0].0, WIDTH_NAMES[WIDTH_NAMES.len() - 1].0);
+    let upper = WIDTH_NAMES
+        .iter()
+        .position(|(width, _)| *width >= wdth)
+        .unwrap_or(WIDTH_NAMES.len() - 1)
+        .max(1);
+    let (low, high) = (WIDTH_NAMES[upper - 1].0, WIDTH_NAMES[upper].0);
+    let class = upper as f32 + (wdth - low) / (high - low);
+    class.round() as u16
 }
 
 fn verify_glyph_compatibility(designspace: &DesignSpace, masters: &[FontRef]) -> Result<()> {
@@ -177,15 +277,163 @@ fn verify_glyph_compatibility(designspace: &DesignSpace, masters: &[FontRef]) ->
         }
     }
 
-    Ok(())
+    // Without a default glyf there is nothing to compare; the caller reports it
+    if default_font.glyf().is_err() {
+        return Ok(());
+    }
+    let mut master_glyfs = Vec::with_capacity(masters.len());
+    let mut master_locas = Vec::with_capacity(masters.len());
+    for (idx, master) in masters.iter().enumerate() {
+        let missing = || MissingTable {
+            path: designspace.sources[idx].path.clone(),
+            table: "glyf".to_string(),
+        };
+        master_glyfs.push(master.glyf().map_err(|_| missing())?);
+        master_locas.push(master.loca(None).map_err(|_| missing())?);
+    }
+
+    let glyphs = incompatible_glyphs(
+        designspace,
+        &master_glyfs,
+        &master_locas,
+        default_idx,
+        expected_glyphs,
+    );
+    if glyphs.is_empty() { Ok(()) } else { Err(Error::IncompatibleMasters { glyphs }) }
+}
+
+/// Compare every glyph's structure against the default master: glyph kind,
+/// contour and point counts and on-curve flags for simple glyphs, component
+/// counts for composites.
+///
+/// Glyphs a master leaves empty are skipped, since they take the default
+/// outline.
+fn incompatible_glyphs(
+    designspace: &DesignSpace,
+    master_glyfs: &[read_fonts::tables::glyf::Glyf],
+    master_locas: &[read_fonts::tables::loca::Loca],
+    default_idx: usize,
+    num_glyphs: u16,
+) -> Vec<GlyphIncompat> {
+    use read_fonts::tables::glyf::Glyph;
+
+    let glyph = |master_idx: usize, gid| {
+        master_locas[master_idx]
+            .get_glyf(gid, &master_glyfs[master_idx])
+            .ok()
+            .flatten()
+    };
+
+    let mut incompatible = Vec::new();
+    for glyph_idx in 0..num_glyphs {
+        let gid = GlyphId::new(u32::from(glyph_idx));
+        let Some(default_glyph) = glyph(default_idx, gid) else {
+            continue;
+        };
+        for master_idx in (0..master_glyfs.len()).filter(|idx| *idx != default_idx) {
+            let Some(master_glyph) = glyph(master_idx, gid) else {
+                continue;
+            };
+            let reason = match (&default_glyph, &master_glyph) {
+                (Glyph::Simple(expected), Glyph::Simple(actual)) => {
+                    simple_incompatibility(expected, actual)
+                }
+                (Glyph::Composite(expected), Glyph::Composite(actual)) => {
+                    let expected = expected.components().count();
+                    let actual = actual.components().count();
+                    (expected != actual)
+                        .then_some(IncompatReason::ComponentCount { expected, actual })
+                }
+                _ => Some(IncompatReason::GlyphKind),
+            };
+            if let Some(reason) = reason {
+                incompatible.push(GlyphIncompat {
+                    glyph_id: gid.to_u32(),
+                    path: designspace.sources[master_idx].path.clone(),
+                    reason,
+                });
+            }
+        }
+    }
+    incompatible
+}
+
+/// First structural difference between two simple glyphs, if any.
+fn simple_incompatibility(expected: &SimpleGlyph, actual: &SimpleGlyph) -> Option<IncompatReason> {
+    let (expected_ends, actual_ends) =
+        (expected.end_pts_of_contours(), actual.end_pts_of_contours());
+    if expected_ends.len() != actual_ends.len() {
+        return Some(IncompatReason::ContourCount {
+            expected: expected_ends.len(),
+            actual: actual_ends.len(),
+        });
+    }
+    let mut start = 0;
+    for (contour, (expected_end, actual_end)) in expected_ends.iter().zip(actual_ends).enumerate() {
+        let (expected_end, actual_end) =
+            (usize::from(expected_end.get()), usize::from(actual_end.get()));
+        if expected_end != actual_end {
+            return Some(IncompatReason::PointCount {
+                contour,
+                expected: (expected_end + 1).saturating_sub(start),
+                actual: (actual_end + 1).saturating_sub(start),
+            });
+        }
+        start = expected_end + 1;
+    }
+    expected
+        .points()
+        .zip(actual.points())
+        .position(|(expected, actual)| expected.on_curve != actual.on_curve)
+        .map(|point| IncompatReason::OnCurveFlag { point })
+}
+
+/// Factor that brings each master's coordinates to the default master's
+/// unitsPerEm.
+///
+/// Masters at the default's UPEM get 1.0. Any other master is an error
+/// unless [`DesignSpace::scale_upem`] is enabled.
+fn master_scales(designspace: &DesignSpace, masters: &[FontRef]) -> Result<Vec<f64>> {
+    let default_idx = designspace.default_source_index().ok_or(Error::NoDefaultSource)?;
+    let default_upem = masters[default_idx].head()?.units_per_em();
+
+    masters
+        .iter()
+        .zip(&designspace.sources)
+        .map(|(master, source)| {
+            let upem = master.head()?.units_per_em();
+            if upem == default_upem {
+                Ok(1.0)
+            } else if designspace.scale_upem {
+                info!("Scaling '{}' from {upem} to {default_upem} UPEM", source.path.display());
+                Ok(f64::from(default_upem) / f64::from(upem))
+            } else {
+                Err(UpemMismatch {
+                    path: source.path.clone(),
+                    expected: default_upem,
+                    actual: upem,
+                })
+            }
+        })
+        .collect()
+}
+
+/// Scale a coordinate pair by `scale`, rounding to the nearest unit.
+pub(crate) fn scale_xy(x: i16, y: i16, scale: f64) -> (i16, i16) {
+    if scale == 1.0 {
+        return (x, y);
+    }
+    let scale = |v: i16| (f64::from(v) * scale).round() as i16;
+    (scale(x), scale(y))
 }
 
-fn build_fvar(designspace: &DesignSpace) -> Result<Fvar> {
+pub(crate) fn build_fvar(designspace: &DesignSpace) -> Result<Fvar> {
+    let axis_name_ids = StatNames::new(designspace).axis_name_ids;
     let axes: Vec<VariationAxisRecord> = designspace
         .axes
         .iter()
-        .enumerate()
-        .map(|(idx, axis)| {
+        .zip(axis_name_ids)
+        .map(|(axis, axis_name_id)| {
             let mut tag_bytes = [b' '; 4];
             for (i, b) in axis.tag.bytes().take(4).enumerate() {
                 tag_bytes[i] = b;
@@ -197,7 +445,7 @@ fn build_fvar(designspace: &DesignSpace) -> Result<Fvar> {
                 default_value: Fixed::from_f64(f64::from(axis.default)),
                 max_value: Fixed::from_f64(f64::from(axis.maximum)),
                 flags: 0u16,
-                axis_name_id: NameId::new(256 + idx as u16),
+                axis_name_id: NameId::new(axis_name_id),
             }
         })
         .collect();
@@ -232,6 +480,85 @@ fn build_fvar(designspace: &DesignSpace) -> Result<Fvar> {
     })
 }
 
+fn build_avar(designspace: &DesignSpace) -> Option<Avar> {
+    let maps = designspace.avar_maps()?;
+    let segment_maps = maps
+        .iter()
+        .map(|map| {
+            SegmentMaps::new(
+                map.iter()
+                    .map(|(from, to)| {
+                        AxisValueMap::new(F2Dot14::from_f32(*from), F2Dot14::from_f32(*to))
+                    })
+                    .collect(),
+            )
+        })
+        .collect();
+    Some(Avar::new(segment_maps))
+}
+
+/// Build an HVAR table whose item variation store holds one delta set per
+/// glyph, so glyph IDs serve as inner indices and no advance mapping is
+/// needed.
+///
+/// Deltas come from the same variation model as the outlines, so each
+/// region's advance delta is what the advance width phantom point would
+/// carry in gvar.
+fn build_hvar(
+    designspace: &DesignSpace,
+    masters: &[FontRef],
+    scales: &[f64],
+    model: &VariationModel,
+    num_glyphs: u16,
+) -> Result<Hvar> {
+    let master_hmtx: Vec<_> = masters
+        .iter()
+        .map(TableProvider::hmtx)
+        .collect::<result::Result<Vec<_>, _>>()?;
+    let regions = variation_regions(model);
+
+    let mut store = VariationStoreBuilder::new_with_implicit_indices(designspace.axes.len() as u16);
+    for glyph_idx in 0..num_glyphs {
+        let gid = GlyphId::new(u32::from(glyph_idx));
+        let advances: Vec<i16> = master_hmtx
+            .iter()
+            .zip(scales)
+            .map(|(hmtx, &scale)| {
+                let advance = hmtx.advance(gid).unwrap_or_default();
+                scale_xy(i16::try_from(advance).unwrap_or(i16::MAX), 0, scale).0
+            })
+            .collect();
+        let (_, deltas) = model.compute_deltas(&advances);
+        store.add_deltas(regions.iter().cloned().zip(deltas).collect());
+    }
+    let (store, _) = store.build();
+
+    Ok(Hvar::new(store, None, None, None))
+}
+
+/// The model's regions as ItemVariationStore regions.
+pub(crate) fn variation_regions(model: &VariationModel) -> Vec<VariationRegion> {
+    model
+        .regions
+        .iter()
+        .map(|region| {
+            VariationRegion::new(
+                region
+                    .axes
+                    .iter()
+                    .map(|&(min, peak, max)| {
+                        RegionAxisCoordinates::new(
+                            F2Dot14::from_f32(min),
+                            F2Dot14::from_f32(peak),
+                            F2Dot14::from_f32(max),
+                        )
+                    })
+                    .collect(),
+            )
+        })
+        .collect()
+}
+
 /// Build name table, copying from default and adding instance names.
 fn build_name(default_font: &FontRef, designspace: &DesignSpace) -> Result<Name> {
     let name_table = default_font.name().map_err(|_| MissingTable {
@@ -246,25 +573,41 @@ fn build_name(default_font: &FontRef, designspace: &DesignSpace) -> Result<Name>
         ..INSTANCE_NAME_ID_START + designspace.instances.len() as u16)
         .collect();
 
-    // Name IDs used for STAT table values
-    let stat_name_ids: HashSet<u16> = [280, 281, 282, 283, 284, 285, 286, 287, 290, 291]
-        .into_iter()
-        .collect();
+    // Name IDs used for axis names and STAT table values
+    let stat_strings = StatNames::new(designspace).strings;
+    let stat_name_ids: HashSet<u16> = stat_strings.iter().map(|(id, _)| *id).collect();
 
     // Name IDs used for fvar instance PostScript names
     let ps_name_ids: HashSet<u16> = (INSTANCE_PS_NAME_ID_START
         ..INSTANCE_PS_NAME_ID_START + designspace.instances.len() as u16)
         .collect();
 
+    // Family override: name ID -> replacement string
+    let family_names: Vec<(u16, String)> = match &designspace.family_name {
+        Some(family) => {
+            let subfamily = english_name(&name_table, NameId::TYPOGRAPHIC_SUBFAMILY_NAME)
+                .or_else(|| english_name(&name_table, NameId::SUBFAMILY_NAME))
+                .unwrap_or_else(|| "Regular".to_string());
+            vec![
+                (1, family.clone()),
+                (4, format!("{family} {subfamily}")),
+                (6, format!("{}-{}", family.replace(' ', ""), subfamily.replace(' ', ""))),
+                (16, family.clone()),
+            ]
+        }
+        None => Vec::new(),
+    };
+
     // Copy existing name records (skip any that will be replaced)
     for record in name_table.name_record() {
         let name_id = record.name_id().to_u16();
 
-        // Skip name IDs that will be used for instances, STAT, or instance
-        // PostScript names.
+        // Skip name IDs that will be used for instances, STAT, instance
+        // PostScript names, or the family override.
         if instance_name_ids.contains(&name_id)
             || stat_name_ids.contains(&name_id)
             || ps_name_ids.contains(&name_id)
+            || family_names.iter().any(|(id, _)| *id == name_id)
         {
             continue;
         }
@@ -283,6 +626,18 @@ fn build_name(default_font: &FontRef, designspace: &DesignSpace) -> Result<Name>
         ));
     }
 
+    // Add overridden family names for both platforms (Windows and Mac)
+    for (name_id, string) in &family_names {
+        new_records.push(NameRecord::new(
+            3,
+            1,
+            0x409,
+            NameId::new(*name_id),
+            string.clone().into(),
+        ));
+        new_records.push(NameRecord::new(1, 0, 0, NameId::new(*name_id), string.clone().into()));
+    }
+
     // Add instance names for both platforms (Windows and Mac)
     for (idx, instance) in designspace.instances.iter().enumerate() {
         let name_id = INSTANCE_NAME_ID_START + idx as u16;
@@ -319,35 +674,10 @@ fn build_name(default_font: &FontRef, designspace: &DesignSpace) -> Result<Name>
         }
     }
 
-    // Add STAT table name entries
-    // Weight values (name IDs 280-287), restricted to the axis range.
-    for (_value, name, name_id) in weight_stops_in_range(designspace) {
-        // Windows
-        new_records.push(NameRecord::new(
-            3,
-            1,
-            0x409,
-            NameId::new(name_id),
-            name.to_string().into(),
-        ));
-        // Mac
-        new_records.push(NameRecord::new(1, 0, 0, NameId::new(name_id), name.to_string().into()));
-    }
-
-    // Italic values (name IDs 290-291)
-    let stat_italic_names = [(290, "Upright"), (291, "Italic")];
-
-    for (name_id, name) in stat_italic_names {
-        // Windows
-        new_records.push(NameRecord::new(
-            3,
-            1,
-            0x409,
-            NameId::new(name_id),
-            name.to_string().into(),
-        ));
-        // Mac
-        new_records.push(NameRecord::new(1, 0, 0, NameId::new(name_id), name.to_string().into()));
+    // Add axis and STAT value names for both platforms (Windows and Mac)
+    for (name_id, name) in stat_strings {
+        new_records.push(NameRecord::new(3, 1, 0x409, NameId::new(name_id), name.clone().into()));
+        new_records.push(NameRecord::new(1, 0, 0, NameId::new(name_id), name.into()));
     }
 
     // Sort records by (platformID, encodingID, languageID, nameID)
@@ -363,6 +693,28 @@ fn build_name(default_font: &FontRef, designspace: &DesignSpace) -> Result<Name>
     Ok(Name::new(new_records))
 }
 
+/// First Windows English (or, failing that, any) string for `name_id`.
+fn english_name(name_table: &read_fonts::tables::name::Name, name_id: NameId) -> Option<String> {
+    let string = |record: &read_fonts::tables::name::NameRecord| {
+        record
+            .string(name_table.string_data())
+            .ok()
+            .map(|s| s.chars().collect::<String>())
+    };
+    let records = name_table.name_record();
+    records
+        .iter()
+        .filter(|record| record.name_id() == name_id)
+        .find(|record| record.platform_id() == 3 && record.language_id() == 0x409)
+        .and_then(string)
+        .or_else(|| {
+            records
+                .iter()
+                .filter(|record| record.name_id() == name_id)
+                .find_map(string)
+        })
+}
+
 use std::{
     fs::read,
     result,
@@ -379,7 +731,8 @@ use write_fonts::{
 };
 
 use crate::error::Error::{
-    GlyphCountMismatch, MissingTable, ParseFont, PointCountMismatch, ReadFont,
+    DefaultGlyphEmpty, GlyphCountMismatch, MissingTable, ParseFont, PointCountMismatch, ReadFont,
+    UpemMismatch,
 };
 
 static TOTAL_POINTS: AtomicUsize = AtomicUsize::new(0);
@@ -391,6 +744,7 @@ static IUP_OPTIMIZE_NS: AtomicU64 = AtomicU64::new(0);
 fn build_gvar(
     designspace: &DesignSpace,
     masters: &[FontRef],
+    scales: &[f64],
     model: &VariationModel,
     num_glyphs: u16,
 ) -> Result<Gvar> {
@@ -417,7 +771,7 @@ fn build_gvar(
     let all_variations: Vec<GlyphVariations> = (0..num_glyphs)
         .map(|glyph_idx| {
             let gid = GlyphId::new(u32::from(glyph_idx));
-            build_glyph_variations(gid, designspace, &master_glyfs, &master_locas, model)
+            build_glyph_variations(gid, designspace, &master_glyfs, &master_locas, scales, model)
         })
         .collect::<Result<Vec<_>>>()?;
     let variations_elapsed = variations_start.elapsed().as_secs_f64();
@@ -451,6 +805,7 @@ fn build_glyph_variations(
     designspace: &DesignSpace,
     master_glyfs: &[read_fonts::tables::glyf::Glyf],
     master_locas: &[read_fonts::tables::loca::Loca],
+    scales: &[f64],
     model: &VariationModel,
 ) -> Result<GlyphVariations> {
     use read_fonts::tables::glyf::Glyph;
@@ -464,7 +819,16 @@ fn build_glyph_variations(
         .flatten();
 
     let Some(default_glyph) = default_glyph else {
-        // Empty glyph - no variations needed
+        // Empty glyph - no variations needed, unless another master draws it
+        let drawn = master_glyfs.iter().zip(master_locas).position(|(glyf, loca)| {
+            matches!(loca.get_glyf(gid, glyf), Ok(Some(glyph)) if !is_empty_outline(&glyph))
+        });
+        if let Some(master_idx) = drawn {
+            return Err(DefaultGlyphEmpty {
+                path: designspace.sources[master_idx].path.clone(),
+                glyph_id: gid.to_u32(),
+            });
+        }
         return Ok(GlyphVariations::new(gid, vec![]));
     };
 
@@ -475,6 +839,7 @@ fn build_glyph_variations(
             designspace,
             master_glyfs,
             master_locas,
+            scales,
             model,
         ),
         Glyph::Composite(composite) => build_composite_glyph_variations(
@@ -483,17 +848,29 @@ fn build_glyph_variations(
             designspace,
             master_glyfs,
             master_locas,
+            scales,
             model,
         ),
     }
 }
 
+/// Whether a glyph has no points or components to vary.
+fn is_empty_outline(glyph: &read_fonts::tables::glyf::Glyph) -> bool {
+    use read_fonts::tables::glyf::Glyph;
+
+    match glyph {
+        Glyph::Simple(simple) => simple.num_points() == 0,
+        Glyph::Composite(composite) => composite.components().next().is_none(),
+    }
+}
+
 fn build_simple_glyph_variations(
     gid: GlyphId,
     default_simple: &SimpleGlyph,
     designspace: &DesignSpace,
     master_glyfs: &[read_fonts::tables::glyf::Glyf],
     master_locas: &[read_fonts::tables::loca::Loca],
+    scales: &[f64],
     model: &VariationModel,
 ) -> Result<GlyphVariations> {
     use read_fonts::tables::glyf::Glyph;
@@ -516,7 +893,10 @@ fn build_simple_glyph_variations(
                         actual: simple.num_points(),
                     });
                 }
-                simple.points().map(|p| (p.x, p.y)).collect()
+                simple
+                    .points()
+                    .map(|p| scale_xy(p.x, p.y, scales[master_idx]))
+                    .collect()
             }
             _ => {
                 // If a master has no glyph or a different type, use default points
@@ -527,12 +907,6 @@ fn build_simple_glyph_variations(
         master_points.push(points);
     }
 
-    // Get default master coordinates for IUP optimization
-    let default_coords: Vec<Point> = default_simple
-        .points()
-        .map(|p| Point::new(f64::from(p.x), f64::from(p.y)))
-        .collect();
-
     // Get contour end points for IUP
     let contour_ends: Vec<usize> = default_simple
         .end_pts_of_contours()
@@ -540,6 +914,27 @@ fn build_simple_glyph_variations(
         .map(|v| v.get() as usize)
         .collect();
 
+    Ok(point_variations(gid, &master_points, &contour_ends, model))
+}
+
+/// Compute a simple glyph's gvar deltas from its points in every master.
+///
+/// `master_points` is indexed by source and every entry must have the same
+/// length; `contour_ends` holds the last point index of each contour.
+pub(crate) fn point_variations(
+    gid: GlyphId,
+    master_points: &[Vec<(i16, i16)>],
+    contour_ends: &[usize],
+    model: &VariationModel,
+) -> GlyphVariations {
+    let num_points = master_points[model.default_idx].len();
+
+    // Get default master coordinates for IUP optimization
+    let default_coords: Vec<Point> = master_points[model.default_idx]
+        .iter()
+        .map(|&(x, y)| Point::new(f64::from(x), f64::from(y)))
+        .collect();
+
     // Precompute tents for all regions (these are constant per glyph)
     let all_tents: Vec<Vec<Tent>> = model
         .regions
@@ -610,7 +1005,7 @@ fn build_simple_glyph_variations(
         // Note: We keep all deltas including phantom points - gvar requires them
         let iup_start = Instant::now();
         let deltas =
-            match iup_delta_optimize(raw_deltas.clone(), coords_with_phantom, 0.5, &contour_ends) {
+            match iup_delta_optimize(raw_deltas.clone(), coords_with_phantom, 0.5, contour_ends) {
                 Ok(optimized) => {
                     // Track IUP statistics (outline points only, not phantom)
                     let outline_deltas = &optimized[..num_points];
@@ -641,7 +1036,7 @@ fn build_simple_glyph_variations(
         glyph_deltas.push(GlyphDeltas::new(tents, deltas));
     }
 
-    Ok(GlyphVariations::new(gid, glyph_deltas))
+    GlyphVariations::new(gid, glyph_deltas)
 }
 
 fn build_composite_glyph_variations(
@@ -650,6 +1045,7 @@ fn build_composite_glyph_variations(
     designspace: &DesignSpace,
     master_glyfs: &[read_fonts::tables::glyf::Glyf],
     master_locas: &[read_fonts::tables::loca::Loca],
+    scales: &[f64],
     model: &VariationModel,
 ) -> Result<GlyphVariations> {
     use read_fonts::tables::glyf::Glyph;
@@ -660,7 +1056,7 @@ fn build_composite_glyph_variations(
     // Collect component offsets from all masters
     let mut master_offsets: Vec<Vec<(i16, i16)>> = Vec::with_capacity(designspace.sources.len());
 
-    for (glyf, loca) in master_glyfs.iter().zip(master_locas.iter()) {
+    for (master_idx, (glyf, loca)) in master_glyfs.iter().zip(master_locas.iter()).enumerate() {
         let glyph = loca.get_glyf(gid, glyf).ok().flatten();
 
         let offsets: Vec<(i16, i16)> = match glyph {
@@ -669,7 +1065,7 @@ fn build_composite_glyph_variations(
                 .map(|c| {
                     let anchor = c.anchor;
                     match anchor {
-                        Anchor::Offset { x, y } => (x, y),
+                        Anchor::Offset { x, y } => scale_xy(x, y, scales[master_idx]),
                         _ => (0, 0),
                     }
                 })
@@ -789,100 +1185,156 @@ fn build_head(default_font: &FontRef, loca_format: LocaFormat) -> Result<Head> {
     ))
 }
 
-/// Weight axis stops as `(user_value, name_id)`.
-///
-/// Name IDs 280-287 hold the corresponding strings in the name table; the
-/// human-readable names come from [`warpnine_font_ops::weight_name`].
-const WEIGHT_STOPS: [(f64, u16); 8] = [
-    (300.0, 280),
-    (400.0, 281),
-    (500.0, 282),
-    (600.0, 283),
-    (700.0, 284),
-    (800.0, 285),
-    (900.0, 286),
-    (1000.0, 287),
-];
-
 /// fvar instance PostScript name IDs start here (one per instance).
 const INSTANCE_PS_NAME_ID_START: u16 = 300;
 
-/// Weight stops that fall within the designspace `wght` axis range.
+/// `wdth` values named after the OS/2 `usWidthClass` steps.
+const WIDTH_NAMES: [(f32, &str); 9] = [
+    (50.0, "UltraCondensed"),
+    (62.5, "ExtraCondensed"),
+    (75.0, "Condensed"),
+    (87.5, "SemiCondensed"),
+    (100.0, "Normal"),
+    (112.5, "SemiExpanded"),
+    (125.0, "Expanded"),
+    (150.0, "ExtraExpanded"),
+    (200.0, "UltraExpanded"),
+];
+
+/// A format 1 STAT axis value.
+#[derive(Debug, Clone, PartialEq)]
+struct StatValue {
+    axis_index: u16,
+    value: f32,
+    name_id: u16,
+    elidable: bool,
+}
+
+/// STAT axis values and the name-table strings they refer to.
 ///
-/// Sans (`wght` max 900) drops ExtraBlack (1000); Mono (max 1000) keeps it.
-/// Falls back to all stops if there is no `wght` axis.
-fn weight_stops_in_range(designspace: &DesignSpace) -> Vec<(f64, &'static str, u16)> {
-    let (min, max) = designspace
-        .axes
-        .iter()
-        .find(|a| a.tag == "wght")
-        .map_or((f64::MIN, f64::MAX), |a| (f64::from(a.minimum), f64::from(a.maximum)));
+/// Name IDs are allocated in a fixed order starting after the instance
+/// PostScript names: axis names in designspace order, then each axis's
+/// values in ascending order, then the elided fallback. Equal strings share
+/// an ID, so the same designspace always yields the same IDs.
+#[derive(Debug)]
+pub(crate) struct StatNames {
+    /// Name ID of each axis, in designspace order (shared with fvar)
+    axis_name_ids: Vec<u16>,
+    values: Vec<StatValue>,
+    elided_fallback_name_id: u16,
+    /// `(name_id, string)` records to add to the name table
+    pub strings: Vec<(u16, String)>,
+}
 
-    WEIGHT_STOPS
-        .into_iter()
-        .filter(|(value, _)| *value >= min && *value <= max)
-        .map(|(value, name_id)| (value, weight_name(value as u16), name_id))
-        .collect()
+impl StatNames {
+    pub fn new(designspace: &DesignSpace) -> Self {
+        let mut strings: Vec<(u16, String)> = Vec::new();
+        let first_id = INSTANCE_PS_NAME_ID_START + designspace.instances.len() as u16;
+        let mut name_id = |string: String| {
+            if let Some((id, _)) = strings.iter().find(|(_, s)| *s == string) {
+                return *id;
+            }
+            let id = first_id + strings.len() as u16;
+            strings.push((id, string));
+            id
+        };
+
+        let axis_name_ids = designspace
+            .axes
+            .iter()
+            .map(|axis| name_id(axis.name.clone()))
+            .collect();
+
+        let mut values = Vec::new();
+        for (axis_index, axis) in designspace.axes.iter().enumerate() {
+            // Every axis describes its default, even if no instance sits there
+            let mut coords: Vec<f32> = designspace
+                .instances
+                .iter()
+                .map(|instance| instance.axis_value(axis))
+                .filter(|value| (axis.minimum..=axis.maximum).contains(value))
+                .chain([axis.default])
+                .collect();
+            coords.sort_by(f32::total_cmp);
+            coords.dedup();
+
+            values.extend(coords.into_iter().map(|value| StatValue {
+                axis_index: axis_index as u16,
+                value,
+                name_id: name_id(stat_value_name(&axis.tag, value)),
+                elidable: value == axis.default,
+            }));
+        }
+        let elided_fallback_name_id = name_id("Regular".to_string());
+
+        Self {
+            axis_name_ids,
+            values,
+            elided_fallback_name_id,
+            strings,
+        }
+    }
+}
+
+/// Display name of a STAT axis value: registered names for `wght`, `wdth`
+/// and `ital`, otherwise the value itself.
+fn stat_value_name(tag: &str, value: f32) -> String {
+    let named = match tag {
+        "wght" if value % 100.0 == 0.0 && (100.0..=1000.0).contains(&value) => {
+            Some(weight_name(value as u16))
+        }
+        "wdth" => WIDTH_NAMES
+            .iter()
+            .find(|(width, _)| *width == value)
+            .map(|(_, name)| *name),
+        "ital" if value == 0.0 => Some("Upright"),
+        "ital" if value == 1.0 => Some("Italic"),
+        _ => None,
+    };
+    named.map_or_else(|| value.to_string(), str::to_string)
 }
 
 /// Build STAT table for style attributes.
 ///
 /// The STAT table is required for proper style menu grouping in applications.
+/// Each axis gets one format 1 value per distinct coordinate among the named
+/// instances (plus its default, which is marked elidable); see [`StatNames`].
 fn build_stat(designspace: &DesignSpace) -> Result<Stat> {
+    let names = StatNames::new(designspace);
+
     // Build axis records - these describe the axes in the font
     let axis_records: Vec<StatAxisRecord> = designspace
         .axes
         .iter()
+        .zip(&names.axis_name_ids)
         .enumerate()
-        .map(|(idx, axis)| {
+        .map(|(idx, (axis, name_id))| {
             let mut tag_bytes = [b' '; 4];
             for (i, b) in axis.tag.bytes().take(4).enumerate() {
                 tag_bytes[i] = b;
             }
-            // Use name IDs 256+ for axis names (matching fvar)
-            AxisRecord::new(Tag::new(&tag_bytes), NameId::new(256 + idx as u16), idx as u16)
+            AxisRecord::new(Tag::new(&tag_bytes), NameId::new(*name_id), idx as u16)
         })
         .collect();
 
-    // Build axis values for each named instance/weight
-    let mut axis_values: Vec<AxisValue> = Vec::new();
-
-    // For weight axis: add a value for each weight stop within the axis range.
-    for (value, _name, name_id) in weight_stops_in_range(designspace) {
-        let mut flags = AxisValueTableFlags::empty();
-        // Mark Regular (400) as the elidable default
-        if (value - 400.0).abs() < 0.1 {
-            flags |= AxisValueTableFlags::ELIDABLE_AXIS_VALUE_NAME;
-        }
-        axis_values.push(AxisValue::format_1(
-            0, // wght is axis index 0
-            flags,
-            NameId::new(name_id),
-            Fixed::from_f64(value),
-        ));
-    }
-
-    // For italic axis: add values for upright and italic
-    let italic_values: [(f64, &str, u16, bool); 2] = [
-        (0.0, "Upright", 290, true), // Upright is elidable default
-        (1.0, "Italic", 291, false),
-    ];
-
-    for (value, _name, name_id, is_default) in italic_values {
-        let mut flags = AxisValueTableFlags::empty();
-        if is_default {
-            flags |= AxisValueTableFlags::ELIDABLE_AXIS_VALUE_NAME;
-        }
-        axis_values.push(AxisValue::format_1(
-            1, // ital is axis index 1
-            flags,
-            NameId::new(name_id),
-            Fixed::from_f64(value),
-        ));
-    }
+    let axis_values: Vec<AxisValue> = names
+        .values
+        .iter()
+        .map(|value| {
+            let mut flags = AxisValueTableFlags::empty();
+            if value.elidable {
+                flags |= AxisValueTableFlags::ELIDABLE_AXIS_VALUE_NAME;
+            }
+            AxisValue::format_1(
+                value.axis_index,
+                flags,
+                NameId::new(value.name_id),
+                Fixed::from_f64(f64::from(value.value)),
+            )
+        })
+        .collect();
 
-    // Use "Regular" (name ID 281) as the elided fallback name
-    Ok(Stat::new(axis_records, axis_values, NameId::new(281)))
+    Ok(Stat::new(axis_records, axis_values, NameId::new(names.elided_fallback_name_id)))
 }
 
 /// Build a GDEF table without VarStore.
@@ -948,10 +1400,38 @@ fn build_gsub_without_feature_variations(
 
 #[cfg(test)]
 mod tests {
+    use std::path::PathBuf;
+
+    use read_fonts::{
+        FontRead,
+        tables::{
+            glyf::{CurvePoint, Glyf as ReadGlyf},
+            loca::Loca as ReadLoca,
+        },
+    };
+    use write_fonts::{
+        dump_table,
+        tables::glyf::{Bbox, Contour, SimpleGlyph},
+    };
+
     use super::*;
-    use crate::designspace::Axis;
+    use crate::{
+        AvarMapIssue,
+        designspace::{Axis, Instance, Source},
+    };
 
     fn ds(wght_max: f32) -> DesignSpace {
+        let weights = [300.0, 400.0, 500.0, 600.0, 700.0, 800.0, 900.0, 1000.0];
+        let instances = weights
+            .into_iter()
+            .filter(|weight| *weight <= wght_max)
+            .flat_map(|weight| {
+                [
+                    Instance::new("Upright", vec![("wght", weight), ("ital", 0.0)]),
+                    Instance::new("Italic", vec![("wght", weight), ("ital", 1.0)]),
+                ]
+            })
+            .collect();
         DesignSpace::new(
             vec![
                 Axis::new("wght", "Weight", 300.0, 400.0, wght_max),
@@ -959,23 +1439,261 @@ mod tests {
             ],
             vec![],
         )
+        .with_instances(instances)
+    }
+
+    /// Names of the STAT values on `axis_index`.
+    fn stat_value_names(designspace: &DesignSpace, axis_index: u16) -> Vec<String> {
+        let names = StatNames::new(designspace);
+        names
+            .values
+            .iter()
+            .filter(|value| value.axis_index == axis_index)
+            .map(|value| {
+                let (_, name) = names.strings.iter().find(|(id, _)| *id == value.name_id).unwrap();
+                name.clone()
+            })
+            .collect()
     }
 
     #[test]
     fn mono_keeps_extrablack() {
-        let stops = weight_stops_in_range(&ds(1000.0));
-        let names: Vec<&str> = stops.iter().map(|(_, n, _)| *n).collect();
         assert_eq!(
-            names,
+            stat_value_names(&ds(1000.0), 0),
             ["Light", "Regular", "Medium", "SemiBold", "Bold", "ExtraBold", "Black", "ExtraBlack"]
         );
+        assert_eq!(stat_value_names(&ds(1000.0), 1), ["Upright", "Italic"]);
     }
 
     #[test]
     fn sans_drops_extrablack_beyond_max() {
-        let stops = weight_stops_in_range(&ds(900.0));
-        let names: Vec<&str> = stops.iter().map(|(_, n, _)| *n).collect();
-        assert_eq!(names, ["Light", "Regular", "Medium", "SemiBold", "Bold", "ExtraBold", "Black"]);
-        assert!(stops.iter().all(|(v, _, _)| *v <= 900.0));
+        assert_eq!(
+            stat_value_names(&ds(900.0), 0),
+            ["Light", "Regular", "Medium", "SemiBold", "Bold", "ExtraBold", "Black"]
+        );
+    }
+
+    #[test]
+    fn stat_describes_every_axis() {
+        let instance = |name, wght, wdth, opsz| {
+            Instance::new(name, vec![("wght", wght), ("wdth", wdth), ("opsz", opsz)])
+        };
+        let designspace = DesignSpace::new(
+            vec![
+                Axis::new("wght", "Weight", 100.0, 400.0, 900.0),
+                Axis::new("wdth", "Width", 75.0, 100.0, 100.0),
+                Axis::new("opsz", "Optical Size", 8.0, 12.0, 72.0),
+            ],
+            vec![],
+        )
+        .with_instances(vec![
+            instance("Thin Condensed", 100.0, 75.0, 8.0),
+            instance("Regular", 400.0, 100.0, 12.0),
+            instance("Bold Display", 700.0, 100.0, 72.0),
+        ]);
+
+        assert_eq!(stat_value_names(&designspace, 0), ["Thin", "Regular", "Bold"]);
+        assert_eq!(stat_value_names(&designspace, 1), ["Condensed", "Normal"]);
+        assert_eq!(stat_value_names(&designspace, 2), ["8", "12", "72"]);
+
+        let stat = build_stat(&designspace).unwrap();
+        assert_eq!(stat.design_axes.len(), 3);
+        let elidable: Vec<(u16, f32)> = StatNames::new(&designspace)
+            .values
+            .iter()
+            .filter(|value| value.elidable)
+            .map(|value| (value.axis_index, value.value))
+            .collect();
+        assert_eq!(elidable, [(0, 400.0), (1, 100.0), (2, 12.0)]);
+
+        // Axis names come first after the three PostScript name slots, and
+        // "Regular" is shared by the wght value and the elided fallback.
+        let names = StatNames::new(&designspace);
+        assert_eq!(names.axis_name_ids, [303, 304, 305]);
+        assert_eq!(names.elided_fallback_name_id, 307);
+        assert_eq!(names.strings, StatNames::new(&designspace).strings);
+    }
+
+    /// Serialized glyf and loca for the given glyphs, plus whether loca is long.
+    fn glyf_loca(glyphs: &[WriteGlyph]) -> (Vec<u8>, Vec<u8>, bool) {
+        let mut builder = GlyfLocaBuilder::new();
+        for glyph in glyphs {
+            builder.add_glyph(glyph).unwrap();
+        }
+        let (glyf, loca, format) = builder.build();
+        (dump_table(&glyf).unwrap(), dump_table(&loca).unwrap(), format == LocaFormat::Long)
+    }
+
+    #[test]
+    fn family_name_override() {
+        let font = FontRef::new(font_test_data::TINOS_SUBSET).unwrap();
+        let designspace = DesignSpace::new(
+            vec![Axis::new("wght", "Weight", 400.0, 400.0, 700.0)],
+            vec![Source::new(PathBuf::from("Regular.ttf"), vec![("wght", 400.0)])],
+        )
+        .with_family_name("Warpnine Mono VF".to_string());
+
+        let name = build_name(&font, &designspace).unwrap();
+        let strings = |id: u16| -> Vec<String> {
+            name.name_record
+                .iter()
+                .filter(|record| record.name_id == NameId::new(id))
+                .map(|record| record.string.to_string())
+                .collect()
+        };
+        assert_eq!(strings(16), ["Warpnine Mono VF", "Warpnine Mono VF"]);
+        assert_eq!(strings(1), ["Warpnine Mono VF", "Warpnine Mono VF"]);
+        assert_eq!(strings(6)[0], "WarpnineMonoVF-Regular");
+    }
+
+    #[test]
+    fn avar_only_from_axis_maps() {
+        let axis = Axis::new("wght", "Weight", 300.0, 400.0, 1000.0);
+        let designspace = |axis: Axis| {
+            DesignSpace::new(
+                vec![axis],
+                vec![Source::new(PathBuf::from("Missing.ttf"), vec![("wght", 400.0)])],
+            )
+        };
+        assert!(build_avar(&designspace(axis.clone())).is_none());
+
+        let map = vec![(-1.0, -1.0), (0.0, 0.0), (0.5, 0.25), (1.0, 1.0)];
+        let avar = build_avar(&designspace(axis.clone().with_map(map))).unwrap();
+        let to: Vec<f32> = avar.axis_segment_maps[0]
+            .axis_value_maps
+            .iter()
+            .map(|value_map| value_map.to_coordinate.to_f32())
+            .collect();
+        assert_eq!(to, [-1.0, 0.0, 0.25, 1.0]);
+
+        let invalid = designspace(axis.with_map(vec![(-1.0, -1.0), (1.0, 1.0)]));
+        match build_variable_font(&invalid) {
+            Err(Error::InvalidAvarMap { axis, reason }) => {
+                assert_eq!(axis, "wght");
+                assert_eq!(reason, AvarMapIssue::MissingAnchor(0.0));
+                assert_eq!(reason.to_string(), "missing anchor (0, 0)");
+            }
+            other => panic!("expected InvalidAvarMap, got {other:?}"),
+        }
+    }
+
+    fn simple(contours: &[&[(i16, i16, bool)]]) -> WriteGlyph {
+        let mut glyph = SimpleGlyph {
+            bbox: Bbox::default(),
+            contours: contours
+                .iter()
+                .map(|points| {
+                    points
+                        .iter()
+                        .map(|&(x, y, on_curve)| CurvePoint::new(x, y, on_curve))
+                        .collect::<Vec<_>>()
+                        .into()
+                })
+                .collect(),
+            instructions: vec![],
+        };
+        glyph.recompute_bounding_box();
+        WriteGlyph::Simple(glyph)
+    }
+
+    #[test]
+    fn every_incompatible_glyph_is_reported() {
+        let designspace = DesignSpace::new(
+            vec![Axis::new("wght", "Weight", 400.0, 400.0, 700.0)],
+            vec![
+                Source::new(PathBuf::from("Regular.ttf"), vec![("wght", 400.0)]),
+                Source::new(PathBuf::from("Bold.ttf"), vec![("wght", 700.0)]),
+            ],
+        );
+        let triangle: &[(i16, i16, bool)] = &[(0, 0, true), (100, 0, true), (50, 100, true)];
+        let curved: &[(i16, i16, bool)] = &[(0, 0, true), (100, 0, false), (50, 100, true)];
+        let square: &[(i16, i16, bool)] =
+            &[(0, 0, true), (100, 0, true), (100, 100, true), (0, 100, true)];
+
+        let regular = [
+            simple(&[triangle]),
+            simple(&[triangle]),
+            simple(&[triangle]),
+            simple(&[triangle, triangle]),
+            simple(&[triangle]),
+        ];
+        let bold = [
+            simple(&[triangle]),
+            simple(&[curved]),
+            simple(&[triangle, square]),
+            simple(&[triangle, square]),
+            WriteGlyph::Empty,
+        ];
+        let masters = [glyf_loca(&regular), glyf_loca(&bold)];
+        let glyfs: Vec<ReadGlyf> = masters
+            .iter()
+            .map(|(glyf, _, _)| ReadGlyf::read(FontData::new(glyf)).unwrap())
+            .collect();
+        let locas: Vec<ReadLoca> = masters
+            .iter()
+            .map(|(_, loca, is_long)| ReadLoca::read(FontData::new(loca), *is_long).unwrap())
+            .collect();
+
+        let found = incompatible_glyphs(&designspace, &glyfs, &locas, 0, 5);
+        let reasons: Vec<(u32, IncompatReason)> = found
+            .iter()
+            .map(|glyph| (glyph.glyph_id, glyph.reason.clone()))
+            .collect();
+        assert_eq!(
+            reasons,
+            [
+                (1, IncompatReason::OnCurveFlag { point: 1 }),
+                (2, IncompatReason::ContourCount { expected: 1, actual: 2 }),
+                (3, IncompatReason::PointCount { contour: 1, expected: 3, actual: 4 }),
+            ]
+        );
+        assert!(found.iter().all(|glyph| glyph.path.as_os_str() == "Bold.ttf"));
+    }
+
+    #[test]
+    fn default_glyph_empty_is_an_error() {
+        let designspace = DesignSpace::new(
+            vec![Axis::new("wght", "Weight", 400.0, 400.0, 700.0)],
+            vec![
+                Source::new(PathBuf::from("Regular.ttf"), vec![("wght", 400.0)]),
+                Source::new(PathBuf::from("Bold.ttf"), vec![("wght", 700.0)]),
+            ],
+        );
+        let model = VariationModel::new(&designspace).unwrap();
+
+        let triangle = WriteGlyph::Simple(SimpleGlyph {
+            bbox: Bbox { x_min: 0, y_min: 0, x_max: 100, y_max: 100 },
+            contours: vec![Contour::from(vec![
+                CurvePoint::on_curve(0, 0),
+                CurvePoint::on_curve(100, 0),
+                CurvePoint::on_curve(50, 100),
+            ])],
+            instructions: vec![],
+        });
+        let masters = [glyf_loca(&[WriteGlyph::Empty]), glyf_loca(&[triangle])];
+        let glyfs: Vec<ReadGlyf> = masters
+            .iter()
+            .map(|(glyf, _, _)| ReadGlyf::read(FontData::new(glyf)).unwrap())
+            .collect();
+        let locas: Vec<ReadLoca> = masters
+            .iter()
+            .map(|(_, loca, is_long)| ReadLoca::read(FontData::new(loca), *is_long).unwrap())
+            .collect();
+
+        let result = build_glyph_variations(
+            GlyphId::new(0),
+            &designspace,
+            &glyfs,
+            &locas,
+            &[1.0; 2],
+            &model,
+        );
+        match result {
+            Err(Error::DefaultGlyphEmpty { path, glyph_id }) => {
+                assert_eq!(path, PathBuf::from("Bold.ttf"));
+                assert_eq!(glyph_id, 0);
+            }
+            other => panic!("expected DefaultGlyphEmpty, got {other:?}"),
+        }
     }
 }
@@ -0,0 +1,122 @@
+//! GPOS anchors that move between masters, varied through GDEF's VarStore.
+
+use std::{env::temp_dir, fs, process::id};
+
+use font_test_data::TINOS_SUBSET;
+use read_fonts::{
+    FontRef, TableProvider,
+    tables::{
+        gpos::{AnchorTable, PositionLookup},
+        layout::DeviceOrVariationIndex,
+        variations::DeltaSetIndex,
+    },
+    types::{F2Dot14, GlyphId16, Tag},
+};
+use warpnine_font_vf_builder::{Axis, DesignSpace, Source, build_variable_font};
+use write_fonts::{
+    FontBuilder,
+    tables::{
+        gpos::{
+            AnchorTable as WriteAnchor, BaseArray, BaseRecord, Gpos, MarkArray, MarkBasePosFormat1,
+            MarkRecord, PositionLookup as WriteLookup, PositionLookupList,
+        },
+        layout::{
+            CoverageTable, Feature, FeatureList, FeatureRecord, LangSys, Lookup, LookupFlag,
+            Script, ScriptList, ScriptRecord,
+        },
+    },
+};
+
+/// Copy of the test font with a mark-to-base lookup attaching glyph 2 to
+/// glyph 1, the base anchor at (`base_x`, 500).
+fn master_with_anchor(base_x: i16) -> Vec<u8> {
+    let subtable = MarkBasePosFormat1::new(
+        CoverageTable::format_1(vec![GlyphId16::new(2)]),
+        CoverageTable::format_1(vec![GlyphId16::new(1)]),
+        MarkArray::new(vec![MarkRecord::new(0, WriteAnchor::format_1(100, 0))]),
+        BaseArray::new(vec![BaseRecord::new(vec![Some(WriteAnchor::format_1(base_x, 500))])]),
+    );
+    let gpos = Gpos::new(
+        ScriptList::new(vec![ScriptRecord::new(
+            Tag::new(b"DFLT"),
+            Script::new(Some(LangSys::new(vec![0])), vec![]),
+        )]),
+        FeatureList::new(vec![FeatureRecord::new(Tag::new(b"mark"), Feature::new(None, vec![0]))]),
+        PositionLookupList::new(vec![WriteLookup::MarkToBase(Lookup::new(
+            LookupFlag::default(),
+            vec![subtable],
+        ))]),
+    );
+    let mut builder = FontBuilder::new();
+    builder.add_table(&gpos).unwrap();
+    builder.copy_missing_tables(FontRef::new(TINOS_SUBSET).unwrap());
+    builder.build()
+}
+
+#[test]
+fn moving_anchor_varies_through_gdef_varstore() {
+    let dir = temp_dir().join(format!("warpnine-anchors-{}", id()));
+    fs::create_dir_all(&dir).unwrap();
+    let regular = dir.join("Regular.ttf");
+    fs::write(&regular, master_with_anchor(250)).unwrap();
+    let bold = dir.join("Bold.ttf");
+    fs::write(&bold, master_with_anchor(310)).unwrap();
+    let wide = dir.join("Wide.ttf");
+    fs::write(&wide, master_with_anchor(250)).unwrap();
+
+    let designspace = DesignSpace::new(
+        vec![
+            Axis::new("wght", "Weight", 400.0, 400.0, 700.0),
+            Axis::new("wdth", "Width", 100.0, 100.0, 125.0),
+        ],
+        vec![
+            Source::new(&regular, vec![("wght", 400.0), ("wdth", 100.0)]),
+            Source::new(&bold, vec![("wght", 700.0), ("wdth", 100.0)]),
+            Source::new(&wide, vec![("wght", 400.0), ("wdth", 125.0)]),
+        ],
+    );
+    let vf = build_variable_font(&designspace);
+    fs::remove_dir_all(&dir).unwrap();
+
+    let vf = vf.unwrap();
+    let font = FontRef::new(&vf).unwrap();
+    let store = font.gdef().unwrap().item_var_store().unwrap().unwrap();
+    let regions = store.variation_region_list().unwrap();
+    assert_eq!(regions.axis_count(), 2);
+
+    let gpos = font.gpos().unwrap();
+    let Ok(PositionLookup::MarkToBase(lookup)) = gpos.lookup_list().unwrap().lookups().get(0)
+    else {
+        panic!("mark-to-base lookup missing");
+    };
+    let subtable = lookup.subtables().get(0).unwrap();
+    let base_array = subtable.base_array().unwrap();
+    let base_record = base_array.base_records().get(0).unwrap();
+    let base_anchor = base_record
+        .base_anchors(base_array.offset_data())
+        .get(0)
+        .unwrap()
+        .unwrap();
+    let AnchorTable::Format3(anchor) = base_anchor else {
+        panic!("base anchor is not variable");
+    };
+    assert_eq!((anchor.x_coordinate(), anchor.y_coordinate()), (250, 500));
+    assert!(anchor.y_device().is_none());
+    let Some(Ok(DeviceOrVariationIndex::VariationIndex(index))) = anchor.x_device() else {
+        panic!("x coordinate has no VariationIndex");
+    };
+    let index = DeltaSetIndex {
+        outer: index.delta_set_outer_index(),
+        inner: index.delta_set_inner_index(),
+    };
+    for (wght, expected) in [(0.0, 0), (0.5, 30), (1.0, 60)] {
+        let coords = [F2Dot14::from_f32(wght), F2Dot14::from_f32(0.0)];
+        let delta = store.compute_delta(index, &coords).unwrap();
+        assert_eq!(delta, expected, "wght {wght}");
+    }
+    // The mark anchor does not move, so it stays static
+    let mark_anchor = subtable.mark_array().unwrap().mark_records()[0]
+        .mark_anchor(subtable.mark_array().unwrap().offset_data())
+        .unwrap();
+    assert!(matches!(mark_anchor, AnchorTable::Format1(_)));
+}
@@ -0,0 +1,111 @@
+//! End-to-end check that an auto-derived avar round-trips through instancing.
+
+mod common;
+
+use std::{env::temp_dir, fs, path::PathBuf, process::id};
+
+use common::master;
+use font_instancer::{AxisLocation, instantiate};
+use font_test_data::TINOS_SUBSET;
+use read_fonts::{
+    FontRef, TableProvider,
+    tables::glyf::{CurvePoint, Glyph as ReadGlyph},
+    types::{Fixed, GlyphId},
+};
+use warpnine_font_vf_builder::{AvarSpacing, Axis, DesignSpace, Source, build_variable_font};
+
+/// Copy of the test font with every simple glyph shifted right by `dx`.
+fn shifted_master(dx: i16) -> Vec<u8> {
+    master(|point| CurvePoint { x: point.x + dx, ..point })
+}
+
+fn first_point_x(data: &[u8]) -> i16 {
+    let font = FontRef::new(data).unwrap();
+    let glyf = font.glyf().unwrap();
+    let loca = font.loca(None).unwrap();
+    (0..u32::from(font.maxp().unwrap().num_glyphs()))
+        .find_map(|gid| match loca.get_glyf(GlyphId::new(gid), &glyf).unwrap() {
+            Some(ReadGlyph::Simple(simple)) => simple.points().next().map(|p| p.x),
+            _ => None,
+        })
+        .unwrap()
+}
+
+#[test]
+fn auto_avar_places_uneven_master_midway() {
+    let dir = temp_dir().join(format!("warpnine-auto-avar-{}", id()));
+    fs::create_dir_all(&dir).unwrap();
+    let master = |name: &str, dx: i16| -> PathBuf {
+        let path = dir.join(name);
+        fs::write(&path, shifted_master(dx)).unwrap();
+        path
+    };
+
+    // Outlines move 1 unit per weight unit, so any instance should land at
+    // `base + (wght - 400)` no matter how the axis is warped.
+    let designspace = DesignSpace::new(
+        vec![Axis::new("wght", "Weight", 400.0, 400.0, 1000.0)],
+        vec![
+            Source::new(master("Regular.ttf", 0), vec![("wght", 400.0)]),
+            Source::new(master("Medium.ttf", 100), vec![("wght", 500.0)]),
+            Source::new(master("Black.ttf", 600), vec![("wght", 1000.0)]),
+        ],
+    )
+    .auto_avar(true);
+    let vf = build_variable_font(&designspace).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+
+    // Medium sits at 1/6 of the linear range but halfway once warped.
+    let font = FontRef::new(&vf).unwrap();
+    let avar = font.avar().expect("variable font has avar");
+    let map = avar.axis_segment_maps().iter().next().unwrap().unwrap();
+    let medium = map.apply(Fixed::from_f64(1.0 / 6.0)).to_f64();
+    assert!((medium - 0.5).abs() < 0.001, "{medium}");
+
+    let base = first_point_x(TINOS_SUBSET);
+    for wght in [500.0, 750.0, 1000.0] {
+        let instance = instantiate(&vf, &[AxisLocation::new("wght", wght)]).unwrap();
+        assert_eq!(first_point_x(&instance), base + (wght as i16 - 400), "wght={wght}");
+    }
+}
+
+#[test]
+fn geometric_spacing_interpolates_by_weight_ratio() {
+    let dir = temp_dir().join(format!("warpnine-auto-avar-geometric-{}", id()));
+    fs::create_dir_all(&dir).unwrap();
+    let master = |name: &str, dx: i16| -> PathBuf {
+        let path = dir.join(name);
+        fs::write(&path, shifted_master(dx)).unwrap();
+        path
+    };
+
+    let designspace = DesignSpace::new(
+        vec![
+            Axis::new("wght", "Weight", 400.0, 400.0, 1000.0).with_spacing(AvarSpacing::Geometric),
+        ],
+        vec![
+            Source::new(master("Regular.ttf", 0), vec![("wght", 400.0)]),
+            Source::new(master("Bold.ttf", 300), vec![("wght", 700.0)]),
+            Source::new(master("Black.ttf", 600), vec![("wght", 1000.0)]),
+        ],
+    )
+    .auto_avar(true);
+    let vf = build_variable_font(&designspace).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+
+    // Bold stays halfway, and the geometric mean of two masters' weights
+    // lands halfway between their outlines instead of at the linear position.
+    let font = FontRef::new(&vf).unwrap();
+    let avar = font.avar().expect("variable font has avar");
+    let map = avar.axis_segment_maps().iter().next().unwrap().unwrap();
+    assert_eq!(map.apply(Fixed::from_f64(0.5)), Fixed::from_f64(0.5));
+
+    let base = first_point_x(TINOS_SUBSET);
+    for (wght, dx) in
+        [(700.0_f32, 300), ((400.0_f32 * 700.0).sqrt(), 150), ((700.0_f32 * 1000.0).sqrt(), 450)]
+    {
+        let instance = instantiate(&vf, &[AxisLocation::new("wght", wght)]).unwrap();
+        let x = first_point_x(&instance);
+        assert!((x - (base + dx)).abs() <= 1, "wght={wght}: {x} vs {}", base + dx);
+    }
+}
@@ -0,0 +1,125 @@
+//! Masters derived from the test font, shared by the integration tests.
+
+// Each test binary uses only some of these helpers.
+#![allow(dead_code)]
+
+use font_test_data::TINOS_SUBSET;
+use read_fonts::{
+    FontData, FontRef, TableProvider, TopLevelTable,
+    tables::glyf::{CurvePoint, Glyph as ReadGlyph},
+    types::{GlyphId, Tag},
+};
+use write_fonts::{
+    FontBuilder, FontWrite, dump_table,
+    from_obj::FromObjRef,
+    tables::glyf::{Anchor, CompositeGlyph, GlyfLocaBuilder, Glyph, SimpleGlyph},
+    validate::Validate,
+};
+
+/// Copy of the test font with every simple glyph point passed through
+/// `map_point`.
+pub fn master(map_point: impl Fn(CurvePoint) -> CurvePoint) -> Vec<u8> {
+    Master::new(map_point).build()
+}
+
+type EditContour<'a> = Box<dyn Fn(u32, usize, &mut Vec<CurvePoint>) + 'a>;
+type MapOffset<'a> = Box<dyn Fn(i16, i16) -> (i16, i16) + 'a>;
+
+/// Copy of the test font with edited outlines and replaced tables.
+pub struct Master<'a> {
+    map_point: Box<dyn Fn(CurvePoint) -> CurvePoint + 'a>,
+    edit_contour: Option<EditContour<'a>>,
+    map_offset: Option<MapOffset<'a>>,
+    tables: Vec<(Tag, Vec<u8>)>,
+}
+
+impl<'a> Master<'a> {
+    /// Pass every simple glyph point through `map_point`.
+    pub fn new(map_point: impl Fn(CurvePoint) -> CurvePoint + 'a) -> Self {
+        Self {
+            map_point: Box::new(map_point),
+            edit_contour: None,
+            map_offset: None,
+            tables: Vec::new(),
+        }
+    }
+
+    /// Then edit each contour's mapped points, given the glyph ID and the
+    /// contour index.
+    pub fn contours(mut self, edit: impl Fn(u32, usize, &mut Vec<CurvePoint>) + 'a) -> Self {
+        self.edit_contour = Some(Box::new(edit));
+        self
+    }
+
+    /// Pass composite component offsets through `map_offset`; they are
+    /// copied unchanged otherwise.
+    pub fn offsets(mut self, map_offset: impl Fn(i16, i16) -> (i16, i16) + 'a) -> Self {
+        self.map_offset = Some(Box::new(map_offset));
+        self
+    }
+
+    /// Replace the test font's copy of `table`.
+    pub fn table<T>(mut self, table: &T) -> Self
+    where
+        T: FontWrite + Validate + TopLevelTable,
+    {
+        self.tables.push((T::TAG, dump_table(table).unwrap()));
+        self
+    }
+
+    pub fn build(self) -> Vec<u8> {
+        let font = FontRef::new(TINOS_SUBSET).unwrap();
+        let glyf = font.glyf().unwrap();
+        let loca = font.loca(None).unwrap();
+
+        let mut builder = GlyfLocaBuilder::new();
+        for gid in 0..u32::from(font.maxp().unwrap().num_glyphs()) {
+            let glyph = match loca.get_glyf(GlyphId::new(gid), &glyf).unwrap() {
+                None => Glyph::Empty,
+                Some(ReadGlyph::Simple(simple)) => {
+                    let mut simple = SimpleGlyph::from_obj_ref(&simple, FontData::new(&[]));
+                    for (idx, contour) in simple.contours.iter_mut().enumerate() {
+                        let mut points: Vec<CurvePoint> = contour.clone().into();
+                        for point in &mut points {
+                            *point = (self.map_point)(*point);
+                        }
+                        if let Some(edit) = &self.edit_contour {
+                            edit(gid, idx, &mut points);
+                        }
+                        *contour = points.into();
+                    }
+                    simple.recompute_bounding_box();
+                    Glyph::Simple(simple)
+                }
+                Some(ReadGlyph::Composite(composite)) => {
+                    let composite = CompositeGlyph::from_obj_ref(&composite, FontData::new(&[]));
+                    match &self.map_offset {
+                        None => Glyph::Composite(composite),
+                        Some(map_offset) => {
+                            let components = composite.components().iter().map(|component| {
+                                let mut component = component.clone();
+                                if let Anchor::Offset { x, y } = component.anchor {
+                                    let (x, y) = map_offset(x, y);
+                                    component.anchor = Anchor::Offset { x, y };
+                                }
+                                (component, composite.bbox)
+                            });
+                            Glyph::Composite(CompositeGlyph::try_from_iter(components).unwrap())
+                        }
+                    }
+                }
+            };
+            builder.add_glyph(&glyph).unwrap();
+        }
+        let (glyf, loca, _) = builder.build();
+
+        let mut font_builder = FontBuilder::new();
+        font_builder.add_table(&glyf).unwrap();
+        font_builder.add_table(&loca).unwrap();
+        for (tag, data) in self.tables {
+            font_builder.add_raw(tag, data);
+        }
+        font_builder.copy_missing_tables(font);
+        font_builder.build()
+    }
+}
@@ -0,0 +1,61 @@
+//! Building from a fontmake-style `.designspace` file.
+
+use std::{env::temp_dir, fs, process::id};
+
+use font_test_data::TINOS_SUBSET;
+use read_fonts::{FontRef, TableProvider};
+use warpnine_font_vf_builder::{DesignSpace, build_variable_font};
+
+const DESIGNSPACE: &str = r#"<?xml version='1.0' encoding='UTF-8'?>
+<designspace format="5.0">
+  <axes>
+    <axis tag="wght" name="Weight" minimum="400" default="400" maximum="700"/>
+  </axes>
+  <rules>
+    <rule name="dollar"><conditionset><condition name="Weight" minimum="600"/></conditionset></rule>
+  </rules>
+  <sources>
+    <source filename="masters/Regular.ttf" name="Regular" stylename="Regular">
+      <location><dimension name="Weight" xvalue="400"/></location>
+    </source>
+    <source filename="masters/Bold.ttf" name="Bold" stylename="Bold">
+      <location><dimension name="Weight" xvalue="700"/></location>
+    </source>
+  </sources>
+  <instances>
+    <instance name="Test Bold" stylename="Bold" postscriptfontname="Test-Bold">
+      <location><dimension name="Weight" xvalue="700"/></location>
+    </instance>
+  </instances>
+  <lib><dict/></lib>
+</designspace>
+"#;
+
+#[test]
+fn designspace_file_builds() {
+    let dir = temp_dir().join(format!("warpnine-designspace-{}", id()));
+    fs::create_dir_all(dir.join("masters")).unwrap();
+    fs::write(dir.join("masters/Regular.ttf"), TINOS_SUBSET).unwrap();
+    fs::write(dir.join("masters/Bold.ttf"), TINOS_SUBSET).unwrap();
+    let path = dir.join("Test.designspace");
+    fs::write(&path, DESIGNSPACE).unwrap();
+
+    let designspace = DesignSpace::from_designspace_xml(&path).unwrap();
+    let vf = build_variable_font(&designspace);
+    fs::remove_dir_all(&dir).unwrap();
+
+    let axis = &designspace.axes[0];
+    assert_eq!((axis.tag.as_str(), axis.name.as_str()), ("wght", "Weight"));
+    assert_eq!((axis.minimum, axis.default, axis.maximum), (400.0, 400.0, 700.0));
+    assert_eq!(designspace.sources[1].path, dir.join("masters/Bold.ttf"));
+    assert_eq!(designspace.sources[1].location["wght"], 700.0);
+    assert_eq!(designspace.sources[1].style_name.as_deref(), Some("Bold"));
+    let instance = &designspace.instances[0];
+    assert_eq!(instance.name, "Bold");
+    assert_eq!(instance.postscript_name.as_deref(), Some("Test-Bold"));
+    assert_eq!(instance.location["wght"], 700.0);
+
+    let vf = vf.unwrap();
+    let fvar = FontRef::new(&vf).unwrap().fvar().unwrap();
+    assert_eq!(fvar.instances().unwrap().len(), 1);
+}
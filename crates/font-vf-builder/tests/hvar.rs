@@ -0,0 +1,67 @@
+//! HVAR advance deltas derived from the masters' hmtx.
+
+mod common;
+
+use std::{env::temp_dir, fs, process::id};
+
+use common::Master;
+use font_test_data::TINOS_SUBSET;
+use read_fonts::{
+    FontData, FontRef, TableProvider,
+    types::{F2Dot14, GlyphId},
+};
+use warpnine_font_vf_builder::{Axis, DesignSpace, Source, build_variable_font};
+use write_fonts::{
+    from_obj::FromObjRef,
+    tables::hmtx::{Hmtx, LongMetric},
+};
+
+/// Copy of the test font with every advance widened by `extra` units.
+fn widened_master(extra: u16) -> Vec<u8> {
+    let font = FontRef::new(TINOS_SUBSET).unwrap();
+    let mut hmtx = Hmtx::from_obj_ref(&font.hmtx().unwrap(), FontData::new(&[]));
+    for metric in &mut hmtx.h_metrics {
+        *metric = LongMetric::new(metric.advance + extra, metric.side_bearing);
+    }
+    Master::new(|point| point).table(&hmtx).build()
+}
+
+#[test]
+fn hvar_deltas_follow_master_advances() {
+    let dir = temp_dir().join(format!("warpnine-hvar-{}", id()));
+    fs::create_dir_all(&dir).unwrap();
+    let regular = dir.join("Regular.ttf");
+    fs::write(&regular, TINOS_SUBSET).unwrap();
+    let medium = dir.join("Medium.ttf");
+    fs::write(&medium, widened_master(30)).unwrap();
+    let bold = dir.join("Bold.ttf");
+    fs::write(&bold, widened_master(100)).unwrap();
+
+    let designspace = DesignSpace::new(
+        vec![Axis::new("wght", "Weight", 400.0, 400.0, 700.0)],
+        vec![
+            Source::new(&regular, vec![("wght", 400.0)]),
+            Source::new(&medium, vec![("wght", 550.0)]),
+            Source::new(&bold, vec![("wght", 700.0)]),
+        ],
+    );
+    let plain = build_variable_font(&designspace);
+    let with_hvar = build_variable_font(&designspace.with_hvar(true));
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert!(FontRef::new(&plain.unwrap()).unwrap().hvar().is_err());
+
+    let vf = with_hvar.unwrap();
+    let font = FontRef::new(&vf).unwrap();
+    let hvar = font.hvar().unwrap();
+    let num_glyphs = font.maxp().unwrap().num_glyphs();
+    for (coord, expected) in [(0.0, 0), (0.25, 15), (0.5, 30), (0.75, 65), (1.0, 100)] {
+        let coords = [F2Dot14::from_f32(coord)];
+        for gid in 0..num_glyphs {
+            let delta = hvar
+                .advance_width_delta(GlyphId::new(u32::from(gid)), &coords)
+                .unwrap();
+            assert_eq!(delta.to_i32(), expected, "glyph {gid} at {coord}");
+        }
+    }
+}
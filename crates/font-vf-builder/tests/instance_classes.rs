@@ -0,0 +1,52 @@
+//! OS/2 weight and width classes reported for the named instances.
+//!
+//! The real WarpnineMono designspace lives in `warpnine-core`, which depends
+//! on this crate, and its masters are build outputs; `warpnine-core` checks it
+//! when they are present. Here the fixture font stands in for every master.
+
+use std::{env::temp_dir, fs, process::id};
+
+use font_test_data::TINOS_SUBSET;
+use warpnine_font_vf_builder::{
+    Axis, DesignSpace, Instance, Source, build_variable_font_with_report,
+};
+
+#[test]
+fn named_instances_map_to_os2_classes() {
+    let dir = temp_dir().join(format!("warpnine-instance-classes-{}", id()));
+    fs::create_dir_all(&dir).unwrap();
+    let masters = ["Regular", "Bold", "Condensed"].map(|name| {
+        let path = dir.join(format!("{name}.ttf"));
+        fs::write(&path, TINOS_SUBSET).unwrap();
+        path
+    });
+
+    // The WarpnineMono weight range, plus a width axis
+    let designspace = DesignSpace::new(
+        vec![
+            Axis::new("wght", "Weight", 300.0, 400.0, 1000.0),
+            Axis::new("wdth", "Width", 75.0, 100.0, 100.0),
+        ],
+        vec![
+            Source::new(&masters[0], vec![("wght", 400.0), ("wdth", 100.0)]),
+            Source::new(&masters[1], vec![("wght", 1000.0), ("wdth", 100.0)]),
+            Source::new(&masters[2], vec![("wght", 400.0), ("wdth", 75.0)]),
+        ],
+    )
+    .with_instances(vec![
+        Instance::new("Light", [("wght", 300.0)]),
+        Instance::new("Bold", [("wght", 700.0), ("wdth", 100.0)]),
+        Instance::new("SemiCondensed Medium", [("wght", 499.6), ("wdth", 87.5)]),
+        Instance::new("Condensed ExtraBlack", [("wght", 1000.0), ("wdth", 75.0)]),
+    ]);
+    let result = build_variable_font_with_report(&designspace);
+    fs::remove_dir_all(&dir).unwrap();
+
+    let (_, report) = result.unwrap();
+    let classes = &report.instance_classes;
+    assert_eq!(classes.len(), 4);
+    assert_eq!(classes["Light"], (300, 5));
+    assert_eq!(classes["Bold"], (700, 5));
+    assert_eq!(classes["SemiCondensed Medium"], (500, 4));
+    assert_eq!(classes["Condensed ExtraBlack"], (1000, 3));
+}
@@ -0,0 +1,94 @@
+//! Variable fonts built from hand-specified point data.
+
+use std::collections::HashMap;
+
+use font_instancer::{AxisLocation, instantiate};
+use read_fonts::{
+    FontRef, TableProvider,
+    tables::glyf::{CurvePoint, Glyph},
+    types::GlyphId,
+};
+use warpnine_font_vf_builder::{Axis, Error, GlyphPoints, build_variable_font_from_points};
+
+/// A rectangle from (100, 0) to (`right`, 700).
+fn rectangle(right: i16) -> Vec<CurvePoint> {
+    [(100, 0), (100, 700), (right, 700), (right, 0)]
+        .into_iter()
+        .map(|(x, y)| CurvePoint::on_curve(x, y))
+        .collect()
+}
+
+fn at_weight(weight: f32) -> HashMap<String, f32> {
+    HashMap::from([("wght".to_string(), weight)])
+}
+
+fn outline_at(vf: &[u8], weight: f32) -> Vec<(i16, i16)> {
+    let instance = instantiate(vf, &[AxisLocation::new("wght", weight)]).unwrap();
+    let font = FontRef::new(&instance).unwrap();
+    match font
+        .loca(None)
+        .unwrap()
+        .get_glyf(GlyphId::new(1), &font.glyf().unwrap())
+        .unwrap()
+    {
+        Some(Glyph::Simple(simple)) => simple.points().map(|p| (p.x, p.y)).collect(),
+        _ => panic!("expected a simple glyph"),
+    }
+}
+
+#[test]
+fn single_glyph_from_points_instances_back() {
+    let axes = [Axis::new("wght", "Weight", 400.0, 400.0, 700.0)];
+    let locations = [at_weight(400.0), at_weight(700.0)];
+    let glyphs = [
+        GlyphPoints::new(vec![], vec![vec![], vec![]], 500),
+        GlyphPoints::new(vec![3], vec![rectangle(500), rectangle(600)], 600),
+    ];
+    let vf =
+        build_variable_font_from_points("Points Test", &axes, &locations, &glyphs, 1000).unwrap();
+
+    let font = FontRef::new(&vf).unwrap();
+    assert_eq!(font.head().unwrap().units_per_em(), 1000);
+    assert_eq!(font.maxp().unwrap().num_glyphs(), 2);
+    assert_eq!(font.hmtx().unwrap().advance(GlyphId::new(1)), Some(600));
+    let name = font.name().unwrap();
+    let names: Vec<(u16, String)> = name
+        .name_record()
+        .iter()
+        .take(4)
+        .map(|record| {
+            let string = record.string(name.string_data()).unwrap();
+            (record.name_id().to_u16(), string.chars().collect())
+        })
+        .collect();
+    assert_eq!(
+        names,
+        [
+            (1, "Points Test".to_string()),
+            (2, "Regular".to_string()),
+            (4, "Points Test Regular".to_string()),
+            (6, "PointsTest-Regular".to_string()),
+        ]
+    );
+
+    let corners = |right| vec![(100, 0), (100, 700), (right, 700), (right, 0)];
+    assert_eq!(outline_at(&vf, 400.0), corners(500));
+    assert_eq!(outline_at(&vf, 550.0), corners(550));
+    assert_eq!(outline_at(&vf, 700.0), corners(600));
+}
+
+#[test]
+fn mismatched_point_sets_are_rejected() {
+    let axes = [Axis::new("wght", "Weight", 400.0, 400.0, 700.0)];
+    let locations = [at_weight(400.0), at_weight(700.0)];
+    let mut short = rectangle(600);
+    short.pop();
+    let glyphs = [GlyphPoints::new(vec![3], vec![rectangle(500), short], 600)];
+
+    match build_variable_font_from_points("Points Test", &axes, &locations, &glyphs, 1000) {
+        Err(Error::PointSetMismatch { master, glyph_id, expected, actual }) => {
+            assert_eq!((master, glyph_id, expected, actual), (1, 0, 4, 3));
+        }
+        other => panic!("expected PointSetMismatch, got {other:?}"),
+    }
+}
@@ -0,0 +1,95 @@
+//! Built variable fonts must reproduce each master when instanced at its location.
+
+mod common;
+
+use std::{env::temp_dir, fs, path::PathBuf, process::id};
+
+use common::master;
+use read_fonts::{
+    FontRef, TableProvider,
+    tables::{glyf::CurvePoint, variations::PackedPointNumbers},
+    types::GlyphId,
+};
+use warpnine_font_vf_builder::{
+    Axis, DesignSpace, Error, Source, build_variable_font_verified, verify_round_trip,
+};
+
+/// Copy of the test font with every simple glyph's x coordinates scaled by
+/// `scale` and shifted right by `dx`.
+fn distorted_master(scale: f32, dx: i16) -> Vec<u8> {
+    master(|point| CurvePoint {
+        x: (f32::from(point.x) * scale).round() as i16 + dx,
+        ..point
+    })
+}
+
+/// Byte offset in `vf` of the packed x deltas of `gid`'s first gvar tuple,
+/// if that run carries explicit (non-zero) deltas.
+fn first_x_delta_run(vf: &[u8], gid: u32) -> Option<usize> {
+    let font = FontRef::new(vf).unwrap();
+    let data = font.gvar().unwrap().data_for_gid(GlyphId::new(gid)).unwrap()?;
+    let bytes = data.as_bytes();
+    let count_flags = u16::from_be_bytes([bytes[0], bytes[1]]);
+    let data_offset = usize::from(u16::from_be_bytes([bytes[2], bytes[3]]));
+    let tuple_index = u16::from_be_bytes([bytes[6], bytes[7]]);
+
+    let mut serialized = data.split_off(data_offset)?;
+    if count_flags & SHARED_POINT_NUMBERS != 0 {
+        serialized = PackedPointNumbers::split_off_front(serialized).1;
+    }
+    if tuple_index & PRIVATE_POINT_NUMBERS != 0 {
+        serialized = PackedPointNumbers::split_off_front(serialized).1;
+    }
+    let run = serialized.as_bytes();
+    let offset = run.as_ptr() as usize - vf.as_ptr() as usize;
+    (!run.is_empty() && run[0] & DELTAS_ARE_ZERO == 0).then_some(offset)
+}
+
+const SHARED_POINT_NUMBERS: u16 = 0x8000;
+const PRIVATE_POINT_NUMBERS: u16 = 0x2000;
+const DELTAS_ARE_ZERO: u8 = 0x80;
+const DELTAS_ARE_WORDS: u8 = 0x40;
+
+#[test]
+fn round_trip_detects_corrupted_deltas() {
+    let dir = temp_dir().join(format!("warpnine-round-trip-{}", id()));
+    fs::create_dir_all(&dir).unwrap();
+    let master = |name: &str, scale: f32, dx: i16| -> PathBuf {
+        let path = dir.join(name);
+        fs::write(&path, distorted_master(scale, dx)).unwrap();
+        path
+    };
+
+    let regular = master("Regular.ttf", 1.0, 0);
+    let designspace = DesignSpace::new(
+        vec![Axis::new("wght", "Weight", 400.0, 400.0, 900.0)],
+        vec![
+            Source::new(&regular, vec![("wght", 400.0)]),
+            Source::new(master("Medium.ttf", 1.05, 20), vec![("wght", 500.0)]),
+            Source::new(master("Black.ttf", 1.2, 60), vec![("wght", 900.0)]),
+        ],
+    );
+    let mut vf = build_variable_font_verified(&designspace).unwrap();
+
+    // Move the first x delta of one glyph's first tuple by 40 units.
+    let num_glyphs = u32::from(FontRef::new(&vf).unwrap().maxp().unwrap().num_glyphs());
+    let (gid, run) = (0..num_glyphs)
+        .find_map(|gid| first_x_delta_run(&vf, gid).map(|run| (gid, run)))
+        .expect("a glyph with explicit deltas");
+    if vf[run] & DELTAS_ARE_WORDS != 0 {
+        let delta = i16::from_be_bytes([vf[run + 1], vf[run + 2]]) + 40;
+        vf[run + 1..run + 3].copy_from_slice(&delta.to_be_bytes());
+    } else {
+        vf[run + 1] = (vf[run + 1] as i8).wrapping_add(40) as u8;
+    }
+    let result = verify_round_trip(&designspace, &vf);
+    fs::remove_dir_all(&dir).unwrap();
+
+    match result {
+        Err(Error::RoundTripMismatch { path, glyph_id }) => {
+            assert_eq!(glyph_id, gid);
+            assert_ne!(path, regular);
+        }
+        other => panic!("expected RoundTripMismatch, got {other:?}"),
+    }
+}
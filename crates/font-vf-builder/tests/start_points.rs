@@ -0,0 +1,129 @@
+//! Contours that start at a different point, or run the other way, in a
+//! master are reported and can be realigned with the default master.
+
+mod common;
+
+use std::{env::temp_dir, fs, path::PathBuf, process::id};
+
+use common::Master;
+use font_test_data::TINOS_SUBSET;
+use read_fonts::{
+    FontRef, TableProvider,
+    tables::glyf::{CurvePoint, Glyph as ReadGlyph},
+    types::GlyphId,
+};
+use warpnine_font_vf_builder::{Axis, DesignSpace, Source, StartPointWarning};
+
+/// Simple glyphs of the test font with more than one contour point, in
+/// glyph ID order.
+fn simple_glyph_ids() -> Vec<u32> {
+    let font = FontRef::new(TINOS_SUBSET).unwrap();
+    let (glyf, loca) = (font.glyf().unwrap(), font.loca(None).unwrap());
+    (0..u32::from(font.maxp().unwrap().num_glyphs()))
+        .filter(|&gid| {
+            matches!(
+                loca.get_glyf(GlyphId::new(gid), &glyf).unwrap(),
+                Some(ReadGlyph::Simple(simple)) if simple.num_points() > 6
+            )
+        })
+        .collect()
+}
+
+/// Copy of the test font with x coordinates widened by 10%, the first
+/// contour of `rotated` starting 3 points later, and the first contour of
+/// `reversed` running backwards from the same start point.
+fn bold_master(rotated: Option<u32>, reversed: Option<u32>) -> Vec<u8> {
+    Master::new(|point| CurvePoint {
+        x: (f32::from(point.x) * 1.1).round() as i16,
+        ..point
+    })
+    .contours(|gid, idx, points| {
+        if idx == 0 && rotated == Some(gid) {
+            points.rotate_left(3);
+        }
+        if idx == 0 && reversed == Some(gid) {
+            points[1..].reverse();
+        }
+    })
+    .build()
+}
+
+fn glyph_points(data: &[u8], gid: u32) -> Vec<CurvePoint> {
+    let font = FontRef::new(data).unwrap();
+    match font
+        .loca(None)
+        .unwrap()
+        .get_glyf(GlyphId::new(gid), &font.glyf().unwrap())
+    {
+        Ok(Some(ReadGlyph::Simple(simple))) => simple.points().collect(),
+        _ => panic!("glyph {gid} should be simple"),
+    }
+}
+
+#[test]
+fn rotated_and_reversed_contours_are_found_and_fixed() {
+    let dir = temp_dir().join(format!("warpnine-start-points-{}", id()));
+    fs::create_dir_all(&dir).unwrap();
+    let master = |name: &str, data: Vec<u8>| -> PathBuf {
+        let path = dir.join(name);
+        fs::write(&path, data).unwrap();
+        path
+    };
+
+    let glyphs = simple_glyph_ids();
+    let (rotated, reversed) = (glyphs[0], glyphs[1]);
+    let first_contour_len = |gid: u32| {
+        let font = FontRef::new(TINOS_SUBSET).unwrap();
+        match font
+            .loca(None)
+            .unwrap()
+            .get_glyf(GlyphId::new(gid), &font.glyf().unwrap())
+        {
+            Ok(Some(ReadGlyph::Simple(simple))) => {
+                usize::from(simple.end_pts_of_contours()[0].get()) + 1
+            }
+            _ => unreachable!(),
+        }
+    };
+
+    let bold = master("Bold.ttf", bold_master(Some(rotated), Some(reversed)));
+    let designspace = DesignSpace::new(
+        vec![Axis::new("wght", "Weight", 400.0, 400.0, 700.0)],
+        vec![
+            Source::new(master("Regular.ttf", TINOS_SUBSET.to_vec()), vec![("wght", 400.0)]),
+            Source::new(bold.clone(), vec![("wght", 700.0)]),
+        ],
+    );
+
+    let warnings = designspace.check_start_points().unwrap();
+    assert_eq!(
+        warnings,
+        [
+            StartPointWarning {
+                glyph_id: rotated,
+                path: bold.clone(),
+                contour: 0,
+                start: first_contour_len(rotated) - 3,
+                reversed: false,
+            },
+            StartPointWarning {
+                glyph_id: reversed,
+                path: bold.clone(),
+                contour: 0,
+                start: 0,
+                reversed: true,
+            },
+        ]
+    );
+
+    let fixed = designspace.fix_start_points().unwrap();
+    assert_eq!(fixed[0], TINOS_SUBSET);
+    let expected = bold_master(None, None);
+    for gid in [rotated, reversed] {
+        assert_eq!(glyph_points(&fixed[1], gid), glyph_points(&expected, gid));
+    }
+
+    fs::write(&bold, &fixed[1]).unwrap();
+    assert!(designspace.check_start_points().unwrap().is_empty());
+    fs::remove_dir_all(&dir).ok();
+}
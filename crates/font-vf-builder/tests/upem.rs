@@ -0,0 +1,65 @@
+//! Masters drawn at a different unitsPerEm than the default master.
+
+mod common;
+
+use std::{env::temp_dir, fs, path::PathBuf, process::id};
+
+use common::Master;
+use font_test_data::TINOS_SUBSET;
+use read_fonts::{FontData, FontRef, TableProvider, tables::glyf::CurvePoint};
+use warpnine_font_vf_builder::{
+    Axis, DesignSpace, Error, Source, build_variable_font, build_variable_font_verified,
+};
+use write_fonts::{from_obj::FromObjRef, tables::head::Head};
+
+/// Copy of the test font redrawn at `upem`, with x coordinates widened by
+/// `widen` in the original units.
+fn master_at_upem(upem: u16, widen: f32) -> Vec<u8> {
+    let font = FontRef::new(TINOS_SUBSET).unwrap();
+    let scale = f32::from(upem) / f32::from(font.head().unwrap().units_per_em());
+    let mut head = Head::from_obj_ref(&font.head().unwrap(), FontData::new(&[]));
+    head.units_per_em = upem;
+
+    let scaled = |value: i16, by: f32| (f32::from(value) * by).round() as i16;
+    Master::new(|point| CurvePoint {
+        x: scaled(point.x, widen * scale),
+        y: scaled(point.y, scale),
+        ..point
+    })
+    .offsets(|x, y| (scaled(x, scale), scaled(y, scale)))
+    .table(&head)
+    .build()
+}
+
+#[test]
+fn master_at_other_upem_is_scaled_or_rejected() {
+    let dir = temp_dir().join(format!("warpnine-upem-{}", id()));
+    fs::create_dir_all(&dir).unwrap();
+    let default_upem = FontRef::new(TINOS_SUBSET).unwrap().head().unwrap().units_per_em();
+    let other_upem = if default_upem == 1000 { 2048 } else { 1000 };
+
+    let regular = dir.join("Regular.ttf");
+    fs::write(&regular, TINOS_SUBSET).unwrap();
+    let bold: PathBuf = dir.join("Bold.ttf");
+    fs::write(&bold, master_at_upem(other_upem, 1.2)).unwrap();
+
+    let designspace = DesignSpace::new(
+        vec![Axis::new("wght", "Weight", 400.0, 400.0, 700.0)],
+        vec![
+            Source::new(&regular, vec![("wght", 400.0)]),
+            Source::new(&bold, vec![("wght", 700.0)]),
+        ],
+    );
+    let scaled = build_variable_font_verified(&designspace);
+    let rejected = build_variable_font(&designspace.scale_upem(false));
+    fs::remove_dir_all(&dir).unwrap();
+
+    let vf = scaled.unwrap();
+    assert_eq!(FontRef::new(&vf).unwrap().head().unwrap().units_per_em(), default_upem);
+    match rejected {
+        Err(Error::UpemMismatch { path, expected, actual }) => {
+            assert_eq!((path, expected, actual), (bold, default_upem, other_upem));
+        }
+        other => panic!("expected UpemMismatch, got {other:?}"),
+    }
+}
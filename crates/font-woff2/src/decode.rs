@@ -0,0 +1,402 @@
+//! WOFF2 decoding (`decode` feature), for checking encoder output in tests.
+//!
+//! Reverses the brotli compression and the `glyf`/`loca` transform,
+//! rebuilding an sfnt that read-fonts can parse. Font collections, the
+//! `hmtx` transform and the metadata and private blocks are not supported,
+//! as the encoder never produces them.
+
+use anyhow::{Context, Result, bail, ensure};
+
+/// Tags addressed by index in a table directory entry's flags
+const KNOWN_TAGS: [&[u8; 4]; 63] = [
+    b"cmap", b"head", b"hhea", b"hmtx", b"maxp", b"name", b"OS/2", b"post", b"cvt ", b"fpgm",
+    b"glyf", b"loca", b"prep", b"CFF ", b"VORG", b"EBDT", b"EBLC", b"gasp", b"hdmx", b"kern",
+    b"LTSH", b"PCLT", b"VDMX", b"vhea", b"vmtx", b"BASE", b"GDEF", b"GPOS", b"GSUB", b"EBSC",
+    b"JSTF", b"MATH", b"CBDT", b"CBLC", b"COLR", b"CPAL", b"SVG ", b"sbix", b"acnt", b"avar",
+    b"bdat", b"bloc", b"bsln", b"cvar", b"fdsc", b"feat", b"fmtx", b"fvar", b"gvar", b"hsty",
+    b"just", b"lcar", b"mort", b"morx", b"opbd", b"prop", b"trak", b"Zapf", b"Silf", b"Glat",
+    b"Gloc", b"Feat", b"Sill",
+];
+
+/// Size of the WOFF2 header
+const HEADER_SIZE: usize = 48;
+
+/// Composite glyph flags
+const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+const WE_HAVE_A_SCALE: u16 = 0x0008;
+const MORE_COMPONENTS: u16 = 0x0020;
+const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+const WE_HAVE_INSTRUCTIONS: u16 = 0x0100;
+
+/// A table directory entry
+struct Entry {
+    tag: [u8; 4],
+    orig_length: usize,
+    /// Length in the decompressed stream, which differs from `orig_length`
+    /// for transformed tables
+    length: usize,
+    transformed: bool,
+}
+
+/// Decode a WOFF2 font to sfnt (TrueType or CFF) data.
+pub fn decode_woff2(data: &[u8]) -> Result<Vec<u8>> {
+    let mut header = Reader::new(data);
+    ensure!(header.bytes(4)? == b"wOF2", "not a WOFF2 font");
+    let flavor = header.u32()?;
+    ensure!(flavor != u32::from_be_bytes(*b"ttcf"), "WOFF2 collections are not supported");
+    header.bytes(4)?;
+    let num_tables = header.u16()?;
+    header.bytes(6)?;
+    let compressed_length = header.u32()? as usize;
+
+    let mut directory = Reader::new(data.get(HEADER_SIZE..).context("truncated WOFF2 header")?);
+    let entries = (0..num_tables)
+        .map(|_| {
+            let flags = directory.u8()?;
+            let tag = match flags & 0x3F {
+                63 => directory.tag()?,
+                index => *KNOWN_TAGS[usize::from(index)],
+            };
+            let version = flags >> 6;
+            let orig_length = directory.base128()? as usize;
+            let transformed =
+                if &tag == b"glyf" || &tag == b"loca" { version == 0 } else { version != 0 };
+            let length = if transformed { directory.base128()? as usize } else { orig_length };
+            Ok(Entry { tag, orig_length, length, transformed })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let start = HEADER_SIZE + directory.pos;
+    let stream = data
+        .get(start..start + compressed_length)
+        .context("truncated WOFF2 table data")?;
+    let mut decompressed = Vec::new();
+    std::io::Read::read_to_end(
+        &mut brotli_decompressor::Decompressor::new(stream, 4096),
+        &mut decompressed,
+    )
+    .context("failed to decompress WOFF2 table data")?;
+
+    let mut tables: Vec<([u8; 4], Vec<u8>)> = Vec::with_capacity(entries.len());
+    let mut offset = 0;
+    for entry in &entries {
+        let table = decompressed
+            .get(offset..offset + entry.length)
+            .with_context(|| format!("truncated {} table", tag_str(&entry.tag)))?;
+        tables.push((entry.tag, table.to_vec()));
+        offset += entry.length;
+    }
+
+    let find =
+        |tables: &[([u8; 4], Vec<u8>)], tag: &[u8; 4]| tables.iter().position(|(t, _)| t == tag);
+    let is_transformed = |tag: &[u8; 4]| entries.iter().any(|e| &e.tag == tag && e.transformed);
+
+    ensure!(!is_transformed(b"hmtx"), "transformed hmtx is not supported");
+    if is_transformed(b"glyf") {
+        let glyf_index = find(&tables, b"glyf").context("no glyf table")?;
+        let loca_index = find(&tables, b"loca").context("transformed glyf without loca")?;
+        let (glyf, loca) = reconstruct_glyf(&tables[glyf_index].1)?;
+        ensure!(
+            loca.len() == entries[loca_index].orig_length,
+            "reconstructed loca has the wrong size"
+        );
+        tables[glyf_index].1 = glyf;
+        tables[loca_index].1 = loca;
+    }
+
+    Ok(build_sfnt(flavor, tables))
+}
+
+/// Rebuild `glyf` and `loca` from the transformed `glyf`
+fn reconstruct_glyf(data: &[u8]) -> Result<(Vec<u8>, Vec<u8>)> {
+    let mut header = Reader::new(data);
+    // reserved and optionFlags; the overlap bitmap after the streams is unused
+    header.bytes(4)?;
+    let num_glyphs = header.u16()?;
+    let index_format = header.u16()?;
+    let mut sizes = [0usize; 7];
+    for size in &mut sizes {
+        *size = header.u32()? as usize;
+    }
+
+    let mut streams = Vec::with_capacity(sizes.len());
+    let mut offset = header.pos;
+    for size in sizes {
+        streams
+            .push(Reader::new(data.get(offset..offset + size).context("truncated glyf stream")?));
+        offset += size;
+    }
+    let [
+        mut n_contours,
+        mut n_points,
+        mut flags,
+        mut glyphs,
+        mut composites,
+        mut bboxes,
+        mut instructions,
+    ] = <[Reader; 7]>::try_from(streams).ok().context("glyf stream count")?;
+    let bitmap_length = 4 * usize::from(num_glyphs).div_ceil(32);
+    let bbox_bitmap = bboxes.bytes(bitmap_length)?.to_vec();
+    let has_bbox = |gid: usize| bbox_bitmap[gid / 8] & (0x80 >> (gid % 8)) != 0;
+
+    let mut glyf = Vec::new();
+    let mut offsets = Vec::with_capacity(usize::from(num_glyphs) + 1);
+    for gid in 0..usize::from(num_glyphs) {
+        offsets.push(glyf.len());
+        let contours = n_contours.i16()?;
+        let mut glyph = Vec::new();
+        match contours {
+            0 => {
+                ensure!(!has_bbox(gid), "empty glyph {gid} has a bounding box");
+            }
+            -1 => {
+                ensure!(has_bbox(gid), "composite glyph {gid} has no bounding box");
+                let bbox = bboxes.bytes(8)?;
+                glyph.extend_from_slice(&contours.to_be_bytes());
+                glyph.extend_from_slice(bbox);
+                let mut any_instructions = false;
+                loop {
+                    let flags = composites.u16()?;
+                    let mut length = 4;
+                    length += if flags & ARG_1_AND_2_ARE_WORDS != 0 { 4 } else { 2 };
+                    if flags & WE_HAVE_A_SCALE != 0 {
+                        length += 2;
+                    } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+                        length += 4;
+                    } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+                        length += 8;
+                    }
+                    glyph.extend_from_slice(&flags.to_be_bytes());
+                    glyph.extend_from_slice(composites.bytes(length - 2)?);
+                    any_instructions |= flags & WE_HAVE_INSTRUCTIONS != 0;
+                    if flags & MORE_COMPONENTS == 0 {
+                        break;
+                    }
+                }
+                if any_instructions {
+                    let length = glyphs.u255_16()?;
+                    glyph.extend_from_slice(&length.to_be_bytes());
+                    glyph.extend_from_slice(instructions.bytes(usize::from(length))?);
+                }
+            }
+            count if count > 0 => {
+                let mut end_points = Vec::with_capacity(count as usize);
+                let mut total = 0u16;
+                for _ in 0..count {
+                    total = total
+                        .checked_add(n_points.u255_16()?)
+                        .context("too many points in glyph")?;
+                    end_points.push(total.checked_sub(1).context("contour without points")?);
+                }
+                let mut points = Vec::with_capacity(usize::from(total));
+                let (mut x, mut y) = (0i32, 0i32);
+                for _ in 0..total {
+                    let flag = flags.u8()?;
+                    let (dx, dy) = decode_triplet(flag & 0x7F, &mut glyphs)?;
+                    x += dx;
+                    y += dy;
+                    points.push((x, y, flag & 0x80 == 0));
+                }
+                let instruction_length = glyphs.u255_16()?;
+
+                let bbox: [i16; 4] = if has_bbox(gid) {
+                    let mut bbox = Reader::new(bboxes.bytes(8)?);
+                    [bbox.i16()?, bbox.i16()?, bbox.i16()?, bbox.i16()?]
+                } else {
+                    let xs = points.iter().map(|p| p.0);
+                    let ys = points.iter().map(|p| p.1);
+                    [
+                        xs.clone().min().unwrap_or(0) as i16,
+                        ys.clone().min().unwrap_or(0) as i16,
+                        xs.max().unwrap_or(0) as i16,
+                        ys.max().unwrap_or(0) as i16,
+                    ]
+                };
+
+                glyph.extend_from_slice(&contours.to_be_bytes());
+                for value in bbox {
+                    glyph.extend_from_slice(&value.to_be_bytes());
+                }
+                for end in end_points {
+                    glyph.extend_from_slice(&end.to_be_bytes());
+                }
+                glyph.extend_from_slice(&instruction_length.to_be_bytes());
+                glyph.extend_from_slice(instructions.bytes(usize::from(instruction_length))?);
+                // Every coordinate is written as a 16-bit delta
+                glyph.extend(points.iter().map(|&(_, _, on_curve)| u8::from(on_curve)));
+                for axis in [0, 1] {
+                    let mut previous = 0i32;
+                    for point in &points {
+                        let value = if axis == 0 { point.0 } else { point.1 };
+                        glyph.extend_from_slice(&((value - previous) as i16).to_be_bytes());
+                        previous = value;
+                    }
+                }
+            }
+            count => bail!("glyph {gid} has {count} contours"),
+        }
+        glyf.extend_from_slice(&glyph);
+        glyf.resize(glyf.len().next_multiple_of(4), 0);
+    }
+    offsets.push(glyf.len());
+
+    let mut loca = Vec::new();
+    for offset in offsets {
+        if index_format == 0 {
+            loca.extend_from_slice(&((offset / 2) as u16).to_be_bytes());
+        } else {
+            loca.extend_from_slice(&(offset as u32).to_be_bytes());
+        }
+    }
+    Ok((glyf, loca))
+}
+
+/// One point's coordinate deltas from its flag and the glyph stream
+fn decode_triplet(flag: u8, glyphs: &mut Reader) -> Result<(i32, i32)> {
+    let with_sign = |flag: u8, value: i32| if flag & 1 != 0 { value } else { -value };
+    let flag_i = i32::from(flag);
+    Ok(match flag {
+        0..10 => {
+            let b0 = i32::from(glyphs.u8()?);
+            (0, with_sign(flag, ((flag_i & 14) << 7) + b0))
+        }
+        10..20 => {
+            let b0 = i32::from(glyphs.u8()?);
+            (with_sign(flag, (((flag_i - 10) & 14) << 7) + b0), 0)
+        }
+        20..84 => {
+            let base = flag_i - 20;
+            let b0 = i32::from(glyphs.u8()?);
+            (
+                with_sign(flag, 1 + (base & 0x30) + (b0 >> 4)),
+                with_sign(flag >> 1, 1 + ((base & 0x0C) << 2) + (b0 & 0x0F)),
+            )
+        }
+        84..120 => {
+            let base = flag_i - 84;
+            let (b0, b1) = (i32::from(glyphs.u8()?), i32::from(glyphs.u8()?));
+            (
+                with_sign(flag, 1 + ((base / 12) << 8) + b0),
+                with_sign(flag >> 1, 1 + (((base % 12) >> 2) << 8) + b1),
+            )
+        }
+        120..124 => {
+            let bytes = glyphs.bytes(3)?;
+            let (b0, b1, b2) = (i32::from(bytes[0]), i32::from(bytes[1]), i32::from(bytes[2]));
+            (with_sign(flag, (b0 << 4) + (b1 >> 4)), with_sign(flag >> 1, ((b1 & 0x0F) << 8) + b2))
+        }
+        _ => {
+            let bytes = glyphs.bytes(4)?;
+            let x = i32::from(u16::from_be_bytes([bytes[0], bytes[1]]));
+            let y = i32::from(u16::from_be_bytes([bytes[2], bytes[3]]));
+            (with_sign(flag, x), with_sign(flag >> 1, y))
+        }
+    })
+}
+
+/// Assemble an sfnt from its tables, sorted by tag
+fn build_sfnt(flavor: u32, mut tables: Vec<([u8; 4], Vec<u8>)>) -> Vec<u8> {
+    tables.sort_by_key(|(tag, _)| *tag);
+    let num_tables = tables.len() as u16;
+    let entry_selector = num_tables.checked_ilog2().unwrap_or(0) as u16;
+    let search_range = (1u16 << entry_selector) * 16;
+
+    let mut out = Vec::new();
+    out.extend_from_slice(&flavor.to_be_bytes());
+    for value in [num_tables, search_range, entry_selector, num_tables * 16 - search_range] {
+        out.extend_from_slice(&value.to_be_bytes());
+    }
+    let mut offset = 12 + 16 * tables.len();
+    for (tag, table) in &tables {
+        out.extend_from_slice(tag);
+        out.extend_from_slice(&checksum(table).to_be_bytes());
+        out.extend_from_slice(&(offset as u32).to_be_bytes());
+        out.extend_from_slice(&(table.len() as u32).to_be_bytes());
+        offset += table.len().next_multiple_of(4);
+    }
+    for (_, table) in &tables {
+        out.extend_from_slice(table);
+        out.resize(out.len().next_multiple_of(4), 0);
+    }
+    out
+}
+
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0u8; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+fn tag_str(tag: &[u8; 4]) -> String {
+    String::from_utf8_lossy(tag).into_owned()
+}
+
+/// Bounds-checked big-endian reads
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        let bytes = self
+            .data
+            .get(self.pos..self.pos + len)
+            .context("unexpected end of WOFF2 data")?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        let bytes = self.bytes(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn i16(&mut self) -> Result<i16> {
+        Ok(self.u16()? as i16)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let bytes = self.bytes(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn tag(&mut self) -> Result<[u8; 4]> {
+        Ok(self.u32()?.to_be_bytes())
+    }
+
+    /// A `UIntBase128`
+    fn base128(&mut self) -> Result<u32> {
+        let mut value = 0u32;
+        for _ in 0..5 {
+            let byte = self.u8()?;
+            value =
+                value.checked_mul(128).context("UIntBase128 overflow")? | u32::from(byte & 0x7F);
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        bail!("UIntBase128 longer than 5 bytes")
+    }
+
+    /// A `255UInt16`
+    fn u255_16(&mut self) -> Result<u16> {
+        Ok(match self.u8()? {
+            253 => self.u16()?,
+            254 => 253 * 2 + u16::from(self.u8()?),
+            255 => 253 + u16::from(self.u8()?),
+            code => u16::from(code),
+        })
+    }
+}